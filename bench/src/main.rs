@@ -2,14 +2,52 @@ use std::{
   collections::HashSet,
   env, fs,
   path::PathBuf,
+  process::exit,
   rc::Rc,
   time::{Duration, Instant},
 };
 
+use serde_json::json;
+
 use valuescript_compiler::{assemble, compile, resolve_path, ResolvedPath};
 use valuescript_vm::{Bytecode, ValTrait, VirtualMachine};
 
+// Compare with `cargo run --release --features arena`
+#[cfg(feature = "arena")]
+#[global_allocator]
+static ALLOCATOR: valuescript_vm::ArenaAllocator = valuescript_vm::ArenaAllocator;
+
+struct Options {
+  warmup: usize,
+  json_path: Option<String>,
+  compare_path: Option<String>,
+
+  /// How much slower (in percent) than the baseline a benchmark can be before it's a regression
+  threshold: f64,
+}
+
+struct Stats {
+  samples: usize,
+  mean: f64,
+  median: f64,
+  stddev: f64,
+}
+
 fn main() {
+  let options = match parse_options(&env::args().skip(1).collect::<Vec<_>>()) {
+    Ok(options) => options,
+    Err(message) => {
+      println!("ERROR: {}\n", message);
+      show_help();
+      exit(1);
+    }
+  };
+
+  let baseline = options
+    .compare_path
+    .as_ref()
+    .map(|path| read_baseline(path));
+
   let exe_path = std::env::current_exe().unwrap();
   let mut current_dir = exe_path.parent().unwrap();
   while current_dir.file_name().unwrap() != "target" {
@@ -25,7 +63,8 @@ fn main() {
 
   files.sort();
 
-  let mut results = Vec::<f64>::new();
+  let mut results = Vec::<(String, Stats)>::new();
+  let mut regressions = Vec::<String>::new();
 
   for file_path in files {
     let file_contents = fs::read_to_string(&file_path).expect("Failed to read file contents");
@@ -77,47 +116,211 @@ fn main() {
       .module
       .expect("Should have exited if module is None");
 
-    let bytecode = Rc::new(Bytecode::new(assemble(&module)));
+    let bytecode = Rc::new(
+      Bytecode::new(assemble(&module).expect("Failed to assemble"))
+        .expect("Failed to load bytecode"),
+    );
 
     let mut vm = VirtualMachine::default();
 
+    let mut run = || {
+      let before = Instant::now();
+      let result = vm.run(bytecode.clone(), None, &[]);
+      let duration_ms = before.elapsed().as_secs_f64() * 1000.0;
+
+      if let Err(result) = result {
+        panic!("{} failed: {}", friendly_file_path, result.codify());
+      }
+
+      duration_ms
+    };
+
+    for _ in 0..options.warmup {
+      run();
+    }
+
     let mut file_results = Vec::<f64>::new();
 
     let start = Instant::now();
 
     while Instant::now() - start < Duration::from_secs(1) {
-      let before = Instant::now();
-      let result = vm.run(bytecode.clone(), None, &[]);
-      let after = Instant::now();
+      file_results.push(run());
+    }
 
-      let duration_ms = after.duration_since(before).as_millis();
+    let stats = Stats::new(&file_results);
 
-      file_results.push(duration_ms as f64);
+    let comparison = match baseline
+      .as_ref()
+      .and_then(|baseline| baseline.get(friendly_file_path)?.get("median")?.as_f64())
+    {
+      Some(baseline_median) => {
+        let change = 100.0 * (stats.median - baseline_median) / baseline_median;
 
-      if let Err(result) = result {
-        panic!("{} failed: {}", friendly_file_path, result.codify());
+        match change > options.threshold {
+          true => {
+            regressions.push(friendly_file_path.to_string());
+            format!(" {:>+6.1}% REGRESSION", change)
+          }
+          false => format!(" {:>+6.1}%", change),
+        }
       }
-    }
-
-    let result = if file_results.len() > 2 {
-      geometric_mean(&file_results[1..file_results.len() - 1])
-    } else {
-      geometric_mean(&file_results)
+      None => "".to_string(),
     };
 
-    results.push(result);
+    println!(
+      "{:<37} {:>8.2}ms ±{:>6.2}ms (mean {:.2}ms, n={}){}",
+      friendly_file_path, stats.median, stats.stddev, stats.mean, stats.samples, comparison
+    );
 
-    println!("{:<37} {:>6.1}ms", friendly_file_path, result);
+    results.push((friendly_file_path.to_string(), stats));
   }
 
-  let score = geometric_mean(&results);
+  let score = geometric_mean(
+    &results
+      .iter()
+      .map(|(_, stats)| stats.median)
+      .collect::<Vec<_>>(),
+  );
+
+  println!("{:<37} ==========", "");
+  println!("{:<37} {:>8.2}ms", "Score", score);
+
+  if let Some(json_path) = &options.json_path {
+    let mut benchmarks = serde_json::Map::new();
+
+    for (path, stats) in &results {
+      benchmarks.insert(
+        path.clone(),
+        json!({
+          "samples": stats.samples,
+          "mean": stats.mean,
+          "median": stats.median,
+          "stddev": stats.stddev,
+        }),
+      );
+    }
+
+    let output = json!({ "score": score, "benchmarks": benchmarks });
 
-  println!("{:<37} ========", "");
-  println!("{:<37} {:>6.1}ms", "Score", score);
+    fs::write(json_path, format!("{:#}\n", output))
+      .unwrap_or_else(|err| panic!("Failed to write {}: {}", json_path, err));
+  }
 
   if !failed_paths.is_empty() {
     panic!("See failures above");
   }
+
+  if !regressions.is_empty() {
+    println!();
+    println!(
+      "{} benchmark(s) regressed by more than {}%: {}",
+      regressions.len(),
+      options.threshold,
+      regressions.join(", ")
+    );
+
+    exit(1);
+  }
+}
+
+impl Stats {
+  fn new(samples: &[f64]) -> Stats {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let n = sorted.len();
+    let mean = sorted.iter().sum::<f64>() / n as f64;
+
+    let median = match n % 2 {
+      0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+      _ => sorted[n / 2],
+    };
+
+    let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+
+    Stats {
+      samples: n,
+      mean,
+      median,
+      stddev: variance.sqrt(),
+    }
+  }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+  let mut options = Options {
+    warmup: 3,
+    json_path: None,
+    compare_path: None,
+    threshold: 10.0,
+  };
+
+  let mut i = 0;
+
+  while i < args.len() {
+    let arg = &args[i];
+
+    if arg == "-h" || arg == "--help" {
+      show_help();
+      exit(0);
+    }
+
+    let value = match args.get(i + 1) {
+      Some(value) => value.clone(),
+      None => return Err(format!("Missing value for {}", arg)),
+    };
+
+    i += 2;
+
+    match arg.as_str() {
+      "--warmup" => {
+        options.warmup = value
+          .parse()
+          .map_err(|_| format!("Invalid --warmup value {}", value))?
+      }
+      "--json" => options.json_path = Some(value),
+      "--compare" => options.compare_path = Some(value),
+      "--threshold" => {
+        options.threshold = value
+          .parse()
+          .map_err(|_| format!("Invalid --threshold value {}", value))?
+      }
+      _ => return Err(format!("Unrecognized option {}", arg)),
+    }
+  }
+
+  Ok(options)
+}
+
+/// Reads the benchmarks of a file written by `--json`.
+fn read_baseline(path: &str) -> serde_json::Value {
+  let content =
+    fs::read_to_string(path).unwrap_or_else(|err| panic!("Failed to read {}: {}", path, err));
+
+  let baseline: serde_json::Value =
+    serde_json::from_str(&content).unwrap_or_else(|err| panic!("Invalid {}: {}", path, err));
+
+  baseline["benchmarks"].clone()
+}
+
+fn show_help() {
+  println!("bench");
+  println!();
+  println!("Runs the inputs marked with //! bench() for a second each");
+  println!();
+  println!("OPTIONS:");
+  println!("    --warmup <n>");
+  println!("            Untimed runs before measuring (default: 3)");
+  println!();
+  println!("    --json <path>");
+  println!("            Write the results as JSON, for use with --compare");
+  println!();
+  println!("    --compare <path>");
+  println!("            Compare medians with results written by --json, and exit with an error");
+  println!("            if any benchmark regressed");
+  println!();
+  println!("    --threshold <percent>");
+  println!("            How much slower counts as a regression (default: 10)");
 }
 
 fn get_files_recursively(dir_path: &PathBuf) -> Result<Vec<PathBuf>, std::io::Error> {
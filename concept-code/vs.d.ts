@@ -12,6 +12,17 @@ declare namespace Debug {
   export function makeCopyCounter<T = undefined>(
     tag?: T,
   ): { tag: T; count: number };
+
+  export function time(label?: string): void;
+
+  /** returns the elapsed milliseconds (according to the host's clock) */
+  export function timeEnd(label?: string): number;
+
+  /** throws if `cond` is falsy */
+  export function assert(cond: unknown, message?: string): void;
+
+  /** codifies `value`, abbreviating arrays and objects nested deeper than `depth` (default 2) */
+  export function inspect(value: unknown, depth?: number): string;
 }
 
 declare module "ffi:console" {
@@ -0,0 +1,26 @@
+//! test_output(["RangeError","Maximum call stack size exceeded",true])
+
+export default function () {
+  const o = {
+    get a(): number {
+      return this.a;
+    },
+  };
+
+  let result;
+
+  try {
+    result = o.a;
+  } catch (e) {
+    result = [(e as Error).name, (e as Error).message];
+  }
+
+  // Nested calls still work after recovering from the error
+  const p = {
+    get b() {
+      return true;
+    },
+  };
+
+  return [...result, p.b];
+}
@@ -0,0 +1,18 @@
+//! test_output([3,1,1,2,undefined,undefined,undefined])
+
+export default function () {
+  const values = [1, 2, 3];
+
+  let holes = [1, 2];
+  holes[3] = 4;
+
+  return [
+    values.at(-1),
+    values.at(0),
+    values.at(),
+    values.at(1.5),
+    values.at(3),
+    values.at(-4),
+    holes.at(2),
+  ];
+}
@@ -0,0 +1,17 @@
+//! test_output([[2,4,6],["A","B"],[0,1,2],[1,4,9],[[undefined,0],[undefined,1]]])
+
+export default function () {
+  return [
+    Array.from([1, 2, 3], (x) => 2 * x),
+    Array.from("ab", (c) => c.toUpperCase()),
+    Array.from({ length: 3 }, (_, i) => i),
+    Array.from(gen(), (x) => x * x),
+    Array.from(Array(2), (x, i) => [x, i]),
+  ];
+}
+
+function* gen() {
+  yield 1;
+  yield 2;
+  yield 3;
+}
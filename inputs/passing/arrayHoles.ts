@@ -0,0 +1,35 @@
+//! test_output([[2,,6],false,true,true,-1,[1,3],1,[],false,true,[1,undefined,3],[[1,undefined]]])
+
+export default function () {
+  // deno-lint-ignore no-sparse-arrays
+  const arr = [1, , 3];
+
+  let iterated = [];
+
+  for (const x of arr) {
+    iterated.push(x);
+  }
+
+  let entries = [];
+
+  for (const entry of arr.entries()) {
+    if (entry[0] === 1) {
+      entries.push(entry);
+    }
+  }
+
+  return [
+    arr.map((x) => x * 2),
+    1 in arr,
+    2 in arr,
+    arr.includes(undefined),
+    arr.indexOf(undefined),
+    arr.flat(),
+    arr.findIndex((x) => x === undefined),
+    arr.filter((x) => x === undefined),
+    arr.some((x) => x === undefined),
+    arr.every((x) => x !== undefined),
+    iterated,
+    entries,
+  ];
+}
@@ -0,0 +1,67 @@
+//! test_output([42,"oops",[2,5,7],21,"caught bad","resolved","fast",4,6,2])
+
+export default async function () {
+  const doubled = await double(21);
+
+  let error;
+
+  try {
+    await fail("oops");
+  } catch (e) {
+    error = e;
+  }
+
+  const all = await Promise.all([double(1), Promise.resolve(5), 7]);
+  const chained = await double(10).then((x) => x + 1);
+  const caught = await fail("bad").catch((e) => `caught ${e}`);
+  const executor = await new Promise<string>((resolve) => resolve("resolved"));
+  const race = await Promise.race([delayed("slow", 3), delayed("fast", 1)]);
+  const counted = await new Counter(4).get();
+  const tripled = await triple(2);
+  const index = (await Promise.resolve([1, 2, 3])).indexOf(3);
+
+  return [
+    doubled,
+    error,
+    all,
+    chained,
+    caught,
+    executor,
+    race,
+    counted,
+    tripled,
+    index,
+  ];
+}
+
+async function double(x: number | Promise<number>) {
+  return 2 * (await x);
+}
+
+async function fail(message: string): Promise<never> {
+  await null;
+  throw message;
+}
+
+async function delayed(value: string, ticks: number) {
+  for (let i = 0; i < ticks; i++) {
+    await null;
+  }
+
+  return value;
+}
+
+const triple = async (x: number) => 3 * (await x);
+
+class Counter {
+  n: number;
+
+  constructor(n: number) {
+    this.n = n;
+  }
+
+  async get() {
+    await null;
+    return this.n;
+  }
+}
@@ -0,0 +1,70 @@
+//! test_output([[3,2,1],[1,2,3],[5,4],[2,"stopped",true,true],["caught boom","rejected again"],[[2,false],[1,false],["done",true]]])
+
+export default async function () {
+  let values = [];
+
+  for await (const x of countdown(3)) {
+    values.push(x);
+  }
+
+  let fromSync = [];
+
+  for await (const x of [Promise.resolve(1), 2, delayed(3)]) {
+    fromSync.push(x);
+  }
+
+  let early = [];
+
+  for await (const x of countdown(5)) {
+    if (x < 4) {
+      break;
+    }
+
+    early.push(x);
+  }
+
+  let gen = countdown(2);
+  const first = await gen.next();
+  const returned = await gen.return("stopped");
+  const after = await gen.next();
+
+  let thrower = catcher();
+  await thrower.next();
+  const caught = await thrower.throw("boom");
+  const rejected = await thrower.throw("again").catch((e) => `rejected ${e}`);
+
+  // Requests made before the previous ones have settled are served in order
+  let queued = countdown(2);
+  const results = await Promise.all([queued.next(), queued.next(), queued.next()]);
+
+  return [
+    values,
+    fromSync,
+    early,
+    [first.value, returned.value, returned.done, after.done],
+    [caught.value, rejected],
+    results.map((r) => [r.value, r.done]),
+  ];
+}
+
+async function* countdown(n: number) {
+  while (n > 0) {
+    await null;
+    yield n--;
+  }
+
+  return delayed("done");
+}
+
+async function* catcher() {
+  try {
+    yield 1;
+  } catch (e) {
+    yield `caught ${e}`;
+  }
+}
+
+async function delayed<T>(value: T) {
+  await null;
+  return value;
+}
@@ -0,0 +1,22 @@
+//! test_output(["SGVsbG8sIFdvcmxkIQ==","YWI=","6Q==","Hello, World!","ab","é","",Error{"message":"Invalid character: btoa only supports characters in the Latin1 range"}])
+
+export default function () {
+  let error;
+
+  try {
+    btoa("👋");
+  } catch (e) {
+    error = e;
+  }
+
+  return [
+    btoa("Hello, World!"),
+    btoa("ab"),
+    btoa("é"),
+    atob("SGVsbG8sIFdvcmxkIQ=="),
+    atob("YW I"),
+    atob("6Q=="),
+    atob(""),
+    error,
+  ];
+}
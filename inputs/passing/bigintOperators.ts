@@ -0,0 +1,37 @@
+//! test_output([[3n,-3n,1n,-1n],[-3n,2n,-1n,0n,40n],[1n,-1n,1024n],[2n,0n],[true,false,true,true],["Division by zero","Division by zero","Cannot mix BigInt and other types","Cannot mix BigInt with other types","Cannot convert a BigInt value to a number","BigInts don't support unsigned right shift"],[NaN,NaN,1]])
+
+export default function () {
+  let inc = 1n;
+  inc++;
+
+  let dec = 1n;
+  dec--;
+
+  const big = 2n ** 40n;
+
+  return [
+    [7n / 2n, -7n / 2n, 7n % 2n, -7n % 2n],
+    [-5n >> 1n, 5n << -1n, -5n >> 100n, 5n >> 100n, 5n << 3n],
+    [1n ** big, (-1n) ** (big + 1n), 2n ** 10n],
+    [inc, dec],
+    [1n == (1 as any), 1n === (1 as any), 2n > 1, "10" == (10n as any)],
+    [
+      errorMessage(() => 1n / 0n),
+      errorMessage(() => 1n % 0n),
+      errorMessage(() => 1n + (1 as any)),
+      errorMessage(() => 1n * (2 as any)),
+      errorMessage(() => +(1n as any)),
+      errorMessage(() => (1n as any) >>> 0n),
+    ],
+    [1 ** NaN, (-1) ** Infinity, NaN ** 0],
+  ];
+}
+
+function errorMessage(f: () => unknown) {
+  try {
+    f();
+    return "no error";
+  } catch (e) {
+    return (e as Error).message;
+  }
+}
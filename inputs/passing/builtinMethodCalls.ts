@@ -0,0 +1,17 @@
+//! bench()
+
+export default function main() {
+  let words: string[] = [];
+
+  for (let i = 0; i < 20000; i++) {
+    words.push(i.toString().padStart(5, "0"));
+  }
+
+  let total = 0;
+
+  for (const word of words) {
+    total += word.codePointAt(4)!;
+  }
+
+  return [words.length, words.at(-1), total];
+}
@@ -0,0 +1,27 @@
+//! test_output([[0,1,2],[0,2,4],["a0","b1"]])
+
+export default function () {
+  let fns: (() => number)[] = [];
+
+  for (let i = 0; i < 3; i++) {
+    fns.push(() => i);
+  }
+
+  let doubles: (() => number)[] = [];
+
+  for (let i = 0; i < 6; i += 2) {
+    function double() {
+      return i;
+    }
+
+    doubles.push(double);
+  }
+
+  let labels: (() => string)[] = [];
+
+  for (const [i, label] of ["a", "b"].entries()) {
+    labels.push(() => `${label}${i}`);
+  }
+
+  return [fns.map((f) => f()), doubles.map((f) => f()), labels.map((f) => f())];
+}
@@ -0,0 +1,49 @@
+//! test_output([3,[10,"b"],"hi, x"])
+
+export default function () {
+  const Point = class {
+    x: number;
+    y: number;
+
+    constructor(x: number, y: number) {
+      this.x = x;
+      this.y = y;
+    }
+
+    sum() {
+      return this.x + this.y;
+    }
+  };
+
+  const offset = 10;
+  const label = "b";
+
+  const Offset = class Labeled {
+    value: number;
+
+    constructor(value: number) {
+      this.value = value + offset;
+    }
+
+    label() {
+      return label;
+    }
+  };
+
+  const offsetInstance = new Offset(0);
+  const Greeter = makeGreeter("hi");
+
+  return [
+    new Point(1, 2).sum(),
+    [offsetInstance.value, offsetInstance.label()],
+    new Greeter().greet("x"),
+  ];
+}
+
+function makeGreeter(greeting: string) {
+  return class {
+    greet(name: string) {
+      return `${greeting}, ${name}`;
+    }
+  };
+}
@@ -0,0 +1,47 @@
+//! test_output(["Rex says woof",["Rex",4,true],"Animal",true,true,false,"Tom makes a sound",3])
+
+export default function () {
+  const dog = new Dog("Rex");
+  const cat = new Cat("Tom");
+
+  return [
+    dog.speak(),
+    [dog.name, dog.legs, dog.good],
+    Dog.kind(),
+    dog instanceof Animal,
+    dog instanceof Dog,
+    cat instanceof Dog,
+    cat.speak(),
+    Cat.count,
+  ];
+}
+
+class Animal {
+  static count = 3;
+
+  legs = 4;
+
+  constructor(public name: string) {}
+
+  speak() {
+    return `${this.name} makes a sound`;
+  }
+
+  static kind() {
+    return "Animal";
+  }
+}
+
+class Dog extends Animal {
+  good = true;
+
+  constructor(name: string) {
+    super(name);
+  }
+
+  speak() {
+    return `${this.name} says woof`;
+  }
+}
+
+class Cat extends Animal {}
@@ -0,0 +1,11 @@
+//! test_output(["TypeError","Class extends value 1 is not a class"])
+
+export default function () {
+  try {
+    return new B();
+  } catch (e) {
+    return [(e as Error).name, (e as Error).message];
+  }
+}
+
+class B extends (1 as any) {}
@@ -0,0 +1,22 @@
+//! test_output([{"width":2,"height":3,"area":12,"log":["init","ctor"],"name":"box"},["width","height","area","log","name"]])
+
+export default function () {
+  const box = new Box(2);
+
+  return [box, Object.keys(box)];
+}
+
+class Box {
+  declare kind: string;
+  width = 2;
+  height = 3;
+  area = this.width * this.height;
+  log = ["init"];
+  name: string;
+
+  constructor(scale: number) {
+    this.log.push("ctor");
+    this.name = "box";
+    this.area *= scale;
+  }
+}
@@ -0,0 +1,36 @@
+//! test_output([[1,4,9],5,19,"total: 19",3])
+
+export default function () {
+  return [
+    Squares.values,
+    Squares.first,
+    Squares.sum(),
+    Squares.label,
+    Counter.start,
+  ];
+}
+
+class Squares {
+  static values = [1, 2, 3].map((x) => x * x);
+  static first = this.values[0] + 4;
+  static total = this.add(this.values) + this.first;
+  static label = `total: ${this.total}`;
+
+  static add(values: number[]) {
+    let sum = 0;
+
+    for (const v of values) {
+      sum += v;
+    }
+
+    return sum;
+  }
+
+  static sum() {
+    return this.total;
+  }
+}
+
+class Counter {
+  static start = 3;
+}
@@ -0,0 +1,28 @@
+//! test_output([2,4,[2,4],"4 from Config",7,"4 from Config"])
+
+export default function () {
+  return [
+    Config.base,
+    Config.doubled,
+    Config.both,
+    Config.label,
+    Config.total,
+    Config.describe(),
+  ];
+}
+
+class Config {
+  static base = [1, 2].length;
+  static doubled = Config.base * 2;
+  static both = [this.base, Config.doubled];
+  static label = Config.describe();
+  static total = Config.add(this.base, 5);
+
+  static describe() {
+    return `${this.doubled} from Config`;
+  }
+
+  static add(a: number, b: number) {
+    return a + b;
+  }
+}
@@ -0,0 +1,27 @@
+//! test_output(["Config failed","Config failed","ok"])
+
+export default function () {
+  let results: string[] = [];
+
+  for (let i = 0; i < 2; i++) {
+    try {
+      results.push(Config.value);
+    } catch (e) {
+      results.push((e as Error).message);
+    }
+  }
+
+  results.push(Other.value);
+
+  return results;
+}
+
+class Config {
+  static value: string = (() => {
+    throw new Error("Config failed");
+  })();
+}
+
+class Other {
+  static value = "ok";
+}
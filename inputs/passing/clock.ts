@@ -0,0 +1,10 @@
+//! test_output([0,0,0])
+
+// Time is frozen at 0 by default so that program runs are reproducible. Hosts can provide a
+// different clock.
+
+export default function () {
+  const start = performance.now();
+
+  return [Date.now(), start, performance.now() - start];
+}
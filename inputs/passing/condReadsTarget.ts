@@ -0,0 +1,20 @@
+//! test_output([0,5,["skipped"]])
+
+export default function () {
+  const flag = makeFalse();
+
+  let x = 5;
+  x = flag ? x : 0;
+
+  let y = 5;
+  y = flag ? 0 : y;
+
+  let log: string[] = [];
+  flag ? log.push("taken") : log.push("skipped");
+
+  return [x, y, log];
+}
+
+function makeFalse() {
+  return false;
+}
@@ -0,0 +1,16 @@
+//! test_output([Error{"message":"No entropy source was provided by the host"},"object"])
+
+// crypto only works when the host provides an entropy source. This keeps program runs
+// reproducible by default.
+
+export default function () {
+  let error;
+
+  try {
+    crypto.randomUUID();
+  } catch (e) {
+    error = e;
+  }
+
+  return [error, typeof crypto];
+}
@@ -0,0 +1,24 @@
+//! test_output([0,"[1,[2,[3,[Array]]]]","[1,[Array]]","Assertion failed: expected 3 elements"])
+
+export default function () {
+  Debug.time("t");
+  const elapsed = Debug.timeEnd("t");
+
+  const nested = [1, [2, [3, [4]]]];
+
+  let message = "";
+
+  try {
+    Debug.assert(nested.length === 2);
+    Debug.assert(nested.length === 3, "expected 3 elements");
+  } catch (e) {
+    message = (e as Error).message;
+  }
+
+  return [
+    elapsed,
+    Debug.inspect(nested),
+    Debug.inspect(nested, 0),
+    message,
+  ];
+}
@@ -0,0 +1,30 @@
+//! test_output([{"b":2},true,[1,,3],3,{"outer":{}},[true,false],[undefined,1],[undefined,true]])
+
+export default function () {
+  let obj: Record<string, number> = { a: 1, b: 2, c: 3 };
+  delete obj.a;
+  const deletedMissing = delete obj.d;
+
+  const hadC = "c" in obj;
+  delete obj["c"];
+
+  let arr = [1, 2, 3];
+  delete arr[1];
+
+  let nested: { outer: { inner?: string } } = { outer: { inner: "x" } };
+  delete nested.outer.inner;
+
+  let count = 0;
+  const voided = void count++;
+
+  return [
+    obj,
+    deletedMissing,
+    arr,
+    arr.length,
+    nested,
+    [hadC, "c" in obj],
+    [voided, count],
+    [void 0, void 0 === undefined],
+  ];
+}
@@ -0,0 +1,34 @@
+//! test_output([3,[7,"default"],[1,{"x":2,"y":3}],15])
+
+export default function main() {
+  const sum = ([a, b]: [number, number]) => a + b;
+
+  const describe = ({
+    n,
+    label = "default",
+  }: {
+    n: number;
+    label?: string;
+  }) => [n, label];
+
+  const point = new Point(1, { x: 2, y: 3 });
+
+  return [
+    sum([1, 2]),
+    describe({ n: 7 }),
+    [point.id, { x: point.x, y: point.y }],
+    [[4, 5], [6]].map(([x, y = 0]) => x + y).reduce((a, b) => a + b),
+  ];
+}
+
+class Point {
+  id: number;
+  x: number;
+  y: number;
+
+  constructor(id: number, { x, y }: { x: number; y: number }) {
+    this.id = id;
+    this.x = x;
+    this.y = y;
+  }
+}
@@ -0,0 +1,40 @@
+//! test_output([["Up","Right",3],{"Red":"red","Green":"green"},[1,2,4,7,-8],[10,11,"ten"],"Right"])
+
+enum Color {
+  Red = "red",
+  Green = "green",
+}
+
+enum Flags {
+  A = 1 << 0,
+  B = 1 << 1,
+  C = Flags.B << 1,
+  All = A | B | C,
+  Inverted = ~All,
+}
+
+export default function () {
+  enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+  }
+
+  enum Offset {
+    Ten = 10,
+    Eleven,
+  }
+
+  function turn(direction: Direction) {
+    return Direction[(direction + 3) % 4];
+  }
+
+  return [
+    [Direction[Direction.Up], Direction[3], Direction.Right],
+    Color,
+    [Flags.A, Flags.B, Flags.C, Flags.All, Flags.Inverted],
+    [Offset.Ten, Offset.Eleven, Offset[10].toLowerCase()],
+    turn(Direction.Up),
+  ];
+}
@@ -0,0 +1,54 @@
+//! test_output([[true,true,false,false,false],[true,true,false,true],[true,true,true,false,true],[true,false,true,false],[true,false],[true,true,false],[true,false,false]])
+
+export default function () {
+  const eq = (a: any, b: any) => a == b;
+
+  const nullish = [
+    eq(null, undefined),
+    eq(undefined, null),
+    eq(undefined, 0),
+    eq(null, false),
+    eq(null, ""),
+  ];
+
+  const booleans = [
+    eq(true, 1),
+    eq("1", true),
+    eq(false, "false"),
+    eq(false, ""),
+  ];
+
+  const numbersAndStrings = [
+    eq(" 1.0 ", 1),
+    eq("", 0),
+    eq("0x10", 16),
+    eq("inf", Infinity),
+    eq("-Infinity", -Infinity),
+  ];
+
+  const bigints = [
+    eq(1n, 1),
+    eq(1n, 1.5),
+    eq("10", 10n),
+    eq("ten", 10n),
+  ];
+
+  const nanAndZero = [
+    eq(0, -0),
+    eq(NaN, NaN),
+  ];
+
+  const objects = [
+    eq([1], 1),
+    eq([1, 2], "1,2"),
+    eq({}, []),
+  ];
+
+  const symbols = [
+    eq(Symbol.for("a"), Symbol.for("a")),
+    eq(Symbol.for("a"), "a"),
+    eq(Symbol.iterator, Symbol.for("Symbol.iterator")),
+  ];
+
+  return [nullish, booleans, numbersAndStrings, bigints, nanAndZero, objects, symbols];
+}
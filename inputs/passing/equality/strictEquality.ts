@@ -0,0 +1,16 @@
+//! test_output([true,false,false,false,false,true,false,false])
+
+export default function () {
+  const eq = (a: any, b: any) => a === b;
+
+  return [
+    eq(0, -0),
+    eq(NaN, NaN),
+    eq(1n, 1),
+    eq("1", 1),
+    eq(null, undefined),
+    eq(Symbol.for("a"), Symbol.for("a")),
+    eq(Symbol.for("a"), Symbol.for("b")),
+    eq(true, 1),
+  ];
+}
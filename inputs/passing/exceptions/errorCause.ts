@@ -0,0 +1,10 @@
+//! test_output([Error{"message":"outer","cause":TypeError{"message":"inner"}},RangeError{"message":"no cause"},AggregateError{"message":"multiple","errors":[Error{"message":"a"},Error{"message":"b"}]},AggregateError{"message":"","cause":"x","errors":[]}])
+
+export default function () {
+  return [
+    new Error("outer", { cause: new TypeError("inner") }),
+    new RangeError("no cause", {}),
+    new AggregateError([new Error("a"), new Error("b")], "multiple"),
+    AggregateError([], undefined, { cause: "x" }),
+  ];
+}
@@ -0,0 +1,38 @@
+//! test_output([["1","2","b","a"],["0","2"],["x","y"],["origin"],[],["1","2","b","a"],[["0",1],["2",3]]])
+
+class Point {
+  static origin = 0;
+
+  constructor(public x: number, public y: number) {}
+
+  norm() {
+    return Math.sqrt(this.x * this.x + this.y * this.y);
+  }
+}
+
+export default function () {
+  const obj = { b: 1, 2: 2, a: 3, 1: 4 };
+
+  // deno-lint-ignore no-sparse-arrays
+  const arr = [1, , 3];
+
+  return [
+    keysOf(obj),
+    keysOf(arr),
+    keysOf(new Point(1, 2)),
+    keysOf(Point),
+    keysOf(5),
+    Object.keys(obj),
+    Object.entries(arr),
+  ];
+}
+
+function keysOf(value: unknown) {
+  let keys = [];
+
+  for (const key in value as object) {
+    keys.push(key);
+  }
+
+  return keys;
+}
@@ -0,0 +1,28 @@
+//! test_output([13,107,1011,9,"Hello world",[1,2,3],"function"])
+
+export default function () {
+  const bound = add.bind(1000, 5);
+  const greet = greeting.bind(undefined, "Hello");
+
+  return [
+    add.call(10, 1, 2),
+    add.apply(100, [3, 4]),
+    bound(6),
+    Math.max.apply(undefined, [3, 9, 2]),
+    greet("world"),
+    [1, 2, 3].map(identity.bind(undefined)),
+    typeof bound,
+  ];
+}
+
+function add(this: number, a: number, b: number) {
+  return this + a + b;
+}
+
+function greeting(greeting: string, name: string) {
+  return `${greeting} ${name}`;
+}
+
+function identity<T>(x: T) {
+  return x;
+}
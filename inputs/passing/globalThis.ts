@@ -0,0 +1,9 @@
+//! test_output([3,"undefined","object"])
+
+export default function () {
+  return [
+    globalThis.Math.max(1, 3),
+    typeof (globalThis as Record<string, unknown>).notProvidedByHost,
+    typeof globalThis.console,
+  ];
+}
@@ -0,0 +1,7 @@
+export const counter = {
+  count: 21,
+
+  get double() {
+    return this.count * 2;
+  },
+};
@@ -0,0 +1,41 @@
+//! test_output([[1,3],[[1,"a"],[1,"b"],[2,"a"],[2,"b"]],[["x",1]]])
+
+export default function () {
+  let odds: number[] = [];
+
+  for (const x of [1, 2, 3, 4, 5]) {
+    if (x % 2 === 0) {
+      continue;
+    }
+
+    if (x > 4) {
+      break;
+    }
+
+    odds.push(x);
+  }
+
+  let pairs: [number, string][] = [];
+
+  for (const i of [1, 2]) {
+    for (const s of ["a", "b", "c"]) {
+      if (s === "c") {
+        break;
+      }
+
+      pairs.push([i, s]);
+    }
+  }
+
+  let entries: [string, number][] = [];
+
+  for (const [k, v] of [["x", 1], ["y", 0]] as [string, number][]) {
+    if (v === 0) {
+      continue;
+    }
+
+    entries.push([k, v]);
+  }
+
+  return [odds, pairs, entries];
+}
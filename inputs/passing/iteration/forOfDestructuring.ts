@@ -0,0 +1,34 @@
+//! test_output([["a:1","b:2"],3,"boom",[2,"y"],"b"])
+
+export default function () {
+  let labels = [];
+
+  for (const [k, v] of [["a", 1], ["b", 2]]) {
+    labels.push(`${k}:${v}`);
+  }
+
+  let sum = 0;
+
+  for (const { x, y = 1 } of [{ x: 1 }, { x: 1, y: 0 }]) {
+    sum += x + y;
+  }
+
+  let message = "";
+
+  try {
+    throw new Error("boom");
+  } catch ({ message: m }) {
+    message = m;
+  }
+
+  let last;
+  let name;
+
+  for ([last, name] of [[1, "x"], [2, "y"]]) {}
+
+  let key;
+
+  for (key in { a: 1, b: 2 }) {}
+
+  return [labels, sum, message, [last, name], key];
+}
@@ -0,0 +1,21 @@
+//! test_output([2,3,3,4])
+
+export default function () {
+  let a = count();
+  a.next();
+  a.next();
+
+  let b = a;
+  b.next();
+
+  return [a.next().value, b.next().value, a.next().value, b.next().value];
+}
+
+function* count() {
+  let i = 0;
+
+  while (true) {
+    yield i;
+    i++;
+  }
+}
@@ -0,0 +1,64 @@
+//! test_output([[42,true],true,["caught boom",true],[7,true],["from finally",true],1])
+
+export default function () {
+  let results = [];
+
+  let a = gen();
+  a.next();
+  results.push(pair(a.return(42)));
+  results.push(a.next().done);
+
+  let b = catcher();
+  b.next();
+  results.push(pair(b.throw("boom")));
+
+  let c = catcher();
+  c.next();
+  results.push(pair(c.return(7)));
+
+  let d = overridingFinally();
+  d.next();
+  results.push(pair(d.return(5)));
+
+  let sum = 0;
+
+  for (const x of gen()) {
+    sum += x;
+    break;
+  }
+
+  results.push(sum);
+
+  return results;
+}
+
+function pair(res: IteratorResult<unknown>) {
+  return [res.value, res.done];
+}
+
+function* gen() {
+  try {
+    yield 1;
+    yield 2;
+  } finally {
+    // Runs when the generator is closed early
+  }
+}
+
+function* catcher() {
+  try {
+    yield 1;
+  } catch (e) {
+    return `caught ${e}`;
+  }
+
+  return "finished";
+}
+
+function* overridingFinally() {
+  try {
+    yield 1;
+  } finally {
+    return "from finally";
+  }
+}
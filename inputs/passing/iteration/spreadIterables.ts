@@ -0,0 +1,49 @@
+//! test_output([[0,1,2,3,"a","b"],6,[10,20,30],[1,2,3],"object is not iterable"])
+
+export default function () {
+  const counter = new Counter(3);
+
+  return [
+    [0, ...counter, ..."ab"],
+    sum(...gen()),
+    [...new Counter(3)].map((x) => x * 10),
+    new Triple(...counter).values(),
+    spreadError(),
+  ];
+}
+
+class Counter {
+  constructor(public n: number) {}
+
+  *[Symbol.iterator]() {
+    for (let i = 1; i <= this.n; i++) {
+      yield i;
+    }
+  }
+}
+
+class Triple {
+  constructor(public a: number, public b: number, public c: number) {}
+
+  values() {
+    return [this.a, this.b, this.c];
+  }
+}
+
+function* gen() {
+  yield 1;
+  yield 2;
+  yield 3;
+}
+
+function sum(a: number, b: number, c: number) {
+  return a + b + c;
+}
+
+function spreadError() {
+  try {
+    return [...({} as number[])];
+  } catch (e) {
+    return (e as Error).message;
+  }
+}
@@ -0,0 +1,26 @@
+//! test_output([[0,5,15],[0,3,700]])
+
+export default function () {
+  let acc = accumulate();
+  let outer = delegate();
+
+  return [
+    [acc.next().value, acc.next(5).value, acc.next(10).value],
+    [outer.next().value, outer.next(3).value, outer.next(4).value],
+  ];
+}
+
+function* accumulate() {
+  let total = 0;
+
+  total += yield total;
+  total += yield total;
+
+  return total;
+}
+
+function* delegate() {
+  const total = yield* accumulate();
+
+  return total * 100;
+}
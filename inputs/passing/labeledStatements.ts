@@ -0,0 +1,56 @@
+//! test_output([[[0,0],[0,1],[0,2],[1,0]],[[0,0],[1,0],[2,0]],["a"],3])
+
+export default function () {
+  let pairs: [number, number][] = [];
+
+  outer: for (let i = 0; i < 3; i++) {
+    for (let j = 0; j < 3; j++) {
+      if (i === 1 && j === 1) {
+        break outer;
+      }
+
+      pairs.push([i, j]);
+    }
+  }
+
+  let firsts: [number, number][] = [];
+
+  rows: for (const i of [0, 1, 2]) {
+    for (const j of [0, 1]) {
+      if (j === 1) {
+        continue rows;
+      }
+
+      firsts.push([i, j]);
+    }
+  }
+
+  let log: string[] = [];
+
+  block: {
+    log.push("a");
+
+    if (log.length > 0) {
+      break block;
+    }
+
+    log.push("b");
+  }
+
+  let count = 0;
+
+  while (true) {
+    inner: {
+      count++;
+
+      if (count < 3) {
+        break inner;
+      }
+
+      // Unlabeled breaks skip labeled blocks and exit the loop
+      break;
+    }
+  }
+
+  return [pairs, firsts, log, count];
+}
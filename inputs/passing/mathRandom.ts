@@ -0,0 +1,8 @@
+//! test_output([0.8833108082136426,0.43152799704850997,0.026433771592597743])
+
+// Math.random is deterministic by default. The sequence depends on the seed provided by the host
+// (0 unless otherwise specified).
+
+export default function () {
+  return [Math.random(), Math.random(), Math.random()];
+}
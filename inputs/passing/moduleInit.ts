@@ -0,0 +1,29 @@
+//! test_output([6,["a","b"]])
+
+// Statements at the top level run once, before the exports are read
+
+export default function () {
+  return [total, names];
+}
+
+const values = [1, 2, 3];
+const total = 6;
+const names = ["a", "b"];
+
+{
+  let sum = 0;
+
+  for (const value of values) {
+    sum += value;
+  }
+
+  if (sum !== total) {
+    throw new Error("Unexpected sum");
+  }
+}
+
+try {
+  throw new Error("Caught during module initialization");
+} catch (error) {
+  console.log((error as Error).message);
+}
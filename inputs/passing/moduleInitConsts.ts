@@ -0,0 +1,16 @@
+//! test_output([[10,20,30],8,[1,2,3],42])
+
+// Constants that need to run code are evaluated as part of the module init
+
+import { counter } from "./helpers/counter.ts";
+
+export default function () {
+  return [xs, double(4), ys, counter.double];
+}
+
+function double(x: number) {
+  return ys[1] * x;
+}
+
+const xs = [1, 2, 3].map((x) => x * 10);
+const ys = xs.map((x) => x / 10);
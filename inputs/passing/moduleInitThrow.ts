@@ -0,0 +1,9 @@
+//! test_output(E: Error{"message":"Failed to initialize"})
+
+// An exception during module initialization is thrown to the caller
+
+export default function () {
+  return "unreachable";
+}
+
+throw new Error("Failed to initialize");
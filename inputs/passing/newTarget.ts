@@ -0,0 +1,19 @@
+//! test_output([true,undefined])
+
+export default function () {
+  const point = new Point(1, 2);
+
+  return [point.constructedWith === Point, plain()];
+}
+
+class Point {
+  constructedWith: unknown;
+
+  constructor(public x: number, public y: number) {
+    this.constructedWith = new.target;
+  }
+}
+
+function plain() {
+  return new.target;
+}
@@ -0,0 +1,22 @@
+//! test_output([[true,false,false,false],[true,false,false,false],[true,true]])
+
+export default function () {
+  const numberIsNaN = [
+    Number.isNaN(NaN),
+    Number.isNaN("hello"),
+    Number.isNaN(undefined),
+    Number.isNaN(1),
+  ];
+
+  const numberIsFinite = [
+    Number.isFinite(1),
+    Number.isFinite("1"),
+    Number.isFinite(Infinity),
+    Number.isFinite(NaN),
+  ];
+
+  // The global versions convert their argument to a number first
+  const global = [isNaN("hello" as unknown as number), isFinite("1" as unknown as number)];
+
+  return [numberIsNaN, numberIsFinite, global];
+}
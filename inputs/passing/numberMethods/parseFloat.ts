@@ -1,4 +1,4 @@
-//! test_output([[37,-37,37.1,37,37.1,1.5,0.1,1.23],[NaN]])
+//! test_output([[37,-37,37.1,37,37.1,1.5,0.1,1.23,3.5,-50,Infinity,1],[NaN,NaN]])
 
 export default function () {
   const numbers = [
@@ -10,10 +10,15 @@ export default function () {
     Number.parseFloat("1.5"),
     Number.parseFloat("0.1"),
     Number.parseFloat("1.23"),
+    Number.parseFloat("3.5px"),
+    Number.parseFloat("-.5e2"),
+    Number.parseFloat("Infinity and beyond"),
+    Number.parseFloat("1e"),
   ];
 
   const nan_values = [
     Number.parseFloat("hello"),
+    Number.parseFloat("."),
   ];
 
   return [numbers, nan_values];
@@ -1,4 +1,4 @@
-//! test_output([[37,-37,15,37,37,31],[NaN,NaN]])
+//! test_output([[37,-37,15,37,37,31,31,10,12,100000000000000000000],[NaN,NaN,NaN]])
 
 export default function () {
   const positive_and_negative = [
@@ -8,11 +8,16 @@ export default function () {
     Number.parseInt("37.1"),
     Number.parseInt("  37 "),
     Number.parseInt("1F", 16),
+    Number.parseInt("0x1f"),
+    Number.parseInt("10", 0),
+    Number.parseInt("+12px"),
+    Number.parseInt("100000000000000000000"),
   ];
 
   const nan_values = [
     Number.parseInt("hello"),
     Number.parseInt("10", 40),
+    Number.parseInt("0x"),
   ];
 
   return [positive_and_negative, nan_values];
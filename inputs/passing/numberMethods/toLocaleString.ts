@@ -0,0 +1,14 @@
+//! test_output(["1,234,567.891","1,234,567.89","1234567.891","0.50","-1,000","NaN","∞","12"])
+
+export default function () {
+  return [
+    (1234567.891).toLocaleString(),
+    (1234567.891).toLocaleString("en-US", { maximumFractionDigits: 2 }),
+    (1234567.891).toLocaleString(undefined, { useGrouping: false }),
+    (0.5).toLocaleString("en-US", { minimumFractionDigits: 2 }),
+    (-1000).toLocaleString(),
+    NaN.toLocaleString(),
+    Infinity.toLocaleString(),
+    (12.0001).toLocaleString(),
+  ];
+}
@@ -0,0 +1,16 @@
+//! test_output(["0.30000000000000004","1e+21","100000000000000000000","1e-7","0.000001","1.5e+300","-1.25e-10","123.456","0","5e-324"])
+
+export default function () {
+  return [
+    0.1 + 0.2,
+    1e21,
+    1e20,
+    1e-7,
+    1e-6,
+    1.5e300,
+    -1.25e-10,
+    123.456,
+    -0,
+    5e-324,
+  ].map((x) => x.toString());
+}
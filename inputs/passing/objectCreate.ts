@@ -0,0 +1,25 @@
+//! test_output(["hi obj","hi proto","proto",null,"hi x",{"v":1}])
+
+export default function () {
+  const proto = {
+    name: "proto",
+    greet: function (this: { name: string }) {
+      return `hi ${this.name}`;
+    },
+  };
+
+  let obj = Object.create(proto);
+  obj.name = "obj";
+
+  let other = { name: "x" };
+  Object.setPrototypeOf(other, proto);
+
+  return [
+    obj.greet(),
+    proto.greet(),
+    Object.getPrototypeOf(obj).name,
+    Object.getPrototypeOf({}),
+    (other as typeof proto).greet(),
+    Object.create(null, { v: { value: 1 } }),
+  ];
+}
@@ -0,0 +1,26 @@
+//! test_output([2,3,{"value":1,"writable":true,"enumerable":true,"configurable":true},undefined,{"inner":{"a":1,"b":2,"c":3}},{"a":1},{"value":1,"writable":true,"enumerable":false,"configurable":true}])
+
+export default function () {
+  let point = { x: 1 } as { x: number; y: number; sum: number };
+
+  Object.defineProperty(point, "y", { value: 2, enumerable: true });
+
+  Object.defineProperty(point, "sum", {
+    get: function (this: { x: number; y: number }) {
+      return this.x + this.y;
+    },
+  });
+
+  let box = { inner: { a: 1 } };
+  Object.defineProperties(box.inner, { b: { value: 2 }, c: { value: 3 } });
+
+  return [
+    point.y,
+    point.sum,
+    Object.getOwnPropertyDescriptor(point, "x"),
+    Object.getOwnPropertyDescriptor(point, "missing"),
+    box,
+    Object.defineProperty({}, "a", { value: 1 }),
+    Object.getOwnPropertyDescriptor([5], "length"),
+  ];
+}
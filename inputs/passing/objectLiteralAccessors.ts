@@ -0,0 +1,63 @@
+//! test_output([["Hi, Ada","Hello, Ada"],[6,2,30],[["celsius","fahrenheit"],32],[10,false]])
+
+export default function () {
+  const greeting = "Hello";
+
+  const greeter = {
+    name: "Ada",
+    short() {
+      return `Hi, ${this.name}`;
+    },
+    long() {
+      return `${greeting}, ${this.name}`;
+    },
+  };
+
+  const scale = 2;
+
+  let rect = {
+    width: 3,
+    height: 1,
+    get area() {
+      return this.width * this.height * scale;
+    },
+    get doubleHeight() {
+      return this.height * 2;
+    },
+    set doubleHeight(value: number) {
+      this.height = value / 2;
+    },
+  };
+
+  const area = rect.area;
+  const doubleHeight = rect.doubleHeight;
+  rect.doubleHeight = 10;
+
+  const temperature = {
+    celsius: 0,
+    get fahrenheit() {
+      return this.celsius * 1.8 + 32;
+    },
+  };
+
+  let counter = {
+    count: 0,
+    increment() {
+      this.count++;
+    },
+    get isEmpty() {
+      return this.count === 0;
+    },
+  };
+
+  for (let i = 0; i < 10; i++) {
+    counter.increment();
+  }
+
+  return [
+    [greeter.short(), greeter.long()],
+    [area, doubleHeight, rect.area],
+    [Object.keys(temperature), temperature.fahrenheit],
+    [counter.count, counter.isEmpty],
+  ];
+}
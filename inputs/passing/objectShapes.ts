@@ -0,0 +1,20 @@
+//! test_output([true,true,{"x":1,"y":2,"z":5},true,false])
+
+export default function () {
+  let points = [];
+
+  for (let i = 0; i < 3; i++) {
+    points.push({ x: i, y: i * 2 });
+  }
+
+  let p = points[1];
+  p.z = 5;
+
+  return [
+    points[2] === { x: 2, y: 4 },
+    { y: 2, x: 1 } === { x: 1, y: 2 },
+    p,
+    p === { x: 1, y: 2, z: 5 },
+    p === { x: 1, y: 2 },
+  ];
+}
@@ -0,0 +1,16 @@
+//! test_output([{"a":1,"b":3,"c":4},{"x":1,"y":2},{"b":2,"c":3},{"c":3},[1,2],{"0":"a","k":true}])
+
+export default function () {
+  const base = { a: 1, b: 2 };
+  const merged = { ...base, b: 3, c: 4 };
+
+  let point: { x: number; y?: number } = { x: 1 };
+  point = { ...point, y: 2 };
+
+  const { a, ...rest } = { a: 1, b: 2, c: 3 };
+
+  const key = "b";
+  const { [key]: b, ...rest2 } = rest;
+
+  return [merged, point, rest, rest2, [a, b], { ...["a"], k: true }];
+}
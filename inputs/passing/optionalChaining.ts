@@ -0,0 +1,29 @@
+//! test_output([[undefined,1,undefined,1],[undefined,undefined,undefined],["hi",undefined,"x"],[1,2,3]])
+
+export default function () {
+  const some = { a: { b: 1 }, k: "b", greet: () => "hi" } as Partial<Obj>;
+  const none = undefined as Partial<Obj> | undefined;
+
+  const members = [none?.a, some?.a?.b, none?.a.b, some?.a?.[some.k!]];
+
+  // None of these evaluate past the `?.`, so nothing throws
+  const skipped = [none?.[fail()], none?.a.b.toString(), none?.greet(fail())];
+
+  const calls = [some.greet?.(), some.nothing?.(), some?.k?.replace("b", "x")];
+
+  let arr = [1, 2];
+  arr?.push(3);
+
+  return [members, skipped, calls, arr];
+}
+
+type Obj = {
+  a: { b: number };
+  k: string;
+  greet: (...args: unknown[]) => string;
+  nothing: () => string;
+};
+
+function fail(): string {
+  throw new Error("Evaluated past ?.");
+}
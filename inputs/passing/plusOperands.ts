@@ -0,0 +1,32 @@
+//! bench()
+
+export default function main() {
+  return [sumNumbers(), joinStrings()];
+}
+
+function sumNumbers() {
+  let total = 0;
+
+  for (let i = 0; i < 100000; i++) {
+    total = total + i + 0.5;
+  }
+
+  return total;
+}
+
+function joinStrings() {
+  const words = ["alpha", "beta", "gamma", "delta"];
+  let total = 0;
+
+  for (let i = 0; i < 20000; i++) {
+    let line = "";
+
+    for (const word of words) {
+      line = line + word + ", ";
+    }
+
+    total += line.length;
+  }
+
+  return total;
+}
@@ -0,0 +1,14 @@
+//! test_output([{"1":"one","2":"two","10":"ten","b":"b","a":"a","01":"zero one"},{"z":1,"y":2,"x":3},true])
+
+export default function () {
+  let obj: Record<string, string> = { b: "b", 10: "ten", a: "a" };
+  obj["2"] = "two";
+  obj["01"] = "zero one";
+  obj[1] = "one";
+
+  let later: Record<string, number> = { z: 0, y: 2 };
+  later.x = 3;
+  later.z = 1;
+
+  return [obj, later, { a: 1, b: 2 } === { b: 2, a: 1 }];
+}
@@ -0,0 +1,28 @@
+//! test_output([[true,false,"/a+b/gi"],[["12-ab","12","ab"],7,["34-cd","34","cd"],null],[["1","22","333"],"a<1>b<22>c<333>","Smith, John","a-1b-3c"],[["a","b","c"],["x","1","y","2","z"],3,[["a1","a","1"],["b2","b","2"]]]])
+
+export default function () {
+  const re = /a+b/gi;
+  const basics = [/^a.c$/im.test("x\nAbC"), /\bcat\b/.test("concatenate"), re.toString()];
+
+  let pairs = /(\d+)-(?<word>[a-z]+)/g;
+  const input = "x 12-ab 34-cd";
+  const first = pairs.exec(input);
+  const lastIndex = pairs.lastIndex;
+  const execs = [first, lastIndex, pairs.exec(input), pairs.exec(input)];
+
+  const replaced = [
+    "a1b22c333".match(/\d+/g),
+    "a1b22c333".replace(/\d+/g, "<$&>"),
+    "John Smith".replace(/(\w+)\s(\w+)/, "$2, $1"),
+    "a-b-c".replace(/-/g, (m, i) => m + i),
+  ];
+
+  const others = [
+    "a, b,c".split(/,\s*/),
+    "x1y2z".split(/(\d)/),
+    "abc1".search(new RegExp("\\d")),
+    "a1b2".matchAll(/([a-z])(\d)/g),
+  ];
+
+  return [basics, execs, replaced, others];
+}
@@ -0,0 +1,24 @@
+//! test_output([200000,true,["zz","z"],RangeError{"message":"Maximum regular expression steps exceeded"}])
+
+// Long inputs don't overflow the stack, and runaway backtracking throws instead of hanging
+
+const doubled = /(\w)\1/;
+
+export default function () {
+  const long = "a".repeat(200000);
+
+  return [
+    /a*/.exec(long)![0].length,
+    /^(\w+)\s\1$/.test(long + " " + long),
+    doubled.exec("xyzzy"),
+    tryTest(/(a*)*b/, "a".repeat(30)),
+  ];
+}
+
+function tryTest(re: RegExp, input: string) {
+  try {
+    return re.test(input);
+  } catch (e) {
+    return e;
+  }
+}
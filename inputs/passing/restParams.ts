@@ -0,0 +1,51 @@
+//! test_output([10,0,["a",["b","c"]],[1,[2,3]],[3,[[4,5],[]]],{"x":1,"y":2,"label":"point"},6])
+
+export default function () {
+  const [head, ...tail] = ["a", "b", "c"];
+
+  return [
+    sum(1, 2, 3, 4),
+    sum(),
+    [head, tail],
+    first(1, 2, 3),
+    count(...[[4, 5], []]),
+    { ...new LabeledPoint(1, 2) },
+    new Calculator().add(1, 2, 3),
+  ];
+}
+
+function sum(...nums: number[]) {
+  let total = 0;
+
+  for (const n of nums) {
+    total += n;
+  }
+
+  return total;
+}
+
+const first = (x: number, ...rest: number[]) => [x, rest];
+
+function count(...[a, ...others]: number[][]) {
+  return [a.length + others.length, [a, ...others]];
+}
+
+class Point {
+  x: number;
+  y: number;
+
+  constructor(x: number, y: number) {
+    this.x = x;
+    this.y = y;
+  }
+}
+
+class LabeledPoint extends Point {
+  label = "point";
+}
+
+class Calculator {
+  add(...nums: number[]) {
+    return sum(...nums);
+  }
+}
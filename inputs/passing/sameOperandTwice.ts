@@ -0,0 +1,15 @@
+//! test_output([49,true,true])
+
+// The right operand can be moved out of the register that the left operand also reads
+
+export default function () {
+  return [square(7), same([1]), same(() => 1)];
+}
+
+function square(x: number) {
+  return x * x;
+}
+
+function same(a: unknown) {
+  return a === a;
+}
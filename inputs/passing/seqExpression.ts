@@ -0,0 +1,28 @@
+//! test_output([6,30,[1,4],{"x":15},[[0,3],[1,2]],8])
+
+export default function () {
+  let a = 1;
+  const b = (a++, a++, a * 10);
+
+  let arr = [1];
+  (a++, arr).push(a);
+
+  let obj = { x: 0 };
+  (a += 1, obj).x = 10;
+  (a++, obj).x += 5;
+
+  let pairs = [];
+  let i, j;
+
+  for (i = 0, j = 3; i < j; i++, j--) {
+    pairs.push([i, j]);
+  }
+
+  const doubled = (0, double)(4);
+
+  return [a, b, arr, obj, pairs, doubled];
+}
+
+function double(x: number) {
+  return 2 * x;
+}
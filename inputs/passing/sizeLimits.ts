@@ -0,0 +1,22 @@
+//! test_output([RangeError{"message":"Invalid string length"},RangeError{"message":"Invalid string length"},RangeError{"message":"Invalid array length"},RangeError{"message":"Invalid array length"},RangeError{"message":"Maximum BigInt size exceeded"},RangeError{"message":"Maximum BigInt size exceeded"}])
+
+// Sizes are limited by default, so these throw instead of trying to allocate the result
+
+export default function () {
+  return [
+    tryRun(() => "x".repeat(1e12)),
+    tryRun(() => "x".padStart(1e12)),
+    tryRun(() => Array(1e12)),
+    tryRun(() => Array.from({ length: 1e12 })),
+    tryRun(() => 2n ** 2000000000n),
+    tryRun(() => 1n << 10000000000n),
+  ];
+}
+
+function tryRun(fn: () => unknown) {
+  try {
+    return fn();
+  } catch (e) {
+    return e;
+  }
+}
@@ -0,0 +1,17 @@
+//! bench()
+
+export default function main() {
+  let total = 0;
+
+  for (let i = 0; i < 20000; i++) {
+    let line = "";
+
+    for (let j = 0; j < 10; j++) {
+      line += "item " + j + ", ";
+    }
+
+    total += line.length;
+  }
+
+  return total;
+}
@@ -1,5 +1,7 @@
-//! test_output("r")
+//! test_output(["r","f","f","o",undefined,undefined])
 
-export default function() {
-  return "foobar".at(-1);
+export default function () {
+  const s = "foobar";
+
+  return [s.at(-1), s.at(0), s.at(), s.at(1.7), s.at(6), s.at(-7)];
 }
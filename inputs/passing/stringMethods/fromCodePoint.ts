@@ -1,5 +1,22 @@
-//! test_output("Hi 👋")
+//! test_output(["Hi 👋","🚀","�a","�",128640,72,RangeError{"message":"Invalid code point 1.5"},RangeError{"message":"Invalid code point 1114112"}])
 
 export default function () {
-  return String.fromCodePoint(72, 105, 32, 128075);
+  return [
+    String.fromCodePoint(72, 105, 32, 128075),
+    String.fromCodePoint(0xD83D, 0xDE80),
+    String.fromCodePoint(0xD83D, 97),
+    String.fromCodePoint(0xDE80),
+    "🚀".codePointAt(),
+    "Hi".codePointAt(0.5),
+    tryFromCodePoint(1.5),
+    tryFromCodePoint(0x110000),
+  ];
+}
+
+function tryFromCodePoint(codePoint: number) {
+  try {
+    return String.fromCodePoint(codePoint);
+  } catch (e) {
+    return e;
+  }
 }
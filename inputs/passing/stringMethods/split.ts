@@ -1,4 +1,4 @@
-//! test_output([["🚀","🍹","a","b","c","£","한","🎨"],["f","","bar"],["","bar"],["foo",""],["one","two","three"]])
+//! test_output([["🚀","🍹","a","b","c","£","한","🎨"],["f","","bar"],["","bar"],["foo",""],["one","two","three"],["one","two"],[],["one","two","three"],["one two three"],[],["a","b"]])
 
 export default function () {
   return [
@@ -7,5 +7,11 @@ export default function () {
     "foobar".split("foo"),
     "foobar".split("bar"),
     "one two three".split(" "),
+    "one two three".split(" ", 2),
+    "one two three".split(" ", 0),
+    "one two three".split(" ", -1),
+    "one two three".split(),
+    "one two three".split(undefined, 0),
+    "abc".split("", 2),
   ];
 }
@@ -0,0 +1,19 @@
+//! test_output(["a","c",undefined,undefined,undefined,"b",undefined,undefined,undefined,3,undefined])
+
+export default function () {
+  const s = "abc";
+
+  return [
+    s[0],
+    s[2],
+    s[3],
+    s[-1],
+    s[1.5],
+    s["1"],
+    s["01"],
+    s["1.0"],
+    s[NaN],
+    s.length,
+    [10, 20]["01" as unknown as number],
+  ];
+}
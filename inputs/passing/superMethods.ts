@@ -0,0 +1,66 @@
+//! test_output(["Rex makes a sound, and then woofs","Rex",["Rex",2],"Dog (Animal)",[1,2]])
+
+export default function () {
+  const dog = new Dog("Rex");
+
+  let counter = new Counter();
+  counter.increment();
+
+  return [
+    dog.speak(),
+    dog.describe(),
+    [dog.name, counter.count],
+    Dog.kind(),
+    dog.tags(),
+  ];
+}
+
+class Animal {
+  constructor(public name: string) {}
+
+  speak() {
+    return `${this.name} makes a sound`;
+  }
+
+  describe() {
+    return this.name;
+  }
+
+  tags() {
+    return [1];
+  }
+
+  static kind() {
+    return "Animal";
+  }
+}
+
+class Dog extends Animal {
+  speak() {
+    return `${super.speak()}, and then woofs`;
+  }
+
+  tags() {
+    const more = () => [...super["tags"](), 2];
+    return more();
+  }
+
+  static kind() {
+    return `Dog (${super.kind()})`;
+  }
+}
+
+class Base {
+  count = 0;
+
+  increment() {
+    this.count++;
+  }
+}
+
+class Counter extends Base {
+  increment() {
+    super.increment();
+    super.increment();
+  }
+}
@@ -0,0 +1,24 @@
+//! test_output([true,false,true,false,true,Symbol.hasInstance])
+
+export default function main() {
+  const Str = {
+    [Symbol.hasInstance]: (x: unknown) => typeof x === "string",
+  };
+
+  return [
+    4 instanceof Even,
+    3 instanceof Even,
+    "x" instanceof Str,
+    1 instanceof Str,
+    new Point() instanceof Point,
+    Symbol.hasInstance,
+  ];
+}
+
+class Even {
+  static [Symbol.hasInstance](x: unknown) {
+    return typeof x === "number" && x % 2 === 0;
+  }
+}
+
+class Point {}
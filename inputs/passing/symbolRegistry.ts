@@ -0,0 +1,18 @@
+//! test_output([true,false,"app.key",undefined,"app.key","Symbol.iterator","Symbol(app.key)",Symbol.for("app.key"),3])
+
+export default function () {
+  const key = Symbol.for("app.key");
+  const obj = { [key]: 3 };
+
+  return [
+    key === Symbol.for("app.key"),
+    key === Symbol.iterator,
+    Symbol.keyFor(key),
+    Symbol.keyFor(Symbol.iterator),
+    key.description,
+    Symbol.iterator.description,
+    key.toString(),
+    key,
+    obj[Symbol.for("app.key")],
+  ];
+}
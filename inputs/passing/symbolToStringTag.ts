@@ -0,0 +1,22 @@
+//! test_output(["[object Object]","[object Tag]","[object Lazy]","[object Counter]",Tag{"x":1},Counter{"count":0}])
+
+export default function () {
+  const tagged = { [Symbol.toStringTag]: "Tag", x: 1 };
+
+  let lazy = {};
+  Object.defineProperty(lazy, Symbol.toStringTag, { get: () => "Lazy" });
+
+  return [
+    String({}),
+    String(tagged),
+    String(lazy),
+    `${new Counter()}`,
+    tagged,
+    new Counter(),
+  ];
+}
+
+class Counter {
+  count = 0;
+  [Symbol.toStringTag] = "Counter";
+}
@@ -0,0 +1,13 @@
+//! test_output(["line1\nline2","tab\there 3","é 😀","`quoted` ${not}",[1,2]])
+
+export default function () {
+  const n = 3;
+
+  return [
+    `line1\nline2`,
+    `tab\there ${n}`,
+    `é \u{1F600}`,
+    `\`quoted\` \${not}`,
+    [`\n`.length, `${n}\t`.length],
+  ];
+}
@@ -0,0 +1,23 @@
+//! test_output([[104,105,32,240,159,145,139],"hi 👋","utf-8","a�b","ok",TypeError{"message":"The encoded data was not valid utf-8"}])
+
+export default function () {
+  const bytes = new TextEncoder().encode("hi 👋");
+  const decoder = new TextDecoder();
+
+  let fatalError;
+
+  try {
+    new TextDecoder("utf-8", { fatal: true }).decode([97, 255, 98]);
+  } catch (e) {
+    fatalError = e;
+  }
+
+  return [
+    bytes,
+    decoder.decode(bytes),
+    decoder.encoding,
+    decoder.decode([97, 255, 98]),
+    decoder.decode([0xef, 0xbb, 0xbf, 111, 107]),
+    fatalError,
+  ];
+}
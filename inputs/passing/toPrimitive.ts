@@ -0,0 +1,32 @@
+//! test_output([101,202,true,"total: 101",42,"default","x","[object Object]"])
+
+class Money {
+  cents: number;
+
+  constructor(cents: number) {
+    this.cents = cents;
+  }
+
+  valueOf() {
+    return this.cents;
+  }
+}
+
+export default function () {
+  const price: any = new Money(101);
+
+  const hinted: any = {
+    [Symbol.toPrimitive]: (hint: string) => hint === "number" ? 42 : hint,
+  };
+
+  return [
+    price + 0,
+    price * 2,
+    price < 200,
+    "total: " + price,
+    hinted * 1,
+    hinted + "",
+    "" + { toString: () => "x" },
+    "" + {},
+  ];
+}
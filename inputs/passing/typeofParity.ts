@@ -0,0 +1,34 @@
+//! test_output(["undefined","object","boolean","number","bigint","symbol","string","object","object","function","function","function","function","function","object","object","function","object"])
+
+class Point {}
+
+function* gen() {
+  yield 1;
+}
+
+function add(a: number, b: number) {
+  return a + b;
+}
+
+export default function () {
+  return [
+    typeof undefined,
+    typeof null,
+    typeof true,
+    typeof 1,
+    typeof 1n,
+    typeof Symbol.iterator,
+    typeof "",
+    typeof [],
+    typeof {},
+    typeof add,
+    typeof (() => 1),
+    typeof Point,
+    typeof Array,
+    typeof Error,
+    typeof Math,
+    typeof gen(),
+    typeof [].map,
+    typeof new Point(),
+  ];
+}
@@ -47,7 +47,7 @@ pub fn main() {
 
       let module = compile_result.module.expect("Expected module");
 
-      let bytecode = assemble(&module);
+      let bytecode = assemble(&module).expect("Failed to assemble");
       let bytecode_len = bytecode.len();
 
       file_count += 1;
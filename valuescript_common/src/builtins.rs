@@ -34,6 +34,47 @@ pub enum BuiltinName {
 
   #[allow(non_camel_case_types)]
   console,
+
+  Date,
+
+  #[allow(non_camel_case_types)]
+  performance,
+
+  #[allow(non_camel_case_types)]
+  crypto,
+
+  TextEncoder,
+  TextDecoder,
+
+  #[allow(non_camel_case_types)]
+  atob,
+
+  #[allow(non_camel_case_types)]
+  btoa,
+
+  AggregateError,
+  SymbolToPrimitive,
+  Object,
+  ForInKeys,
+
+  #[allow(non_camel_case_types)]
+  globalThis,
+
+  GeneratorReturn,
+
+  #[allow(non_camel_case_types)]
+  queueMicrotask,
+
+  SuperConstructor,
+  SymbolHasInstance,
+  SymbolToStringTag,
+  ObjectSpread,
+  ObjectRest,
+  RegExp,
+  Promise,
+  ObjectDefineAccessor,
+  SuperPrototype,
+  SymbolAsyncIterator,
 }
 
 pub const BUILTIN_NAMES: [&str; BuiltinName::COUNT] = [
@@ -55,6 +96,30 @@ pub const BUILTIN_NAMES: [&str; BuiltinName::COUNT] = [
   "SymbolIterator",
   "BigInt",
   "console",
+  "Date",
+  "performance",
+  "crypto",
+  "TextEncoder",
+  "TextDecoder",
+  "atob",
+  "btoa",
+  "AggregateError",
+  "SymbolToPrimitive",
+  "Object",
+  "ForInKeys",
+  "globalThis",
+  "GeneratorReturn",
+  "queueMicrotask",
+  "SuperConstructor",
+  "SymbolHasInstance",
+  "SymbolToStringTag",
+  "ObjectSpread",
+  "ObjectRest",
+  "RegExp",
+  "Promise",
+  "ObjectDefineAccessor",
+  "SuperPrototype",
+  "SymbolAsyncIterator",
 ];
 
 pub const BUILTIN_COUNT: usize = BuiltinName::COUNT;
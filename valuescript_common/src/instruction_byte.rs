@@ -59,6 +59,9 @@ pub enum InstructionByte {
   Cat = 0x38,
   Yield = 0x39,
   YieldStar = 0x3a,
+  NewTarget = 0x3b,
+  Delete = 0x3c,
+  Await = 0x3d,
 }
 
 impl InstructionByte {
@@ -125,6 +128,9 @@ impl InstructionByte {
       0x38 => Cat,
       0x39 => Yield,
       0x3a => YieldStar,
+      0x3b => NewTarget,
+      0x3c => Delete,
+      0x3d => Await,
 
       _ => panic!("Unrecognized instruction: {}", byte),
     }
@@ -1,5 +1,7 @@
 mod builtins;
 mod instruction_byte;
+mod number_to_string;
 
 pub use builtins::*;
 pub use instruction_byte::*;
+pub use number_to_string::number_to_string;
@@ -0,0 +1,61 @@
+/// Formats a number the way JavaScript does (Number::toString in the spec).
+///
+/// Rust's own formatting already finds the shortest digits that round-trip, but it never uses
+/// exponent notation and it keeps the sign of -0. JavaScript switches to exponent notation for
+/// magnitudes of at least 1e21 or below 1e-6, and formats -0 as "0".
+pub fn number_to_string(x: f64) -> String {
+  if x.is_nan() {
+    return "NaN".to_string();
+  }
+
+  if x == 0.0 {
+    return "0".to_string();
+  }
+
+  if x.is_infinite() {
+    return match x.is_sign_positive() {
+      true => "Infinity".to_string(),
+      false => "-Infinity".to_string(),
+    };
+  }
+
+  // Rust's exponent format gives us the shortest round-trip digits, e.g. "1.2345e-7"
+  let scientific = format!("{:e}", x.abs());
+  let (mantissa, exponent) = scientific.split_once('e').expect("Missing exponent");
+
+  let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+  let k = digits.len() as i32;
+  let n = exponent.parse::<i32>().expect("Invalid exponent") + 1;
+
+  let mut res = String::new();
+
+  if x < 0.0 {
+    res.push('-');
+  }
+
+  if k <= n && n <= 21 {
+    res.push_str(&digits);
+    res.push_str(&"0".repeat((n - k) as usize));
+  } else if 0 < n && n <= 21 {
+    res.push_str(&digits[..n as usize]);
+    res.push('.');
+    res.push_str(&digits[n as usize..]);
+  } else if -6 < n && n <= 0 {
+    res.push_str("0.");
+    res.push_str(&"0".repeat(-n as usize));
+    res.push_str(&digits);
+  } else {
+    res.push_str(&digits[..1]);
+
+    if k > 1 {
+      res.push('.');
+      res.push_str(&digits[1..]);
+    }
+
+    res.push('e');
+    res.push(if n > 0 { '+' } else { '-' });
+    res.push_str(&(n - 1).abs().to_string());
+  }
+
+  res
+}
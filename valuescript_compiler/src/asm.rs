@@ -5,6 +5,7 @@ use std::{
 };
 
 use num_bigint::BigInt;
+use valuescript_common::number_to_string;
 
 use crate::{
   assembler::ValueType, expression_compiler::CompiledExpression, instruction::RegisterVisitMut,
@@ -339,8 +340,14 @@ impl StructuredFormattable for Pointer {
 #[derive(Default, Debug, Clone)]
 pub struct Function {
   pub is_generator: bool,
+  pub is_async: bool,
   pub meta: Option<Pointer>,
   pub parameters: Vec<Register>,
+
+  /// Whether the last parameter is a rest parameter, which collects the remaining arguments into
+  /// an array.
+  pub has_rest_param: bool,
+
   pub body: Vec<FnLine>,
 }
 
@@ -351,6 +358,10 @@ impl StructuredFormattable for Function {
       Some(p) => format!(" {}", Structured(p)),
     };
 
+    if self.is_async {
+      sf.write("async ")?;
+    }
+
     match self.is_generator {
       false => sf.write(&format!("function{}(", meta_str))?,
       true => sf.write(&format!("function*{}(", meta_str))?,
@@ -360,6 +371,11 @@ impl StructuredFormattable for Function {
       if i > 0 {
         sf.write(", ")?;
       }
+
+      if self.has_rest_param && i == self.parameters.len() - 1 {
+        sf.write("...")?;
+      }
+
       sf.write(parameter)?;
     }
     sf.write(") {")?;
@@ -385,6 +401,7 @@ impl StructuredFormattable for Function {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Class {
   pub meta: Meta,
+  pub extends: Option<Value>,
   pub constructor: Value,
   pub prototype: Value,
   pub static_: Value,
@@ -396,6 +413,11 @@ impl StructuredFormattable for Class {
 
     sf.nest(|sf| {
       sf.write_line(&[&"meta: ", &self.meta, &","])?;
+
+      if let Some(extends) = &self.extends {
+        sf.write_line(&[&"extends: ", extends, &","])?;
+      }
+
       sf.write_line(&[&"constructor: ", &self.constructor, &","])?;
       sf.write_line(&[&"prototype: ", &MultilineValue(&self.prototype), &","])?;
       sf.write_line(&[&"static: ", &MultilineValue(&self.static_), &","])?;
@@ -617,6 +639,10 @@ impl Value {
         }
       }
       Value::Class(class) => {
+        if let Some(extends) = &mut class.extends {
+          extends.visit_values_mut(visit);
+        }
+
         class.constructor.visit_values_mut(visit);
         class.prototype.visit_values_mut(visit);
         class.static_.visit_values_mut(visit);
@@ -654,6 +680,10 @@ impl Value {
         class.constructor.visit_registers_mut_rev(visit);
         class.prototype.visit_registers_mut_rev(visit);
         class.static_.visit_registers_mut_rev(visit);
+
+        if let Some(extends) = &mut class.extends {
+          extends.visit_registers_mut_rev(visit);
+        }
       }
       Value::Void => {}
       Value::Undefined => {}
@@ -679,14 +709,11 @@ impl StructuredFormattable for Value {
       Value::Null => sf.write("null"),
       Value::Bool(value) => sf.write(&value.to_string()),
       Value::Number(Number(value)) => {
-        if value.is_infinite() {
-          if value.is_sign_positive() {
-            sf.write("Infinity")
-          } else {
-            sf.write("-Infinity")
-          }
+        if *value == 0.0 && value.is_sign_negative() {
+          // Unlike JavaScript, assembly needs to preserve the sign of zero
+          sf.write("-0")
         } else {
-          sf.write(&value.to_string())
+          sf.write(&number_to_string(*value))
         }
       }
       Value::BigInt(value) => sf.write_slice(&[&value.to_string(), &"n"]),
@@ -13,28 +13,145 @@ use crate::asm::{
   StructuredFormattable, Value,
 };
 
-pub fn assemble(module: &Module) -> Vec<u8> {
-  let mut assembler = Assembler {
-    output: Vec::new(),
-    fn_data: Default::default(),
-    definitions_map: LocationMap {
-      references: HashMap::new(),
-      found_locations: HashMap::new(),
-    },
-  };
+/// Positions in bytecode (pointers, labels and the constant pool) are written as two bytes, so
+/// bytecode can't be longer than this.
+const MAX_BYTECODE_LEN: usize = 0x10000;
+
+pub fn assemble(module: &Module) -> Result<Vec<u8>, String> {
+  Ok(assemble_impl(module, false)?.0)
+}
+
+/// Like `assemble`, but also returns a listing of where each line of each function (and lazy value)
+/// ended up in the bytecode. This is useful when debugging the assembler or the decoder.
+pub fn assemble_with_listing(module: &Module) -> Result<(Vec<u8>, Listing), String> {
+  let (output, listing) = assemble_impl(module, true)?;
+  Ok((output, listing.unwrap_or_default()))
+}
+
+fn assemble_impl(
+  module: &Module,
+  with_listing: bool,
+) -> Result<(Vec<u8>, Option<Listing>), String> {
+  // The first pass only counts the constants, so that the second pass can move the repeated ones
+  // into the constant pool.
+  let mut counter = Assembler::new(ConstantPool::default());
+  counter.module(module);
+
+  let mut assembler = Assembler::new(counter.constants.finalize());
+
+  if with_listing {
+    assembler.listing_ranges = Some(Vec::new());
+  }
+
+  if !assembler.constants.entries.is_empty() {
+    assembler.output.push(ValueType::ConstantPool as u8);
+    assembler.output.push(0xff);
+    assembler.output.push(0xff);
+  }
 
   assembler.module(module);
+  assembler.constant_pool();
+
+  // Positions past the limit would have been truncated when they were written
+  if assembler.output.len() > MAX_BYTECODE_LEN {
+    return Err(format!(
+      "Bytecode is too large ({} bytes, the maximum is {})",
+      assembler.output.len(),
+      MAX_BYTECODE_LEN,
+    ));
+  }
 
-  assembler.output
+  // Bytes are read after assembly is complete so that they include the resolved locations
+  let listing = assembler.listing_ranges.map(|ranges| Listing {
+    lines: ranges
+      .into_iter()
+      .map(|(definition, fn_line, start, end)| ListingLine {
+        definition,
+        fn_line,
+        offset: start,
+        bytes: assembler.output[start..end].to_vec(),
+      })
+      .collect(),
+  });
+
+  Ok((assembler.output, listing))
+}
+
+/// The bytecode produced for each line of the functions and lazy values of a module.
+#[derive(Default)]
+pub struct Listing {
+  pub lines: Vec<ListingLine>,
+}
+
+pub struct ListingLine {
+  pub definition: Pointer,
+  pub fn_line: FnLine,
+  pub offset: usize,
+
+  /// Empty for lines that don't produce bytecode, like labels and comments
+  pub bytes: Vec<u8>,
+}
+
+impl std::fmt::Display for Listing {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut definition: Option<&Pointer> = None;
+
+    for line in &self.lines {
+      if definition != Some(&line.definition) {
+        if definition.is_some() {
+          writeln!(f)?;
+        }
+
+        writeln!(f, "{}:", Structured(&line.definition))?;
+        definition = Some(&line.definition);
+      }
+
+      let bytes = line
+        .bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+      writeln!(
+        f,
+        "  {:#06x}  {:<24} {}",
+        line.offset,
+        bytes,
+        Structured(&line.fn_line)
+      )?;
+    }
+
+    Ok(())
+  }
 }
 
 struct Assembler {
   output: Vec<u8>,
   fn_data: AssemblerFnData,
   definitions_map: LocationMap,
+  constants: ConstantPool,
+  definition: Option<Pointer>,
+
+  /// When producing a listing, the definition, line and output range of each line in a body
+  listing_ranges: Option<Vec<(Pointer, FnLine, usize, usize)>>,
 }
 
 impl Assembler {
+  fn new(constants: ConstantPool) -> Assembler {
+    Assembler {
+      output: Vec::new(),
+      fn_data: Default::default(),
+      definitions_map: LocationMap {
+        references: HashMap::new(),
+        found_locations: HashMap::new(),
+      },
+      constants,
+      definition: None,
+      listing_ranges: None,
+    }
+  }
+
   fn module(&mut self, module: &Module) {
     self.value(&module.export_default);
     self.output.push(ValueType::ExportStar as u8);
@@ -54,6 +171,8 @@ impl Assembler {
   }
 
   fn definition(&mut self, definition: &Definition) {
+    self.definition = Some(definition.pointer.clone());
+
     self.definitions_map.found_locations.insert(
       LocationRef::Pointer(definition.pointer.clone()),
       self.output.len(),
@@ -76,10 +195,14 @@ impl Assembler {
   }
 
   fn function(&mut self, function: &Function) {
-    self.output.push(match function.is_generator {
-      false => ValueType::Function,
-      true => ValueType::GeneratorFunction,
-    } as u8);
+    self
+      .output
+      .push(match (function.is_generator, function.is_async) {
+        (true, false) => ValueType::GeneratorFunction,
+        (true, true) => ValueType::AsyncGeneratorFunction,
+        (false, true) => ValueType::AsyncFunction,
+        (false, false) => ValueType::Function,
+      } as u8);
 
     match &function.meta {
       Some(p) => {
@@ -96,7 +219,11 @@ impl Assembler {
     self.fn_data.register_count_pos = self.output.len();
     self.output.push(0xff); // Placeholder for register count
 
-    self.output.push(function.parameters.len() as u8);
+    // The high bit of the parameter count marks the last parameter as a rest parameter
+    self.output.push(match function.has_rest_param {
+      false => function.parameters.len() as u8,
+      true => function.parameters.len() as u8 | 0x80,
+    });
 
     let mut param_set = HashSet::<Register>::new();
 
@@ -112,17 +239,7 @@ impl Assembler {
       self.lookup_register(parameter);
     }
 
-    for fn_line in &function.body {
-      match fn_line {
-        FnLine::Instruction(instruction) => {
-          self.instruction(instruction);
-        }
-        FnLine::Label(label) => {
-          self.label(label);
-        }
-        FnLine::Empty | FnLine::Comment(..) | FnLine::Release(..) => {}
-      }
-    }
+    self.body(&function.body);
 
     self.output.push(Instruction::End.byte() as u8);
 
@@ -136,7 +253,8 @@ impl Assembler {
   fn meta(&mut self, meta: &Meta) {
     self.output.push(ValueType::Meta as u8);
 
-    self.string(&meta.name);
+    // The decoder reads the name directly, so it can't come from the constant pool
+    self.inline_string(&meta.name);
 
     match &meta.content_hashable {
       ContentHashable::Empty => self.output.push(0x00),
@@ -174,7 +292,21 @@ impl Assembler {
     self.fn_data.register_count_pos = self.output.len();
     self.output.push(0xff); // Placeholder for register count
 
-    for fn_line in &lazy.body {
+    self.body(&lazy.body);
+
+    self.output.push(Instruction::End.byte() as u8);
+
+    // TODO: Handle >255 registers
+    // +3: return, this, ignore
+    self.output[self.fn_data.register_count_pos] = (self.fn_data.register_map.len() + 3) as u8;
+
+    self.fn_data.labels_map.resolve(&mut self.output);
+  }
+
+  fn body(&mut self, body: &[FnLine]) {
+    for fn_line in body {
+      let start = self.output.len();
+
       match fn_line {
         FnLine::Instruction(instruction) => {
           self.instruction(instruction);
@@ -184,20 +316,32 @@ impl Assembler {
         }
         FnLine::Empty | FnLine::Comment(..) | FnLine::Release(..) => {}
       }
-    }
 
-    self.output.push(Instruction::End.byte() as u8);
-
-    // TODO: Handle >255 registers
-    // +3: return, this, ignore
-    self.output[self.fn_data.register_count_pos] = (self.fn_data.register_map.len() + 3) as u8;
-
-    self.fn_data.labels_map.resolve(&mut self.output);
+      if let (Some(listing_ranges), Some(definition)) = (&mut self.listing_ranges, &self.definition)
+      {
+        listing_ranges.push((
+          definition.clone(),
+          fn_line.clone(),
+          start,
+          self.output.len(),
+        ));
+      }
+    }
   }
 
   fn class(&mut self, class: &Class) {
-    self.output.push(ValueType::Class as u8);
-    self.meta(&class.meta);
+    match &class.extends {
+      None => {
+        self.output.push(ValueType::Class as u8);
+        self.meta(&class.meta);
+      }
+      Some(extends) => {
+        self.output.push(ValueType::DerivedClass as u8);
+        self.meta(&class.meta);
+        self.value(extends);
+      }
+    }
+
     self.value(&class.constructor);
     self.value(&class.prototype);
     self.value(&class.static_);
@@ -326,6 +470,18 @@ impl Assembler {
         self.value(value);
         self.register(dst);
       }
+      Await(value, dst) => {
+        self.value(value);
+        self.register(dst);
+      }
+      NewTarget(dst) => {
+        self.register(dst);
+      }
+      Delete(obj, key, dst) => {
+        self.register(obj);
+        self.value(key);
+        self.register(dst);
+      }
     }
   }
 
@@ -411,12 +567,16 @@ impl Assembler {
       for b in (value as i8).to_le_bytes() {
         self.output.push(b);
       }
-    } else {
-      self.output.push(ValueType::Number as u8);
+    } else if !self.constant(Constant::Number(value.to_bits())) {
+      self.inline_number(value);
+    }
+  }
 
-      for b in value.to_le_bytes() {
-        self.output.push(b);
-      }
+  fn inline_number(&mut self, value: f64) {
+    self.output.push(ValueType::Number as u8);
+
+    for b in value.to_le_bytes() {
+      self.output.push(b);
     }
   }
 
@@ -436,6 +596,12 @@ impl Assembler {
   }
 
   fn string(&mut self, value: &String) {
+    if !self.constant(Constant::String(value.clone())) {
+      self.inline_string(value);
+    }
+  }
+
+  fn inline_string(&mut self, value: &String) {
     self.output.push(ValueType::String as u8);
     self.varsize_uint(value.len());
 
@@ -444,6 +610,46 @@ impl Assembler {
     }
   }
 
+  /// Writes a reference to the constant if it's in the pool. Otherwise, returns false so that the
+  /// caller writes it inline.
+  fn constant(&mut self, constant: Constant) -> bool {
+    if self.constants.collecting {
+      self.constants.record(constant);
+      return false;
+    }
+
+    match self.constants.indexes.get(&constant) {
+      Some(index) => {
+        let index = *index;
+        self.output.push(ValueType::Constant as u8);
+        self.varsize_uint(index);
+
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn constant_pool(&mut self) {
+    if self.constants.entries.is_empty() {
+      return;
+    }
+
+    let pool_pos = self.output.len();
+    self.output[1] = (pool_pos % 256) as u8;
+    self.output[2] = (pool_pos / 256) as u8;
+
+    let entries = std::mem::take(&mut self.constants.entries);
+    self.varsize_uint(entries.len());
+
+    for entry in &entries {
+      match entry {
+        Constant::String(string) => self.inline_string(string),
+        Constant::Number(bits) => self.inline_number(f64::from_bits(*bits)),
+      }
+    }
+  }
+
   fn pointer(&mut self, value: &Pointer) {
     self.output.push(ValueType::Pointer as u8);
     self
@@ -506,9 +712,104 @@ pub enum ValueType {
   GeneratorFunction = 0x14,
   ExportStar = 0x15,
   Meta = 0x16,
+  ConstantPool = 0x17,
+  Constant = 0x18,
+  DerivedClass = 0x19,
+  Compressed = 0x1a,
+  AsyncFunction = 0x1b,
+  AsyncGeneratorFunction = 0x1c,
   // External = TBD,
 }
 
+#[derive(Hash, PartialEq, Eq, Clone)]
+enum Constant {
+  String(String),
+  Number(u64),
+}
+
+impl Constant {
+  fn inline_len(&self) -> usize {
+    match self {
+      Constant::String(string) => 1 + varsize_uint_len(string.len()) + string.len(),
+      Constant::Number(_) => 9,
+    }
+  }
+}
+
+/// Strings and numbers that are used more than once, so they can be stored once at the end of the
+/// bytecode and referenced by index.
+struct ConstantPool {
+  collecting: bool,
+  counts: HashMap<Constant, usize>,
+  entries: Vec<Constant>,
+  indexes: HashMap<Constant, usize>,
+}
+
+impl Default for ConstantPool {
+  fn default() -> Self {
+    ConstantPool {
+      collecting: true,
+      counts: HashMap::new(),
+      entries: vec![],
+      indexes: HashMap::new(),
+    }
+  }
+}
+
+impl ConstantPool {
+  fn record(&mut self, constant: Constant) {
+    let count = self.counts.entry(constant.clone()).or_insert(0);
+
+    if *count == 0 {
+      self.entries.push(constant);
+    }
+
+    *count += 1;
+  }
+
+  /// Keeps the constants that make the output smaller when pooled, with the most used ones first
+  /// so that they get the shortest indexes.
+  fn finalize(self) -> ConstantPool {
+    let mut entries = self.entries;
+
+    // Stable, so ties stay in order of first use and the output is deterministic
+    entries.sort_by_key(|c| std::cmp::Reverse(self.counts[c]));
+
+    let mut pool = ConstantPool {
+      collecting: false,
+      counts: HashMap::new(),
+      entries: vec![],
+      indexes: HashMap::new(),
+    };
+
+    for constant in entries {
+      let count = self.counts[&constant];
+      let ref_len = 1 + varsize_uint_len(pool.entries.len());
+
+      if count < 2 || count * ref_len + constant.inline_len() >= count * constant.inline_len() {
+        continue;
+      }
+
+      pool.indexes.insert(constant.clone(), pool.entries.len());
+      pool.entries.push(constant);
+    }
+
+    pool
+  }
+}
+
+fn varsize_uint_len(value: usize) -> usize {
+  let mut len = 1;
+  let mut x = value / 128;
+
+  while x != 0 {
+    len += 1;
+    x /= 128;
+  }
+
+  len
+}
+
 #[derive(Hash, PartialEq, Eq, Clone)]
 enum LocationRef {
   Pointer(Pointer),
@@ -535,7 +836,7 @@ impl LocationMap {
     self.references.entry(ref_).or_default().push(output.len());
 
     output.push(0xff);
-    output.push(0xff); // TODO: Support >65535
+    output.push(0xff);
   }
 
   fn resolve(&self, output: &mut [u8]) {
@@ -554,7 +855,7 @@ impl LocationMap {
 
       for ref_location in ref_locations {
         output[*ref_location] = (*location % 256) as u8;
-        output[*ref_location + 1] = (*location / 256) as u8; // TODO: Support >65535
+        output[*ref_location + 1] = (*location / 256) as u8;
       }
     }
   }
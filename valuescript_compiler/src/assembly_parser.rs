@@ -6,9 +6,13 @@ use valuescript_common::{InstructionByte, BUILTIN_NAMES};
 
 use crate::asm::{
   Array, Builtin, Class, ContentHashable, Definition, DefinitionContent, ExportStar, FnLine,
-  Function, Hash, Instruction, Label, LabelRef, Meta, Module, Number, Object, Pointer, Register,
-  Value,
+  Function, Hash, Instruction, Label, LabelRef, Lazy, Meta, Module, Number, Object, Pointer,
+  Register, Value,
 };
+use crate::diagnostic::{Diagnostic, DiagnosticLevel};
+
+/// Parsing stops at the first error, which is reported with the span of the offending input.
+type ParseResult<T> = Result<T, Diagnostic>;
 
 pub struct AssemblyParser<'a> {
   pub content: &'a str,
@@ -16,15 +20,15 @@ pub struct AssemblyParser<'a> {
 }
 
 impl<'a> AssemblyParser<'a> {
-  fn module(&mut self) -> Module {
-    self.parse_exact("export");
-    self.parse_whitespace();
+  fn module(&mut self) -> ParseResult<Module> {
+    self.parse_exact("export")?;
+    self.parse_whitespace()?;
 
-    let export_default = self.assemble_value();
-    self.parse_whitespace();
+    let export_default = self.assemble_value()?;
+    self.parse_whitespace()?;
 
-    let export_star = self.assemble_export_star();
-    self.parse_whitespace();
+    let export_star = self.assemble_export_star()?;
+    self.parse_whitespace()?;
 
     let mut definitions = Vec::<Definition>::new();
 
@@ -35,77 +39,37 @@ impl<'a> AssemblyParser<'a> {
         break;
       }
 
-      definitions.push(self.assemble_definition());
+      definitions.push(self.assemble_definition()?);
     }
 
-    Module {
+    Ok(Module {
       export_default,
       export_star,
       definitions,
-    }
-  }
-
-  fn get_pos_index(&self) -> usize {
-    let mut start = self.content.chars();
-    let mut i = 0_usize;
-
-    loop {
-      if start.clone().eq(self.pos.clone()) {
-        return i;
-      }
-
-      i += 1;
-      start.next();
-    }
-  }
-
-  fn get_line_col(&self, offset: isize) -> LineCol {
-    let mut line = 1;
-    let mut col = 1;
-
-    let raw_pos = self.get_pos_index();
-    let pos = (raw_pos as isize + offset).max(0) as usize;
-
-    for (i, c) in self.content.chars().enumerate() {
-      if i == pos {
-        break;
-      }
-
-      if c == '\n' {
-        line += 1;
-        col = 1;
-      } else {
-        col += 1;
-      }
-    }
-
-    LineCol { line, col }
+    })
   }
 
-  fn get_lines(&self) -> Vec<&str> {
-    return self.content.split('\n').collect();
+  /// The byte offset of the parser's position, moved back by `chars_back` characters (eg to point
+  /// at a character that has already been consumed).
+  fn byte_pos(&self, chars_back: usize) -> usize {
+    let remaining: usize = self.pos.clone().map(char::len_utf8).sum();
+    let pos = self.content.len() - remaining;
+
+    self.content[..pos]
+      .chars()
+      .rev()
+      .take(chars_back)
+      .fold(pos, |pos, c| pos - c.len_utf8())
   }
 
-  fn render_pos(&self, offset: isize, message: &str) -> String {
-    let LineCol { line, col } = self.get_line_col(offset);
-    let source_lines = self.get_lines();
-
-    let mut output = String::new();
-
-    output += &format!("(unknown):{}:{}: {}:\n", line, col, message);
+  fn error<T>(&self, chars_back: usize, message: &str) -> ParseResult<T> {
+    let pos = swc_common::BytePos(self.byte_pos(chars_back) as u32);
 
-    if line >= 2 {
-      output += &format!("{: >6} | {}\n", line - 1, source_lines[line - 2]);
-    }
-
-    output += &format!("{: >6} | {}\n", line, source_lines[line - 1]);
-    output += &format!("{: >6} | {}\n", "", " ".repeat(col - 1) + "^");
-
-    if line < source_lines.len() {
-      output += &format!("{: >6} | {}\n", line + 1, source_lines[line]);
-    }
-
-    output
+    Err(Diagnostic {
+      level: DiagnosticLevel::Error,
+      message: message.to_string(),
+      span: swc_common::Span::new(pos, pos, Default::default()),
+    })
   }
 
   fn test_chars(&self, chars: &str) -> bool {
@@ -120,7 +84,7 @@ impl<'a> AssemblyParser<'a> {
     true
   }
 
-  fn parse_whitespace(&mut self) {
+  fn parse_whitespace(&mut self) -> ParseResult<()> {
     let mut count = 0;
 
     loop {
@@ -135,8 +99,10 @@ impl<'a> AssemblyParser<'a> {
     }
 
     if count == 0 {
-      panic!("{}", self.render_pos(0, "Expected whitespace"));
+      return self.error(0, "Expected whitespace");
     }
+
+    Ok(())
   }
 
   fn parse_optional_whitespace(&mut self) {
@@ -155,7 +121,7 @@ impl<'a> AssemblyParser<'a> {
 
   fn parse_line(&mut self) {
     loop {
-      if let Some('\n') = self.pos.next() {
+      if let Some('\n') | None = self.pos.next() {
         return;
       }
     }
@@ -174,32 +140,36 @@ impl<'a> AssemblyParser<'a> {
     }
   }
 
-  fn assemble_definition(&mut self) -> Definition {
-    self.parse_exact("@");
-    let def_name = self.parse_identifier();
+  fn assemble_definition(&mut self) -> ParseResult<Definition> {
+    self.parse_exact("@")?;
+    let def_name = self.parse_identifier()?;
     self.parse_optional_whitespace();
-    self.parse_exact("=");
+    self.parse_exact("=")?;
     self.parse_optional_whitespace();
 
     let content = 'b: {
-      if self.test_chars("function") {
-        break 'b DefinitionContent::Function(self.assemble_function());
+      if self.test_chars("function") || self.test_chars("async function") {
+        break 'b DefinitionContent::Function(self.assemble_function()?);
       }
 
       if self.test_chars("meta") {
-        break 'b DefinitionContent::Meta(self.assemble_fn_meta());
+        break 'b DefinitionContent::Meta(self.assemble_fn_meta()?);
       }
 
-      DefinitionContent::Value(self.assemble_value())
+      if self.test_chars("lazy") {
+        break 'b DefinitionContent::Lazy(self.assemble_lazy()?);
+      }
+
+      DefinitionContent::Value(self.assemble_value()?)
     };
 
-    Definition {
+    Ok(Definition {
       pointer: Pointer { name: def_name },
       content,
-    }
+    })
   }
 
-  fn parse_instruction_word(&mut self) -> InstructionByte {
+  fn parse_instruction_word(&mut self) -> ParseResult<InstructionByte> {
     let instruction_word_map: HashMap<&str, InstructionByte> = HashMap::from([
       ("end", InstructionByte::End),
       ("mov", InstructionByte::Mov),
@@ -236,7 +206,7 @@ impl<'a> AssemblyParser<'a> {
       ("in", InstructionByte::In),
       ("call", InstructionByte::Call),
       ("apply", InstructionByte::Apply),
-      ("const_apply", InstructionByte::Apply),
+      ("const_apply", InstructionByte::ConstApply),
       ("bind", InstructionByte::Bind),
       ("sub", InstructionByte::Sub),
       ("submov", InstructionByte::SubMov),
@@ -260,6 +230,9 @@ impl<'a> AssemblyParser<'a> {
       ("cat", InstructionByte::Cat),
       ("yield", InstructionByte::Yield),
       ("yield*", InstructionByte::YieldStar),
+      ("await", InstructionByte::Await),
+      ("new_target", InstructionByte::NewTarget),
+      ("delete", InstructionByte::Delete),
     ]);
 
     for (word, instruction) in instruction_word_map {
@@ -267,14 +240,25 @@ impl<'a> AssemblyParser<'a> {
         advance_chars(&mut self.pos, word.len());
         match self.pos.peek() {
           Some('\n') | None | Some(' ') => {}
-          _ => panic!("Unexpected non-whitespace character after instruction word"),
+          _ => {
+            return self.error(
+              0,
+              "Unexpected non-whitespace character after instruction word",
+            )
+          }
         }
         self.parse_optional_spaces();
-        return instruction;
+        return Ok(instruction);
       }
     }
 
-    panic!("{}", self.render_pos(0, "Failed to parse instruction"));
+    let word = self
+      .pos
+      .clone()
+      .take_while(|c| !c.is_whitespace())
+      .collect::<String>();
+
+    self.error(0, &format!("Unrecognized instruction `{}`", word))
   }
 
   fn test_instruction_word(&self, word: &str) -> bool {
@@ -326,55 +310,51 @@ impl<'a> AssemblyParser<'a> {
     Some(res)
   }
 
-  fn parse_identifier(&mut self) -> String {
-    let optional_identifier = self.test_identifier();
-
-    if optional_identifier.is_none() {
-      panic!("{}", self.render_pos(0, "Invalid identifier"));
-    }
+  fn parse_identifier(&mut self) -> ParseResult<String> {
+    let identifier = match self.test_identifier() {
+      Some(identifier) => identifier,
+      None => return self.error(0, "Invalid identifier"),
+    };
 
-    let identifier = optional_identifier.unwrap();
     advance_chars(&mut self.pos, identifier.len());
 
-    identifier
+    Ok(identifier)
   }
 
-  fn parse_exact(&mut self, chars: &str) {
+  fn parse_exact(&mut self, chars: &str) -> ParseResult<()> {
     for c in chars.chars() {
-      if self.pos.next() != Some(c) {
-        panic!("{}", self.render_pos(-1, &format!("Expected '{}'", c)));
+      match self.pos.next() {
+        Some(next) if next == c => {}
+        Some(_) => return self.error(1, &format!("Expected '{}'", c)),
+        None => return self.error(0, &format!("Expected '{}' but found end of input", c)),
       }
     }
+
+    Ok(())
   }
 
-  fn parse_one_of(&mut self, options: &[&str]) -> String {
+  fn parse_one_of(&mut self, options: &[&str]) -> ParseResult<String> {
     for opt in options {
       if self.test_chars(opt) {
         advance_chars(&mut self.pos, opt.len());
-        return opt.to_string();
+        return Ok(opt.to_string());
       }
     }
 
-    panic!(
-      "{}",
-      self.render_pos(0, &format!("Expected one of {:?}", options))
-    );
+    self.error(0, &format!("Expected one of {:?}", options))
   }
 
-  fn parse_string_literal(&mut self) -> String {
+  fn parse_string_literal(&mut self) -> ParseResult<String> {
     let mut result = "".to_string();
 
-    self.parse_exact("\"");
+    self.parse_exact("\"")?;
     let mut escaping = false;
 
     loop {
-      let oc = self.pos.next();
-
-      if oc.is_none() {
-        break;
-      }
-
-      let c = oc.unwrap();
+      let c = match self.pos.next() {
+        Some(c) => c,
+        None => return self.error(0, "Unterminated string literal"),
+      };
 
       if escaping {
         if c == '\\' {
@@ -385,8 +365,18 @@ impl<'a> AssemblyParser<'a> {
           result.push('\n');
         } else if c == 't' {
           result.push('\t');
+        } else if c == 'r' {
+          result.push('\r');
+        } else if c == 'b' {
+          result.push('\u{8}');
+        } else if c == 'f' {
+          result.push('\u{c}');
+        } else if c == '/' {
+          result.push('/');
+        } else if c == 'u' {
+          result.push(self.parse_unicode_escape()?);
         } else {
-          panic!("{}", self.render_pos(-1, "Unimplemented escape sequence"));
+          return self.error(1, "Unimplemented escape sequence");
         }
 
         escaping = false;
@@ -399,80 +389,120 @@ impl<'a> AssemblyParser<'a> {
       }
     }
 
-    if escaping {
-      panic!(
-        "{}",
-        self.render_pos(0, "Unexpected end of input after escape character")
-      );
+    Ok(result)
+  }
+
+  /// Parses the hex digits of a `\uXXXX` escape, which is how strings are written when they
+  /// contain other control characters.
+  fn parse_unicode_escape(&mut self) -> ParseResult<char> {
+    let mut code = 0;
+
+    for _ in 0..4 {
+      match self.pos.next().and_then(|c| c.to_digit(16)) {
+        Some(digit) => code = code * 16 + digit,
+        None => return self.error(1, "Expected hex digit in unicode escape"),
+      }
     }
 
-    result
+    match char::from_u32(code) {
+      Some(c) => Ok(c),
+      None => self.error(1, "Invalid unicode escape"),
+    }
   }
 
-  fn assemble_function(&mut self) -> Function {
+  fn assemble_function(&mut self) -> ParseResult<Function> {
     let mut function = Function::default();
 
-    self.parse_exact("function");
+    if self.test_chars("async") {
+      self.parse_exact("async")?;
+      self.parse_whitespace()?;
+      function.is_async = true;
+    }
+
+    self.parse_exact("function")?;
 
     if self.test_chars("*") {
       advance_chars(&mut self.pos, 1);
       function.is_generator = true;
     }
 
-    self.parse_whitespace();
+    self.parse_optional_whitespace();
 
     if self.test_chars("(") {
       // Leave meta as void
-      self.parse_exact("(");
+      self.parse_exact("(")?;
     } else {
-      function.meta = Some(self.assemble_pointer());
+      function.meta = Some(self.assemble_pointer()?);
       self.parse_optional_whitespace();
-      self.parse_exact("(");
+      self.parse_exact("(")?;
     }
 
     loop {
       self.parse_optional_whitespace();
-      let mut next = self.parse_one_of(&["%", ")"]);
+      let mut next = self.parse_one_of(&["%", "...%", ")"])?;
 
       if next == ")" {
         break;
       }
 
-      if next != "%" {
-        panic!("Expected this to be impossible");
+      if function.has_rest_param {
+        return self.error(0, "Rest parameter must be last");
       }
 
-      let take = self.parse_one_of(&["!", ""]) == "!";
+      match next.as_str() {
+        "%" => {}
+        "...%" => function.has_rest_param = true,
+        _ => panic!("Expected this to be impossible"),
+      }
+
+      let take = self.parse_one_of(&["!", ""])? == "!";
 
-      let param_name = self.parse_identifier();
+      let param_name = self.parse_identifier()?;
 
       function.parameters.push(Register {
         take,
         name: param_name.clone(),
       });
 
-      next = self.parse_one_of(&[",", ")"]);
+      next = self.parse_one_of(&[",", ")"])?;
 
       if next == ")" {
         break;
       }
     }
 
+    function.body = self.assemble_body()?;
+
+    Ok(function)
+  }
+
+  fn assemble_lazy(&mut self) -> ParseResult<Lazy> {
+    self.parse_exact("lazy")?;
+
+    Ok(Lazy {
+      body: self.assemble_body()?,
+    })
+  }
+
+  /// Parses the `{ ... }` body of a function or lazy value.
+  fn assemble_body(&mut self) -> ParseResult<Vec<FnLine>> {
+    let mut body = Vec::<FnLine>::new();
+
     self.parse_optional_whitespace();
-    self.parse_exact("{");
+    self.parse_exact("{")?;
     self.parse_line();
 
     loop {
       self.parse_optional_spaces();
 
-      let c = *self
-        .pos
-        .peek()
-        .expect("Expected instruction, label, or end of function");
+      let c = match self.pos.peek() {
+        Some(c) => *c,
+        None => return self.error(0, "Expected instruction, label, or `}` to end the function"),
+      };
 
       if c == '\n' {
         self.pos.next();
-        function.body.push(FnLine::Empty);
+        body.push(FnLine::Empty);
         continue;
       }
 
@@ -482,7 +512,7 @@ impl<'a> AssemblyParser<'a> {
       }
 
       if c == '/' {
-        self.parse_exact("//");
+        self.parse_exact("//")?;
 
         let mut msg = String::new();
 
@@ -493,44 +523,44 @@ impl<'a> AssemblyParser<'a> {
           }
         }
 
-        function.body.push(FnLine::Comment(msg.trim().to_string()));
+        body.push(FnLine::Comment(msg.trim().to_string()));
 
         continue;
       }
 
       if c == '(' {
-        self.parse_exact("(release");
-        self.parse_whitespace();
+        self.parse_exact("(release")?;
+        self.parse_whitespace()?;
 
-        let reg = self.assemble_register();
+        let reg = self.assemble_register()?;
         self.parse_optional_whitespace();
-        self.parse_exact(")\n");
+        self.parse_exact(")\n")?;
 
-        function.body.push(FnLine::Release(reg));
+        body.push(FnLine::Release(reg));
 
         continue;
       }
 
       let optional_label = self.test_label();
 
-      function.body.push(match optional_label {
+      body.push(match optional_label {
         Some(label) => FnLine::Label(self.assemble_label(label)),
-        None => FnLine::Instruction(self.assemble_instruction()),
+        None => FnLine::Instruction(self.assemble_instruction()?),
       });
     }
 
-    function
+    Ok(body)
   }
 
-  fn assemble_fn_meta(&mut self) -> Meta {
-    self.parse_exact("meta {");
+  fn assemble_fn_meta(&mut self) -> ParseResult<Meta> {
+    self.parse_exact("meta {")?;
     self.parse_optional_whitespace();
 
-    self.parse_exact("name: ");
+    self.parse_exact("name: ")?;
 
-    let name = self.parse_string_literal();
+    let name = self.parse_string_literal()?;
 
-    self.parse_exact(",");
+    self.parse_exact(",")?;
     self.parse_optional_whitespace();
 
     let content_hashable = 'b: {
@@ -539,317 +569,335 @@ impl<'a> AssemblyParser<'a> {
       }
 
       if self.test_chars("srcHash:") {
-        self.parse_exact("srcHash: ");
+        self.parse_exact("srcHash: ")?;
 
-        let src_hash = self.assemble_hash();
+        let src_hash = self.assemble_hash()?;
 
-        self.parse_exact(",");
+        self.parse_exact(",")?;
         self.parse_optional_whitespace();
 
-        self.parse_exact("deps: ");
-        let deps = self.assemble_array().values;
-        self.parse_exact(",");
+        self.parse_exact("deps: ")?;
+        let deps = self.assemble_array()?.values;
+        self.parse_exact(",")?;
         self.parse_optional_whitespace();
 
         break 'b ContentHashable::Src(src_hash, deps);
       }
 
       if self.test_chars("contentHash:") {
-        self.parse_exact("contentHash: ");
+        self.parse_exact("contentHash: ")?;
 
-        let content_hash = self.assemble_hash();
+        let content_hash = self.assemble_hash()?;
 
-        self.parse_exact(",");
+        self.parse_exact(",")?;
         self.parse_optional_whitespace();
 
         break 'b ContentHashable::Content(content_hash);
       }
 
-      panic!("{}", self.render_pos(-1, "Expected ContentHashable"));
+      return self.error(0, "Expected ContentHashable");
     };
 
-    self.parse_exact("}");
+    self.parse_exact("}")?;
 
-    Meta {
+    Ok(Meta {
       name,
       content_hashable,
-    }
+    })
   }
 
-  fn assemble_class(&mut self) -> Class {
-    self.parse_exact("class {");
+  fn assemble_class(&mut self) -> ParseResult<Class> {
+    self.parse_exact("class {")?;
     self.parse_optional_whitespace();
 
-    self.parse_exact("meta: ");
-    let meta = self.assemble_fn_meta();
-    self.parse_exact(",");
+    self.parse_exact("meta: ")?;
+    let meta = self.assemble_fn_meta()?;
+    self.parse_exact(",")?;
     self.parse_optional_whitespace();
 
-    self.parse_exact("constructor: ");
-    let constructor = self.assemble_value();
-    self.parse_exact(",");
+    let extends = match self.test_chars("extends: ") {
+      true => {
+        self.parse_exact("extends: ")?;
+        let extends = self.assemble_value()?;
+        self.parse_exact(",")?;
+        self.parse_optional_whitespace();
+
+        Some(extends)
+      }
+      false => None,
+    };
+
+    self.parse_exact("constructor: ")?;
+    let constructor = self.assemble_value()?;
+    self.parse_exact(",")?;
     self.parse_optional_whitespace();
 
-    self.parse_exact("prototype: ");
-    let prototype = self.assemble_value();
-    self.parse_exact(",");
+    self.parse_exact("prototype: ")?;
+    let prototype = self.assemble_value()?;
+    self.parse_exact(",")?;
     self.parse_optional_whitespace();
 
-    self.parse_exact("static: ");
-    let static_ = self.assemble_value();
-    self.parse_exact(",");
+    self.parse_exact("static: ")?;
+    let static_ = self.assemble_value()?;
+    self.parse_exact(",")?;
     self.parse_optional_whitespace();
 
-    self.parse_exact("}");
+    self.parse_exact("}")?;
 
-    Class {
+    Ok(Class {
       meta,
+      extends,
       constructor,
       prototype,
       static_,
-    }
+    })
   }
 
-  fn assemble_instruction(&mut self) -> Instruction {
+  fn assemble_instruction(&mut self) -> ParseResult<Instruction> {
     use InstructionByte::*;
 
-    let instr = self.parse_instruction_word();
+    let instr = self.parse_instruction_word()?;
 
     let res = match instr {
       End => Instruction::End,
-      Mov => Instruction::Mov(self.assemble_value(), self.assemble_register()),
-      OpInc => Instruction::OpInc(self.assemble_register()),
-      OpDec => Instruction::OpDec(self.assemble_register()),
+      Mov => Instruction::Mov(self.assemble_value()?, self.assemble_register()?),
+      OpInc => Instruction::OpInc(self.assemble_register()?),
+      OpDec => Instruction::OpDec(self.assemble_register()?),
       OpPlus => Instruction::OpPlus(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpMinus => Instruction::OpMinus(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpMul => Instruction::OpMul(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpDiv => Instruction::OpDiv(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpMod => Instruction::OpMod(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpExp => Instruction::OpExp(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpEq => Instruction::OpEq(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpNe => Instruction::OpNe(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpTripleEq => Instruction::OpTripleEq(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpTripleNe => Instruction::OpTripleNe(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpAnd => Instruction::OpAnd(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpOr => Instruction::OpOr(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      OpNot => Instruction::OpNot(self.assemble_value(), self.assemble_register()),
+      OpNot => Instruction::OpNot(self.assemble_value()?, self.assemble_register()?),
       OpLess => Instruction::OpLess(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpLessEq => Instruction::OpLessEq(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpGreater => Instruction::OpGreater(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpGreaterEq => Instruction::OpGreaterEq(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpNullishCoalesce => Instruction::OpNullishCoalesce(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpOptionalChain => Instruction::OpOptionalChain(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpBitAnd => Instruction::OpBitAnd(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpBitOr => Instruction::OpBitOr(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      OpBitNot => Instruction::OpBitNot(self.assemble_value(), self.assemble_register()),
+      OpBitNot => Instruction::OpBitNot(self.assemble_value()?, self.assemble_register()?),
       OpBitXor => Instruction::OpBitXor(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpLeftShift => Instruction::OpLeftShift(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpRightShift => Instruction::OpRightShift(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       OpRightShiftUnsigned => Instruction::OpRightShiftUnsigned(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      TypeOf => Instruction::TypeOf(self.assemble_value(), self.assemble_register()),
+      TypeOf => Instruction::TypeOf(self.assemble_value()?, self.assemble_register()?),
       InstanceOf => Instruction::InstanceOf(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       In => Instruction::In(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       Call => Instruction::Call(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       Apply => Instruction::Apply(
-        self.assemble_value(),
-        self.assemble_register(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_register()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       ConstApply => Instruction::ConstApply(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       Bind => Instruction::Bind(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       Sub => Instruction::Sub(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       SubMov => Instruction::SubMov(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       SubCall => Instruction::SubCall(
-        self.assemble_register(),
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_register()?,
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      Jmp => Instruction::Jmp(self.assemble_label_read()),
-      JmpIf => Instruction::JmpIf(self.assemble_value(), self.assemble_label_read()),
-      JmpIfNot => Instruction::JmpIfNot(self.assemble_value(), self.assemble_label_read()),
-      UnaryPlus => Instruction::UnaryPlus(self.assemble_value(), self.assemble_register()),
-      UnaryMinus => Instruction::UnaryMinus(self.assemble_value(), self.assemble_register()),
+      Jmp => Instruction::Jmp(self.assemble_label_read()?),
+      JmpIf => Instruction::JmpIf(self.assemble_value()?, self.assemble_label_read()?),
+      JmpIfNot => Instruction::JmpIfNot(self.assemble_value()?, self.assemble_label_read()?),
+      UnaryPlus => Instruction::UnaryPlus(self.assemble_value()?, self.assemble_register()?),
+      UnaryMinus => Instruction::UnaryMinus(self.assemble_value()?, self.assemble_register()?),
       New => Instruction::New(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      Throw => Instruction::Throw(self.assemble_value()),
-      Import => Instruction::Import(self.assemble_value(), self.assemble_register()),
-      ImportStar => Instruction::ImportStar(self.assemble_value(), self.assemble_register()),
-      SetCatch => Instruction::SetCatch(self.assemble_label_read(), self.assemble_register()),
+      Throw => Instruction::Throw(self.assemble_value()?),
+      Import => Instruction::Import(self.assemble_value()?, self.assemble_register()?),
+      ImportStar => Instruction::ImportStar(self.assemble_value()?, self.assemble_register()?),
+      SetCatch => Instruction::SetCatch(self.assemble_label_read()?, self.assemble_register()?),
       UnsetCatch => Instruction::UnsetCatch,
       ConstSubCall => Instruction::ConstSubCall(
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
       RequireMutableThis => Instruction::RequireMutableThis,
       ThisSubCall => Instruction::ThisSubCall(
-        self.assemble_register(),
-        self.assemble_value(),
-        self.assemble_value(),
-        self.assemble_register(),
+        self.assemble_register()?,
+        self.assemble_value()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      Next => Instruction::Next(self.assemble_register(), self.assemble_register()),
+      Next => Instruction::Next(self.assemble_register()?, self.assemble_register()?),
       UnpackIterRes => Instruction::UnpackIterRes(
-        self.assemble_register(),
-        self.assemble_register(),
-        self.assemble_register(),
+        self.assemble_register()?,
+        self.assemble_register()?,
+        self.assemble_register()?,
+      ),
+      Cat => Instruction::Cat(self.assemble_value()?, self.assemble_register()?),
+      Yield => Instruction::Yield(self.assemble_value()?, self.assemble_register()?),
+      YieldStar => Instruction::YieldStar(self.assemble_value()?, self.assemble_register()?),
+      Await => Instruction::Await(self.assemble_value()?, self.assemble_register()?),
+      NewTarget => Instruction::NewTarget(self.assemble_register()?),
+      Delete => Instruction::Delete(
+        self.assemble_register()?,
+        self.assemble_value()?,
+        self.assemble_register()?,
       ),
-      Cat => Instruction::Cat(self.assemble_value(), self.assemble_register()),
-      Yield => Instruction::Yield(self.assemble_value(), self.assemble_register()),
-      YieldStar => Instruction::YieldStar(self.assemble_value(), self.assemble_register()),
     };
 
     self.parse_line();
 
-    res
+    Ok(res)
   }
 
-  pub fn assemble_value(&mut self) -> Value {
+  pub fn assemble_value(&mut self) -> ParseResult<Value> {
     self.parse_optional_whitespace();
 
-    match self.pos.peek() {
-      None => {
-        panic!("{}", self.render_pos(0, "Expected value"));
-      }
-      Some('%') => Value::Register(self.assemble_register()),
-      Some('@') => Value::Pointer(self.assemble_pointer()),
-      Some('$') => Value::Builtin(self.assemble_builtin()),
-      Some('[') => Value::Array(Box::new(self.assemble_array())),
-      Some('-' | '.' | '0'..='9') => self.assemble_number(),
-      Some('"') => Value::String(self.parse_string_literal()),
-      Some('{') => Value::Object(Box::new(self.assemble_object())),
-      Some('c') => Value::Class(Box::new(self.assemble_class())),
+    Ok(match self.pos.peek() {
+      None => return self.error(0, "Expected value"),
+      Some('%') => Value::Register(self.assemble_register()?),
+      Some('@') => Value::Pointer(self.assemble_pointer()?),
+      Some('$') => Value::Builtin(self.assemble_builtin()?),
+      Some('[') => Value::Array(Box::new(self.assemble_array()?)),
+      Some('-' | '.' | '0'..='9') => self.assemble_number()?,
+      Some('"') => Value::String(self.parse_string_literal()?),
+      Some('{') => Value::Object(Box::new(self.assemble_object()?)),
+      Some('c') => Value::Class(Box::new(self.assemble_class()?)),
       Some(ref_c) => {
         let c = *ref_c;
 
@@ -862,7 +910,7 @@ impl<'a> AssemblyParser<'a> {
           "Infinity",
           "NaN",
           "",
-        ]);
+        ])?;
 
         match parsed.as_str() {
           "void" => Value::Void,
@@ -875,50 +923,45 @@ impl<'a> AssemblyParser<'a> {
 
           // TODO: Finish implementing the different values
           _ => {
-            panic!(
-              "{}",
-              self.render_pos(
-                -(parsed.len() as isize),
-                &format!("Unimplemented value type or unexpected character {}", c)
-              )
+            return self.error(
+              parsed.chars().count(),
+              &format!("Unimplemented value type or unexpected character {}", c),
             );
           }
         }
       }
-    }
+    })
   }
 
-  fn assemble_pointer(&mut self) -> Pointer {
-    self.parse_exact("@");
-    let name = self.parse_identifier();
-    Pointer { name }
+  fn assemble_pointer(&mut self) -> ParseResult<Pointer> {
+    self.parse_exact("@")?;
+    let name = self.parse_identifier()?;
+    Ok(Pointer { name })
   }
 
-  fn assemble_array(&mut self) -> Array {
+  fn assemble_array(&mut self) -> ParseResult<Array> {
     let mut array = Array::default();
 
     self.parse_optional_whitespace();
 
-    self.parse_exact("[");
+    self.parse_exact("[")?;
 
     loop {
       self.parse_optional_whitespace();
 
       match self.pos.peek() {
-        None => {
-          panic!("{}", self.render_pos(0, "Expected value or array end"));
-        }
+        None => return self.error(0, "Expected value or array end"),
         Some(']') => {
           self.pos.next();
-          break array;
+          break Ok(array);
         }
         _ => {}
       }
 
-      array.values.push(self.assemble_value());
+      array.values.push(self.assemble_value()?);
       self.parse_optional_whitespace();
 
-      let next = self.parse_one_of(&[",", "]"]);
+      let next = self.parse_one_of(&[",", "]"])?;
 
       if next == "," {
         self.parse_optional_whitespace();
@@ -926,32 +969,41 @@ impl<'a> AssemblyParser<'a> {
       }
 
       if next == "]" {
-        break array;
+        break Ok(array);
       }
 
       panic!("Expected this to be impossible");
     }
   }
 
-  fn assemble_register(&mut self) -> Register {
+  fn assemble_register(&mut self) -> ParseResult<Register> {
     self.parse_optional_whitespace();
-    self.parse_exact("%");
-    let take = self.parse_one_of(&["!", ""]) == "!";
-    let name = self.parse_identifier();
 
-    Register { take, name }
+    if !self.test_chars("%") {
+      return self.error(0, "Expected register");
+    }
+
+    self.parse_exact("%")?;
+    let take = self.parse_one_of(&["!", ""])? == "!";
+
+    let name = match self.test_identifier() {
+      Some(_) => self.parse_identifier()?,
+      None => return self.error(0, "Invalid register name"),
+    };
+
+    Ok(Register { take, name })
   }
 
-  fn assemble_builtin(&mut self) -> Builtin {
-    self.parse_exact("$");
+  fn assemble_builtin(&mut self) -> ParseResult<Builtin> {
+    self.parse_exact("$")?;
 
-    let name = self.parse_identifier();
+    let name = self.parse_identifier()?;
 
     if !BUILTIN_NAMES.contains(&name.as_str()) {
-      panic!("Unrecognized builtin ${}", name);
+      return self.error(name.len(), &format!("Unrecognized builtin ${}", name));
     }
 
-    Builtin { name }
+    Ok(Builtin { name })
   }
 
   fn test_label(&self) -> Option<String> {
@@ -972,178 +1024,135 @@ impl<'a> AssemblyParser<'a> {
     Label { name }
   }
 
-  fn assemble_label_read(&mut self) -> LabelRef {
+  fn assemble_label_read(&mut self) -> ParseResult<LabelRef> {
     self.parse_optional_whitespace();
-    self.parse_exact(":");
-    let name = self.parse_identifier();
+    self.parse_exact(":")?;
+    let name = self.parse_identifier()?;
 
-    LabelRef { name }
+    Ok(LabelRef { name })
   }
 
-  fn assemble_number(&mut self) -> Value {
-    if self.parse_one_of(&["-Infinity", ""]) == "-Infinity" {
-      return Value::Number(Number(f64::NEG_INFINITY));
+  fn assemble_number(&mut self) -> ParseResult<Value> {
+    if self.parse_one_of(&["-Infinity", ""])? == "-Infinity" {
+      return Ok(Value::Number(Number(f64::NEG_INFINITY)));
     }
 
     let mut num_string = "".to_string();
 
-    while let Some('-' | '.' | 'e' | 'n' | '0'..='9') = self.pos.peek() {
+    while let Some('-' | '+' | '.' | 'e' | 'n' | '0'..='9') = self.pos.peek() {
       num_string.push(self.pos.next().unwrap());
     }
 
     if num_string.ends_with('n') {
       num_string.pop();
 
-      match BigInt::parse_bytes(num_string.as_bytes(), 10) {
-        Some(bigint) => return Value::BigInt(bigint),
-        None => {
-          panic!(
-            "{}",
-            self.render_pos(-(num_string.len() as isize + 1), "Expected valid number")
-          );
-        }
-      }
+      return match BigInt::parse_bytes(num_string.as_bytes(), 10) {
+        Some(bigint) => Ok(Value::BigInt(bigint)),
+        None => self.error(num_string.len() + 1, "Expected valid number"),
+      };
     }
 
-    let value_result = f64::from_str(num_string.as_str());
-
-    if value_result.is_err() {
-      panic!(
-        "{}",
-        self.render_pos(-(num_string.len() as isize), "Expected valid number")
-      );
+    match f64::from_str(num_string.as_str()) {
+      Ok(value) => Ok(Value::Number(Number(value))),
+      Err(_) => self.error(num_string.len(), "Expected valid number"),
     }
-
-    Value::Number(Number(value_result.unwrap()))
   }
 
-  fn assemble_object(&mut self) -> Object {
+  fn assemble_object(&mut self) -> ParseResult<Object> {
     let mut object = Object::default();
 
-    self.parse_exact("{");
+    self.parse_exact("{")?;
 
     loop {
-      match self.assemble_object_kv() {
-        None => break object,
+      match self.assemble_object_kv()? {
+        None => break Ok(object),
         Some(kv) => object.properties.push(kv),
       };
 
       self.parse_optional_whitespace();
 
-      let c = *self.pos.peek().expect("Expected comma or object end");
-
-      match c {
-        ',' => {
-          self.pos.next();
-        }
-        '}' => {
-          self.pos.next();
-          break object;
-        }
-        _ => {
-          panic!(
-            "{}",
-            self.render_pos(0, &format!("Unexpected character {}", c))
-          );
-        }
+      if self.parse_object_separator()? {
+        break Ok(object);
       }
     }
   }
 
-  fn assemble_object_kv(&mut self) -> Option<(Value, Value)> {
-    self.parse_optional_whitespace();
-    let c = *self.pos.peek().expect("Expected object content or end");
+  /// Parses the `,` between object properties or the `}` that ends the object, returning whether
+  /// the object ended.
+  fn parse_object_separator(&mut self) -> ParseResult<bool> {
+    match self.pos.next() {
+      Some(',') => Ok(false),
+      Some('}') => Ok(true),
+      Some(c) => self.error(1, &format!("Unexpected character {}", c)),
+      None => self.error(0, "Expected comma or object end"),
+    }
+  }
 
-    let key = match c {
-      '}' => {
+  fn assemble_object_kv(&mut self) -> ParseResult<Option<(Value, Value)>> {
+    self.parse_optional_whitespace();
+    let key = match self.pos.peek() {
+      None => return self.error(0, "Expected object content or end"),
+      Some('}') => {
         self.pos.next();
-        return None;
+        return Ok(None);
       }
-      _ => self.assemble_value(),
+      _ => self.assemble_value()?,
     };
 
     self.parse_optional_whitespace();
-    self.parse_exact(":");
-    let value = self.assemble_value();
+    self.parse_exact(":")?;
+    let value = self.assemble_value()?;
 
-    Some((key, value))
+    Ok(Some((key, value)))
   }
 
-  fn assemble_export_star(&mut self) -> ExportStar {
+  fn assemble_export_star(&mut self) -> ParseResult<ExportStar> {
     let mut export_star = ExportStar::default();
 
-    self.parse_exact("{");
+    self.parse_exact("{")?;
 
     loop {
       self.parse_optional_whitespace();
 
-      if self.parse_one_of(&["include ", ""]) == "" {
+      if self.parse_one_of(&["include ", ""])?.is_empty() {
         break;
       }
 
-      export_star.includes.push(self.assemble_pointer());
+      export_star.includes.push(self.assemble_pointer()?);
       self.parse_optional_whitespace();
 
-      let c = *self.pos.peek().expect("Expected comma or object end");
-
-      match c {
-        ',' => {
-          self.pos.next();
-        }
-        '}' => {
-          self.pos.next();
-          return export_star;
-        }
-        _ => {
-          panic!(
-            "{}",
-            self.render_pos(0, &format!("Unexpected character {}", c))
-          );
-        }
+      if self.parse_object_separator()? {
+        return Ok(export_star);
       }
     }
 
     loop {
-      match self.assemble_object_kv() {
-        None => break export_star,
+      match self.assemble_object_kv()? {
+        None => break Ok(export_star),
         Some(kv) => export_star.local.properties.push(kv),
       };
 
       self.parse_optional_whitespace();
 
-      let c = *self.pos.peek().expect("Expected comma or object end");
-
-      match c {
-        ',' => {
-          self.pos.next();
-        }
-        '}' => {
-          self.pos.next();
-          break export_star;
-        }
-        _ => {
-          panic!(
-            "{}",
-            self.render_pos(0, &format!("Unexpected character {}", c))
-          );
-        }
+      if self.parse_object_separator()? {
+        break Ok(export_star);
       }
     }
   }
 
-  fn assemble_hash(&mut self) -> Hash {
-    self.parse_exact("#");
+  fn assemble_hash(&mut self) -> ParseResult<Hash> {
+    self.parse_exact("#")?;
 
     let mut res = [0u8; 32];
 
     for res_byte in &mut res {
       *res_byte = match self.assemble_hex_byte() {
         Some(b) => b,
-        None => panic!("{}", self.render_pos(0, "Expected hex byte")),
+        None => return self.error(0, "Expected hex byte"),
       }
     }
 
-    Hash(res)
+    Ok(Hash(res))
   }
 
   fn assemble_hex_byte(&mut self) -> Option<u8> {
@@ -1154,7 +1163,9 @@ impl<'a> AssemblyParser<'a> {
   }
 }
 
-pub fn parse_module(content: &str) -> Module {
+/// Parses a module written in assembly. Errors are reported as a diagnostic whose span is the
+/// byte offset of the problem in `content`.
+pub fn parse_module(content: &str) -> Result<Module, Diagnostic> {
   let mut assembler = AssemblyParser {
     content,
     pos: content.chars().peekable(),
@@ -1176,14 +1187,3 @@ fn advance_chars(iter: &mut std::iter::Peekable<std::str::Chars>, len: usize) {
     iter.next();
   }
 }
-
-struct LineCol {
-  line: usize,
-  col: usize,
-}
-
-impl std::fmt::Display for LineCol {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "line {} col {}", self.line, self.col)
-  }
-}
@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::asm::{
+  Definition, DefinitionContent, ExportStar, FnLine, Instruction, Lazy, Module, Object, Pointer,
+  Register, Value,
+};
+use crate::compile::CompileOptions;
+use crate::gather_modules::{gather_modules_from_entry_points, PathAndModule};
+use crate::import_pattern::{ImportKind, ImportPattern};
+use crate::minify_names::{minify_names, NameMap};
+use crate::name_allocator::{ident_from_str, PointerAllocator};
+use crate::resolve_path::{resolve_path, ResolvedPath};
+use crate::{link_module, Diagnostic, DiagnosticLevel};
+
+pub struct BundleResult {
+  pub module: Option<Module>,
+  pub diagnostics: HashMap<ResolvedPath, Vec<Diagnostic>>,
+  pub manifest: BundleManifest,
+
+  /// The original names, when names are minified
+  pub name_map: Option<NameMap>,
+}
+
+/// Describes the exports of a bundle.
+///
+/// With a single entry point, the bundle exports the same things as the entry point. Otherwise,
+/// the default export of the bundle is an object with the default export of each entry point (by
+/// name), and the named exports of each entry point are grouped under its name.
+#[derive(Default, Debug, Clone)]
+pub struct BundleManifest {
+  pub entries: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleEntry {
+  /// The file stem of the entry point
+  pub name: String,
+  pub path: ResolvedPath,
+  pub has_default: bool,
+  pub exports: Vec<String>,
+}
+
+impl BundleManifest {
+  pub fn to_json(&self) -> String {
+    let entries = self
+      .entries
+      .iter()
+      .map(|entry| {
+        format!(
+          "{{\"name\":{},\"path\":{},\"default\":{},\"exports\":[{}]}}",
+          json_string(&entry.name),
+          json_string(&entry.path.path),
+          entry.has_default,
+          entry
+            .exports
+            .iter()
+            .map(|export| json_string(export))
+            .collect::<Vec<_>>()
+            .join(","),
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+
+    format!("{{\"entries\":[{}]}}", entries)
+  }
+}
+
+fn json_string(str: &str) -> String {
+  serde_json::to_string(str).expect("Failed to encode string")
+}
+
+/// Compiles the module graph of all the entry points into a single module, which only includes what
+/// is reachable from the exports of the entry points.
+pub fn bundle<ReadFile>(
+  entry_points: &[ResolvedPath],
+  read_file: ReadFile,
+  options: &CompileOptions,
+) -> BundleResult
+where
+  ReadFile: Fn(&str) -> Result<String, String>,
+{
+  let mut gm = gather_modules_from_entry_points(entry_points, read_file, &options.path_aliases);
+
+  let mut result = BundleResult {
+    module: None,
+    diagnostics: HashMap::new(),
+    manifest: BundleManifest::default(),
+    name_map: None,
+  };
+
+  let mut names = HashSet::<String>::new();
+
+  for entry_point in entry_points {
+    let name = Path::new(&entry_point.path)
+      .file_stem()
+      .and_then(|stem| stem.to_str())
+      .unwrap_or("")
+      .to_string();
+
+    if !names.insert(name.clone()) {
+      gm.diagnostics
+        .entry(entry_point.clone())
+        .or_default()
+        .push(Diagnostic {
+          level: DiagnosticLevel::Error,
+          message: format!("Another entry point is also named `{}`", name),
+          span: swc_common::DUMMY_SP,
+        });
+    }
+
+    let (has_default, exports) = match gm.modules.get(entry_point) {
+      Some(path_and_module) => (
+        path_and_module.module.export_default != Value::Void,
+        export_names(path_and_module, &gm.modules),
+      ),
+      None => (false, vec![]),
+    };
+
+    result.manifest.entries.push(BundleEntry {
+      name,
+      path: entry_point.clone(),
+      has_default,
+      exports,
+    });
+  }
+
+  let bundle_path = match entry_points {
+    [entry_point] => entry_point.clone(),
+    _ => {
+      // Import paths are resolved relative to the parent of this path, which is empty, so the
+      // entry points are imported by their own paths
+      let bundle_path = ResolvedPath::from("(bundle)".to_string());
+
+      gm.modules.insert(
+        bundle_path.clone(),
+        PathAndModule {
+          path: bundle_path.clone(),
+          module: entries_module(&result.manifest),
+        },
+      );
+
+      bundle_path
+    }
+  };
+
+  let mut link_module_result = link_module(&bundle_path, &gm.modules);
+
+  result.diagnostics = gm.diagnostics;
+
+  result
+    .diagnostics
+    .entry(entry_points.first().cloned().unwrap_or(bundle_path))
+    .or_default()
+    .append(&mut link_module_result.diagnostics);
+
+  result.module = link_module_result.module;
+
+  if options.minify_names {
+    if let Some(module) = &mut result.module {
+      result.name_map = Some(minify_names(module));
+    }
+  }
+
+  result
+}
+
+/// A module that imports each entry point, used as the entry point of a bundle with several.
+fn entries_module(manifest: &BundleManifest) -> Module {
+  let mut module = Module::default();
+  let mut pointer_allocator = PointerAllocator::default();
+
+  let mut default_exports = Object::default();
+  let mut named_exports = Object::default();
+
+  for entry in &manifest.entries {
+    let base_name = ident_from_str(&entry.name);
+
+    let import_default = pointer_allocator.allocate(&format!("{}_default", base_name));
+    let import_star = pointer_allocator.allocate(&base_name);
+
+    module.definitions.push(import_definition(
+      import_default.clone(),
+      Instruction::Import(Value::String(entry.path.path.clone()), Register::return_()),
+    ));
+
+    module.definitions.push(import_definition(
+      import_star.clone(),
+      Instruction::ImportStar(Value::String(entry.path.path.clone()), Register::return_()),
+    ));
+
+    default_exports.properties.push((
+      Value::String(entry.name.clone()),
+      Value::Pointer(import_default),
+    ));
+
+    named_exports.properties.push((
+      Value::String(entry.name.clone()),
+      Value::Pointer(import_star),
+    ));
+  }
+
+  module.export_default = Value::Object(Box::new(default_exports));
+
+  module.export_star = ExportStar {
+    includes: vec![],
+    local: named_exports,
+  };
+
+  module
+}
+
+fn import_definition(pointer: Pointer, instruction: Instruction) -> Definition {
+  Definition {
+    pointer,
+    content: DefinitionContent::Lazy(Lazy {
+      body: vec![FnLine::Instruction(instruction)],
+    }),
+  }
+}
+
+/// The named exports of a module, including those from `export * from`.
+fn export_names(
+  path_and_module: &PathAndModule,
+  modules: &HashMap<ResolvedPath, PathAndModule>,
+) -> Vec<String> {
+  let mut names = Vec::<String>::new();
+  let mut modules_to_process = vec![path_and_module];
+  let mut processed = HashSet::<ResolvedPath>::new();
+
+  while let Some(path_and_module) = modules_to_process.pop() {
+    if !processed.insert(path_and_module.path.clone()) {
+      continue;
+    }
+
+    let export_star = &path_and_module.module.export_star;
+
+    for (key, _value) in &export_star.local.properties {
+      if let Value::String(name) = key {
+        if !names.contains(name) {
+          names.push(name.clone());
+        }
+      }
+    }
+
+    for include in &export_star.includes {
+      let import_pattern = path_and_module
+        .module
+        .definitions
+        .iter()
+        .find(|definition| &definition.pointer == include)
+        .and_then(ImportPattern::decode);
+
+      if let Some(import_pattern) = import_pattern {
+        if import_pattern.kind == ImportKind::Star {
+          let path = resolve_path(&path_and_module.path, &import_pattern.path);
+
+          if let Some(included) = modules.get(&path) {
+            modules_to_process.push(included);
+          }
+        }
+      }
+    }
+  }
+
+  names
+}
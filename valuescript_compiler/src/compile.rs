@@ -1,22 +1,51 @@
 use std::collections::HashMap;
 
-use crate::{asm::Module, gather_modules, link_module, Diagnostic, ResolvedPath};
+use crate::gather_modules::gather_modules_from_entry_points;
+use crate::minify_names::{minify_names, NameMap};
+use crate::resolve_path::PathAlias;
+use crate::{asm::Module, link_module, Diagnostic, ResolvedPath};
 
 pub struct CompileResult {
   pub module: Option<Module>,
   pub diagnostics: HashMap<ResolvedPath, Vec<Diagnostic>>,
+
+  /// The original names, when names are minified
+  pub name_map: Option<NameMap>,
+}
+
+#[derive(Default, Clone)]
+pub struct CompileOptions {
+  /// Rename definitions and registers to short machine names (see `minify_names`)
+  pub minify_names: bool,
+
+  /// Imports matching these are resolved to the alias target instead of relative to the importer
+  pub path_aliases: Vec<PathAlias>,
 }
 
 pub fn compile<ReadFile>(entry_point: ResolvedPath, read_file: ReadFile) -> CompileResult
 where
   ReadFile: Fn(&str) -> Result<String, String>,
 {
-  let gm = gather_modules(entry_point.clone(), read_file);
+  compile_with_options(entry_point, read_file, &CompileOptions::default())
+}
+
+pub fn compile_with_options<ReadFile>(
+  entry_point: ResolvedPath,
+  read_file: ReadFile,
+  options: &CompileOptions,
+) -> CompileResult
+where
+  ReadFile: Fn(&str) -> Result<String, String>,
+{
+  let gm =
+    gather_modules_from_entry_points(&[entry_point.clone()], read_file, &options.path_aliases);
+
   let mut link_module_result = link_module(&gm.entry_point, &gm.modules);
 
   let mut result = CompileResult {
     module: link_module_result.module,
     diagnostics: gm.diagnostics,
+    name_map: None,
   };
 
   result
@@ -25,5 +54,12 @@ where
     .or_default()
     .append(&mut link_module_result.diagnostics);
 
+  // This happens after linking so that the names are short across the whole program
+  if options.minify_names {
+    if let Some(module) = &mut result.module {
+      result.name_map = Some(minify_names(module));
+    }
+  }
+
   result
 }
@@ -0,0 +1,74 @@
+use swc_common::BytePos;
+
+use crate::diagnostic::remove_ignored_diagnostics;
+use crate::module_compiler::{compile_program, parse, CompilerOutput};
+
+/// Compiles an expression or a list of statements (eg a line entered into a REPL) that can refer
+/// to the existing bindings named in `environment`.
+///
+/// The default export of the resulting module is a function that takes the values of the bindings
+/// as parameters (in the same order) and returns the value of the snippet, which is the value of
+/// its last statement if that's an expression statement. Diagnostic spans refer to `source`.
+pub fn compile_expr(source: &str, environment: &[String]) -> CompilerOutput {
+  let prefix = format!("export default function ({}) {{\n", environment.join(", "));
+  let wrapped_source = format!("{}{}\n}}\n", prefix, source);
+
+  let (program_optional, mut diagnostics) = parse(&wrapped_source);
+  remove_ignored_diagnostics(&wrapped_source, &mut diagnostics);
+
+  let mut compiler_output = match program_optional {
+    Some(mut program) => {
+      return_last_expression(&mut program);
+      compile_program(&wrapped_source, &program)
+    }
+    None => CompilerOutput::default(),
+  };
+
+  diagnostics.append(&mut compiler_output.diagnostics);
+
+  let offset = prefix.len() as u32;
+
+  for diagnostic in &mut diagnostics {
+    diagnostic.span.lo = BytePos(diagnostic.span.lo.0.saturating_sub(offset));
+    diagnostic.span.hi = BytePos(diagnostic.span.hi.0.saturating_sub(offset));
+  }
+
+  compiler_output.diagnostics = diagnostics;
+
+  compiler_output
+}
+
+/// Replaces `expr;` with `return expr;` when it's the last statement of the wrapper function.
+fn return_last_expression(program: &mut swc_ecma_ast::Program) {
+  use swc_ecma_ast::{DefaultDecl, ModuleDecl, ModuleItem, Program, ReturnStmt, Stmt};
+
+  let module = match program {
+    Program::Module(module) => module,
+    Program::Script(_) => return,
+  };
+
+  let fn_ = match module.body.first_mut() {
+    Some(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export))) => match &mut export.decl {
+      DefaultDecl::Fn(fn_) => fn_,
+      _ => return,
+    },
+    _ => return,
+  };
+
+  let last_stmt = match fn_
+    .function
+    .body
+    .as_mut()
+    .and_then(|body| body.stmts.last_mut())
+  {
+    Some(last_stmt) => last_stmt,
+    None => return,
+  };
+
+  if let Stmt::Expr(expr_stmt) = last_stmt {
+    *last_stmt = Stmt::Return(ReturnStmt {
+      span: expr_stmt.span,
+      arg: Some(expr_stmt.expr.clone()),
+    });
+  }
+}
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, fmt};
+use std::{any::Any, cell::RefCell, collections::HashMap, fmt};
 
 #[derive(serde::Serialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum DiagnosticLevel {
@@ -52,6 +52,24 @@ impl Diagnostic {
     })
   }
 
+  /// Converts a panic caught by `catch_unwind` into an internal error, so that bugs in the
+  /// compiler are reported like other diagnostics instead of crashing the caller.
+  pub fn from_panic(span: swc_common::Span, payload: &(dyn Any + Send)) -> Self {
+    let panic_message = match payload.downcast_ref::<&str>() {
+      Some(message) => message.to_string(),
+      None => match payload.downcast_ref::<String>() {
+        Some(message) => message.clone(),
+        None => "(unknown panic)".to_string(),
+      },
+    };
+
+    Diagnostic {
+      level: DiagnosticLevel::InternalError,
+      message: format!("Compiler panicked: {}", panic_message),
+      span,
+    }
+  }
+
   pub fn todo(span: swc_common::Span, message: &str) -> Self {
     Diagnostic {
       level: DiagnosticLevel::InternalError,
@@ -91,6 +109,72 @@ impl Diagnostic {
       span,
     }
   }
+
+  /// The code used to suppress this diagnostic with a `// vs-ignore <code>` comment. Only lints,
+  /// TODOs and unsupported features can be suppressed.
+  pub fn code(&self) -> Option<&'static str> {
+    match self.level {
+      DiagnosticLevel::Lint => Some("lint"),
+      _ if self.message.starts_with("TODO: ") => Some("todo"),
+      _ if self.message.starts_with("Not supported: ") => Some("not-supported"),
+      _ => None,
+    }
+  }
+}
+
+/// Removes the diagnostics that are suppressed by `// vs-ignore <code>...` comments in the source.
+/// A comment on its own line applies to the next line, otherwise it applies to its own line.
+/// Without any codes, the comment suppresses every diagnostic that has a code.
+pub fn remove_ignored_diagnostics(source: &str, diagnostics: &mut Vec<Diagnostic>) {
+  let mut line_starts = vec![0];
+  let mut ignores = HashMap::<usize, Vec<Vec<&str>>>::new();
+
+  for (line, text) in source.split('\n').enumerate() {
+    line_starts.push(line_starts[line] + text.len() + 1);
+
+    let (code_part, comment) = match text.split_once("// vs-ignore") {
+      Some(parts) => parts,
+      None => continue,
+    };
+
+    if comment.starts_with(|c: char| !c.is_whitespace()) {
+      continue;
+    }
+
+    let target_line = match code_part.trim().is_empty() {
+      true => line + 1,
+      false => line,
+    };
+
+    let codes = comment
+      .split(|c: char| c.is_whitespace() || c == ',')
+      .filter(|c| !c.is_empty())
+      .collect();
+
+    ignores.entry(target_line).or_default().push(codes);
+  }
+
+  if ignores.is_empty() {
+    return;
+  }
+
+  diagnostics.retain(|diagnostic| {
+    let code = match diagnostic.code() {
+      Some(code) => code,
+      None => return true,
+    };
+
+    let pos = diagnostic.span.lo.0 as usize;
+    let line = line_starts.partition_point(|start| *start <= pos) - 1;
+
+    let ignored = ignores.get(&line).is_some_and(|comments| {
+      comments
+        .iter()
+        .any(|codes| codes.is_empty() || codes.contains(&code))
+    });
+
+    !ignored
+  });
 }
 
 pub trait DiagnosticContainer {
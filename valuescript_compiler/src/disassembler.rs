@@ -0,0 +1,636 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use num_bigint::{BigInt, Sign};
+
+use valuescript_common::{InstructionByte, BUILTIN_NAMES};
+
+use crate::asm::{
+  Array, Builtin, Class, ContentHashable, Definition, DefinitionContent, ExportStar, FnLine,
+  Function, Hash, Instruction, Label, LabelRef, Lazy, Meta, Module, Number, Object, Pointer,
+  Register, Value,
+};
+use crate::assembler::ValueType;
+use crate::name_allocator::{ident_from_str, NameAllocator};
+use crate::visit_pointers::{visit_pointers, PointerVisitation};
+
+/// Converts bytecode back into a module (the inverse of `assemble`).
+///
+/// Bytecode doesn't include the names of registers, labels and definitions, so these are
+/// generated. Definitions are named after the functions and classes they contain where possible.
+pub fn disassemble(bytecode: &[u8]) -> Result<Module, String> {
+  let mut disassembler = Disassembler {
+    bytecode,
+    pos: 0,
+    constants: vec![],
+    label_targets: BTreeSet::new(),
+  };
+
+  disassembler.module()
+}
+
+struct Disassembler<'a> {
+  bytecode: &'a [u8],
+  pos: usize,
+  constants: Vec<Value>,
+
+  /// The positions that jumps in the current function refer to
+  label_targets: BTreeSet<usize>,
+}
+
+impl<'a> Disassembler<'a> {
+  fn module(&mut self) -> Result<Module, String> {
+    if self.peek_byte()? == ValueType::Compressed as u8 {
+      return Err("Compressed bytecode needs to be decompressed first".into());
+    }
+
+    let mut end = self.bytecode.len();
+
+    if self.peek_byte()? == ValueType::ConstantPool as u8 {
+      self.pos += 1;
+      end = self.location()?;
+
+      let start = self.pos;
+      self.pos = end;
+      self.constant_pool()?;
+      self.pos = start;
+    }
+
+    let export_default = self.value()?;
+
+    self.expect_byte(ValueType::ExportStar as u8, "export star")?;
+    let include_count = self.varsize_uint()?;
+    let mut includes = Vec::<Pointer>::new();
+
+    for _ in 0..include_count {
+      match self.value()? {
+        Value::Pointer(pointer) => includes.push(pointer),
+        _ => return Err(self.error("Expected pointer in export star")),
+      }
+    }
+
+    let local = match self.value()? {
+      Value::Object(object) => *object,
+      _ => return Err(self.error("Expected object in export star")),
+    };
+
+    let mut module = Module {
+      export_default,
+      export_star: ExportStar { includes, local },
+      definitions: vec![],
+    };
+
+    while self.pos < end {
+      let pointer = position_pointer(self.pos);
+      let content = self.definition_content()?;
+
+      module.definitions.push(Definition { pointer, content });
+    }
+
+    name_definitions(&mut module)?;
+
+    Ok(module)
+  }
+
+  fn constant_pool(&mut self) -> Result<(), String> {
+    let count = self.varsize_uint()?;
+
+    for _ in 0..count {
+      let constant = match self.byte()? {
+        b if b == ValueType::String as u8 => Value::String(self.inline_string()?),
+        b if b == ValueType::Number as u8 => Value::Number(Number(self.f64()?)),
+        _ => return Err(self.error("Invalid constant")),
+      };
+
+      self.constants.push(constant);
+    }
+
+    Ok(())
+  }
+
+  fn definition_content(&mut self) -> Result<DefinitionContent, String> {
+    let type_ = self.peek_byte()?;
+
+    Ok(match type_ {
+      b if b == ValueType::Function as u8
+        || b == ValueType::GeneratorFunction as u8
+        || b == ValueType::AsyncFunction as u8
+        || b == ValueType::AsyncGeneratorFunction as u8 =>
+      {
+        self.pos += 1;
+
+        DefinitionContent::Function(self.function(
+          b == ValueType::GeneratorFunction as u8 || b == ValueType::AsyncGeneratorFunction as u8,
+          b == ValueType::AsyncFunction as u8 || b == ValueType::AsyncGeneratorFunction as u8,
+        )?)
+      }
+      b if b == ValueType::Meta as u8 => DefinitionContent::Meta(self.meta()?),
+      b if b == ValueType::Lazy as u8 => {
+        self.pos += 1;
+        let _register_count = self.byte()?;
+
+        DefinitionContent::Lazy(Lazy { body: self.body()? })
+      }
+      _ => DefinitionContent::Value(self.value()?),
+    })
+  }
+
+  fn function(&mut self, is_generator: bool, is_async: bool) -> Result<Function, String> {
+    let meta = match self.byte()? {
+      0 => None,
+      _ => match self.value()? {
+        Value::Pointer(pointer) => Some(pointer),
+        _ => return Err(self.error("Expected pointer for function meta")),
+      },
+    };
+
+    let _register_count = self.byte()?;
+    let parameter_count_byte = self.byte()?;
+    let parameter_count = parameter_count_byte & 0x7f;
+
+    // Parameters occupy the registers after return and this
+    let parameters = (0..parameter_count)
+      .map(|i| register_from_index(i + 2))
+      .collect();
+
+    Ok(Function {
+      is_generator,
+      is_async,
+      meta,
+      parameters,
+      has_rest_param: parameter_count_byte & 0x80 != 0,
+      body: self.body()?,
+    })
+  }
+
+  fn body(&mut self) -> Result<Vec<FnLine>, String> {
+    self.label_targets.clear();
+
+    // The final End isn't part of the body, so it's stored as None. Labels at the end of the body
+    // still refer to it though.
+    let mut instructions = Vec::<(usize, Option<Instruction>)>::new();
+
+    loop {
+      let instruction_pos = self.pos;
+      let instruction = self.instruction()?;
+
+      if let Instruction::End = instruction {
+        // An explicit end instruction can be followed by more of the body, which we can only
+        // detect by finding jumps that go past it
+        if self.label_targets.range(self.pos..).next().is_none() {
+          instructions.push((instruction_pos, None));
+          break;
+        }
+      }
+
+      instructions.push((instruction_pos, Some(instruction)));
+    }
+
+    let mut body = Vec::<FnLine>::new();
+
+    for (instruction_pos, instruction) in instructions {
+      if self.label_targets.remove(&instruction_pos) {
+        body.push(FnLine::Label(Label {
+          name: label_name(instruction_pos),
+        }));
+      }
+
+      if let Some(instruction) = instruction {
+        body.push(FnLine::Instruction(instruction));
+      }
+    }
+
+    if let Some(target) = self.label_targets.iter().next() {
+      return Err(format!("Jump to {} is not an instruction boundary", target));
+    }
+
+    Ok(body)
+  }
+
+  fn instruction(&mut self) -> Result<Instruction, String> {
+    use Instruction::*;
+
+    let byte = self.byte()?;
+
+    if byte > InstructionByte::Await as u8 {
+      return Err(self.error(&format!("Unrecognized instruction: {}", byte)));
+    }
+
+    Ok(match InstructionByte::from_byte(byte) {
+      InstructionByte::End => End,
+      InstructionByte::Mov => Mov(self.value()?, self.register()?),
+      InstructionByte::OpInc => OpInc(self.register()?),
+      InstructionByte::OpDec => OpDec(self.register()?),
+      InstructionByte::OpPlus => OpPlus(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpMinus => OpMinus(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpMul => OpMul(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpDiv => OpDiv(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpMod => OpMod(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpExp => OpExp(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpEq => OpEq(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpNe => OpNe(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpTripleEq => OpTripleEq(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpTripleNe => OpTripleNe(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpAnd => OpAnd(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpOr => OpOr(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpNot => OpNot(self.value()?, self.register()?),
+      InstructionByte::OpLess => OpLess(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpLessEq => OpLessEq(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpGreater => OpGreater(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpGreaterEq => OpGreaterEq(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpNullishCoalesce => {
+        OpNullishCoalesce(self.value()?, self.value()?, self.register()?)
+      }
+      InstructionByte::OpOptionalChain => {
+        OpOptionalChain(self.value()?, self.value()?, self.register()?)
+      }
+      InstructionByte::OpBitAnd => OpBitAnd(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpBitOr => OpBitOr(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpBitNot => OpBitNot(self.value()?, self.register()?),
+      InstructionByte::OpBitXor => OpBitXor(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpLeftShift => OpLeftShift(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpRightShift => OpRightShift(self.value()?, self.value()?, self.register()?),
+      InstructionByte::OpRightShiftUnsigned => {
+        OpRightShiftUnsigned(self.value()?, self.value()?, self.register()?)
+      }
+      InstructionByte::TypeOf => TypeOf(self.value()?, self.register()?),
+      InstructionByte::InstanceOf => InstanceOf(self.value()?, self.value()?, self.register()?),
+      InstructionByte::In => In(self.value()?, self.value()?, self.register()?),
+      InstructionByte::Call => Call(self.value()?, self.value()?, self.register()?),
+      InstructionByte::Apply => Apply(
+        self.value()?,
+        self.register()?,
+        self.value()?,
+        self.register()?,
+      ),
+      InstructionByte::ConstApply => ConstApply(
+        self.value()?,
+        self.value()?,
+        self.value()?,
+        self.register()?,
+      ),
+      InstructionByte::Bind => Bind(self.value()?, self.value()?, self.register()?),
+      InstructionByte::Sub => Sub(self.value()?, self.value()?, self.register()?),
+      InstructionByte::SubMov => SubMov(self.value()?, self.value()?, self.register()?),
+      InstructionByte::SubCall => SubCall(
+        self.register()?,
+        self.value()?,
+        self.value()?,
+        self.register()?,
+      ),
+      InstructionByte::Jmp => Jmp(self.label_ref()?),
+      InstructionByte::JmpIf => JmpIf(self.value()?, self.label_ref()?),
+      InstructionByte::JmpIfNot => JmpIfNot(self.value()?, self.label_ref()?),
+      InstructionByte::UnaryPlus => UnaryPlus(self.value()?, self.register()?),
+      InstructionByte::UnaryMinus => UnaryMinus(self.value()?, self.register()?),
+      InstructionByte::New => New(self.value()?, self.value()?, self.register()?),
+      InstructionByte::Throw => Throw(self.value()?),
+      InstructionByte::Import => Import(self.value()?, self.register()?),
+      InstructionByte::ImportStar => ImportStar(self.value()?, self.register()?),
+      InstructionByte::SetCatch => SetCatch(self.label_ref()?, self.register()?),
+      InstructionByte::UnsetCatch => UnsetCatch,
+      InstructionByte::ConstSubCall => ConstSubCall(
+        self.value()?,
+        self.value()?,
+        self.value()?,
+        self.register()?,
+      ),
+      InstructionByte::RequireMutableThis => RequireMutableThis,
+      InstructionByte::ThisSubCall => ThisSubCall(
+        self.register()?,
+        self.value()?,
+        self.value()?,
+        self.register()?,
+      ),
+      InstructionByte::Next => Next(self.register()?, self.register()?),
+      InstructionByte::UnpackIterRes => {
+        UnpackIterRes(self.register()?, self.register()?, self.register()?)
+      }
+      InstructionByte::Cat => Cat(self.value()?, self.register()?),
+      InstructionByte::Yield => Yield(self.value()?, self.register()?),
+      InstructionByte::YieldStar => YieldStar(self.value()?, self.register()?),
+      InstructionByte::Await => Await(self.value()?, self.register()?),
+      InstructionByte::NewTarget => NewTarget(self.register()?),
+      InstructionByte::Delete => Delete(self.register()?, self.value()?, self.register()?),
+    })
+  }
+
+  fn value(&mut self) -> Result<Value, String> {
+    let type_ = self.byte()?;
+
+    Ok(match type_ {
+      b if b == ValueType::Void as u8 => Value::Void,
+      b if b == ValueType::Undefined as u8 => Value::Undefined,
+      b if b == ValueType::Null as u8 => Value::Null,
+      b if b == ValueType::False as u8 => Value::Bool(false),
+      b if b == ValueType::True as u8 => Value::Bool(true),
+      b if b == ValueType::SignedByte as u8 => Value::Number(Number(self.byte()? as i8 as f64)),
+      b if b == ValueType::Number as u8 => Value::Number(Number(self.f64()?)),
+      b if b == ValueType::String as u8 => Value::String(self.inline_string()?),
+      b if b == ValueType::BigInt as u8 => Value::BigInt(self.bigint()?),
+      b if b == ValueType::Array as u8 => {
+        let mut values = Vec::<Value>::new();
+
+        while self.peek_byte()? != ValueType::End as u8 {
+          values.push(self.value()?);
+        }
+
+        self.pos += 1;
+
+        Value::Array(Box::new(Array { values }))
+      }
+      b if b == ValueType::Object as u8 => {
+        let mut properties = Vec::<(Value, Value)>::new();
+
+        while self.peek_byte()? != ValueType::End as u8 {
+          properties.push((self.value()?, self.value()?));
+        }
+
+        self.pos += 1;
+
+        Value::Object(Box::new(Object { properties }))
+      }
+      b if b == ValueType::Pointer as u8 => Value::Pointer(position_pointer(self.location()?)),
+      b if b == ValueType::Register as u8 => Value::Register(self.register()?),
+      b if b == ValueType::TakeRegister as u8 => Value::Register(self.register()?.take()),
+      b if b == ValueType::Builtin as u8 => {
+        let code = self.varsize_uint()?;
+
+        match BUILTIN_NAMES.get(code) {
+          Some(name) => Value::Builtin(Builtin {
+            name: name.to_string(),
+          }),
+          None => return Err(self.error(&format!("Unrecognized builtin: {}", code))),
+        }
+      }
+      b if b == ValueType::Class as u8 || b == ValueType::DerivedClass as u8 => {
+        let meta = self.meta()?;
+
+        let extends = match b == ValueType::DerivedClass as u8 {
+          true => Some(self.value()?),
+          false => None,
+        };
+
+        Value::Class(Box::new(Class {
+          meta,
+          extends,
+          constructor: self.value()?,
+          prototype: self.value()?,
+          static_: self.value()?,
+        }))
+      }
+      b if b == ValueType::Constant as u8 => {
+        let index = self.varsize_uint()?;
+
+        match self.constants.get(index) {
+          Some(constant) => constant.clone(),
+          None => return Err(self.error(&format!("Missing constant: {}", index))),
+        }
+      }
+      _ => {
+        self.pos -= 1;
+        return Err(self.error(&format!("Unexpected value type: {}", type_)));
+      }
+    })
+  }
+
+  fn meta(&mut self) -> Result<Meta, String> {
+    self.expect_byte(ValueType::Meta as u8, "meta")?;
+    self.expect_byte(ValueType::String as u8, "meta name")?;
+    let name = self.inline_string()?;
+
+    let content_hashable = match self.byte()? {
+      0x00 => ContentHashable::Empty,
+      0x01 => {
+        let src_hash = self.hash()?;
+        let dep_count = self.varsize_uint()?;
+        let mut deps = Vec::<Value>::new();
+
+        for _ in 0..dep_count {
+          deps.push(self.value()?);
+        }
+
+        ContentHashable::Src(src_hash, deps)
+      }
+      0x02 => ContentHashable::Content(self.hash()?),
+      _ => return Err(self.error("Invalid content hashable")),
+    };
+
+    Ok(Meta {
+      name,
+      content_hashable,
+    })
+  }
+
+  fn register(&mut self) -> Result<Register, String> {
+    Ok(register_from_index(self.byte()?))
+  }
+
+  fn label_ref(&mut self) -> Result<LabelRef, String> {
+    let target = self.location()?;
+    self.label_targets.insert(target);
+
+    Ok(LabelRef {
+      name: label_name(target),
+    })
+  }
+
+  fn hash(&mut self) -> Result<Hash, String> {
+    let mut hash = [0u8; 32];
+
+    for b in hash.iter_mut() {
+      *b = self.byte()?;
+    }
+
+    Ok(Hash(hash))
+  }
+
+  fn bigint(&mut self) -> Result<BigInt, String> {
+    let sign = match self.byte()? {
+      0 => Sign::Minus,
+      1 => Sign::NoSign,
+      2 => Sign::Plus,
+      _ => return Err(self.error("Invalid bigint sign")),
+    };
+
+    let len = self.varsize_uint()?;
+    let bytes = self.bytes(len)?;
+
+    Ok(BigInt::from_bytes_le(sign, bytes))
+  }
+
+  fn inline_string(&mut self) -> Result<String, String> {
+    let len = self.varsize_uint()?;
+    let bytes = self.bytes(len)?;
+
+    String::from_utf8(bytes.to_vec()).map_err(|_| self.error("Invalid utf8 in string"))
+  }
+
+  fn f64(&mut self) -> Result<f64, String> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(self.bytes(8)?);
+
+    Ok(f64::from_le_bytes(bytes))
+  }
+
+  fn location(&mut self) -> Result<usize, String> {
+    let lo = self.byte()? as usize;
+    let hi = self.byte()? as usize;
+
+    Ok(lo + 256 * hi) // TODO: Support >65535
+  }
+
+  fn varsize_uint(&mut self) -> Result<usize, String> {
+    let mut res = 0_usize;
+    let mut mul = 1_usize;
+
+    loop {
+      let byte = self.byte()?;
+      res += mul * (byte % 128) as usize;
+
+      if byte & 128 == 0 {
+        return Ok(res);
+      }
+
+      mul *= 128;
+    }
+  }
+
+  fn expect_byte(&mut self, expected: u8, what: &str) -> Result<(), String> {
+    if self.peek_byte()? != expected {
+      return Err(self.error(&format!("Expected {}", what)));
+    }
+
+    self.pos += 1;
+
+    Ok(())
+  }
+
+  fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+    let bytecode: &'a [u8] = self.bytecode;
+
+    match bytecode.get(self.pos..self.pos + len) {
+      Some(bytes) => {
+        self.pos += len;
+        Ok(bytes)
+      }
+      None => Err(self.error("Unexpected end of bytecode")),
+    }
+  }
+
+  fn byte(&mut self) -> Result<u8, String> {
+    let byte = self.peek_byte()?;
+    self.pos += 1;
+
+    Ok(byte)
+  }
+
+  fn peek_byte(&self) -> Result<u8, String> {
+    match self.bytecode.get(self.pos) {
+      Some(byte) => Ok(*byte),
+      None => Err(self.error("Unexpected end of bytecode")),
+    }
+  }
+
+  fn error(&self, message: &str) -> String {
+    format!("{} at {}", message, self.pos)
+  }
+}
+
+fn register_from_index(index: u8) -> Register {
+  match index {
+    0 => Register::return_(),
+    1 => Register::this(),
+    0xff => Register::ignore(),
+    _ => Register::named(format!("r{}", index - 2)),
+  }
+}
+
+fn label_name(pos: usize) -> String {
+  format!("L{}", pos)
+}
+
+/// Pointers refer to positions until the definitions are named.
+fn position_pointer(pos: usize) -> Pointer {
+  Pointer {
+    name: pos.to_string(),
+  }
+}
+
+/// Replaces position pointers with names, based on the names of functions and classes where
+/// possible.
+fn name_definitions(module: &mut Module) -> Result<(), String> {
+  let mut meta_names = HashMap::<String, String>::new();
+
+  for defn in &module.definitions {
+    if let DefinitionContent::Meta(meta) = &defn.content {
+      meta_names.insert(defn.pointer.name.clone(), meta.name.clone());
+    }
+  }
+
+  let mut allocator = NameAllocator::default();
+  let mut names = BTreeMap::<String, String>::new();
+  let mut meta_owners = HashMap::<String, String>::new();
+
+  for defn in &module.definitions {
+    let name = match &defn.content {
+      DefinitionContent::Function(function) => {
+        let meta_name = function
+          .meta
+          .as_ref()
+          .and_then(|meta| meta_names.get(&meta.name))
+          .filter(|name| !name.is_empty());
+
+        let name = match meta_name {
+          Some(meta_name) => allocator.allocate(&ident_from_str(meta_name)),
+          None => allocator.allocate_numbered("_anon"),
+        };
+
+        if let Some(meta) = &function.meta {
+          meta_owners.insert(meta.name.clone(), name.clone());
+        }
+
+        name
+      }
+      DefinitionContent::Value(Value::Class(class)) if !class.meta.name.is_empty() => {
+        allocator.allocate(&ident_from_str(&class.meta.name))
+      }
+      DefinitionContent::Meta(..) => continue,
+      DefinitionContent::Value(..) | DefinitionContent::Lazy(..) => {
+        allocator.allocate_numbered("_defn")
+      }
+    };
+
+    names.insert(defn.pointer.name.clone(), name);
+  }
+
+  for defn in &module.definitions {
+    if let DefinitionContent::Meta(..) = &defn.content {
+      let name = match meta_owners.get(&defn.pointer.name) {
+        Some(owner) => allocator.allocate(&format!("{}_meta", owner)),
+        None => allocator.allocate_numbered("_meta"),
+      };
+
+      names.insert(defn.pointer.name.clone(), name);
+    }
+  }
+
+  let mut invalid_pointer = None::<String>;
+
+  visit_pointers(module, |visitation| {
+    let pointer = match visitation {
+      PointerVisitation::Export(pointer) => pointer,
+      PointerVisitation::Definition(pointer) => pointer,
+      PointerVisitation::Reference(_, pointer) => pointer,
+    };
+
+    match names.get(&pointer.name) {
+      Some(name) => pointer.name = name.clone(),
+      None => invalid_pointer = Some(pointer.name.clone()),
+    }
+  });
+
+  match invalid_pointer {
+    Some(pos) => Err(format!("Pointer to {} is not a definition", pos)),
+    None => Ok(()),
+  }
+}
@@ -2,13 +2,18 @@ use std::cell::RefCell;
 use std::mem::take;
 
 use swc_common::Spanned;
+use valuescript_common::number_to_string;
 
-use crate::asm::{Array, Instruction, Label, Number, Object, Register, Structured, Value};
+use crate::asm::{
+  Array, Builtin, ContentHashable, Instruction, Label, Number, Object, Register, Structured, Value,
+};
 use crate::diagnostic::{Diagnostic, DiagnosticContainer, DiagnosticReporter};
-use crate::function_compiler::{FunctionCompiler, Functionish};
+use crate::function_compiler::{FunctionCompiler, Functionish, SuperHome};
 use crate::ident::Ident as CrateIdent;
 use crate::scope::{NameId, OwnerId};
-use crate::scope_analysis::{fn_to_owner_id, NameType};
+use crate::scope_analysis::{
+  class_to_owner_id, fn_to_owner_id, getter_to_function, setter_to_function, NameType,
+};
 use crate::target_accessor::TargetAccessor;
 
 #[derive(Debug, Default)]
@@ -101,20 +106,22 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       Update(update_exp) => self.update_expression(update_exp, target_register),
       Bin(bin_exp) => self.binary_expression(bin_exp, target_register),
       Assign(assign_exp) => self.assign_expression(assign_exp, false, target_register),
+      Member(_) | Call(_) if is_opt_chain(expr) => self.opt_chain_expression(expr, target_register),
       Member(member_exp) => self.member_expression(member_exp, target_register),
-      SuperProp(super_prop) => {
-        self.todo(super_prop.span, "SuperProp expression");
-        CompiledExpression::empty()
-      }
+      SuperProp(super_prop) => self.super_prop_expression(super_prop, target_register),
       Cond(cond_exp) => self.cond_expression(cond_exp, target_register),
       Call(call_exp) => match &call_exp.callee {
         swc_ecma_ast::Callee::Expr(callee_expr) => match &**callee_expr {
           swc_ecma_ast::Expr::Member(member_expr) => {
             self.method_call_expression(member_expr, &call_exp.args, target_register)
           }
+          swc_ecma_ast::Expr::SuperProp(super_prop) => {
+            self.super_method_call_expression(super_prop, &call_exp.args, target_register)
+          }
           _ => self.call_expression(call_exp, target_register),
         },
-        _ => {
+        swc_ecma_ast::Callee::Super(_) => self.super_call_expression(call_exp),
+        swc_ecma_ast::Callee::Import(_) => {
           self.todo(call_exp.callee.span(), "non-expression callee");
 
           CompiledExpression::empty()
@@ -129,6 +136,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
         self.compile(seq_exp.exprs.last().unwrap(), target_register)
       }
       Ident(ident) => self.ident(&CrateIdent::from_swc_ident(ident), target_register),
+      Lit(swc_ecma_ast::Lit::Regex(regex)) => self.regex_literal(regex, target_register),
       Lit(lit) => self.compile_literal(lit).to_ce(),
       Tpl(tpl) => self.template_literal(tpl, target_register),
       TaggedTpl(tagged_tpl) => {
@@ -136,24 +144,10 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
         CompiledExpression::empty()
       }
       Arrow(arrow) => self.arrow_expression(arrow, target_register),
-      Class(class_exp) => {
-        // TODO: Handle captures
-        let p = self
-          .fnc
-          .mc
-          .compile_class(None, class_exp.ident.as_ref(), &class_exp.class);
-
-        CompiledExpression::new(Value::Pointer(p), vec![])
-      }
+      Class(class_exp) => self.class_expression(class_exp, target_register),
       Yield(yield_expr) => self.yield_expr(yield_expr, target_register),
-      MetaProp(meta_prop) => {
-        self.todo(meta_prop.span, "MetaProp expression");
-        CompiledExpression::empty()
-      }
-      Await(await_exp) => {
-        self.todo(await_exp.span, "Await expression");
-        CompiledExpression::empty()
-      }
+      MetaProp(meta_prop) => self.meta_prop(meta_prop, target_register),
+      Await(await_exp) => self.await_expr(await_exp, target_register),
       Paren(p) => self.compile(&p.expr, target_register),
       JSXMember(jsx_member) => {
         self.todo(jsx_member.span(), "JSXMember expression");
@@ -190,10 +184,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
         self.todo(private_name.span, "PrivateName expression");
         CompiledExpression::empty()
       }
-      OptChain(opt_chain) => {
-        self.todo(opt_chain.span, "OptChain expression");
-        CompiledExpression::empty()
-      }
+      OptChain(_) => self.opt_chain_expression(expr, target_register),
       Invalid(invalid) => {
         self.error(invalid.span, "Invalid expression");
 
@@ -228,6 +219,10 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     un_exp: &swc_ecma_ast::UnaryExpr,
     target_register: Option<Register>,
   ) -> CompiledExpression {
+    if un_exp.op == swc_ecma_ast::UnaryOp::Delete {
+      return self.delete_expression(un_exp, target_register);
+    }
+
     let mut nested_registers = Vec::<Register>::new();
 
     let arg = self.compile(&un_exp.arg, None);
@@ -257,6 +252,49 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     CompiledExpression::new(Value::Register(target), nested_registers)
   }
 
+  pub fn delete_expression(
+    &mut self,
+    un_exp: &swc_ecma_ast::UnaryExpr,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    let member = match get_delete_member(&un_exp.arg) {
+      Some(member) => member,
+      None => {
+        self.error(
+          un_exp.arg.span(),
+          "The operand of a delete operator must be a property reference",
+        );
+
+        return CompiledExpression::empty();
+      }
+    };
+
+    let mut nested_registers = Vec::<Register>::new();
+
+    let target: Register = match &target_register {
+      None => {
+        let res = self.fnc.allocate_tmp();
+        nested_registers.push(res.clone());
+        res
+      }
+      Some(t) => t.clone(),
+    };
+
+    let mut obj = TargetAccessor::compile(self, &member.obj, false);
+    let subscript = self.member_prop(&member.prop, None);
+
+    self.fnc.push(Instruction::Delete(
+      obj.register(),
+      subscript.value.clone(),
+      target.clone(),
+    ));
+
+    self.fnc.release_ce(subscript);
+    obj.packup(self, false);
+
+    CompiledExpression::new(Value::Register(target), nested_registers)
+  }
+
   pub fn binary_expression(
     &mut self,
     bin: &swc_ecma_ast::BinExpr,
@@ -477,13 +515,35 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
 
     let mut sub_nested_registers = Vec::<Register>::new();
 
+    // After a spread or accessor, the object is built up in this register instead of object_asm. It isn't the
+    // target register because the remaining properties might read it, like `x = { ...x }`.
+    let mut obj_reg: Option<Register> = None;
+
     for i in 0..object_exp.props.len() {
       use swc_ecma_ast::Prop;
       use swc_ecma_ast::PropOrSpread;
 
       match &object_exp.props[i] {
         PropOrSpread::Spread(spread) => {
-          self.todo(spread.span(), "spread expression");
+          let obj_reg =
+            self.object_literal_reg(&mut obj_reg, &mut object_asm, &mut sub_nested_registers);
+
+          let compiled_spread = self.compile(&spread.expr, None);
+
+          self.fnc.push(Instruction::Call(
+            Value::Builtin(Builtin {
+              name: "ObjectSpread".to_string(),
+            }),
+            Value::Array(Box::new(Array {
+              values: vec![
+                Value::Register(obj_reg.clone()),
+                compiled_spread.value.clone(),
+              ],
+            })),
+            obj_reg,
+          ));
+
+          self.fnc.release_ce(compiled_spread);
         }
         PropOrSpread::Prop(prop) => match &**prop {
           Prop::Shorthand(ident) => {
@@ -511,8 +571,24 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
             object_asm.properties.push((prop_key, prop_value));
           }
           Prop::Assign(assign) => self.todo(assign.span(), "Assign prop"),
-          Prop::Getter(getter) => self.todo(getter.span(), "Getter prop"),
-          Prop::Setter(setter) => self.todo(setter.span(), "Setter prop"),
+          Prop::Getter(getter) => {
+            let obj_reg =
+              self.object_literal_reg(&mut obj_reg, &mut object_asm, &mut sub_nested_registers);
+
+            let compiled_key = self.prop_name(&getter.key);
+            let get = self.object_literal_fn(None, getter_to_function(getter));
+
+            self.define_accessor(&obj_reg, compiled_key, get, Value::Undefined.to_ce());
+          }
+          Prop::Setter(setter) => {
+            let obj_reg =
+              self.object_literal_reg(&mut obj_reg, &mut object_asm, &mut sub_nested_registers);
+
+            let compiled_key = self.prop_name(&setter.key);
+            let set = self.object_literal_fn(None, setter_to_function(setter));
+
+            self.define_accessor(&obj_reg, compiled_key, Value::Undefined.to_ce(), set);
+          }
           Prop::Method(method) => {
             let mut compiled_key = self.prop_name(&method.key);
             compiled_key.release_checker.has_unreleased_registers = false;
@@ -525,24 +601,34 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
               _ => None,
             };
 
-            let fn_name = fn_ident.clone().map(|ident| ident.sym.to_string());
-
-            let p = match &fn_name {
-              Some(name) => self.fnc.allocate_defn(name),
-              None => self.fnc.allocate_defn_numbered("_anon"),
-            };
-
-            FunctionCompiler::new(self.fnc.mc).compile(
-              p.clone(),
-              Functionish::Fn(fn_ident, method.function.clone()),
-            );
+            let mut compiled_value = self.object_literal_fn(fn_ident, method.function.clone());
+            compiled_value.release_checker.has_unreleased_registers = false;
+            sub_nested_registers.append(&mut compiled_value.nested_registers);
 
-            object_asm.properties.push((prop_key, Value::Pointer(p)));
+            object_asm.properties.push((prop_key, compiled_value.value));
           }
         },
       }
     }
 
+    if let Some(obj_reg) = obj_reg {
+      self.submov_props(&mut object_asm, &obj_reg, &mut sub_nested_registers);
+
+      return match target_register {
+        None => CompiledExpression::new(Value::Register(obj_reg.clone()), vec![obj_reg]),
+        Some(tr) => {
+          self.fnc.push(Instruction::Mov(
+            Value::Register(obj_reg.clone()),
+            tr.clone(),
+          ));
+
+          self.fnc.release_reg(&obj_reg);
+
+          CompiledExpression::new(Value::Register(tr), vec![])
+        }
+      };
+    }
+
     match target_register {
       None => CompiledExpression::new(Value::Object(Box::new(object_asm)), sub_nested_registers),
       Some(tr) => {
@@ -560,6 +646,123 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     }
   }
 
+  /// The register that an object literal is built up in, for the properties that can't be
+  /// included in the literal value itself (spreads and accessors). The properties so far are moved
+  /// into it.
+  fn object_literal_reg(
+    &mut self,
+    obj_reg: &mut Option<Register>,
+    object_asm: &mut Object,
+    sub_nested_registers: &mut Vec<Register>,
+  ) -> Register {
+    match obj_reg {
+      Some(obj_reg) => {
+        self.submov_props(object_asm, obj_reg, sub_nested_registers);
+        obj_reg.clone()
+      }
+      None => {
+        let new_obj_reg = self.fnc.allocate_tmp();
+
+        self.fnc.push(Instruction::Mov(
+          Value::Object(Box::new(take(object_asm))),
+          new_obj_reg.clone(),
+        ));
+
+        for reg in take(sub_nested_registers) {
+          self.fnc.release_reg(&reg);
+        }
+
+        *obj_reg = Some(new_obj_reg.clone());
+        new_obj_reg
+      }
+    }
+  }
+
+  /// Compiles a method, getter or setter defined in an object literal. Like function expressions,
+  /// it's bound to the variables it captures.
+  fn object_literal_fn(
+    &mut self,
+    fn_ident: Option<swc_ecma_ast::Ident>,
+    function: swc_ecma_ast::Function,
+  ) -> CompiledExpression {
+    let fn_name = fn_ident.as_ref().map(|ident| ident.sym.to_string());
+
+    let definition_pointer = match &fn_name {
+      Some(name) => self.fnc.allocate_defn(name),
+      None => self.fnc.allocate_defn_numbered("_anon"),
+    };
+
+    let capture_params = self
+      .fnc
+      .mc
+      .scope_analysis
+      .get_register_captures(&fn_to_owner_id(fn_ident.as_ref(), &function));
+
+    let span = function.span;
+
+    FunctionCompiler::new(self.fnc.mc).compile(
+      definition_pointer.clone(),
+      Functionish::Fn(fn_ident, function),
+    );
+
+    match capture_params.len() {
+      0 => Value::Pointer(definition_pointer).to_ce(),
+      _ => self.capturing_fn_ref(
+        span,
+        fn_name,
+        &Value::Pointer(definition_pointer),
+        &capture_params,
+        None,
+      ),
+    }
+  }
+
+  /// Adds a getter and/or setter (undefined for the missing half) to the object in obj_reg.
+  fn define_accessor(
+    &mut self,
+    obj_reg: &Register,
+    key: CompiledExpression,
+    get: CompiledExpression,
+    set: CompiledExpression,
+  ) {
+    self.fnc.push(Instruction::Call(
+      Value::Builtin(Builtin {
+        name: "ObjectDefineAccessor".to_string(),
+      }),
+      Value::Array(Box::new(Array {
+        values: vec![
+          Value::Register(obj_reg.clone()),
+          key.value.clone(),
+          get.value.clone(),
+          set.value.clone(),
+        ],
+      })),
+      obj_reg.clone(),
+    ));
+
+    self.fnc.release_ce(key);
+    self.fnc.release_ce(get);
+    self.fnc.release_ce(set);
+  }
+
+  /// Assigns the properties compiled since the last spread of an object literal to obj_reg.
+  fn submov_props(
+    &mut self,
+    object_asm: &mut Object,
+    obj_reg: &Register,
+    sub_nested_registers: &mut Vec<Register>,
+  ) {
+    for (key, value) in take(&mut object_asm.properties) {
+      self
+        .fnc
+        .push(Instruction::SubMov(key, value, obj_reg.clone()));
+    }
+
+    for reg in take(sub_nested_registers) {
+      self.fnc.release_reg(&reg);
+    }
+  }
+
   pub fn prop_name(&mut self, prop_name: &swc_ecma_ast::PropName) -> CompiledExpression {
     use swc_ecma_ast::PropName;
 
@@ -568,11 +771,8 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     let value = match &prop_name {
       PropName::Ident(ident) => Value::String(ident.sym.to_string()),
       PropName::Str(str_) => Value::String(str_.value.to_string()),
-      PropName::Num(num) =>
-      // TODO: JS number stringification (different from rust)
-      {
-        Value::String(num.value.to_string()) // TODO: Can we just use Value::Number here?
-      }
+      // TODO: Can we just use Value::Number here?
+      PropName::Num(num) => Value::String(number_to_string(num.value)),
       PropName::Computed(comp) => {
         // TODO: Always using a register is maybe not ideal
         // At the least, the assembly supports definitions and should
@@ -610,7 +810,23 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     member_exp: &swc_ecma_ast::MemberExpr,
     target_register: Option<Register>,
   ) -> CompiledExpression {
-    let compiled_obj = self.compile(&member_exp.obj, None);
+    self.member_expression_impl(member_exp, target_register, None)
+  }
+
+  /// When part of an optional chain, `opt_chain` is the label to jump to when the chain finds a
+  /// nullish value, and whether the object should be checked (like `a?.b` but not `a?.b.c`).
+  fn member_expression_impl(
+    &mut self,
+    member_exp: &swc_ecma_ast::MemberExpr,
+    target_register: Option<Register>,
+    opt_chain: Option<(&Label, bool)>,
+  ) -> CompiledExpression {
+    let compiled_obj = self.opt_chain_obj(&member_exp.obj, opt_chain.map(|(label, _)| label));
+
+    if let Some((nullish_label, true)) = opt_chain {
+      self.jmp_if_nullish(&compiled_obj.value, nullish_label);
+    }
+
     let compiled_prop = self.member_prop(&member_exp.prop, None);
 
     let tmp_dest: Register;
@@ -635,6 +851,192 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     CompiledExpression::new(Value::Register(dest.clone()), nested_registers)
   }
 
+  /// Compiles a chain like `a?.b.c()`. When a `?.` finds null or undefined, the rest of the chain
+  /// isn't evaluated and the result is undefined.
+  pub fn opt_chain_expression(
+    &mut self,
+    expr: &swc_ecma_ast::Expr,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    let mut nested_registers = vec![];
+
+    let dst = match target_register {
+      Some(reg) => reg,
+      None => {
+        let tmp = self.fnc.allocate_tmp();
+        nested_registers.push(tmp.clone());
+
+        tmp
+      }
+    };
+
+    let nullish_label = Label {
+      name: self
+        .fnc
+        .label_allocator
+        .allocate_numbered("opt_chain_nullish"),
+    };
+
+    let opt_chain_end_label = Label {
+      name: self.fnc.label_allocator.allocate_numbered("opt_chain_end"),
+    };
+
+    let link = self.opt_chain_link(expr, &nullish_label, Some(dst.clone()));
+    self.fnc.release_ce(link);
+
+    self.fnc.push(Instruction::Jmp(opt_chain_end_label.ref_()));
+
+    self.fnc.label(nullish_label);
+    self
+      .fnc
+      .push(Instruction::Mov(Value::Undefined, dst.clone()));
+
+    self.fnc.label(opt_chain_end_label);
+
+    CompiledExpression::new(Value::Register(dst), nested_registers)
+  }
+
+  /// Compiles part of an optional chain, jumping to `nullish_label` to skip the rest of the chain.
+  fn opt_chain_link(
+    &mut self,
+    expr: &swc_ecma_ast::Expr,
+    nullish_label: &Label,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    use swc_ecma_ast::Expr;
+
+    match expr {
+      Expr::OptChain(opt_chain) => match &opt_chain.base {
+        swc_ecma_ast::OptChainBase::Member(member_exp) => {
+          self.member_expression_impl(member_exp, target_register, Some((nullish_label, true)))
+        }
+        swc_ecma_ast::OptChainBase::Call(opt_call) => {
+          // `a?.b()` checks a, but `a.b?.()` checks a.b
+          let optional_part = match opt_chain.question_dot_token.lo >= opt_call.callee.span().hi {
+            true => OptionalPart::Method,
+            false => OptionalPart::Object,
+          };
+
+          self.opt_chain_call(
+            &opt_call.callee,
+            &opt_call.args,
+            nullish_label,
+            optional_part,
+            target_register,
+          )
+        }
+      },
+      Expr::Member(member_exp) => {
+        self.member_expression_impl(member_exp, target_register, Some((nullish_label, false)))
+      }
+      Expr::Call(call_exp) => match &call_exp.callee {
+        swc_ecma_ast::Callee::Expr(callee) => self.opt_chain_call(
+          callee,
+          &call_exp.args,
+          nullish_label,
+          OptionalPart::Neither,
+          target_register,
+        ),
+        _ => self.compile(expr, target_register),
+      },
+      _ => self.compile(expr, target_register),
+    }
+  }
+
+  /// Compiles a call in an optional chain. `optional_part` is what the `?.` of the call applies to,
+  /// if it has one.
+  fn opt_chain_call(
+    &mut self,
+    callee: &swc_ecma_ast::Expr,
+    args: &[swc_ecma_ast::ExprOrSpread],
+    nullish_label: &Label,
+    optional_part: OptionalPart,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    use swc_ecma_ast::Expr;
+
+    let (member_exp, optional_part) = match (callee, optional_part) {
+      (Expr::Member(member_exp), _) => (member_exp, optional_part),
+      (
+        Expr::OptChain(swc_ecma_ast::OptChainExpr {
+          base: swc_ecma_ast::OptChainBase::Member(member_exp),
+          ..
+        }),
+        OptionalPart::Neither,
+      ) => (member_exp, OptionalPart::Object),
+      _ => {
+        // Not a method call, eg `f?.()` or `a?.b()()`
+        let mut nested_registers = Vec::<Register>::new();
+
+        let compiled_callee = self.opt_chain_obj(callee, Some(nullish_label));
+
+        if optional_part != OptionalPart::Neither {
+          self.jmp_if_nullish(&compiled_callee.value, nullish_label);
+        }
+
+        let compiled_args = self.args(args.iter().map(Some), None);
+
+        let dest = match target_register {
+          Some(tr) => tr,
+          None => {
+            let tmp = self.fnc.allocate_tmp();
+            nested_registers.push(tmp.clone());
+
+            tmp
+          }
+        };
+
+        self.fnc.push(Instruction::Call(
+          compiled_callee.value.clone(),
+          compiled_args.value.clone(),
+          dest.clone(),
+        ));
+
+        self.fnc.release_ce(compiled_args);
+        self.fnc.release_ce(compiled_callee);
+
+        return CompiledExpression::new(Value::Register(dest), nested_registers);
+      }
+    };
+
+    self.method_call_expression_impl(
+      member_exp,
+      args,
+      target_register,
+      Some((nullish_label, optional_part)),
+    )
+  }
+
+  /// Compiles the object of a member expression (or the callee of a call), continuing the optional
+  /// chain if there is one.
+  fn opt_chain_obj(
+    &mut self,
+    expr: &swc_ecma_ast::Expr,
+    nullish_label: Option<&Label>,
+  ) -> CompiledExpression {
+    match nullish_label {
+      Some(nullish_label) if is_opt_chain(expr) => self.opt_chain_link(expr, nullish_label, None),
+      _ => self.compile(expr, None),
+    }
+  }
+
+  fn jmp_if_nullish(&mut self, value: &Value, nullish_label: &Label) {
+    let is_nullish = self.fnc.allocate_tmp();
+
+    self.fnc.push(Instruction::OpEq(
+      value.clone(),
+      Value::Null,
+      is_nullish.clone(),
+    ));
+
+    self.fnc.push(Instruction::JmpIf(
+      Value::Register(is_nullish.clone()),
+      nullish_label.ref_(),
+    ));
+
+    self.fnc.release_reg(&is_nullish);
+  }
+
   pub fn cond_expression(
     &mut self,
     cond_exp: &swc_ecma_ast::CondExpr,
@@ -652,7 +1054,9 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       }
     };
 
-    self.compile_into(&cond_exp.test, dst.clone());
+    // The test isn't compiled into dst because the branches might read it, like
+    // `x = cond ? x : 0`
+    let test = self.compile(&cond_exp.test, None);
 
     let true_label = Label {
       name: self.fnc.label_allocator.allocate_numbered("cond_true"),
@@ -662,10 +1066,10 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       name: self.fnc.label_allocator.allocate_numbered("cond_end"),
     };
 
-    self.fnc.push(Instruction::JmpIf(
-      Value::Register(dst.clone()),
-      true_label.ref_(),
-    ));
+    self
+      .fnc
+      .push(Instruction::JmpIf(test.value.clone(), true_label.ref_()));
+    self.fnc.release_ce(test);
 
     self.compile_into(&cond_exp.alt, dst.clone());
     self.fnc.push(Instruction::Jmp(cond_end_label.ref_()));
@@ -810,6 +1214,165 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     CompiledExpression::new(Value::Register(dest), nested_registers)
   }
 
+  pub fn super_call_expression(&mut self, call_exp: &swc_ecma_ast::CallExpr) -> CompiledExpression {
+    let super_class = match &self.fnc.super_class {
+      Some(super_class) => super_class.clone(),
+      None => {
+        self.fnc.error(
+          call_exp.callee.span(),
+          "super() is only valid in a derived class constructor",
+        );
+
+        return CompiledExpression::empty();
+      }
+    };
+
+    let args = call_exp.args.iter().map(Some);
+    let compiled_args = self.args(args, None);
+
+    self.apply_super_constructor(super_class, compiled_args.value.clone());
+
+    self.fnc.release_ce(compiled_args);
+
+    CompiledExpression::new(Value::Register(Register::this()), vec![])
+  }
+
+  /// Calls the constructor of `super_class` on `this` with `args`, and then runs the field
+  /// initializers that were waiting for `this` to be available.
+  pub fn apply_super_constructor(&mut self, super_class: Value, args: Value) {
+    let super_constructor = self.fnc.allocate_tmp();
+
+    self.fnc.push(Instruction::Call(
+      Value::Builtin(Builtin {
+        name: "SuperConstructor".to_string(),
+      }),
+      Value::Array(Box::new(Array {
+        values: vec![super_class],
+      })),
+      super_constructor.clone(),
+    ));
+
+    self.fnc.push(Instruction::Apply(
+      Value::Register(super_constructor.clone()),
+      Register::this(),
+      args,
+      Register::ignore(),
+    ));
+
+    self.fnc.release_reg(&super_constructor);
+
+    let mut post_super_initializers = self.fnc.post_super_initializers.clone();
+    self.fnc.fn_.body.append(&mut post_super_initializers);
+    self.fnc.super_called = true;
+  }
+
+  /// The object that `super.x` looks up `x` on.
+  fn super_home_object(&mut self, span: swc_common::Span) -> CompiledExpression {
+    match self.fnc.super_home.clone() {
+      Some(SuperHome::Prototype(super_class)) => {
+        let prototype = self.fnc.allocate_tmp();
+
+        self.fnc.push(Instruction::Call(
+          Value::Builtin(Builtin {
+            name: "SuperPrototype".to_string(),
+          }),
+          Value::Array(Box::new(Array {
+            values: vec![super_class],
+          })),
+          prototype.clone(),
+        ));
+
+        CompiledExpression::new(Value::Register(prototype.clone()), vec![prototype])
+      }
+      Some(SuperHome::Static(super_class)) => super_class.to_ce(),
+      None => {
+        self.fnc.error(
+          span,
+          "super properties are only valid in members of a derived class",
+        );
+
+        CompiledExpression::empty()
+      }
+    }
+  }
+
+  pub fn super_prop_expression(
+    &mut self,
+    super_prop: &swc_ecma_ast::SuperPropExpr,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    let mut nested_registers = Vec::<Register>::new();
+
+    let home = self.super_home_object(super_prop.obj.span);
+
+    let key = match &super_prop.prop {
+      swc_ecma_ast::SuperProp::Ident(ident) => Value::String(ident.sym.to_string()).to_ce(),
+      swc_ecma_ast::SuperProp::Computed(computed) => self.compile(&computed.expr, None),
+    };
+
+    let dest = match target_register {
+      Some(tr) => tr,
+      None => {
+        let tmp = self.fnc.allocate_tmp();
+        nested_registers.push(tmp.clone());
+
+        tmp
+      }
+    };
+
+    self.fnc.push(Instruction::Sub(
+      home.value.clone(),
+      key.value.clone(),
+      dest.clone(),
+    ));
+
+    self.fnc.release_ce(key);
+    self.fnc.release_ce(home);
+
+    CompiledExpression::new(Value::Register(dest), nested_registers)
+  }
+
+  /// Calls the method of the base class with the current `this` (`super.method(...)`).
+  pub fn super_method_call_expression(
+    &mut self,
+    super_prop: &swc_ecma_ast::SuperPropExpr,
+    args: &[swc_ecma_ast::ExprOrSpread],
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    let mut nested_registers = Vec::<Register>::new();
+
+    let method = self.super_prop_expression(super_prop, None);
+
+    let compiled_args = {
+      let args_iter = args.iter().map(Some);
+      self.args(args_iter, None)
+    };
+
+    let dest = match target_register {
+      Some(tr) => tr,
+      None => {
+        let tmp = self.fnc.allocate_tmp();
+        nested_registers.push(tmp.clone());
+
+        tmp
+      }
+    };
+
+    let this = self.get_register_for_ident_mutation(&CrateIdent::this(super_prop.obj.span));
+
+    self.fnc.push(Instruction::Apply(
+      method.value.clone(),
+      this,
+      compiled_args.value.clone(),
+      dest.clone(),
+    ));
+
+    self.fnc.release_ce(compiled_args);
+    self.fnc.release_ce(method);
+
+    CompiledExpression::new(Value::Register(dest), nested_registers)
+  }
+
   pub fn new_expression(
     &mut self,
     new_exp: &swc_ecma_ast::NewExpr,
@@ -856,6 +1419,16 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     callee_expr: &swc_ecma_ast::MemberExpr,
     args: &[swc_ecma_ast::ExprOrSpread],
     target_register: Option<Register>,
+  ) -> CompiledExpression {
+    self.method_call_expression_impl(callee_expr, args, target_register, None)
+  }
+
+  fn method_call_expression_impl(
+    &mut self,
+    callee_expr: &swc_ecma_ast::MemberExpr,
+    args: &[swc_ecma_ast::ExprOrSpread],
+    target_register: Option<Register>,
+    opt_chain: Option<(&Label, OptionalPart)>,
   ) -> CompiledExpression {
     let mut nested_registers = Vec::<Register>::new();
     let mut sub_nested_registers = Vec::<Register>::new();
@@ -871,9 +1444,9 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
         &callee_expr.obj,
         true,
       )),
-      false => {
-        TargetAccessorOrCompiledExpression::CompiledExpression(self.compile(&callee_expr.obj, None))
-      }
+      false => TargetAccessorOrCompiledExpression::CompiledExpression(
+        self.opt_chain_obj(&callee_expr.obj, opt_chain.map(|(label, _)| label)),
+      ),
     };
 
     let obj_value = match &obj {
@@ -881,11 +1454,28 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       TargetAccessorOrCompiledExpression::CompiledExpression(ce) => ce.value.clone(),
     };
 
+    if let Some((nullish_label, OptionalPart::Object)) = opt_chain {
+      self.jmp_if_nullish(&obj_value, nullish_label);
+    }
+
     let mut prop = self.member_prop(&callee_expr.prop, None);
 
     prop.release_checker.has_unreleased_registers = false;
     sub_nested_registers.append(&mut prop.nested_registers);
 
+    if let Some((nullish_label, OptionalPart::Method)) = opt_chain {
+      let method = self.fnc.allocate_tmp();
+
+      self.fnc.push(Instruction::Sub(
+        obj_value.clone(),
+        prop.value.clone(),
+        method.clone(),
+      ));
+
+      self.jmp_if_nullish(&Value::Register(method.clone()), nullish_label);
+      self.fnc.release_reg(&method);
+    }
+
     let compiled_args = {
       let args_iter = args.iter().map(Some);
       self.args(args_iter, None)
@@ -942,6 +1532,18 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       }
     }
 
+    if let Some(arg) = written_back_arg(callee_expr, args) {
+      let is_object_builtin = obj_value
+        == Value::Builtin(Builtin {
+          name: "Object".to_string(),
+        });
+
+      if is_object_builtin && TargetAccessor::is_eligible_expr(self, arg) {
+        let mut target = TargetAccessor::compile(self, arg, true);
+        target.assign_and_packup(self, &Value::Register(dest.clone()), false);
+      }
+    }
+
     for reg in sub_nested_registers {
       self.fnc.release_reg(&reg);
     }
@@ -1000,7 +1602,12 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       .scope_analysis
       .get_register_captures(&OwnerId::Span(arrow_expr.span));
 
-    FunctionCompiler::new(self.fnc.mc).compile(
+    // Arrow functions use the `super` of their enclosing function
+    let super_home = self.fnc.super_home.clone();
+
+    let mut fnc = FunctionCompiler::new(self.fnc.mc);
+    fnc.super_home = super_home;
+    fnc.compile(
       definition_pointer.clone(),
       Functionish::Arrow(arrow_expr.clone()),
     );
@@ -1017,6 +1624,105 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     }
   }
 
+  pub fn class_expression(
+    &mut self,
+    class_exp: &swc_ecma_ast::ClassExpr,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    let ident = class_exp.ident.as_ref();
+    let p = self.fnc.mc.compile_class(None, ident, &class_exp.class);
+
+    let owner_id = class_to_owner_id(ident, &class_exp.class);
+
+    if self
+      .fnc
+      .mc
+      .scope_analysis
+      .get_register_captures(&owner_id)
+      .is_empty()
+    {
+      return Value::Pointer(p).to_ce();
+    }
+
+    let mut class_value = match self.fnc.mc.constants_map.get(&p) {
+      Some(class_value @ Value::Class(_)) => class_value.clone(),
+      _ => {
+        self.internal_error(class_exp.class.span, "Failed to find compiled class");
+        return Value::Pointer(p).to_ce();
+      }
+    };
+
+    if let Value::Class(class) = &mut class_value {
+      if let Value::Pointer(_) = class.static_ {
+        self.todo(
+          class_exp.class.span,
+          "Static field initializers in a class that captures variables",
+        );
+      }
+
+      // Linking only replaces source hashes with content hashes in definitions, and the decoder
+      // can't read source hashes. A class assembled at runtime is a new class each time anyway.
+      class.meta.content_hashable = ContentHashable::Empty;
+    }
+
+    // The class captures variables, so it's assembled at runtime from its functions bound to the
+    // captured values
+    let mut nested_registers = Vec::<Register>::new();
+
+    class_value.visit_values_mut(&mut |value| {
+      let fn_owner_id = match value {
+        Value::Pointer(fn_p) => match self.fnc.mc.fn_owner_ids.get(fn_p) {
+          Some(fn_owner_id) => fn_owner_id.clone(),
+          None => return,
+        },
+        _ => return,
+      };
+
+      let captures = self
+        .fnc
+        .mc
+        .scope_analysis
+        .get_register_captures(&fn_owner_id);
+
+      if captures.is_empty() {
+        return;
+      }
+
+      let mut bound_fn =
+        self.capturing_fn_ref(class_exp.class.span, None, &value.clone(), &captures, None);
+
+      nested_registers.append(&mut bound_fn.nested_registers);
+      bound_fn.release_checker.has_unreleased_registers = false;
+
+      *value = bound_fn.value;
+    });
+
+    let class_reg = match target_register {
+      Some(target_register) => target_register,
+      None => {
+        let class_reg = self.fnc.allocate_tmp();
+        nested_registers.push(class_reg.clone());
+        class_reg
+      }
+    };
+
+    self
+      .fnc
+      .push(Instruction::Mov(class_value, class_reg.clone()));
+
+    // Only the class register is still needed
+    let mut result_registers = vec![];
+
+    for reg in nested_registers {
+      match reg == class_reg {
+        true => result_registers.push(reg),
+        false => self.fnc.release_reg(&reg),
+      }
+    }
+
+    CompiledExpression::new(Value::Register(class_reg), result_registers)
+  }
+
   pub fn capturing_fn_ref(
     &mut self,
     span: swc_common::Span,
@@ -1134,6 +1840,40 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     CompiledExpression::new(Value::Register(reg), nested_registers)
   }
 
+  /// Regex literals create a new RegExp each time they're evaluated, like `new RegExp(...)`.
+  pub fn regex_literal(
+    &mut self,
+    regex: &swc_ecma_ast::Regex,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    let mut nested_registers = Vec::<Register>::new();
+
+    let dest = match target_register {
+      Some(tr) => tr,
+      None => {
+        let tmp = self.fnc.allocate_tmp();
+        nested_registers.push(tmp.clone());
+
+        tmp
+      }
+    };
+
+    self.fnc.push(Instruction::New(
+      Value::Builtin(Builtin {
+        name: "RegExp".to_string(),
+      }),
+      Value::Array(Box::new(Array {
+        values: vec![
+          Value::String(regex.exp.to_string()),
+          Value::String(regex.flags.to_string()),
+        ],
+      })),
+      dest.clone(),
+    ));
+
+    CompiledExpression::new(Value::Register(dest), nested_registers)
+  }
+
   pub fn template_literal(
     &mut self,
     tpl: &swc_ecma_ast::Tpl,
@@ -1144,7 +1884,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     assert_eq!(tpl.quasis.len(), len + 1);
 
     if len == 0 {
-      return Value::String(tpl.quasis[0].raw.to_string()).to_ce();
+      return Value::String(cooked_quasi(&tpl.quasis[0], self)).to_ce();
     }
 
     let mut nested_registers = Vec::<Register>::new();
@@ -1159,7 +1899,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     let first_expr = self.compile(&tpl.exprs[0], None);
 
     self.fnc.push(Instruction::OpPlus(
-      Value::String(tpl.quasis[0].raw.to_string()),
+      Value::String(cooked_quasi(&tpl.quasis[0], self)),
       first_expr.value.clone(),
       acc_reg.clone(),
     ));
@@ -1169,7 +1909,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     for i in 1..len {
       self.fnc.push(Instruction::OpPlus(
         Value::Register(acc_reg.clone()),
-        Value::String(tpl.quasis[i].raw.to_string()),
+        Value::String(cooked_quasi(&tpl.quasis[i], self)),
         acc_reg.clone(),
       ));
 
@@ -1184,7 +1924,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       self.fnc.release_ce(expr_i);
     }
 
-    let last_str = tpl.quasis[len].raw.to_string();
+    let last_str = cooked_quasi(&tpl.quasis[len], self);
 
     if !last_str.is_empty() {
       self.fnc.push(Instruction::OpPlus(
@@ -1202,6 +1942,11 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     yield_expr: &swc_ecma_ast::YieldExpr,
     target_register: Option<Register>,
   ) -> CompiledExpression {
+    if yield_expr.delegate && self.fnc.fn_.is_async {
+      self.fnc.todo(yield_expr.span, "yield* in async generators");
+      return CompiledExpression::empty();
+    }
+
     let mut nested_registers = Vec::<Register>::new();
 
     let arg_compiled = match &yield_expr.arg {
@@ -1218,9 +1963,17 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       }
     };
 
+    let mut arg_value = arg_compiled.value.clone();
+
+    if self.fnc.fn_.is_async && yield_expr.arg.is_some() {
+      // Async generators yield the settled value
+      self.fnc.await_(arg_value, dst.clone());
+      arg_value = Value::Register(dst.clone());
+    }
+
     self.fnc.push(match yield_expr.delegate {
-      false => Instruction::Yield(arg_compiled.value.clone(), dst.clone()),
-      true => Instruction::YieldStar(arg_compiled.value.clone(), dst.clone()),
+      false => Instruction::Yield(arg_value, dst.clone()),
+      true => Instruction::YieldStar(arg_value, dst.clone()),
     });
 
     self.fnc.release_ce(arg_compiled);
@@ -1228,6 +1981,76 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
     CompiledExpression::new(Value::Register(dst), nested_registers)
   }
 
+  pub fn await_expr(
+    &mut self,
+    await_expr: &swc_ecma_ast::AwaitExpr,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    if !self.fnc.fn_.is_async {
+      self.error(
+        await_expr.span,
+        "await is only supported directly inside async functions",
+      );
+
+      return CompiledExpression::empty();
+    }
+
+    let mut nested_registers = Vec::<Register>::new();
+
+    let arg_compiled = self.compile(&await_expr.arg, None);
+
+    let dst = match target_register {
+      Some(t) => t,
+      None => {
+        let tmp = self.fnc.allocate_tmp();
+        nested_registers.push(tmp.clone());
+        tmp
+      }
+    };
+
+    // The async function is resumed with the settled value (or has the reason thrown into it)
+    self.fnc.await_(arg_compiled.value.clone(), dst.clone());
+
+    self.fnc.release_ce(arg_compiled);
+
+    CompiledExpression::new(Value::Register(dst), nested_registers)
+  }
+
+  pub fn meta_prop(
+    &mut self,
+    meta_prop: &swc_ecma_ast::MetaPropExpr,
+    target_register: Option<Register>,
+  ) -> CompiledExpression {
+    match meta_prop.kind {
+      swc_ecma_ast::MetaPropKind::NewTarget => {}
+      swc_ecma_ast::MetaPropKind::ImportMeta => {
+        self.todo(meta_prop.span, "import.meta");
+        return CompiledExpression::empty();
+      }
+    }
+
+    if self.fnc.is_arrow {
+      // Arrow functions should use the new.target of the enclosing function
+      self.todo(meta_prop.span, "new.target in arrow functions");
+      return CompiledExpression::empty();
+    }
+
+    let mut nested_registers = Vec::<Register>::new();
+
+    let dst = match target_register {
+      Some(t) => t,
+      None => {
+        let tmp = self.fnc.allocate_tmp();
+        nested_registers.push(tmp.clone());
+        tmp
+      }
+    };
+
+    self.fnc.push(Instruction::NewTarget(dst.clone()));
+
+    CompiledExpression::new(Value::Register(dst), nested_registers)
+  }
+
   pub fn ident(
     &mut self,
     ident: &CrateIdent,
@@ -1245,10 +2068,15 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
         false => None,
       },
       _ => {
-        self.internal_error(
-          ident.span,
-          &format!("Failed to lookup identifier `{}`", ident.sym),
-        );
+        let message = match self.fnc.mc.scope_analysis.suggestions.get(&ident.span) {
+          Some(suggestion) => format!(
+            "Failed to lookup identifier `{}` (did you mean `{}`?)",
+            ident.sym, suggestion
+          ),
+          None => format!("Failed to lookup identifier `{}`", ident.sym),
+        };
+
+        self.internal_error(ident.span, &message);
 
         None
       }
@@ -1346,11 +2174,21 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
 
           let elem_reg = self.fnc.get_pattern_register(elem);
 
-          self.fnc.push(Instruction::Sub(
-            Value::Register(register.clone()),
-            Value::Number(Number(i as f64)),
-            elem_reg.clone(),
-          ));
+          self.fnc.push(match elem {
+            Pat::Rest(_) => Instruction::ConstSubCall(
+              Value::Register(register.clone()),
+              Value::String("slice".to_string()),
+              Value::Array(Box::new(Array {
+                values: vec![Value::Number(Number(i as f64))],
+              })),
+              elem_reg.clone(),
+            ),
+            _ => Instruction::Sub(
+              Value::Register(register.clone()),
+              Value::Number(Number(i as f64)),
+              elem_reg.clone(),
+            ),
+          });
 
           self.pat(elem, &elem_reg, false);
         }
@@ -1360,6 +2198,9 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
         }
       }
       Pat::Object(object) => {
+        // The keys that a rest pattern excludes. Computed keys are kept until the rest pattern.
+        let mut rest_excluded_keys = Vec::<CompiledExpression>::new();
+
         for prop in &object.props {
           use swc_ecma_ast::ObjectPatProp;
 
@@ -1374,7 +2215,7 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
                 param_reg.clone(),
               ));
 
-              self.fnc.release_ce(compiled_key);
+              rest_excluded_keys.push(compiled_key);
 
               self.pat(&kv.value, &param_reg, false);
             }
@@ -1386,20 +2227,46 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
 
               self.fnc.push(Instruction::Sub(
                 Value::Register(register.clone()),
-                Value::String(key),
+                Value::String(key.clone()),
                 reg.clone(),
               ));
 
+              rest_excluded_keys.push(Value::String(key).to_ce());
+
               if let Some(value) = &assign.value {
                 self.default_expr(value, &reg);
               }
             }
             ObjectPatProp::Rest(rest) => {
-              self.todo(rest.span, "Rest pattern in object destructuring");
+              let rest_reg = self.fnc.get_pattern_register(&rest.arg);
+
+              self.fnc.push(Instruction::Call(
+                Value::Builtin(Builtin {
+                  name: "ObjectRest".to_string(),
+                }),
+                Value::Array(Box::new(Array {
+                  values: vec![
+                    Value::Register(register.clone()),
+                    Value::Array(Box::new(Array {
+                      values: rest_excluded_keys
+                        .iter()
+                        .map(|key| key.value.clone())
+                        .collect(),
+                    })),
+                  ],
+                })),
+                rest_reg.clone(),
+              ));
+
+              self.pat(&rest.arg, &rest_reg, false);
             }
           }
         }
 
+        for key in rest_excluded_keys {
+          self.fnc.release_ce(key);
+        }
+
         if !skip_release {
           self.fnc.release_reg(register);
         }
@@ -1407,8 +2274,9 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
       Pat::Invalid(_) => {
         // Diagnostic emitted elsewhere
       }
-      Pat::Rest(_) => {
-        // TODO (Diagnostic emitted elsewhere)
+      Pat::Rest(rest) => {
+        // The register already holds the remaining elements (or arguments)
+        self.pat(&rest.arg, register, skip_release);
       }
       Pat::Expr(expr) => {
         let mut at = TargetAccessor::compile(self, expr, true);
@@ -1546,6 +2414,75 @@ impl<'a, 'fnc> ExpressionCompiler<'a, 'fnc> {
   }
 }
 
+/// What the `?.` of a call in an optional chain applies to: `a?.b()` checks the object and
+/// `a.b?.()` checks the method. Calls continuing a chain, like `a?.b.c()`, don't check anything.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OptionalPart {
+  Neither,
+  Object,
+  Method,
+}
+
+/// Whether `expr` is (or continues) an optional chain, like `a?.b` or `a?.b.c()`.
+pub fn is_opt_chain(expr: &swc_ecma_ast::Expr) -> bool {
+  use swc_ecma_ast::{Callee, Expr};
+
+  match expr {
+    Expr::OptChain(_) => true,
+    Expr::Member(member_exp) => is_opt_chain(&member_exp.obj),
+    Expr::Call(call_exp) => match &call_exp.callee {
+      Callee::Expr(callee) => is_opt_chain(callee),
+      _ => false,
+    },
+    _ => false,
+  }
+}
+
+/// Calls like `Object.defineProperty(obj, ...)` modify their first argument in JS. The builtins
+/// return the modified object instead, and the compiler assigns it back to the argument. This
+/// finds that argument, if `callee` looks like one of these builtins and the argument is something
+/// that can be assigned to.
+pub fn written_back_arg<'b>(
+  callee: &swc_ecma_ast::MemberExpr,
+  args: &'b [swc_ecma_ast::ExprOrSpread],
+) -> Option<&'b swc_ecma_ast::Expr> {
+  use swc_ecma_ast::{Expr, MemberProp};
+
+  match (&*callee.obj, &callee.prop) {
+    (Expr::Ident(obj), MemberProp::Ident(prop)) if obj.sym == *"Object" => {
+      if !matches!(
+        &*prop.sym,
+        "defineProperty" | "defineProperties" | "setPrototypeOf"
+      ) {
+        return None;
+      }
+    }
+    _ => return None,
+  }
+
+  let arg = args.first()?;
+
+  match (&arg.spread, &*arg.expr) {
+    (None, Expr::Ident(_) | Expr::Member(_) | Expr::This(_)) => Some(&arg.expr),
+    _ => None,
+  }
+}
+
+/// The string value of a segment of a template literal, with its escape sequences processed. The
+/// raw text (`quasi.raw`) is only used by tagged templates.
+pub fn cooked_quasi(
+  quasi: &swc_ecma_ast::TplElement,
+  reporter: &impl DiagnosticReporter,
+) -> String {
+  match &quasi.cooked {
+    Some(cooked) => cooked.to_string(),
+    None => {
+      reporter.error(quasi.span, "Invalid escape sequence in template literal");
+      quasi.raw.to_string()
+    }
+  }
+}
+
 pub fn make_unary_op(op: swc_ecma_ast::UnaryOp, arg: Value, dst: Register) -> Option<Instruction> {
   use swc_ecma_ast::UnaryOp::*;
 
@@ -1555,8 +2492,24 @@ pub fn make_unary_op(op: swc_ecma_ast::UnaryOp, arg: Value, dst: Register) -> Op
     Bang => Some(Instruction::OpNot(arg, dst)),
     Tilde => Some(Instruction::OpBitNot(arg, dst)),
     TypeOf => Some(Instruction::TypeOf(arg, dst)),
-    Void => None,   // TODO
-    Delete => None, // TODO
+    // The operand has already been evaluated, so all that's left is the result
+    Void => Some(Instruction::Mov(Value::Undefined, dst)),
+
+    // Needs the operand as a target rather than a value, see delete_expression
+    Delete => None,
+  }
+}
+
+fn get_delete_member(expr: &swc_ecma_ast::Expr) -> Option<&swc_ecma_ast::MemberExpr> {
+  use swc_ecma_ast::Expr::*;
+
+  match expr {
+    Member(member) => Some(member),
+    Paren(e) => get_delete_member(&e.expr),
+    TsTypeAssertion(e) => get_delete_member(&e.expr),
+    TsNonNull(e) => get_delete_member(&e.expr),
+    TsAs(e) => get_delete_member(&e.expr),
+    _ => None,
   }
 }
 
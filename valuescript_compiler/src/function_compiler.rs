@@ -5,8 +5,8 @@ use std::mem::take;
 use swc_common::Spanned;
 
 use crate::asm::{
-  Builtin, ContentHashable, Definition, DefinitionContent, FnLine, Function, Instruction, Label,
-  Meta, Pointer, Register, Value,
+  Array, Builtin, ContentHashable, Definition, DefinitionContent, FnLine, Function, Instruction,
+  Label, Meta, Pointer, Register, Value,
 };
 use crate::diagnostic::{Diagnostic, DiagnosticContainer, DiagnosticReporter};
 use crate::expression_compiler::CompiledExpression;
@@ -22,7 +22,13 @@ use crate::src_hash::src_hash;
 pub enum Functionish {
   Fn(Option<swc_ecma_ast::Ident>, swc_ecma_ast::Function),
   Arrow(swc_ecma_ast::ArrowExpr),
-  Constructor(Vec<FnLine>, OwnerId, swc_ecma_ast::Constructor),
+  /// Instance fields, class owner, constructor, and the class being extended (if any)
+  Constructor(
+    Vec<swc_ecma_ast::ClassProp>,
+    OwnerId,
+    swc_ecma_ast::Constructor,
+    Option<Value>,
+  ),
 }
 
 impl Functionish {
@@ -30,10 +36,31 @@ impl Functionish {
     match self {
       Functionish::Fn(ident, fn_) => fn_to_owner_id(ident.as_ref(), fn_),
       Functionish::Arrow(arrow) => OwnerId::Span(arrow.span),
-      Functionish::Constructor(_, owner_id, _) => owner_id.clone(),
+      Functionish::Constructor(_, owner_id, _, _) => owner_id.clone(),
     }
   }
 
+  pub fn span(&self) -> swc_common::Span {
+    match self {
+      Functionish::Fn(_, fn_) => fn_.span,
+      Functionish::Arrow(arrow) => arrow.span,
+      Functionish::Constructor(_, _, constructor, _) => constructor.span,
+    }
+  }
+
+  pub fn has_rest_param(&self) -> bool {
+    let last_pat = match self {
+      Functionish::Fn(_, fn_) => fn_.params.last().map(|p| &p.pat),
+      Functionish::Arrow(arrow) => arrow.params.last(),
+      Functionish::Constructor(_, _, constructor, _) => match constructor.params.last() {
+        Some(swc_ecma_ast::ParamOrTsParamProp::Param(p)) => Some(&p.pat),
+        _ => None,
+      },
+    };
+
+    matches!(last_pat, Some(swc_ecma_ast::Pat::Rest(_)))
+  }
+
   pub fn meta(&self, mc: &ModuleCompiler) -> Meta {
     match self {
       Functionish::Fn(ident, fn_) => Meta {
@@ -52,7 +79,7 @@ impl Functionish {
           mc.scope_analysis.get_deps(arrow.span),
         ),
       },
-      Functionish::Constructor(_, _, _constructor) => Meta {
+      Functionish::Constructor(..) => Meta {
         name: "".to_string(),                     // TODO: Use class name?
         content_hashable: ContentHashable::Empty, // TODO
       },
@@ -60,6 +87,16 @@ impl Functionish {
   }
 }
 
+/// Where `super.x` looks up `x` within a class member.
+#[derive(Clone, Debug)]
+pub enum SuperHome {
+  /// Instance methods and constructors use the prototype of the base class
+  Prototype(Value),
+
+  /// Static methods use the base class itself
+  Static(Value),
+}
+
 #[derive(Clone, Debug)]
 pub struct QueuedFunction {
   pub definition_pointer: Pointer,
@@ -70,6 +107,28 @@ pub struct QueuedFunction {
 pub struct LoopLabels {
   pub continue_: Option<Label>,
   pub break_: Label,
+
+  /// The labels of the statement (eg `outer` for `outer: for (...) {}`), for labeled break and
+  /// continue
+  pub names: Vec<String>,
+
+  /// Whether unlabeled breaks exit to break_, which is false for labeled blocks since only
+  /// `break label` exits them
+  pub unlabeled_break: bool,
+}
+
+/// What a for-in/of loop iterates over.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum IterationKind {
+  /// The values of a for-of loop.
+  Values,
+
+  /// The keys of a for-in loop, from the ForInKeys builtin.
+  Keys,
+
+  /// The settled values of a for-await loop. These come from the async iterator if there is one,
+  /// and otherwise from the (sync) iterator, with each value being awaited.
+  AwaitedValues,
 }
 
 pub struct CatchSetting {
@@ -84,10 +143,28 @@ pub struct FunctionCompiler<'a> {
   pub reg_allocator: RegAllocator,
   pub label_allocator: NameAllocator,
   pub loop_labels: Vec<LoopLabels>,
+
+  /// Labels of a labeled loop that is about to be compiled, which are added to its LoopLabels
+  pub pending_label_names: Vec<String>,
+
   pub catch_settings: Vec<CatchSetting>,
   pub end_label: Option<Label>,
   pub is_returning_register: Option<Register>,
   pub finally_labels: Vec<Label>,
+  pub is_arrow: bool,
+
+  /// The class being extended when compiling a derived class constructor
+  pub super_class: Option<Value>,
+
+  /// Set when compiling a member of a derived class (or an arrow function within one), for
+  /// `super.x`
+  pub super_home: Option<SuperHome>,
+
+  /// Code that runs immediately after `super(...)` in a derived class constructor (parameter
+  /// properties and member initializers)
+  pub post_super_initializers: Vec<FnLine>,
+
+  pub super_called: bool,
 }
 
 impl<'a> DiagnosticContainer for FunctionCompiler<'a> {
@@ -105,10 +182,16 @@ impl<'a> FunctionCompiler<'a> {
       reg_allocator: RegAllocator::default(),
       label_allocator: NameAllocator::default(),
       loop_labels: vec![],
+      pending_label_names: vec![],
       catch_settings: vec![],
       end_label: None,
       is_returning_register: None,
       finally_labels: vec![],
+      is_arrow: false,
+      super_class: None,
+      super_home: None,
+      post_super_initializers: vec![],
+      super_called: false,
     }
   }
 
@@ -193,6 +276,16 @@ impl<'a> FunctionCompiler<'a> {
     self.reg_allocator.allocate_numbered_fresh(prefix)
   }
 
+  /// Suspends until `value` has settled, and puts the result in `dst`. Async functions are driven
+  /// like generators, so their awaits are yields. Async generators need yield for yielding, so
+  /// their awaits use the await instruction instead.
+  pub fn await_(&mut self, value: Value, dst: Register) {
+    self.push(match self.fn_.is_generator {
+      false => Instruction::Yield(value, dst),
+      true => Instruction::Await(value, dst),
+    });
+  }
+
   pub fn release_reg(&mut self, reg: &Register) {
     // Note: We no longer release registers back into the name allocator. See
     // `NameAllocator::release` for more information.
@@ -229,6 +322,20 @@ impl<'a> FunctionCompiler<'a> {
       Functionish::Constructor(..) => false,
     };
 
+    // Async functions are compiled like generators, where each await is a yield (see await_)
+    self.fn_.is_async = match &functionish {
+      Functionish::Fn(_, fn_) => fn_.is_async,
+      Functionish::Arrow(arrow_expr) => arrow_expr.is_async,
+      Functionish::Constructor(..) => false,
+    };
+
+    self.is_arrow = matches!(functionish, Functionish::Arrow(_));
+
+    if let Functionish::Constructor(_, _, _, super_class) = &functionish {
+      self.super_class = super_class.clone();
+      self.super_home = super_class.clone().map(SuperHome::Prototype);
+    }
+
     let meta_ptr = self
       .mc
       .allocate_defn(&format!("{}_meta", definition_pointer.name));
@@ -268,6 +375,8 @@ impl<'a> FunctionCompiler<'a> {
       self.fn_.parameters.push(reg.clone());
     }
 
+    self.fn_.has_rest_param = functionish.has_rest_param();
+
     self.add_param_code(&functionish, &param_registers);
 
     match functionish {
@@ -292,9 +401,18 @@ impl<'a> FunctionCompiler<'a> {
           expression_compiler.compile_into(expr, Register::return_());
         }
       },
-      Functionish::Constructor(member_initializers_assembly, _class_span, constructor) => {
-        let mut mia_copy = member_initializers_assembly.clone();
-        self.fn_.body.append(&mut mia_copy);
+      Functionish::Constructor(fields, _class_span, constructor, _) => {
+        let fields_start = self.fn_.body.len();
+
+        for field in &fields {
+          self.field_initializer(field, Register::this());
+        }
+
+        if self.super_class.is_some() {
+          // `this` isn't available until `super(...)` returns
+          let mut field_initializers = self.fn_.body.split_off(fields_start);
+          self.post_super_initializers.append(&mut field_initializers);
+        }
 
         match &constructor.body {
           Some(block) => {
@@ -304,8 +422,25 @@ impl<'a> FunctionCompiler<'a> {
           // This case is constructed artificially when there is no explicit constructor but there
           // are member initializer expressions which need to be compiled into a constructor. I'm
           // not sure whether SWC ever produces this case.
-          None => {}
+          None => {
+            if let Some(super_class) = self.super_class.clone() {
+              // Synthesize `constructor(...args) { super(...args); }`
+              let args = self.allocate_reg("args");
+              self.fn_.parameters.push(args.clone());
+              self.fn_.has_rest_param = true;
+
+              let mut ec = ExpressionCompiler { fnc: self };
+              ec.apply_super_constructor(super_class, Value::Register(args));
+            }
+          }
         };
+
+        if self.super_class.is_some() && !self.super_called {
+          self.error(
+            constructor.span,
+            "Derived class constructor must call super()",
+          );
+        }
       }
     };
 
@@ -330,6 +465,8 @@ impl<'a> FunctionCompiler<'a> {
   }
 
   fn handle_block_body(&mut self, block: &swc_ecma_ast::BlockStmt) {
+    self.lint_unreachable(&block.stmts);
+
     for i in 0..block.stmts.len() {
       self.statement(&block.stmts[i], i == block.stmts.len() - 1);
     }
@@ -349,7 +486,7 @@ impl<'a> FunctionCompiler<'a> {
           param_registers.push(self.get_pattern_register_opt(p));
         }
       }
-      Functionish::Constructor(_, _class_span, constructor) => {
+      Functionish::Constructor(_, _class_span, constructor, _) => {
         for potspp in &constructor.params {
           match potspp {
             swc_ecma_ast::ParamOrTsParamProp::TsParamProp(ts_param_prop) => {
@@ -390,7 +527,7 @@ impl<'a> FunctionCompiler<'a> {
       Pat::Array(_) => self.allocate_numbered_reg("_array_pat"),
       Pat::Object(_) => self.allocate_numbered_reg("_object_pat"),
       Pat::Invalid(_) => self.allocate_numbered_reg("_invalid_pat"),
-      Pat::Rest(_) => self.allocate_numbered_reg("_rest_pat"),
+      Pat::Rest(rest) => return self.get_pattern_register_opt(&rest.arg),
       Pat::Expr(_) => self.allocate_numbered_reg("_expr_pat"),
     })
   }
@@ -440,7 +577,7 @@ impl<'a> FunctionCompiler<'a> {
           }
         }
       }
-      Functionish::Constructor(_, _class_span, constructor) => {
+      Functionish::Constructor(_, _class_span, constructor, _) => {
         for (i, potspp) in constructor.params.iter().enumerate() {
           match potspp {
             swc_ecma_ast::ParamOrTsParamProp::TsParamProp(tpp) => {
@@ -462,11 +599,19 @@ impl<'a> FunctionCompiler<'a> {
                   }
                 };
 
-                ec.fnc.push(Instruction::SubMov(
+                let init = Instruction::SubMov(
                   Value::String(field_name),
                   Value::Register(reg.clone()),
                   Register::this(),
-                ));
+                );
+
+                match ec.fnc.super_class {
+                  None => ec.fnc.push(init),
+                  Some(_) => ec
+                    .fnc
+                    .post_super_initializers
+                    .push(FnLine::Instruction(init)),
+                }
               }
             }
             swc_ecma_ast::ParamOrTsParamProp::Param(p) => {
@@ -481,7 +626,29 @@ impl<'a> FunctionCompiler<'a> {
     };
   }
 
-  fn statement(&mut self, statement: &swc_ecma_ast::Stmt, fn_last: bool) {
+  /// Assigns a class field to `target`. Used for instance fields in the constructor prologue (on
+  /// `this`) and for static fields that need to be evaluated lazily.
+  pub fn field_initializer(&mut self, field: &swc_ecma_ast::ClassProp, target: Register) {
+    let mut ec = ExpressionCompiler { fnc: self };
+
+    let compiled_key = ec.prop_name(&field.key);
+
+    let compiled_value = match &field.value {
+      None => CompiledExpression::new(Value::Undefined, vec![]),
+      Some(expr) => ec.compile(expr, None),
+    };
+
+    ec.fnc.push(Instruction::SubMov(
+      compiled_key.value.clone(),
+      compiled_value.value.clone(),
+      target,
+    ));
+
+    ec.fnc.release_ce(compiled_key);
+    ec.fnc.release_ce(compiled_value);
+  }
+
+  pub fn statement(&mut self, statement: &swc_ecma_ast::Stmt, fn_last: bool) {
     use swc_ecma_ast::Stmt::*;
 
     match statement {
@@ -498,6 +665,11 @@ impl<'a> FunctionCompiler<'a> {
           Some(expr) => {
             let mut ec = ExpressionCompiler { fnc: self };
             ec.compile_into(expr, Register::return_());
+
+            // Async generators settle their final result too
+            if self.fn_.is_generator && self.fn_.is_async {
+              self.await_(Value::Register(Register::return_()), Register::return_());
+            }
           }
         }
 
@@ -521,29 +693,38 @@ impl<'a> FunctionCompiler<'a> {
         }
       }
 
-      Labeled(labeled) => self.todo(labeled.span, "Labeled statement"),
+      Labeled(labeled) => self.labeled(labeled),
 
       Break(break_) => {
-        if break_.label.is_some() {
-          self.todo(break_.span, "labeled break statement");
-
-          return;
-        }
-
-        let loop_labels = self.loop_labels.last();
+        let loop_labels = match &break_.label {
+          Some(label) => self.find_loop_labels(&label.sym),
+          None => self.loop_labels.iter().rev().find(|ll| ll.unlabeled_break),
+        };
 
         match loop_labels {
           Some(loop_labels) => {
             self.push(Instruction::Jmp(loop_labels.break_.ref_()));
           }
-          None => {
-            self.error(break_.span, "break statement outside loop");
-          }
+          None => match &break_.label {
+            Some(label) => self.error(label.span, &format!("undefined label {}", label.sym)),
+            None => self.error(break_.span, "break statement outside loop"),
+          },
         }
       }
       Continue(continue_) => {
-        if continue_.label.is_some() {
-          self.todo(continue_.span, "labeled continue statement");
+        if let Some(label) = &continue_.label {
+          let continue_label = self
+            .find_loop_labels(&label.sym)
+            .map(|loop_labels| loop_labels.continue_.clone());
+
+          match continue_label {
+            Some(Some(continue_label)) => self.push(Instruction::Jmp(continue_label.ref_())),
+            Some(None) => self.error(
+              label.span,
+              &format!("continue label {} does not refer to a loop", label.sym),
+            ),
+            None => self.error(label.span, &format!("undefined label {}", label.sym)),
+          }
 
           return;
         }
@@ -589,7 +770,9 @@ impl<'a> FunctionCompiler<'a> {
       For(for_) => {
         self.for_(for_);
       }
-      ForIn(for_in) => self.todo(for_in.span, "ForIn statement"),
+      ForIn(for_in) => {
+        self.for_in(for_in);
+      }
       ForOf(for_of) => {
         self.for_of(for_of);
       }
@@ -602,12 +785,93 @@ impl<'a> FunctionCompiler<'a> {
     }
   }
 
+  fn labeled(&mut self, labeled: &swc_ecma_ast::LabeledStmt) {
+    use swc_ecma_ast::Stmt;
+
+    self.pending_label_names.push(labeled.label.sym.to_string());
+
+    match &*labeled.body {
+      // These use the pending label names when they push their LoopLabels
+      Stmt::While(_)
+      | Stmt::DoWhile(_)
+      | Stmt::For(_)
+      | Stmt::ForIn(_)
+      | Stmt::ForOf(_)
+      | Stmt::Switch(_)
+      | Stmt::Labeled(_) => self.statement(&labeled.body, false),
+
+      _ => {
+        let end_label = Label {
+          name: self.label_allocator.allocate_numbered("labeled_end"),
+        };
+
+        self.loop_labels.push(LoopLabels {
+          continue_: None,
+          break_: end_label.clone(),
+          names: std::mem::take(&mut self.pending_label_names),
+          unlabeled_break: false,
+        });
+
+        self.statement(&labeled.body, false);
+
+        self.loop_labels.pop();
+        self.label(end_label);
+      }
+    }
+  }
+
+  pub fn push_loop_labels(&mut self, continue_: Option<Label>, break_: Label) {
+    self.loop_labels.push(LoopLabels {
+      continue_,
+      break_,
+      names: std::mem::take(&mut self.pending_label_names),
+      unlabeled_break: true,
+    });
+  }
+
+  fn find_loop_labels(&self, name: &str) -> Option<&LoopLabels> {
+    self
+      .loop_labels
+      .iter()
+      .rev()
+      .find(|loop_labels| loop_labels.names.iter().any(|n| n == name))
+  }
+
   fn block_statement(&mut self, block: &swc_ecma_ast::BlockStmt) {
+    self.lint_unreachable(&block.stmts);
+
     for stmt in &block.stmts {
       self.statement(stmt, false);
     }
   }
 
+  /// Reports the first statement that can't run because an earlier statement in the same block
+  /// always exits it. Declarations that don't run any code (eg hoisted functions) are ignored.
+  fn lint_unreachable(&self, stmts: &[swc_ecma_ast::Stmt]) {
+    use swc_ecma_ast::{Decl, Stmt};
+
+    let exit_pos = match stmts.iter().position(|stmt| {
+      matches!(
+        stmt,
+        Stmt::Return(_) | Stmt::Throw(_) | Stmt::Break(_) | Stmt::Continue(_)
+      )
+    }) {
+      Some(exit_pos) => exit_pos,
+      None => return,
+    };
+
+    let unreachable = stmts[exit_pos + 1..].iter().find(|stmt| {
+      !matches!(
+        stmt,
+        Stmt::Empty(_) | Stmt::Decl(Decl::Fn(_) | Decl::TsInterface(_) | Decl::TsTypeAlias(_))
+      )
+    });
+
+    if let Some(stmt) = unreachable {
+      self.lint(stmt.span(), "Unreachable code");
+    }
+  }
+
   fn if_(&mut self, if_: &swc_ecma_ast::IfStmt) {
     let mut ec = ExpressionCompiler { fnc: self };
 
@@ -655,10 +919,7 @@ impl<'a> FunctionCompiler<'a> {
       name: ec.fnc.label_allocator.allocate_numbered("sw_end"),
     };
 
-    ec.fnc.loop_labels.push(LoopLabels {
-      continue_: None,
-      break_: end_label.clone(),
-    });
+    ec.fnc.push_loop_labels(None, end_label.clone());
 
     let case_labels = (0..switch.cases.len())
       .map(&mut |i| Label {
@@ -707,6 +968,7 @@ impl<'a> FunctionCompiler<'a> {
 
     for (i, case) in switch.cases.iter().enumerate() {
       ec.fnc.label(case_labels[i].clone());
+      ec.fnc.lint_unreachable(&case.cons);
 
       for stmt in &case.cons {
         ec.fnc.statement(stmt, false);
@@ -765,6 +1027,10 @@ impl<'a> FunctionCompiler<'a> {
         .param
       {
         Some(_) => self.allocate_numbered_reg("_error"),
+
+        // Generators need the error to check for generator.return()
+        None if self.fn_.is_generator => self.allocate_numbered_reg("_error"),
+
         None => Register::ignore(),
       };
 
@@ -806,6 +1072,10 @@ impl<'a> FunctionCompiler<'a> {
       self.label(catch_label.unwrap());
       self.apply_catch_setting(); // TODO: Avoid redundant unset_catch
 
+      if self.fn_.is_generator {
+        self.rethrow_generator_return(catch_error_reg.as_ref().unwrap());
+      }
+
       for (reg, snap_reg) in snap_pairs {
         self.push(Instruction::Mov(Value::Register(snap_reg), reg));
       }
@@ -909,6 +1179,34 @@ impl<'a> FunctionCompiler<'a> {
     }
   }
 
+  /// generator.return() throws the GeneratorReturn symbol through the generator so that finally
+  /// blocks run. Catch clauses must not intercept it, so they start by passing it on.
+  fn rethrow_generator_return(&mut self, error_reg: &Register) {
+    let is_return = self.allocate_numbered_reg("_is_return");
+
+    let after_rethrow_label = Label {
+      name: self.label_allocator.allocate_numbered("after_rethrow"),
+    };
+
+    self.push(Instruction::OpTripleEq(
+      Value::Register(error_reg.clone()),
+      Value::Builtin(Builtin {
+        name: "GeneratorReturn".to_string(),
+      }),
+      is_return.clone(),
+    ));
+
+    self.push(Instruction::JmpIfNot(
+      Value::Register(is_return.clone()),
+      after_rethrow_label.ref_(),
+    ));
+
+    self.push(Instruction::Throw(Value::Register(error_reg.clone())));
+    self.label(after_rethrow_label);
+
+    self.release_reg(&is_return);
+  }
+
   fn apply_catch_setting(&mut self) {
     if let Some(catch_setting) = self.catch_settings.last() {
       self.push(Instruction::SetCatch(
@@ -934,10 +1232,7 @@ impl<'a> FunctionCompiler<'a> {
       name: self.label_allocator.allocate_numbered("while_end"),
     };
 
-    self.loop_labels.push(LoopLabels {
-      continue_: Some(start_label.clone()),
-      break_: end_label.clone(),
-    });
+    self.push_loop_labels(Some(start_label.clone()), end_label.clone());
 
     self.label(start_label.clone());
 
@@ -972,10 +1267,7 @@ impl<'a> FunctionCompiler<'a> {
       name: self.label_allocator.allocate_numbered("do_while_end"),
     };
 
-    self.loop_labels.push(LoopLabels {
-      continue_: Some(continue_label.clone()),
-      break_: end_label.clone(),
-    });
+    self.push_loop_labels(Some(continue_label.clone()), end_label.clone());
 
     self.label(start_label.clone());
 
@@ -1026,10 +1318,7 @@ impl<'a> FunctionCompiler<'a> {
 
     self.label(for_test_label.clone());
 
-    self.loop_labels.push(LoopLabels {
-      continue_: Some(for_continue_label.clone()),
-      break_: for_end_label.clone(),
-    });
+    self.push_loop_labels(Some(for_continue_label.clone()), for_end_label.clone());
 
     match &for_.test {
       Some(cond) => {
@@ -1065,35 +1354,133 @@ impl<'a> FunctionCompiler<'a> {
   }
 
   fn for_of(&mut self, for_of: &swc_ecma_ast::ForOfStmt) {
+    let kind = match for_of.await_token {
+      Some(_) => IterationKind::AwaitedValues,
+      None => IterationKind::Values,
+    };
+
+    self.iteration_loop(&for_of.left, &for_of.right, &for_of.body, kind);
+  }
+
+  fn for_in(&mut self, for_in: &swc_ecma_ast::ForInStmt) {
+    self.iteration_loop(
+      &for_in.left,
+      &for_in.right,
+      &for_in.body,
+      IterationKind::Keys,
+    );
+  }
+
+  /// Compiles a for-of, for-in or for-await loop.
+  fn iteration_loop(
+    &mut self,
+    left: &swc_ecma_ast::VarDeclOrPat,
+    right: &swc_ecma_ast::Expr,
+    body: &swc_ecma_ast::Stmt,
+    kind: IterationKind,
+  ) {
     let mut ec = ExpressionCompiler { fnc: self };
 
-    let pat = match &for_of.left {
+    let (pat, value_reg) = match left {
       swc_ecma_ast::VarDeclOrPat::VarDecl(var_decl) => {
         if var_decl.decls.len() != 1 {
-          panic!("Unexpected number of declarations on left side of for-of loop");
+          ec.fnc.internal_error(
+            var_decl.span,
+            "Unexpected number of declarations on left side of for-in/of loop",
+          );
+
+          return;
         }
 
-        &var_decl.decls[0].name
+        let pat = &var_decl.decls[0].name;
+        (pat, ec.fnc.get_pattern_register(pat))
       }
-      swc_ecma_ast::VarDeclOrPat::Pat(pat) => pat,
-    };
 
-    let value_reg = ec.fnc.get_pattern_register(pat);
+      // Assigning to existing variables, which shouldn't be overwritten when the loop ends, so the
+      // value goes through its own register
+      swc_ecma_ast::VarDeclOrPat::Pat(pat) => (pat, ec.fnc.allocate_numbered_reg("_value")),
+    };
 
     let iter_reg = ec.fnc.allocate_numbered_reg("_iter");
     let iter_res_reg = ec.fnc.allocate_numbered_reg("_iter_res");
     let done_reg = ec.fnc.allocate_numbered_reg("_done");
 
-    ec.compile_into(&for_of.right, iter_reg.clone());
+    ec.compile_into(right, iter_reg.clone());
 
-    ec.fnc.push(Instruction::ConstSubCall(
-      Value::Register(iter_reg.clone()),
-      Value::Builtin(Builtin {
-        name: "SymbolIterator".to_string(),
-      }),
-      Value::Array(Box::default()),
-      iter_reg.clone(),
-    ));
+    if kind == IterationKind::Keys {
+      ec.fnc.push(Instruction::Call(
+        Value::Builtin(Builtin {
+          name: "ForInKeys".to_string(),
+        }),
+        Value::Array(Box::new(Array {
+          values: vec![Value::Register(iter_reg.clone())],
+        })),
+        iter_reg.clone(),
+      ));
+    }
+
+    // Holds the async iterator method in for-await loops, which is falsy when iterating over a sync
+    // iterable instead
+    let async_iter_fn_reg = match kind {
+      IterationKind::AwaitedValues => Some(ec.fnc.allocate_numbered_reg("_async_iter_fn")),
+      _ => None,
+    };
+
+    match &async_iter_fn_reg {
+      None => ec.fnc.push(Instruction::ConstSubCall(
+        Value::Register(iter_reg.clone()),
+        Value::Builtin(Builtin {
+          name: "SymbolIterator".to_string(),
+        }),
+        Value::Array(Box::default()),
+        iter_reg.clone(),
+      )),
+      Some(async_iter_fn_reg) => {
+        let sync_iter_label = Label {
+          name: ec.fnc.label_allocator.allocate_numbered("for_sync_iter"),
+        };
+
+        let iter_ready_label = Label {
+          name: ec.fnc.label_allocator.allocate_numbered("for_iter_ready"),
+        };
+
+        ec.fnc.push(Instruction::Sub(
+          Value::Register(iter_reg.clone()),
+          Value::Builtin(Builtin {
+            name: "SymbolAsyncIterator".to_string(),
+          }),
+          async_iter_fn_reg.clone(),
+        ));
+
+        ec.fnc.push(Instruction::JmpIfNot(
+          Value::Register(async_iter_fn_reg.clone()),
+          sync_iter_label.ref_(),
+        ));
+
+        ec.fnc.push(Instruction::ConstSubCall(
+          Value::Register(iter_reg.clone()),
+          Value::Builtin(Builtin {
+            name: "SymbolAsyncIterator".to_string(),
+          }),
+          Value::Array(Box::default()),
+          iter_reg.clone(),
+        ));
+
+        ec.fnc.push(Instruction::Jmp(iter_ready_label.ref_()));
+        ec.fnc.label(sync_iter_label);
+
+        ec.fnc.push(Instruction::ConstSubCall(
+          Value::Register(iter_reg.clone()),
+          Value::Builtin(Builtin {
+            name: "SymbolIterator".to_string(),
+          }),
+          Value::Array(Box::default()),
+          iter_reg.clone(),
+        ));
+
+        ec.fnc.label(iter_ready_label);
+      }
+    }
 
     let for_test_label = Label {
       name: ec.fnc.label_allocator.allocate_numbered("for_test"),
@@ -1111,22 +1498,72 @@ impl<'a> FunctionCompiler<'a> {
 
     ec.fnc.label(for_test_label.clone());
 
-    ec.fnc.loop_labels.push(LoopLabels {
-      continue_: Some(for_continue_label.clone()),
-      break_: for_end_label.clone(),
-    });
+    // Breaking out of a for-of loop closes the iterator by calling its return method (if any), so
+    // that generators can run their finally blocks
+    let for_break_label = match kind == IterationKind::Keys {
+      true => for_end_label.clone(),
+      false => Label {
+        name: ec.fnc.label_allocator.allocate_numbered("for_break"),
+      },
+    };
+
+    ec.fnc
+      .push_loop_labels(Some(for_continue_label.clone()), for_break_label.clone());
 
     ec.fnc.push(Instruction::JmpIf(
       Value::Register(done_reg.clone()),
       for_end_label.ref_(),
     ));
 
-    ec.pat(pat, &value_reg, true);
+    if let Some(async_iter_fn_reg) = &async_iter_fn_reg {
+      // Values from sync iterators are awaited
+      let value_ready_label = Label {
+        name: ec.fnc.label_allocator.allocate_numbered("for_value_ready"),
+      };
+
+      ec.fnc.push(Instruction::JmpIf(
+        Value::Register(async_iter_fn_reg.clone()),
+        value_ready_label.ref_(),
+      ));
 
-    self.statement(&for_of.body, false);
+      ec.fnc
+        .await_(Value::Register(value_reg.clone()), value_reg.clone());
+
+      ec.fnc.label(value_ready_label);
+    }
+
+    match (left, pat) {
+      (swc_ecma_ast::VarDeclOrPat::Pat(_), swc_ecma_ast::Pat::Ident(_)) => {
+        let ident_reg = ec.fnc.get_pattern_register(pat);
+
+        ec.fnc.push(Instruction::Mov(
+          Value::Register(value_reg.clone()),
+          ident_reg,
+        ));
+      }
+      _ => ec.pat(pat, &value_reg, true),
+    }
+
+    self.statement(body, false);
 
     self.label(for_continue_label);
-    self.push(Instruction::Next(iter_reg, iter_res_reg.clone()));
+    self.push(Instruction::Next(iter_reg.clone(), iter_res_reg.clone()));
+
+    if let Some(async_iter_fn_reg) = &async_iter_fn_reg {
+      // Async iterators give a promise for each iteration result
+      let res_ready_label = Label {
+        name: self.label_allocator.allocate_numbered("for_res_ready"),
+      };
+
+      self.push(Instruction::JmpIfNot(
+        Value::Register(async_iter_fn_reg.clone()),
+        res_ready_label.ref_(),
+      ));
+
+      self.await_(Value::Register(iter_res_reg.clone()), iter_res_reg.clone());
+
+      self.label(res_ready_label);
+    }
 
     self.push(Instruction::UnpackIterRes(
       iter_res_reg.clone(),
@@ -1138,6 +1575,49 @@ impl<'a> FunctionCompiler<'a> {
 
     self.push(Instruction::Jmp(for_test_label.ref_()));
 
+    if kind != IterationKind::Keys {
+      self.label(for_break_label);
+
+      let return_fn_reg = self.allocate_numbered_reg("_return_fn");
+
+      self.push(Instruction::Sub(
+        Value::Register(iter_reg.clone()),
+        Value::String("return".to_string()),
+        return_fn_reg.clone(),
+      ));
+
+      self.push(Instruction::JmpIfNot(
+        Value::Register(return_fn_reg.clone()),
+        for_end_label.ref_(),
+      ));
+
+      let return_res_reg = match kind {
+        IterationKind::AwaitedValues => self.allocate_numbered_reg("_return_res"),
+        _ => Register::ignore(),
+      };
+
+      self.push(Instruction::SubCall(
+        iter_reg,
+        Value::String("return".to_string()),
+        Value::Array(Box::default()),
+        return_res_reg.clone(),
+      ));
+
+      if kind == IterationKind::AwaitedValues {
+        self.await_(
+          Value::Register(return_res_reg.clone()),
+          return_res_reg.clone(),
+        );
+        self.release_reg(&return_res_reg);
+      }
+
+      self.release_reg(&return_fn_reg);
+    }
+
+    if let Some(async_iter_fn_reg) = &async_iter_fn_reg {
+      self.release_reg(async_iter_fn_reg);
+    }
+
     self.label(for_end_label);
 
     self.loop_labels.pop();
@@ -1179,11 +1659,7 @@ impl<'a> FunctionCompiler<'a> {
       TsInterface(interface_decl) => self.todo(interface_decl.span, "TsInterface declaration"),
       TsTypeAlias(_) => {}
       TsEnum(ts_enum) => {
-        let pointer = match self
-          .mc
-          .scope_analysis
-          .lookup_value(&OwnerId::Module, &Ident::from_swc_ident(&ts_enum.id))
-        {
+        let pointer = match self.lookup_value(&Ident::from_swc_ident(&ts_enum.id)) {
           Some(Value::Pointer(p)) => p,
           _ => {
             self.internal_error(
@@ -1295,6 +1771,13 @@ fn instruction_needs_mutable_this(
     return dst.is_this();
   }
 
+  if let Instruction::Apply(_, this, _, dst) = instruction {
+    // Similarly, applying a function to `this` (eg `super.method()`) propagates constness
+    if this.is_this() {
+      return dst.is_this();
+    }
+  }
+
   let mut result = false;
 
   instruction.visit_registers_mut_rev(&mut |rvm| {
@@ -3,10 +3,11 @@ use std::collections::{HashMap, HashSet};
 use queues::{IsQueue, Queue};
 
 use crate::{
-  asm::Module,
+  asm::{DefinitionContent, FnLine, Instruction, Module, Value},
   compile_module,
   import_pattern::ImportPattern,
-  resolve_path::{resolve_path, ResolvedPath},
+  resolve_package::{is_bare_specifier, resolve_package},
+  resolve_path::{resolve_path, resolve_path_alias, PathAlias, ResolvedPath},
   Diagnostic, DiagnosticLevel,
 };
 
@@ -36,25 +37,51 @@ pub struct GatheredModules {
 }
 
 pub fn gather_modules<ReadFile>(entry_point: ResolvedPath, read_file: ReadFile) -> GatheredModules
+where
+  ReadFile: Fn(&str) -> Result<String, String>,
+{
+  gather_modules_from_entry_points(&[entry_point], read_file, &[])
+}
+
+/// Like `gather_modules`, but for several entry points (eg when bundling). The first one is used
+/// as the `entry_point` of the result. Imports that match `path_aliases` are rewritten to refer to
+/// the alias targets.
+pub fn gather_modules_from_entry_points<ReadFile>(
+  entry_points: &[ResolvedPath],
+  read_file: ReadFile,
+  path_aliases: &[PathAlias],
+) -> GatheredModules
 where
   ReadFile: Fn(&str) -> Result<String, String>,
 {
   let mut gm = GatheredModules {
-    entry_point,
+    entry_point: entry_points.first().cloned().unwrap_or(ResolvedPath {
+      path: "".to_string(),
+    }),
     modules: HashMap::new(),
     diagnostics: HashMap::new(),
   };
 
   let mut dependencies = Queue::<Dependency>::new();
 
-  dependencies
-    .add(Dependency {
-      path: gm.entry_point.clone(),
+  for entry_point in entry_points {
+    if let Err(err) = dependencies.add(Dependency {
+      path: entry_point.clone(),
       reason: DependencyReason::EntryPoint,
-    })
-    .expect("Failed to add to queue");
+    }) {
+      gm.diagnostics
+        .entry(entry_point.clone())
+        .or_default()
+        .push(queue_error(err));
+    }
+  }
 
   while let Ok(dependency) = dependencies.remove() {
+    // A module can be queued more than once if it's imported again before it's processed
+    if gm.modules.contains_key(&dependency.path) {
+      continue;
+    }
+
     let file_contents = match read_file(&dependency.path.path) {
       Ok(file_contents) => file_contents,
       Err(err) => {
@@ -78,6 +105,12 @@ where
     };
 
     let mut compiler_output = compile_module(&file_contents);
+    rewrite_import_paths(
+      &mut compiler_output.module,
+      &dependency.path,
+      path_aliases,
+      &read_file,
+    );
     // println!("{}: {}", dependency.path, compiler_output.module);
 
     gm.diagnostics
@@ -97,12 +130,15 @@ where
         continue;
       }
 
-      dependencies
-        .add(Dependency {
-          path: imported_path,
-          reason: DependencyReason::ImportedBy(dependency.path.clone()),
-        })
-        .expect("Failed to add to queue");
+      if let Err(err) = dependencies.add(Dependency {
+        path: imported_path,
+        reason: DependencyReason::ImportedBy(dependency.path.clone()),
+      }) {
+        gm.diagnostics
+          .entry(dependency.path.clone())
+          .or_default()
+          .push(queue_error(err));
+      }
     }
 
     gm.modules.insert(dependency.path, path_and_module);
@@ -111,6 +147,49 @@ where
   gm
 }
 
+fn queue_error(err: &str) -> Diagnostic {
+  Diagnostic::internal_error(
+    swc_common::DUMMY_SP,
+    &format!("Failed to add to queue: {}", err),
+  )
+}
+
+/// Rewrites imports that use path aliases or refer to packages so that they refer to the resolved
+/// path instead. Other imports are left relative to the importer.
+fn rewrite_import_paths<ReadFile>(
+  module: &mut Module,
+  importer_path: &ResolvedPath,
+  path_aliases: &[PathAlias],
+  read_file: &ReadFile,
+) where
+  ReadFile: Fn(&str) -> Result<String, String>,
+{
+  for definition in &mut module.definitions {
+    if ImportPattern::decode(definition).is_none() {
+      continue;
+    }
+
+    let import_string = match &mut definition.content {
+      DefinitionContent::Lazy(lazy) => match lazy.body.first_mut() {
+        Some(FnLine::Instruction(
+          Instruction::Import(Value::String(string), _)
+          | Instruction::ImportStar(Value::String(string), _),
+        )) => string,
+        _ => continue,
+      },
+      _ => continue,
+    };
+
+    if let Some(target) = resolve_path_alias(import_string, path_aliases) {
+      *import_string = target;
+    } else if is_bare_specifier(import_string) {
+      if let Some(resolved) = resolve_package(importer_path, import_string, read_file) {
+        *import_string = resolved.path;
+      }
+    }
+  }
+}
+
 pub fn get_imported_paths(path_and_module: &PathAndModule) -> HashSet<ResolvedPath> {
   let mut imported_paths = HashSet::<ResolvedPath>::new();
 
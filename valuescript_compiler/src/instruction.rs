@@ -63,6 +63,9 @@ pub enum Instruction {
   Cat(Value, Register),
   Yield(Value, Register),
   YieldStar(Value, Register),
+  Await(Value, Register),
+  NewTarget(Register),
+  Delete(Register, Value, Register),
 }
 
 pub enum InstructionFieldMut<'a> {
@@ -122,7 +125,8 @@ impl Instruction {
       | ImportStar(arg, dst)
       | Cat(arg, dst)
       | Yield(arg, dst)
-      | YieldStar(arg, dst) => {
+      | YieldStar(arg, dst)
+      | Await(arg, dst) => {
         visit(InstructionFieldMut::Value(arg));
         visit(InstructionFieldMut::Register(dst));
       }
@@ -217,6 +221,16 @@ impl Instruction {
         visit(InstructionFieldMut::Register(done_dst));
       }
 
+      NewTarget(dst) => {
+        visit(InstructionFieldMut::Register(dst));
+      }
+
+      Delete(obj, key, dst) => {
+        visit(InstructionFieldMut::Register(obj));
+        visit(InstructionFieldMut::Value(key));
+        visit(InstructionFieldMut::Register(dst));
+      }
+
       UnsetCatch | RequireMutableThis => {}
     }
   }
@@ -239,7 +253,8 @@ impl Instruction {
       | ImportStar(arg, dst)
       | Cat(arg, dst)
       | Yield(arg, dst)
-      | YieldStar(arg, dst) => {
+      | YieldStar(arg, dst)
+      | Await(arg, dst) => {
         visit(RegisterVisitMut::write(dst));
         arg.visit_registers_mut_rev(visit);
       }
@@ -350,6 +365,16 @@ impl Instruction {
         visit(RegisterVisitMut::read(iter_res));
       }
 
+      NewTarget(dst) => {
+        visit(RegisterVisitMut::write(dst));
+      }
+
+      Delete(obj, key, dst) => {
+        visit(RegisterVisitMut::write(dst));
+        key.visit_registers_mut_rev(visit);
+        visit(RegisterVisitMut::read_and_write(obj));
+      }
+
       UnsetCatch | RequireMutableThis => {}
     }
   }
@@ -418,6 +443,9 @@ impl Instruction {
       Cat(..) => InstructionByte::Cat,
       Yield(..) => InstructionByte::Yield,
       YieldStar(..) => InstructionByte::YieldStar,
+      Await(..) => InstructionByte::Await,
+      NewTarget(..) => InstructionByte::NewTarget,
+      Delete(..) => InstructionByte::Delete,
     }
   }
 }
@@ -582,6 +610,13 @@ impl StructuredFormattable for Instruction {
       Instruction::YieldStar(value, register) => {
         sf.write_slice_joined(" ", &[&"yield*", value, register])
       }
+      Instruction::Await(value, register) => {
+        sf.write_slice_joined(" ", &[&"await", value, register])
+      }
+      Instruction::NewTarget(register) => sf.write_slice_joined(" ", &[&"new_target", register]),
+      Instruction::Delete(obj, key, register) => {
+        sf.write_slice_joined(" ", &[&"delete", obj, key, register])
+      }
     }
   }
 }
@@ -1,9 +1,12 @@
 pub mod asm;
 mod assembler;
 pub mod assembly_parser;
+mod bundle;
 mod compile;
+mod compile_expr;
 mod constants;
 mod diagnostic;
+mod disassembler;
 mod expression_compiler;
 mod function_compiler;
 mod gather_modules;
@@ -11,9 +14,11 @@ mod ident;
 mod import_pattern;
 mod instruction;
 mod link_module;
+mod minify_names;
 mod module_compiler;
 mod name_allocator;
 mod optimization;
+mod resolve_package;
 mod resolve_path;
 mod scope;
 mod scope_analysis;
@@ -23,15 +28,44 @@ mod target_accessor;
 mod visit_pointers;
 
 pub use assembler::assemble;
+pub use assembler::assemble_with_listing;
+pub use assembler::Listing;
 pub use assembly_parser::parse_module;
+pub use bundle::bundle;
+pub use bundle::BundleEntry;
+pub use bundle::BundleManifest;
+pub use bundle::BundleResult;
 pub use compile::compile;
+pub use compile::compile_with_options;
+pub use compile::CompileOptions;
 pub use compile::CompileResult;
+pub use compile_expr::compile_expr;
 pub use diagnostic::Diagnostic;
 pub use diagnostic::DiagnosticLevel;
+pub use disassembler::disassemble;
 pub use gather_modules::gather_modules;
+pub use gather_modules::gather_modules_from_entry_points;
+pub use link_module::link_assembly;
 pub use link_module::link_module;
+pub use minify_names::minify_names;
+pub use minify_names::NameMap;
 pub use module_compiler::compile_module;
+pub use module_compiler::compile_module_with_options;
+pub use module_compiler::compile_program;
+pub use module_compiler::parse;
 pub use module_compiler::CompilerOutput;
 pub use optimization::try_to_val::TryToVal;
 pub use resolve_path::resolve_path;
+pub use resolve_path::resolve_path_alias;
+pub use resolve_path::PathAlias;
 pub use resolve_path::ResolvedPath;
+pub use scope::NameId;
+pub use scope::OwnerId;
+pub use scope_analysis::Capture;
+pub use scope_analysis::Name;
+pub use scope_analysis::NameType;
+pub use scope_analysis::Ref;
+pub use scope_analysis::ScopeAnalysis;
+
+// Re-exported so that callers of compile_program can build the AST with the matching version
+pub use swc_ecma_ast;
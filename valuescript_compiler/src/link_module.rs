@@ -1,11 +1,11 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::mem::swap;
+use std::mem::{replace, swap};
 
 use tiny_keccak::{Hasher, Keccak};
 
 use crate::asm::{
-  ContentHashable, Definition, DefinitionContent, ExportStar, FnLine, Hash, Instruction, Object,
-  Pointer, Structured, Value,
+  ContentHashable, Definition, DefinitionContent, ExportStar, FnLine, Hash, Instruction,
+  InstructionFieldMut, Lazy, Object, Pointer, Structured, Value,
 };
 use crate::gather_modules::PathAndModule;
 use crate::import_pattern::{ImportKind, ImportPattern};
@@ -121,6 +121,33 @@ pub fn link_module(
   result
 }
 
+/// Links modules that were written in assembly rather than compiled (eg `vstc assemble` with
+/// several inputs). The first module is the entry point, and the `import` instructions of each
+/// module can refer to the others by path. Pointers are renamed as needed so that modules can
+/// reuse the same names.
+pub fn link_assembly(modules: Vec<(ResolvedPath, Module)>) -> LinkModuleResult {
+  let entry_point = match modules.first() {
+    Some((path, _)) => path.clone(),
+    None => {
+      return LinkModuleResult {
+        module: None,
+        diagnostics: vec![Diagnostic {
+          level: DiagnosticLevel::Error,
+          message: "No modules to link".to_string(),
+          span: swc_common::DUMMY_SP,
+        }],
+      }
+    }
+  };
+
+  let modules = modules
+    .into_iter()
+    .map(|(path, module)| (path.clone(), PathAndModule { path, module }))
+    .collect::<HashMap<_, _>>();
+
+  link_module(&entry_point, &modules)
+}
+
 fn rewrite_pointers(module: &mut Module, pointer_allocator: &mut NameAllocator) {
   let mut pointer_map = HashMap::<Pointer, Pointer>::new();
 
@@ -477,10 +504,38 @@ fn find_ptr_src_trace(
     }
     DefinitionContent::Meta(_fn_meta) => None,
     DefinitionContent::Value(value) => find_value_src_trace(module, ptr_to_index, value),
-    DefinitionContent::Lazy(_) => None,
+    DefinitionContent::Lazy(lazy) => Some(lazy_src_trace(lazy)),
   }
 }
 
+/// Lazy values don't have a source hash, so they're traced by their assembly instead. The pointers
+/// they use are replaced with a placeholder and become deps, so that their names don't affect the
+/// trace.
+fn lazy_src_trace(lazy: &Lazy) -> (String, Vec<Value>) {
+  let mut lazy = lazy.clone();
+  let mut deps = Vec::<Value>::new();
+
+  for fn_line in &mut lazy.body {
+    if let FnLine::Instruction(instr) = fn_line {
+      instr.visit_fields_mut(&mut |field| {
+        if let InstructionFieldMut::Value(value) = field {
+          value.visit_values_mut(&mut |value| {
+            if let Value::Pointer(p) = value {
+              let placeholder = Pointer {
+                name: "dep".to_string(),
+              };
+
+              deps.push(Value::Pointer(replace(p, placeholder)));
+            }
+          });
+        }
+      });
+    }
+  }
+
+  (Structured(&lazy).to_string(), deps)
+}
+
 fn find_value_src_trace(
   module: &Module,
   ptr_to_index: &HashMap<Pointer, usize>,
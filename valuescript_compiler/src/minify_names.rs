@@ -0,0 +1,129 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::asm::{DefinitionContent, FnLine, Module, Pointer, Register};
+use crate::visit_pointers::{visit_pointers, PointerVisitation};
+
+/// The original names of everything renamed by `minify_names`, keyed by the new names.
+#[derive(Default, Debug, Clone)]
+pub struct NameMap {
+  pub pointers: BTreeMap<String, String>,
+
+  /// Registers of each function (and lazy value), keyed by the new name of its definition.
+  pub registers: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl std::fmt::Display for NameMap {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for (new_name, original_name) in &self.pointers {
+      writeln!(f, "@{} @{}", new_name, original_name)?;
+
+      for (new_reg, original_reg) in self.registers.get(new_name).into_iter().flatten() {
+        writeln!(f, "  %{} %{}", new_reg, original_reg)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Renames definitions and registers to short machine names to make the output smaller. The names
+/// from the source are still available in the returned map (and in the function metadata).
+pub fn minify_names(module: &mut Module) -> NameMap {
+  let mut name_map = NameMap::default();
+  let mut new_pointers = HashMap::<Pointer, Pointer>::new();
+
+  for definition in &module.definitions {
+    let name = short_name(new_pointers.len());
+
+    name_map
+      .pointers
+      .insert(name.clone(), definition.pointer.name.clone());
+
+    new_pointers.insert(definition.pointer.clone(), Pointer { name });
+  }
+
+  visit_pointers(module, |visitation| match visitation {
+    PointerVisitation::Export(pointer)
+    | PointerVisitation::Definition(pointer)
+    | PointerVisitation::Reference(_, pointer) => {
+      if let Some(new_pointer) = new_pointers.get(pointer) {
+        *pointer = new_pointer.clone();
+      }
+    }
+  });
+
+  for definition in &mut module.definitions {
+    let registers = match &mut definition.content {
+      DefinitionContent::Function(function) => {
+        minify_registers(&mut function.parameters, &mut function.body)
+      }
+      DefinitionContent::Lazy(lazy) => minify_registers(&mut [], &mut lazy.body),
+      DefinitionContent::Value(_) | DefinitionContent::Meta(_) => continue,
+    };
+
+    if !registers.is_empty() {
+      name_map
+        .registers
+        .insert(definition.pointer.name.clone(), registers);
+    }
+  }
+
+  name_map
+}
+
+fn minify_registers(parameters: &mut [Register], body: &mut [FnLine]) -> BTreeMap<String, String> {
+  let mut new_names = HashMap::<String, String>::new();
+  let mut next_i = 0;
+
+  let mut rename = |register: &mut Register| {
+    if register.is_special() {
+      return;
+    }
+
+    let new_name = new_names
+      .entry(register.name.clone())
+      .or_insert_with(|| loop {
+        let candidate = Register::named(short_name(next_i));
+        next_i += 1;
+
+        if !candidate.is_special() {
+          break candidate.name;
+        }
+      });
+
+    register.name = new_name.clone();
+  };
+
+  for param in parameters {
+    rename(param);
+  }
+
+  for line in body {
+    match line {
+      FnLine::Instruction(instruction) => {
+        instruction.visit_registers_mut_rev(&mut |rvm| rename(rvm.register));
+      }
+      FnLine::Release(register) => rename(register),
+      FnLine::Label(_) | FnLine::Empty | FnLine::Comment(_) => {}
+    }
+  }
+
+  new_names
+    .into_iter()
+    .map(|(original_name, new_name)| (new_name, original_name))
+    .collect()
+}
+
+/// a, b, ..., z, aa, ab, ...
+fn short_name(i: usize) -> String {
+  let mut chars = Vec::<char>::new();
+  let mut i = i + 1;
+
+  while i > 0 {
+    i -= 1;
+    chars.push((b'a' + (i % 26) as u8) as char);
+    i /= 26;
+  }
+
+  chars.iter().rev().collect()
+}
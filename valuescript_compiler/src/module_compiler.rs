@@ -1,25 +1,34 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::mem::take;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
 
 use swc_common::errors::{DiagnosticBuilder, Emitter};
 use swc_common::{errors::Handler, FileName, SourceMap, Spanned};
 use swc_ecma_ast::EsVersion;
 use swc_ecma_parser::{Syntax, TsConfig};
+use valuescript_vm::operations::{to_i32, to_u32};
 
 use crate::asm::{
-  Class, ContentHashable, Definition, DefinitionContent, FnLine, Instruction, Lazy, Meta, Module,
-  Number, Object, Pointer, Register, Structured, Value,
+  Class, ContentHashable, Definition, DefinitionContent, FnLine, Function, Instruction,
+  InstructionFieldMut, Lazy, Meta, Module, Number, Object, Pointer, Register, Structured, Value,
 };
-use crate::diagnostic::{Diagnostic, DiagnosticContainer, DiagnosticReporter};
-use crate::expression_compiler::{CompiledExpression, ExpressionCompiler};
-use crate::function_compiler::{FunctionCompiler, Functionish};
+use crate::compile::CompileOptions;
+use crate::diagnostic::{
+  remove_ignored_diagnostics, Diagnostic, DiagnosticContainer, DiagnosticLevel, DiagnosticReporter,
+};
+use crate::expression_compiler::ExpressionCompiler;
+use crate::function_compiler::{FunctionCompiler, Functionish, SuperHome};
 use crate::ident::Ident;
+use crate::minify_names::{minify_names, NameMap};
 use crate::name_allocator::{ident_from_str, NameAllocator};
 use crate::scope::OwnerId;
-use crate::scope_analysis::{class_to_owner_id, ScopeAnalysis};
+use crate::scope_analysis::{
+  class_to_owner_id, is_module_init_stmt, module_init_owner_id, ScopeAnalysis,
+};
 use crate::src_hash::src_hash;
-use crate::static_expression_compiler::StaticExpressionCompiler;
+use crate::static_expression_compiler::{is_static_expr, StaticExpressionCompiler};
 
 struct DiagnosticCollector {
   diagnostics: Arc<Mutex<Vec<Diagnostic>>>,
@@ -33,7 +42,109 @@ impl Emitter for DiagnosticCollector {
   }
 }
 
+/// How many statements can be blanked out to recover from syntax errors before giving up.
+const MAX_PARSE_RECOVERIES: usize = 16;
+
+/// Parses the source, recovering from syntax errors that swc can't recover from itself by blanking
+/// out the top-level statement containing the error and trying again. This means the rest of the
+/// file can still be compiled (and report diagnostics), which is important for editors. Blanking
+/// keeps every other character in place, so spans still refer to the original source.
 pub fn parse(source: &str) -> (Option<swc_ecma_ast::Program>, Vec<Diagnostic>) {
+  let mut diagnostics = Vec::<Diagnostic>::new();
+  let mut recovered_source = source.to_string();
+
+  for _ in 0..=MAX_PARSE_RECOVERIES {
+    let (program, attempt_diagnostics) = parse_without_recovery(&recovered_source);
+
+    let error_pos = attempt_diagnostics
+      .iter()
+      .find(|d| d.level == DiagnosticLevel::Error)
+      .map(|d| d.span.lo.0 as usize);
+
+    for diagnostic in attempt_diagnostics {
+      // Errors that swc recovers from are reported again on each attempt
+      let is_duplicate = diagnostics
+        .iter()
+        .any(|d| d.span == diagnostic.span && d.message == diagnostic.message);
+
+      if !is_duplicate {
+        diagnostics.push(diagnostic);
+      }
+    }
+
+    if program.is_some() {
+      return (program, diagnostics);
+    }
+
+    match error_pos {
+      Some(pos) if blank_statement_at(&mut recovered_source, pos) => {}
+      _ => break,
+    }
+  }
+
+  (None, diagnostics)
+}
+
+/// Replaces the top-level statement containing `pos` with whitespace (keeping newlines). Top-level
+/// statements are found by indentation: they start with a line that isn't indented and continue
+/// until the next one, except for lines that only close brackets. Returns false if there was
+/// nothing left to blank.
+fn blank_statement_at(source: &mut String, pos: usize) -> bool {
+  let is_statement_start = |line: &str| {
+    line
+      .chars()
+      .next()
+      .is_some_and(|c| !c.is_whitespace() && !matches!(c, '}' | ')' | ']'))
+  };
+
+  let mut line_starts = vec![0];
+
+  for (i, c) in source.char_indices() {
+    if c == '\n' {
+      line_starts.push(i + 1);
+    }
+  }
+
+  let pos_line = match line_starts.iter().rposition(|start| *start <= pos) {
+    Some(line) => line,
+    None => return false,
+  };
+
+  let line_text = |line: usize| {
+    let end = line_starts.get(line + 1).copied().unwrap_or(source.len());
+    &source[line_starts[line]..end]
+  };
+
+  let mut start_line = pos_line;
+
+  while start_line > 0 && !is_statement_start(line_text(start_line)) {
+    start_line -= 1;
+  }
+
+  let mut end_line = start_line + 1;
+
+  while end_line < line_starts.len() && !is_statement_start(line_text(end_line)) {
+    end_line += 1;
+  }
+
+  let start = line_starts[start_line];
+  let end = line_starts.get(end_line).copied().unwrap_or(source.len());
+
+  if source[start..end].chars().all(char::is_whitespace) {
+    return false;
+  }
+
+  let blanked: String = source[start..end]
+    .bytes()
+    .map(|b| if b == b'\n' { '\n' } else { ' ' })
+    .collect();
+
+  source.replace_range(start..end, &blanked);
+
+  true
+}
+
+fn parse_without_recovery(source: &str) -> (Option<swc_ecma_ast::Program>, Vec<Diagnostic>) {
   let source_map = Arc::<SourceMap>::default();
 
   let diagnostics_arc = Arc::new(Mutex::new(Vec::<Diagnostic>::new()));
@@ -69,19 +180,46 @@ pub fn parse(source: &str) -> (Option<swc_ecma_ast::Program>, Vec<Diagnostic>) {
 pub struct CompilerOutput {
   pub diagnostics: Vec<Diagnostic>,
   pub module: Module,
+
+  /// The original names, when names are minified (see `CompileOptions`)
+  pub name_map: Option<NameMap>,
 }
 
+/// Compiles a program that has already been parsed (eg by `parse`), so that tools which need the
+/// AST anyway don't parse twice, and so that other front-ends can produce ValueScript assembly.
+///
+/// Spans in the program are expected to be offsets into `source`, which is used for `vs-ignore`
+/// comments and for hashing the source of functions and classes. Programs built by hand can use
+/// `DUMMY_SP` and an empty source.
 pub fn compile_program(source: &str, program: &swc_ecma_ast::Program) -> CompilerOutput {
-  let compiler = ModuleCompiler::compile_program(source, program);
+  // Functions are compiled with their own catch_unwind (see compile_fn), this catches panics
+  // elsewhere in the module (eg scope analysis)
+  let compiler = match catch_unwind(AssertUnwindSafe(|| {
+    ModuleCompiler::compile_program(source, program)
+  })) {
+    Ok(compiler) => compiler,
+    Err(payload) => {
+      return CompilerOutput {
+        diagnostics: vec![Diagnostic::from_panic(program.span(), &*payload)],
+        module: Module::default(),
+        name_map: None,
+      };
+    }
+  };
+
+  let mut diagnostics = compiler.diagnostics.take();
+  remove_ignored_diagnostics(source, &mut diagnostics);
 
   CompilerOutput {
-    diagnostics: compiler.diagnostics.take(),
+    diagnostics,
     module: compiler.module,
+    name_map: None,
   }
 }
 
 pub fn compile_module(source: &str) -> CompilerOutput {
   let (program_optional, mut diagnostics) = parse(source);
+  remove_ignored_diagnostics(source, &mut diagnostics);
 
   let mut compiler_output = match program_optional {
     Some(program) => compile_program(source, &program),
@@ -94,12 +232,32 @@ pub fn compile_module(source: &str) -> CompilerOutput {
   compiler_output
 }
 
+pub fn compile_module_with_options(source: &str, options: &CompileOptions) -> CompilerOutput {
+  let mut compiler_output = compile_module(source);
+
+  if options.minify_names {
+    compiler_output.name_map = Some(minify_names(&mut compiler_output.module));
+  }
+
+  compiler_output
+}
+
+/// Code that runs as part of the module's initialization (see ModuleCompiler::compile_module_init).
+enum ModuleInitItem<'m> {
+  Stmt(&'m swc_ecma_ast::Stmt),
+  Const(Pointer),
+}
+
 #[derive(Default)]
 pub struct ModuleCompiler {
   pub diagnostics: RefCell<Vec<Diagnostic>>,
   pub definition_allocator: NameAllocator,
   pub scope_analysis: ScopeAnalysis,
   pub constants_map: HashMap<Pointer, Value>,
+
+  /// The owner of each function compiled by compile_fn, which determines its captures
+  pub fn_owner_ids: HashMap<Pointer, OwnerId>,
+
   pub source: String,
   pub module: Module,
 }
@@ -159,6 +317,137 @@ impl ModuleCompiler {
     for module_item in &module.body {
       self.compile_module_item(module_item);
     }
+
+    let init_items = self.module_init_items(module);
+
+    if !init_items.is_empty() {
+      self.compile_module_init(module, &init_items);
+    }
+  }
+
+  /// The top level statements that aren't declarations, and the constants that need to run code to
+  /// be evaluated (see compile_lazy_const), in the order they appear.
+  fn module_init_items<'m>(&self, module: &'m swc_ecma_ast::Module) -> Vec<ModuleInitItem<'m>> {
+    let mut items = Vec::<ModuleInitItem>::new();
+
+    for module_item in &module.body {
+      let var_decl = match module_item {
+        swc_ecma_ast::ModuleItem::Stmt(stmt) => {
+          if is_module_init_stmt(stmt) {
+            items.push(ModuleInitItem::Stmt(stmt));
+            continue;
+          }
+
+          match stmt {
+            swc_ecma_ast::Stmt::Decl(swc_ecma_ast::Decl::Var(var_decl)) => var_decl,
+            _ => continue,
+          }
+        }
+        swc_ecma_ast::ModuleItem::ModuleDecl(swc_ecma_ast::ModuleDecl::ExportDecl(ed)) => {
+          match &ed.decl {
+            swc_ecma_ast::Decl::Var(var_decl) => var_decl,
+            _ => continue,
+          }
+        }
+        _ => continue,
+      };
+
+      if var_decl.declare {
+        continue;
+      }
+
+      for decl in &var_decl.decls {
+        if let (swc_ecma_ast::Pat::Ident(bi), Some(init)) = (&decl.name, &decl.init) {
+          if is_static_expr(init) {
+            continue;
+          }
+
+          if let Some(Value::Pointer(p)) = self
+            .scope_analysis
+            .lookup(&Ident::from_swc_ident(&bi.id))
+            .map(|name| name.value.clone())
+          {
+            items.push(ModuleInitItem::Const(p));
+          }
+        }
+      }
+    }
+
+    items
+  }
+
+  /// Compiles the top level statements that aren't declarations into a lazy value, and makes each
+  /// export evaluate it before providing its value. This way the statements run once, before any of
+  /// the exports are read. Constants that need to run code are evaluated in between, in source
+  /// order.
+  fn compile_module_init(&mut self, module: &swc_ecma_ast::Module, items: &[ModuleInitItem]) {
+    let init_defn_name = self.allocate_defn("_module_init");
+
+    let mut fnc = FunctionCompiler::new(self);
+    fnc.set_owner_id(module_init_owner_id(module));
+
+    for item in items {
+      match item {
+        ModuleInitItem::Stmt(swc_ecma_ast::Stmt::Return(return_)) => {
+          fnc.not_supported(return_.span, "module level Return statement")
+        }
+        ModuleInitItem::Stmt(stmt) => fnc.statement(stmt, false),
+        ModuleInitItem::Const(p) => fnc.push(Instruction::Mov(
+          Value::Pointer(p.clone()),
+          Register::ignore(),
+        )),
+      }
+    }
+
+    fnc.insert_all_releases();
+
+    let mut body = Vec::<FnLine>::new();
+    body.append(&mut fnc.fn_.body);
+
+    self.module.definitions.push(Definition {
+      pointer: init_defn_name.clone(),
+      content: DefinitionContent::Lazy(Lazy { body }),
+    });
+
+    if self.module.export_default != Value::Void {
+      let export_default = take(&mut self.module.export_default);
+
+      self.module.export_default =
+        self.after_module_init(&init_defn_name, "default", export_default);
+    }
+
+    let mut properties = take(&mut self.module.export_star.local.properties);
+
+    for (key, value) in &mut properties {
+      let name = match key {
+        Value::String(name) => name.clone(),
+        _ => "export".to_string(),
+      };
+
+      *value = self.after_module_init(&init_defn_name, &name, take(value));
+    }
+
+    self.module.export_star.local.properties = properties;
+  }
+
+  /// A lazy value for `value` that evaluates the module init first.
+  fn after_module_init(&mut self, init_defn_name: &Pointer, name: &str, value: Value) -> Value {
+    let defn_name = self.allocate_defn(&format!("_export_{}", name));
+
+    self.module.definitions.push(Definition {
+      pointer: defn_name.clone(),
+      content: DefinitionContent::Lazy(Lazy {
+        body: vec![
+          FnLine::Instruction(Instruction::Mov(
+            Value::Pointer(init_defn_name.clone()),
+            Register::ignore(),
+          )),
+          FnLine::Instruction(Instruction::Mov(value, Register::return_())),
+        ],
+      }),
+    });
+
+    Value::Pointer(defn_name)
   }
 
   fn compile_module_item(&mut self, module_item: &swc_ecma_ast::ModuleItem) {
@@ -189,28 +478,11 @@ impl ModuleCompiler {
   }
 
   fn compile_module_statement(&mut self, stmt: &swc_ecma_ast::Stmt) {
-    use swc_ecma_ast::Stmt::*;
-
     match stmt {
-      Decl(decl) => self.compile_module_level_decl(decl),
-      Block(block) => self.not_supported(block.span, "module level Block statement"),
-      Empty(_) => {}
-      Debugger(debugger) => self.not_supported(debugger.span, "module level Debugger statement"),
-      With(with) => self.not_supported(with.span, "module level With statement"),
-      Return(return_) => self.not_supported(return_.span, "module level Return statement"),
-      Labeled(labeled) => self.not_supported(labeled.span, "module level Labeled statement"),
-      Break(break_) => self.not_supported(break_.span, "module level Break statement"),
-      Continue(continue_) => self.not_supported(continue_.span, "module level Continue statement"),
-      If(if_) => self.not_supported(if_.span, "module level If statement"),
-      Switch(switch) => self.not_supported(switch.span, "module level Switch statement"),
-      Throw(throw) => self.not_supported(throw.span, "module level Throw statement"),
-      Try(try_) => self.not_supported(try_.span, "module level Try statement"),
-      While(while_) => self.not_supported(while_.span, "module level While statement"),
-      DoWhile(do_while) => self.not_supported(do_while.span, "module level DoWhile statement"),
-      For(for_) => self.not_supported(for_.span, "module level For statement"),
-      ForIn(for_in) => self.not_supported(for_in.span, "module level ForIn statement"),
-      ForOf(for_of) => self.not_supported(for_of.span, "module level ForOf statement"),
-      Expr(expr) => self.not_supported(expr.span, "module level Expr statement"),
+      swc_ecma_ast::Stmt::Decl(decl) => self.compile_module_level_decl(decl),
+
+      // Other statements are compiled by compile_module_init
+      _ => {}
     };
   }
 
@@ -263,8 +535,6 @@ impl ModuleCompiler {
       };
 
       if let (Some(ident), Some(init)) = (ident, init) {
-        let value = self.static_ec().expr(init);
-
         let pointer = match self.scope_analysis.lookup(&Ident::from_swc_ident(ident)) {
           Some(name) => match &name.value {
             Value::Pointer(p) => p.clone(),
@@ -279,12 +549,17 @@ impl ModuleCompiler {
           }
         };
 
-        self.constants_map.insert(pointer.clone(), value.clone());
+        if is_static_expr(init) {
+          let value = self.static_ec().expr(init);
+          self.constants_map.insert(pointer.clone(), value.clone());
 
-        self.module.definitions.push(Definition {
-          pointer: pointer.clone(),
-          content: DefinitionContent::Value(value),
-        });
+          self.module.definitions.push(Definition {
+            pointer: pointer.clone(),
+            content: DefinitionContent::Value(value),
+          });
+        } else {
+          self.compile_lazy_const(&pointer, init);
+        }
 
         if export {
           self.module.export_star.local.properties.push((
@@ -296,6 +571,26 @@ impl ModuleCompiler {
     }
   }
 
+  /// Compiles a constant that needs to run code to be evaluated (e.g. `const xs = [1, 2].map(f)`)
+  /// into a lazy value. The module init evaluates it in source order (see module_init_items), and
+  /// it's only evaluated once because lazy values are cached.
+  fn compile_lazy_const(&mut self, pointer: &Pointer, init: &swc_ecma_ast::Expr) {
+    let mut fnc = FunctionCompiler::new(self);
+    fnc.set_owner_id(OwnerId::Module);
+
+    ExpressionCompiler { fnc: &mut fnc }.compile_into(init, Register::return_());
+
+    fnc.insert_all_releases();
+
+    let mut body = Vec::<FnLine>::new();
+    body.append(&mut fnc.fn_.body);
+
+    self.module.definitions.push(Definition {
+      pointer: pointer.clone(),
+      content: DefinitionContent::Lazy(Lazy { body }),
+    });
+  }
+
   fn compile_fn_decl(&mut self, export: bool, fn_: &swc_ecma_ast::FnDecl) {
     let fn_name = fn_.ident.sym.to_string();
 
@@ -703,7 +998,39 @@ impl ModuleCompiler {
   }
 
   pub fn compile_fn(&mut self, defn_pointer: Pointer, functionish: Functionish) {
-    FunctionCompiler::new(self).compile(defn_pointer, functionish);
+    self.compile_fn_with_super_home(defn_pointer, functionish, None);
+  }
+
+  pub fn compile_fn_with_super_home(
+    &mut self,
+    defn_pointer: Pointer,
+    functionish: Functionish,
+    super_home: Option<SuperHome>,
+  ) {
+    let span = functionish.span();
+
+    self
+      .fn_owner_ids
+      .insert(defn_pointer.clone(), functionish.owner_id());
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+      let mut fnc = FunctionCompiler::new(self);
+      fnc.super_home = super_home;
+      fnc.compile(defn_pointer.clone(), functionish);
+    }));
+
+    if let Err(payload) = result {
+      self
+        .diagnostics
+        .borrow_mut()
+        .push(Diagnostic::from_panic(span, &*payload));
+
+      // Other definitions may refer to this function, so it still needs a definition
+      self.module.definitions.push(Definition {
+        pointer: defn_pointer,
+        content: DefinitionContent::Function(Function::default()),
+      });
+    }
   }
 
   pub fn compile_class(
@@ -741,52 +1068,51 @@ impl ModuleCompiler {
       ));
     }
 
-    // member initializers function compiler
-    let mut mi_fnc = FunctionCompiler::new(self);
-    mi_fnc.set_owner_id(OwnerId::Span(class.span));
+    let extends = class
+      .super_class
+      .as_ref()
+      .map(|super_class| self.compile_super_class(super_class));
+
+    // Static fields are evaluated lazily (in declaration order) if any of them need to run code
+    let lazy_statics = class.body.iter().any(|class_member| match class_member {
+      swc_ecma_ast::ClassMember::ClassProp(class_prop) => {
+        class_prop.is_static && matches!(&class_prop.value, Some(value) if !is_static_expr(value))
+      }
+      _ => false,
+    });
+
+    let mut lazy_static_props = Vec::<&swc_ecma_ast::ClassProp>::new();
+
+    // Instance fields are initialized by the constructor
+    let mut fields = Vec::<swc_ecma_ast::ClassProp>::new();
 
     for class_member in &class.body {
       match class_member {
         swc_ecma_ast::ClassMember::ClassProp(class_prop) => {
-          if class_prop.is_static {
-            let key = mi_fnc.mc.static_ec().prop_name(&class_prop.key);
+          if class_prop.declare || class_prop.is_abstract {
+            // Type only
+          } else if class_prop.is_static && lazy_statics {
+            lazy_static_props.push(class_prop);
+          } else if class_prop.is_static {
+            let key = self.static_ec().prop_name(&class_prop.key);
 
             let value = match &class_prop.value {
-              Some(expr) => mi_fnc.mc.static_ec().expr(expr),
+              Some(expr) => self.static_ec().expr(expr),
               None => Value::Undefined,
             };
 
             static_.properties.push((key, value));
           } else {
-            let mut ec = ExpressionCompiler { fnc: &mut mi_fnc };
-
-            let compiled_key = ec.prop_name(&class_prop.key);
-
-            let compiled_value = match &class_prop.value {
-              None => CompiledExpression::new(Value::Undefined, vec![]),
-              Some(expr) => ec.compile(expr, None),
-            };
-
-            ec.fnc.push(Instruction::SubMov(
-              compiled_key.value.clone(),
-              compiled_value.value.clone(),
-              Register::this(),
-            ));
-
-            ec.fnc.release_ce(compiled_key);
-            ec.fnc.release_ce(compiled_value);
+            fields.push(class_prop.clone());
           }
         }
         swc_ecma_ast::ClassMember::PrivateProp(private_prop) => {
-          mi_fnc.todo(private_prop.span, "private props")
+          self.todo(private_prop.span, "private props")
         }
         _ => {}
       }
     }
 
-    let mut member_initializers_assembly = Vec::<FnLine>::new();
-    member_initializers_assembly.append(&mut mi_fnc.fn_.body);
-
     let mut ctor = swc_ecma_ast::Constructor {
       span: class.span,
       key: swc_ecma_ast::PropName::Str(swc_ecma_ast::Str {
@@ -806,15 +1132,16 @@ impl ModuleCompiler {
       }
     }
 
-    if !member_initializers_assembly.is_empty() || ctor.body.is_some() {
+    if !fields.is_empty() || ctor.body.is_some() {
       let ctor_defn_name = self.allocate_defn(&format!("{}_constructor", defn_name.name));
 
       self.compile_fn(
         ctor_defn_name.clone(),
         Functionish::Constructor(
-          member_initializers_assembly.clone(),
+          fields,
           class_to_owner_id(ident, class),
           ctor,
+          extends.clone(),
         ),
       );
 
@@ -847,9 +1174,15 @@ impl ModuleCompiler {
             _ => None,
           };
 
-          self.compile_fn(
+          let super_home = extends.clone().map(|extends| match method.is_static {
+            false => SuperHome::Prototype(extends),
+            true => SuperHome::Static(extends),
+          });
+
+          self.compile_fn_with_super_home(
             method_defn_name.clone(),
             Functionish::Fn(method_id, method.function.clone()),
+            super_home,
           );
 
           let dst = match method.is_static {
@@ -880,7 +1213,7 @@ impl ModuleCompiler {
       }
     }
 
-    let class_value = Value::Class(Box::new(Class {
+    let mut class_value = Class {
       meta: Meta {
         name: ident.map_or_else(String::new, |ident| ident.sym.to_string()),
         content_hashable: ContentHashable::Src(
@@ -888,10 +1221,18 @@ impl ModuleCompiler {
           self.scope_analysis.get_deps(class.span),
         ),
       },
+      extends,
       constructor,
       prototype: Value::Object(Box::new(prototype)),
       static_: Value::Object(Box::new(static_)),
-    }));
+    };
+
+    if lazy_statics {
+      class_value.static_ =
+        self.compile_lazy_statics(&defn_name, ident, class, &class_value, &lazy_static_props);
+    }
+
+    let class_value = Value::Class(Box::new(class_value));
 
     self.module.definitions.push(Definition {
       pointer: defn_name.clone(),
@@ -903,8 +1244,101 @@ impl ModuleCompiler {
     defn_name
   }
 
+  /// Compiles the static fields of a class into a lazy value that starts with the static methods
+  /// and then runs the field initializers in order. During initialization, `this` (and the class
+  /// name) is the class with the static methods and fields before the initializer.
+  fn compile_lazy_statics(
+    &mut self,
+    defn_name: &Pointer,
+    ident: Option<&swc_ecma_ast::Ident>,
+    class: &swc_ecma_ast::Class,
+    class_value: &Class,
+    static_props: &[&swc_ecma_ast::ClassProp],
+  ) -> Value {
+    let static_defn_name = self.allocate_defn(&format!("{}_static", defn_name.name));
+
+    let mut fnc = FunctionCompiler::new(self);
+    fnc.set_owner_id(class_to_owner_id(ident, class));
+
+    let static_reg = fnc.allocate_reg("static");
+    fnc.push(Instruction::Mov(
+      class_value.static_.clone(),
+      static_reg.clone(),
+    ));
+
+    // The class definition is still being decoded when the lazy value runs, so the class is
+    // assembled at runtime from the statics so far
+    let mut partial_class = class_value.clone();
+    partial_class.meta.content_hashable = ContentHashable::Empty;
+    partial_class.static_ = Value::Register(static_reg.clone());
+
+    for class_prop in static_props {
+      fnc.push(Instruction::Mov(
+        Value::Class(Box::new(partial_class.clone())),
+        Register::this(),
+      ));
+
+      let start = fnc.fn_.body.len();
+      fnc.field_initializer(class_prop, static_reg.clone());
+
+      for line in &mut fnc.fn_.body[start..] {
+        if let FnLine::Instruction(instr) = line {
+          instr.visit_fields_mut(&mut |field| {
+            if let InstructionFieldMut::Value(value) = field {
+              value.visit_values_mut(&mut |value| {
+                if matches!(value, Value::Pointer(p) if p == defn_name) {
+                  *value = Value::Register(Register::this());
+                }
+              });
+            }
+          });
+        }
+      }
+    }
+
+    fnc.push(Instruction::Mov(
+      Value::Register(static_reg.clone()),
+      Register::return_(),
+    ));
+
+    fnc.release_reg(&static_reg);
+
+    let mut body = Vec::<FnLine>::new();
+    body.append(&mut fnc.fn_.body);
+
+    self.module.definitions.push(Definition {
+      pointer: static_defn_name.clone(),
+      content: DefinitionContent::Lazy(Lazy { body }),
+    });
+
+    Value::Pointer(static_defn_name)
+  }
+
+  fn compile_super_class(&mut self, super_class: &swc_ecma_ast::Expr) -> Value {
+    // Refer to the base class by pointer rather than inlining it so that the derived class shares
+    // its prototype (needed for instanceof)
+    if let swc_ecma_ast::Expr::Ident(ident) = super_class {
+      if let Some(Value::Pointer(p)) = self
+        .scope_analysis
+        .lookup(&Ident::from_swc_ident(ident))
+        .map(|name| name.value.clone())
+      {
+        return Value::Pointer(p);
+      }
+    }
+
+    match self.static_ec().expr(super_class) {
+      Value::Register(_) => {
+        self.todo(super_class.span(), "Extending a non-constant class");
+        Value::Undefined
+      }
+      value => value,
+    }
+  }
+
   pub fn compile_enum_value(&mut self, ts_enum: &swc_ecma_ast::TsEnumDecl) -> Value {
     let mut properties = Vec::<(Value, Value)>::new();
+    let mut members = HashMap::<String, Value>::new();
     let mut next_default_id: Option<f64> = Some(0.0);
 
     for member in &ts_enum.members {
@@ -913,47 +1347,110 @@ impl ModuleCompiler {
         swc_ecma_ast::TsEnumMemberId::Str(str) => str.value.to_string(),
       };
 
-      let init_value = match &member.init {
-        Some(init) => {
-          let init_value = self.static_ec().expr(init);
-
-          match self.static_ec().expr(init) {
-            Value::Number(Number(n)) => {
-              next_default_id = Some(n + 1.0);
-              Some(Value::Number(Number(n)))
-            }
-            Value::String(_) => Some(init_value),
-            _ => None,
+      let value = match &member.init {
+        Some(init) => match self.enum_init_value(ts_enum, &members, init) {
+          Some(value) => value,
+          None => {
+            self.todo(init.span(), "Non-constant enum initializer");
+            Value::Number(Number(0.0))
           }
-        }
-        None => None,
+        },
+        None => match next_default_id {
+          Some(id) => Value::Number(Number(id)),
+          None => {
+            self.error(member.span, "Missing required initializer");
+            Value::Number(Number(0.0))
+          }
+        },
       };
 
-      let value = match init_value {
-        Some(value) => value,
-        None => {
-          let id = match next_default_id {
-            Some(id) => id,
-            None => {
-              self.error(member.span, "Missing required initializer");
-              0.0
-            }
-          };
-
-          let value = Value::Number(Number(id));
-          next_default_id = Some(id + 1.0);
-
-          value
-        }
+      next_default_id = match &value {
+        Value::Number(Number(n)) => Some(n + 1.0),
+        _ => None,
       };
 
       properties.push((Value::String(key.clone()), value.clone()));
-      properties.push((value, Value::String(key)));
+
+      // Only numeric members get a reverse mapping, like TypeScript
+      if let Value::Number(_) = value {
+        properties.push((value.clone(), Value::String(key.clone())));
+      }
+
+      members.insert(key, value);
     }
 
     Value::Object(Box::new(Object { properties }))
   }
 
+  /// Evaluates a constant enum initializer, which can refer to the members before it (either
+  /// directly or via the enum itself).
+  fn enum_init_value(
+    &mut self,
+    ts_enum: &swc_ecma_ast::TsEnumDecl,
+    members: &HashMap<String, Value>,
+    init: &swc_ecma_ast::Expr,
+  ) -> Option<Value> {
+    use swc_ecma_ast::{BinaryOp, Expr, MemberProp, UnaryOp};
+
+    match init {
+      Expr::Ident(ident) if members.contains_key(&*ident.sym) => members.get(&*ident.sym).cloned(),
+      Expr::Member(member) => match (&*member.obj, &member.prop) {
+        (Expr::Ident(obj), MemberProp::Ident(prop)) if obj.sym == ts_enum.id.sym => {
+          members.get(&*prop.sym).cloned()
+        }
+        _ => None,
+      },
+      Expr::Paren(paren) => self.enum_init_value(ts_enum, members, &paren.expr),
+      Expr::Unary(unary) => {
+        let arg = match self.enum_init_value(ts_enum, members, &unary.arg)? {
+          Value::Number(Number(x)) => x,
+          _ => return None,
+        };
+
+        match unary.op {
+          UnaryOp::Minus => Some(Value::Number(Number(-arg))),
+          UnaryOp::Plus => Some(Value::Number(Number(arg))),
+          UnaryOp::Tilde => Some(Value::Number(Number(!to_i32(arg) as f64))),
+          _ => None,
+        }
+      }
+      Expr::Bin(bin) => {
+        let left = self.enum_init_value(ts_enum, members, &bin.left)?;
+        let right = self.enum_init_value(ts_enum, members, &bin.right)?;
+
+        let (left, right) = match (left, right) {
+          (Value::Number(Number(left)), Value::Number(Number(right))) => (left, right),
+          (Value::String(left), Value::String(right)) if bin.op == BinaryOp::Add => {
+            return Some(Value::String(left + &right));
+          }
+          _ => return None,
+        };
+
+        let res = match bin.op {
+          BinaryOp::Add => left + right,
+          BinaryOp::Sub => left - right,
+          BinaryOp::Mul => left * right,
+          BinaryOp::Div => left / right,
+          BinaryOp::Mod => left % right,
+          BinaryOp::Exp => left.powf(right),
+          BinaryOp::LShift => to_i32(left).wrapping_shl(to_u32(right)) as f64,
+          BinaryOp::RShift => to_i32(left).wrapping_shr(to_u32(right)) as f64,
+          BinaryOp::ZeroFillRShift => to_u32(left).wrapping_shr(to_u32(right)) as f64,
+          BinaryOp::BitAnd => (to_i32(left) & to_i32(right)) as f64,
+          BinaryOp::BitOr => (to_i32(left) | to_i32(right)) as f64,
+          BinaryOp::BitXor => (to_i32(left) ^ to_i32(right)) as f64,
+          _ => return None,
+        };
+
+        Some(Value::Number(Number(res)))
+      }
+      _ => match self.static_ec().expr(init) {
+        value @ (Value::Number(_) | Value::String(_)) => Some(value),
+        _ => None,
+      },
+    }
+  }
+
   pub fn static_ec(&mut self) -> StaticExpressionCompiler {
     StaticExpressionCompiler::new(self)
   }
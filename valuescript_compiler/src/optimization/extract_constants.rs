@@ -122,7 +122,8 @@ fn is_constant(value: &Value) -> bool {
       .iter()
       .all(|(k, v)| is_constant(k) && is_constant(v)),
     Value::Class(class) => {
-      is_constant(&class.constructor)
+      class.extends.as_ref().map_or(true, is_constant)
+        && is_constant(&class.constructor)
         && is_constant(&class.prototype)
         && is_constant(&class.static_)
     }
@@ -1,6 +1,9 @@
 use num_bigint::BigInt;
+use valuescript_common::number_to_string;
 use valuescript_vm::{
-  operations, unicode_at,
+  operations,
+  property_map::PropertyMap,
+  unicode_at,
   vs_class::VsClass,
   vs_object::VsObject,
   vs_value::{number_to_index, ToVal, Val},
@@ -136,6 +139,8 @@ impl Kal {
           .map(|(k, v)| (Kal::from_value(k), Kal::from_value(v)))
           .collect(),
       })),
+      // Derived classes are opaque to the optimizer
+      Value::Class(class) if class.extends.is_some() => Kal::Unknown,
       Value::Class(class) => Kal::Class(Box::new(Class {
         meta: class.meta.clone(),
         constructor: Kal::from_value(&class.constructor),
@@ -212,6 +217,7 @@ impl Kal {
       Kal::Function(_) => None,
       Kal::Class(class) => Some(Value::Class(Box::new(asm::Class {
         meta: class.meta.clone(),
+        extends: None,
         constructor: class.constructor.try_to_value()?,
         prototype: class.prototype.try_to_value()?,
         static_: class.static_.try_to_value()?,
@@ -241,7 +247,7 @@ impl Kal {
         result.to_val()
       }
       Kal::Object(obj) => {
-        let mut string_map = BTreeMap::<String, Val>::new();
+        let mut string_map = PropertyMap::new();
 
         for (key, value) in obj.properties {
           string_map.insert(key.try_to_val()?.to_string(), value.try_to_val()?);
@@ -281,7 +287,7 @@ impl Kal {
       Kal::Undefined => Some("undefined".to_string()),
       Kal::Null => Some("null".to_string()),
       Kal::Bool(b) => Some(b.to_string()),
-      Kal::Number(Number(x)) => Some(x.to_string()),
+      Kal::Number(Number(x)) => Some(number_to_string(*x)),
       Kal::BigInt(bi) => Some(bi.to_string()),
       Kal::String(s) => Some(s.clone()),
       Kal::Array(_) => None,
@@ -495,7 +501,7 @@ impl FnState {
       OpNot(a1, dst) => self.apply_unary_op(a1, dst, operations::op_not),
       OpBitNot(a1, dst) => self.apply_unary_op(a1, dst, operations::op_bit_not),
       TypeOf(a1, dst) => self.apply_unary_op(a1, dst, operations::op_typeof),
-      UnaryPlus(a1, dst) => self.apply_unary_op(a1, dst, operations::op_unary_plus),
+      UnaryPlus(a1, dst) => self.apply_fallible_unary_op(a1, dst, operations::op_unary_plus),
       UnaryMinus(a1, dst) => self.apply_unary_op(a1, dst, operations::op_unary_minus),
       Import(a1, dst) | ImportStar(a1, dst) | Cat(a1, dst) => {
         self.eval_arg(a1);
@@ -504,7 +510,7 @@ impl FnState {
         self.set(dst.name.clone(), Kal::Unknown);
       }
 
-      Yield(a1, dst) | YieldStar(a1, dst) => {
+      Yield(a1, dst) | YieldStar(a1, dst) | Await(a1, dst) => {
         self.eval_arg(a1);
         self.set(dst.name.clone(), Kal::Unknown);
       }
@@ -680,6 +686,14 @@ impl FnState {
         self.set(value_reg.name.clone(), Kal::Unknown);
         self.set(done.name.clone(), Kal::Unknown);
       }
+      NewTarget(dst) => {
+        self.set(dst.name.clone(), Kal::Unknown);
+      }
+      Delete(obj, key, dst) => {
+        self.eval_arg(key);
+        self.set(obj.name.clone(), Kal::Unknown);
+        self.set(dst.name.clone(), Kal::Unknown);
+      }
     }
 
     match instr {
@@ -746,7 +760,10 @@ impl FnState {
       | UnpackIterRes(_, _, _)
       | Cat(_, _)
       | Yield(_, _)
-      | YieldStar(_, _) => {}
+      | YieldStar(_, _)
+      | Await(_, _)
+      | NewTarget(_)
+      | Delete(_, _, _) => {}
     }
   }
 
@@ -790,6 +807,7 @@ impl FnState {
 
         Kal::Object(Box::new(Object { properties }))
       }
+      Value::Class(class) if class.extends.is_some() => Kal::Unknown,
       Value::Class(class) => Kal::Class(Box::new(Class {
         meta: class.meta.clone(),
         constructor: self.eval_arg(&mut class.constructor),
@@ -845,6 +863,21 @@ impl FnState {
     Some(())
   }
 
+  fn apply_fallible_unary_op(
+    &mut self,
+    arg: &mut Value,
+    dst: &Register,
+    op: fn(input: &Val) -> Result<Val, Val>,
+  ) {
+    let kal = self
+      .eval_arg(arg)
+      .try_to_val()
+      .and_then(|arg| op(&arg).ok())
+      .and_then(|val| val.try_to_kal());
+
+    self.set(dst.name.clone(), kal.unwrap_or(Kal::Unknown));
+  }
+
   fn apply_binary_op(
     &mut self,
     left: &mut Value,
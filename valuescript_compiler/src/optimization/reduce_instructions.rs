@@ -27,7 +27,9 @@ fn reduce(instr: Instruction) -> Option<Instruction> {
   match &instr {
     End | OpInc(..) | OpDec(..) | Call(..) | Apply(..) | ConstApply(..) | SubCall(..) | Jmp(..)
     | New(..) | Throw(..) | SetCatch(..) | UnsetCatch | ConstSubCall(..) | RequireMutableThis
-    | ThisSubCall(..) | Next(..) | Yield(..) | YieldStar(..) => Some(instr),
+    | ThisSubCall(..) | Next(..) | Yield(..) | YieldStar(..) | Await(..) | Delete(..) => {
+      Some(instr)
+    }
 
     Mov(arg, dst) => 'b: {
       if dst.is_ignore() {
@@ -99,6 +101,10 @@ fn reduce(instr: Instruction) -> Option<Instruction> {
       Value::Bool(true) => None,
       _ => Some(instr),
     },
+    NewTarget(dst) => match dst.is_ignore() {
+      true => None,
+      false => Some(instr),
+    },
     UnpackIterRes(_, value_dst, done_dst) => {
       if value_dst.is_ignore() && done_dst.is_ignore() {
         None
@@ -259,6 +259,7 @@ fn identify_primary_registers(fn_: &mut Function) -> HashSet<String> {
       Instruction::Cat(value, _)
       | Instruction::Yield(value, _)
       | Instruction::YieldStar(value, _)
+      | Instruction::Await(value, _)
       | Instruction::JmpIf(value, _)
       | Instruction::JmpIfNot(value, _)
       | Instruction::Throw(value) => {
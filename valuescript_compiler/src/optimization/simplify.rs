@@ -238,6 +238,9 @@ fn is_jmp_instr(instr: &Instruction) -> bool {
     | Instruction::UnpackIterRes(..)
     | Instruction::Cat(..)
     | Instruction::Yield(..)
-    | Instruction::YieldStar(..) => false,
+    | Instruction::YieldStar(..)
+    | Instruction::Await(..)
+    | Instruction::NewTarget(..)
+    | Instruction::Delete(..) => false,
   }
 }
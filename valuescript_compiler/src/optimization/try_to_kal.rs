@@ -21,6 +21,23 @@ impl TryToKal for Val {
         VsSymbol::ITERATOR => Kal::Builtin(Builtin {
           name: "SymbolIterator".to_string(),
         }),
+        VsSymbol::ASYNC_ITERATOR => Kal::Builtin(Builtin {
+          name: "SymbolAsyncIterator".to_string(),
+        }),
+        VsSymbol::TO_PRIMITIVE => Kal::Builtin(Builtin {
+          name: "SymbolToPrimitive".to_string(),
+        }),
+        VsSymbol::HAS_INSTANCE => Kal::Builtin(Builtin {
+          name: "SymbolHasInstance".to_string(),
+        }),
+        VsSymbol::TO_STRING_TAG => Kal::Builtin(Builtin {
+          name: "SymbolToStringTag".to_string(),
+        }),
+        VsSymbol::GENERATOR_RETURN => Kal::Builtin(Builtin {
+          name: "GeneratorReturn".to_string(),
+        }),
+        // TODO: convert registered symbols to Kal
+        VsSymbol::CLASS_NAME | VsSymbol::Registered(_) => return None,
       },
       Val::String(s) => Kal::String(s.to_string()),
       Val::Array(arr) => {
@@ -45,6 +62,22 @@ impl TryToKal for Val {
             VsSymbol::ITERATOR => Kal::Builtin(Builtin {
               name: "SymbolIterator".to_string(),
             }),
+            VsSymbol::ASYNC_ITERATOR => Kal::Builtin(Builtin {
+              name: "SymbolAsyncIterator".to_string(),
+            }),
+            VsSymbol::TO_PRIMITIVE => Kal::Builtin(Builtin {
+              name: "SymbolToPrimitive".to_string(),
+            }),
+            VsSymbol::HAS_INSTANCE => Kal::Builtin(Builtin {
+              name: "SymbolHasInstance".to_string(),
+            }),
+            VsSymbol::TO_STRING_TAG => Kal::Builtin(Builtin {
+              name: "SymbolToStringTag".to_string(),
+            }),
+            VsSymbol::GENERATOR_RETURN => Kal::Builtin(Builtin {
+              name: "GeneratorReturn".to_string(),
+            }),
+            VsSymbol::CLASS_NAME | VsSymbol::Registered(_) => return None,
           };
 
           properties.push((k, v.try_to_kal()?));
@@ -1,6 +1,5 @@
-use std::collections::BTreeMap;
-
 use valuescript_vm::{
+  property_map::PropertyMap,
   vs_class::VsClass,
   vs_object::VsObject,
   vs_value::{ToVal, Val},
@@ -31,7 +30,7 @@ impl TryToVal for Value {
         result.to_val()
       }
       Value::Object(obj) => {
-        let mut string_map = BTreeMap::<String, Val>::new();
+        let mut string_map = PropertyMap::new();
 
         for (key, value) in obj.properties {
           string_map.insert(key.try_to_val()?.to_string(), value.try_to_val()?);
@@ -44,6 +43,10 @@ impl TryToVal for Value {
         }
         .to_val()
       }
+      // Inheritance is wired up by the bytecode decoder
+      Value::Class(class) if class.extends.is_some() => {
+        return Err("Invalid argument".to_val());
+      }
       Value::Class(class) => VsClass {
         name: class.meta.name,
         content_hash: match class.meta.content_hashable {
@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use crate::resolve_path::{resolve_path, resolve_path_alias, PathAlias, ResolvedPath};
+
+/// Whether an import refers to a package (eg `lodash` or `@scope/pkg/sub.ts`) rather than a file.
+pub fn is_bare_specifier(path: &str) -> bool {
+  !(path.starts_with("./")
+    || path.starts_with("../")
+    || path.starts_with('/')
+    || path.contains("://")
+    || path == "."
+    || path == "..")
+}
+
+/// Finds the file that a package import refers to, node_modules-style: the package is looked up in
+/// `node_modules` of each directory above the importer, and its `package.json` decides which file
+/// is used (`exports`, then `module`, then `main`, then `index.ts`).
+pub fn resolve_package<ReadFile>(
+  importer_path: &ResolvedPath,
+  specifier: &str,
+  read_file: &ReadFile,
+) -> Option<ResolvedPath>
+where
+  ReadFile: Fn(&str) -> Result<String, String>,
+{
+  let (name, subpath) = split_specifier(specifier)?;
+
+  if importer_path.path.contains("://") {
+    return None;
+  }
+
+  let importer_dir = Path::new(&importer_path.path).parent()?;
+
+  for dir in importer_dir.ancestors() {
+    let package_json_path = dir.join("node_modules").join(name).join("package.json");
+
+    let package_json_path = ResolvedPath {
+      path: package_json_path.to_str()?.to_string(),
+    };
+
+    let package_json = match read_file(&package_json_path.path) {
+      Ok(package_json) => package_json,
+      Err(_) => continue,
+    };
+
+    let package_json: serde_json::Value = serde_json::from_str(&package_json).ok()?;
+    let entry = package_entry(&package_json, subpath)?;
+
+    return Some(resolve_path(&package_json_path, &entry));
+  }
+
+  None
+}
+
+/// Splits `@scope/pkg/sub.ts` into `@scope/pkg` and `sub.ts` (or `pkg` into `pkg` and ``).
+fn split_specifier(specifier: &str) -> Option<(&str, &str)> {
+  let name_len = match specifier.starts_with('@') {
+    true => {
+      let scope_len = specifier.find('/')?;
+
+      match specifier[scope_len + 1..].find('/') {
+        Some(i) => scope_len + 1 + i,
+        None => specifier.len(),
+      }
+    }
+    false => specifier.find('/').unwrap_or(specifier.len()),
+  };
+
+  let (name, subpath) = specifier.split_at(name_len);
+
+  Some((name, subpath.trim_start_matches('/')))
+}
+
+/// The path (relative to the package) of the file for `subpath` of the package.
+fn package_entry(package_json: &serde_json::Value, subpath: &str) -> Option<String> {
+  let export_key = match subpath {
+    "" => ".".to_string(),
+    _ => format!("./{}", subpath),
+  };
+
+  match package_json.get("exports") {
+    Some(serde_json::Value::Object(exports)) if exports.keys().any(|k| k.starts_with('.')) => {
+      let aliases = exports
+        .iter()
+        .filter_map(|(key, target)| {
+          Some(PathAlias {
+            pattern: key.clone(),
+            target: export_target(target)?,
+          })
+        })
+        .collect::<Vec<_>>();
+
+      return resolve_path_alias(&export_key, &aliases);
+    }
+    Some(exports) if subpath.is_empty() => {
+      // Either a string or the conditions for "."
+      if let Some(target) = export_target(exports) {
+        return Some(target);
+      }
+    }
+    _ => {}
+  }
+
+  if !subpath.is_empty() {
+    return Some(format!("./{}", subpath));
+  }
+
+  for field in ["module", "main"] {
+    if let Some(serde_json::Value::String(entry)) = package_json.get(field) {
+      return Some(entry.clone());
+    }
+  }
+
+  Some("./index.ts".to_string())
+}
+
+/// The path of an `exports` target, which is either a path or an object of conditions.
+fn export_target(target: &serde_json::Value) -> Option<String> {
+  match target {
+    serde_json::Value::String(path) => Some(path.clone()),
+    serde_json::Value::Object(conditions) => ["valuescript", "import", "default"]
+      .iter()
+      .find_map(|condition| export_target(conditions.get(*condition)?)),
+    _ => None,
+  }
+}
@@ -18,6 +18,23 @@ impl std::fmt::Display for ResolvedPath {
 }
 
 pub fn resolve_path(importer_path: &ResolvedPath, path: &String) -> ResolvedPath {
+  if let Some((origin, url_path)) = split_url(path) {
+    return ResolvedPath {
+      path: format!("{}{}", origin, normalize_url_path(Path::new(url_path))),
+    };
+  }
+
+  // Modules at urls import paths relative to the url
+  if let Some((origin, importer_url_path)) = split_url(&importer_path.path) {
+    let parent = Path::new(importer_url_path)
+      .parent()
+      .unwrap_or_else(|| Path::new("/"));
+
+    return ResolvedPath {
+      path: format!("{}{}", origin, normalize_url_path(&parent.join(path))),
+    };
+  }
+
   let importer_path_buf = PathBuf::from(&importer_path.path);
   let parent = importer_path_buf.parent().unwrap_or_else(|| Path::new("/"));
 
@@ -29,14 +46,78 @@ pub fn resolve_path(importer_path: &ResolvedPath, path: &String) -> ResolvedPath
   }
 }
 
+/// A tsconfig-style path alias, eg `@lib/*` → `/project/src/lib/*`. The pattern can contain one `*`,
+/// which is replaced in the target by the part of the import path that it matched. Targets should
+/// be absolute, like the paths produced by `resolve_path`.
+#[derive(Clone, Debug)]
+pub struct PathAlias {
+  pub pattern: String,
+  pub target: String,
+}
+
+/// The path that an import of `path` refers to according to the aliases, if any of them match.
+/// Like tsconfig, an exact match is preferred, and then the match with the longest prefix before the
+/// `*`.
+pub fn resolve_path_alias(path: &str, path_aliases: &[PathAlias]) -> Option<String> {
+  let mut best_match: Option<(usize, String)> = None;
+
+  for alias in path_aliases {
+    let (prefix, suffix) = match alias.pattern.split_once('*') {
+      Some(parts) => parts,
+      None => {
+        if path == alias.pattern {
+          return Some(alias.target.clone());
+        }
+
+        continue;
+      }
+    };
+
+    let matches = path.len() >= prefix.len() + suffix.len()
+      && path.starts_with(prefix)
+      && path.ends_with(suffix);
+
+    if !matches
+      || best_match
+        .as_ref()
+        .is_some_and(|(len, _)| *len >= prefix.len())
+    {
+      continue;
+    }
+
+    let wildcard = &path[prefix.len()..path.len() - suffix.len()];
+    best_match = Some((prefix.len(), alias.target.replacen('*', wildcard, 1)));
+  }
+
+  best_match.map(|(_, target)| target)
+}
+
+/// Splits `https://example.com/a/b.ts` into `https://example.com` and `/a/b.ts`.
+fn split_url(path: &str) -> Option<(&str, &str)> {
+  let rest = path.strip_prefix("https://")?;
+  let origin_len = "https://".len() + rest.find('/').unwrap_or(rest.len());
+
+  Some(path.split_at(origin_len))
+}
+
+fn normalize_url_path(url_path: &Path) -> String {
+  let normalized = normalize_path(Path::new("/").join(url_path));
+  normalized
+    .to_str()
+    .expect("Failed to convert path to string")
+    .to_string()
+}
+
 fn normalize_path(path_buf: PathBuf) -> PathBuf {
   let mut dir_stack = Vec::new();
 
   for component in path_buf.components() {
     match component {
       std::path::Component::ParentDir => {
-        // TODO: Error if we're at the root dir
-        dir_stack.pop();
+        // TODO: Error if we're at the root dir (for now, like in urls, it stays at the root)
+        if !matches!(dir_stack.last(), Some(std::path::Component::RootDir) | None) {
+          dir_stack.pop();
+        }
       }
       std::path::Component::CurDir => {}
       _ => {
@@ -110,6 +110,61 @@ impl ScopeTrait for Scope {
   }
 }
 
+/// Finds the visible name that is closest to `name` (by edit distance), for "did you mean"
+/// suggestions. Only names that are reasonably close are suggested.
+pub fn suggest_name(scope: &Scope, name: &str) -> Option<String> {
+  let max_distance = std::cmp::max(1, name.chars().count() / 3);
+
+  let mut candidates = Vec::<(usize, String)>::new();
+  let mut current = Some(scope.clone());
+
+  while let Some(scope) = current {
+    for candidate in scope.borrow().name_map.keys() {
+      let distance = edit_distance(name, candidate);
+
+      if distance <= max_distance {
+        candidates.push((distance, candidate.to_string()));
+      }
+    }
+
+    current = scope.borrow().parent.clone();
+  }
+
+  // Sorting the candidates makes the suggestion deterministic when there are ties
+  candidates.sort();
+  candidates
+    .into_iter()
+    .next()
+    .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance, where changing only the case of a character costs nothing.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let a: Vec<char> = a.chars().collect();
+  let b: Vec<char> = b.chars().collect();
+
+  let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+
+  for i in 1..=a.len() {
+    let mut row = vec![i; b.len() + 1];
+
+    for j in 1..=b.len() {
+      let substitution_cost = match a[i - 1].eq_ignore_ascii_case(&b[j - 1]) {
+        true => 0,
+        false => 1,
+      };
+
+      row[j] = (prev_row[j] + 1)
+        .min(row[j - 1] + 1)
+        .min(prev_row[j - 1] + substitution_cost);
+    }
+
+    prev_row = row;
+  }
+
+  prev_row[b.len()]
+}
+
 pub fn init_std_scope() -> Scope {
   let mut name_map = HashMap::new();
 
@@ -11,21 +11,19 @@ use crate::{
   asm::{Builtin, Register, Value},
   constants::CONSTANTS,
   diagnostic::{DiagnosticContainer, DiagnosticReporter},
+  expression_compiler::written_back_arg,
   ident::Ident,
   name_allocator::{PointerAllocator, RegAllocator},
-  scope::{init_std_scope, NameId, OwnerId, Scope, ScopeTrait},
+  scope::{init_std_scope, suggest_name, NameId, OwnerId, Scope, ScopeTrait},
 };
 
 use super::diagnostic::Diagnostic;
 
-// TODO: Find a use for these or remove them
+/// A reference to a name from inside a function or class that doesn't own it.
 #[derive(Clone, Debug)]
 pub struct Capture {
-  #[allow(dead_code)]
-  ref_: swc_common::Span,
-
-  #[allow(dead_code)]
-  captor_id: OwnerId,
+  pub ref_: swc_common::Span,
+  pub captor_id: OwnerId,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -71,10 +69,22 @@ pub struct ScopeAnalysis {
   pub capture_values: HashMap<(OwnerId, NameId), Value>,
   pub mutations: BTreeMap<swc_common::Span, NameId>,
   pub optional_mutations: BTreeMap<swc_common::Span, NameId>,
+
+  /// Mutations in the update of a `for` loop to variables declared with `let` in its head. Each
+  /// iteration gets a fresh copy of these variables, so these mutations don't affect the values
+  /// captured during earlier iterations.
+  pub per_iteration_mutations: HashSet<swc_common::Span>,
+
   pub refs: BTreeMap<swc_common::Span, Ref>,
   pub diagnostics: RefCell<Vec<Diagnostic>>,
   pub pointer_allocator: PointerAllocator,
   pub reg_allocators: HashMap<OwnerId, RegAllocator>,
+
+  /// "Did you mean" suggestions for unresolved identifiers, by identifier span.
+  pub suggestions: HashMap<swc_common::Span, String>,
+
+  /// Owners of class expressions, which (unlike class declarations) can capture variables.
+  pub class_expressions: HashSet<OwnerId>,
 }
 
 impl DiagnosticContainer for ScopeAnalysis {
@@ -128,8 +138,21 @@ impl ScopeAnalysis {
 
     sa.module_level_hoists(&scope, module);
 
+    // Top level statements that aren't declarations run in their own scope (see
+    // ModuleCompiler::compile_module_init)
+    let init_scope = scope.nest(Some(module_init_owner_id(module)));
+
+    for stmt in module_init_stmts(module) {
+      sa.function_level_hoists_stmt(&init_scope, stmt);
+    }
+
     for module_item in &module.body {
-      sa.module_item(&scope, module_item);
+      match module_item {
+        swc_ecma_ast::ModuleItem::Stmt(stmt) if is_module_init_stmt(stmt) => {
+          sa.stmt(&init_scope, stmt);
+        }
+        _ => sa.module_item(&scope, module_item),
+      }
     }
 
     sa.find_capture_mutations();
@@ -146,6 +169,78 @@ impl ScopeAnalysis {
     sa
   }
 
+  /// The reference at `pos`, if any. Declarations count as references to the name they declare.
+  pub fn ref_at(&self, pos: swc_common::BytePos) -> Option<&Ref> {
+    let end = swc_common::Span {
+      lo: pos,
+      hi: swc_common::BytePos(u32::MAX),
+      ctxt: Default::default(),
+    };
+
+    let (_span, ref_) = self.refs.range(..=end).next_back()?;
+
+    match ref_.span.hi >= pos {
+      true => Some(ref_),
+      false => None,
+    }
+  }
+
+  /// All the references to a name (including its declaration) in source order, eg for renaming.
+  pub fn references(&self, name_id: &NameId) -> Vec<&Ref> {
+    self
+      .refs
+      .values()
+      .filter(|ref_| &ref_.name_id == name_id)
+      .collect()
+  }
+
+  /// The names from enclosing scopes that are used by a function or class.
+  pub fn captures_of(&self, captor_id: &OwnerId) -> Vec<&Name> {
+    let mut name_ids = match self.captures.get(captor_id) {
+      Some(caps) => caps.iter().collect::<Vec<_>>(),
+      None => return vec![],
+    };
+
+    name_ids.sort();
+
+    name_ids
+      .into_iter()
+      .filter_map(|name_id| self.names.get(name_id))
+      .collect()
+  }
+
+  /// Whether the reference is to a name that hasn't been initialized yet (the temporal dead zone).
+  /// Captures aren't included, since they are checked when the value is bound instead. Being
+  /// syntactically in the TDZ is actually ok sometimes, see captureButNotRefBeforeInit.ts.
+  pub fn is_in_tdz(&self, ref_: &Ref) -> bool {
+    let name = match self.names.get(&ref_.name_id) {
+      Some(name) => name,
+      None => return false,
+    };
+
+    if ref_.owner_id != name.owner_id {
+      return false;
+    }
+
+    let name_span = match &ref_.name_id {
+      NameId::Span(span) | NameId::This(span) => *span,
+      NameId::Builtin(_) | NameId::Constant(_) => return false,
+    };
+
+    if ref_.span.lo == name_span.lo {
+      // The origin of a name is allowed, eg
+      // const x = 42;
+      //       ^ Not a tdz violation
+
+      return false;
+    }
+
+    match name.tdz_end {
+      Some(tdz_end) => ref_.span.lo() <= tdz_end,
+      None => false,
+    }
+  }
+
   pub fn lookup(&self, ident: &Ident) -> Option<&Name> {
     let name_id = &self.refs.get(&ident.span)?.name_id;
     self.names.get(name_id)
@@ -481,10 +576,17 @@ impl ScopeAnalysis {
           break 'b;
         }
 
+        let mut member_names = HashSet::<swc_atoms::JsWord>::new();
+
         for member in &ts_enum.members {
           if let Some(init) = &member.init {
-            self.expr(scope, init);
+            self.enum_init(scope, &member_names, init);
           }
+
+          member_names.insert(match &member.id {
+            swc_ecma_ast::TsEnumMemberId::Ident(ident) => ident.sym.clone(),
+            swc_ecma_ast::TsEnumMemberId::Str(str) => str.value.clone(),
+          });
         }
       }
       Decl::TsModule(ts_module) => self.not_supported(ts_module.span, "TsModule declaration"),
@@ -594,8 +696,10 @@ impl ScopeAnalysis {
         }
       },
       ModuleItem::Stmt(stmt) => {
-        self.function_level_hoists_stmt(scope, stmt);
-        self.block_level_hoists_stmt(scope, stmt);
+        if !is_module_init_stmt(stmt) {
+          self.function_level_hoists_stmt(scope, stmt);
+          self.block_level_hoists_stmt(scope, stmt);
+        }
       }
     };
   }
@@ -905,6 +1009,10 @@ impl ScopeAnalysis {
       None => class_.span,
     };
 
+    if let Some(super_class) = &class_.super_class {
+      self.expr(scope, super_class);
+    }
+
     let child_scope = scope.nest(Some(OwnerId::Span(owner_span)));
     self.insert_this_name(&child_scope, owner_span);
 
@@ -1072,7 +1180,7 @@ impl ScopeAnalysis {
           self.expr(scope, arg);
         }
       }
-      Expr::Await(await_) => self.todo(await_.span, "async/await"),
+      Expr::Await(await_) => self.expr(scope, &await_.arg),
       Expr::Member(member) => self.member(scope, member),
       Expr::Call(call) => self.call(scope, call),
       Expr::New(new) => {
@@ -1097,6 +1205,11 @@ impl ScopeAnalysis {
         self.arrow(scope, arrow);
       }
       Expr::Class(class_expr) => {
+        self.class_expressions.insert(class_to_owner_id(
+          class_expr.ident.as_ref(),
+          &class_expr.class,
+        ));
+
         self.class_(scope, &class_expr.ident, &class_expr.class);
       }
       Expr::MetaProp(_) => {}
@@ -1119,7 +1232,14 @@ impl ScopeAnalysis {
           OptChainBase::Member(member) => self.member(scope, member),
         }
       }
-      Expr::SuperProp(super_prop) => self.todo(super_prop.span, "super_prop"),
+      Expr::SuperProp(super_prop) => {
+        // Uses `this`, which super.method(...) is applied to
+        self.ident(scope, &Ident::this(super_prop.obj.span));
+
+        if let swc_ecma_ast::SuperProp::Computed(computed) = &super_prop.prop {
+          self.expr(scope, &computed.expr);
+        }
+      }
       Expr::JSXEmpty(_) => {}
       Expr::JSXNamespacedName(_)
       | Expr::JSXElement(_)
@@ -1152,8 +1272,18 @@ impl ScopeAnalysis {
       swc_ecma_ast::Callee::Expr(expr) => {
         self.expr(scope, expr);
 
+        if let swc_ecma_ast::Expr::SuperProp(super_prop) = &**expr {
+          self.mutate_ident(scope, &Ident::this(super_prop.obj.span), true);
+        }
+
         if let swc_ecma_ast::Expr::Member(member) = &**expr {
           self.mutate_expr(scope, &member.obj, true);
+
+          if let Some(arg) = written_back_arg(member, &call.args) {
+            if let Some(NameId::Builtin(_)) = scope.get(&swc_atoms::JsWord::from("Object")) {
+              self.mutate_expr(scope, arg, false);
+            }
+          }
         };
       }
     }
@@ -1314,10 +1444,15 @@ impl ScopeAnalysis {
         diagnostic = Some(Diagnostic::todo(super_prop.span, "mutate super_prop"))
       }
       Expr::Cond(cond) => diagnostic = Some(Diagnostic::todo(cond.span, "mutate cond")),
-      Expr::Seq(seq) => diagnostic = Some(Diagnostic::todo(seq.span, "mutate seq")),
+      Expr::Seq(seq) => {
+        // Only the last expression is the target, the others are just evaluated
+        if let Some(last) = seq.exprs.last() {
+          self.mutate_expr(scope, last, optional);
+        }
+      }
       Expr::Lit(_) => diagnostic = Some(Diagnostic::todo(expr.span(), "mutate lit")),
       Expr::Yield(yield_) => diagnostic = Some(Diagnostic::todo(yield_.span, "mutate yield")),
-      Expr::Await(await_) => self.todo(await_.span, "mutate await"),
+      Expr::Await(await_) => diagnostic = Some(Diagnostic::todo(await_.span, "mutate await")),
       Expr::JSXMember(_) => self.todo(expr.span(), "mutate jsx_member"),
       Expr::JSXNamespacedName(_) => self.todo(expr.span(), "mutate jsx_namespaced_name"),
       Expr::JSXEmpty(_) => {}
@@ -1386,11 +1521,25 @@ impl ScopeAnalysis {
     }
   }
 
+  fn unresolved_reference(&mut self, scope: &Scope, ident: &Ident) {
+    match suggest_name(scope, &ident.sym) {
+      Some(suggestion) => {
+        self.error(
+          ident.span,
+          &format!("Unresolved reference (did you mean `{}`?)", suggestion),
+        );
+
+        self.suggestions.insert(ident.span, suggestion);
+      }
+      None => self.error(ident.span, "Unresolved reference"),
+    }
+  }
+
   fn mutate_ident(&mut self, scope: &Scope, ident: &Ident, optional: bool) {
     let name_id = match scope.get(&ident.sym) {
       Some(name_id) => name_id,
       None => {
-        self.error(ident.span, "Unresolved reference");
+        self.unresolved_reference(scope, ident);
         return;
       }
     };
@@ -1424,7 +1573,7 @@ impl ScopeAnalysis {
     let name_id = match scope.get(&ident.sym) {
       Some(name_id) => name_id,
       None => {
-        self.error(ident.span, "Unresolved reference");
+        self.unresolved_reference(scope, ident);
         return;
       }
     };
@@ -1449,6 +1598,28 @@ impl ScopeAnalysis {
     self.handle_capture_chain(scope, &name, ident.span);
   }
 
+  /// Enum initializers can refer to the members before them without qualification, so those names
+  /// aren't references to the enclosing scope.
+  fn enum_init(
+    &mut self,
+    scope: &Scope,
+    member_names: &HashSet<swc_atoms::JsWord>,
+    expr: &swc_ecma_ast::Expr,
+  ) {
+    use swc_ecma_ast::Expr;
+
+    match expr {
+      Expr::Ident(ident) if member_names.contains(&ident.sym) => {}
+      Expr::Paren(paren) => self.enum_init(scope, member_names, &paren.expr),
+      Expr::Unary(unary) => self.enum_init(scope, member_names, &unary.arg),
+      Expr::Bin(bin) => {
+        self.enum_init(scope, member_names, &bin.left);
+        self.enum_init(scope, member_names, &bin.right);
+      }
+      _ => self.expr(scope, expr),
+    }
+  }
+
   fn prop_key(&mut self, scope: &Scope, prop_name: &swc_ecma_ast::PropName) {
     use swc_ecma_ast::PropName;
 
@@ -1477,18 +1648,11 @@ impl ScopeAnalysis {
         }
         swc_ecma_ast::Prop::Getter(getter) => {
           self.prop_key(scope, &getter.key);
-
-          if let Some(body) = &getter.body {
-            self.block_stmt(scope, body);
-          }
+          self.function(scope, None, &getter_to_function(getter), true);
         }
         swc_ecma_ast::Prop::Setter(setter) => {
           self.prop_key(scope, &setter.key);
-          self.param_pat(scope, &setter.param);
-
-          if let Some(body) = &setter.body {
-            self.block_stmt(scope, body);
-          }
+          self.function(scope, None, &setter_to_function(setter), true);
         }
         swc_ecma_ast::Prop::Method(method) => {
           self.prop_key(scope, &method.key);
@@ -1609,6 +1773,12 @@ impl ScopeAnalysis {
           self.expr(&child_scope, update);
         }
 
+        if let Some(swc_ecma_ast::VarDeclOrExpr::VarDecl(var_decl)) = &for_.init {
+          if var_decl.kind == swc_ecma_ast::VarDeclKind::Let {
+            self.find_per_iteration_mutations(var_decl, &for_.update);
+          }
+        }
+
         self.stmt(&child_scope, &for_.body);
       }
       Stmt::ForIn(for_in) => {
@@ -1620,7 +1790,9 @@ impl ScopeAnalysis {
             self.var_decl(&child_scope, var_decl);
           }
           swc_ecma_ast::VarDeclOrPat::Pat(pat) => {
-            self.param_pat(&child_scope, pat);
+            // Without a declaration, the loop assigns to existing variables
+            self.pat(&child_scope, pat);
+            self.mutate_pat(&child_scope, pat);
           }
         }
 
@@ -1636,7 +1808,9 @@ impl ScopeAnalysis {
             self.var_decl(&child_scope, var_decl);
           }
           swc_ecma_ast::VarDeclOrPat::Pat(pat) => {
-            self.param_pat(&child_scope, pat);
+            // Without a declaration, the loop assigns to existing variables
+            self.pat(&child_scope, pat);
+            self.mutate_pat(&child_scope, pat);
           }
         }
 
@@ -1652,6 +1826,43 @@ impl ScopeAnalysis {
     };
   }
 
+  fn find_per_iteration_mutations(
+    &mut self,
+    var_decl: &swc_ecma_ast::VarDecl,
+    update: &Option<Box<swc_ecma_ast::Expr>>,
+  ) {
+    let update_span = match update {
+      Some(update) => update.span(),
+      None => return,
+    };
+
+    let mut loop_names = HashSet::<NameId>::new();
+
+    for decl in &var_decl.decls {
+      for ident in self.get_pat_idents(&decl.name) {
+        loop_names.insert(NameId::Span(ident.span));
+      }
+    }
+
+    let start = swc_common::Span {
+      lo: update_span.lo,
+      hi: update_span.lo,
+      ctxt: update_span.ctxt,
+    };
+
+    let end = swc_common::Span {
+      lo: update_span.hi,
+      hi: update_span.hi,
+      ctxt: update_span.ctxt,
+    };
+
+    for (span, name_id) in self.mutations.range(start..end) {
+      if loop_names.contains(name_id) {
+        self.per_iteration_mutations.insert(*span);
+      }
+    }
+  }
+
   fn block_stmt(&mut self, scope: &Scope, block_stmt: &swc_ecma_ast::BlockStmt) {
     let child_scope = scope.nest(None);
     self.block_level_hoists(&child_scope, block_stmt);
@@ -1686,6 +1897,7 @@ impl ScopeAnalysis {
         for prop in &object_pat.props {
           match prop {
             swc_ecma_ast::ObjectPatProp::KeyValue(key_value) => {
+              self.prop_key(scope, &key_value.key);
               self.param_pat(scope, &key_value.value);
             }
             swc_ecma_ast::ObjectPatProp::Assign(assign) => {
@@ -1721,7 +1933,13 @@ impl ScopeAnalysis {
   fn find_capture_mutations(&self) {
     for (name_id, name) in &self.names {
       if !name.captures.is_empty() {
-        if name.type_ == NameType::Let {
+        let mutations = name
+          .mutations
+          .iter()
+          .filter(|mutation| !self.per_iteration_mutations.contains(mutation))
+          .collect::<Vec<_>>();
+
+        if name.type_ == NameType::Let && mutations.len() == name.mutations.len() {
           match name_id {
             NameId::Span(span) => {
               self.lint(
@@ -1742,7 +1960,7 @@ impl ScopeAnalysis {
           }
         }
 
-        for mutation in &name.mutations {
+        for mutation in mutations {
           self.error(
             *mutation,
             &format!("Cannot mutate captured variable `{}`", name.sym),
@@ -1895,35 +2113,11 @@ impl ScopeAnalysis {
 
   fn diagnose_tdz_violations(&self) {
     for ref_ in self.refs.values() {
-      let name = self.names.get(&ref_.name_id).expect("Name not found");
-
-      if ref_.owner_id != name.owner_id {
-        // Captures are processed when the value is bound instead. Being syntactically in the TDZ is
-        // actually ok sometimes, see captureButNotRefBeforeInit.ts.
+      if !self.is_in_tdz(ref_) {
         continue;
       }
 
-      let name_span = match &ref_.name_id {
-        NameId::Span(span) | NameId::This(span) => *span,
-        NameId::Builtin(_) | NameId::Constant(_) => continue,
-      };
-
-      if ref_.span.lo == name_span.lo {
-        // The origin of a name is allowed, eg
-        // const x = 42;
-        //       ^ Not a tdz violation
-
-        continue;
-      }
-
-      let tdz_end = match name.tdz_end {
-        Some(tdz_end) => tdz_end,
-        None => continue,
-      };
-
-      if ref_.span.lo() > tdz_end {
-        continue;
-      }
+      let name = self.names.get(&ref_.name_id).expect("Name not found");
 
       self.error(
         ref_.span,
@@ -1946,6 +2140,10 @@ impl ScopeAnalysis {
         None => continue,
       };
 
+      if self.class_expressions.contains(&owner_id) {
+        continue;
+      }
+
       let captures = match self.captures.get(&owner_id) {
         Some(captures) => captures,
         None => continue,
@@ -2057,6 +2255,38 @@ pub fn fn_to_owner_id(
   OwnerId::Span(fn_owner_span(name, function))
 }
 
+/// Getters in object literals are compiled like functions without parameters.
+pub fn getter_to_function(getter: &swc_ecma_ast::GetterProp) -> swc_ecma_ast::Function {
+  swc_ecma_ast::Function {
+    params: vec![],
+    decorators: vec![],
+    span: getter.span,
+    body: getter.body.clone(),
+    is_generator: false,
+    is_async: false,
+    type_params: None,
+    return_type: None,
+  }
+}
+
+/// Setters in object literals are compiled like functions with one parameter.
+pub fn setter_to_function(setter: &swc_ecma_ast::SetterProp) -> swc_ecma_ast::Function {
+  swc_ecma_ast::Function {
+    params: vec![swc_ecma_ast::Param {
+      span: setter.param.span(),
+      decorators: vec![],
+      pat: setter.param.clone(),
+    }],
+    decorators: vec![],
+    span: setter.span,
+    body: setter.body.clone(),
+    is_generator: false,
+    is_async: false,
+    type_params: None,
+    return_type: None,
+  }
+}
+
 pub fn class_owner_span(
   name: Option<&swc_ecma_ast::Ident>,
   class: &swc_ecma_ast::Class,
@@ -2067,6 +2297,36 @@ pub fn class_owner_span(
   }
 }
 
+/// Whether a top level statement is part of the module's initialization code, rather than a
+/// declaration.
+pub fn is_module_init_stmt(stmt: &swc_ecma_ast::Stmt) -> bool {
+  !matches!(
+    stmt,
+    swc_ecma_ast::Stmt::Decl(_) | swc_ecma_ast::Stmt::Empty(_)
+  )
+}
+
+pub fn module_init_stmts(module: &swc_ecma_ast::Module) -> Vec<&swc_ecma_ast::Stmt> {
+  module
+    .body
+    .iter()
+    .filter_map(|module_item| match module_item {
+      swc_ecma_ast::ModuleItem::Stmt(stmt) if is_module_init_stmt(stmt) => Some(stmt),
+      _ => None,
+    })
+    .collect()
+}
+
+/// The owner of the module's initialization code. This is the (empty) span at the start of the
+/// module so that it doesn't coincide with a function that spans the whole module.
+pub fn module_init_owner_id(module: &swc_ecma_ast::Module) -> OwnerId {
+  OwnerId::Span(swc_common::Span {
+    lo: module.span.lo,
+    hi: module.span.lo,
+    ctxt: Default::default(),
+  })
+}
+
 pub fn class_to_owner_id(
   name: Option<&swc_ecma_ast::Ident>,
   class: &swc_ecma_ast::Class,
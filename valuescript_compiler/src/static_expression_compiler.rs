@@ -1,12 +1,16 @@
 use std::cell::RefCell;
 
 use swc_common::Spanned;
+use valuescript_common::number_to_string;
 use valuescript_vm::operations::to_i32;
 
 use crate::{
-  asm::{Array, Builtin, Number, Object, Value},
+  asm::{
+    Array, Builtin, Definition, DefinitionContent, FnLine, Instruction, Lazy, Number, Object,
+    Register, Value,
+  },
   diagnostic::{DiagnosticContainer, DiagnosticReporter},
-  expression_compiler::value_from_literal,
+  expression_compiler::{cooked_quasi, value_from_literal},
   function_compiler::Functionish,
   ident::Ident,
   module_compiler::ModuleCompiler,
@@ -29,13 +33,35 @@ impl<'a> StaticExpressionCompiler<'a> {
   }
 
   pub fn expr(&mut self, expr: &swc_ecma_ast::Expr) -> Value {
-    let symbol_iterator_opt = as_symbol_iterator(expr);
-
-    if let Some(symbol_iterator) = symbol_iterator_opt {
-      return symbol_iterator;
+    if let Some(symbol) = as_well_known_symbol(expr) {
+      return symbol;
     }
 
     match expr {
+      swc_ecma_ast::Expr::Lit(swc_ecma_ast::Lit::Regex(regex)) => {
+        // The RegExp is created by `new`, so it's evaluated lazily like the module init
+        let p = self.mc.allocate_defn_numbered("_regex");
+
+        self.mc.module.definitions.push(Definition {
+          pointer: p.clone(),
+          content: DefinitionContent::Lazy(Lazy {
+            body: vec![FnLine::Instruction(Instruction::New(
+              Value::Builtin(Builtin {
+                name: "RegExp".to_string(),
+              }),
+              Value::Array(Box::new(Array {
+                values: vec![
+                  Value::String(regex.exp.to_string()),
+                  Value::String(regex.flags.to_string()),
+                ],
+              })),
+              Register::return_(),
+            ))],
+          }),
+        });
+
+        Value::Pointer(p)
+      }
       swc_ecma_ast::Expr::Lit(lit) => match value_from_literal(lit) {
         Ok(value) => value,
         Err(msg) => {
@@ -253,7 +279,7 @@ impl<'a> StaticExpressionCompiler<'a> {
         assert_eq!(tpl.quasis.len(), len + 1);
 
         if len == 0 {
-          break 'b Value::String(tpl.quasis[0].raw.to_string());
+          break 'b Value::String(cooked_quasi(&tpl.quasis[0], self));
         }
 
         self.todo(tpl.span, "Static eval of template literal");
@@ -271,14 +297,16 @@ impl<'a> StaticExpressionCompiler<'a> {
     match prop_name {
       swc_ecma_ast::PropName::Ident(ident) => Value::String(ident.sym.to_string()),
       swc_ecma_ast::PropName::Str(str) => Value::String(str.value.to_string()),
-      swc_ecma_ast::PropName::Num(num) => Value::String(num.value.to_string()),
+      swc_ecma_ast::PropName::Num(num) => Value::String(number_to_string(num.value)),
       swc_ecma_ast::PropName::Computed(computed) => self.expr(&computed.expr),
       swc_ecma_ast::PropName::BigInt(bi) => Value::String(bi.value.to_string()),
     }
   }
 }
 
-fn as_symbol_iterator(expr: &swc_ecma_ast::Expr) -> Option<Value> {
+/// Well-known symbols like `Symbol.iterator` are available statically (e.g. as computed method
+/// names in classes) via their dedicated builtins.
+fn as_well_known_symbol(expr: &swc_ecma_ast::Expr) -> Option<Value> {
   let member_expr = match expr {
     swc_ecma_ast::Expr::Member(member_expr) => member_expr,
     _ => return None,
@@ -293,16 +321,64 @@ fn as_symbol_iterator(expr: &swc_ecma_ast::Expr) -> Option<Value> {
     _ => return None,
   };
 
-  match &member_expr.prop {
-    swc_ecma_ast::MemberProp::Ident(ident) => {
-      if ident.sym.to_string() != "iterator" {
-        return None;
-      }
-    }
+  let name = match &member_expr.prop {
+    swc_ecma_ast::MemberProp::Ident(ident) => match ident.sym.to_string().as_str() {
+      "iterator" => "SymbolIterator",
+      "asyncIterator" => "SymbolAsyncIterator",
+      "toPrimitive" => "SymbolToPrimitive",
+      "hasInstance" => "SymbolHasInstance",
+      "toStringTag" => "SymbolToStringTag",
+      _ => return None,
+    },
     _ => return None,
-  }
+  };
 
   Some(Value::Builtin(Builtin {
-    name: "SymbolIterator".to_string(),
+    name: name.to_string(),
   }))
 }
+
+/// Whether `expr` can be compiled by the static expression compiler. Other expressions need to run
+/// code to be evaluated.
+pub fn is_static_expr(expr: &swc_ecma_ast::Expr) -> bool {
+  use swc_ecma_ast::{Expr, Prop, PropOrSpread, UnaryOp};
+
+  if as_well_known_symbol(expr).is_some() {
+    return true;
+  }
+
+  match expr {
+    Expr::Lit(_) | Expr::Ident(_) | Expr::Fn(_) | Expr::Arrow(_) | Expr::Class(_) => true,
+    Expr::Array(array) => array.elems.iter().all(|item| match item {
+      Some(item) => item.spread.is_none() && is_static_expr(&item.expr),
+      None => true,
+    }),
+    Expr::Object(object) => object.props.iter().all(|prop| match prop {
+      PropOrSpread::Spread(_) => false,
+      PropOrSpread::Prop(prop) => match &**prop {
+        Prop::KeyValue(kv) => is_static_prop_name(&kv.key) && is_static_expr(&kv.value),
+        Prop::Method(method) => is_static_prop_name(&method.key),
+        Prop::Shorthand(_) | Prop::Assign(_) | Prop::Getter(_) | Prop::Setter(_) => false,
+      },
+    }),
+    Expr::Unary(unary) => {
+      matches!(unary.op, UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Tilde)
+        && is_static_expr(&unary.arg)
+    }
+    Expr::Seq(seq) => seq.exprs.iter().all(|expr| is_static_expr(expr)),
+    Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+    Expr::Paren(paren) => is_static_expr(&paren.expr),
+    Expr::TsTypeAssertion(tta) => is_static_expr(&tta.expr),
+    Expr::TsConstAssertion(tca) => is_static_expr(&tca.expr),
+    Expr::TsNonNull(tnn) => is_static_expr(&tnn.expr),
+    Expr::TsAs(ta) => is_static_expr(&ta.expr),
+    _ => false,
+  }
+}
+
+fn is_static_prop_name(prop_name: &swc_ecma_ast::PropName) -> bool {
+  match prop_name {
+    swc_ecma_ast::PropName::Computed(computed) => is_static_expr(&computed.expr),
+    _ => true,
+  }
+}
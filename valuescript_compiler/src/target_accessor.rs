@@ -32,6 +32,10 @@ impl TargetAccessor {
       Member(member) => TargetAccessor::is_eligible_expr(ec, &member.obj),
 
       Paren(e) => TargetAccessor::is_eligible_expr(ec, &e.expr),
+      Seq(seq) => match seq.exprs.last() {
+        Some(last) => TargetAccessor::is_eligible_expr(ec, last),
+        None => false,
+      },
       TsTypeAssertion(e) => TargetAccessor::is_eligible_expr(ec, &e.expr),
       //
       // Deliberately excluding this so you can get a const_subcall using (x as const).foo().
@@ -81,6 +85,10 @@ impl TargetAccessor {
       }
 
       Paren(e) => TargetAccessor::compile(ec, &e.expr, false),
+      Seq(seq) => {
+        let last = TargetAccessor::compile_seq_prefix(ec, seq);
+        TargetAccessor::compile(ec, last, is_outermost)
+      }
       TsTypeAssertion(e) => TargetAccessor::compile(ec, &e.expr, false),
       TsNonNull(e) => TargetAccessor::compile(ec, &e.expr, false),
       TsAs(e) => TargetAccessor::compile(ec, &e.expr, false),
@@ -100,6 +108,22 @@ impl TargetAccessor {
     }
   }
 
+  /// Evaluates everything before the last expression of a seq for its side effects, and returns
+  /// the last expression, which is the actual target.
+  fn compile_seq_prefix<'a>(
+    ec: &mut ExpressionCompiler,
+    seq: &'a swc_ecma_ast::SeqExpr,
+  ) -> &'a swc_ecma_ast::Expr {
+    let (last, others) = seq.exprs.split_last().expect("Empty seq expression");
+
+    for expr in others {
+      let ce = ec.compile(expr, None);
+      ec.fnc.release_ce(ce);
+    }
+
+    last
+  }
+
   pub fn compile_ident(ec: &mut ExpressionCompiler, ident: &CrateIdent) -> TargetAccessor {
     TargetAccessor::Register(ec.get_register_for_ident_mutation(ident))
   }
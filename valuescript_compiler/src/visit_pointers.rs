@@ -110,6 +110,11 @@ where
       }
       Class(class) => {
         self.meta(owner, &mut class.meta);
+
+        if let Some(extends) = &mut class.extends {
+          self.value(owner, extends);
+        }
+
         self.value(owner, &mut class.constructor);
         self.value(owner, &mut class.prototype);
         self.value(owner, &mut class.static_);
@@ -132,7 +137,7 @@ where
 
     match instruction {
       End | UnsetCatch | RequireMutableThis | OpInc(..) | OpDec(..) | Jmp(..) | SetCatch(..)
-      | Next(..) | UnpackIterRes(..) => {}
+      | Next(..) | UnpackIterRes(..) | NewTarget(..) => {}
       Mov(arg, _)
       | OpNot(arg, _)
       | OpBitNot(arg, _)
@@ -144,7 +149,8 @@ where
       | Throw(arg)
       | Cat(arg, _)
       | Yield(arg, _)
-      | YieldStar(arg, _) => {
+      | YieldStar(arg, _)
+      | Await(arg, _) => {
         self.value(Some(owner), arg);
       }
       OpPlus(arg1, arg2, _)
@@ -195,6 +201,9 @@ where
         self.value(Some(owner), key);
         self.value(Some(owner), args);
       }
+      Delete(_obj, key, _) => {
+        self.value(Some(owner), key);
+      }
       SubCall(_this, key, args, _) | ThisSubCall(_this, key, args, _) => {
         self.value(Some(owner), key);
         self.value(Some(owner), args);
@@ -0,0 +1,144 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ptr::null_mut;
+
+const CLASS_SIZE: usize = 16;
+const CLASS_COUNT: usize = 8; // Blocks of up to 128 bytes
+const MAX_CACHED_PER_CLASS: usize = 4096;
+
+/// Global allocator that recycles small allocations through thread-local free lists instead of
+/// returning them to the system allocator.
+///
+/// Most of the allocations made while running a program are small and short-lived (the `Rc`
+/// boxes behind arrays, objects, strings and frames), so this avoids most of the round trips to
+/// the system allocator. The cached blocks are handed back to the system when
+/// `VirtualMachine::run` finishes.
+///
+/// There can only be one global allocator, so this is instead of CountingAllocator, and runs with
+/// the memory limit of VirtualMachineBuilder fail with the arena feature. Opt in from the final
+/// binary:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: valuescript_vm::ArenaAllocator = valuescript_vm::ArenaAllocator;
+/// ```
+pub struct ArenaAllocator;
+
+struct FreeBlock {
+  next: *mut FreeBlock,
+}
+
+struct Pool {
+  heads: [Cell<*mut FreeBlock>; CLASS_COUNT],
+  lens: [Cell<usize>; CLASS_COUNT],
+}
+
+impl Pool {
+  const fn new() -> Pool {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const HEAD: Cell<*mut FreeBlock> = Cell::new(null_mut());
+    #[allow(clippy::declare_interior_mutable_const)]
+    const LEN: Cell<usize> = Cell::new(0);
+
+    Pool {
+      heads: [HEAD; CLASS_COUNT],
+      lens: [LEN; CLASS_COUNT],
+    }
+  }
+}
+
+thread_local! {
+  // Pool has no destructor, so accessing it never allocates or registers a destructor, which
+  // would otherwise recurse into the allocator.
+  static POOL: Pool = const { Pool::new() };
+}
+
+/// The size class for an allocation, or None if it should go straight to the system allocator.
+fn size_class(layout: &Layout) -> Option<usize> {
+  if layout.size() == 0 || layout.size() > CLASS_SIZE * CLASS_COUNT || layout.align() > CLASS_SIZE {
+    return None;
+  }
+
+  Some((layout.size() - 1) / CLASS_SIZE)
+}
+
+fn class_layout(class: usize) -> Layout {
+  // Sizes and alignment are constant and valid
+  unsafe { Layout::from_size_align_unchecked((class + 1) * CLASS_SIZE, CLASS_SIZE) }
+}
+
+unsafe impl GlobalAlloc for ArenaAllocator {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let class = match size_class(&layout) {
+      Some(class) => class,
+      None => return System.alloc(layout),
+    };
+
+    let recycled = POOL
+      .try_with(|pool| {
+        let head = pool.heads[class].get();
+
+        if head.is_null() {
+          return null_mut();
+        }
+
+        pool.heads[class].set((*head).next);
+        pool.lens[class].set(pool.lens[class].get() - 1);
+
+        head as *mut u8
+      })
+      .unwrap_or(null_mut());
+
+    if !recycled.is_null() {
+      return recycled;
+    }
+
+    System.alloc(class_layout(class))
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    let class = match size_class(&layout) {
+      Some(class) => class,
+      None => return System.dealloc(ptr, layout),
+    };
+
+    let cached = POOL
+      .try_with(|pool| {
+        if pool.lens[class].get() >= MAX_CACHED_PER_CLASS {
+          return false;
+        }
+
+        let block = ptr as *mut FreeBlock;
+        (*block).next = pool.heads[class].get();
+        pool.heads[class].set(block);
+        pool.lens[class].set(pool.lens[class].get() + 1);
+
+        true
+      })
+      .unwrap_or(false);
+
+    if !cached {
+      System.dealloc(ptr, class_layout(class));
+    }
+  }
+}
+
+/// Returns the blocks cached by the current thread to the system allocator. Does nothing if
+/// ArenaAllocator is not the global allocator.
+pub fn release_arena() {
+  let _ = POOL.try_with(|pool| {
+    for class in 0..CLASS_COUNT {
+      let mut head = pool.heads[class].replace(null_mut());
+      pool.lens[class].set(0);
+
+      while !head.is_null() {
+        // Only blocks allocated by ArenaAllocator with this class's layout are ever cached
+        unsafe {
+          let next = (*head).next;
+          System.dealloc(head as *mut u8, class_layout(class));
+          head = next;
+        }
+      }
+    }
+  });
+}
@@ -11,6 +11,10 @@ pub static FIND: NativeFrameFunction = NativeFrameFunction {
 struct FindState {}
 
 impl ArrayMappingState for FindState {
+  fn visits_holes(&self) -> bool {
+    true
+  }
+
   fn process(&mut self, _i: usize, element: &Val, mapped: Val) -> Option<Val> {
     match mapped.is_truthy() {
       true => Some(element.clone()),
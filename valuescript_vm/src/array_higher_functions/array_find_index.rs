@@ -11,6 +11,10 @@ pub static FIND_INDEX: NativeFrameFunction = NativeFrameFunction {
 struct FindIndexState {}
 
 impl ArrayMappingState for FindIndexState {
+  fn visits_holes(&self) -> bool {
+    true
+  }
+
   fn process(&mut self, i: usize, _element: &Val, mapped: Val) -> Option<Val> {
     match mapped.is_truthy() {
       true => Some(Val::Number(i as f64)),
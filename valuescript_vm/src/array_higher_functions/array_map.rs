@@ -20,6 +20,10 @@ impl ArrayMappingState for MapState {
     None
   }
 
+  fn process_hole(&mut self, _i: usize) {
+    self.map_results.push(Val::Void);
+  }
+
   fn finish(&mut self) -> Val {
     let mut map_results = Vec::new();
     std::mem::swap(&mut self.map_results, &mut map_results);
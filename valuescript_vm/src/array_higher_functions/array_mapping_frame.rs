@@ -1,14 +1,22 @@
-use std::rc::Rc;
-
 use crate::builtins::type_error_builtin::ToTypeError;
 use crate::native_function::ThisWrapper;
+use crate::rc::{MaybeSync, Rc};
 use crate::stack_frame::{CallResult, FrameStepOk, StackFrameTrait};
 use crate::stack_frame::{FrameStepResult, StackFrame};
-use crate::vs_array::VsArray;
+use crate::vs_array::{read_element, VsArray};
 use crate::vs_value::{LoadFunctionResult, Val, ValTrait};
 
-pub trait ArrayMappingState {
+pub trait ArrayMappingState: MaybeSync {
   fn process(&mut self, i: usize, element: &Val, mapped: Val) -> Option<Val>;
+
+  /// Whether holes are passed to the mapper (as undefined), like find and findIndex do. Otherwise
+  /// holes are skipped and reported via process_hole.
+  fn visits_holes(&self) -> bool {
+    false
+  }
+
+  fn process_hole(&mut self, _i: usize) {}
+
   fn finish(&mut self) -> Val;
   fn clone_to_array_mapping_state(&self) -> Box<dyn ArrayMappingState>;
 }
@@ -86,17 +94,20 @@ impl StackFrameTrait for ArrayMappingFrame {
 
     match array_data.elements.get(array_i) {
       Some(el) => match el {
-        Val::Void => Ok(FrameStepOk::Continue),
+        Val::Void if !self.state.visits_holes() => {
+          self.state.process_hole(array_i);
+          Ok(FrameStepOk::Continue)
+        }
         _ => match self.mapper.load_function() {
           LoadFunctionResult::NotAFunction => Err("map fn is not a function".to_type_error()),
           LoadFunctionResult::NativeFunction(native_fn) => {
             match self.state.process(
               array_i,
-              el,
+              read_element(el),
               native_fn(
                 ThisWrapper::new(false, &mut self.this_arg.clone()),
                 vec![
-                  el.clone(),
+                  read_element(el).clone(),
                   Val::Number(array_i as f64),
                   Val::Array(array_data.clone()),
                 ],
@@ -111,7 +122,7 @@ impl StackFrameTrait for ArrayMappingFrame {
           }
           LoadFunctionResult::StackFrame(mut new_frame) => {
             new_frame.write_this(true, self.this_arg.clone())?;
-            new_frame.write_param(el.clone());
+            new_frame.write_param(read_element(el).clone());
             new_frame.write_param(Val::Number(array_i as f64));
             new_frame.write_param(Val::Array(array_data.clone()));
             Ok(FrameStepOk::Push(new_frame))
@@ -133,7 +144,7 @@ impl StackFrameTrait for ArrayMappingFrame {
         self.early_exit = Some(Err("Array fn called on non-array".to_type_error()));
         return;
       }
-      Some(ad) => &ad.elements[array_i],
+      Some(ad) => read_element(&ad.elements[array_i]),
     };
 
     self.early_exit = self
@@ -1,8 +1,7 @@
-use std::rc::Rc;
-
 use crate::builtins::type_error_builtin::ToTypeError;
 use crate::native_frame_function::NativeFrameFunction;
 use crate::native_function::ThisWrapper;
+use crate::rc::Rc;
 use crate::stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait};
 use crate::vs_array::VsArray;
 use crate::vs_value::{LoadFunctionResult, Val, ValTrait};
@@ -1,6 +1,5 @@
 use std::cmp::{max, min};
 use std::mem::take;
-use std::rc::Rc;
 
 use crate::array_higher_functions::{
   array_every::EVERY, array_filter::FILTER, array_find::FIND, array_find_index::FIND_INDEX,
@@ -13,8 +12,10 @@ use crate::iteration::array_entries_iterator::ArrayEntriesIterator;
 use crate::iteration::array_iterator::ArrayIterator;
 use crate::native_function::{native_fn, NativeFunction};
 use crate::operations::op_triple_eq_impl;
+use crate::rc::Rc;
+use crate::size_limits::check_array_length;
 use crate::todo_fn::TODO;
-use crate::vs_array::VsArray;
+use crate::vs_array::{read_element, VsArray};
 use crate::vs_symbol::VsSymbol;
 use crate::vs_value::{ToDynamicVal, ToVal, Val, ValTrait, VsType};
 
@@ -24,15 +25,29 @@ pub fn op_sub_array(array: &mut Rc<VsArray>, key: &Val) -> Result<Val, Val> {
   }
 
   if let Val::Symbol(symbol) = key {
-    return Ok(
-      match symbol {
-        VsSymbol::ITERATOR => &VALUES,
-      }
-      .to_val(),
-    );
+    return Ok(match symbol {
+      VsSymbol::ITERATOR => VALUES.to_val(),
+      VsSymbol::ASYNC_ITERATOR
+      | VsSymbol::TO_PRIMITIVE
+      | VsSymbol::HAS_INSTANCE
+      | VsSymbol::TO_STRING_TAG
+      | VsSymbol::GENERATOR_RETURN
+      | VsSymbol::CLASS_NAME
+      | VsSymbol::Registered(_) => Val::Undefined,
+    });
   }
 
-  Ok(Val::Static(match key.to_string().as_str() {
+  Ok(match key.to_string().as_str() {
+    "length" => (array.elements.len() as f64).to_val(),
+    method => match get_array_method(method) {
+      Some(method) => Val::Static(method),
+      None => Val::Undefined,
+    },
+  })
+}
+
+pub fn get_array_method(method: &str) -> Option<&'static dyn ValTrait> {
+  Some(match method {
     "at" => &AT,
     "concat" => &CONCAT,
     "copyWithin" => &COPY_WITHIN,
@@ -51,7 +66,6 @@ pub fn op_sub_array(array: &mut Rc<VsArray>, key: &Val) -> Result<Val, Val> {
     "join" => &JOIN,
     "keys" => &TODO,
     "lastIndexOf" => &LAST_INDEX_OF,
-    "length" => return Ok((array.elements.len() as f64).to_val()),
     "map" => &MAP,
     "pop" => &POP,
     "push" => &PUSH,
@@ -67,8 +81,8 @@ pub fn op_sub_array(array: &mut Rc<VsArray>, key: &Val) -> Result<Val, Val> {
     "toString" => &TO_STRING,
     "unshift" => &UNSHIFT,
     "values" => &VALUES,
-    _ => return Ok(Val::Undefined),
-  }))
+    _ => return None,
+  })
 }
 
 pub fn op_sub_array_index(array: &mut Rc<VsArray>, index: usize) -> Result<Val, Val> {
@@ -91,7 +105,7 @@ static AT: NativeFunction = native_fn(|this, params| {
   Ok(match this.get() {
     Val::Array(array_data) => match to_wrapping_index(params.get(0), array_data.elements.len()) {
       None => Val::Undefined,
-      Some(i) => array_data.elements[i].clone(),
+      Some(i) => read_element(&array_data.elements[i]).clone(),
     },
     _ => return Err("array indirection".to_internal_error()),
   })
@@ -108,6 +122,8 @@ static CONCAT: NativeFunction = native_fn(|this, params| {
             new_array.elements.push(p);
           }
           Some(p_array_data) => {
+            check_array_length(new_array.elements.len() + p_array_data.elements.len())?;
+
             for elem in &p_array_data.elements {
               new_array.elements.push(elem.clone());
             }
@@ -115,6 +131,8 @@ static CONCAT: NativeFunction = native_fn(|this, params| {
         }
       }
 
+      check_array_length(new_array.elements.len())?;
+
       new_array.to_val()
     }
     _ => return Err("array indirection".to_internal_error()),
@@ -239,11 +257,15 @@ static FLAT: NativeFunction = native_fn(|this, params| {
       for el in &array_data.elements {
         match &el.as_array_data() {
           None => {
-            new_elems.push(el.clone());
+            if !matches!(el, Val::Void) {
+              new_elems.push(el.clone());
+            }
           }
           Some(p_array_data) => {
             for elem in &p_array_data.elements {
-              new_elems.push(elem.clone());
+              if !matches!(elem, Val::Void) {
+                new_elems.push(elem.clone());
+              }
             }
           }
         }
@@ -261,7 +283,7 @@ static INCLUDES: NativeFunction = native_fn(|this, params| {
       let search_param = params.get(0).unwrap_or(&Val::Undefined);
 
       for elem in &array_data.elements {
-        let is_eq = op_triple_eq_impl(elem, search_param)
+        let is_eq = op_triple_eq_impl(read_element(elem), search_param)
           .map_err(|e| e.to_string())
           .unwrap(); // TODO: Exception
 
@@ -282,6 +304,10 @@ static INDEX_OF: NativeFunction = native_fn(|this, params| {
       let search_param = params.get(0).unwrap_or(&Val::Undefined);
 
       for i in 0..array_data.elements.len() {
+        if let Val::Void = array_data.elements[i] {
+          continue;
+        }
+
         let is_eq = op_triple_eq_impl(&array_data.elements[i], search_param)
           .map_err(|e| e.to_string())
           .unwrap(); // TODO: Exception
@@ -339,6 +365,10 @@ static LAST_INDEX_OF: NativeFunction = native_fn(|this, params| {
       let search_param = params.get(0).unwrap_or(&Val::Undefined);
 
       for i in (0..array_data.elements.len()).rev() {
+        if let Val::Void = array_data.elements[i] {
+          continue;
+        }
+
         let is_eq = op_triple_eq_impl(&array_data.elements[i], search_param)
           .map_err(|e| e.to_string())
           .unwrap(); // TODO: Exception
@@ -383,6 +413,8 @@ static PUSH: NativeFunction = native_fn(|mut this, mut params| {
 
   Ok(match this {
     Val::Array(array_data) => {
+      check_array_length(array_data.elements.len() + params.len())?;
+
       let array_data_mut = Rc::make_mut(array_data);
       array_data_mut.elements.append(&mut params);
       (array_data_mut.elements.len() as f64).to_val()
@@ -542,6 +574,8 @@ static UNSHIFT: NativeFunction = native_fn(|mut this, params| {
 
   Ok(match this {
     Val::Array(array_data) => {
+      check_array_length(array_data.elements.len() + params.len())?;
+
       let array_data_mut = Rc::make_mut(array_data);
 
       for (i, p) in params.into_iter().enumerate() {
@@ -0,0 +1,185 @@
+use std::mem::take;
+
+use crate::builtins::type_error_builtin::ToTypeError;
+use crate::bytecode_stack_frame::BytecodeStackFrame;
+use crate::generator::Generator;
+use crate::native_frame_function::NativeFrameFunction;
+use crate::promise::{when_settled, Reaction, VsPromise};
+use crate::stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait};
+use crate::vs_value::{LoadFunctionResult, ToDynamicVal, ToVal, Val, ValTrait};
+
+/// Runs an async function. The body is compiled like a generator where each `await` is a yield, so
+/// this drives it as a generator: the body runs until it awaits a value, and then it's resumed by a
+/// microtask (ASYNC_RESUME) once that value has settled. The call itself returns a promise for the
+/// result of the body.
+///
+/// `this` is const within async functions, since the body can continue after the call returns.
+#[derive(Clone)]
+pub struct AsyncFrame {
+  /// The body of the async function before it has started.
+  body: Option<BytecodeStackFrame>,
+
+  this: Val,
+  generator: Val,
+  promise: Option<VsPromise>,
+
+  /// The value to resume the body with, and whether it should be thrown instead.
+  resumption: Option<(Val, bool)>,
+
+  /// The iteration result of the body once it has suspended or completed.
+  iter_result: Option<Val>,
+
+  /// Parameters of ASYNC_RESUME: generator, promise, value, rejected.
+  params: Vec<Val>,
+
+  /// Set when the body threw, which rejects the promise.
+  done: bool,
+}
+
+impl AsyncFrame {
+  pub fn new(body: BytecodeStackFrame) -> AsyncFrame {
+    AsyncFrame {
+      body: Some(body),
+      ..AsyncFrame::resuming()
+    }
+  }
+
+  fn resuming() -> AsyncFrame {
+    AsyncFrame {
+      body: None,
+      this: Val::Undefined,
+      generator: Val::Undefined,
+      promise: None,
+      resumption: None,
+      iter_result: None,
+      params: vec![],
+      done: false,
+    }
+  }
+
+  fn start(&mut self) {
+    if let Some(mut body) = take(&mut self.body) {
+      body.suspend();
+
+      self.generator = Generator::new(Box::new(body)).to_dynamic_val();
+      self.promise = Some(VsPromise::new_pending());
+      self.resumption = Some((Val::Undefined, false));
+
+      return;
+    }
+
+    let mut params = take(&mut self.params).into_iter();
+    self.generator = params.next().unwrap_or(Val::Undefined);
+    self.promise = params
+      .next()
+      .and_then(|promise| VsPromise::from_val(&promise));
+
+    let value = params.next().unwrap_or(Val::Undefined);
+    let rejected = params.next().is_some_and(|rejected| rejected.is_truthy());
+    self.resumption = Some((value, rejected));
+  }
+
+  fn finish(&mut self) -> FrameStepResult {
+    Ok(FrameStepOk::Pop(CallResult {
+      return_: match self.promise {
+        Some(promise) => promise.to_dynamic_val(),
+        None => Val::Undefined,
+      },
+      this: take(&mut self.this),
+    }))
+  }
+}
+
+impl StackFrameTrait for AsyncFrame {
+  fn write_this(&mut self, _const: bool, this: Val) -> Result<(), Val> {
+    if let Some(body) = &mut self.body {
+      body.write_this(true, this.clone())?;
+    }
+
+    self.this = this;
+
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    match &mut self.body {
+      Some(body) => body.write_param(param),
+      None => self.params.push(param),
+    }
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    if self.done {
+      return self.finish();
+    }
+
+    if let Some(iter_result) = take(&mut self.iter_result) {
+      let value = iter_result.sub(&"value".to_val())?;
+
+      if let Some(promise) = self.promise {
+        match iter_result.sub(&"done".to_val())?.is_truthy() {
+          true => promise.resolve(value),
+          false => when_settled(
+            value,
+            Reaction {
+              job: ASYNC_RESUME.to_val(),
+              params: vec![take(&mut self.generator), promise.to_dynamic_val()],
+            },
+          ),
+        }
+      }
+
+      return self.finish();
+    }
+
+    if self.resumption.is_none() {
+      self.start();
+    }
+
+    let (value, rejected) = take(&mut self.resumption).unwrap_or((Val::Undefined, false));
+
+    let method = match rejected {
+      false => "next",
+      true => "throw",
+    };
+
+    let mut frame = match self.generator.sub(&method.to_val())?.load_function() {
+      LoadFunctionResult::StackFrame(frame) => frame,
+      _ => return Err("Invalid async function state".to_type_error()),
+    };
+
+    frame.write_this(false, take(&mut self.generator))?;
+    frame.write_param(value);
+
+    Ok(FrameStepOk::Push(frame))
+  }
+
+  fn apply_call_result(&mut self, call_result: CallResult) {
+    self.generator = call_result.this;
+    self.iter_result = Some(call_result.return_);
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for AsyncFrame")
+  }
+
+  fn catch_exception(&mut self, exception: &mut Val) {
+    // Exceptions that make it out of the body reject the promise instead of propagating
+    if let Some(promise) = self.promise {
+      promise.reject(take(exception));
+      *exception = Val::Void;
+
+      // The next step completes the call with the (now rejected) promise
+      self.done = true;
+    }
+  }
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
+
+/// Resumes an async function once the value it awaited has settled.
+static ASYNC_RESUME: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(AsyncFrame::resuming()),
+};
@@ -0,0 +1,499 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::mem::take;
+
+use num_bigint::BigInt;
+
+use crate::builtins::internal_error_builtin::ToInternalError;
+use crate::builtins::type_error_builtin::ToTypeError;
+use crate::generator::Generator;
+use crate::host::with_current_host;
+use crate::iteration::iteration_result::IterationResult;
+use crate::iteration::return_this::RETURN_THIS;
+use crate::native_frame_function::NativeFrameFunction;
+use crate::promise::{when_settled, Reaction, VsPromise};
+use crate::rc::Rc;
+use crate::stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait};
+use crate::vs_array::VsArray;
+use crate::vs_class::VsClass;
+use crate::vs_symbol::VsSymbol;
+use crate::vs_value::{LoadFunctionResult, ToDynamicVal, ToVal, Val, ValTrait, VsType};
+
+/// A handle to an async generator in the async generator table of the host.
+///
+/// Like promises, async generators have identity. The body keeps running after next() has returned
+/// its promise, so requests made through any copy of the handle go to the same body. Requests are
+/// served one at a time, in the order they were made.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VsAsyncGenerator {
+  pub id: u64,
+}
+
+/// The state of the async generators created by a program.
+#[derive(Default)]
+pub struct AsyncGeneratorTable {
+  next_id: u64,
+  states: HashMap<u64, AsyncGeneratorState>,
+}
+
+struct AsyncGeneratorState {
+  body: Body,
+
+  /// Requests that haven't completed yet, oldest first. The first one is being served unless the
+  /// body is idle.
+  queue: VecDeque<Request>,
+}
+
+enum Body {
+  /// Suspended at the start or at a yield (or finished), waiting for a request.
+  Idle(Generator),
+
+  /// Being run by an AsyncGeneratorFrame.
+  Running,
+
+  /// Suspended at an await, waiting for ASYNC_GENERATOR_RESUME.
+  Awaiting(Generator),
+}
+
+#[derive(Clone, Copy)]
+enum RequestKind {
+  Next,
+  Return,
+  Throw,
+}
+
+struct Request {
+  kind: RequestKind,
+  value: Val,
+  promise: VsPromise,
+}
+
+impl VsAsyncGenerator {
+  pub fn new(generator: Generator) -> VsAsyncGenerator {
+    with_current_host(|host| {
+      let table = &mut host.async_generators;
+      let id = table.next_id;
+      table.next_id += 1;
+
+      table.states.insert(
+        id,
+        AsyncGeneratorState {
+          body: Body::Idle(generator),
+          queue: VecDeque::new(),
+        },
+      );
+
+      VsAsyncGenerator { id }
+    })
+  }
+
+  pub fn from_val(val: &Val) -> Option<VsAsyncGenerator> {
+    match val {
+      Val::Dynamic(dynamic) => dynamic.as_any().downcast_ref::<VsAsyncGenerator>().copied(),
+      _ => None,
+    }
+  }
+
+  fn with_state<T>(&self, f: impl FnOnce(&mut AsyncGeneratorState) -> T) -> Result<T, Val> {
+    with_current_host(|host| host.async_generators.states.get_mut(&self.id).map(f))
+      .ok_or_else(|| "Unknown async generator".to_internal_error())
+  }
+}
+
+impl ValTrait for VsAsyncGenerator {
+  fn typeof_(&self) -> VsType {
+    VsType::Object
+  }
+
+  fn to_number(&self) -> f64 {
+    f64::NAN
+  }
+
+  fn to_index(&self) -> Option<usize> {
+    None
+  }
+
+  fn is_primitive(&self) -> bool {
+    false
+  }
+
+  fn is_truthy(&self) -> bool {
+    true
+  }
+
+  fn is_nullish(&self) -> bool {
+    false
+  }
+
+  fn bind(&self, _params: Vec<Val>) -> Option<Val> {
+    None
+  }
+
+  fn as_bigint_data(&self) -> Option<BigInt> {
+    None
+  }
+
+  fn as_array_data(&self) -> Option<Rc<VsArray>> {
+    None
+  }
+
+  fn as_class_data(&self) -> Option<Rc<VsClass>> {
+    None
+  }
+
+  fn load_function(&self) -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn sub(&self, key: &Val) -> Result<Val, Val> {
+    if let Val::Symbol(VsSymbol::ASYNC_ITERATOR) = key {
+      return Ok(RETURN_THIS.to_val());
+    }
+
+    Ok(match key.to_string().as_str() {
+      "next" => NEXT.to_val(),
+      "return" => RETURN.to_val(),
+      "throw" => THROW.to_val(),
+      _ => Val::Undefined,
+    })
+  }
+
+  fn has(&self, key: &Val) -> Option<bool> {
+    if let Val::Symbol(VsSymbol::ASYNC_ITERATOR) = key {
+      return Some(true);
+    }
+
+    Some(matches!(
+      key.to_string().as_str(),
+      "next" | "return" | "throw"
+    ))
+  }
+
+  fn submov(&mut self, _key: &Val, _value: Val) -> Result<(), Val> {
+    Err("Cannot assign to subscript of an async generator".to_type_error())
+  }
+
+  fn pretty_fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\x1b[36m[AsyncGenerator]\x1b[39m")
+  }
+
+  fn codify(&self) -> String {
+    "AsyncGenerator { [native data] }".to_string()
+  }
+}
+
+impl fmt::Display for VsAsyncGenerator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "[object AsyncGenerator]")
+  }
+}
+
+static NEXT: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(AsyncGeneratorFrame::request(RequestKind::Next)),
+};
+
+static RETURN: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(AsyncGeneratorFrame::request(RequestKind::Return)),
+};
+
+static THROW: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(AsyncGeneratorFrame::request(RequestKind::Throw)),
+};
+
+/// Resumes an async generator once the value it awaited has settled.
+/// Params: async generator, value, rejected.
+static ASYNC_GENERATOR_RESUME: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::<AsyncGeneratorFrame>::default(),
+};
+
+/// Runs the body of an async generator, either for a call of next/return/throw or to resume it
+/// after an await. The frame keeps serving queued requests until the body awaits or there are no
+/// requests left.
+#[derive(Clone, Default)]
+struct AsyncGeneratorFrame {
+  handle: Option<VsAsyncGenerator>,
+
+  /// The request made by calling this frame, if it's a call of next/return/throw.
+  kind: Option<RequestKind>,
+
+  /// The parameters of the call.
+  params: Vec<Val>,
+
+  /// The promise returned by the call, if it's a call of next/return/throw.
+  promise: Option<VsPromise>,
+
+  /// The body while this frame is running it.
+  generator: Option<Generator>,
+
+  /// What to pass into the body before stepping it: a value for the suspended yield/await, or an
+  /// exception to throw at it.
+  resumption: Option<Result<Val, Val>>,
+
+  /// Set by return(): the value to complete with once the finally blocks have run.
+  return_value: Option<Val>,
+
+  /// The result of the request being served, once the body has thrown out of itself.
+  completion: Option<Result<Val, Val>>,
+
+  started: bool,
+}
+
+impl AsyncGeneratorFrame {
+  fn request(kind: RequestKind) -> AsyncGeneratorFrame {
+    AsyncGeneratorFrame {
+      kind: Some(kind),
+      ..Default::default()
+    }
+  }
+
+  fn param(&self, i: usize) -> Val {
+    self.params.get(i).cloned().unwrap_or(Val::Undefined)
+  }
+
+  fn handle(&self) -> Result<VsAsyncGenerator, Val> {
+    self
+      .handle
+      .ok_or_else(|| "AsyncGenerator method called on different object".to_type_error())
+  }
+
+  fn start(&mut self) -> FrameStepResult {
+    let kind = match self.kind {
+      Some(kind) => kind,
+      None => {
+        // Resuming after an await
+        self.handle = VsAsyncGenerator::from_val(&self.param(0));
+
+        let generator = self.handle()?.with_state(|state| {
+          match std::mem::replace(&mut state.body, Body::Running) {
+            Body::Awaiting(generator) => Some(generator),
+            body => {
+              state.body = body;
+              None
+            }
+          }
+        })?;
+
+        self.generator =
+          Some(generator.ok_or_else(|| "Async generator was not awaiting".to_internal_error())?);
+
+        self.resumption = Some(match self.param(2).is_truthy() {
+          false => Ok(self.param(1)),
+          true => Err(self.param(1)),
+        });
+
+        return Ok(FrameStepOk::Continue);
+      }
+    };
+
+    let promise = VsPromise::new_pending();
+    self.promise = Some(promise);
+
+    let request = Request {
+      kind,
+      value: self.param(0),
+      promise,
+    };
+
+    let idle_generator = self.handle()?.with_state(|state| {
+      state.queue.push_back(request);
+
+      if state.queue.len() != 1 {
+        return None;
+      }
+
+      match std::mem::replace(&mut state.body, Body::Running) {
+        Body::Idle(generator) => Some(generator),
+        body => {
+          state.body = body;
+          None
+        }
+      }
+    })?;
+
+    match idle_generator {
+      Some(generator) => {
+        self.generator = Some(generator);
+        self.begin(kind, self.param(0))
+      }
+
+      // The request will be served after the ones before it
+      None => self.finish(),
+    }
+  }
+
+  /// Starts serving a request.
+  fn begin(&mut self, kind: RequestKind, value: Val) -> FrameStepResult {
+    let done = match &self.generator {
+      Some(generator) => generator.is_done(),
+      None => return Err("Async generator is not running".to_internal_error()),
+    };
+
+    if done {
+      return self.complete(match kind {
+        RequestKind::Next => Ok(Val::Undefined),
+        RequestKind::Return => Ok(value),
+        RequestKind::Throw => Err(value),
+      });
+    }
+
+    self.resumption = Some(match kind {
+      RequestKind::Next => Ok(value),
+      RequestKind::Return => {
+        self.return_value = Some(value);
+        Err(VsSymbol::GENERATOR_RETURN.to_val())
+      }
+      RequestKind::Throw => Err(value),
+    });
+
+    Ok(FrameStepOk::Continue)
+  }
+
+  /// Completes the request being served, either with the final value of the body (Ok) or by
+  /// rejecting it (Err), and finishes the body.
+  fn complete(&mut self, result: Result<Val, Val>) -> FrameStepResult {
+    self.generator = Some(Generator::finished());
+
+    self.settle_request(result.map(|value| IterationResult { value, done: true }.to_dynamic_val()))
+  }
+
+  /// Settles the promise of the request being served, and moves on to the next one.
+  fn settle_request(&mut self, result: Result<Val, Val>) -> FrameStepResult {
+    let handle = self.handle()?;
+
+    let (request, next) = handle.with_state(|state| {
+      let request = state.queue.pop_front();
+      let next = state
+        .queue
+        .front()
+        .map(|next| (next.kind, next.value.clone()));
+
+      (request, next)
+    })?;
+
+    if let Some(request) = request {
+      request.promise.settle(result);
+    }
+
+    match next {
+      Some((kind, value)) => self.begin(kind, value),
+      None => {
+        let generator = take(&mut self.generator).unwrap_or_default();
+        handle.with_state(|state| state.body = Body::Idle(generator))?;
+
+        self.finish()
+      }
+    }
+  }
+
+  fn finish(&mut self) -> FrameStepResult {
+    Ok(FrameStepOk::Pop(CallResult {
+      return_: match self.promise {
+        Some(promise) => promise.to_dynamic_val(),
+        None => Val::Undefined,
+      },
+      this: match self.handle {
+        Some(handle) => handle.to_dynamic_val(),
+        None => Val::Undefined,
+      },
+    }))
+  }
+}
+
+impl StackFrameTrait for AsyncGeneratorFrame {
+  fn write_this(&mut self, _const: bool, this: Val) -> Result<(), Val> {
+    // Requests don't modify the handle, so they're allowed on const async generators
+    self.handle = VsAsyncGenerator::from_val(&this);
+
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    self.params.push(param);
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    if !self.started {
+      self.started = true;
+      return self.start();
+    }
+
+    if let Some(result) = take(&mut self.completion) {
+      return self.settle_request(result);
+    }
+
+    let generator = match &mut self.generator {
+      Some(generator) => generator,
+      None => return self.finish(),
+    };
+
+    match take(&mut self.resumption) {
+      Some(Ok(value)) => generator.resume_with(value),
+      Some(Err(exception)) => return Err(exception),
+      None => {}
+    }
+
+    match generator.step()? {
+      FrameStepOk::Pop(call_result) => self.complete(Ok(call_result.return_)),
+      FrameStepOk::Yield(value) => {
+        self.settle_request(Ok(IterationResult { value, done: false }.to_dynamic_val()))
+      }
+      FrameStepOk::Await(value) => {
+        let handle = self.handle()?;
+        let generator = take(&mut self.generator).unwrap_or_default();
+        handle.with_state(|state| state.body = Body::Awaiting(generator))?;
+
+        when_settled(
+          value,
+          Reaction {
+            job: ASYNC_GENERATOR_RESUME.to_val(),
+            params: vec![handle.to_dynamic_val()],
+          },
+        );
+
+        self.finish()
+      }
+      fso => Ok(fso),
+    }
+  }
+
+  fn apply_call_result(&mut self, call_result: CallResult) {
+    if let Some(generator) = &mut self.generator {
+      generator.apply_call_result(call_result);
+    }
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for AsyncGeneratorFrame")
+  }
+
+  fn catch_exception(&mut self, exception: &mut Val) {
+    let generator = match &mut self.generator {
+      Some(generator) => generator,
+      None => return,
+    };
+
+    generator.catch_exception(exception);
+
+    if let Val::Void = exception {
+      return;
+    }
+
+    // The exception made it out of the body, which completes the request being served
+    self.completion = Some(match take(exception) {
+      Val::Symbol(VsSymbol::GENERATOR_RETURN) => Ok(
+        IterationResult {
+          value: take(&mut self.return_value).unwrap_or(Val::Undefined),
+          done: true,
+        }
+        .to_dynamic_val(),
+      ),
+      exception => Err(exception),
+    });
+
+    self.generator = Some(Generator::finished());
+  }
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+
+use crate::native_function::{native_fn, ThisWrapper};
+use crate::operations::op_submov;
+use crate::property_map::PropertyMap;
+use crate::vs_array::VsArray;
+use crate::vs_value::ToVal;
+use crate::ValTrait;
+use crate::{
+  native_function::NativeFunction,
+  rc::Rc,
+  vs_class::VsClass,
+  vs_object::VsObject,
+  vs_value::{LoadFunctionResult, Val},
+};
+
+use super::builtin_object::BuiltinObject;
+use super::error_builtin::install_cause;
+use super::type_error_builtin::ToTypeError;
+
+pub struct AggregateErrorBuiltin {}
+
+impl BuiltinObject for AggregateErrorBuiltin {
+  fn bo_name() -> &'static str {
+    "AggregateError"
+  }
+
+  fn bo_sub(_key: &str) -> Val {
+    Val::Undefined
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NativeFunction(|_: ThisWrapper, params: Vec<Val>| -> Result<Val, Val> {
+      let mut error = VsObject {
+        string_map: Default::default(),
+        symbol_map: Default::default(),
+        prototype: Some(make_aggregate_error_prototype()),
+      }
+      .to_val();
+
+      init_aggregate_error(&mut error, &params)?;
+
+      Ok(error)
+    })
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    Some(Rc::new(VsClass {
+      name: "AggregateError".to_string(),
+      content_hash: None,
+      constructor: Val::Static(&CONSTRUCTOR),
+      prototype: make_aggregate_error_prototype(),
+      static_: VsObject::default().to_val(),
+    }))
+  }
+}
+
+impl fmt::Display for AggregateErrorBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function AggregateError() {{ [native code] }}")
+  }
+}
+
+// TODO: Static? (Rc -> Arc?)
+fn make_aggregate_error_prototype() -> Val {
+  VsObject {
+    string_map: PropertyMap::from([
+      ("name".to_string(), "AggregateError".to_val()),
+      ("toString".to_string(), AGGREGATE_ERROR_TO_STRING.to_val()),
+    ]),
+    symbol_map: Default::default(),
+    prototype: None,
+  }
+  .to_val()
+}
+
+fn init_aggregate_error(error: &mut Val, params: &[Val]) -> Result<(), Val> {
+  // TODO: Support other iterables
+  let errors = match params.get(0).and_then(|p| p.as_array_data()) {
+    Some(errors) => errors,
+    None => return Err("AggregateError expects an array of errors".to_type_error()),
+  };
+
+  let message = match params.get(1) {
+    None | Some(Val::Undefined) => "".to_string(),
+    Some(param) => param.to_string(),
+  };
+
+  op_submov(error, &"message".to_val(), message.to_val())?;
+  install_cause(error, params.get(2))?;
+  op_submov(
+    error,
+    &"errors".to_val(),
+    VsArray::from(errors.elements.clone()).to_val(),
+  )?;
+
+  Ok(())
+}
+
+static CONSTRUCTOR: NativeFunction = native_fn(|mut this, params| {
+  init_aggregate_error(this.get_mut()?, &params)?;
+
+  Ok(Val::Undefined)
+});
+
+static AGGREGATE_ERROR_TO_STRING: NativeFunction = native_fn(|this, _params| {
+  let message = this.get().sub(&"message".to_val())?;
+  Ok(format!("AggregateError({})", message).to_val())
+});
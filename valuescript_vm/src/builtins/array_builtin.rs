@@ -1,10 +1,17 @@
-use std::{fmt, rc::Rc};
+use std::{fmt, mem::take};
 
 use crate::{
+  cat_stack_frame::CatStackFrame,
+  native_frame_function::NativeFrameFunction,
   native_function::{native_fn, NativeFunction, ThisWrapper},
+  operations::op_sub,
+  rc::Rc,
+  size_limits::check_array_length,
+  stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait},
   vs_array::VsArray,
   vs_class::VsClass,
-  vs_value::{LoadFunctionResult, ToVal, Val},
+  vs_symbol::VsSymbol,
+  vs_value::{LoadFunctionResult, ToVal, Val, VsType},
   ValTrait,
 };
 
@@ -54,58 +61,196 @@ static IS_ARRAY: NativeFunction = native_fn(|_this, params| {
   })
 });
 
-static FROM: NativeFunction = native_fn(|_this, params| {
-  let first_param = match params.get(0) {
-    None => return Err("undefined is not iterable".to_type_error()),
-    Some(p) => p,
-  };
+static FROM: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(ArrayFromFrame::default()),
+};
+
+#[derive(Clone, Default)]
+struct ArrayFromFrame {
+  items: Val,
+  mapper: Val,
+  this_arg: Val,
+  param_i: usize,
+
+  state: ArrayFromState,
+}
+
+#[derive(Clone, Default)]
+enum ArrayFromState {
+  #[default]
+  Start,
+  Collecting,
+  Collected(Rc<VsArray>),
+  Mapping {
+    elements: Rc<VsArray>,
+    i: usize,
+    res: Vec<Val>,
+  },
+}
+
+impl ArrayFromFrame {
+  fn start(&mut self) -> FrameStepResult {
+    let elements = match &self.items {
+      Val::Array(arr) => arr.clone(),
+      Val::String(s) => Rc::new(VsArray::from(
+        s.chars().map(|c| c.to_val()).collect::<Vec<Val>>(),
+      )),
+      Val::Void | Val::Undefined | Val::Null | Val::CopyCounter(..) => {
+        return Err("items is not iterable".to_type_error())
+      }
+      Val::Bool(..) | Val::Number(..) | Val::BigInt(..) | Val::Symbol(..) => {
+        Rc::new(VsArray::default())
+      }
+      Val::Object(..) | Val::Function(..) | Val::Class(..) | Val::Static(..) | Val::Dynamic(..) => {
+        let make_iter = op_sub(&mut self.items.clone(), &VsSymbol::ITERATOR.to_val())?;
+
+        if make_iter.typeof_() == VsType::Function {
+          // Let CatStackFrame drive the iterator protocol so that user-defined iterators run
+          // through the normal call machinery
+          self.state = ArrayFromState::Collecting;
+
+          return Ok(FrameStepOk::Push(Box::new(CatStackFrame::from_vec_val(
+            vec![self.items.clone()],
+          ))));
+        }
+
+        match array_like_to_vec(&self.items)? {
+          Some(elements) => Rc::new(VsArray::from(elements)),
+          None => Rc::new(VsArray::default()),
+        }
+      }
+    };
 
-  if params.len() > 1 {
-    return Err("TODO: Using Array.from with a map function".to_internal_error());
+    self.begin_mapping(elements)
   }
 
-  Ok(match first_param {
-    Val::Array(arr) => Val::Array(arr.clone()),
-    Val::String(s) => s.chars().map(|c| c.to_val()).collect::<Vec<Val>>().to_val(),
-    Val::Void | Val::Undefined | Val::Null | Val::CopyCounter(..) => {
-      return Err("items is not iterable".to_type_error())
-    }
-    Val::Bool(..) | Val::Number(..) | Val::BigInt(..) | Val::Symbol(..) => {
-      VsArray::default().to_val()
+  fn begin_mapping(&mut self, elements: Rc<VsArray>) -> FrameStepResult {
+    match self.mapper {
+      Val::Void | Val::Undefined => Ok(FrameStepOk::Pop(CallResult {
+        return_: Val::Array(elements),
+        this: Val::Undefined,
+      })),
+      _ => {
+        self.state = ArrayFromState::Mapping {
+          res: Vec::with_capacity(elements.elements.len()),
+          elements,
+          i: 0,
+        };
+
+        Ok(FrameStepOk::Continue)
+      }
     }
-    Val::Object(..) | Val::Function(..) | Val::Class(..) | Val::Static(..) | Val::Dynamic(..) => {
-      let len = first_param
-        .sub(&"length".to_val())
-        .map_err(|e| e.to_string())
-        .unwrap() // TODO: Exception
-        .to_number();
-
-      if len.is_sign_negative() || len.is_nan() {
-        return Ok(VsArray::default().to_val());
+  }
+}
+
+impl StackFrameTrait for ArrayFromFrame {
+  fn write_this(&mut self, _const: bool, _this: Val) -> Result<(), Val> {
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    match self.param_i {
+      0 => self.items = param,
+      1 => self.mapper = param,
+      2 => self.this_arg = param,
+      _ => {}
+    };
+
+    self.param_i += 1;
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    let (elements, i, res) = match &mut self.state {
+      ArrayFromState::Start => return self.start(),
+      ArrayFromState::Collecting => {
+        return Err("Unexpected step during Collecting".to_internal_error())
+      }
+      ArrayFromState::Collected(elements) => {
+        let elements = elements.clone();
+        return self.begin_mapping(elements);
       }
+      ArrayFromState::Mapping { elements, i, res } => (elements, i, res),
+    };
 
-      if len.is_infinite() {
-        return Err("Invalid array length".to_range_error());
+    let array_i = *i;
+
+    let el = match elements.elements.get(array_i) {
+      None => {
+        return Ok(FrameStepOk::Pop(CallResult {
+          return_: take(res).to_val(),
+          this: Val::Undefined,
+        }))
       }
+      Some(Val::Void) => Val::Undefined,
+      Some(el) => el.clone(),
+    };
 
-      let len = len as usize;
+    *i += 1;
 
-      let mut arr = Vec::with_capacity(len);
+    match self.mapper.load_function() {
+      LoadFunctionResult::NotAFunction => Err("map fn is not a function".to_type_error()),
+      LoadFunctionResult::NativeFunction(native_fn) => {
+        res.push(native_fn(
+          ThisWrapper::new(true, &mut self.this_arg.clone()),
+          vec![el, Val::Number(array_i as f64)],
+        )?);
 
-      // TODO: We should probably use a frame and step through this
-      for i in 0..len {
-        arr.push(
-          first_param
-            .sub(&(i as f64).to_val())
-            .map_err(|e| e.to_string())
-            .unwrap(), // TODO: Exception
-        );
+        Ok(FrameStepOk::Continue)
       }
+      LoadFunctionResult::StackFrame(mut new_frame) => {
+        new_frame.write_this(true, self.this_arg.clone())?;
+        new_frame.write_param(el);
+        new_frame.write_param(Val::Number(array_i as f64));
+        Ok(FrameStepOk::Push(new_frame))
+      }
+    }
+  }
 
-      VsArray::from(arr).to_val()
+  fn apply_call_result(&mut self, call_result: CallResult) {
+    match &mut self.state {
+      ArrayFromState::Start => panic!("Unexpected call result during Start"),
+      ArrayFromState::Collecting => match call_result.return_ {
+        Val::Array(elements) => self.state = ArrayFromState::Collected(elements),
+        _ => panic!("Expected CatStackFrame to produce an array"),
+      },
+      ArrayFromState::Collected(..) => panic!("Unexpected call result during Collected"),
+      ArrayFromState::Mapping { res, .. } => res.push(call_result.return_),
     }
-  })
-});
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for ArrayFromFrame")
+  }
+
+  fn catch_exception(&mut self, _exception: &mut Val) {}
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
+
+fn array_like_to_vec(items: &Val) -> Result<Option<Vec<Val>>, Val> {
+  let len = items.sub(&"length".to_val())?.to_number();
+
+  if len.is_sign_negative() || len.is_nan() {
+    return Ok(None);
+  }
+
+  if len.is_infinite() {
+    return Err("Invalid array length".to_range_error());
+  }
+
+  let len = len as usize;
+  check_array_length(len)?;
+
+  let mut arr = Vec::with_capacity(len);
+
+  for i in 0..len {
+    arr.push(items.sub(&(i as f64).to_val())?);
+  }
+
+  Ok(Some(arr))
+}
 
 static OF: NativeFunction = native_fn(|_this, params| Ok(VsArray::from(params).to_val()));
 
@@ -121,6 +266,7 @@ fn to_array(_: ThisWrapper, params: Vec<Val>) -> Result<Val, Val> {
       }
 
       let len = number as usize;
+      check_array_length(len)?;
 
       let mut arr = Vec::with_capacity(len);
 
@@ -0,0 +1,101 @@
+use crate::native_function::{native_fn, NativeFunction};
+use crate::vs_value::ToVal;
+
+use super::error_builtin::ToError;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Like JavaScript, btoa operates on "binary strings", where every character represents a byte and
+// must be in the range U+0000 to U+00FF.
+pub static BTOA: NativeFunction = native_fn(|_this, params| {
+  let input = match params.get(0) {
+    Some(p) => p.to_string(),
+    None => return Err("btoa requires 1 argument".to_error()),
+  };
+
+  let mut bytes = Vec::<u8>::with_capacity(input.len());
+
+  for c in input.chars() {
+    if c as u32 > 0xFF {
+      return Err(
+        "Invalid character: btoa only supports characters in the Latin1 range".to_error(),
+      );
+    }
+
+    bytes.push(c as u8);
+  }
+
+  let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+  for chunk in bytes.chunks(3) {
+    let b = [
+      chunk[0],
+      *chunk.get(1).unwrap_or(&0),
+      *chunk.get(2).unwrap_or(&0),
+    ];
+
+    let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+    for i in 0..4 {
+      if i <= chunk.len() {
+        result.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+      } else {
+        result.push('=');
+      }
+    }
+  }
+
+  Ok(result.to_val())
+});
+
+pub static ATOB: NativeFunction = native_fn(|_this, params| {
+  let input = match params.get(0) {
+    Some(p) => p.to_string(),
+    None => return Err("atob requires 1 argument".to_error()),
+  };
+
+  let invalid =
+    || "Invalid character: the string to be decoded is not correctly encoded".to_error();
+
+  let mut input = input
+    .chars()
+    .filter(|c| !matches!(c, '\t' | '\n' | '\x0C' | '\r' | ' '))
+    .collect::<Vec<char>>();
+
+  if input.len() % 4 == 0 {
+    for _ in 0..2 {
+      if input.last() == Some(&'=') {
+        input.pop();
+      }
+    }
+  }
+
+  if input.len() % 4 == 1 {
+    return Err(invalid());
+  }
+
+  let mut result = String::with_capacity(input.len() * 3 / 4);
+  let mut buffer = 0u32;
+  let mut bits = 0;
+
+  for c in input {
+    let value = match c {
+      'A'..='Z' => c as u32 - 'A' as u32,
+      'a'..='z' => c as u32 - 'a' as u32 + 26,
+      '0'..='9' => c as u32 - '0' as u32 + 52,
+      '+' => 62,
+      '/' => 63,
+      _ => return Err(invalid()),
+    };
+
+    buffer = (buffer << 6) | value;
+    bits += 6;
+
+    if bits >= 8 {
+      bits -= 8;
+      result.push(char::from(((buffer >> bits) & 0xFF) as u8));
+    }
+  }
+
+  Ok(result.to_val())
+});
@@ -1,10 +1,10 @@
 use std::fmt;
-use std::rc::Rc;
 
 use num_bigint::BigInt;
 
 use crate::native_function::ThisWrapper;
 use crate::{
+  rc::Rc,
   vs_class::VsClass,
   vs_value::{LoadFunctionResult, Val},
 };
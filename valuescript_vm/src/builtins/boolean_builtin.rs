@@ -1,7 +1,8 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use crate::{
   native_function::ThisWrapper,
+  rc::Rc,
   vs_class::VsClass,
   vs_value::{LoadFunctionResult, Val},
   ValTrait,
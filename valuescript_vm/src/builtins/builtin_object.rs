@@ -1,8 +1,9 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use num_bigint::BigInt;
 
 use crate::{
+  rc::{MaybeSync, Rc},
   vs_array::VsArray,
   vs_class::VsClass,
   vs_value::{Val, VsType},
@@ -11,7 +12,7 @@ use crate::{
 
 use super::type_error_builtin::ToTypeError;
 
-pub trait BuiltinObject: fmt::Display {
+pub trait BuiltinObject: fmt::Display + MaybeSync {
   fn bo_name() -> &'static str;
   fn bo_sub(key: &str) -> Val;
   fn bo_load_function() -> LoadFunctionResult;
@@ -23,7 +24,12 @@ where
   T: BuiltinObject,
 {
   fn typeof_(&self) -> VsType {
-    VsType::Object
+    // Callable builtins like Array and Error are functions in JS, the rest (Math, console...) are
+    // plain objects
+    match Self::bo_load_function() {
+      LoadFunctionResult::NotAFunction if Self::bo_as_class_data().is_none() => VsType::Object,
+      _ => VsType::Function,
+    }
   }
 
   fn to_number(&self) -> f64 {
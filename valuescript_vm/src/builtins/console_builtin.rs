@@ -1,7 +1,8 @@
 use std::fmt;
-use std::rc::Rc;
 
+use crate::host::with_current_host;
 use crate::native_function::{native_fn, NativeFunction};
+use crate::rc::Rc;
 use crate::vs_class::VsClass;
 use crate::vs_value::{LoadFunctionResult, ToVal, Val};
 
@@ -38,15 +39,13 @@ impl fmt::Display for ConsoleBuiltin {
 }
 
 static LOG: NativeFunction = native_fn(|_this, params| {
-  for (i, p) in params.iter().enumerate() {
-    if i > 0 {
-      print!(" ");
-    }
-
-    print!("{}", p);
-  }
+  let line = params
+    .iter()
+    .map(|p| p.to_string())
+    .collect::<Vec<String>>()
+    .join(" ");
 
-  println!();
+  with_current_host(|host| host.console.log(&line));
 
   Ok(Val::Undefined)
 });
@@ -0,0 +1,107 @@
+use std::fmt;
+
+use crate::host::with_current_host;
+use crate::native_function::{native_fn, NativeFunction};
+use crate::rc::Rc;
+use crate::vs_array::VsArray;
+use crate::vs_class::VsClass;
+use crate::vs_value::{LoadFunctionResult, ToVal, Val};
+use crate::ValTrait;
+
+use super::builtin_object::BuiltinObject;
+use super::error_builtin::ToError;
+use super::range_error_builtin::ToRangeError;
+use super::type_error_builtin::ToTypeError;
+
+pub struct CryptoBuiltin {}
+
+impl BuiltinObject for CryptoBuiltin {
+  fn bo_name() -> &'static str {
+    "crypto"
+  }
+
+  fn bo_sub(key: &str) -> Val {
+    match key {
+      "getRandomValues" => GET_RANDOM_VALUES.to_val(),
+      "randomUUID" => RANDOM_UUID.to_val(),
+
+      _ => Val::Undefined,
+    }
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    None
+  }
+}
+
+impl fmt::Display for CryptoBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "[object Crypto]")
+  }
+}
+
+fn fill_random_bytes(dest: &mut [u8]) -> Result<(), Val> {
+  with_current_host(|host| match &mut host.entropy {
+    Some(entropy) => {
+      entropy.fill_bytes(dest);
+      Ok(())
+    }
+    None => Err("No entropy source was provided by the host".to_error()),
+  })
+}
+
+// There are no typed arrays in ValueScript, and arrays can't be mutated through a parameter, so
+// this returns a new array of random bytes with the same length as the input (like Uint8Array).
+static GET_RANDOM_VALUES: NativeFunction = native_fn(|_this, params| {
+  let len = match params.get(0).and_then(|p| p.as_array_data()) {
+    Some(array_data) => array_data.elements.len(),
+    None => return Err("getRandomValues expects an array".to_type_error()),
+  };
+
+  if len > 65536 {
+    return Err("getRandomValues is limited to 65536 bytes".to_range_error());
+  }
+
+  let mut bytes = vec![0u8; len];
+  fill_random_bytes(&mut bytes)?;
+
+  Ok(
+    VsArray::from(
+      bytes
+        .into_iter()
+        .map(|b| Val::Number(b as f64))
+        .collect::<Vec<Val>>(),
+    )
+    .to_val(),
+  )
+});
+
+static RANDOM_UUID: NativeFunction = native_fn(|_this, _params| {
+  let mut bytes = [0u8; 16];
+  fill_random_bytes(&mut bytes)?;
+
+  // Version 4, variant 1
+  bytes[6] = (bytes[6] & 0x0f) | 0x40;
+  bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+  let hex = bytes
+    .iter()
+    .map(|b| format!("{:02x}", b))
+    .collect::<String>();
+
+  Ok(
+    format!(
+      "{}-{}-{}-{}-{}",
+      &hex[0..8],
+      &hex[8..12],
+      &hex[12..16],
+      &hex[16..20],
+      &hex[20..32]
+    )
+    .to_val(),
+  )
+});
@@ -1,12 +1,14 @@
 use std::fmt;
-use std::rc::Rc;
 
 use crate::copy_counter::CopyCounter;
+use crate::host::with_current_host;
 use crate::native_function::{native_fn, NativeFunction};
+use crate::rc::Rc;
 use crate::vs_class::VsClass;
-use crate::vs_value::{LoadFunctionResult, Val};
+use crate::vs_value::{LoadFunctionResult, ToVal, Val, ValTrait};
 
 use super::builtin_object::BuiltinObject;
+use super::error_builtin::ToError;
 
 pub struct DebugBuiltin {}
 
@@ -19,6 +21,10 @@ impl BuiltinObject for DebugBuiltin {
     Val::Static(match key {
       "log" => &LOG,
       "makeCopyCounter" => &MAKE_COPY_COUNTER,
+      "time" => &TIME,
+      "timeEnd" => &TIME_END,
+      "assert" => &ASSERT,
+      "inspect" => &INSPECT,
       _ => return Val::Undefined,
     })
   }
@@ -58,3 +64,69 @@ static MAKE_COPY_COUNTER: NativeFunction = native_fn(|_this, params| {
 
   Ok(Val::CopyCounter(Box::new(CopyCounter::new(tag))))
 });
+
+fn timer_label(params: &[Val]) -> String {
+  match params.first() {
+    None | Some(Val::Undefined) => "default".to_string(),
+    Some(label) => label.to_string(),
+  }
+}
+
+static TIME: NativeFunction = native_fn(|_this, params| {
+  let label = timer_label(&params);
+
+  with_current_host(|host| {
+    let start = host.clock.performance_now();
+    host.debug_timers.insert(label, start);
+  });
+
+  Ok(Val::Undefined)
+});
+
+/// Returns the milliseconds since the matching `Debug.time` call (according to the host's clock),
+/// and also logs it to the host's console.
+static TIME_END: NativeFunction = native_fn(|_this, params| {
+  let label = timer_label(&params);
+
+  let elapsed = with_current_host(|host| {
+    let start = host.debug_timers.remove(&label)?;
+    Some(host.clock.performance_now() - start)
+  });
+
+  let elapsed = match elapsed {
+    Some(elapsed) => elapsed,
+    None => return Err(format!("No timer named {}", label).to_error()),
+  };
+
+  let line = format!("Debug.timeEnd: {}: {}ms", label, elapsed.to_val());
+  with_current_host(|host| host.console.log(&line));
+
+  Ok(elapsed.to_val())
+});
+
+static ASSERT: NativeFunction = native_fn(|_this, params| {
+  let cond = params.first().cloned().unwrap_or(Val::Undefined);
+
+  if cond.is_truthy() {
+    return Ok(Val::Undefined);
+  }
+
+  Err(match params.get(1) {
+    Some(message) => format!("Assertion failed: {}", message).to_error(),
+    None => "Assertion failed".to_error(),
+  })
+});
+
+/// Returns the codified form of a value, with arrays and objects nested deeper than `depth`
+/// (default 2) abbreviated.
+static INSPECT: NativeFunction = native_fn(|_this, params| {
+  let value = params.first().cloned().unwrap_or(Val::Undefined);
+
+  let depth = match params.get(1) {
+    None | Some(Val::Undefined) => 2,
+    Some(depth) => depth.to_number().max(0.0) as usize,
+  };
+
+  // Like node's util.inspect, the depth is how many levels are shown below the top one
+  Ok(value.inspect(depth.saturating_add(1)).to_val())
+});
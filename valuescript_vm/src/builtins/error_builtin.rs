@@ -1,16 +1,16 @@
-use std::collections::BTreeMap;
 use std::fmt;
-use std::rc::Rc;
 
 use crate::native_function::{native_fn, ThisWrapper};
+use crate::property_map::PropertyMap;
 use crate::vs_class::VsClass;
 use crate::vs_value::ToVal;
 use crate::ValTrait;
 use crate::{
   native_function::NativeFunction,
   operations::op_submov,
+  rc::Rc,
   vs_object::VsObject,
-  vs_value::{LoadFunctionResult, Val},
+  vs_value::{LoadFunctionResult, Val, VsType},
 };
 
 use super::builtin_object::BuiltinObject;
@@ -28,13 +28,15 @@ impl BuiltinObject for ErrorBuiltin {
 
   fn bo_load_function() -> LoadFunctionResult {
     LoadFunctionResult::NativeFunction(|_: ThisWrapper, params: Vec<Val>| -> Result<Val, Val> {
-      Ok(
-        match params.get(0) {
-          Some(param) => param.clone().to_val_string(),
-          None => "".to_val(),
-        }
-        .to_error(),
-      )
+      let mut error = match params.get(0) {
+        Some(param) => param.clone().to_val_string(),
+        None => "".to_val(),
+      }
+      .to_error();
+
+      install_cause(&mut error, params.get(1))?;
+
+      Ok(error)
     })
   }
 
@@ -62,7 +64,7 @@ pub trait ToError {
 impl ToError for Val {
   fn to_error(self) -> Val {
     VsObject {
-      string_map: BTreeMap::from([("message".to_string(), self.to_val_string())]),
+      string_map: PropertyMap::from([("message".to_string(), self.to_val_string())]),
       symbol_map: Default::default(),
       prototype: Some(make_error_prototype()),
     }
@@ -82,10 +84,24 @@ impl ToError for &str {
   }
 }
 
+/// Implements the `cause` option of error constructors, eg `new Error(msg, { cause })`.
+pub fn install_cause(error: &mut Val, options: Option<&Val>) -> Result<(), Val> {
+  let options = match options {
+    Some(options) if options.typeof_() == VsType::Object && !options.is_nullish() => options,
+    _ => return Ok(()),
+  };
+
+  if options.has(&"cause".to_val()) == Some(true) {
+    op_submov(error, &"cause".to_val(), options.sub(&"cause".to_val())?)?;
+  }
+
+  Ok(())
+}
+
 // TODO: Static? (Rc -> Arc?)
 fn make_error_prototype() -> Val {
   VsObject {
-    string_map: BTreeMap::from([
+    string_map: PropertyMap::from([
       ("name".to_string(), "Error".to_val()),
       ("toString".to_string(), ERROR_TO_STRING.to_val()),
     ]),
@@ -101,7 +117,9 @@ static SET_MESSAGE: NativeFunction = native_fn(|mut this, params| {
     None => "".to_string(),
   };
 
-  op_submov(this.get_mut()?, &"message".to_val(), message.to_val())?;
+  let this = this.get_mut()?;
+  op_submov(this, &"message".to_val(), message.to_val())?;
+  install_cause(this, params.get(1))?;
 
   Ok(Val::Undefined)
 });
@@ -0,0 +1,46 @@
+use std::fmt;
+
+use valuescript_common::BUILTIN_NAMES;
+
+use crate::host::with_current_host;
+use crate::rc::Rc;
+use crate::vs_class::VsClass;
+use crate::vs_value::{LoadFunctionResult, Val};
+
+use super::builtin_object::BuiltinObject;
+use super::BUILTIN_VALS;
+
+/// `globalThis`, which provides the builtins by name, including the extra builtins provided by the
+/// host (which take precedence).
+pub struct GlobalThisBuiltin {}
+
+impl BuiltinObject for GlobalThisBuiltin {
+  fn bo_name() -> &'static str {
+    "globalThis"
+  }
+
+  fn bo_sub(key: &str) -> Val {
+    if let Some(val) = with_current_host(|host| host.extra_builtins.get(key).cloned()) {
+      return val;
+    }
+
+    match BUILTIN_NAMES.iter().position(|name| *name == key) {
+      Some(index) => BUILTIN_VALS[index](),
+      None => Val::Undefined,
+    }
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    None
+  }
+}
+
+impl fmt::Display for GlobalThisBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "[object global]")
+  }
+}
@@ -1,8 +1,9 @@
 use std::fmt;
-use std::rc::Rc;
 
+use crate::host::with_current_host;
 use crate::native_function::{native_fn, NativeFunction};
 use crate::operations::to_u32;
+use crate::rc::Rc;
 use crate::vs_class::VsClass;
 use crate::vs_value::{LoadFunctionResult, ToVal, Val, ValTrait};
 
@@ -52,7 +53,7 @@ impl BuiltinObject for MathBuiltin {
       "max" => MAX.to_val(),
       "min" => MIN.to_val(),
       "pow" => POW.to_val(),
-      // random: Not included because it cannot work as expected in ValueScript
+      "random" => RANDOM.to_val(),
       "round" => ROUND.to_val(),
       "sign" => SIGN.to_val(),
       "sin" => SIN.to_val(),
@@ -225,6 +226,12 @@ static POW: NativeFunction = native_fn(|_this, params| {
   Ok(Val::Number(x.powf(y)))
 });
 
+static RANDOM: NativeFunction = native_fn(|_this, _params| {
+  Ok(Val::Number(with_current_host(|host| {
+    host.random.next_f64()
+  })))
+});
+
 static ROUND: NativeFunction = native_fn(|_this, params| {
   let x = param_to_number(params.get(0));
   Ok(Val::Number(x.round()))
@@ -1,16 +1,29 @@
+mod aggregate_error_builtin;
 mod array_builtin;
+mod base64;
 mod bigint_builtin;
 mod boolean_builtin;
 mod builtin_object;
 mod console_builtin;
+mod crypto_builtin;
+mod date_builtin;
 mod debug_builtin;
 pub mod error_builtin;
+mod global_this_builtin;
 pub mod internal_error_builtin;
 mod math_builtin;
 mod number_builtin;
+mod object_builtin;
+mod performance_builtin;
+mod promise_builtin;
+mod queue_microtask;
 pub mod range_error_builtin;
+pub mod regexp_builtin;
 mod string_builtin;
+mod super_constructor;
 mod symbol_builtin;
+mod text_decoder_builtin;
+mod text_encoder_builtin;
 pub mod type_error_builtin;
 
 use valuescript_common::BUILTIN_COUNT;
@@ -21,11 +34,16 @@ use crate::{
 };
 
 use self::{
-  array_builtin::ArrayBuiltin, bigint_builtin::BigIntBuiltin, boolean_builtin::BooleanBuiltin,
-  console_builtin::ConsoleBuiltin, debug_builtin::DebugBuiltin, error_builtin::ErrorBuiltin,
+  aggregate_error_builtin::AggregateErrorBuiltin, array_builtin::ArrayBuiltin,
+  bigint_builtin::BigIntBuiltin, boolean_builtin::BooleanBuiltin, console_builtin::ConsoleBuiltin,
+  crypto_builtin::CryptoBuiltin, date_builtin::DateBuiltin, debug_builtin::DebugBuiltin,
+  error_builtin::ErrorBuiltin, global_this_builtin::GlobalThisBuiltin,
   internal_error_builtin::InternalErrorBuiltin, math_builtin::MathBuiltin,
-  number_builtin::NumberBuiltin, range_error_builtin::RangeErrorBuiltin,
+  number_builtin::NumberBuiltin, object_builtin::ObjectBuiltin,
+  performance_builtin::PerformanceBuiltin, promise_builtin::PromiseBuiltin,
+  range_error_builtin::RangeErrorBuiltin, regexp_builtin::RegExpBuiltin,
   string_builtin::StringBuiltin, symbol_builtin::SymbolBuiltin,
+  text_decoder_builtin::TextDecoderBuiltin, text_encoder_builtin::TextEncoderBuiltin,
   type_error_builtin::TypeErrorBuiltin,
 };
 
@@ -48,4 +66,28 @@ pub static BUILTIN_VALS: [fn() -> Val; BUILTIN_COUNT] = [
   || VsSymbol::ITERATOR.to_val(),
   || BigIntBuiltin {}.to_val(),
   || ConsoleBuiltin {}.to_val(),
+  || DateBuiltin {}.to_val(),
+  || PerformanceBuiltin {}.to_val(),
+  || CryptoBuiltin {}.to_val(),
+  || TextEncoderBuiltin {}.to_val(),
+  || TextDecoderBuiltin {}.to_val(),
+  || base64::ATOB.to_val(),
+  || base64::BTOA.to_val(),
+  || AggregateErrorBuiltin {}.to_val(),
+  || VsSymbol::TO_PRIMITIVE.to_val(),
+  || ObjectBuiltin {}.to_val(),
+  || object_builtin::FOR_IN_KEYS.to_val(),
+  || GlobalThisBuiltin {}.to_val(),
+  || VsSymbol::GENERATOR_RETURN.to_val(),
+  || queue_microtask::QUEUE_MICROTASK.to_val(),
+  || super_constructor::SUPER_CONSTRUCTOR.to_val(),
+  || VsSymbol::HAS_INSTANCE.to_val(),
+  || VsSymbol::TO_STRING_TAG.to_val(),
+  || object_builtin::OBJECT_SPREAD.to_val(),
+  || object_builtin::OBJECT_REST.to_val(),
+  || RegExpBuiltin {}.to_val(),
+  || PromiseBuiltin {}.to_val(),
+  || object_builtin::OBJECT_DEFINE_ACCESSOR.to_val(),
+  || super_constructor::SUPER_PROTOTYPE.to_val(),
+  || VsSymbol::ASYNC_ITERATOR.to_val(),
 ];
@@ -1,10 +1,11 @@
 use std::fmt;
-use std::rc::Rc;
 
 use crate::native_function::{native_fn, ThisWrapper};
+use crate::operations::to_i32;
 use crate::vs_value::ToVal;
 use crate::{
   native_function::NativeFunction,
+  rc::Rc,
   vs_class::VsClass,
   vs_value::{LoadFunctionResult, Val},
   ValTrait,
@@ -29,9 +30,9 @@ impl BuiltinObject for NumberBuiltin {
       "NEGATIVE_INFINITY" => f64::NEG_INFINITY.to_val(),
       "POSITIVE_INFINITY" => f64::INFINITY.to_val(),
       "NaN" => f64::NAN.to_val(),
-      "isFinite" => IS_FINITE.to_val(),
+      "isFinite" => NUMBER_IS_FINITE.to_val(),
       "isInteger" => IS_INTEGER.to_val(),
-      "isNaN" => IS_NAN.to_val(),
+      "isNaN" => NUMBER_IS_NAN.to_val(),
       "isSafeInteger" => IS_SAFE_INTEGER.to_val(),
       "parseFloat" => PARSE_FLOAT.to_val(),
       "parseInt" => PARSE_INT.to_val(),
@@ -69,10 +70,17 @@ pub static IS_FINITE: NativeFunction = native_fn(|_this, params| {
   })
 });
 
+static NUMBER_IS_FINITE: NativeFunction = native_fn(|_this, params| {
+  Ok(match params.first() {
+    Some(Val::Number(number)) => Val::Bool(number.is_finite()),
+    _ => Val::Bool(false),
+  })
+});
+
 static IS_INTEGER: NativeFunction = native_fn(|_this, params| {
   let num = match params.get(0) {
-    Some(n) => n.to_number(),
-    None => return Ok(Val::Bool(false)),
+    Some(Val::Number(n)) => *n,
+    _ => return Ok(Val::Bool(false)),
   };
 
   let is_finite = num.is_finite();
@@ -90,10 +98,17 @@ pub static IS_NAN: NativeFunction = native_fn(|_this, params| {
   })
 });
 
+static NUMBER_IS_NAN: NativeFunction = native_fn(|_this, params| {
+  Ok(match params.first() {
+    Some(Val::Number(number)) => Val::Bool(number.is_nan()),
+    _ => Val::Bool(false),
+  })
+});
+
 static IS_SAFE_INTEGER: NativeFunction = native_fn(|_this, params| {
   let num = match params.get(0) {
-    Some(n) => n.to_number(),
-    None => return Ok(Val::Bool(false)),
+    Some(Val::Number(n)) => *n,
+    _ => return Ok(Val::Bool(false)),
   };
 
   let is_finite = num.is_finite();
@@ -106,46 +121,125 @@ static IS_SAFE_INTEGER: NativeFunction = native_fn(|_this, params| {
 });
 
 pub static PARSE_FLOAT: NativeFunction = native_fn(|_this, params| {
-  Ok(if let Some(value) = params.get(0) {
-    let string_value = value.to_string().trim().to_string();
-
-    match string_value.parse::<f64>() {
-      Ok(number) => Val::Number(number),
-      Err(_) => Val::Number(f64::NAN),
-    }
-  } else {
-    Val::Number(f64::NAN)
+  Ok(match params.get(0) {
+    Some(value) => Val::Number(parse_float_prefix(&value.to_string())),
+    None => Val::Number(f64::NAN),
   })
 });
 
-pub static PARSE_INT: NativeFunction = native_fn(|_this, params| {
-  Ok(if let Some(value) = params.get(0) {
-    let string_value = value.to_string().trim_start().to_string();
-    let radix = params.get(1).and_then(|v| v.to_index()).unwrap_or(10);
+/// Parses the longest prefix of `s` (after leading whitespace) that is a decimal literal, so that
+/// eg "3.5px" gives 3.5.
+fn parse_float_prefix(s: &str) -> f64 {
+  let s = s.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{feff}');
+  let bytes = s.as_bytes();
 
-    if !(2..=36).contains(&radix) {
-      return Ok(Val::Number(f64::NAN));
-    }
+  let mut end = match bytes.first() {
+    Some(b'+' | b'-') => 1,
+    _ => 0,
+  };
 
-    let (is_negative, string_value) = if let Some(stripped) = string_value.strip_prefix('-') {
-      (true, stripped)
-    } else {
-      (false, string_value.as_str())
+  if s[end..].starts_with("Infinity") {
+    return match s.starts_with('-') {
+      true => f64::NEG_INFINITY,
+      false => f64::INFINITY,
     };
+  }
+
+  let digits_start = end;
+  end = skip_digits(bytes, end);
+  let mut has_digits = end > digits_start;
+
+  if bytes.get(end) == Some(&b'.') {
+    let fraction_start = end + 1;
+    end = skip_digits(bytes, fraction_start);
+    has_digits |= end > fraction_start;
+  }
+
+  if !has_digits {
+    return f64::NAN;
+  }
 
-    let string_value = match string_value.find(|c: char| !c.is_digit(radix as u32)) {
-      Some(pos) => &string_value[..pos],
-      None => string_value,
+  if let Some(b'e' | b'E') = bytes.get(end) {
+    let exponent_start = match bytes.get(end + 1) {
+      Some(b'+' | b'-') => end + 2,
+      _ => end + 1,
     };
 
-    match i64::from_str_radix(string_value, radix as u32) {
-      Ok(number) => {
-        let number = if is_negative { -number } else { number };
-        Val::Number(number as f64)
-      }
-      Err(_) => Val::Number(f64::NAN),
+    let exponent_end = skip_digits(bytes, exponent_start);
+
+    if exponent_end > exponent_start {
+      end = exponent_end;
     }
-  } else {
-    Val::Number(f64::NAN)
-  })
+  }
+
+  s[..end].parse::<f64>().unwrap_or(f64::NAN)
+}
+
+fn skip_digits(bytes: &[u8], mut pos: usize) -> usize {
+  while bytes.get(pos).is_some_and(|b| b.is_ascii_digit()) {
+    pos += 1;
+  }
+
+  pos
+}
+
+pub static PARSE_INT: NativeFunction = native_fn(|_this, params| {
+  let string_value = match params.get(0) {
+    Some(value) => value.to_string(),
+    None => return Ok(Val::Number(f64::NAN)),
+  };
+
+  let mut string_value =
+    string_value.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{feff}');
+
+  let is_negative = string_value.starts_with('-');
+
+  if let Some(stripped) = string_value.strip_prefix(['-', '+']) {
+    string_value = stripped;
+  }
+
+  // A radix of 0 (including when it's missing) means 10, or 16 if the string starts with 0x
+  let mut radix = params.get(1).map_or(0, |v| to_i32(v.to_number()));
+
+  let strip_hex_prefix = match radix {
+    0 => {
+      radix = 10;
+      true
+    }
+    16 => true,
+    2..=36 => false,
+    _ => return Ok(Val::Number(f64::NAN)),
+  };
+
+  if strip_hex_prefix {
+    if let Some(stripped) = string_value
+      .strip_prefix("0x")
+      .or_else(|| string_value.strip_prefix("0X"))
+    {
+      string_value = stripped;
+      radix = 16;
+    }
+  }
+
+  let digits = match string_value.find(|c: char| !c.is_digit(radix as u32)) {
+    Some(pos) => &string_value[..pos],
+    None => string_value,
+  };
+
+  if digits.is_empty() {
+    return Ok(Val::Number(f64::NAN));
+  }
+
+  let res = match radix {
+    // Parsing as a float rounds correctly, even when there are too many digits for an integer type
+    10 => digits.parse::<f64>().unwrap_or(f64::NAN),
+    _ => digits.chars().fold(0.0, |res, c| {
+      res * radix as f64 + c.to_digit(radix as u32).unwrap_or(0) as f64
+    }),
+  };
+
+  Ok(Val::Number(match is_negative {
+    true => -res,
+    false => res,
+  }))
 });
@@ -0,0 +1,502 @@
+use std::{collections::HashSet, fmt};
+
+use crate::{
+  native_function::{native_fn, NativeFunction, ThisWrapper},
+  operations::op_submov,
+  rc::Rc,
+  vs_accessor::VsAccessor,
+  vs_class::VsClass,
+  vs_object::VsObject,
+  vs_symbol::VsSymbol,
+  vs_value::{LoadFunctionResult, ToDynamicVal, ToVal, Val, ValTrait},
+};
+
+use super::{
+  builtin_object::BuiltinObject, internal_error_builtin::ToInternalError,
+  type_error_builtin::ToTypeError,
+};
+
+pub struct ObjectBuiltin {}
+
+impl BuiltinObject for ObjectBuiltin {
+  fn bo_name() -> &'static str {
+    "Object"
+  }
+
+  fn bo_sub(key: &str) -> Val {
+    match key {
+      "keys" => KEYS.to_val(),
+      "values" => VALUES.to_val(),
+      "entries" => ENTRIES.to_val(),
+      "defineProperty" => DEFINE_PROPERTY.to_val(),
+      "defineProperties" => DEFINE_PROPERTIES.to_val(),
+      "getOwnPropertyDescriptor" => GET_OWN_PROPERTY_DESCRIPTOR.to_val(),
+      "getOwnPropertyDescriptors" => GET_OWN_PROPERTY_DESCRIPTORS.to_val(),
+      "create" => CREATE.to_val(),
+      "getPrototypeOf" => GET_PROTOTYPE_OF.to_val(),
+      "setPrototypeOf" => SET_PROTOTYPE_OF.to_val(),
+      _ => Val::Undefined,
+    }
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NativeFunction(to_object)
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    None
+  }
+}
+
+impl fmt::Display for ObjectBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function Object() {{ [native code] }}")
+  }
+}
+
+/// The keys visited by `for (key in val)`: the keys from `enumerable_keys`, including the ones
+/// inherited from prototypes.
+pub static FOR_IN_KEYS: NativeFunction = native_fn(|_this, params| {
+  Ok(match params.first() {
+    Some(val) => enumerable_keys(val, true)?.to_val(),
+    None => vec![].to_val(),
+  })
+});
+
+/// Copies the properties of the second param (see `spread_keys`) onto the first (a new object),
+/// which is how the compiler implements `{ ...obj }`.
+pub static OBJECT_SPREAD: NativeFunction = native_fn(|_this, params| {
+  let mut params = params.into_iter();
+  let mut target = params.next().unwrap_or_default();
+  let source = params.next().unwrap_or_default();
+
+  let target_object = match &mut target {
+    Val::Object(object) => Rc::make_mut(object),
+    _ => return Err("Object spread target should be an object".to_internal_error()),
+  };
+
+  for key in spread_keys(&source)? {
+    let value = source.sub(&key)?;
+    target_object.define_value(&key, value);
+  }
+
+  Ok(target)
+});
+
+/// Adds a getter and/or setter (the third and fourth params, undefined when absent) to the first
+/// param (a new object), which is how the compiler implements `{ get x() { ... } }`.
+pub static OBJECT_DEFINE_ACCESSOR: NativeFunction = native_fn(|_this, params| {
+  let mut params = params.into_iter();
+  let mut target = params.next().unwrap_or_default();
+  let key = params.next().unwrap_or_default();
+
+  let mut accessor_fn = || {
+    params
+      .next()
+      .filter(|fn_| !matches!(fn_, Val::Undefined | Val::Void))
+  };
+
+  let accessor = VsAccessor {
+    get: accessor_fn(),
+    set: accessor_fn(),
+  };
+
+  match &mut target {
+    Val::Object(object) => Rc::make_mut(object).define_accessor(&key, accessor),
+    _ => return Err("Accessor target should be an object".to_internal_error()),
+  };
+
+  Ok(target)
+});
+
+/// A new object with the properties of the first param (see `spread_keys`) except for the keys in
+/// the second param, which is how the compiler implements `const { a, ...rest } = obj`.
+pub static OBJECT_REST: NativeFunction = native_fn(|_this, params| {
+  let source = object_param(params.first())?;
+
+  let excluded = match params.get(1) {
+    Some(Val::Array(excluded)) => excluded.elements.clone(),
+    _ => vec![],
+  };
+
+  let is_excluded = |key: &Val| {
+    excluded
+      .iter()
+      .any(|excluded_key| match (key, excluded_key) {
+        (Val::Symbol(a), Val::Symbol(b)) => a == b,
+        (Val::Symbol(_), _) | (_, Val::Symbol(_)) => false,
+        _ => key.to_string() == excluded_key.to_string(),
+      })
+  };
+
+  let mut rest = VsObject::default();
+
+  for key in spread_keys(source)? {
+    if !is_excluded(&key) {
+      rest.define_value(&key, source.sub(&key)?);
+    }
+  }
+
+  Ok(rest.to_val())
+});
+
+static KEYS: NativeFunction = native_fn(|_this, params| {
+  let obj = object_param(params.first())?;
+
+  Ok(enumerable_keys(obj, false)?.to_val())
+});
+
+static VALUES: NativeFunction = native_fn(|_this, params| {
+  let obj = object_param(params.first())?;
+  let mut values = vec![];
+
+  for key in enumerable_keys(obj, false)? {
+    values.push(obj.sub(&key)?);
+  }
+
+  Ok(values.to_val())
+});
+
+static ENTRIES: NativeFunction = native_fn(|_this, params| {
+  let obj = object_param(params.first())?;
+  let mut entries = vec![];
+
+  for key in enumerable_keys(obj, false)? {
+    let value = obj.sub(&key)?;
+    entries.push(vec![key, value].to_val());
+  }
+
+  Ok(entries.to_val())
+});
+
+// Objects can't be modified in place by a function they're passed to, so defineProperty,
+// defineProperties and setPrototypeOf return the modified object. The compiler assigns the result back to the first
+// argument (when it's a variable or property), so `Object.defineProperty(obj, ...)` still works
+// like it does in JS.
+
+static DEFINE_PROPERTY: NativeFunction = native_fn(|_this, params| {
+  let mut params = params.into_iter();
+  let mut obj = params.next().unwrap_or_default();
+  let key = params.next().unwrap_or_default();
+  let descriptor = params.next().unwrap_or_default();
+
+  define_property(&mut obj, &key, &descriptor)?;
+
+  Ok(obj)
+});
+
+static DEFINE_PROPERTIES: NativeFunction = native_fn(|_this, params| {
+  let mut params = params.into_iter();
+  let mut obj = params.next().unwrap_or_default();
+  let descriptors = params.next().unwrap_or_default();
+
+  for key in enumerable_keys(object_param(Some(&descriptors))?, false)? {
+    define_property(&mut obj, &key, &descriptors.sub(&key)?)?;
+  }
+
+  Ok(obj)
+});
+
+static GET_OWN_PROPERTY_DESCRIPTOR: NativeFunction = native_fn(|_this, params| {
+  let obj = object_param(params.first())?;
+  let key = params.get(1).cloned().unwrap_or_default();
+
+  Ok(own_property_descriptor(obj, &key).unwrap_or_default())
+});
+
+static GET_OWN_PROPERTY_DESCRIPTORS: NativeFunction = native_fn(|_this, params| {
+  let obj = object_param(params.first())?;
+  let mut descriptors = VsObject::default();
+
+  for key in own_keys(obj) {
+    if let Some(descriptor) = own_property_descriptor(obj, &key) {
+      descriptors.define_value(&key, descriptor);
+    }
+  }
+
+  Ok(descriptors.to_val())
+});
+
+// Prototypes are values like everything else, so an object gets a copy of its prototype (which is
+// cheap, because it's reference counted). Changing the prototype object afterwards doesn't affect
+// objects that were created from it.
+
+static CREATE: NativeFunction = native_fn(|_this, params| {
+  let mut params = params.into_iter();
+  let prototype = prototype_param(params.next())?;
+
+  let mut obj = VsObject {
+    prototype,
+    ..Default::default()
+  }
+  .to_val();
+
+  if let Some(descriptors) = params.next().filter(|d| !matches!(d, Val::Undefined)) {
+    for key in enumerable_keys(object_param(Some(&descriptors))?, false)? {
+      define_property(&mut obj, &key, &descriptors.sub(&key)?)?;
+    }
+  }
+
+  Ok(obj)
+});
+
+/// Plain objects (and all non-objects) don't have a prototype in ValueScript, so this is null
+/// unless the object was created by a class or has had its prototype set explicitly.
+static GET_PROTOTYPE_OF: NativeFunction = native_fn(|_this, params| {
+  Ok(match object_param(params.first())? {
+    Val::Object(object) => object.prototype.clone().unwrap_or(Val::Null),
+    _ => Val::Null,
+  })
+});
+
+static SET_PROTOTYPE_OF: NativeFunction = native_fn(|_this, params| {
+  let mut params = params.into_iter();
+  let mut obj = params.next().unwrap_or_default();
+  let prototype = prototype_param(params.next())?;
+
+  match &mut obj {
+    Val::Undefined | Val::Null => {
+      return Err("Object.setPrototypeOf called on null or undefined".to_type_error())
+    }
+    Val::Object(object) => Rc::make_mut(object).prototype = prototype,
+    val if val.is_primitive() => {}
+    _ => return Err("TODO: setPrototypeOf for non-plain objects".to_internal_error()),
+  };
+
+  Ok(obj)
+});
+
+fn prototype_param(param: Option<Val>) -> Result<Option<Val>, Val> {
+  match param {
+    Some(Val::Null) => Ok(None),
+    Some(prototype @ Val::Object(_)) => Ok(Some(prototype)),
+    _ => Err("Object prototype may only be an Object or null".to_type_error()),
+  }
+}
+
+/// Defines `key` on `obj` using a property descriptor. Every property is writable and
+/// configurable, and own properties of objects are always enumerable, so those flags are accepted
+/// but have no effect.
+fn define_property(obj: &mut Val, key: &Val, descriptor: &Val) -> Result<(), Val> {
+  if descriptor.is_primitive() {
+    return Err("Property description must be an object".to_type_error());
+  }
+
+  let get = descriptor.sub(&"get".to_val())?;
+  let set = descriptor.sub(&"set".to_val())?;
+  let value = descriptor.sub(&"value".to_val())?;
+
+  let accessor = match (get, set) {
+    (Val::Undefined, Val::Undefined) => None,
+    (get, set) => {
+      for (name, fn_) in [("Getter", &get), ("Setter", &set)] {
+        if !matches!(fn_, Val::Undefined)
+          && matches!(fn_.load_function(), LoadFunctionResult::NotAFunction)
+        {
+          return Err(format!("{} must be a function", name).to_type_error());
+        }
+      }
+
+      if !matches!(value, Val::Undefined) {
+        return Err(
+          "Invalid property descriptor. Cannot both specify accessors and a value".to_type_error(),
+        );
+      }
+
+      Some(VsAccessor {
+        get: Some(get).filter(|get| !matches!(get, Val::Undefined)),
+        set: Some(set).filter(|set| !matches!(set, Val::Undefined)),
+      })
+    }
+  };
+
+  match obj {
+    Val::Object(object) => {
+      let object = Rc::make_mut(object);
+
+      match accessor {
+        Some(accessor) => object.define_value(key, accessor.to_dynamic_val()),
+        None => object.define_value(key, value),
+      }
+
+      Ok(())
+    }
+    Val::Array(_) => match accessor {
+      Some(_) => Err("TODO: Accessors on arrays".to_internal_error()),
+      None => op_submov(obj, key, value),
+    },
+    _ => Err("Object.defineProperty called on non-object".to_type_error()),
+  }
+}
+
+/// The keys that have descriptors, ie the keys from `enumerable_keys` plus the non-enumerable
+/// ones and symbols.
+fn own_keys(val: &Val) -> Vec<Val> {
+  let mut keys = vec![];
+
+  match val {
+    Val::Array(array) => {
+      for (i, element) in array.elements.iter().enumerate() {
+        if !matches!(element, Val::Void) {
+          keys.push(i.to_string().to_val());
+        }
+      }
+
+      keys.push("length".to_val());
+    }
+    Val::Object(object) => {
+      keys.extend(object.string_map.keys().map(|k| k.clone().to_val()));
+
+      keys.extend(
+        object
+          .symbol_map
+          .keys()
+          .filter(|k| **k != VsSymbol::CLASS_NAME)
+          .map(|k| k.clone().to_val()),
+      );
+    }
+    Val::Class(class) => {
+      if let Val::Object(static_) = &class.static_ {
+        keys.extend(static_.string_map.keys().map(|k| k.clone().to_val()));
+        keys.extend(static_.symbol_map.keys().map(|k| k.clone().to_val()));
+      }
+    }
+    _ => {}
+  }
+
+  keys
+}
+
+fn own_property_descriptor(val: &Val, key: &Val) -> Option<Val> {
+  let (property, enumerable) = match val {
+    Val::Array(array) => match key.to_string().as_str() {
+      "length" => ((array.elements.len() as f64).to_val(), false),
+      _ => match key.to_index().and_then(|i| array.elements.get(i)) {
+        None | Some(Val::Void) => return None,
+        Some(element) => (element.clone(), true),
+      },
+    },
+    Val::Object(object) => (object.own_property(key)?.clone(), true),
+    Val::Class(class) => match &class.static_ {
+      Val::Object(static_) => {
+        let property = static_.own_property(key)?;
+        (property.clone(), !is_class_member(property))
+      }
+      _ => return None,
+    },
+    _ => return None,
+  };
+
+  let mut descriptor = VsObject::default();
+
+  match VsAccessor::from_val(&property) {
+    Some(accessor) => {
+      descriptor.define_value(&"get".to_val(), accessor.get.clone().unwrap_or_default());
+      descriptor.define_value(&"set".to_val(), accessor.set.clone().unwrap_or_default());
+    }
+    None => {
+      descriptor.define_value(&"value".to_val(), property);
+      descriptor.define_value(&"writable".to_val(), true.to_val());
+    }
+  }
+
+  descriptor.define_value(&"enumerable".to_val(), enumerable.to_val());
+  descriptor.define_value(&"configurable".to_val(), true.to_val());
+
+  Some(descriptor.to_val())
+}
+
+fn to_object(_: ThisWrapper, params: Vec<Val>) -> Result<Val, Val> {
+  Ok(match params.into_iter().next() {
+    Some(val) if !val.is_primitive() => val,
+    _ => VsObject::default().to_val(),
+  })
+}
+
+fn object_param(param: Option<&Val>) -> Result<&Val, Val> {
+  match param {
+    None | Some(Val::Undefined | Val::Null) => {
+      Err("Cannot convert undefined or null to object".to_type_error())
+    }
+    Some(val) => Ok(val),
+  }
+}
+
+/// The enumerable string keys of `val`, in JS property order (array indices first, then the
+/// other keys in insertion order). Symbol keys are never included.
+///
+/// With `include_prototypes`, the keys of prototypes follow, skipping keys that are shadowed by an
+/// earlier object. Class members (methods and accessors) aren't enumerable, which applies to
+/// everything on a class's prototype and to the static methods of a class.
+pub fn enumerable_keys(val: &Val, include_prototypes: bool) -> Result<Vec<Val>, Val> {
+  let mut keys = vec![];
+
+  match val {
+    Val::Array(array) => {
+      for (i, element) in array.elements.iter().enumerate() {
+        if !matches!(element, Val::Void) {
+          keys.push(i.to_string().to_val());
+        }
+      }
+    }
+    Val::String(_) => {
+      let len = val.sub(&"length".to_val())?.to_number() as usize;
+
+      for i in 0..len {
+        keys.push(i.to_string().to_val());
+      }
+    }
+    Val::Object(object) => {
+      keys.extend(object.string_map.keys().map(|k| k.clone().to_val()));
+
+      if include_prototypes {
+        let mut seen: HashSet<&str> = object.string_map.keys().map(|k| k.as_str()).collect();
+        let mut prototype = &object.prototype;
+
+        while let Some(Val::Object(proto)) = prototype {
+          for (key, value) in &proto.string_map {
+            if seen.insert(key.as_str()) && !is_class_member(value) {
+              keys.push(key.clone().to_val());
+            }
+          }
+
+          prototype = &proto.prototype;
+        }
+      }
+    }
+    Val::Class(class) => {
+      if let Val::Object(static_) = &class.static_ {
+        for (key, value) in &static_.string_map {
+          if !is_class_member(value) {
+            keys.push(key.clone().to_val());
+          }
+        }
+      }
+    }
+    _ => {}
+  };
+
+  Ok(keys)
+}
+
+/// The keys copied by object spread and rest: the keys from `enumerable_keys`, plus symbol keys.
+fn spread_keys(val: &Val) -> Result<Vec<Val>, Val> {
+  let mut keys = enumerable_keys(val, false)?;
+
+  if let Val::Object(object) = val {
+    keys.extend(
+      object
+        .symbol_map
+        .keys()
+        .filter(|k| **k != VsSymbol::CLASS_NAME)
+        .map(|k| k.clone().to_val()),
+    );
+  }
+
+  Ok(keys)
+}
+
+fn is_class_member(value: &Val) -> bool {
+  VsAccessor::from_val(value).is_some()
+    || !matches!(value.load_function(), LoadFunctionResult::NotAFunction)
+}
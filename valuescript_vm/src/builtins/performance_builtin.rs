@@ -0,0 +1,45 @@
+use std::fmt;
+
+use crate::host::with_current_host;
+use crate::native_function::{native_fn, NativeFunction};
+use crate::rc::Rc;
+use crate::vs_class::VsClass;
+use crate::vs_value::{LoadFunctionResult, ToVal, Val};
+
+use super::builtin_object::BuiltinObject;
+
+pub struct PerformanceBuiltin {}
+
+impl BuiltinObject for PerformanceBuiltin {
+  fn bo_name() -> &'static str {
+    "performance"
+  }
+
+  fn bo_sub(key: &str) -> Val {
+    match key {
+      "now" => NOW.to_val(),
+
+      _ => Val::Undefined,
+    }
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    None
+  }
+}
+
+impl fmt::Display for PerformanceBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "[object Performance]")
+  }
+}
+
+static NOW: NativeFunction = native_fn(|_this, _params| {
+  Ok(Val::Number(with_current_host(|host| {
+    host.clock.performance_now()
+  })))
+});
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::native_function::{native_fn, NativeFunction};
+use crate::promise::{promise_all, when_settled, PromiseResolver, Reaction, VsPromise, SETTLE};
+use crate::rc::Rc;
+use crate::vs_class::VsClass;
+use crate::vs_object::VsObject;
+use crate::vs_value::{LoadFunctionResult, ToDynamicVal, ToVal, Val, ValTrait};
+use crate::VirtualMachine;
+
+use super::builtin_object::BuiltinObject;
+use super::type_error_builtin::ToTypeError;
+
+pub struct PromiseBuiltin {}
+
+impl BuiltinObject for PromiseBuiltin {
+  fn bo_name() -> &'static str {
+    "Promise"
+  }
+
+  fn bo_sub(key: &str) -> Val {
+    match key {
+      "resolve" => RESOLVE.to_val(),
+      "reject" => REJECT.to_val(),
+      "all" => ALL.to_val(),
+      "race" => RACE.to_val(),
+      _ => Val::Undefined,
+    }
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    Some(Rc::new(VsClass {
+      name: "Promise".to_string(),
+      content_hash: None,
+      constructor: CONSTRUCTOR.to_val(),
+      prototype: VsObject::default().to_val(),
+      static_: VsObject::default().to_val(),
+    }))
+  }
+}
+
+impl fmt::Display for PromiseBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function Promise() {{ [native code] }}")
+  }
+}
+
+/// `new Promise(executor)`. The executor is called immediately with the resolve and reject
+/// functions, and the promise replaces the instance that `new` created.
+static CONSTRUCTOR: NativeFunction = native_fn(|_this, params| {
+  let executor = match params.first() {
+    Some(executor) if !matches!(executor.load_function(), LoadFunctionResult::NotAFunction) => {
+      executor.clone()
+    }
+    _ => return Err("Promise resolver is not a function".to_type_error()),
+  };
+
+  let promise = VsPromise::new_pending();
+
+  let resolvers = vec![
+    PromiseResolver {
+      promise,
+      rejects: false,
+    }
+    .to_dynamic_val(),
+    PromiseResolver {
+      promise,
+      rejects: true,
+    }
+    .to_dynamic_val(),
+  ];
+
+  if let Err(reason) = VirtualMachine::call_nested(&executor, Val::Undefined, resolvers) {
+    promise.reject(reason);
+  }
+
+  Ok(promise.to_dynamic_val())
+});
+
+static RESOLVE: NativeFunction = native_fn(|_this, params| {
+  let value = params.into_iter().next().unwrap_or(Val::Undefined);
+
+  if VsPromise::from_val(&value).is_some() {
+    return Ok(value);
+  }
+
+  let promise = VsPromise::new_pending();
+  promise.resolve(value);
+
+  Ok(promise.to_dynamic_val())
+});
+
+static REJECT: NativeFunction = native_fn(|_this, params| {
+  let promise = VsPromise::new_pending();
+  promise.reject(params.into_iter().next().unwrap_or(Val::Undefined));
+
+  Ok(promise.to_dynamic_val())
+});
+
+fn promises_param(params: &[Val], method_name: &str) -> Result<Vec<Val>, Val> {
+  match params.first().and_then(|param| param.as_array_data()) {
+    Some(array_data) => Ok(array_data.elements.clone()),
+    None => Err(format!("Promise.{} requires an array", method_name).to_type_error()),
+  }
+}
+
+static ALL: NativeFunction =
+  native_fn(|_this, params| Ok(promise_all(promises_param(&params, "all")?).to_dynamic_val()));
+
+/// Settles the same way as the first of the promises to settle.
+static RACE: NativeFunction = native_fn(|_this, params| {
+  let result = VsPromise::new_pending();
+
+  for promise in promises_param(&params, "race")? {
+    when_settled(
+      promise,
+      Reaction {
+        job: SETTLE.to_val(),
+        params: vec![result.to_dynamic_val()],
+      },
+    );
+  }
+
+  Ok(result.to_dynamic_val())
+});
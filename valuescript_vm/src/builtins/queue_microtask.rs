@@ -0,0 +1,20 @@
+use crate::host::with_current_host;
+use crate::native_function::{native_fn, NativeFunction};
+use crate::vs_value::{LoadFunctionResult, Val, ValTrait};
+
+use super::type_error_builtin::ToTypeError;
+
+/// `queueMicrotask(callback)`, which runs `callback` once the current task (and the microtasks
+/// queued before it) have completed.
+pub static QUEUE_MICROTASK: NativeFunction = native_fn(|_this, params| {
+  let callback = match params.first() {
+    Some(callback) if !matches!(callback.load_function(), LoadFunctionResult::NotAFunction) => {
+      callback.clone()
+    }
+    _ => return Err("queueMicrotask: callback is not a function".to_type_error()),
+  };
+
+  with_current_host(|host| host.scheduler.queue_microtask(callback, vec![]));
+
+  Ok(Val::Undefined)
+});
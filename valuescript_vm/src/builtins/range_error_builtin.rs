@@ -1,18 +1,20 @@
 use std::fmt;
-use std::{collections::BTreeMap, rc::Rc};
 
 use crate::native_function::{native_fn, ThisWrapper};
+use crate::property_map::PropertyMap;
 use crate::vs_value::ToVal;
 use crate::ValTrait;
 use crate::{
   native_function::NativeFunction,
   operations::op_submov,
+  rc::Rc,
   vs_class::VsClass,
   vs_object::VsObject,
   vs_value::{LoadFunctionResult, Val},
 };
 
 use super::builtin_object::BuiltinObject;
+use super::error_builtin::install_cause;
 
 pub struct RangeErrorBuiltin {}
 
@@ -49,7 +51,7 @@ impl fmt::Display for RangeErrorBuiltin {
 // TODO: Static? (Rc -> Arc?)
 fn make_range_error_prototype() -> Val {
   VsObject {
-    string_map: BTreeMap::from([
+    string_map: PropertyMap::from([
       ("name".to_string(), "RangeError".to_val()),
       ("toString".to_string(), Val::Static(&RANGE_ERROR_TO_STRING)),
     ]),
@@ -65,26 +67,30 @@ static SET_MESSAGE: NativeFunction = native_fn(|mut this, params| {
     None => "".to_string(),
   };
 
-  op_submov(this.get_mut()?, &"message".to_val(), message.to_val())?;
+  let this = this.get_mut()?;
+  op_submov(this, &"message".to_val(), message.to_val())?;
+  install_cause(this, params.get(1))?;
 
   Ok(Val::Undefined)
 });
 
 pub fn to_range_error(_: ThisWrapper, params: Vec<Val>) -> Result<Val, Val> {
-  Ok(
-    VsObject {
-      string_map: BTreeMap::from([(
-        "message".to_string(),
-        match params.get(0) {
-          Some(param) => param.clone().to_val_string(),
-          None => "".to_val(),
-        },
-      )]),
-      symbol_map: Default::default(),
-      prototype: Some(make_range_error_prototype()),
-    }
-    .to_val(),
-  )
+  let mut error = VsObject {
+    string_map: PropertyMap::from([(
+      "message".to_string(),
+      match params.get(0) {
+        Some(param) => param.clone().to_val_string(),
+        None => "".to_val(),
+      },
+    )]),
+    symbol_map: Default::default(),
+    prototype: Some(make_range_error_prototype()),
+  }
+  .to_val();
+
+  install_cause(&mut error, params.get(1))?;
+
+  Ok(error)
 }
 
 static RANGE_ERROR_TO_STRING: NativeFunction = native_fn(|this, _params| {
@@ -111,7 +117,7 @@ impl ToRangeError for String {
 impl ToRangeError for Val {
   fn to_range_error(self) -> Val {
     VsObject {
-      string_map: BTreeMap::from([("message".to_string(), self)]),
+      string_map: PropertyMap::from([("message".to_string(), self)]),
       symbol_map: Default::default(),
       prototype: Some(make_range_error_prototype()),
     }
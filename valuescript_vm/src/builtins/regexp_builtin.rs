@@ -0,0 +1,223 @@
+use std::fmt;
+
+use crate::native_function::{native_fn, NativeFunction, ThisWrapper};
+use crate::operations::op_submov;
+use crate::property_map::PropertyMap;
+use crate::rc::Rc;
+use crate::regex::{Regex, RegexFlags, RegexMatch};
+use crate::vs_class::VsClass;
+use crate::vs_object::VsObject;
+use crate::vs_value::{LoadFunctionResult, ToVal, Val, ValTrait};
+
+use super::builtin_object::BuiltinObject;
+use super::error_builtin::ToError;
+use super::range_error_builtin::ToRangeError;
+
+pub struct RegExpBuiltin {}
+
+impl BuiltinObject for RegExpBuiltin {
+  fn bo_name() -> &'static str {
+    "RegExp"
+  }
+
+  fn bo_sub(_key: &str) -> Val {
+    Val::Undefined
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    // Like JS, RegExp(...) is the same as new RegExp(...)
+    LoadFunctionResult::NativeFunction(|_: ThisWrapper, params: Vec<Val>| -> Result<Val, Val> {
+      let mut regexp = VsObject {
+        string_map: Default::default(),
+        symbol_map: Default::default(),
+        prototype: Some(make_regexp_prototype()),
+      }
+      .to_val();
+
+      (CONSTRUCTOR.fn_)(ThisWrapper::new(false, &mut regexp), params)?;
+
+      Ok(regexp)
+    })
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    Some(Rc::new(VsClass {
+      name: "RegExp".to_string(),
+      content_hash: None,
+      constructor: CONSTRUCTOR.to_val(),
+      prototype: make_regexp_prototype(),
+      static_: VsObject::default().to_val(),
+    }))
+  }
+}
+
+impl fmt::Display for RegExpBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function RegExp() {{ [native code] }}")
+  }
+}
+
+fn make_regexp_prototype() -> Val {
+  VsObject {
+    string_map: PropertyMap::from([
+      ("exec".to_string(), EXEC.to_val()),
+      ("test".to_string(), TEST.to_val()),
+      ("toString".to_string(), TO_STRING.to_val()),
+    ]),
+    symbol_map: Default::default(),
+    prototype: None,
+  }
+  .to_val()
+}
+
+/// Whether `val` is a RegExp object (as opposed to a string to search for).
+pub fn is_regexp(val: &Val) -> bool {
+  if !matches!(val, Val::Object(_)) {
+    return false;
+  }
+
+  match val.sub(&"exec".to_val()) {
+    Ok(Val::Static(exec)) => std::ptr::addr_eq(exec, &EXEC),
+    _ => false,
+  }
+}
+
+/// The compiled regex of a RegExp object, and its lastIndex.
+pub fn to_regex(regexp: &Val) -> Result<(Regex, usize), Val> {
+  let source = regexp.sub(&"source".to_val())?.to_string();
+  let flags = RegexFlags::parse(&regexp.sub(&"flags".to_val())?.to_string()).map_err(to_error)?;
+  let regex = Regex::new(&source, flags).map_err(to_error)?;
+
+  let last_index = regexp.sub(&"lastIndex".to_val())?.to_number();
+
+  let last_index = match last_index.is_finite() && last_index > 0.0 {
+    true => last_index as usize,
+    false => 0,
+  };
+
+  Ok((regex, last_index))
+}
+
+fn to_error(message: String) -> Val {
+  message.to_error()
+}
+
+/// The error for a search that exceeded the regex step limit.
+pub fn to_search_error(message: String) -> Val {
+  message.to_range_error()
+}
+
+/// The array of the match and the capture groups (undefined for groups that didn't participate),
+/// like `exec` gives.
+///
+/// Arrays don't have named properties in ValueScript, so unlike JS it doesn't have `index`,
+/// `input` or `groups`.
+pub fn match_array(input: &str, m: &RegexMatch) -> Val {
+  m.captures
+    .iter()
+    .map(|capture| match capture {
+      Some((start, end)) => input[*start..*end].to_val(),
+      None => Val::Undefined,
+    })
+    .collect::<Vec<Val>>()
+    .to_val()
+}
+
+static CONSTRUCTOR: NativeFunction = native_fn(|mut this, params| {
+  let (source, flags) = match params.first() {
+    Some(pattern) if is_regexp(pattern) => (
+      pattern.sub(&"source".to_val())?.to_string(),
+      match params.get(1) {
+        None | Some(Val::Undefined) => pattern.sub(&"flags".to_val())?.to_string(),
+        Some(flags) => flags.to_string(),
+      },
+    ),
+    _ => (
+      match params.first() {
+        None | Some(Val::Undefined) => "(?:)".to_string(),
+        Some(pattern) => pattern.to_string(),
+      },
+      match params.get(1) {
+        None | Some(Val::Undefined) => "".to_string(),
+        Some(flags) => flags.to_string(),
+      },
+    ),
+  };
+
+  let flags = RegexFlags::parse(&flags).map_err(to_error)?;
+
+  // Invalid patterns are reported here rather than when the regex is used
+  Regex::new(&source, flags).map_err(to_error)?;
+
+  let this = this.get_mut()?;
+
+  for (key, value) in [
+    ("source", source.to_val()),
+    ("flags", flags.to_string().to_val()),
+    ("global", flags.global.to_val()),
+    ("ignoreCase", flags.ignore_case.to_val()),
+    ("multiline", flags.multiline.to_val()),
+    ("dotAll", flags.dot_all.to_val()),
+    ("unicode", flags.unicode.to_val()),
+    ("sticky", flags.sticky.to_val()),
+    ("lastIndex", Val::Number(0.0)),
+  ] {
+    op_submov(this, &key.to_val(), value)?;
+  }
+
+  Ok(Val::Undefined)
+});
+
+/// Finds the next match for `exec` and `test`, which start from lastIndex (and update it) when the
+/// regex is global or sticky. The update is skipped when the RegExp is const.
+fn exec_impl(this: &mut ThisWrapper, input: &str) -> Result<Option<RegexMatch>, Val> {
+  let (regex, last_index) = to_regex(this.get())?;
+  let uses_last_index = regex.flags.global || regex.flags.sticky;
+
+  if !uses_last_index {
+    return regex.find(input, 0).map_err(to_search_error);
+  }
+
+  let m = match last_index <= input.len() {
+    true => regex.find(input, last_index).map_err(to_search_error)?,
+    false => None,
+  };
+
+  if let Ok(this) = this.get_mut() {
+    op_submov(
+      this,
+      &"lastIndex".to_val(),
+      Val::Number(m.as_ref().map_or(0, |m| m.end()) as f64),
+    )?;
+  }
+
+  Ok(m)
+}
+
+static EXEC: NativeFunction = native_fn(|mut this, params| {
+  let input = match params.first() {
+    Some(input) => input.to_string(),
+    None => "undefined".to_string(),
+  };
+
+  Ok(match exec_impl(&mut this, &input)? {
+    Some(m) => match_array(&input, &m),
+    None => Val::Null,
+  })
+});
+
+static TEST: NativeFunction = native_fn(|mut this, params| {
+  let input = match params.first() {
+    Some(input) => input.to_string(),
+    None => "undefined".to_string(),
+  };
+
+  Ok(exec_impl(&mut this, &input)?.is_some().to_val())
+});
+
+static TO_STRING: NativeFunction = native_fn(|this, _params| {
+  let source = this.get().sub(&"source".to_val())?.to_string();
+  let flags = this.get().sub(&"flags".to_val())?.to_string();
+
+  Ok(format!("/{}/{}", source, flags).to_val())
+});
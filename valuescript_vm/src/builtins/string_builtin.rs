@@ -1,10 +1,10 @@
 use std::fmt;
-use std::rc::Rc;
 
 use crate::native_function::{native_fn, ThisWrapper};
 use crate::vs_value::ToVal;
 use crate::{
   native_function::NativeFunction,
+  rc::Rc,
   vs_class::VsClass,
   vs_value::{LoadFunctionResult, Val},
   ValTrait,
@@ -49,16 +49,40 @@ impl fmt::Display for StringBuiltin {
 
 static FROM_CODE_POINT: NativeFunction = native_fn(|_this, params| {
   let mut result = String::new();
+  let mut pending_high_surrogate: Option<u32> = None;
 
   for param in params {
-    let code_point = param.to_number() as u32; // TODO: Check overflow behavior
+    let number = param.to_number();
 
-    let char = match std::char::from_u32(code_point) {
-      Some(c) => c,
-      None => return Err("Invalid code point".to_range_error()),
-    };
+    if number != number.trunc() || !(0.0..=0x10FFFF as f64).contains(&number) {
+      return Err(format!("Invalid code point {}", param).to_range_error());
+    }
+
+    let code_point = number as u32;
+
+    // Strings are utf8 in ValueScript, so surrogates can't be stored directly. Instead we combine
+    // surrogate pairs into the code point they represent, and replace lone surrogates with U+FFFD
+    // (which is also what happens when JavaScript encodes them as utf8).
+    if let Some(high) = pending_high_surrogate.take() {
+      if (0xDC00..=0xDFFF).contains(&code_point) {
+        let combined = 0x10000 + ((high - 0xD800) << 10) + (code_point - 0xDC00);
+        result.push(std::char::from_u32(combined).expect("Invalid surrogate pair"));
+        continue;
+      }
+
+      result.push(char::REPLACEMENT_CHARACTER);
+    }
+
+    if (0xD800..=0xDBFF).contains(&code_point) {
+      pending_high_surrogate = Some(code_point);
+      continue;
+    }
+
+    result.push(std::char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER));
+  }
 
-    result.push(char);
+  if pending_high_surrogate.is_some() {
+    result.push(char::REPLACEMENT_CHARACTER);
   }
 
   Ok(result.to_val())
@@ -0,0 +1,30 @@
+use crate::native_function::{native_fn, NativeFunction};
+use crate::vs_value::{ToVal, Val, ValTrait};
+
+use super::type_error_builtin::ToTypeError;
+
+/// Gets the constructor that `super(...)` calls for a class extending `params[0]`. This is a
+/// no-op function when the base class (and everything it extends) has no constructor.
+pub static SUPER_CONSTRUCTOR: NativeFunction = native_fn(|_this, params| {
+  let base = match params.first().and_then(|base| base.as_class_data()) {
+    Some(base) => base,
+    None => return Err("Class extends value is not a constructor".to_type_error()),
+  };
+
+  Ok(match &base.constructor {
+    Val::Void => NOOP_CONSTRUCTOR.to_val(),
+    constructor => constructor.clone(),
+  })
+});
+
+/// Gets the prototype of the base class `params[0]`, which is where `super.method` is looked up in
+/// instance methods.
+pub static SUPER_PROTOTYPE: NativeFunction =
+  native_fn(
+    |_this, params| match params.first().and_then(|base| base.as_class_data()) {
+      Some(base) => Ok(base.prototype.clone()),
+      None => Err("Class extends value is not a constructor".to_type_error()),
+    },
+  );
+
+static NOOP_CONSTRUCTOR: NativeFunction = native_fn(|_this, _params| Ok(Val::Undefined));
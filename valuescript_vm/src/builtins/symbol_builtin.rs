@@ -1,12 +1,15 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use crate::{
+  native_function::{native_fn, NativeFunction},
+  rc::Rc,
   vs_class::VsClass,
   vs_symbol::VsSymbol,
-  vs_value::{LoadFunctionResult, ToVal, Val},
+  vs_value::{LoadFunctionResult, ToVal, Val, ValTrait},
 };
 
 use super::builtin_object::BuiltinObject;
+use super::type_error_builtin::ToTypeError;
 
 pub struct SymbolBuiltin {}
 
@@ -18,6 +21,12 @@ impl BuiltinObject for SymbolBuiltin {
   fn bo_sub(key: &str) -> Val {
     match key {
       "iterator" => VsSymbol::ITERATOR.to_val(),
+      "asyncIterator" => VsSymbol::ASYNC_ITERATOR.to_val(),
+      "toPrimitive" => VsSymbol::TO_PRIMITIVE.to_val(),
+      "hasInstance" => VsSymbol::HAS_INSTANCE.to_val(),
+      "toStringTag" => VsSymbol::TO_STRING_TAG.to_val(),
+      "for" => FOR.to_val(),
+      "keyFor" => KEY_FOR.to_val(),
       _ => Val::Undefined,
     }
   }
@@ -36,3 +45,23 @@ impl fmt::Display for SymbolBuiltin {
     write!(f, "[object Symbol]")
   }
 }
+
+static FOR: NativeFunction = native_fn(|_this, params| {
+  let key = match params.first() {
+    Some(key) => key.to_string(),
+    None => "undefined".to_string(),
+  };
+
+  Ok(VsSymbol::Registered(key.into()).to_val())
+});
+
+static KEY_FOR: NativeFunction = native_fn(|_this, params| {
+  Ok(match params.first() {
+    Some(Val::Symbol(symbol)) => match symbol.registry_key() {
+      Some(key) => Val::String(key.into()),
+      None => Val::Undefined,
+    },
+    Some(val) => return Err(format!("{} is not a symbol", val.codify()).to_type_error()),
+    None => return Err("undefined is not a symbol".to_type_error()),
+  })
+});
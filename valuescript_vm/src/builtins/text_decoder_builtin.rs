@@ -0,0 +1,120 @@
+use std::fmt;
+
+use crate::native_function::{native_fn, NativeFunction, ThisWrapper};
+use crate::operations::{op_submov, to_u32};
+use crate::property_map::PropertyMap;
+use crate::rc::Rc;
+use crate::vs_class::VsClass;
+use crate::vs_object::VsObject;
+use crate::vs_value::{LoadFunctionResult, ToVal, Val};
+use crate::ValTrait;
+
+use super::builtin_object::BuiltinObject;
+use super::range_error_builtin::ToRangeError;
+use super::type_error_builtin::ToTypeError;
+
+pub struct TextDecoderBuiltin {}
+
+impl BuiltinObject for TextDecoderBuiltin {
+  fn bo_name() -> &'static str {
+    "TextDecoder"
+  }
+
+  fn bo_sub(_key: &str) -> Val {
+    Val::Undefined
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NativeFunction(|_: ThisWrapper, _params: Vec<Val>| -> Result<Val, Val> {
+      Err("Constructor TextDecoder requires 'new'".to_type_error())
+    })
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    Some(Rc::new(VsClass {
+      name: "TextDecoder".to_string(),
+      content_hash: None,
+      constructor: CONSTRUCTOR.to_val(),
+      prototype: make_text_decoder_prototype(),
+      static_: VsObject::default().to_val(),
+    }))
+  }
+}
+
+impl fmt::Display for TextDecoderBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function TextDecoder() {{ [native code] }}")
+  }
+}
+
+fn make_text_decoder_prototype() -> Val {
+  VsObject {
+    string_map: PropertyMap::from([
+      ("name".to_string(), "TextDecoder".to_val()),
+      ("encoding".to_string(), "utf-8".to_val()),
+      ("decode".to_string(), DECODE.to_val()),
+    ]),
+    symbol_map: Default::default(),
+    prototype: None,
+  }
+  .to_val()
+}
+
+static CONSTRUCTOR: NativeFunction = native_fn(|mut this, params| {
+  if let Some(label) = params.get(0) {
+    if !matches!(label, Val::Undefined) {
+      let label = label.to_string().trim().to_ascii_lowercase();
+
+      if !matches!(label.as_str(), "utf-8" | "utf8" | "unicode-1-1-utf-8") {
+        return Err(
+          format!("The encoding label provided ('{}') is invalid", label).to_range_error(),
+        );
+      }
+    }
+  }
+
+  let (fatal, ignore_bom) = match params.get(1) {
+    Some(options) if !options.is_nullish() => (
+      options.sub(&"fatal".to_val())?.is_truthy(),
+      options.sub(&"ignoreBOM".to_val())?.is_truthy(),
+    ),
+    _ => (false, false),
+  };
+
+  let this = this.get_mut()?;
+  op_submov(this, &"fatal".to_val(), fatal.to_val())?;
+  op_submov(this, &"ignoreBOM".to_val(), ignore_bom.to_val())?;
+
+  Ok(Val::Undefined)
+});
+
+// Accepts an array of numbers as bytes, since there are no typed arrays in ValueScript.
+static DECODE: NativeFunction = native_fn(|this, params| {
+  let bytes = match params.get(0) {
+    None | Some(Val::Undefined) => vec![],
+    Some(p) => match p.as_array_data() {
+      Some(array_data) => array_data
+        .elements
+        .iter()
+        .map(|b| to_u32(b.to_number()) as u8)
+        .collect::<Vec<u8>>(),
+      None => return Err("decode expects an array of bytes".to_type_error()),
+    },
+  };
+
+  let fatal = this.get().sub(&"fatal".to_val())?.is_truthy();
+  let ignore_bom = this.get().sub(&"ignoreBOM".to_val())?.is_truthy();
+
+  let bytes = match !ignore_bom && bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+    true => &bytes[3..],
+    false => &bytes[..],
+  };
+
+  Ok(match fatal {
+    true => match std::str::from_utf8(bytes) {
+      Ok(s) => s.to_val(),
+      Err(_) => return Err("The encoded data was not valid utf-8".to_type_error()),
+    },
+    false => String::from_utf8_lossy(bytes).into_owned().to_val(),
+  })
+});
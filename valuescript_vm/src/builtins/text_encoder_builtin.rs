@@ -0,0 +1,78 @@
+use std::fmt;
+
+use crate::native_function::{native_fn, NativeFunction, ThisWrapper};
+use crate::property_map::PropertyMap;
+use crate::rc::Rc;
+use crate::vs_array::VsArray;
+use crate::vs_class::VsClass;
+use crate::vs_object::VsObject;
+use crate::vs_value::{LoadFunctionResult, ToVal, Val};
+
+use super::builtin_object::BuiltinObject;
+use super::type_error_builtin::ToTypeError;
+
+pub struct TextEncoderBuiltin {}
+
+impl BuiltinObject for TextEncoderBuiltin {
+  fn bo_name() -> &'static str {
+    "TextEncoder"
+  }
+
+  fn bo_sub(_key: &str) -> Val {
+    Val::Undefined
+  }
+
+  fn bo_load_function() -> LoadFunctionResult {
+    LoadFunctionResult::NativeFunction(|_: ThisWrapper, _params: Vec<Val>| -> Result<Val, Val> {
+      Err("Constructor TextEncoder requires 'new'".to_type_error())
+    })
+  }
+
+  fn bo_as_class_data() -> Option<Rc<VsClass>> {
+    Some(Rc::new(VsClass {
+      name: "TextEncoder".to_string(),
+      content_hash: None,
+      constructor: Val::Void,
+      prototype: make_text_encoder_prototype(),
+      static_: VsObject::default().to_val(),
+    }))
+  }
+}
+
+impl fmt::Display for TextEncoderBuiltin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function TextEncoder() {{ [native code] }}")
+  }
+}
+
+fn make_text_encoder_prototype() -> Val {
+  VsObject {
+    string_map: PropertyMap::from([
+      ("name".to_string(), "TextEncoder".to_val()),
+      ("encoding".to_string(), "utf-8".to_val()),
+      ("encode".to_string(), ENCODE.to_val()),
+    ]),
+    symbol_map: Default::default(),
+    prototype: None,
+  }
+  .to_val()
+}
+
+// There are no typed arrays in ValueScript, so the bytes are provided as an array of numbers (the
+// same values a Uint8Array would contain).
+static ENCODE: NativeFunction = native_fn(|_this, params| {
+  let string = match params.get(0) {
+    None | Some(Val::Undefined) => "".to_string(),
+    Some(p) => p.to_string(),
+  };
+
+  Ok(
+    VsArray::from(
+      string
+        .bytes()
+        .map(|b| Val::Number(b as f64))
+        .collect::<Vec<Val>>(),
+    )
+    .to_val(),
+  )
+});
@@ -1,18 +1,20 @@
 use std::fmt;
-use std::{collections::BTreeMap, rc::Rc};
 
 use crate::native_function::{native_fn, ThisWrapper};
+use crate::property_map::PropertyMap;
 use crate::vs_value::ToVal;
 use crate::ValTrait;
 use crate::{
   native_function::NativeFunction,
   operations::op_submov,
+  rc::Rc,
   vs_class::VsClass,
   vs_object::VsObject,
   vs_value::{LoadFunctionResult, Val},
 };
 
 use super::builtin_object::BuiltinObject;
+use super::error_builtin::install_cause;
 
 pub struct TypeErrorBuiltin {}
 
@@ -27,13 +29,15 @@ impl BuiltinObject for TypeErrorBuiltin {
 
   fn bo_load_function() -> LoadFunctionResult {
     LoadFunctionResult::NativeFunction(|_: ThisWrapper, params: Vec<Val>| -> Result<Val, Val> {
-      Ok(
-        match params.get(0) {
-          Some(param) => param.clone().to_val_string(),
-          None => "".to_val(),
-        }
-        .to_type_error(),
-      )
+      let mut error = match params.get(0) {
+        Some(param) => param.clone().to_val_string(),
+        None => "".to_val(),
+      }
+      .to_type_error();
+
+      install_cause(&mut error, params.get(1))?;
+
+      Ok(error)
     })
   }
 
@@ -57,7 +61,7 @@ impl fmt::Display for TypeErrorBuiltin {
 // TODO: Static? (Rc -> Arc?)
 fn make_type_error_prototype() -> Val {
   VsObject {
-    string_map: BTreeMap::from([
+    string_map: PropertyMap::from([
       ("name".to_string(), "TypeError".to_val()),
       ("toString".to_string(), TYPE_ERROR_TO_STRING.to_val()),
     ]),
@@ -73,7 +77,9 @@ static SET_MESSAGE: NativeFunction = native_fn(|mut this, params| {
     None => "".to_string(),
   };
 
-  op_submov(this.get_mut()?, &"message".to_val(), message.to_val())?;
+  let this = this.get_mut()?;
+  op_submov(this, &"message".to_val(), message.to_val())?;
+  install_cause(this, params.get(1))?;
 
   Ok(Val::Undefined)
 });
@@ -102,7 +108,7 @@ impl ToTypeError for String {
 impl ToTypeError for Val {
   fn to_type_error(self) -> Val {
     VsObject {
-      string_map: BTreeMap::from([("message".to_string(), self)]),
+      string_map: PropertyMap::from([("message".to_string(), self)]),
       symbol_map: Default::default(),
       prototype: Some(make_type_error_prototype()),
     }
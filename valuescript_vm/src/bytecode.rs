@@ -1,10 +1,22 @@
-use std::{cell::RefCell, collections::HashMap, fmt, ops::Index, rc::Rc, slice::SliceIndex};
+use std::{
+  collections::{hash_map::DefaultHasher, HashMap},
+  fmt,
+  hash::{Hash, Hasher},
+  ops::Index,
+  slice::SliceIndex,
+};
 
-use crate::{bytecode_decoder::BytecodeDecoder, vs_value::Val};
+use crate::{
+  bytecode_decoder::BytecodeDecoder,
+  rc::{Rc, RefCell, Weak},
+  vs_value::{ToVal, Val},
+};
 
 pub struct Bytecode {
   pub code: Vec<u8>,
   pub cache: RefCell<HashMap<usize, Val>>,
+  pub constants: Vec<Val>,
+  pub start: usize,
 }
 
 impl<I: SliceIndex<[u8]>> Index<I> for Bytecode {
@@ -22,14 +34,159 @@ impl fmt::Debug for Bytecode {
 }
 
 impl Bytecode {
-  pub fn new(code: Vec<u8>) -> Bytecode {
+  /// Loads `code`, unwrapping it first if it's in a compressed container. Errors when the container
+  /// can't be decompressed.
+  pub fn new(code: Vec<u8>) -> Result<Bytecode, String> {
+    Ok(Bytecode::from_decompressed(Bytecode::decompress(code)?))
+  }
+
+  fn from_decompressed(code: Vec<u8>) -> Bytecode {
+    let (constants, start) = match code.first() {
+      Some(&CONSTANT_POOL) => {
+        let pool_pos = code[1] as usize + 256 * code[2] as usize;
+        (decode_constant_pool(&code, pool_pos), 3)
+      }
+      _ => (vec![], 0),
+    };
+
     Bytecode {
       code,
       cache: RefCell::new(HashMap::new()),
+      constants,
+      start,
+    }
+  }
+
+  /// Like `Rc::new(Bytecode::new(code))`, but reuses the existing instance if the same code is
+  /// already loaded. This means the decoded functions and classes are shared too, so repeatedly
+  /// constructing VMs over the same module doesn't decode it again. Instances are shared across
+  /// the whole process with the `sync` feature, and only within each thread otherwise (since they
+  /// can't be sent to other threads).
+  pub fn shared(code: Vec<u8>) -> Result<Rc<Bytecode>, String> {
+    let code = Bytecode::decompress(code)?;
+    let hash = content_hash(&code);
+
+    Ok(with_shared_bytecode(|shared| {
+      if let Some(bytecode) = shared.get(&hash).and_then(Weak::upgrade) {
+        if bytecode.code == code {
+          return bytecode;
+        }
+      }
+
+      shared.retain(|_, bytecode| bytecode.strong_count() > 0);
+
+      let bytecode = Rc::new(Bytecode::from_decompressed(code));
+      shared.insert(hash, Rc::downgrade(&bytecode));
+
+      bytecode
+    }))
+  }
+
+  /// Wraps the code in a compressed container, which is a marker byte followed by a deflate
+  /// stream. `Bytecode::new` unwraps these transparently.
+  #[cfg(feature = "compression")]
+  pub fn compress(code: &[u8]) -> Vec<u8> {
+    let mut res = vec![COMPRESSED];
+    res.extend(miniz_oxide::deflate::compress_to_vec(code, 9));
+
+    res
+  }
+
+  /// Unwraps the code if it's in a compressed container, otherwise returns it unchanged. Errors
+  /// when the deflate stream is malformed, or when the compression feature isn't enabled.
+  pub fn decompress(code: Vec<u8>) -> Result<Vec<u8>, String> {
+    if code.first() != Some(&COMPRESSED) {
+      return Ok(code);
+    }
+
+    #[cfg(feature = "compression")]
+    {
+      miniz_oxide::inflate::decompress_to_vec(&code[1..])
+        .map_err(|err| format!("Failed to decompress bytecode: {:?}", err))
+    }
+
+    #[cfg(not(feature = "compression"))]
+    Err("Compressed bytecode requires the compression feature".to_string())
+  }
+}
+
+const CONSTANT_POOL: u8 = 0x17;
+const COMPRESSED: u8 = 0x1a;
+
+/// Decodes the constants section, which is a count followed by that many strings and numbers.
+fn decode_constant_pool(code: &[u8], pos: usize) -> Vec<Val> {
+  let mut pos = pos;
+  let count = decode_varsize_uint(code, &mut pos);
+  let mut constants = Vec::with_capacity(count);
+
+  for _ in 0..count {
+    let type_ = code[pos];
+    pos += 1;
+
+    constants.push(match type_ {
+      0x07 => {
+        let mut buf = [0u8; 8];
+        buf.clone_from_slice(&code[pos..pos + 8]);
+        pos += 8;
+
+        Val::Number(f64::from_le_bytes(buf))
+      }
+      0x08 => {
+        let len = decode_varsize_uint(code, &mut pos);
+        let string = String::from_utf8_lossy(&code[pos..pos + len]);
+        pos += len;
+
+        (*string).to_val()
+      }
+      _ => panic!("Unexpected constant type {}", type_),
+    });
+  }
+
+  constants
+}
+
+fn decode_varsize_uint(code: &[u8], pos: &mut usize) -> usize {
+  let mut res = 0_usize;
+  let mut mul = 1_usize;
+
+  loop {
+    let byte = code[*pos];
+    *pos += 1;
+    res += mul * ((byte % 128) as usize);
+
+    if byte & 128 == 0 {
+      return res;
     }
+
+    mul *= 128;
   }
 }
 
+#[cfg(not(feature = "sync"))]
+thread_local! {
+  static SHARED_BYTECODE: RefCell<HashMap<u64, Weak<Bytecode>>> = RefCell::new(HashMap::new());
+}
+
+#[cfg(not(feature = "sync"))]
+fn with_shared_bytecode<T>(f: impl FnOnce(&mut HashMap<u64, Weak<Bytecode>>) -> T) -> T {
+  SHARED_BYTECODE.with(|shared| f(&mut shared.borrow_mut()))
+}
+
+#[cfg(feature = "sync")]
+static SHARED_BYTECODE: std::sync::LazyLock<RefCell<HashMap<u64, Weak<Bytecode>>>> =
+  std::sync::LazyLock::new(|| RefCell::new(HashMap::new()));
+
+#[cfg(feature = "sync")]
+fn with_shared_bytecode<T>(f: impl FnOnce(&mut HashMap<u64, Weak<Bytecode>>) -> T) -> T {
+  f(&mut SHARED_BYTECODE.borrow_mut())
+}
+
+fn content_hash(code: &[u8]) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  code.hash(&mut hasher);
+  hasher.finish()
+}
+
 pub trait DecoderMaker {
   fn decoder(&self, pos: usize) -> BytecodeDecoder;
 }
@@ -42,3 +199,72 @@ impl DecoderMaker for Rc<Bytecode> {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn uncompressed_is_unchanged() {
+    let code = vec![0x00, 0x01, 0x02];
+    assert_eq!(Bytecode::decompress(code.clone()), Ok(code.clone()));
+    assert_eq!(Bytecode::new(code.clone()).unwrap().code, code);
+  }
+
+  #[cfg(feature = "compression")]
+  #[test]
+  fn compression_round_trip() {
+    let code = (0..1000).map(|i| (i % 7) as u8).collect::<Vec<u8>>();
+    let compressed = Bytecode::compress(&code);
+
+    assert_eq!(compressed[0], COMPRESSED);
+    assert!(compressed.len() < code.len());
+    assert_eq!(Bytecode::decompress(compressed.clone()), Ok(code.clone()));
+    assert_eq!(Bytecode::new(compressed.clone()).unwrap().code, code);
+    assert_eq!(Bytecode::shared(compressed).unwrap().code, code);
+  }
+
+  #[cfg(feature = "compression")]
+  #[test]
+  fn corrupted_compressed_input() {
+    let code = (0..1000).map(|i| (i % 7) as u8).collect::<Vec<u8>>();
+    let mut truncated = Bytecode::compress(&code);
+    truncated.truncate(truncated.len() / 2);
+
+    assert!(Bytecode::decompress(truncated.clone()).is_err());
+    assert!(Bytecode::new(truncated).is_err());
+
+    // A deflate block with the reserved block type
+    assert!(Bytecode::decompress(vec![COMPRESSED, 0xff, 0xff]).is_err());
+    assert!(Bytecode::shared(vec![COMPRESSED]).is_err());
+  }
+
+  #[cfg(not(feature = "compression"))]
+  #[test]
+  fn compressed_input_without_feature() {
+    assert!(Bytecode::decompress(vec![COMPRESSED, 0x03, 0x00]).is_err());
+    assert!(Bytecode::new(vec![COMPRESSED, 0x03, 0x00]).is_err());
+  }
+
+  #[test]
+  fn shared_reuses_instances() {
+    let a = Bytecode::shared(vec![0x00, 0x04, 0x05]).unwrap();
+    let b = Bytecode::shared(vec![0x00, 0x04, 0x05]).unwrap();
+    let c = Bytecode::shared(vec![0x00, 0x04, 0x06]).unwrap();
+
+    assert!(Rc::ptr_eq(&a, &b));
+    assert!(!Rc::ptr_eq(&a, &c));
+  }
+
+  #[cfg(feature = "sync")]
+  #[test]
+  fn shared_across_threads() {
+    let code = vec![0x00, 0x07, 0x08];
+    let a = Bytecode::shared(code.clone()).unwrap();
+    let b = std::thread::spawn(move || Bytecode::shared(code).unwrap())
+      .join()
+      .unwrap();
+
+    assert!(Rc::ptr_eq(&a, &b));
+  }
+}
@@ -1,19 +1,23 @@
 use std::collections::BTreeMap;
 use std::mem::take;
-use std::rc::Rc;
 
 use num_bigint::BigInt;
 use num_bigint::Sign;
 use valuescript_common::InstructionByte;
 
+use crate::builtins::type_error_builtin::ToTypeError;
 use crate::builtins::BUILTIN_VALS;
 use crate::bytecode::Bytecode;
-use crate::vs_class::VsClass;
+use crate::property_map::PropertyMap;
+use crate::rc::Rc;
+use crate::vs_class::{name_prototype, VsClass};
 use crate::vs_function::VsFunction;
 use crate::vs_object::VsObject;
 use crate::vs_symbol::VsSymbol;
 use crate::vs_value::ToVal;
 use crate::vs_value::Val;
+use crate::vs_value::ValTrait;
+use crate::VirtualMachine;
 
 #[derive(Clone)]
 pub struct BytecodeDecoder {
@@ -22,6 +26,33 @@ pub struct BytecodeDecoder {
   pub pos: usize,
 }
 
+/// An operand that has been decoded without copying it out of its register.
+pub enum Operand {
+  Register(usize),
+  Val(Val),
+}
+
+impl Operand {
+  pub fn get<'a>(&'a self, registers: &'a [Val]) -> &'a Val {
+    match self {
+      Operand::Register(i) => match &registers[*i] {
+        Val::Void => &Val::Undefined,
+        val => val,
+      },
+      Operand::Val(val) => val,
+    }
+  }
+
+  /// Copies a register operand out of its register, so that it isn't affected by later writes to
+  /// the register.
+  pub fn detach(self, registers: &[Val]) -> Operand {
+    match self {
+      Operand::Register(_) => Operand::Val(self.get(registers).clone()),
+      Operand::Val(_) => self,
+    }
+  }
+}
+
 #[repr(u8)]
 #[derive(PartialEq, Debug)]
 pub enum BytecodeType {
@@ -42,10 +73,17 @@ pub enum BytecodeType {
   TakeRegister = 0x0f,
   Builtin = 0x10,
   Class = 0x11,
+  Lazy = 0x12,
   BigInt = 0x13,
   GeneratorFunction = 0x14,
-  // ExportStar = 0x15,
+  ExportStar = 0x15,
   // Meta = 0x16,
+  // ConstantPool = 0x17,
+  Constant = 0x18,
+  DerivedClass = 0x19,
+  // Compressed = 0x1a,
+  AsyncFunction = 0x1b,
+  AsyncGeneratorFunction = 0x1c,
   Unrecognized = 0xff,
 }
 
@@ -71,9 +109,15 @@ impl BytecodeType {
       0x0f => TakeRegister,
       0x10 => Builtin,
       0x11 => Class,
-
+      0x12 => Lazy,
       0x13 => BigInt,
       0x14 => GeneratorFunction,
+      0x15 => ExportStar,
+
+      0x18 => Constant,
+      0x19 => DerivedClass,
+      0x1b => AsyncFunction,
+      0x1c => AsyncGeneratorFunction,
 
       _ => Unrecognized,
     }
@@ -99,9 +143,28 @@ impl BytecodeDecoder {
     BytecodeType::from_byte(self.peek_byte())
   }
 
-  pub fn decode_val(&mut self, registers: &mut Vec<Val>) -> Val {
-    match self.decode_type() {
+  /// Like decode_val, but plain register reads (the most common operand) are left in place
+  /// instead of being cloned.
+  pub fn decode_operand(&mut self, registers: &mut Vec<Val>) -> Result<Operand, Val> {
+    if self.peek_byte() == BytecodeType::Register as u8 {
+      self.pos += 1;
+
+      if let Some(i) = self.decode_register_index() {
+        return Ok(Operand::Register(i));
+      }
+
+      return Ok(Operand::Val(Val::Undefined));
+    }
+
+    Ok(Operand::Val(self.decode_val(registers)?))
+  }
+
+  /// Decodes a value. This can throw because decoding a pointer to a lazy value (e.g. a class with
+  /// static initializers) evaluates it.
+  pub fn decode_val(&mut self, registers: &mut Vec<Val>) -> Result<Val, Val> {
+    Ok(match self.decode_type() {
       BytecodeType::End => panic!("Cannot decode end"),
+      BytecodeType::ExportStar => panic!("Cannot decode export star"),
       BytecodeType::Void => Val::Void,
       BytecodeType::Undefined => Val::Undefined,
       BytecodeType::Null => Val::Null,
@@ -110,14 +173,14 @@ impl BytecodeDecoder {
       BytecodeType::SignedByte => (self.decode_signed_byte() as f64).to_val(),
       BytecodeType::Number => self.decode_number().to_val(),
       BytecodeType::String => self.decode_string().to_val(),
-      BytecodeType::Array => self.decode_vec_val(registers).to_val(),
+      BytecodeType::Array => self.decode_vec_val(registers)?.to_val(),
       BytecodeType::Object => {
-        let mut string_map: BTreeMap<String, Val> = BTreeMap::new();
+        let mut string_map = PropertyMap::new();
         let mut symbol_map: BTreeMap<VsSymbol, Val> = BTreeMap::new();
 
         while self.peek_type() != BytecodeType::End {
-          let key = self.decode_val(registers);
-          let value = self.decode_val(registers);
+          let key = self.decode_val(registers)?;
+          let value = self.decode_val(registers)?;
 
           match key {
             Val::String(string) => string_map.insert(string.to_string(), value),
@@ -135,8 +198,8 @@ impl BytecodeDecoder {
         }
         .to_val()
       }
-      BytecodeType::Function => self.decode_function(false),
-      BytecodeType::Pointer => self.decode_pointer(registers),
+      BytecodeType::Function => self.decode_function(false, false),
+      BytecodeType::Pointer => self.decode_pointer(registers)?,
       BytecodeType::Register => match registers[self.decode_register_index().unwrap()].clone() {
         Val::Void => Val::Undefined,
         val => val,
@@ -149,31 +212,42 @@ impl BytecodeDecoder {
       BytecodeType::Class => {
         let meta = self.decode_meta();
 
+        let constructor = self.decode_val(registers)?;
+        let prototype = name_prototype(self.decode_val(registers)?, &meta.name);
+
         VsClass {
           name: meta.name,
           content_hash: meta.content_hash,
-          constructor: self.decode_val(registers),
-          prototype: self.decode_val(registers),
-          static_: self.decode_val(registers),
+          constructor,
+          prototype,
+          static_: self.decode_val(registers)?,
         }
         .to_val()
       }
+      BytecodeType::DerivedClass => self.decode_derived_class(registers)?,
+      BytecodeType::Lazy => self.decode_lazy()?,
       BytecodeType::BigInt => self.decode_bigint().to_val(),
-      BytecodeType::GeneratorFunction => self.decode_function(true),
+      BytecodeType::GeneratorFunction => self.decode_function(true, false),
+      BytecodeType::AsyncFunction => self.decode_function(false, true),
+      BytecodeType::AsyncGeneratorFunction => self.decode_function(true, true),
+      BytecodeType::Constant => {
+        let index = self.decode_varsize_uint();
+        self.bytecode.constants[index].clone()
+      }
       BytecodeType::Unrecognized => panic!("Unrecognized bytecode type at {}", self.pos - 1),
-    }
+    })
   }
 
-  pub fn decode_vec_val(&mut self, registers: &mut Vec<Val>) -> Vec<Val> {
+  pub fn decode_vec_val(&mut self, registers: &mut Vec<Val>) -> Result<Vec<Val>, Val> {
     let mut vals: Vec<Val> = Vec::new();
 
     while self.peek_type() != BytecodeType::End {
-      vals.push(self.decode_val(registers));
+      vals.push(self.decode_val(registers)?);
     }
 
     self.decode_type(); // End (TODO: assert)
 
-    vals
+    Ok(vals)
   }
 
   pub fn decode_signed_byte(&mut self) -> i8 {
@@ -256,7 +330,7 @@ impl BytecodeDecoder {
     }
   }
 
-  pub fn decode_pointer(&mut self, registers: &mut Vec<Val>) -> Val {
+  pub fn decode_pointer(&mut self, registers: &mut Vec<Val>) -> Result<Val, Val> {
     let from_pos = self.pos;
     let pos = self.decode_pos();
 
@@ -267,7 +341,11 @@ impl BytecodeDecoder {
       match type_ {
         BytecodeType::Function
         | BytecodeType::GeneratorFunction
+        | BytecodeType::AsyncFunction
+        | BytecodeType::AsyncGeneratorFunction
         | BytecodeType::Class
+        | BytecodeType::DerivedClass
+        | BytecodeType::Lazy
         | BytecodeType::Unrecognized => {}
         _ => {
           panic!("Invalid: {:?} pointer that points backwards", type_);
@@ -278,17 +356,19 @@ impl BytecodeDecoder {
     let cached_val = self.bytecode.cache.borrow().get(&pos).cloned();
 
     match cached_val {
-      Some(val) => val,
+      Some(val) => Ok(val),
       None => {
-        let val = self.clone_at(pos).decode_val(registers);
+        // Errors aren't cached, so a lazy value that throws is evaluated again the next time it's
+        // used (like a module that failed to initialize)
+        let val = self.clone_at(pos).decode_val(registers)?;
         self.bytecode.cache.borrow_mut().insert(pos, val.clone());
 
-        val
+        Ok(val)
       }
     }
   }
 
-  pub fn decode_function(&mut self, is_generator: bool) -> Val {
+  pub fn decode_function(&mut self, is_generator: bool, is_async: bool) -> Val {
     let meta_pos = if self.decode_byte() == 0 {
       None
     } else {
@@ -301,20 +381,88 @@ impl BytecodeDecoder {
 
     // TODO: Support >256
     let register_count = self.decode_byte() as usize;
-    let parameter_count = self.decode_byte() as usize;
+
+    // The high bit marks the last parameter as a rest parameter
+    let parameter_count_byte = self.decode_byte();
 
     VsFunction {
       bytecode: self.bytecode.clone(),
       meta_pos,
       is_generator,
+      is_async,
       register_count,
-      parameter_count,
+      parameter_count: (parameter_count_byte & 0x7f) as usize,
+      has_rest_param: parameter_count_byte & 0x80 != 0,
       start: self.pos,
       binds: Vec::new(),
     }
     .to_val()
   }
 
+  /// Evaluates a lazy value by running its body (like a function without parameters). Lazy values
+  /// are only referenced through pointers, so they are evaluated at most once per bytecode.
+  ///
+  /// If the body throws (e.g. a static initializer that throws), the exception is thrown from the
+  /// instruction that used the value.
+  pub fn decode_lazy(&mut self) -> Result<Val, Val> {
+    let register_count = self.decode_byte() as usize;
+
+    let body = VsFunction {
+      bytecode: self.bytecode.clone(),
+      meta_pos: None,
+      is_generator: false,
+      is_async: false,
+      register_count,
+      parameter_count: 0,
+      has_rest_param: false,
+      start: self.pos,
+      binds: Vec::new(),
+    }
+    .to_val();
+
+    // Lazy values may use `this` as a scratch register (e.g. to build static class fields)
+    VirtualMachine::call_nested_mut(&body, &mut Val::Undefined, vec![])
+  }
+
+  /// Decodes a class that extends another class. The prototype and static objects inherit from
+  /// those of the base class, and a derived class without its own constructor uses the base
+  /// constructor.
+  pub fn decode_derived_class(&mut self, registers: &mut Vec<Val>) -> Result<Val, Val> {
+    let meta = self.decode_meta();
+    let base_val = self.decode_val(registers)?;
+
+    let base = match base_val.as_class_data() {
+      Some(base) => base,
+      None => {
+        return Err(
+          format!("Class extends value {} is not a class", base_val.codify()).to_type_error(),
+        )
+      }
+    };
+
+    let constructor = match self.decode_val(registers)? {
+      Val::Void => base.constructor.clone(),
+      constructor => constructor,
+    };
+
+    let prototype = name_prototype(
+      inherit(self.decode_val(registers)?, &base.prototype)?,
+      &meta.name,
+    );
+    let static_ = inherit(self.decode_val(registers)?, &base.static_)?;
+
+    Ok(
+      VsClass {
+        name: meta.name,
+        content_hash: meta.content_hash,
+        constructor,
+        prototype,
+        static_,
+      }
+      .to_val(),
+    )
+  }
+
   pub fn decode_instruction(&mut self) -> InstructionByte {
     InstructionByte::from_byte(self.decode_byte())
   }
@@ -331,7 +479,22 @@ impl BytecodeDecoder {
     let name = self.decode_string();
 
     let content_hash = match self.decode_byte() {
-      0 | 1 => None, // Empty | Src
+      0 => None, // Empty
+      1 => {
+        // Src, which is only present in unlinked bytecode (content hashes are calculated when
+        // linking). The source hash and the pointers to the dependencies aren't used at runtime.
+        self.pos += 32;
+
+        for _ in 0..self.decode_varsize_uint() {
+          if self.decode_type() != BytecodeType::Pointer {
+            panic!("Expected pointer");
+          }
+
+          self.decode_pos();
+        }
+
+        None
+      }
       2 => {
         // Hash
         let mut res = [0u8; 32];
@@ -353,3 +516,16 @@ pub struct Meta {
   pub name: String,
   pub content_hash: Option<[u8; 32]>,
 }
+
+fn inherit(object: Val, base: &Val) -> Result<Val, Val> {
+  match object {
+    Val::Object(object) => Ok(
+      VsObject {
+        prototype: Some(base.clone()),
+        ..(*object).clone()
+      }
+      .to_val(),
+    ),
+    _ => Err("Expected object for class prototype/static".to_type_error()),
+  }
+}
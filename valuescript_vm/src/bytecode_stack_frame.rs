@@ -7,25 +7,34 @@ use crate::builtins::type_error_builtin::ToTypeError;
 use crate::bytecode_decoder::BytecodeDecoder;
 use crate::bytecode_decoder::BytecodeType;
 use crate::cat_stack_frame::CatStackFrame;
+use crate::method_dispatch::builtin_method;
+use crate::module_registry::import_module;
 use crate::native_function::ThisWrapper;
 use crate::operations;
+use crate::rc::Rc;
 use crate::stack_frame::FrameStepOk;
 use crate::stack_frame::FrameStepResult;
 use crate::stack_frame::{CallResult, StackFrame, StackFrameTrait};
 use crate::vs_object::VsObject;
 use crate::vs_value::ToVal;
-use crate::vs_value::{LoadFunctionResult, Val, ValTrait};
+use crate::vs_value::{number_to_index, LoadFunctionResult, Val, ValTrait};
 
 #[derive(Clone)]
 pub struct BytecodeStackFrame {
   pub decoder: BytecodeDecoder,
   pub registers: Vec<Val>,
+  pub register_count: usize,
   pub const_this: bool,
   pub param_start: usize,
   pub param_end: usize,
+
+  /// The register that collects the arguments after param_end, if there is a rest parameter.
+  pub rest_param: Option<usize>,
+
   pub this_target: Option<usize>,
   pub return_target: Option<usize>,
   pub catch_setting: Option<CatchSetting>,
+  pub new_target: Val,
 }
 
 #[derive(Clone)]
@@ -35,57 +44,139 @@ pub struct CatchSetting {
 }
 
 impl BytecodeStackFrame {
-  pub fn apply_unary_op(&mut self, op: fn(input: &Val) -> Val) {
-    let input = self.decoder.decode_val(&mut self.registers);
+  /// Drops the trailing unused registers while the frame is suspended (e.g. inside a generator),
+  /// so that copies of the suspended frame only clone the registers that are still in use. The
+  /// capacity is kept so that resuming doesn't need to reallocate.
+  pub fn suspend(&mut self) {
+    let used_len = match self.registers.iter().rposition(|r| !matches!(r, Val::Void)) {
+      Some(i) => i + 1,
+      None => 0,
+    };
+
+    self.registers.truncate(used_len);
+  }
+
+  fn resume(&mut self) {
+    if self.registers.len() < self.register_count {
+      self.registers.resize(self.register_count, Val::Void);
+    }
+  }
+
+  pub fn apply_unary_op(&mut self, op: fn(input: &Val) -> Val) -> Result<(), Val> {
+    let input = self.decoder.decode_operand(&mut self.registers)?;
 
     if let Some(register_index) = self.decoder.decode_register_index() {
-      self.registers[register_index] = op(&input);
+      self.registers[register_index] = op(input.get(&self.registers));
     }
+
+    Ok(())
+  }
+
+  pub fn apply_fallible_unary_op(
+    &mut self,
+    op: fn(input: &Val) -> Result<Val, Val>,
+  ) -> Result<(), Val> {
+    let input = self.decoder.decode_operand(&mut self.registers)?;
+
+    if let Some(register_index) = self.decoder.decode_register_index() {
+      self.registers[register_index] = op(input.get(&self.registers))?;
+    }
+
+    Ok(())
   }
 
   pub fn apply_binary_op(
     &mut self,
     op: fn(left: &Val, right: &Val) -> Result<Val, Val>,
   ) -> Result<(), Val> {
-    let left = self.decoder.decode_val(&mut self.registers);
-    let right = self.decoder.decode_val(&mut self.registers);
+    let mut left = self.decoder.decode_operand(&mut self.registers)?;
+
+    // Taking the right operand (eg `x * x` compiles to `op* %x %!x`) can empty the register that
+    // the left operand refers to
+    if self.decoder.peek_type() == BytecodeType::TakeRegister {
+      left = left.detach(&self.registers);
+    }
+
+    let right = self.decoder.decode_operand(&mut self.registers)?;
 
     if let Some(register_index) = self.decoder.decode_register_index() {
-      self.registers[register_index] = op(&left, &right)?;
+      self.registers[register_index] = op(left.get(&self.registers), right.get(&self.registers))?;
     }
 
     Ok(())
   }
 
-  pub fn transfer_parameters(&mut self, new_frame: &mut StackFrame) {
+  /// Fast path for indexing an array in a register with a number (the core of most loops). This
+  /// reads the element in place instead of copying the array reference and going through op_sub.
+  /// Returns None without consuming any operands if the fast path doesn't apply.
+  fn sub_array_index(&mut self) -> Option<Val> {
+    if self.decoder.peek_type() != BytecodeType::Register {
+      return None;
+    }
+
+    let start_pos = self.decoder.pos;
+    self.decoder.decode_type();
+    let array_index = self.decoder.decode_register_index()?;
+
+    let index = match (&self.registers[array_index], self.decoder.peek_type()) {
+      (Val::Array(_), BytecodeType::Register | BytecodeType::SignedByte | BytecodeType::Number) => {
+        // These operands can't throw
+        match self.decoder.decode_val(&mut self.registers) {
+          Ok(Val::Number(n)) => number_to_index(n),
+          _ => None,
+        }
+      }
+      _ => None,
+    };
+
+    let element = match (index, &self.registers[array_index]) {
+      (Some(index), Val::Array(array)) => array.elements.get(index),
+      _ => {
+        // Decoding those operands had no side effects, so we can just rewind
+        self.decoder.pos = start_pos;
+        return None;
+      }
+    };
+
+    Some(match element {
+      None | Some(Val::Void) => Val::Undefined,
+      Some(element) => element.clone(),
+    })
+  }
+
+  pub fn transfer_parameters(&mut self, new_frame: &mut StackFrame) -> Result<(), Val> {
     let bytecode_type = self.decoder.peek_type();
 
     if bytecode_type == BytecodeType::Array {
       self.decoder.decode_type();
 
       while self.decoder.peek_type() != BytecodeType::End {
-        let p = self.decoder.decode_val(&mut self.registers);
+        let p = self.decoder.decode_val(&mut self.registers)?;
         new_frame.write_param(p);
       }
 
       self.decoder.decode_type(); // End (TODO: assert)
 
-      return;
+      return Ok(());
     }
 
-    let params = self.decoder.decode_val(&mut self.registers);
+    // Params that aren't inline come from a Cat (spread), which the compiler follows with a take,
+    // so we can usually move the elements instead of cloning them
+    let params = self.decoder.decode_val(&mut self.registers)?;
 
     match params {
       Val::Array(array_data) => {
-        for param in &array_data.elements {
-          new_frame.write_param(param.clone())
+        for param in Rc::unwrap_or_clone(array_data).elements {
+          new_frame.write_param(param)
         }
       }
       _ => panic!("Unexpected non-array params"),
     }
+
+    Ok(())
   }
 
-  pub fn decode_parameters(&mut self) -> Vec<Val> {
+  pub fn decode_parameters(&mut self) -> Result<Vec<Val>, Val> {
     let mut res = Vec::<Val>::new();
 
     let bytecode_type = self.decoder.peek_type();
@@ -94,21 +185,40 @@ impl BytecodeStackFrame {
       self.decoder.decode_type();
 
       while self.decoder.peek_type() != BytecodeType::End {
-        res.push(self.decoder.decode_val(&mut self.registers));
+        res.push(self.decoder.decode_val(&mut self.registers)?);
       }
 
       self.decoder.decode_type(); // End (TODO: assert)
 
-      return res;
+      return Ok(res);
     }
 
-    let params = self.decoder.decode_val(&mut self.registers);
+    let params = self.decoder.decode_val(&mut self.registers)?;
 
     match params {
-      Val::Array(array_data) => array_data.elements.clone(),
+      Val::Array(array_data) => Ok(Rc::unwrap_or_clone(array_data).elements),
       _ => panic!("Unexpected non-array params"),
     }
   }
+
+  /// Decodes parameters that won't be used, without collecting them into an array.
+  pub fn skip_parameters(&mut self) -> Result<(), Val> {
+    if self.decoder.peek_type() == BytecodeType::Array {
+      self.decoder.decode_type();
+
+      while self.decoder.peek_type() != BytecodeType::End {
+        self.decoder.decode_val(&mut self.registers)?;
+      }
+
+      self.decoder.decode_type(); // End (TODO: assert)
+
+      return Ok(());
+    }
+
+    self.decoder.decode_val(&mut self.registers)?;
+
+    Ok(())
+  }
 }
 
 impl StackFrameTrait for BytecodeStackFrame {
@@ -118,16 +228,26 @@ impl StackFrameTrait for BytecodeStackFrame {
     Ok(())
   }
 
+  fn write_new_target(&mut self, new_target: Val) {
+    self.new_target = new_target;
+  }
+
   fn write_param(&mut self, param: Val) {
     if self.param_start < self.param_end {
       self.registers[self.param_start] = param;
       self.param_start += 1;
+    } else if let Some(rest_param) = self.rest_param {
+      if let Val::Array(array_data) = &mut self.registers[rest_param] {
+        Rc::make_mut(array_data).elements.push(param);
+      }
     }
   }
 
   fn step(&mut self) -> FrameStepResult {
     use InstructionByte::*;
 
+    self.resume();
+
     let instruction_byte = self.decoder.decode_instruction();
 
     match instruction_byte {
@@ -139,7 +259,7 @@ impl StackFrameTrait for BytecodeStackFrame {
       }
 
       Mov => {
-        let val = self.decoder.decode_val(&mut self.registers);
+        let val = self.decoder.decode_val(&mut self.registers)?;
 
         if let Some(register_index) = self.decoder.decode_register_index() {
           self.registers[register_index] = val;
@@ -181,7 +301,7 @@ impl StackFrameTrait for BytecodeStackFrame {
       OpAnd => self.apply_binary_op(operations::op_and)?,
       OpOr => self.apply_binary_op(operations::op_or)?,
 
-      OpNot => self.apply_unary_op(operations::op_not),
+      OpNot => self.apply_unary_op(operations::op_not)?,
 
       OpLess => self.apply_binary_op(operations::op_less)?,
       OpLessEq => self.apply_binary_op(operations::op_less_eq)?,
@@ -189,8 +309,8 @@ impl StackFrameTrait for BytecodeStackFrame {
       OpGreaterEq => self.apply_binary_op(operations::op_greater_eq)?,
       OpNullishCoalesce => self.apply_binary_op(operations::op_nullish_coalesce)?,
       OpOptionalChain => {
-        let mut left = self.decoder.decode_val(&mut self.registers);
-        let right = self.decoder.decode_val(&mut self.registers);
+        let mut left = self.decoder.decode_val(&mut self.registers)?;
+        let right = self.decoder.decode_val(&mut self.registers)?;
 
         if let Some(register_index) = self.decoder.decode_register_index() {
           self.registers[register_index] = operations::op_optional_chain(&mut left, &right)?;
@@ -199,27 +319,27 @@ impl StackFrameTrait for BytecodeStackFrame {
       OpBitAnd => self.apply_binary_op(operations::op_bit_and)?,
       OpBitOr => self.apply_binary_op(operations::op_bit_or)?,
 
-      OpBitNot => self.apply_unary_op(operations::op_bit_not),
+      OpBitNot => self.apply_unary_op(operations::op_bit_not)?,
 
       OpBitXor => self.apply_binary_op(operations::op_bit_xor)?,
       OpLeftShift => self.apply_binary_op(operations::op_left_shift)?,
       OpRightShift => self.apply_binary_op(operations::op_right_shift)?,
       OpRightShiftUnsigned => self.apply_binary_op(operations::op_right_shift_unsigned)?,
 
-      TypeOf => self.apply_unary_op(operations::op_typeof),
+      TypeOf => self.apply_unary_op(operations::op_typeof)?,
 
       InstanceOf => self.apply_binary_op(operations::op_instance_of)?,
       In => self.apply_binary_op(operations::op_in)?,
 
       Call => {
-        let fn_ = self.decoder.decode_val(&mut self.registers);
+        let fn_ = self.decoder.decode_val(&mut self.registers)?;
 
         match fn_.load_function() {
           LoadFunctionResult::NotAFunction => {
             return Err("fn_ is not a function".to_type_error());
           }
           LoadFunctionResult::StackFrame(mut new_frame) => {
-            self.transfer_parameters(&mut new_frame);
+            self.transfer_parameters(&mut new_frame)?;
 
             self.return_target = self.decoder.decode_register_index();
             self.this_target = None;
@@ -229,7 +349,7 @@ impl StackFrameTrait for BytecodeStackFrame {
           LoadFunctionResult::NativeFunction(native_fn) => {
             let res = native_fn(
               ThisWrapper::new(true, &mut Val::Undefined),
-              self.decode_parameters(),
+              self.decode_parameters()?,
             )?;
 
             if let Some(return_target) = self.decoder.decode_register_index() {
@@ -240,38 +360,72 @@ impl StackFrameTrait for BytecodeStackFrame {
       }
 
       Apply | ConstApply => {
-        let fn_ = self.decoder.decode_val(&mut self.registers);
+        let fn_ = self.decoder.decode_val(&mut self.registers)?;
+
+        // ConstApply's `this` can be any value, while Apply's is a register that the (possibly
+        // mutated) `this` is written back to
+        let mut const_this = Val::Undefined;
+
+        let this_target = match instruction_byte {
+          ConstApply => {
+            const_this = self.decoder.decode_val(&mut self.registers)?;
+            None
+          }
+          _ => self.decoder.decode_register_index(),
+        };
+
+        if let Some(this_target) = this_target {
+          if this_target >= self.registers.len() {
+            return Err("Register index out of range".to_internal_error());
+          }
+        }
+
+        // Like ThisSubCall, applying a function to `this` (eg `super.method()`) propagates the
+        // constness of `this`
+        let const_call =
+          instruction_byte == ConstApply || (this_target == Some(1) && self.const_this);
 
         match fn_.load_function() {
           LoadFunctionResult::NotAFunction => {
             return Err("fn_ is not a function".to_type_error());
           }
           LoadFunctionResult::StackFrame(mut new_frame) => {
-            let this_target = self.decoder.decode_register_index();
             self.this_target = this_target;
 
-            if this_target.is_some() {
-              new_frame.write_this(
-                instruction_byte == ConstApply,
-                self.registers[this_target.unwrap()].clone(),
-              )?;
+            match this_target {
+              Some(this_target) => {
+                new_frame.write_this(const_call, self.registers[this_target].clone())?
+              }
+              None if instruction_byte == ConstApply => new_frame.write_this(true, const_this)?,
+              None => {}
             }
 
-            self.transfer_parameters(&mut new_frame);
+            self.transfer_parameters(&mut new_frame)?;
 
             self.return_target = self.decoder.decode_register_index();
 
             return Ok(FrameStepOk::Push(new_frame));
           }
-          LoadFunctionResult::NativeFunction(_native_fn) => {
-            return Err("TODO: apply native functions".to_internal_error());
+          LoadFunctionResult::NativeFunction(native_fn) => {
+            let params = self.decode_parameters()?;
+
+            let this = match this_target {
+              Some(this_target) => &mut self.registers[this_target],
+              None => &mut const_this,
+            };
+
+            let res = native_fn(ThisWrapper::new(const_call, this), params)?;
+
+            if let Some(return_target) = self.decoder.decode_register_index() {
+              self.registers[return_target] = res;
+            };
           }
         }
       }
 
       Bind => {
-        let fn_val = self.decoder.decode_val(&mut self.registers);
-        let params = self.decoder.decode_val(&mut self.registers);
+        let fn_val = self.decoder.decode_val(&mut self.registers)?;
+        let params = self.decoder.decode_val(&mut self.registers)?;
         let register_index = self.decoder.decode_register_index();
 
         let params_array = match params.as_array_data() {
@@ -296,8 +450,16 @@ impl StackFrameTrait for BytecodeStackFrame {
       }
 
       Sub => {
-        let mut left = self.decoder.decode_val(&mut self.registers);
-        let right = self.decoder.decode_val(&mut self.registers);
+        if let Some(res) = self.sub_array_index() {
+          if let Some(register_index) = self.decoder.decode_register_index() {
+            self.registers[register_index] = res;
+          }
+
+          return Ok(FrameStepOk::Continue);
+        }
+
+        let mut left = self.decoder.decode_val(&mut self.registers)?;
+        let right = self.decoder.decode_val(&mut self.registers)?;
 
         if let Some(register_index) = self.decoder.decode_register_index() {
           self.registers[register_index] = operations::op_sub(&mut left, &right)?;
@@ -310,9 +472,9 @@ impl StackFrameTrait for BytecodeStackFrame {
         // theory, this should still be possible because we only need a mutable borrow to an
         // element, not the vec itself. vec.get_many_mut has been considered, but it's not yet
         // stable.
-        let subscript = self.decoder.decode_val(&mut self.registers);
+        let subscript = self.decoder.decode_val(&mut self.registers)?;
 
-        let value = self.decoder.decode_val(&mut self.registers);
+        let value = self.decoder.decode_val(&mut self.registers)?;
 
         let target_index = self.decoder.decode_register_index().unwrap();
 
@@ -322,16 +484,20 @@ impl StackFrameTrait for BytecodeStackFrame {
       ConstSubCall => {
         let const_call = true;
 
-        let mut obj = self.decoder.decode_val(&mut self.registers);
-        let subscript = self.decoder.decode_val(&mut self.registers);
-        let fn_ = obj.sub(&subscript)?;
+        let mut obj = self.decoder.decode_val(&mut self.registers)?;
+        let subscript = self.decoder.decode_val(&mut self.registers)?;
 
-        match fn_.load_function() {
+        let load_result = match builtin_method(&obj, &subscript) {
+          Some(method) => method.load_function(),
+          None => obj.sub(&subscript)?.load_function(),
+        };
+
+        match load_result {
           LoadFunctionResult::NotAFunction => {
             return Err("fn_ is not a function".to_type_error());
           }
           LoadFunctionResult::StackFrame(mut new_frame) => {
-            self.transfer_parameters(&mut new_frame);
+            self.transfer_parameters(&mut new_frame)?;
 
             new_frame.write_this(const_call, obj)?;
 
@@ -341,7 +507,7 @@ impl StackFrameTrait for BytecodeStackFrame {
             return Ok(FrameStepOk::Push(new_frame));
           }
           LoadFunctionResult::NativeFunction(native_fn) => {
-            let params = self.decode_parameters();
+            let params = self.decode_parameters()?;
 
             let res = native_fn(ThisWrapper::new(true, &mut obj), params)?;
 
@@ -357,15 +523,19 @@ impl StackFrameTrait for BytecodeStackFrame {
           || (instruction_byte == InstructionByte::ThisSubCall && self.const_this);
 
         let obj_i = self.decoder.decode_register_index().unwrap();
-        let subscript = self.decoder.decode_val(&mut self.registers);
-        let fn_ = self.registers[obj_i].sub(&subscript)?;
+        let subscript = self.decoder.decode_val(&mut self.registers)?;
 
-        match fn_.load_function() {
+        let load_result = match builtin_method(&self.registers[obj_i], &subscript) {
+          Some(method) => method.load_function(),
+          None => self.registers[obj_i].sub(&subscript)?.load_function(),
+        };
+
+        match load_result {
           LoadFunctionResult::NotAFunction => {
             return Err("fn_ is not a function".to_type_error());
           }
           LoadFunctionResult::StackFrame(mut new_frame) => {
-            self.transfer_parameters(&mut new_frame);
+            self.transfer_parameters(&mut new_frame)?;
 
             new_frame.write_this(const_call, take(&mut self.registers[obj_i]))?;
 
@@ -375,7 +545,7 @@ impl StackFrameTrait for BytecodeStackFrame {
             return Ok(FrameStepOk::Push(new_frame));
           }
           LoadFunctionResult::NativeFunction(native_fn) => {
-            let params = self.decode_parameters();
+            let params = self.decode_parameters()?;
 
             let res = native_fn(
               ThisWrapper::new(const_call, self.registers.get_mut(obj_i).unwrap()),
@@ -395,7 +565,7 @@ impl StackFrameTrait for BytecodeStackFrame {
       }
 
       JmpIf => {
-        let cond = self.decoder.decode_val(&mut self.registers);
+        let cond = self.decoder.decode_val(&mut self.registers)?;
         let dst = self.decoder.decode_pos();
 
         if cond.is_truthy() {
@@ -404,7 +574,7 @@ impl StackFrameTrait for BytecodeStackFrame {
       }
 
       JmpIfNot => {
-        let cond = self.decoder.decode_val(&mut self.registers);
+        let cond = self.decoder.decode_val(&mut self.registers)?;
         let dst = self.decoder.decode_pos();
 
         if !cond.is_truthy() {
@@ -412,13 +582,15 @@ impl StackFrameTrait for BytecodeStackFrame {
         }
       }
 
-      UnaryPlus => self.apply_unary_op(operations::op_unary_plus),
-      UnaryMinus => self.apply_unary_op(operations::op_unary_minus),
+      UnaryPlus => self.apply_fallible_unary_op(operations::op_unary_plus)?,
+      UnaryMinus => self.apply_unary_op(operations::op_unary_minus)?,
 
       New => {
         // TODO: new Array
 
-        let class = match self.decoder.decode_val(&mut self.registers).as_class_data() {
+        let class_val = self.decoder.decode_val(&mut self.registers)?;
+
+        let class = match class_val.as_class_data() {
           Some(class) => class,
           None => {
             return Err("value is not a constructor".to_type_error());
@@ -434,8 +606,7 @@ impl StackFrameTrait for BytecodeStackFrame {
 
         match class.constructor {
           Val::Void => {
-            // Ignore parameters
-            self.decoder.decode_val(&mut self.registers);
+            self.skip_parameters()?;
             let target_register = self.decoder.decode_register_index();
 
             match target_register {
@@ -448,8 +619,9 @@ impl StackFrameTrait for BytecodeStackFrame {
               return Err("fn_ is not a function".to_type_error());
             }
             LoadFunctionResult::StackFrame(mut new_frame) => {
-              self.transfer_parameters(&mut new_frame);
+              self.transfer_parameters(&mut new_frame)?;
               new_frame.write_this(false, instance)?;
+              new_frame.write_new_target(class_val);
 
               self.return_target = None;
               self.this_target = self.decoder.decode_register_index();
@@ -457,11 +629,16 @@ impl StackFrameTrait for BytecodeStackFrame {
               return Ok(FrameStepOk::Push(new_frame));
             }
             LoadFunctionResult::NativeFunction(native_fn) => {
-              native_fn(
+              let res = native_fn(
                 ThisWrapper::new(false, &mut instance),
-                self.decode_parameters(),
+                self.decode_parameters()?,
               )?;
 
+              // Native constructors can provide their own instance (eg Promise)
+              if !matches!(res, Val::Undefined) {
+                instance = res;
+              }
+
               if let Some(target) = self.decoder.decode_register_index() {
                 self.registers[target] = instance;
               };
@@ -494,12 +671,21 @@ impl StackFrameTrait for BytecodeStackFrame {
               _ => Err(error),
             }
           }
-          _ => Err(self.decoder.decode_val(&mut self.registers)),
+          _ => Err(self.decoder.decode_val(&mut self.registers)?),
         };
       }
 
       Import | ImportStar => {
-        return Err("TODO: Dynamic imports".to_internal_error());
+        let path = self.decoder.decode_val(&mut self.registers)?.to_string();
+        let val = import_module(
+          &self.decoder.bytecode,
+          &path,
+          instruction_byte == ImportStar,
+        )?;
+
+        if let Some(register_index) = self.decoder.decode_register_index() {
+          self.registers[register_index] = val;
+        }
       }
 
       SetCatch => {
@@ -572,13 +758,19 @@ impl StackFrameTrait for BytecodeStackFrame {
         let cat_frame = match self.decoder.peek_type() {
           BytecodeType::Array => {
             self.decoder.decode_type();
-            CatStackFrame::from_vec_val(self.decoder.decode_vec_val(&mut self.registers))
+            CatStackFrame::from_vec_val(self.decoder.decode_vec_val(&mut self.registers)?)
           }
-          _ => match self.decoder.decode_val(&mut self.registers) {
-            Val::Array(array) => CatStackFrame::from_vec_val(array.elements.clone()),
-            _ => {
+          // The segment list can also come from a register or pointer (eg in hand-written
+          // assembly), in which case it's usually taken, so the segments can be moved
+          _ => match self.decoder.decode_val(&mut self.registers)? {
+            Val::Array(array) => CatStackFrame::from_vec_val(Rc::unwrap_or_clone(array).elements),
+            segments => {
               return Err(
-                "TODO: cat instruction on non-array (usually type error)".to_internal_error(),
+                format!(
+                  "cat expects an array of segments, not a {}",
+                  segments.typeof_()
+                )
+                .to_type_error(),
               )
             }
           },
@@ -591,24 +783,65 @@ impl StackFrameTrait for BytecodeStackFrame {
       }
 
       Yield => {
-        let val = self.decoder.decode_val(&mut self.registers);
-        self.decoder.decode_register_index(); // TODO: Use this
+        let val = self.decoder.decode_val(&mut self.registers)?;
+
+        // The value passed to next() arrives through resume_with
+        self.return_target = self.decoder.decode_register_index();
+        self.this_target = None;
+
+        self.suspend();
 
         return Ok(FrameStepOk::Yield(val));
       }
 
       YieldStar => {
-        let val = self.decoder.decode_val(&mut self.registers);
-        self.decoder.decode_register_index(); // TODO: Use this
+        let val = self.decoder.decode_val(&mut self.registers)?;
+
+        // The result of the delegated iterator arrives through apply_call_result
+        self.return_target = self.decoder.decode_register_index();
+        self.this_target = None;
+
+        self.suspend();
 
         return Ok(FrameStepOk::YieldStar(val));
       }
+
+      Await => {
+        let val = self.decoder.decode_val(&mut self.registers)?;
+
+        // The settled value arrives through resume_with (or the reason is thrown in)
+        self.return_target = self.decoder.decode_register_index();
+        self.this_target = None;
+
+        self.suspend();
+
+        return Ok(FrameStepOk::Await(val));
+      }
+
+      NewTarget => {
+        if let Some(dst) = self.decoder.decode_register_index() {
+          self.registers[dst] = self.new_target.clone();
+        }
+      }
+
+      Delete => {
+        let target_index = self.decoder.decode_register_index().unwrap();
+        let subscript = self.decoder.decode_val(&mut self.registers)?;
+
+        let res = operations::op_delete(&mut self.registers[target_index], &subscript)?;
+
+        if let Some(dst) = self.decoder.decode_register_index() {
+          self.registers[dst] = res.to_val();
+        }
+      }
     };
 
     Ok(FrameStepOk::Continue)
   }
 
   fn apply_call_result(&mut self, call_result: CallResult) {
+    self.resume();
+
     match self.this_target {
       None => {}
       Some(tt) => {
@@ -628,7 +861,16 @@ impl StackFrameTrait for BytecodeStackFrame {
     panic!("Not appropriate for BytecodeStackFrame")
   }
 
+  fn resume_with(&mut self, value: Val) {
+    self.apply_call_result(CallResult {
+      return_: value,
+      this: Val::Undefined,
+    });
+  }
+
   fn catch_exception(&mut self, exception: &mut Val) {
+    self.resume();
+
     if let Some(catch_setting) = &self.catch_setting {
       let exception = take(exception);
 
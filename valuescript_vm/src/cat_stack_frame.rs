@@ -1,9 +1,10 @@
-use std::{mem::take, rc::Rc};
+use std::mem::take;
 
 use crate::{
   builtins::{internal_error_builtin::ToInternalError, type_error_builtin::ToTypeError},
   native_function::ThisWrapper,
   operations::op_sub,
+  rc::Rc,
   stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait},
   vs_symbol::VsSymbol,
   vs_value::{ToVal, Val},
@@ -63,10 +64,16 @@ impl CatStackFrame {
       return Ok(FrameStepOk::Continue);
     }
 
+    if arg.is_nullish() {
+      return Err(format!("{} is not iterable", arg).to_type_error());
+    }
+
     let make_iter = op_sub(&mut arg, &VsSymbol::ITERATOR.to_val())?;
 
     match make_iter.load_function() {
-      LoadFunctionResult::NotAFunction => Err("Non-iterable cat argument".to_type_error()),
+      LoadFunctionResult::NotAFunction => {
+        Err(format!("{} is not iterable", arg.typeof_()).to_type_error())
+      }
       LoadFunctionResult::NativeFunction(fn_) => {
         self.state = CatFrameState::Iterating(fn_(ThisWrapper::new(true, &mut arg), vec![])?);
         Ok(FrameStepOk::Continue)
@@ -1,5 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
-
+use crate::rc::{Rc, RefCell};
 use crate::vs_value::Val;
 
 #[derive(Debug)]
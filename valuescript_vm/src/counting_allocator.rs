@@ -0,0 +1,73 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A global allocator that keeps track of how many bytes are allocated, which is needed for the
+/// memory limit of VirtualMachineBuilder. Embedders that use a memory limit need to install it:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator(std::alloc::System);
+/// ```
+///
+/// The count is for the whole process, so the memory limit is only accurate when one VM is running
+/// at a time.
+pub struct CountingAllocator<A = System>(pub A);
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let ptr = self.0.alloc(layout);
+
+    if !ptr.is_null() {
+      track_alloc(layout.size());
+      ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    ptr
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    self.0.dealloc(ptr, layout);
+    ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+  }
+
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    let new_ptr = self.0.realloc(ptr, layout, new_size);
+
+    if !new_ptr.is_null() {
+      track_alloc(new_size);
+      ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    new_ptr
+  }
+}
+
+fn track_alloc(size: usize) {
+  let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+  PEAK_ALLOCATED.fetch_max(allocated, Ordering::Relaxed);
+}
+
+/// The number of bytes currently allocated through CountingAllocator (zero if it isn't installed).
+pub fn allocated_bytes() -> usize {
+  ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// The highest number of bytes allocated at once since the last call to
+/// `reset_peak_allocated_bytes`.
+pub fn peak_allocated_bytes() -> usize {
+  PEAK_ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// Resets the peak to the number of bytes currently allocated.
+pub fn reset_peak_allocated_bytes() {
+  PEAK_ALLOCATED.store(ALLOCATED.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+/// The total number of allocations made through CountingAllocator.
+pub fn allocation_count() -> usize {
+  ALLOCATIONS.load(Ordering::Relaxed)
+}
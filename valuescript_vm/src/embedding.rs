@@ -0,0 +1,181 @@
+use num_bigint::BigInt;
+
+use crate::{
+  builtins::{internal_error_builtin::ToInternalError, type_error_builtin::ToTypeError},
+  bytecode::{Bytecode, DecoderMaker},
+  bytecode_decoder::BytecodeType,
+  rc::Rc,
+  vs_object::VsObject,
+  vs_value::{Val, ValTrait},
+};
+
+/// Conversion of Rust values into the parameters of a call, so that exported functions can be
+/// called like `vm.call("fnName", (1.0, "abc"))`.
+pub trait ToParams {
+  fn to_params(self) -> Vec<Val>;
+}
+
+impl ToParams for Vec<Val> {
+  fn to_params(self) -> Vec<Val> {
+    self
+  }
+}
+
+macro_rules! impl_to_params_for_tuple {
+  ($($name:ident),*) => {
+    impl<$($name: Into<Val>),*> ToParams for ($($name,)*) {
+      #[allow(non_snake_case)]
+      fn to_params(self) -> Vec<Val> {
+        let ($($name,)*) = self;
+        vec![$($name.into()),*]
+      }
+    }
+  };
+}
+
+impl_to_params_for_tuple!();
+impl_to_params_for_tuple!(A);
+impl_to_params_for_tuple!(A, B);
+impl_to_params_for_tuple!(A, B, C);
+impl_to_params_for_tuple!(A, B, C, D);
+impl_to_params_for_tuple!(A, B, C, D, E);
+impl_to_params_for_tuple!(A, B, C, D, E, F);
+impl_to_params_for_tuple!(A, B, C, D, E, F, G);
+impl_to_params_for_tuple!(A, B, C, D, E, F, G, H);
+
+/// Extraction of a Rust value from the result of a call. Fails with a TypeError when the value
+/// has the wrong type (there are no implicit conversions).
+pub trait FromVal: Sized {
+  fn from_val(val: Val) -> Result<Self, Val>;
+}
+
+impl FromVal for Val {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    Ok(val)
+  }
+}
+
+impl FromVal for () {
+  fn from_val(_val: Val) -> Result<Self, Val> {
+    Ok(())
+  }
+}
+
+impl FromVal for f64 {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    match val {
+      Val::Number(number) => Ok(number),
+      _ => Err(unexpected_type("number", &val)),
+    }
+  }
+}
+
+impl FromVal for bool {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    match val {
+      Val::Bool(bool) => Ok(bool),
+      _ => Err(unexpected_type("bool", &val)),
+    }
+  }
+}
+
+impl FromVal for BigInt {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    match val {
+      Val::BigInt(bigint) => Ok(bigint),
+      _ => Err(unexpected_type("bigint", &val)),
+    }
+  }
+}
+
+impl FromVal for String {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    match val {
+      Val::String(string) => Ok(string.to_string()),
+      _ => Err(unexpected_type("string", &val)),
+    }
+  }
+}
+
+/// `undefined` and `null` become None.
+impl<T: FromVal> FromVal for Option<T> {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    match val {
+      Val::Undefined | Val::Null => Ok(None),
+      val => T::from_val(val).map(Some),
+    }
+  }
+}
+
+impl<T: FromVal> FromVal for Vec<T> {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    match val.as_array_data() {
+      Some(array) => array
+        .elements
+        .iter()
+        .map(|element| T::from_val(element.clone()))
+        .collect(),
+      None => Err(unexpected_type("array", &val)),
+    }
+  }
+}
+
+fn unexpected_type(expected: &str, val: &Val) -> Val {
+  format!("Expected {}, got {}", expected, val.typeof_()).to_type_error()
+}
+
+/// Reads the exports of bytecode: the default export (as `default`, if there is one) followed by
+/// the named exports.
+pub fn read_exports(bytecode: &Rc<Bytecode>) -> Result<Vec<(String, Val)>, Val> {
+  let mut exports = vec![];
+
+  match bytecode.decoder(bytecode.start).decode_val(&mut vec![])? {
+    Val::Void => {}
+    default => exports.push(("default".to_string(), default)),
+  };
+
+  for (key, value) in &read_export_star(bytecode)?.string_map {
+    exports.push((key.clone(), value.clone()));
+  }
+
+  Ok(exports)
+}
+
+/// Reads the named exports of bytecode. For bytecode that hasn't been linked, this includes the
+/// names from `export * from` modules, which need to be registered with the VM.
+pub fn read_export_star(bytecode: &Rc<Bytecode>) -> Result<VsObject, Val> {
+  let mut bd = bytecode.decoder(bytecode.start);
+  bd.decode_val(&mut vec![])?; // Default export
+
+  if bd.pos >= bytecode.code.len() || bd.decode_type() != BytecodeType::ExportStar {
+    return Err("Expected export star after default export".to_internal_error());
+  }
+
+  let include_count = bd.decode_varsize_uint();
+  let mut includes = vec![];
+
+  for _ in 0..include_count {
+    includes.push(bd.decode_val(&mut vec![])?);
+  }
+
+  let mut export_star = match bd.decode_val(&mut vec![])? {
+    Val::Object(object) => (*object).clone(),
+    _ => return Err("Expected object for named exports".to_internal_error()),
+  };
+
+  // Local exports take precedence, and `export *` never includes the default export
+  for include in includes {
+    let include = match include {
+      Val::Object(include) => include,
+      _ => return Err("Expected object for export star include".to_internal_error()),
+    };
+
+    for (key, value) in &include.string_map {
+      if key != "default" && !export_star.string_map.contains_key(key) {
+        export_star.string_map.insert(key.clone(), value.clone());
+      }
+    }
+  }
+
+  Ok(export_star)
+}
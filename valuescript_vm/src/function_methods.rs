@@ -0,0 +1,277 @@
+use std::fmt;
+use std::mem::take;
+
+use num_bigint::BigInt;
+
+use crate::builtins::type_error_builtin::ToTypeError;
+use crate::native_frame_function::NativeFrameFunction;
+use crate::native_function::{native_fn, NativeFunction, ThisWrapper};
+use crate::rc::Rc;
+use crate::stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait};
+use crate::vs_array::{read_element, VsArray};
+use crate::vs_class::VsClass;
+use crate::vs_value::{LoadFunctionResult, ToDynamicVal, Val, ValTrait, VsType};
+
+pub fn op_sub_function(subscript: &Val) -> Val {
+  match get_function_method(&subscript.to_string()) {
+    Some(method) => Val::Static(method),
+    None => Val::Undefined,
+  }
+}
+
+pub fn get_function_method(method: &str) -> Option<&'static dyn ValTrait> {
+  Some(match method {
+    "apply" => &APPLY,
+    "bind" => &BIND,
+    "call" => &CALL,
+    _ => return None,
+  })
+}
+
+static CALL: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(FunctionCallFrame::new(false)),
+};
+
+static APPLY: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(FunctionCallFrame::new(true)),
+};
+
+static BIND: NativeFunction = native_fn(|this, params| {
+  let fn_ = this.get().clone();
+
+  if let LoadFunctionResult::NotAFunction = fn_.load_function() {
+    return Err("Bind must be called on a function".to_type_error());
+  }
+
+  let mut params = params.into_iter();
+  let this_arg = params.next().unwrap_or_default();
+  let args: Vec<Val> = params.collect();
+
+  // Without a `this`, this is the same as the Bind instruction, which keeps the result a regular
+  // function
+  if let Val::Undefined = this_arg {
+    if let Some(bound_fn) = fn_.bind(args.clone()) {
+      return Ok(bound_fn);
+    }
+  }
+
+  Ok(
+    BoundFunction {
+      fn_,
+      this_arg,
+      args,
+    }
+    .to_dynamic_val(),
+  )
+});
+
+/// Calls the function it was called on (its `this`) with the `this` and arguments it was given,
+/// which is how `fn.call(thisArg, ...args)` and `fn.apply(thisArg, args)` work. Bound functions
+/// use it too, with the `this` and leading arguments already filled in.
+#[derive(Clone)]
+struct FunctionCallFrame {
+  spread_args: bool,
+  bound: bool,
+  this: Val,
+  fn_: Val,
+  this_arg: Val,
+  args: Vec<Val>,
+  param_i: usize,
+  error: Option<Val>,
+  return_: Option<Val>,
+}
+
+impl FunctionCallFrame {
+  fn new(spread_args: bool) -> FunctionCallFrame {
+    FunctionCallFrame {
+      spread_args,
+      bound: false,
+      this: Val::Undefined,
+      fn_: Val::Undefined,
+      this_arg: Val::Undefined,
+      args: vec![],
+      param_i: 0,
+      error: None,
+      return_: None,
+    }
+  }
+}
+
+impl StackFrameTrait for FunctionCallFrame {
+  fn write_this(&mut self, _const: bool, this: Val) -> Result<(), Val> {
+    // The `this` of a bound function can't be overridden, but it's still handed back to the caller
+    if !self.bound {
+      self.fn_ = this.clone();
+    }
+
+    self.this = this;
+
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    match (self.param_i, self.spread_args) {
+      (0, _) => self.this_arg = param,
+      (1, true) => match param {
+        Val::Array(array_data) => {
+          self.args = array_data
+            .elements
+            .iter()
+            .map(|el| read_element(el).clone())
+            .collect();
+        }
+        Val::Undefined | Val::Null => {}
+        _ => self.error = Some("apply arguments must be an array".to_type_error()),
+      },
+      (_, true) => {}
+      (_, false) => self.args.push(param),
+    }
+
+    self.param_i += 1;
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    if let Some(error) = self.error.take() {
+      return Err(error);
+    }
+
+    if let Some(return_) = self.return_.take() {
+      return Ok(FrameStepOk::Pop(CallResult {
+        return_,
+        this: take(&mut self.this),
+      }));
+    }
+
+    match self.fn_.load_function() {
+      LoadFunctionResult::NotAFunction => Err("fn_ is not a function".to_type_error()),
+      LoadFunctionResult::NativeFunction(native_fn) => {
+        let res = native_fn(
+          ThisWrapper::new(true, &mut self.this_arg),
+          take(&mut self.args),
+        )?;
+
+        Ok(FrameStepOk::Pop(CallResult {
+          return_: res,
+          this: take(&mut self.this),
+        }))
+      }
+      LoadFunctionResult::StackFrame(mut new_frame) => {
+        new_frame.write_this(true, self.this_arg.clone())?;
+
+        for arg in take(&mut self.args) {
+          new_frame.write_param(arg);
+        }
+
+        Ok(FrameStepOk::Push(new_frame))
+      }
+    }
+  }
+
+  fn apply_call_result(&mut self, call_result: CallResult) {
+    self.return_ = Some(call_result.return_);
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for FunctionCallFrame")
+  }
+
+  fn catch_exception(&mut self, _exception: &mut Val) {}
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
+
+/// The result of `fn.bind(thisArg, ...args)` when `thisArg` is provided. (Otherwise binding the
+/// arguments is enough, and a regular function is returned.)
+#[derive(Clone)]
+struct BoundFunction {
+  fn_: Val,
+  this_arg: Val,
+  args: Vec<Val>,
+}
+
+impl ValTrait for BoundFunction {
+  fn typeof_(&self) -> VsType {
+    VsType::Function
+  }
+
+  fn to_number(&self) -> f64 {
+    f64::NAN
+  }
+
+  fn to_index(&self) -> Option<usize> {
+    None
+  }
+
+  fn is_primitive(&self) -> bool {
+    false
+  }
+
+  fn is_truthy(&self) -> bool {
+    true
+  }
+
+  fn is_nullish(&self) -> bool {
+    false
+  }
+
+  fn bind(&self, params: Vec<Val>) -> Option<Val> {
+    let mut bound_fn = self.clone();
+    bound_fn.args.extend(params);
+
+    Some(bound_fn.to_dynamic_val())
+  }
+
+  fn as_bigint_data(&self) -> Option<BigInt> {
+    None
+  }
+
+  fn as_array_data(&self) -> Option<Rc<VsArray>> {
+    None
+  }
+
+  fn as_class_data(&self) -> Option<Rc<VsClass>> {
+    None
+  }
+
+  fn load_function(&self) -> LoadFunctionResult {
+    LoadFunctionResult::StackFrame(Box::new(FunctionCallFrame {
+      spread_args: false,
+      bound: true,
+      this: Val::Undefined,
+      fn_: self.fn_.clone(),
+      this_arg: self.this_arg.clone(),
+      args: self.args.clone(),
+      param_i: 1,
+      error: None,
+      return_: None,
+    }))
+  }
+
+  fn sub(&self, key: &Val) -> Result<Val, Val> {
+    Ok(op_sub_function(key))
+  }
+
+  fn has(&self, _key: &Val) -> Option<bool> {
+    Some(false)
+  }
+
+  fn submov(&mut self, _key: &Val, _value: Val) -> Result<(), Val> {
+    Err("Cannot assign to subscript of bound function".to_type_error())
+  }
+
+  fn pretty_fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\x1b[36m[Function]\x1b[39m")
+  }
+
+  fn codify(&self) -> String {
+    "function() { [native code] }".into()
+  }
+}
+
+impl fmt::Display for BoundFunction {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function() {{ [native code] }}")
+  }
+}
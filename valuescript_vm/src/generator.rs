@@ -1,7 +1,6 @@
 use std::{
   fmt,
-  mem::{swap, take},
-  rc::Rc,
+  mem::{replace, swap, take},
 };
 
 use num_bigint::BigInt;
@@ -11,6 +10,7 @@ use crate::{
   iteration::{iteration_result::IterationResult, return_this::RETURN_THIS},
   native_frame_function::NativeFrameFunction,
   native_function::ThisWrapper,
+  rc::Rc,
   stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait},
   vs_array::VsArray,
   vs_class::VsClass,
@@ -23,6 +23,7 @@ use crate::{
 pub struct Generator {
   frame: StackFrame,
   stack: Vec<StackFrame>,
+  done: bool,
 }
 
 impl Generator {
@@ -30,6 +31,90 @@ impl Generator {
     Generator {
       frame,
       stack: vec![],
+      done: false,
+    }
+  }
+
+  /// A generator that has completed.
+  pub fn finished() -> Generator {
+    Generator {
+      done: true,
+      ..Default::default()
+    }
+  }
+
+  pub fn is_done(&self) -> bool {
+    self.done
+  }
+
+  /// Passes the value of the suspended yield (or await) to the body.
+  pub fn resume_with(&mut self, value: Val) {
+    self.frame.resume_with(value);
+  }
+
+  /// Steps the body, including the frames of yield* delegation. Pop means the body itself has
+  /// completed, and Yield/Await mean it has suspended.
+  pub fn step(&mut self) -> FrameStepResult {
+    let fsr = self.frame.step();
+
+    match fsr {
+      Ok(FrameStepOk::Pop(call_result)) => match self.stack.pop() {
+        Some(mut frame) => {
+          frame.apply_call_result(call_result);
+          swap(&mut frame, &mut self.frame);
+
+          Ok(FrameStepOk::Continue)
+        }
+        // TODO: Assert call_result.this is undefined?
+        None => Ok(FrameStepOk::Pop(call_result)),
+      },
+      Ok(FrameStepOk::YieldStar(iterable)) => {
+        let make_iter = iterable.sub(&VsSymbol::ITERATOR.to_val())?;
+
+        let mut frame = 'f: {
+          if let Val::Function(make_iter) = &make_iter {
+            if make_iter.is_generator && !make_iter.is_async {
+              let mut frame: StackFrame = Box::new(make_iter.make_bytecode_frame());
+              frame.write_this(true, iterable)?;
+
+              break 'f frame;
+            }
+          }
+
+          Box::new(YieldStarFrame {
+            iter: YieldStarIter::MakeIterator(iterable, make_iter),
+            iter_result: None,
+            resume_value: None,
+          })
+        };
+
+        swap(&mut frame, &mut self.frame);
+        self.stack.push(frame);
+
+        Ok(FrameStepOk::Continue)
+      }
+      _ => fsr,
+    }
+  }
+
+  pub fn apply_call_result(&mut self, call_result: CallResult) {
+    self.frame.apply_call_result(call_result)
+  }
+
+  /// Gives the body (and the frames of yield* delegation within it) the chance to catch
+  /// `exception`. If it's still there afterwards, it has made it out of the body.
+  pub fn catch_exception(&mut self, exception: &mut Val) {
+    loop {
+      self.frame.catch_exception(exception);
+
+      if let Val::Void = exception {
+        return;
+      }
+
+      match self.stack.pop() {
+        Some(frame) => self.frame = frame,
+        None => break,
+      }
     }
   }
 }
@@ -81,8 +166,11 @@ impl ValTrait for Generator {
 
   fn sub(&self, key: &Val) -> Result<Val, Val> {
     // TODO: Add symbol for next for performance? (Still needs this fallback)
-    if key.to_string() == "next" {
-      return Ok(NEXT.to_val());
+    match key.to_string().as_str() {
+      "next" => return Ok(NEXT.to_val()),
+      "return" => return Ok(RETURN.to_val()),
+      "throw" => return Ok(THROW.to_val()),
+      _ => {}
     }
 
     if let Val::Symbol(VsSymbol::ITERATOR) = key {
@@ -93,7 +181,7 @@ impl ValTrait for Generator {
   }
 
   fn has(&self, key: &Val) -> Option<bool> {
-    if key.to_string() == "next" {
+    if matches!(key.to_string().as_str(), "next" | "return" | "throw") {
       return Some(true);
     }
 
@@ -133,12 +221,75 @@ static NEXT: NativeFrameFunction = NativeFrameFunction {
   make_frame: || Box::<GeneratorFrame>::default(),
 };
 
+static RETURN: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(GeneratorFrame::resuming_with(Resumption::Return)),
+};
+
+static THROW: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(GeneratorFrame::resuming_with(Resumption::Throw)),
+};
+
+/// How the generator continues from the point where it is suspended.
+#[derive(Clone, Copy, Default)]
+enum Resumption {
+  /// Continues from the suspended yield, which evaluates to the parameter.
+  #[default]
+  Next,
+
+  /// Completes the generator as though the suspended yield was a return statement. This throws
+  /// VsSymbol::GENERATOR_RETURN into the generator, which runs the finally blocks (catch clauses in
+  /// generators rethrow it).
+  Return,
+
+  /// Throws the parameter into the generator at the suspended yield.
+  Throw,
+
+  /// The generator has been resumed and is running.
+  Running,
+}
+
+impl Resumption {
+  fn method_name(&self) -> &'static str {
+    match self {
+      Resumption::Next | Resumption::Running => "next",
+      Resumption::Return => "return",
+      Resumption::Throw => "throw",
+    }
+  }
+}
+
 #[derive(Clone, Default)]
 struct GeneratorFrame {
   generator: Generator,
+  resumption: Resumption,
+  param: Option<Val>,
+  return_value: Option<Val>,
+
+  /// Set when generator.return() made it out of the generator without being overridden.
+  returned: bool,
 }
 
-impl GeneratorFrame {}
+impl GeneratorFrame {
+  fn resuming_with(resumption: Resumption) -> GeneratorFrame {
+    GeneratorFrame {
+      resumption,
+      ..Default::default()
+    }
+  }
+
+  /// Completes the call with `value`, leaving the generator finished so that further calls to
+  /// next() produce `{ value: undefined, done: true }`.
+  fn done(&mut self, value: Val) -> FrameStepResult {
+    Ok(FrameStepOk::Pop(CallResult {
+      return_: IterationResult { value, done: true }.to_dynamic_val(),
+      this: Generator {
+        done: true,
+        ..Default::default()
+      }
+      .to_dynamic_val(),
+    }))
+  }
+}
 
 impl StackFrameTrait for GeneratorFrame {
   fn write_this(&mut self, const_: bool, this: Val) -> Result<(), Val> {
@@ -147,47 +298,54 @@ impl StackFrameTrait for GeneratorFrame {
       _ => return Err("TODO: indirection".to_internal_error()),
     };
 
+    let method_name = self.resumption.method_name();
+
     if const_ {
-      return Err("Cannot call Generator.next on a const generator".to_type_error());
+      return Err(
+        format!("Cannot call Generator.{} on a const generator", method_name).to_type_error(),
+      );
     }
 
     let generator = dynamic_make_mut(&mut dynamic)
       .as_any_mut()
       .downcast_mut::<Generator>()
-      .ok_or_else(|| "Generator.next called on different object".to_type_error())?;
+      .ok_or_else(|| {
+        format!("Generator.{} called on different object", method_name).to_type_error()
+      })?;
 
     self.generator = take(generator);
 
     Ok(())
   }
 
-  fn write_param(&mut self, _param: Val) {
-    panic!("TODO: results of yield expressions")
+  fn write_param(&mut self, param: Val) {
+    if self.param.is_none() {
+      self.param = Some(param);
+    }
   }
 
   fn step(&mut self) -> FrameStepResult {
-    let fsr = self.generator.frame.step();
+    match replace(&mut self.resumption, Resumption::Running) {
+      Resumption::Running => {}
+      Resumption::Next => {
+        let value = take(&mut self.param).unwrap_or(Val::Undefined);
+        self.generator.resume_with(value);
+      }
+      Resumption::Return => {
+        self.return_value = Some(take(&mut self.param).unwrap_or(Val::Undefined));
+        return Err(VsSymbol::GENERATOR_RETURN.to_val());
+      }
+      Resumption::Throw => return Err(take(&mut self.param).unwrap_or(Val::Undefined)),
+    }
 
-    match fsr {
-      Err(_) => fsr, // TODO: Stack unwind internal stack first
-      Ok(FrameStepOk::Continue) | Ok(FrameStepOk::Push(_)) => fsr,
-      Ok(FrameStepOk::Pop(call_result)) => match self.generator.stack.pop() {
-        Some(mut frame) => {
-          frame.apply_call_result(call_result);
-          swap(&mut frame, &mut self.generator.frame);
+    if self.returned || self.generator.done {
+      let value = take(&mut self.return_value).unwrap_or(Val::Undefined);
+      return self.done(value);
+    }
 
-          Ok(FrameStepOk::Continue)
-        }
-        None => Ok(FrameStepOk::Pop(CallResult {
-          return_: IterationResult {
-            value: call_result.return_, // TODO: Assert call_result.this is undefined?
-            done: true,
-          }
-          .to_dynamic_val(),
-          this: take(&mut self.generator).to_dynamic_val(),
-        })),
-      },
-      Ok(FrameStepOk::Yield(val)) => Ok(FrameStepOk::Pop(CallResult {
+    match self.generator.step()? {
+      FrameStepOk::Pop(call_result) => self.done(call_result.return_),
+      FrameStepOk::Yield(val) => Ok(FrameStepOk::Pop(CallResult {
         return_: IterationResult {
           value: val,
           done: false,
@@ -195,35 +353,13 @@ impl StackFrameTrait for GeneratorFrame {
         .to_dynamic_val(),
         this: take(&mut self.generator).to_dynamic_val(),
       })),
-      Ok(FrameStepOk::YieldStar(iterable)) => {
-        let make_iter = iterable.sub(&VsSymbol::ITERATOR.to_val())?;
-
-        let mut frame = 'f: {
-          if let Val::Function(make_iter) = &make_iter {
-            if make_iter.is_generator {
-              let mut frame: StackFrame = Box::new(make_iter.make_bytecode_frame());
-              frame.write_this(true, iterable)?;
-
-              break 'f frame;
-            }
-          }
-
-          Box::new(YieldStarFrame {
-            iter: YieldStarIter::MakeIterator(iterable, make_iter),
-            iter_result: None,
-          })
-        };
-
-        swap(&mut frame, &mut self.generator.frame);
-        self.generator.stack.push(frame);
-
-        Ok(FrameStepOk::Continue)
-      }
+      FrameStepOk::Await(_) => Err("Unexpected await in generator".to_internal_error()),
+      fso => Ok(fso),
     }
   }
 
   fn apply_call_result(&mut self, call_result: CallResult) {
-    self.generator.frame.apply_call_result(call_result)
+    self.generator.apply_call_result(call_result)
   }
 
   fn get_call_result(&mut self) -> CallResult {
@@ -231,7 +367,12 @@ impl StackFrameTrait for GeneratorFrame {
   }
 
   fn catch_exception(&mut self, exception: &mut Val) {
-    self.generator.frame.catch_exception(exception)
+    self.generator.catch_exception(exception);
+
+    if let Val::Symbol(VsSymbol::GENERATOR_RETURN) = exception {
+      *exception = Val::Void;
+      self.returned = true;
+    }
   }
 
   fn clone_to_stack_frame(&self) -> StackFrame {
@@ -243,6 +384,9 @@ impl StackFrameTrait for GeneratorFrame {
 struct YieldStarFrame {
   iter: YieldStarIter,
   iter_result: Option<Val>,
+
+  /// The value passed to the generator's next(), which is forwarded to the iterator's next().
+  resume_value: Option<Val>,
 }
 
 #[derive(Clone)]
@@ -301,7 +445,8 @@ impl StackFrameTrait for YieldStarFrame {
             Err("iterator.next is not a function".to_type_error())
           }
           LoadFunctionResult::NativeFunction(native_fn) => {
-            let iter_result = native_fn(ThisWrapper::new(false, iterator), vec![])?;
+            let params = take(&mut self.resume_value).into_iter().collect();
+            let iter_result = native_fn(ThisWrapper::new(false, iterator), params)?;
             let value = iter_result.sub(&"value".to_val())?;
 
             match iter_result.sub(&"done".to_val())?.is_truthy() {
@@ -315,6 +460,10 @@ impl StackFrameTrait for YieldStarFrame {
           LoadFunctionResult::StackFrame(mut frame) => {
             frame.write_this(false, take(iterator))?;
 
+            if let Some(resume_value) = take(&mut self.resume_value) {
+              frame.write_param(resume_value);
+            }
+
             Ok(FrameStepOk::Push(frame))
           }
         }
@@ -348,4 +497,8 @@ impl StackFrameTrait for YieldStarFrame {
   fn clone_to_stack_frame(&self) -> StackFrame {
     Box::new(self.clone())
   }
+
+  fn resume_with(&mut self, value: Val) {
+    self.resume_value = Some(value);
+  }
 }
@@ -1,12 +1,9 @@
 use crate::vs_value::{Val, ValTrait};
 
+/// Converts an index that counts back from the end when negative (like the argument of `.at()`).
+/// A missing index is treated like undefined, which is 0.
 pub fn to_wrapping_index(index: Option<&Val>, len: usize) -> Option<usize> {
-  let unchecked = match index {
-    None => {
-      return None;
-    }
-    Some(i) => to_unchecked_wrapping_index(i, len),
-  };
+  let unchecked = to_unchecked_wrapping_index(index.unwrap_or(&Val::Undefined), len);
 
   if unchecked < 0 || unchecked as usize >= len {
     return None;
@@ -0,0 +1,265 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::async_generator::AsyncGeneratorTable;
+use crate::module_registry::ModuleRegistry;
+use crate::number_methods::format_en_us;
+use crate::promise::PromiseTable;
+use crate::rc::MaybeSync;
+use crate::scheduler::Scheduler;
+use crate::vs_value::Val;
+
+/// Source of randomness for `Math.random`.
+pub trait RandomSource: MaybeSync {
+  /// Returns a number in the range [0, 1).
+  fn next_f64(&mut self) -> f64;
+}
+
+/// Deterministic PRNG (splitmix64). This is the default random source, so that program runs are
+/// reproducible unless the host opts out.
+#[derive(Clone)]
+pub struct SeededRandom {
+  state: u64,
+}
+
+impl SeededRandom {
+  pub fn new(seed: u64) -> SeededRandom {
+    SeededRandom { state: seed }
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    z ^ (z >> 31)
+  }
+}
+
+impl RandomSource for SeededRandom {
+  fn next_f64(&mut self) -> f64 {
+    // Use the top 53 bits so that every output is exactly representable
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+}
+
+/// Source of randomness for the crypto builtin. Hosts must opt in to this, since it makes program
+/// runs non-reproducible.
+pub trait EntropySource: MaybeSync {
+  fn fill_bytes(&mut self, dest: &mut [u8]);
+}
+
+impl EntropySource for SeededRandom {
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+      let bytes = self.next_u64().to_le_bytes();
+      chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+  }
+}
+
+/// Source of time for `Date.now` and `performance.now`.
+pub trait Clock: MaybeSync {
+  /// Milliseconds since the unix epoch.
+  fn now(&mut self) -> f64;
+
+  /// Milliseconds since an arbitrary origin, which must never decrease.
+  fn performance_now(&mut self) -> f64;
+}
+
+/// A clock that always reports the same time. This is the default clock, so that program runs are
+/// reproducible unless the host opts out.
+#[derive(Clone, Default)]
+pub struct FrozenClock {
+  pub time: f64,
+}
+
+impl Clock for FrozenClock {
+  fn now(&mut self) -> f64 {
+    self.time
+  }
+
+  fn performance_now(&mut self) -> f64 {
+    self.time
+  }
+}
+
+/// A deterministic clock that advances by one millisecond every time it is read.
+#[derive(Clone, Default)]
+pub struct LogicalClock {
+  pub time: f64,
+}
+
+impl Clock for LogicalClock {
+  fn now(&mut self) -> f64 {
+    let time = self.time;
+    self.time += 1.0;
+
+    time
+  }
+
+  fn performance_now(&mut self) -> f64 {
+    self.now()
+  }
+}
+
+/// Wall-clock time for `Date.now`, and monotonic time since the clock was created for
+/// `performance.now`.
+#[derive(Clone)]
+pub struct SystemClock {
+  origin: Instant,
+}
+
+impl SystemClock {
+  pub fn new() -> SystemClock {
+    SystemClock {
+      origin: Instant::now(),
+    }
+  }
+}
+
+impl Default for SystemClock {
+  fn default() -> Self {
+    SystemClock::new()
+  }
+}
+
+impl Clock for SystemClock {
+  fn now(&mut self) -> f64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+      Ok(duration) => duration.as_millis() as f64,
+      Err(e) => -(e.duration().as_millis() as f64),
+    }
+  }
+
+  fn performance_now(&mut self) -> f64 {
+    self.origin.elapsed().as_secs_f64() * 1000.0
+  }
+}
+
+/// Destination for `console.log`.
+pub trait Console: MaybeSync {
+  /// Receives each logged line (without a trailing newline).
+  fn log(&mut self, line: &str);
+}
+
+/// Logs to stdout. This is the default console.
+#[derive(Clone, Default)]
+pub struct StdoutConsole;
+
+impl Console for StdoutConsole {
+  fn log(&mut self, line: &str) {
+    println!("{}", line);
+  }
+}
+
+/// The options of `Number.prototype.toLocaleString`, with defaults applied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NumberFormat {
+  pub min_fraction_digits: usize,
+  pub max_fraction_digits: usize,
+  pub use_grouping: bool,
+}
+
+/// Locale-sensitive formatting for `toLocaleString`.
+pub trait Locale: MaybeSync {
+  /// Formats `number`. `locales` are the locales requested by the script (the first argument of
+  /// `toLocaleString`), and are empty if it didn't request any.
+  fn format_number(&mut self, locales: &[String], number: f64, format: &NumberFormat) -> String;
+}
+
+/// Formats as en-US, regardless of the requested locales. This is the default locale, so that
+/// program runs are reproducible unless the host opts out.
+#[derive(Clone, Default)]
+pub struct EnUsLocale;
+
+impl Locale for EnUsLocale {
+  fn format_number(&mut self, _locales: &[String], number: f64, format: &NumberFormat) -> String {
+    format_en_us(number, format)
+  }
+}
+
+/// How string lengths and indexes are measured.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum StringEncoding {
+  /// UTF-8 code units (bytes). This is ValueScript's native representation.
+  #[default]
+  Utf8,
+
+  /// UTF-16 code units, which matches JavaScript for `length`, indexing, `charCodeAt`, `slice`
+  /// etc. Strings are still stored as UTF-8, so unpaired surrogates become U+FFFD when they are
+  /// split off into their own string.
+  Utf16,
+}
+
+/// Capabilities and settings provided by the embedder. Builtins that need to reach outside the
+/// program (such as `Math.random`) go through the host of the currently running VirtualMachine.
+pub struct Host {
+  pub random: Box<dyn RandomSource>,
+  pub clock: Box<dyn Clock>,
+  pub entropy: Option<Box<dyn EntropySource>>,
+  pub string_encoding: StringEncoding,
+  pub console: Box<dyn Console>,
+  pub locale: Box<dyn Locale>,
+
+  /// Values provided by the embedder in addition to the standard builtins. Scripts access these
+  /// through `globalThis`.
+  pub extra_builtins: HashMap<String, Val>,
+
+  /// Modules that imports in unlinked bytecode are resolved against.
+  pub modules: ModuleRegistry,
+
+  pub scheduler: Scheduler,
+  pub promises: PromiseTable,
+  pub async_generators: AsyncGeneratorTable,
+
+  /// Start times of `Debug.time` calls that haven't been ended yet, by label.
+  pub debug_timers: HashMap<String, f64>,
+}
+
+impl Default for Host {
+  fn default() -> Self {
+    Host {
+      random: Box::new(SeededRandom::new(0)),
+      clock: Box::new(FrozenClock::default()),
+      entropy: None,
+      string_encoding: StringEncoding::Utf8,
+      console: Box::new(StdoutConsole),
+      locale: Box::new(EnUsLocale),
+      extra_builtins: HashMap::new(),
+      modules: ModuleRegistry::default(),
+      scheduler: Scheduler::default(),
+      promises: PromiseTable::default(),
+      async_generators: AsyncGeneratorTable::default(),
+      debug_timers: HashMap::new(),
+    }
+  }
+}
+
+thread_local! {
+  static CURRENT_HOST: RefCell<Host> = RefCell::new(Host::default());
+}
+
+/// Installs `host` as the current host while `f` runs.
+pub(crate) fn with_host<T>(host: &mut Host, f: impl FnOnce() -> T) -> T {
+  CURRENT_HOST.with(|current| std::mem::swap(&mut *current.borrow_mut(), host));
+  let res = f();
+  CURRENT_HOST.with(|current| std::mem::swap(&mut *current.borrow_mut(), host));
+
+  res
+}
+
+pub(crate) fn with_current_host<T>(f: impl FnOnce(&mut Host) -> T) -> T {
+  CURRENT_HOST.with(|current| f(&mut current.borrow_mut()))
+}
+
+/// Like with_current_host, but gives None if the current host is already in use.
+pub(crate) fn try_with_current_host<T>(f: impl FnOnce(&mut Host) -> T) -> Option<T> {
+  CURRENT_HOST.with(|current| {
+    let mut host = current.try_borrow_mut().ok()?;
+    Some(f(&mut host))
+  })
+}
@@ -1,11 +1,12 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use num_bigint::BigInt;
 
 use crate::{
   builtins::{internal_error_builtin::ToInternalError, type_error_builtin::ToTypeError},
   native_function::{native_fn, NativeFunction},
-  vs_array::VsArray,
+  rc::Rc,
+  vs_array::{read_element, VsArray},
   vs_class::VsClass,
   vs_symbol::VsSymbol,
   vs_value::{dynamic_make_mut, ToDynamicVal, ToVal, Val, VsType},
@@ -83,6 +84,13 @@ impl ValTrait for ArrayIterator {
         VsSymbol::ITERATOR => {
           return Ok(RETURN_THIS.to_val());
         }
+        VsSymbol::ASYNC_ITERATOR
+        | VsSymbol::TO_PRIMITIVE
+        | VsSymbol::HAS_INSTANCE
+        | VsSymbol::TO_STRING_TAG
+        | VsSymbol::GENERATOR_RETURN
+        | VsSymbol::CLASS_NAME
+        | VsSymbol::Registered(_) => {}
       }
     }
 
@@ -133,7 +141,7 @@ static NEXT: NativeFunction = native_fn(|mut this, _| {
 
       Ok(
         IterationResult {
-          value: item.clone(),
+          value: read_element(item).clone(),
           done: false,
         }
         .to_dynamic_val(),
@@ -1,9 +1,10 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use num_bigint::BigInt;
 
 use crate::{
   builtins::type_error_builtin::ToTypeError,
+  rc::Rc,
   vs_array::VsArray,
   vs_class::VsClass,
   vs_value::{ToVal, Val, VsType},
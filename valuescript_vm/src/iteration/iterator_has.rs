@@ -10,6 +10,13 @@ pub fn iterator_has(key: &Val) -> Option<bool> {
       VsSymbol::ITERATOR => {
         return Some(true);
       }
+      VsSymbol::ASYNC_ITERATOR
+      | VsSymbol::TO_PRIMITIVE
+      | VsSymbol::HAS_INSTANCE
+      | VsSymbol::TO_STRING_TAG
+      | VsSymbol::GENERATOR_RETURN
+      | VsSymbol::CLASS_NAME
+      | VsSymbol::Registered(_) => {}
     }
   }
 
@@ -1,12 +1,14 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use num_bigint::BigInt;
 
 use crate::{
   builtins::{internal_error_builtin::ToInternalError, type_error_builtin::ToTypeError},
   native_function::{native_fn, NativeFunction},
+  rc::Rc,
   vs_array::VsArray,
   vs_class::VsClass,
+  vs_string::VsString,
   vs_symbol::VsSymbol,
   vs_value::{dynamic_make_mut, ToDynamicVal, ToVal, Val, VsType},
   LoadFunctionResult, ValTrait,
@@ -18,12 +20,12 @@ use super::{
 
 #[derive(Clone)]
 pub struct StringIterator {
-  pub string: Rc<str>,
+  pub string: VsString,
   pub index: usize,
 }
 
 impl StringIterator {
-  pub fn new(string: Rc<str>) -> StringIterator {
+  pub fn new(string: VsString) -> StringIterator {
     StringIterator { string, index: 0 }
   }
 
@@ -119,6 +121,13 @@ impl ValTrait for StringIterator {
         VsSymbol::ITERATOR => {
           return Ok(RETURN_THIS.to_val());
         }
+        VsSymbol::ASYNC_ITERATOR
+        | VsSymbol::TO_PRIMITIVE
+        | VsSymbol::HAS_INSTANCE
+        | VsSymbol::TO_STRING_TAG
+        | VsSymbol::GENERATOR_RETURN
+        | VsSymbol::CLASS_NAME
+        | VsSymbol::Registered(_) => {}
       }
     }
 
@@ -1,5 +1,9 @@
+#[cfg(feature = "arena")]
+mod arena;
 mod array_higher_functions;
 mod array_methods;
+mod async_frame;
+mod async_generator;
 mod bigint_methods;
 mod builtins;
 mod bytecode;
@@ -7,28 +11,63 @@ mod bytecode_decoder;
 mod bytecode_stack_frame;
 mod cat_stack_frame;
 mod copy_counter;
+mod counting_allocator;
+mod embedding;
 mod first_stack_frame;
+mod function_methods;
 mod generator;
 mod helpers;
+mod host;
 mod iteration;
 mod make_generator_frame;
+mod method_dispatch;
+mod module_registry;
 mod native_frame_function;
 mod native_function;
 mod number_methods;
 pub mod operations;
+mod promise;
+pub mod property_map;
+pub mod rc;
+mod regex;
+mod run_budget;
+mod scheduler;
+mod size_limits;
 mod stack_frame;
 mod string_methods;
+mod string_regex_methods;
 mod todo_fn;
+mod utf16_string_methods;
+pub mod val_diff;
 mod virtual_machine;
+mod virtual_machine_builder;
+pub mod vs_accessor;
 pub mod vs_array;
 pub mod vs_class;
 mod vs_function;
 pub mod vs_object;
+#[cfg(feature = "serde")]
+pub mod vs_serde;
+mod vs_string;
 mod vs_symbol;
 pub mod vs_value;
 
+#[cfg(feature = "arena")]
+pub use arena::{release_arena, ArenaAllocator};
 pub use bytecode::Bytecode;
+pub use counting_allocator::{
+  allocated_bytes, allocation_count, peak_allocated_bytes, reset_peak_allocated_bytes,
+  CountingAllocator,
+};
+pub use embedding::{read_export_star, read_exports, FromVal, ToParams};
+pub use host::{
+  Clock, Console, EnUsLocale, EntropySource, FrozenClock, Host, Locale, LogicalClock, NumberFormat,
+  RandomSource, SeededRandom, StdoutConsole, StringEncoding, SystemClock,
+};
+pub use scheduler::{Scheduler, Task};
 pub use string_methods::unicode_at;
 pub use virtual_machine::VirtualMachine;
+pub use virtual_machine_builder::{Limits, VirtualMachineBuilder};
+pub use vs_string::VsString;
 pub use vs_symbol::VsSymbol;
 pub use vs_value::{LoadFunctionResult, ValTrait};
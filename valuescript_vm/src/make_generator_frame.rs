@@ -1,6 +1,7 @@
 use std::mem::take;
 
 use crate::{
+  async_generator::VsAsyncGenerator,
   bytecode_stack_frame::BytecodeStackFrame,
   generator::Generator,
   stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait},
@@ -10,11 +11,22 @@ use crate::{
 #[derive(Clone)]
 pub struct MakeGeneratorFrame {
   pub frame: Option<BytecodeStackFrame>,
+  pub is_async: bool,
 }
 
 impl MakeGeneratorFrame {
   pub fn new(frame: BytecodeStackFrame) -> MakeGeneratorFrame {
-    MakeGeneratorFrame { frame: Some(frame) }
+    MakeGeneratorFrame {
+      frame: Some(frame),
+      is_async: false,
+    }
+  }
+
+  pub fn new_async(frame: BytecodeStackFrame) -> MakeGeneratorFrame {
+    MakeGeneratorFrame {
+      frame: Some(frame),
+      is_async: true,
+    }
   }
 
   fn frame_mut(&mut self) -> &mut BytecodeStackFrame {
@@ -36,8 +48,16 @@ impl StackFrameTrait for MakeGeneratorFrame {
   }
 
   fn step(&mut self) -> FrameStepResult {
+    let mut frame = self.take_frame();
+    frame.suspend();
+
+    let generator = Generator::new(Box::new(frame));
+
     Ok(FrameStepOk::Pop(CallResult {
-      return_: Generator::new(Box::new(self.take_frame())).to_dynamic_val(),
+      return_: match self.is_async {
+        false => generator.to_dynamic_val(),
+        true => VsAsyncGenerator::new(generator).to_dynamic_val(),
+      },
       this: Val::Undefined,
     }))
   }
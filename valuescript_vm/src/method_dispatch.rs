@@ -0,0 +1,27 @@
+use crate::{
+  array_methods::get_array_method,
+  number_methods::get_number_method,
+  string_methods::get_string_method,
+  vs_value::{Val, ValTrait},
+};
+
+/// Finds the builtin method being called by `obj[subscript](...)` when `obj` is an array, string
+/// or number, so that the call can skip `op_sub`, which converts the subscript to a string and
+/// wraps the method in a new value.
+///
+/// Returns None if the fast path doesn't apply, in which case the caller should fall back to the
+/// general path.
+pub fn builtin_method(obj: &Val, subscript: &Val) -> Option<&'static dyn ValTrait> {
+  let method = match subscript {
+    Val::String(method) => method,
+    _ => return None,
+  };
+
+  // Method names are matched directly on the receiver's type, which is cheaper than hashing them
+  match obj {
+    Val::Array(_) => get_array_method(method),
+    Val::String(_) => get_string_method(method),
+    Val::Number(_) => get_number_method(method),
+    _ => None,
+  }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+  builtins::error_builtin::ToError,
+  bytecode::{Bytecode, DecoderMaker},
+  embedding::read_export_star,
+  host::with_current_host,
+  rc::Rc,
+  vs_value::{ToVal, Val},
+};
+
+/// Independently compiled modules, keyed by module id (usually the path of the source file).
+///
+/// Bytecode that hasn't been linked still contains its imports, which are resolved against these
+/// modules when they are first used. This allows a changed module to be shipped on its own instead
+/// of recompiling the whole program.
+#[derive(Default)]
+pub struct ModuleRegistry {
+  modules: HashMap<String, Rc<Bytecode>>,
+}
+
+impl ModuleRegistry {
+  /// Registers `bytecode` as the module `id`, returning the bytecode it replaces.
+  pub fn register(&mut self, id: &str, bytecode: Rc<Bytecode>) -> Option<Rc<Bytecode>> {
+    self.modules.insert(normalize(Path::new(id)), bytecode)
+  }
+
+  /// Discards the values decoded from the registered modules, including their resolved imports.
+  pub fn clear_caches(&self) {
+    for bytecode in self.modules.values() {
+      bytecode.cache.borrow_mut().clear();
+    }
+  }
+
+  pub fn get(&self, id: &str) -> Option<Rc<Bytecode>> {
+    self.modules.get(id).cloned()
+  }
+
+  /// The id that `bytecode` was registered with, if any.
+  pub fn id_of(&self, bytecode: &Rc<Bytecode>) -> Option<String> {
+    self
+      .modules
+      .iter()
+      .find(|(_, registered)| Rc::ptr_eq(registered, bytecode))
+      .map(|(id, _)| id.clone())
+  }
+}
+
+/// Resolves `path` relative to the module `importer_id`, the same way the compiler does when
+/// linking.
+pub fn resolve_module_id(importer_id: &str, path: &str) -> String {
+  let parent = Path::new(importer_id)
+    .parent()
+    .unwrap_or_else(|| Path::new("/"));
+
+  normalize(&parent.join(path))
+}
+
+fn normalize(path: &Path) -> String {
+  let mut dir_stack = Vec::new();
+
+  for component in path.components() {
+    match component {
+      Component::ParentDir => {
+        dir_stack.pop();
+      }
+      Component::CurDir => {}
+      _ => dir_stack.push(component),
+    }
+  }
+
+  let mut path_buf = PathBuf::new();
+  path_buf.extend(dir_stack);
+
+  path_buf.to_string_lossy().into_owned()
+}
+
+/// Implements the `import` (default export) and `import*` (named exports) instructions of
+/// `importer`.
+pub fn import_module(importer: &Rc<Bytecode>, path: &str, star: bool) -> Result<Val, Val> {
+  let bytecode = with_current_host(|host| {
+    // Bytecode that isn't registered (such as the entry point) imports relative to the root
+    let importer_id = host
+      .modules
+      .id_of(importer)
+      .unwrap_or_else(|| "/".to_string());
+    let id = resolve_module_id(&importer_id, path);
+
+    host.modules.get(&id).ok_or(id)
+  });
+
+  let bytecode = match bytecode {
+    Ok(bytecode) => bytecode,
+    Err(id) => return Err(format!("Module not found: {}", id).to_error()),
+  };
+
+  if star {
+    return Ok(read_export_star(&bytecode)?.to_val());
+  }
+
+  let default = bytecode.decoder(bytecode.start).decode_val(&mut vec![])?;
+
+  Ok(match default {
+    Val::Void => Val::Undefined,
+    default => default,
+  })
+}
@@ -1,10 +1,10 @@
 use std::fmt;
-use std::rc::Rc;
 
 use num_bigint::BigInt;
 
-use crate::builtins::internal_error_builtin::ToInternalError;
 use crate::builtins::type_error_builtin::ToTypeError;
+use crate::function_methods::op_sub_function;
+use crate::rc::Rc;
 use crate::vs_array::VsArray;
 use crate::vs_class::VsClass;
 use crate::vs_value::{LoadFunctionResult, Val, ValTrait, VsType};
@@ -80,8 +80,8 @@ impl ValTrait for NativeFunction {
     LoadFunctionResult::NativeFunction(self.fn_)
   }
 
-  fn sub(&self, _key: &Val) -> Result<Val, Val> {
-    Err("TODO: Subscript native function".to_internal_error())
+  fn sub(&self, key: &Val) -> Result<Val, Val> {
+    Ok(op_sub_function(key))
   }
 
   fn has(&self, _key: &Val) -> Option<bool> {
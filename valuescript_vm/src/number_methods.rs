@@ -1,5 +1,6 @@
 use crate::builtins::internal_error_builtin::ToInternalError;
 use crate::builtins::range_error_builtin::ToRangeError;
+use crate::host::{with_current_host, NumberFormat};
 use crate::native_function::native_fn;
 use crate::vs_value::ToVal;
 use crate::{
@@ -9,16 +10,22 @@ use crate::{
 };
 
 pub fn op_sub_number(_number: f64, subscript: &Val) -> Val {
-  match subscript.to_string().as_str() {
+  match get_number_method(&subscript.to_string()) {
+    Some(method) => Val::Static(method),
+    None => Val::Undefined,
+  }
+}
+
+pub fn get_number_method(method: &str) -> Option<&'static dyn ValTrait> {
+  Some(match method {
     "toExponential" => &TO_EXPONENTIAL,
     "toFixed" => &TO_FIXED,
-    "toLocaleString" => &TODO_LOCALE,
+    "toLocaleString" => &TO_LOCALE_STRING,
     "toPrecision" => &TODO,
     "toString" => &TO_STRING,
     "valueOf" => &VALUE_OF,
-    _ => return Val::Undefined,
-  }
-  .to_val()
+    _ => return None,
+  })
 }
 
 static TO_FIXED: NativeFunction = native_fn(|this, params| {
@@ -71,9 +78,46 @@ static TO_EXPONENTIAL: NativeFunction = native_fn(|this, params| {
   })
 });
 
-static TODO_LOCALE: NativeFunction = native_fn(|this, _params| match this.get() {
-  Val::Number(_number) => Err("TODO: locale".to_internal_error()),
-  _ => Err("number indirection".to_internal_error()),
+// Formatting is up to the locale of the host (en-US by default). Only the minimumFractionDigits,
+// maximumFractionDigits, and useGrouping options are supported.
+static TO_LOCALE_STRING: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::Number(number) => {
+      let locales = read_locales(params.first());
+      let options = params.get(1);
+
+      let min_fraction_digits = read_digits_option(options, "minimumFractionDigits")?;
+      let max_fraction_digits = read_digits_option(options, "maximumFractionDigits")?;
+
+      let (min_fraction_digits, max_fraction_digits) =
+        match (min_fraction_digits, max_fraction_digits) {
+          (None, None) => (0, 3),
+          (Some(min), None) => (min, min.max(3)),
+          (None, Some(max)) => (0, max),
+          (Some(min), Some(max)) => {
+            if min > max {
+              return Err("maximumFractionDigits value is out of range".to_range_error());
+            }
+
+            (min, max)
+          }
+        };
+
+      let use_grouping = match read_option(options, "useGrouping")? {
+        Val::Undefined => true,
+        v => v.is_truthy(),
+      };
+
+      let format = NumberFormat {
+        min_fraction_digits,
+        max_fraction_digits,
+        use_grouping,
+      };
+
+      with_current_host(|host| host.locale.format_number(&locales, *number, &format)).to_val()
+    }
+    _ => return Err("number indirection".to_internal_error()),
+  })
 });
 
 static TO_STRING: NativeFunction = native_fn(|this, params| {
@@ -128,3 +172,81 @@ fn format_exponential(number: f64, precision: Option<usize>) -> Val {
 
   string.to_val()
 }
+
+fn read_option(options: Option<&Val>, key: &str) -> Result<Val, Val> {
+  match options {
+    Some(options) if !options.is_nullish() => options.sub(&key.to_val()),
+    _ => Ok(Val::Undefined),
+  }
+}
+
+fn read_digits_option(options: Option<&Val>, key: &str) -> Result<Option<usize>, Val> {
+  match read_option(options, key)? {
+    Val::Undefined => Ok(None),
+    value => {
+      let digits = value.to_number().floor();
+
+      if !(0.0..=20.0).contains(&digits) {
+        return Err(format!("{} value is out of range", key).to_range_error());
+      }
+
+      Ok(Some(digits as usize))
+    }
+  }
+}
+
+fn read_locales(locales: Option<&Val>) -> Vec<String> {
+  match locales {
+    None | Some(Val::Undefined) => vec![],
+    Some(Val::Array(array)) => array.elements.iter().map(|l| l.to_string()).collect(),
+    Some(locale) => vec![locale.to_string()],
+  }
+}
+
+pub(crate) fn format_en_us(number: f64, format: &NumberFormat) -> String {
+  let NumberFormat {
+    min_fraction_digits,
+    max_fraction_digits,
+    use_grouping,
+  } = *format;
+
+  if number.is_nan() {
+    return "NaN".to_string();
+  }
+
+  let sign = if number.is_sign_negative() { "-" } else { "" };
+
+  if number.is_infinite() {
+    return format!("{}∞", sign);
+  }
+
+  let fixed = format!("{:.*}", max_fraction_digits, number.abs());
+
+  let (int_part, fraction_part) = match fixed.split_once('.') {
+    Some((i, f)) => (i, f),
+    None => (fixed.as_str(), ""),
+  };
+
+  let mut fraction_part = fraction_part;
+
+  while fraction_part.len() > min_fraction_digits && fraction_part.ends_with('0') {
+    fraction_part = &fraction_part[..fraction_part.len() - 1];
+  }
+
+  let mut result = sign.to_string();
+
+  for (i, c) in int_part.chars().enumerate() {
+    if use_grouping && i != 0 && (int_part.len() - i) % 3 == 0 {
+      result.push(',');
+    }
+
+    result.push(c);
+  }
+
+  if !fraction_part.is_empty() {
+    result.push('.');
+    result.push_str(fraction_part);
+  }
+
+  result
+}
@@ -1,35 +1,67 @@
 use std::collections::BTreeMap;
-use std::rc::Rc;
 use std::str::FromStr;
 
 use num_bigint::BigInt;
+use num_bigint::BigUint;
 use num_bigint::Sign;
 use num_traits::FromPrimitive;
+use num_traits::One;
+use num_traits::Signed;
 use num_traits::ToPrimitive;
+use num_traits::Zero;
 
 use crate::array_methods::op_sub_array;
 use crate::bigint_methods::op_sub_bigint;
 use crate::builtins::internal_error_builtin::ToInternalError;
 use crate::builtins::range_error_builtin::ToRangeError;
 use crate::builtins::type_error_builtin::ToTypeError;
+use crate::function_methods::op_sub_function;
 use crate::native_function::native_fn;
 use crate::native_function::NativeFunction;
 use crate::number_methods::op_sub_number;
+use crate::property_map::PropertyMap;
+use crate::rc::Rc;
+use crate::size_limits::{check_array_length, check_bigint_bits, check_string_length};
 use crate::string_methods::op_sub_string;
+use crate::virtual_machine::VirtualMachine;
+use crate::vs_accessor::VsAccessor;
+use crate::vs_symbol::VsSymbol;
+use crate::vs_value::string_to_number;
+use crate::vs_value::LoadFunctionResult;
 use crate::vs_value::ToVal;
 use crate::vs_value::Val;
 use crate::vs_value::ValTrait;
 use crate::vs_value::VsType;
 
 pub fn op_plus(left: &Val, right: &Val) -> Result<Val, Val> {
-  let left_prim = left.to_primitive();
-  let right_prim = right.to_primitive();
+  // Fast paths that avoid converting the operands to new vals first
+  match (left, right) {
+    (Val::Number(left), Val::Number(right)) => return Ok(Val::Number(left + right)),
+    (Val::String(left), Val::String(right)) => {
+      check_string_length(left.len() + right.len())?;
+
+      let mut res = String::with_capacity(left.len() + right.len());
+      res.push_str(left);
+      res.push_str(right);
+
+      return Ok(res.to_val());
+    }
+    (Val::String(left), Val::Number(_)) => return Ok(format!("{}{}", left, right).to_val()),
+    (Val::Number(_), Val::String(right)) => return Ok(format!("{}{}", left, right).to_val()),
+    _ => {}
+  }
+
+  let left_prim = to_primitive(left, PrimitiveHint::Default)?;
+  let right_prim = to_primitive(right, PrimitiveHint::Default)?;
 
   let left_type = left_prim.typeof_();
   let right_type = right_prim.typeof_();
 
   if left_type == VsType::String || right_type == VsType::String {
-    return Ok((left_prim.to_string() + &right_prim.to_string()).to_val());
+    let res = left_prim.to_string() + &right_prim.to_string();
+    check_string_length(res.len())?;
+
+    return Ok(res.to_val());
   }
 
   if left_type == VsType::BigInt || right_type == VsType::BigInt {
@@ -39,6 +71,7 @@ pub fn op_plus(left: &Val, right: &Val) -> Result<Val, Val> {
 
     match (left_prim.as_bigint_data(), right_prim.as_bigint_data()) {
       (Some(left_bigint), Some(right_bigint)) => {
+        check_bigint_bits(left_bigint.bits().max(right_bigint.bits()) + 1)?;
         return Ok(Val::BigInt(left_bigint + right_bigint));
       }
       _ => return Err("TODO: plus with bigint and non-bigint".to_internal_error()),
@@ -48,14 +81,18 @@ pub fn op_plus(left: &Val, right: &Val) -> Result<Val, Val> {
   Ok(Val::Number(left_prim.to_number() + right_prim.to_number()))
 }
 
-pub fn op_unary_plus(input: &Val) -> Val {
-  match input.as_bigint_data() {
-    Some(bigint) => Val::BigInt(bigint),
-    _ => Val::Number(input.to_number()),
+pub fn op_unary_plus(input: &Val) -> Result<Val, Val> {
+  match input {
+    Val::BigInt(_) => Err("Cannot convert a BigInt value to a number".to_type_error()),
+    _ => Ok(Val::Number(input.to_number())),
   }
 }
 
 pub fn op_minus(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_minus(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
     (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint - right_bigint)),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
@@ -71,30 +108,61 @@ pub fn op_unary_minus(input: &Val) -> Val {
 }
 
 pub fn op_mul(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_mul(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
-    (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint * right_bigint)),
+    (Some(left_bigint), Some(right_bigint)) => {
+      check_bigint_bits(left_bigint.bits() + right_bigint.bits())?;
+      Ok(Val::BigInt(left_bigint * right_bigint))
+    }
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
     _ => Ok(Val::Number(left.to_number() * right.to_number())),
   }
 }
 
 pub fn op_div(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_div(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
-    (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint / right_bigint)),
+    (Some(left_bigint), Some(right_bigint)) => {
+      if right_bigint.is_zero() {
+        return Err("Division by zero".to_range_error());
+      }
+
+      Ok(Val::BigInt(left_bigint / right_bigint))
+    }
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
     _ => Ok(Val::Number(left.to_number() / right.to_number())),
   }
 }
 
 pub fn op_mod(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_mod(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
-    (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint % right_bigint)),
+    (Some(left_bigint), Some(right_bigint)) => {
+      if right_bigint.is_zero() {
+        return Err("Division by zero".to_range_error());
+      }
+
+      Ok(Val::BigInt(left_bigint % right_bigint))
+    }
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
     _ => Ok(Val::Number(left.to_number() % right.to_number())),
   }
 }
 
 pub fn op_exp(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_exp(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
     (Some(left_bigint), Some(right_bigint)) => {
       if right_bigint.sign() == Sign::Minus {
@@ -103,29 +171,64 @@ pub fn op_exp(left: &Val, right: &Val) -> Result<Val, Val> {
 
       let exp = match right_bigint.to_u32() {
         Some(exp) => exp,
-        None => return Err("Exponent must be less than 2^32".to_range_error()),
+
+        // Only 0, 1 and -1 have powers this large that aren't too big to represent
+        None if left_bigint.magnitude() <= &BigUint::one() => match right_bigint.bit(0) {
+          true => return Ok(Val::BigInt(left_bigint)),
+          false => return Ok(Val::BigInt(left_bigint.abs())),
+        },
+        None => return Err("Maximum BigInt size exceeded".to_range_error()),
       };
 
+      check_bigint_bits(left_bigint.bits().saturating_mul(exp as u64))?;
+
       Ok(Val::BigInt(left_bigint.pow(exp)))
     }
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
-    _ => Ok(Val::Number(left.to_number().powf(right.to_number()))),
+    _ => Ok(Val::Number(number_exp(left.to_number(), right.to_number()))),
   }
 }
 
+/// Number exponentiation as in JS, which differs from powf when the base is 1 or -1 and the
+/// exponent is NaN or infinite (JS gives NaN).
+fn number_exp(base: f64, exponent: f64) -> f64 {
+  if exponent.is_nan() || (base.abs() == 1.0 && exponent.is_infinite()) {
+    return f64::NAN;
+  }
+
+  base.powf(exponent)
+}
+
 pub fn op_eq_impl(left: &Val, right: &Val) -> Result<bool, Val> {
   Ok(match (left, right) {
+    (Val::Undefined | Val::Null, right) => matches!(right, Val::Undefined | Val::Null),
     (left, Val::Undefined | Val::Null) => matches!(left, Val::Undefined | Val::Null),
     (Val::Bool(left_bool), Val::Bool(right_bool)) => left_bool == right_bool,
+    (Val::Bool(_), _) => return op_eq_impl(&Val::Number(left.to_number()), right),
+    (_, Val::Bool(_)) => return op_eq_impl(left, &Val::Number(right.to_number())),
     (Val::Number(left_number), Val::Number(right_number)) => left_number == right_number,
     (Val::String(left_string), Val::String(right_string)) => left_string == right_string,
     (Val::Number(left_number), Val::String(right_string)) => {
-      left_number.to_string() == **right_string
+      *left_number == string_to_number(right_string)
     }
     (Val::String(left_string), Val::Number(right_number)) => {
-      **left_string == right_number.to_string()
+      string_to_number(left_string) == *right_number
     }
     (Val::BigInt(left_bigint), Val::BigInt(right_bigint)) => left_bigint == right_bigint,
+    (Val::BigInt(bigint), Val::Number(number)) | (Val::Number(number), Val::BigInt(bigint)) => {
+      number.fract() == 0_f64 && BigInt::from_f64(*number).as_ref() == Some(bigint)
+    }
+    (Val::BigInt(bigint), Val::String(string)) | (Val::String(string), Val::BigInt(bigint)) => {
+      string_to_bigint(string).as_ref() == Some(bigint)
+    }
+    (Val::Symbol(left_symbol), Val::Symbol(right_symbol)) => left_symbol == right_symbol,
+    (Val::Symbol(_), _) | (_, Val::Symbol(_)) => false,
+    (Val::Object(_) | Val::Array(_), right) if right.is_primitive() => {
+      return op_eq_impl(&to_primitive(left, PrimitiveHint::Default)?, right);
+    }
+    (left, Val::Object(_) | Val::Array(_)) if left.is_primitive() => {
+      return op_eq_impl(left, &to_primitive(right, PrimitiveHint::Default)?);
+    }
     (Val::Array(left_array), Val::Array(right_array)) => 'b: {
       if std::ptr::eq(&**left_array, &**right_array) {
         break 'b true;
@@ -165,7 +268,7 @@ pub fn op_eq_impl(left: &Val, right: &Val) -> Result<bool, Val> {
         break 'b true;
       }
 
-      if !compare_btrees(
+      if !compare_property_maps(
         &left_object.string_map,
         &right_object.string_map,
         op_eq_impl,
@@ -210,6 +313,12 @@ pub fn op_eq_impl(left: &Val, right: &Val) -> Result<bool, Val> {
       (None, Some(_)) | (Some(_), None) => return Ok(false),
       (Some(left_hash), Some(right_hash)) => left_hash == right_hash,
     },
+    (Val::Array(_) | Val::Object(_) | Val::Function(_) | Val::Class(_), _)
+    | (_, Val::Array(_) | Val::Object(_) | Val::Function(_) | Val::Class(_))
+      if left.typeof_() != right.typeof_() =>
+    {
+      false
+    }
     _ => {
       if left.is_truthy() != right.is_truthy() {
         return Ok(false);
@@ -250,6 +359,42 @@ where
   Ok(true)
 }
 
+fn compare_property_maps<Cmp>(
+  left: &PropertyMap,
+  right: &PropertyMap,
+  cmp: Cmp,
+) -> Result<bool, Val>
+where
+  Cmp: Fn(&Val, &Val) -> Result<bool, Val>,
+{
+  if left.len() != right.len() {
+    return Ok(false);
+  }
+
+  if let Some((left_values, right_values)) = left.values_if_same_shape(right) {
+    for (left_value, right_value) in left_values.iter().zip(right_values) {
+      if !cmp(left_value, right_value)? {
+        return Ok(false);
+      }
+    }
+
+    return Ok(true);
+  }
+
+  for (key, left_value) in left {
+    let right_value = match right.get(key) {
+      Some(v) => v,
+      None => return Ok(false),
+    };
+
+    if !cmp(left_value, right_value)? {
+      return Ok(false);
+    }
+  }
+
+  Ok(true)
+}
+
 pub fn op_eq(left: &Val, right: &Val) -> Result<Val, Val> {
   Ok(Val::Bool(op_eq_impl(left, right)?))
 }
@@ -266,6 +411,7 @@ pub fn op_triple_eq_impl(left: &Val, right: &Val) -> Result<bool, Val> {
     (Val::Number(left_number), Val::Number(right_number)) => left_number == right_number,
     (Val::String(left_string), Val::String(right_string)) => left_string == right_string,
     (Val::BigInt(left_bigint), Val::BigInt(right_bigint)) => left_bigint == right_bigint,
+    (Val::Symbol(left_symbol), Val::Symbol(right_symbol)) => left_symbol == right_symbol,
     (Val::Array(left_array), Val::Array(right_array)) => 'b: {
       if std::ptr::eq(&**left_array, &**right_array) {
         break 'b true;
@@ -301,7 +447,7 @@ pub fn op_triple_eq_impl(left: &Val, right: &Val) -> Result<bool, Val> {
         break 'b true;
       }
 
-      if !compare_btrees(
+      if !compare_property_maps(
         &left_object.string_map,
         &right_object.string_map,
         op_triple_eq_impl,
@@ -401,7 +547,7 @@ pub fn op_less(left: &Val, right: &Val) -> Result<Val, Val> {
     (Val::Number(left_number), Val::Number(right_number)) => left_number < right_number,
     (Val::String(left_string), Val::String(right_string)) => left_string < right_string,
     (Val::BigInt(left_bigint), Val::BigInt(right_bigint)) => left_bigint < right_bigint,
-    _ => ecma_is_less_than(left, right).unwrap_or(false),
+    _ => ecma_is_less_than(left, right)?.unwrap_or(false),
   }))
 }
 
@@ -413,7 +559,7 @@ pub fn op_less_eq(left: &Val, right: &Val) -> Result<Val, Val> {
     (Val::Number(left_number), Val::Number(right_number)) => left_number <= right_number,
     (Val::String(left_string), Val::String(right_string)) => left_string <= right_string,
     (Val::BigInt(left_bigint), Val::BigInt(right_bigint)) => left_bigint <= right_bigint,
-    _ => match ecma_is_less_than(right, left) {
+    _ => match ecma_is_less_than(right, left)? {
       None => false,
       Some(x) => !x,
     },
@@ -428,7 +574,7 @@ pub fn op_greater(left: &Val, right: &Val) -> Result<Val, Val> {
     (Val::Number(left_number), Val::Number(right_number)) => left_number > right_number,
     (Val::String(left_string), Val::String(right_string)) => left_string > right_string,
     (Val::BigInt(left_bigint), Val::BigInt(right_bigint)) => left_bigint > right_bigint,
-    _ => ecma_is_less_than(right, left).unwrap_or(false),
+    _ => ecma_is_less_than(right, left)?.unwrap_or(false),
   }))
 }
 
@@ -440,7 +586,7 @@ pub fn op_greater_eq(left: &Val, right: &Val) -> Result<Val, Val> {
     (Val::Number(left_number), Val::Number(right_number)) => left_number >= right_number,
     (Val::String(left_string), Val::String(right_string)) => left_string >= right_string,
     (Val::BigInt(left_bigint), Val::BigInt(right_bigint)) => left_bigint >= right_bigint,
-    (left, right) => match ecma_is_less_than(left, right) {
+    (left, right) => match ecma_is_less_than(left, right)? {
       None => false,
       Some(x) => !x,
     },
@@ -482,6 +628,10 @@ pub fn to_u32(x: f64) -> u32 {
 }
 
 pub fn op_bit_and(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_bit_and(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
     (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint & right_bigint)),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
@@ -493,6 +643,10 @@ pub fn op_bit_and(left: &Val, right: &Val) -> Result<Val, Val> {
 }
 
 pub fn op_bit_or(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_bit_or(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
     (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint | right_bigint)),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
@@ -514,6 +668,10 @@ pub fn op_bit_not(input: &Val) -> Val {
 }
 
 pub fn op_bit_xor(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_bit_xor(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
     (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(left_bigint ^ right_bigint)),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
@@ -525,10 +683,12 @@ pub fn op_bit_xor(left: &Val, right: &Val) -> Result<Val, Val> {
 }
 
 pub fn op_left_shift(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_left_shift(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
-    (Some(left_bigint), Some(right_bigint)) => Ok(Val::BigInt(
-      left_bigint << right_bigint.to_i64().expect("TODO"),
-    )),
+    (Some(left_bigint), Some(right_bigint)) => bigint_shift_left(left_bigint, &right_bigint),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
     _ => {
       let res_i32 = to_i32(left.to_number()) << (to_u32(right.to_number()) & 0x1f);
@@ -538,13 +698,12 @@ pub fn op_left_shift(left: &Val, right: &Val) -> Result<Val, Val> {
 }
 
 pub fn op_right_shift(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_right_shift(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
-    (Some(left_bigint), Some(right_bigint)) => {
-      let right_i64 = right_bigint
-        .to_i64()
-        .ok_or("TODO: handle i64 conversion failure".to_val())?;
-      Ok(Val::BigInt(left_bigint >> right_i64))
-    }
+    (Some(left_bigint), Some(right_bigint)) => bigint_shift_left(left_bigint, &-right_bigint),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
     _ => {
       let res_i32 = to_i32(left.to_number()) >> (to_u32(right.to_number()) & 0x1f);
@@ -553,7 +712,39 @@ pub fn op_right_shift(left: &Val, right: &Val) -> Result<Val, Val> {
   }
 }
 
+/// Shifts `bigint` left by `shift` bits, or right if `shift` is negative. Right shifts round towards
+/// negative infinity, like they do for numbers.
+fn bigint_shift_left(bigint: BigInt, shift: &BigInt) -> Result<Val, Val> {
+  if bigint.is_zero() {
+    return Ok(Val::BigInt(bigint));
+  }
+
+  let shift = match shift.to_i64() {
+    Some(shift) => shift,
+    None if shift.is_negative() => i64::MIN,
+    None => return Err("Maximum BigInt size exceeded".to_range_error()),
+  };
+
+  if shift >= 0 {
+    check_bigint_bits(bigint.bits().saturating_add(shift as u64))?;
+    return Ok(Val::BigInt(bigint << shift as u64));
+  }
+
+  let shift = shift.unsigned_abs();
+
+  // Shifting out every bit leaves 0, or -1 for negative numbers
+  Ok(Val::BigInt(match shift >= bigint.bits() {
+    true if bigint.is_negative() => BigInt::from(-1),
+    true => BigInt::zero(),
+    false => bigint >> shift,
+  }))
+}
+
 pub fn op_right_shift_unsigned(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Some((left, right)) = object_operands_to_primitive(left, right, PrimitiveHint::Number)? {
+    return op_right_shift_unsigned(&left, &right);
+  }
+
   match (left.as_bigint_data(), right.as_bigint_data()) {
     (Some(_), Some(_)) => Err("BigInts don't support unsigned right shift".to_type_error()),
     (Some(_), None) | (None, Some(_)) => Err("Cannot mix BigInt with other types".to_type_error()),
@@ -584,6 +775,15 @@ pub fn op_typeof(input: &Val) -> Val {
 }
 
 pub fn op_instance_of(left: &Val, right: &Val) -> Result<Val, Val> {
+  if let Val::Object(_) | Val::Class(_) = right {
+    let has_instance = right.sub(&VsSymbol::HAS_INSTANCE.to_val())?;
+
+    if !has_instance.is_nullish() {
+      let res = VirtualMachine::call_nested(&has_instance, right.clone(), vec![left.clone()])?;
+      return Ok(res.is_truthy().to_val());
+    }
+  }
+
   let class_data = match right.as_class_data() {
     Some(class_data) => class_data,
     None => return Err("Right-hand side of `instanceof` is not a class".to_type_error()),
@@ -601,7 +801,23 @@ pub fn op_instance_of(left: &Val, right: &Val) -> Result<Val, Val> {
     },
   };
 
-  Ok(op_triple_eq_impl(left_prototype, &class_data.prototype)?.to_val())
+  // Walk up the prototype chain so that instances of derived classes are also instances of the
+  // classes they extend
+  let mut prototype = left_prototype.clone();
+
+  loop {
+    if op_triple_eq_impl(&prototype, &class_data.prototype)? {
+      return Ok(true.to_val());
+    }
+
+    prototype = match &prototype {
+      Val::Object(obj) => match &obj.prototype {
+        Some(proto) => proto.clone(),
+        None => return Ok(false.to_val()),
+      },
+      _ => return Ok(false.to_val()),
+    };
+  }
 }
 
 pub fn op_in(left: &Val, right: &Val) -> Result<Val, Val> {
@@ -623,12 +839,23 @@ pub fn op_sub(left: &mut Val, right: &Val) -> Result<Val, Val> {
     }),
     Val::Number(number) => Ok(op_sub_number(*number, right)),
     Val::BigInt(bigint) => Ok(op_sub_bigint(bigint, right)),
-    Val::Symbol(_) => Ok(Val::Undefined),
+    Val::Symbol(symbol) => Ok(match right.to_string().as_str() {
+      "description" => symbol.description().to_val(),
+      "toString" => SYMBOL_TO_STRING.to_val(),
+      "valueOf" => SYMBOL_VALUE_OF.to_val(),
+      _ => Val::Undefined,
+    }),
     Val::String(string_data) => Ok(op_sub_string(string_data, right)),
     Val::Array(array_data) => op_sub_array(array_data, right),
-    Val::Object(object_data) => Ok(object_data.sub(right)), // TODO: move on single ref
-    Val::Function(_) => Ok(Val::Undefined),
-    Val::Class(class) => op_sub(&mut class.static_.clone(), right),
+    Val::Object(object_data) => {
+      // TODO: move on single ref
+      resolve_accessor(object_data.sub(right), || Val::Object(object_data.clone()))
+    }
+    Val::Function(_) => Ok(op_sub_function(right)),
+    Val::Class(class) => match &class.static_ {
+      Val::Object(static_) => resolve_accessor(static_.sub(right), || Val::Class(class.clone())),
+      static_ => op_sub(&mut static_.clone(), right),
+    },
     Val::Static(s) => s.sub(right),
     Val::Dynamic(dynamic_data) => dynamic_data.sub(right),
     Val::CopyCounter(cc) => Ok(match right.to_string().as_str() {
@@ -639,6 +866,15 @@ pub fn op_sub(left: &mut Val, right: &Val) -> Result<Val, Val> {
   }
 }
 
+/// Calls the getter if `val` (a property found by VsObject::sub) is an accessor, using the
+/// receiver as `this`. Other values are returned as-is.
+fn resolve_accessor(val: Val, receiver: impl FnOnce() -> Val) -> Result<Val, Val> {
+  match VsAccessor::from_val(&val) {
+    Some(accessor) => accessor.get_value(receiver()),
+    None => Ok(val),
+  }
+}
+
 pub fn op_submov(target: &mut Val, subscript: &Val, value: Val) -> Result<(), Val> {
   match target {
     Val::Void => Err("Internal: Shouldn't happen".to_internal_error()), // TODO: Internal errors
@@ -665,6 +901,8 @@ pub fn op_submov(target: &mut Val, subscript: &Val, value: Val) -> Result<(), Va
           return Err("TODO: Sparse arrays".to_type_error());
         }
 
+        check_array_length(subscript_index + 1)?;
+
         while subscript_index > array_data_mut.elements.len() {
           array_data_mut.elements.push(Val::Void);
         }
@@ -675,6 +913,10 @@ pub fn op_submov(target: &mut Val, subscript: &Val, value: Val) -> Result<(), Va
       Ok(())
     }
     Val::Object(object_data) => {
+      if let Some(accessor) = VsAccessor::from_val(&object_data.sub(subscript)) {
+        return accessor.clone().set_value(target, value);
+      }
+
       let object_data_mut = Rc::make_mut(object_data);
 
       match subscript {
@@ -695,10 +937,167 @@ pub fn op_submov(target: &mut Val, subscript: &Val, value: Val) -> Result<(), Va
   }
 }
 
-pub fn ecma_is_less_than(x: &Val, y: &Val) -> Option<bool> {
-  let px = x.to_primitive();
-  let py = y.to_primitive();
+/// Removes `subscript` from `target`, giving the result of the `delete` operator. Deleting an
+/// array element leaves a hole rather than shifting the later elements.
+pub fn op_delete(target: &mut Val, subscript: &Val) -> Result<bool, Val> {
+  match target {
+    Val::Void => Err("Internal: Shouldn't happen".to_internal_error()), // TODO: Internal errors
+    Val::Undefined => Err("Cannot delete subscript of undefined".to_type_error()),
+    Val::Null => Err("Cannot delete subscript of null".to_type_error()),
+    Val::Bool(_) | Val::Number(_) | Val::BigInt(_) | Val::Symbol(_) => Ok(true),
+    Val::String(string) => {
+      let is_own_property = match subscript.to_index() {
+        Some(i) => i < string.len(),
+        None => subscript.to_string() == "length",
+      };
+
+      match is_own_property {
+        true => Err("Cannot delete property of string".to_type_error()),
+        false => Ok(true),
+      }
+    }
+    Val::Array(array_data) => {
+      let subscript_index = match subscript.to_index() {
+        Some(i) => i,
+        None => {
+          return match subscript.to_string().as_str() {
+            "length" => Err("Cannot delete length of array".to_type_error()),
+            _ => Ok(true),
+          }
+        }
+      };
+
+      if subscript_index < array_data.elements.len() {
+        Rc::make_mut(array_data).elements[subscript_index] = Val::Void;
+      }
+
+      Ok(true)
+    }
+    Val::Object(object_data) => {
+      let object_data_mut = Rc::make_mut(object_data);
+
+      match subscript {
+        Val::Symbol(symbol) => object_data_mut.symbol_map.remove(symbol),
+        _ => object_data_mut.string_map.remove(&subscript.to_string()),
+      };
+
+      Ok(true)
+    }
+    Val::Function(_) => Err("TODO: function subscript deletion".to_type_error()),
+    Val::Class(_) => Err("Cannot delete subscript of class".to_type_error()),
+    Val::Static(_) => Err("Cannot delete subscript of static value".to_type_error()),
+    Val::Dynamic(_) => Err("Cannot delete subscript of dynamic value".to_type_error()),
+    Val::CopyCounter(_) => Err("Cannot delete subscript of CopyCounter".to_type_error()),
+  }
+}
+
+/// Converts a string to a bigint for loose equality (StringToBigInt in the spec).
+fn string_to_bigint(string: &str) -> Option<BigInt> {
+  let string = string.trim();
+
+  if string.is_empty() {
+    return Some(BigInt::from(0));
+  }
+
+  BigInt::from_str(string).ok()
+}
 
+#[derive(Clone, Copy)]
+pub enum PrimitiveHint {
+  Default,
+  Number,
+  String,
+}
+
+impl PrimitiveHint {
+  fn as_str(self) -> &'static str {
+    match self {
+      PrimitiveHint::Default => "default",
+      PrimitiveHint::Number => "number",
+      PrimitiveHint::String => "string",
+    }
+  }
+}
+
+/// The ToPrimitive algorithm. Objects are converted using their `[Symbol.toPrimitive]` method if
+/// they have one, otherwise `valueOf` and `toString` are tried in the order given by the hint.
+/// Objects without a `toString` method fall back to the default stringification, which stands in
+/// for `Object.prototype.toString`.
+pub fn to_primitive(val: &Val, hint: PrimitiveHint) -> Result<Val, Val> {
+  if !matches!(val, Val::Object(_)) {
+    return Ok(val.to_primitive());
+  }
+
+  let exotic_to_prim = val.sub(&VsSymbol::TO_PRIMITIVE.to_val())?;
+
+  if !exotic_to_prim.is_nullish() {
+    let res =
+      VirtualMachine::call_nested(&exotic_to_prim, val.clone(), vec![hint.as_str().to_val()])?;
+
+    if !res.is_primitive() {
+      return Err("Cannot convert object to primitive value".to_type_error());
+    }
+
+    return Ok(res);
+  }
+
+  let method_names = match hint {
+    PrimitiveHint::String => ["toString", "valueOf"],
+    PrimitiveHint::Default | PrimitiveHint::Number => ["valueOf", "toString"],
+  };
+
+  let mut has_to_string = false;
+
+  for method_name in method_names {
+    let method = val.sub(&method_name.to_val())?;
+
+    if let LoadFunctionResult::NotAFunction = method.load_function() {
+      continue;
+    }
+
+    if method_name == "toString" {
+      has_to_string = true;
+    }
+
+    let res = VirtualMachine::call_nested(&method, val.clone(), vec![])?;
+
+    if res.is_primitive() {
+      return Ok(res);
+    }
+  }
+
+  if !has_to_string {
+    return Ok(val.to_primitive());
+  }
+
+  Err("Cannot convert object to primitive value".to_type_error())
+}
+
+/// Converts the operands to primitives if either of them is an object, so that operators can
+/// retry with the results.
+fn object_operands_to_primitive(
+  left: &Val,
+  right: &Val,
+  hint: PrimitiveHint,
+) -> Result<Option<(Val, Val)>, Val> {
+  if !matches!(left, Val::Object(_)) && !matches!(right, Val::Object(_)) {
+    return Ok(None);
+  }
+
+  Ok(Some((
+    to_primitive(left, hint)?,
+    to_primitive(right, hint)?,
+  )))
+}
+
+pub fn ecma_is_less_than(x: &Val, y: &Val) -> Result<Option<bool>, Val> {
+  let px = to_primitive(x, PrimitiveHint::Number)?;
+  let py = to_primitive(y, PrimitiveHint::Number)?;
+
+  Ok(is_less_than_primitives(px, py))
+}
+
+fn is_less_than_primitives(px: Val, py: Val) -> Option<bool> {
   match (px, py) {
     (Val::BigInt(x), Val::BigInt(y)) => Some(x < y),
     (Val::String(x), Val::String(y)) => Some(x < y),
@@ -784,3 +1183,17 @@ static BOOL_VALUE_OF: NativeFunction = native_fn(|this, _params| {
     _ => return Err("bool indirection".to_type_error()),
   })
 });
+
+static SYMBOL_TO_STRING: NativeFunction = native_fn(|this, _params| {
+  Ok(match this.get() {
+    Val::Symbol(symbol) => format!("Symbol({})", symbol.description()).to_val(),
+    _ => return Err("symbol indirection".to_type_error()),
+  })
+});
+
+static SYMBOL_VALUE_OF: NativeFunction = native_fn(|this, _params| {
+  Ok(match this.get() {
+    Val::Symbol(symbol) => Val::Symbol(symbol.clone()),
+    _ => return Err("symbol indirection".to_type_error()),
+  })
+});
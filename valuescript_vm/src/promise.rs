@@ -0,0 +1,655 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::take;
+
+use num_bigint::BigInt;
+
+use crate::builtins::type_error_builtin::ToTypeError;
+use crate::host::{try_with_current_host, with_current_host};
+use crate::native_frame_function::NativeFrameFunction;
+use crate::native_function::{native_fn, NativeFunction, ThisWrapper};
+use crate::rc::Rc;
+use crate::stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait};
+use crate::vs_array::VsArray;
+use crate::vs_class::VsClass;
+use crate::vs_value::{LoadFunctionResult, ToDynamicVal, ToVal, Val, ValTrait, VsType};
+
+/// A handle to a promise in the promise table of the host.
+///
+/// Unlike other ValueScript values, promises have identity: copies of a promise refer to the same
+/// state, so that settling it is seen by everything waiting on it. The state itself can only be
+/// changed by the resolve/reject functions of the promise (or the async function it belongs to),
+/// and reactions to it always run later as microtasks, which keeps programs deterministic.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VsPromise {
+  pub id: u64,
+}
+
+#[derive(Clone)]
+pub enum PromiseState {
+  Pending(Vec<Reaction>),
+  Fulfilled(Val),
+  Rejected(Val),
+}
+
+/// A call of `job` that is queued as a microtask when the promise settles. The job is called with
+/// `params`, followed by the value (or reason) of the promise and whether it was rejected.
+#[derive(Clone)]
+pub struct Reaction {
+  pub job: Val,
+  pub params: Vec<Val>,
+}
+
+/// The state of the promises created by a program. Settled promises are kept for the life of the
+/// host, since any copy of them might still be awaited.
+#[derive(Default)]
+pub struct PromiseTable {
+  next_id: u64,
+  states: HashMap<u64, PromiseState>,
+
+  /// The elements gathered so far by Promise.all, and the number still pending, by the id of the
+  /// promise it returned.
+  aggregates: HashMap<u64, (Vec<Val>, usize)>,
+}
+
+impl VsPromise {
+  pub fn new_pending() -> VsPromise {
+    with_current_host(|host| {
+      let id = host.promises.next_id;
+      host.promises.next_id += 1;
+      host
+        .promises
+        .states
+        .insert(id, PromiseState::Pending(vec![]));
+
+      VsPromise { id }
+    })
+  }
+
+  pub fn from_val(val: &Val) -> Option<VsPromise> {
+    match val {
+      Val::Dynamic(dynamic) => dynamic.as_any().downcast_ref::<VsPromise>().copied(),
+      _ => None,
+    }
+  }
+
+  /// The value (or reason) of the promise, or None if it's pending.
+  pub fn settled(&self) -> Option<Result<Val, Val>> {
+    with_current_host(|host| settled_state(host.promises.states.get(&self.id)))
+  }
+
+  /// Settles the promise with `value`, or if `value` is a promise, settles it the same way as that
+  /// promise once it has settled.
+  pub fn resolve(&self, value: Val) {
+    match VsPromise::from_val(&value) {
+      Some(other) if other == *self => {
+        self.settle(Err("Chaining cycle detected for promise".to_type_error()));
+      }
+      Some(other) => other.subscribe(Reaction {
+        job: SETTLE.to_val(),
+        params: vec![self.to_dynamic_val()],
+      }),
+      None => self.settle(Ok(value)),
+    }
+  }
+
+  pub fn reject(&self, reason: Val) {
+    self.settle(Err(reason));
+  }
+
+  /// Fulfills (Ok) or rejects (Err) the promise and queues its reactions. Promises can only be
+  /// settled once, so this does nothing if it has already been settled.
+  pub fn settle(&self, result: Result<Val, Val>) {
+    with_current_host(|host| {
+      let state = match host.promises.states.get_mut(&self.id) {
+        Some(state) => state,
+        None => return,
+      };
+
+      let reactions = match state {
+        PromiseState::Pending(reactions) => take(reactions),
+        _ => return,
+      };
+
+      let (value, rejected) = match &result {
+        Ok(value) => (value.clone(), false),
+        Err(reason) => (reason.clone(), true),
+      };
+
+      *state = match result {
+        Ok(value) => PromiseState::Fulfilled(value),
+        Err(reason) => PromiseState::Rejected(reason),
+      };
+
+      for reaction in reactions {
+        let mut params = reaction.params;
+        params.push(value.clone());
+        params.push(rejected.to_val());
+
+        host.scheduler.queue_microtask(reaction.job, params);
+      }
+    });
+  }
+
+  /// Adds a reaction to the promise, which is queued immediately if it has already settled.
+  pub fn subscribe(&self, reaction: Reaction) {
+    with_current_host(|host| {
+      let settled = match host.promises.states.get_mut(&self.id) {
+        Some(PromiseState::Pending(reactions)) => {
+          reactions.push(reaction);
+          return;
+        }
+        state => settled_state(state.map(|state| &*state)),
+      };
+
+      let (value, rejected) = match settled {
+        Some(Ok(value)) => (value, false),
+        Some(Err(reason)) => (reason, true),
+        None => (Val::Undefined, false),
+      };
+
+      let mut params = reaction.params;
+      params.push(value);
+      params.push(rejected.to_val());
+
+      host.scheduler.queue_microtask(reaction.job, params);
+    });
+  }
+
+  /// The state of the promise for formatting, or None if it isn't available (when the promise
+  /// belongs to a different host, or the host is in use).
+  fn lookup(&self) -> Option<Option<Result<Val, Val>>> {
+    try_with_current_host(|host| {
+      let state = host.promises.states.get(&self.id)?;
+      Some(settled_state(Some(state)))
+    })
+    .flatten()
+  }
+}
+
+fn settled_state(state: Option<&PromiseState>) -> Option<Result<Val, Val>> {
+  match state {
+    Some(PromiseState::Fulfilled(value)) => Some(Ok(value.clone())),
+    Some(PromiseState::Rejected(reason)) => Some(Err(reason.clone())),
+    Some(PromiseState::Pending(_)) | None => None,
+  }
+}
+
+/// Adds `reaction` to `value` if it's a promise, and otherwise queues it with `value` as though it
+/// was a fulfilled promise. This is how await works.
+pub fn when_settled(value: Val, reaction: Reaction) {
+  match VsPromise::from_val(&value) {
+    Some(promise) => promise.subscribe(reaction),
+    None => with_current_host(|host| {
+      let mut params = reaction.params;
+      params.push(value);
+      params.push(false.to_val());
+
+      host.scheduler.queue_microtask(reaction.job, params);
+    }),
+  }
+}
+
+/// Settles the promise with the result of another one.
+/// Params: promise, value, rejected.
+pub static SETTLE: NativeFunction = native_fn(|_this, params| {
+  if let [promise, value, rejected] = params.as_slice() {
+    if let Some(promise) = VsPromise::from_val(promise) {
+      promise.settle(match rejected.is_truthy() {
+        false => Ok(value.clone()),
+        true => Err(value.clone()),
+      });
+    }
+  }
+
+  Ok(Val::Undefined)
+});
+
+impl ValTrait for VsPromise {
+  fn typeof_(&self) -> VsType {
+    VsType::Object
+  }
+
+  fn to_number(&self) -> f64 {
+    f64::NAN
+  }
+
+  fn to_index(&self) -> Option<usize> {
+    None
+  }
+
+  fn is_primitive(&self) -> bool {
+    false
+  }
+
+  fn is_truthy(&self) -> bool {
+    true
+  }
+
+  fn is_nullish(&self) -> bool {
+    false
+  }
+
+  fn bind(&self, _params: Vec<Val>) -> Option<Val> {
+    None
+  }
+
+  fn as_bigint_data(&self) -> Option<BigInt> {
+    None
+  }
+
+  fn as_array_data(&self) -> Option<Rc<VsArray>> {
+    None
+  }
+
+  fn as_class_data(&self) -> Option<Rc<VsClass>> {
+    None
+  }
+
+  fn load_function(&self) -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn sub(&self, key: &Val) -> Result<Val, Val> {
+    Ok(match key.to_string().as_str() {
+      "then" => THEN.to_val(),
+      "catch" => CATCH.to_val(),
+      "finally" => FINALLY.to_val(),
+      _ => Val::Undefined,
+    })
+  }
+
+  fn has(&self, key: &Val) -> Option<bool> {
+    Some(matches!(
+      key.to_string().as_str(),
+      "then" | "catch" | "finally"
+    ))
+  }
+
+  fn submov(&mut self, _key: &Val, _value: Val) -> Result<(), Val> {
+    Err("Cannot assign to subscript of a promise".to_type_error())
+  }
+
+  fn pretty_fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.lookup() {
+      Some(Some(Ok(value))) => write!(f, "Promise {{ {} }}", value.pretty()),
+      Some(Some(Err(reason))) => write!(f, "Promise {{ <rejected> {} }}", reason.pretty()),
+      Some(None) => write!(f, "Promise {{ <pending> }}"),
+      None => write!(f, "Promise {{ [native data] }}"),
+    }
+  }
+
+  fn codify(&self) -> String {
+    match self.lookup() {
+      Some(Some(Ok(value))) => format!("Promise {{ {} }}", value.codify()),
+      Some(Some(Err(reason))) => format!("Promise {{ <rejected> {} }}", reason.codify()),
+      Some(None) => "Promise { <pending> }".to_string(),
+      None => "Promise { [native data] }".to_string(),
+    }
+  }
+}
+
+impl fmt::Display for VsPromise {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "[object Promise]")
+  }
+}
+
+fn this_promise(this: &ThisWrapper, method_name: &str) -> Result<VsPromise, Val> {
+  VsPromise::from_val(this.get())
+    .ok_or_else(|| format!("Promise.{} called on a non-promise", method_name).to_type_error())
+}
+
+/// Creates the promise for the result of a then/catch/finally handler, and subscribes the handler
+/// to `promise`.
+fn add_handlers(promise: VsPromise, on_fulfilled: Val, on_rejected: Val, finally: bool) -> Val {
+  let derived = VsPromise::new_pending();
+
+  promise.subscribe(Reaction {
+    job: THEN_REACTION.to_val(),
+    params: vec![
+      on_fulfilled,
+      on_rejected,
+      derived.to_dynamic_val(),
+      finally.to_val(),
+    ],
+  });
+
+  derived.to_dynamic_val()
+}
+
+static THEN: NativeFunction = native_fn(|this, params| {
+  let promise = this_promise(&this, "then")?;
+  let mut params = params.into_iter();
+
+  Ok(add_handlers(
+    promise,
+    params.next().unwrap_or(Val::Undefined),
+    params.next().unwrap_or(Val::Undefined),
+    false,
+  ))
+});
+
+static CATCH: NativeFunction = native_fn(|this, params| {
+  let promise = this_promise(&this, "catch")?;
+
+  Ok(add_handlers(
+    promise,
+    Val::Undefined,
+    params.into_iter().next().unwrap_or(Val::Undefined),
+    false,
+  ))
+});
+
+static FINALLY: NativeFunction = native_fn(|this, params| {
+  let promise = this_promise(&this, "finally")?;
+  let on_finally = params.into_iter().next().unwrap_or(Val::Undefined);
+
+  Ok(add_handlers(promise, on_finally.clone(), on_finally, true))
+});
+
+/// Runs a then/catch/finally handler and settles the derived promise with its result.
+/// Params: on_fulfilled, on_rejected, derived, finally, value, rejected.
+static THEN_REACTION: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::<ThenReactionFrame>::default(),
+};
+
+#[derive(Clone, Default)]
+struct ThenReactionFrame {
+  params: Vec<Val>,
+
+  /// The result of the handler, once it has been called.
+  result: Option<Result<Val, Val>>,
+}
+
+impl ThenReactionFrame {
+  fn param(&self, i: usize) -> Val {
+    self.params.get(i).cloned().unwrap_or(Val::Undefined)
+  }
+
+  fn derived(&self) -> Option<VsPromise> {
+    VsPromise::from_val(&self.param(2))
+  }
+
+  fn finally(&self) -> bool {
+    self.param(3).is_truthy()
+  }
+
+  fn settled_with(&self) -> Result<Val, Val> {
+    match self.param(5).is_truthy() {
+      false => Ok(self.param(4)),
+      true => Err(self.param(4)),
+    }
+  }
+
+  /// Settles the derived promise with the result of the handler, or passes on the result of the
+  /// original promise if there was no handler (or it was a finally handler that didn't throw).
+  fn complete(&self, handler_result: Option<Result<Val, Val>>) -> FrameStepResult {
+    if let Some(derived) = self.derived() {
+      match handler_result {
+        Some(Err(reason)) => derived.reject(reason),
+        Some(Ok(value)) if !self.finally() => derived.resolve(value),
+        _ => derived.settle(self.settled_with()),
+      }
+    }
+
+    Ok(FrameStepOk::Pop(CallResult {
+      return_: Val::Undefined,
+      this: Val::Undefined,
+    }))
+  }
+}
+
+impl StackFrameTrait for ThenReactionFrame {
+  fn write_this(&mut self, _const: bool, _this: Val) -> Result<(), Val> {
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    self.params.push(param);
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    if let Some(result) = take(&mut self.result) {
+      return self.complete(Some(result));
+    }
+
+    let (handler, handler_params) = match (self.finally(), self.settled_with()) {
+      (true, _) => (self.param(0), vec![]),
+      (false, Ok(value)) => (self.param(0), vec![value]),
+      (false, Err(reason)) => (self.param(1), vec![reason]),
+    };
+
+    match handler.load_function() {
+      LoadFunctionResult::NotAFunction => self.complete(None),
+      LoadFunctionResult::NativeFunction(native_fn) => {
+        let result = native_fn(ThisWrapper::new(true, &mut Val::Undefined), handler_params);
+        self.complete(Some(result))
+      }
+      LoadFunctionResult::StackFrame(mut frame) => {
+        frame.write_this(true, Val::Undefined)?;
+
+        for param in handler_params {
+          frame.write_param(param);
+        }
+
+        Ok(FrameStepOk::Push(frame))
+      }
+    }
+  }
+
+  fn apply_call_result(&mut self, call_result: CallResult) {
+    self.result = Some(Ok(call_result.return_));
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for ThenReactionFrame")
+  }
+
+  fn catch_exception(&mut self, exception: &mut Val) {
+    self.result = Some(Err(take(exception)));
+    *exception = Val::Void;
+  }
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
+
+/// The resolve and reject functions that are passed to the executor of `new Promise(...)`.
+#[derive(Clone)]
+pub struct PromiseResolver {
+  pub promise: VsPromise,
+  pub rejects: bool,
+}
+
+impl ValTrait for PromiseResolver {
+  fn typeof_(&self) -> VsType {
+    VsType::Function
+  }
+
+  fn to_number(&self) -> f64 {
+    f64::NAN
+  }
+
+  fn to_index(&self) -> Option<usize> {
+    None
+  }
+
+  fn is_primitive(&self) -> bool {
+    false
+  }
+
+  fn is_truthy(&self) -> bool {
+    true
+  }
+
+  fn is_nullish(&self) -> bool {
+    false
+  }
+
+  fn bind(&self, _params: Vec<Val>) -> Option<Val> {
+    None
+  }
+
+  fn as_bigint_data(&self) -> Option<BigInt> {
+    None
+  }
+
+  fn as_array_data(&self) -> Option<Rc<VsArray>> {
+    None
+  }
+
+  fn as_class_data(&self) -> Option<Rc<VsClass>> {
+    None
+  }
+
+  fn load_function(&self) -> LoadFunctionResult {
+    LoadFunctionResult::StackFrame(Box::new(ResolverFrame {
+      resolver: self.clone(),
+      value: None,
+    }))
+  }
+
+  fn sub(&self, _key: &Val) -> Result<Val, Val> {
+    Ok(Val::Undefined)
+  }
+
+  fn has(&self, _key: &Val) -> Option<bool> {
+    Some(false)
+  }
+
+  fn submov(&mut self, _key: &Val, _value: Val) -> Result<(), Val> {
+    Err("Cannot assign to subscript of a function".to_type_error())
+  }
+
+  fn pretty_fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\x1b[36m[Function]\x1b[39m")
+  }
+
+  fn codify(&self) -> String {
+    "function() { [native code] }".to_string()
+  }
+}
+
+impl fmt::Display for PromiseResolver {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "function() {{ [native code] }}")
+  }
+}
+
+#[derive(Clone)]
+struct ResolverFrame {
+  resolver: PromiseResolver,
+  value: Option<Val>,
+}
+
+impl StackFrameTrait for ResolverFrame {
+  fn write_this(&mut self, _const: bool, _this: Val) -> Result<(), Val> {
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    if self.value.is_none() {
+      self.value = Some(param);
+    }
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    let value = take(&mut self.value).unwrap_or(Val::Undefined);
+
+    match self.resolver.rejects {
+      false => self.resolver.promise.resolve(value),
+      true => self.resolver.promise.reject(value),
+    }
+
+    Ok(FrameStepOk::Pop(CallResult {
+      return_: Val::Undefined,
+      this: Val::Undefined,
+    }))
+  }
+
+  fn apply_call_result(&mut self, _call_result: CallResult) {
+    panic!("Not appropriate for ResolverFrame")
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for ResolverFrame")
+  }
+
+  fn catch_exception(&mut self, _exception: &mut Val) {}
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
+
+/// Creates the promise for Promise.all, which is fulfilled with the values of `promises` once they
+/// have all been fulfilled, or rejected as soon as one of them is rejected.
+pub fn promise_all(promises: Vec<Val>) -> VsPromise {
+  let result = VsPromise::new_pending();
+
+  if promises.is_empty() {
+    result.settle(Ok(Vec::<Val>::new().to_val()));
+    return result;
+  }
+
+  with_current_host(|host| {
+    host.promises.aggregates.insert(
+      result.id,
+      (vec![Val::Undefined; promises.len()], promises.len()),
+    );
+  });
+
+  for (i, promise) in promises.into_iter().enumerate() {
+    when_settled(
+      promise,
+      Reaction {
+        job: ALL_ELEMENT.to_val(),
+        params: vec![result.to_dynamic_val(), Val::Number(i as f64)],
+      },
+    );
+  }
+
+  result
+}
+
+/// Records an element of Promise.all.
+/// Params: result, index, value, rejected.
+static ALL_ELEMENT: NativeFunction = native_fn(|_this, params| {
+  let (result, index, value, rejected) = match params.as_slice() {
+    [result, index, value, rejected] => match VsPromise::from_val(result) {
+      Some(result) => (result, index.to_number() as usize, value.clone(), rejected),
+      None => return Ok(Val::Undefined),
+    },
+    _ => return Ok(Val::Undefined),
+  };
+
+  if rejected.is_truthy() {
+    with_current_host(|host| host.promises.aggregates.remove(&result.id));
+    result.reject(value);
+
+    return Ok(Val::Undefined);
+  }
+
+  let values = with_current_host(|host| {
+    let (values, remaining) = host.promises.aggregates.get_mut(&result.id)?;
+    values[index] = value;
+    *remaining -= 1;
+
+    match *remaining {
+      0 => host
+        .promises
+        .aggregates
+        .remove(&result.id)
+        .map(|(values, _)| values),
+      _ => None,
+    }
+  });
+
+  if let Some(values) = values {
+    result.settle(Ok(values.to_val()));
+  }
+
+  Ok(Val::Undefined)
+});
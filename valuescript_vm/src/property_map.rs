@@ -0,0 +1,334 @@
+use std::{
+  collections::{btree_map::Values, BTreeMap, HashMap},
+  iter::{Chain, Zip},
+  slice,
+};
+
+use crate::rc::{Rc, RefCell, Weak};
+use crate::vs_value::Val;
+
+/// Objects with more keys than this are stored as dictionaries instead of being given a shape.
+const MAX_SHAPE_KEYS: usize = 32;
+
+/// String-keyed property storage that iterates in JavaScript property order: integer-like keys
+/// (array indices) in ascending numeric order, followed by the remaining keys in insertion order.
+///
+/// Objects start out with a shape (hidden class) that is shared with every other object that had
+/// the same keys added in the same order, so they only need to store their values, and comparing
+/// objects with the same shape doesn't need any key lookups. Objects fall back to a dictionary
+/// when a key is removed, when an integer-like key is added, or when they get too big.
+#[derive(Clone, Debug)]
+pub struct PropertyMap(Repr);
+
+#[derive(Clone, Debug)]
+enum Repr {
+  Shaped { shape: Rc<Shape>, values: Vec<Val> },
+  Dictionary(Dictionary),
+}
+
+#[derive(Debug)]
+struct Shape {
+  keys: Vec<String>,
+  indexes: HashMap<String, usize>,
+  transitions: RefCell<HashMap<String, Weak<Shape>>>,
+}
+
+thread_local! {
+  static ROOT_SHAPE: Rc<Shape> = Rc::new(Shape {
+    keys: vec![],
+    indexes: HashMap::new(),
+    transitions: RefCell::new(HashMap::new()),
+  });
+}
+
+impl Shape {
+  /// The shape with `key` added, which is shared by all objects making the same transition.
+  fn with_key(self: &Rc<Shape>, key: &str) -> Option<Rc<Shape>> {
+    if self.keys.len() >= MAX_SHAPE_KEYS {
+      return None;
+    }
+
+    if let Some(shape) = self.transitions.borrow().get(key).and_then(Weak::upgrade) {
+      return Some(shape);
+    }
+
+    let mut keys = self.keys.clone();
+    keys.push(key.to_string());
+
+    let mut indexes = self.indexes.clone();
+    indexes.insert(key.to_string(), self.keys.len());
+
+    let shape = Rc::new(Shape {
+      keys,
+      indexes,
+      transitions: RefCell::new(HashMap::new()),
+    });
+
+    let mut transitions = self.transitions.borrow_mut();
+    transitions.retain(|_, shape| shape.strong_count() > 0);
+    transitions.insert(key.to_string(), Rc::downgrade(&shape));
+
+    Some(shape)
+  }
+}
+
+#[derive(Clone, Default, Debug)]
+struct Dictionary {
+  index_entries: BTreeMap<u32, (String, Val)>,
+  string_entries: BTreeMap<u64, (String, Val)>,
+  string_positions: BTreeMap<String, u64>,
+  next_position: u64,
+}
+
+impl Default for PropertyMap {
+  fn default() -> Self {
+    PropertyMap(Repr::Shaped {
+      shape: ROOT_SHAPE.with(|root| root.clone()),
+      values: vec![],
+    })
+  }
+}
+
+impl PropertyMap {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn len(&self) -> usize {
+    match &self.0 {
+      Repr::Shaped { values, .. } => values.len(),
+      Repr::Dictionary(dict) => dict.index_entries.len() + dict.string_entries.len(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn get(&self, key: &str) -> Option<&Val> {
+    match &self.0 {
+      Repr::Shaped { shape, values } => shape.indexes.get(key).map(|i| &values[*i]),
+      Repr::Dictionary(dict) => dict.get(key),
+    }
+  }
+
+  pub fn get_mut(&mut self, key: &str) -> Option<&mut Val> {
+    match &mut self.0 {
+      Repr::Shaped { shape, values } => match shape.indexes.get(key) {
+        Some(i) => values.get_mut(*i),
+        None => None,
+      },
+      Repr::Dictionary(dict) => dict.get_mut(key),
+    }
+  }
+
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.get(key).is_some()
+  }
+
+  /// Inserts the value, keeping the key's original position if it was already present.
+  pub fn insert(&mut self, key: String, value: Val) -> Option<Val> {
+    if let Repr::Shaped { shape, values } = &mut self.0 {
+      if let Some(i) = shape.indexes.get(&key) {
+        return Some(std::mem::replace(&mut values[*i], value));
+      }
+
+      if to_array_index(&key).is_none() {
+        if let Some(new_shape) = shape.with_key(&key) {
+          *shape = new_shape;
+          values.push(value);
+
+          return None;
+        }
+      }
+    }
+
+    self.dictionary_mut().insert(key, value)
+  }
+
+  pub fn remove(&mut self, key: &str) -> Option<Val> {
+    if let Repr::Shaped { shape, .. } = &self.0 {
+      if !shape.indexes.contains_key(key) {
+        return None;
+      }
+    }
+
+    self.dictionary_mut().remove(key)
+  }
+
+  pub fn iter(&self) -> Iter<'_> {
+    match &self.0 {
+      Repr::Shaped { shape, values } => Iter::Shaped(shape.keys.iter().zip(values.iter())),
+      Repr::Dictionary(dict) => Iter::Dictionary(
+        dict
+          .index_entries
+          .values()
+          .chain(dict.string_entries.values()),
+      ),
+    }
+  }
+
+  pub fn keys(&self) -> impl Iterator<Item = &String> {
+    self.iter().map(|(k, _)| k)
+  }
+
+  pub fn values(&self) -> impl Iterator<Item = &Val> {
+    self.iter().map(|(_, v)| v)
+  }
+
+  /// If both maps have the same shape, returns their values, which are in the same order.
+  pub fn values_if_same_shape<'a>(
+    &'a self,
+    other: &'a PropertyMap,
+  ) -> Option<(&'a [Val], &'a [Val])> {
+    match (&self.0, &other.0) {
+      (
+        Repr::Shaped {
+          shape: left_shape,
+          values: left_values,
+        },
+        Repr::Shaped {
+          shape: right_shape,
+          values: right_values,
+        },
+      ) if Rc::ptr_eq(left_shape, right_shape) => Some((left_values, right_values)),
+      _ => None,
+    }
+  }
+
+  fn dictionary_mut(&mut self) -> &mut Dictionary {
+    if let Repr::Shaped { shape, values } = &mut self.0 {
+      let mut dict = Dictionary::default();
+
+      for (key, value) in shape.keys.iter().zip(std::mem::take(values)) {
+        dict.insert(key.clone(), value);
+      }
+
+      self.0 = Repr::Dictionary(dict);
+    }
+
+    match &mut self.0 {
+      Repr::Dictionary(dict) => dict,
+      Repr::Shaped { .. } => unreachable!(),
+    }
+  }
+}
+
+impl Dictionary {
+  fn get(&self, key: &str) -> Option<&Val> {
+    match to_array_index(key) {
+      Some(i) => self.index_entries.get(&i).map(|(_, v)| v),
+      None => self
+        .string_positions
+        .get(key)
+        .and_then(|pos| self.string_entries.get(pos))
+        .map(|(_, v)| v),
+    }
+  }
+
+  fn get_mut(&mut self, key: &str) -> Option<&mut Val> {
+    match to_array_index(key) {
+      Some(i) => self.index_entries.get_mut(&i).map(|(_, v)| v),
+      None => match self.string_positions.get(key) {
+        Some(pos) => self.string_entries.get_mut(pos).map(|(_, v)| v),
+        None => None,
+      },
+    }
+  }
+
+  fn insert(&mut self, key: String, value: Val) -> Option<Val> {
+    if let Some(i) = to_array_index(&key) {
+      return self
+        .index_entries
+        .insert(i, (key, value))
+        .map(|(_, old)| old);
+    }
+
+    if let Some(existing) = self.get_mut(&key) {
+      return Some(std::mem::replace(existing, value));
+    }
+
+    let pos = self.next_position;
+    self.next_position += 1;
+
+    self.string_positions.insert(key.clone(), pos);
+    self.string_entries.insert(pos, (key, value));
+
+    None
+  }
+
+  fn remove(&mut self, key: &str) -> Option<Val> {
+    match to_array_index(key) {
+      Some(i) => self.index_entries.remove(&i).map(|(_, v)| v),
+      None => {
+        let pos = self.string_positions.remove(key)?;
+        self.string_entries.remove(&pos).map(|(_, v)| v)
+      }
+    }
+  }
+}
+
+type ShapedIter<'a> = Zip<slice::Iter<'a, String>, slice::Iter<'a, Val>>;
+type DictionaryIter<'a> = Chain<Values<'a, u32, (String, Val)>, Values<'a, u64, (String, Val)>>;
+
+pub enum Iter<'a> {
+  Shaped(ShapedIter<'a>),
+  Dictionary(DictionaryIter<'a>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+  type Item = (&'a String, &'a Val);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self {
+      Iter::Shaped(iter) => iter.next(),
+      Iter::Dictionary(iter) => iter.next().map(|(k, v)| (k, v)),
+    }
+  }
+}
+
+impl<'a> IntoIterator for &'a PropertyMap {
+  type Item = (&'a String, &'a Val);
+  type IntoIter = Iter<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl FromIterator<(String, Val)> for PropertyMap {
+  fn from_iter<T: IntoIterator<Item = (String, Val)>>(iter: T) -> Self {
+    let mut map = PropertyMap::new();
+
+    for (key, value) in iter {
+      map.insert(key, value);
+    }
+
+    map
+  }
+}
+
+impl<const N: usize> From<[(String, Val); N]> for PropertyMap {
+  fn from(entries: [(String, Val); N]) -> Self {
+    entries.into_iter().collect()
+  }
+}
+
+/// Returns the numeric value of a key if it is a canonical array index ("0", "1", ... but not
+/// "01" or "4294967295").
+fn to_array_index(key: &str) -> Option<u32> {
+  let bytes = key.as_bytes();
+
+  if bytes.is_empty() || bytes.len() > 10 || (bytes.len() > 1 && bytes[0] == b'0') {
+    return None;
+  }
+
+  if !bytes.iter().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+
+  match key.parse::<u64>() {
+    Ok(i) if i < u32::MAX as u64 => Some(i as u32),
+    _ => None,
+  }
+}
@@ -0,0 +1,64 @@
+//! The shared ownership types used by Vals. These are the usual single-threaded types by default.
+//! With the `sync` feature, they are Arc and a Mutex-based RefCell instead, which makes Vals
+//! `Send + Sync` so that VMs can run on different threads and share their results.
+
+#[cfg(not(feature = "sync"))]
+pub use std::{
+  cell::RefCell,
+  rc::{Rc, Weak},
+};
+
+#[cfg(feature = "sync")]
+pub use std::sync::{Arc as Rc, Weak};
+
+/// Implemented by everything when the `sync` feature is off, and by `Send + Sync` types when it's
+/// on. This is a supertrait of the traits that Vals hold as trait objects.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T: Send + Sync + ?Sized> MaybeSync for T {}
+
+/// A stand-in for std's RefCell based on a Mutex. Unlike RefCell, `borrow` is exclusive, which is
+/// fine because the VM never holds borrows across other borrows of the same cell.
+#[cfg(feature = "sync")]
+#[derive(Default, Debug)]
+pub struct RefCell<T>(std::sync::Mutex<T>);
+
+#[cfg(feature = "sync")]
+impl<T> RefCell<T> {
+  pub fn new(value: T) -> Self {
+    RefCell(std::sync::Mutex::new(value))
+  }
+
+  pub fn borrow(&self) -> std::sync::MutexGuard<'_, T> {
+    self.borrow_mut()
+  }
+
+  pub fn borrow_mut(&self) -> std::sync::MutexGuard<'_, T> {
+    self
+      .0
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+  }
+}
+
+#[cfg(feature = "sync")]
+#[allow(dead_code)]
+fn assert_val_is_send_sync() {
+  fn assert_send_sync<T: Send + Sync>() {}
+  assert_send_sync::<crate::vs_value::Val>();
+}
+
+#[cfg(feature = "sync")]
+#[allow(dead_code)]
+fn assert_virtual_machine_is_send() {
+  fn assert_send<T: Send>() {}
+  assert_send::<crate::VirtualMachine>();
+}
@@ -0,0 +1,1178 @@
+//! A backtracking regular expression engine for RegExp, following JavaScript's syntax.
+//!
+//! Positions are byte offsets into the (utf-8) string, like the indexes used by the other string
+//! methods, and patterns are matched a unicode character at a time.
+//!
+//! Patterns are compiled to instructions that are run with an explicit backtracking stack, so long
+//! inputs don't exhaust the native stack.
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RegexFlags {
+  pub global: bool,
+  pub ignore_case: bool,
+  pub multiline: bool,
+  pub dot_all: bool,
+  pub unicode: bool,
+  pub sticky: bool,
+}
+
+impl RegexFlags {
+  pub fn parse(flags: &str) -> Result<RegexFlags, String> {
+    let mut res = RegexFlags::default();
+
+    for c in flags.chars() {
+      let flag = match c {
+        'g' => &mut res.global,
+        'i' => &mut res.ignore_case,
+        'm' => &mut res.multiline,
+        's' => &mut res.dot_all,
+        'u' => &mut res.unicode,
+        'y' => &mut res.sticky,
+        _ => return Err(format!("Invalid regular expression flags '{}'", flags)),
+      };
+
+      if *flag {
+        return Err(format!("Invalid regular expression flags '{}'", flags));
+      }
+
+      *flag = true;
+    }
+
+    Ok(res)
+  }
+}
+
+impl std::fmt::Display for RegexFlags {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for (enabled, c) in [
+      (self.global, 'g'),
+      (self.ignore_case, 'i'),
+      (self.multiline, 'm'),
+      (self.dot_all, 's'),
+      (self.unicode, 'u'),
+      (self.sticky, 'y'),
+    ] {
+      if enabled {
+        write!(f, "{}", c)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+pub struct Regex {
+  /// The compiled pattern (program 0) and the patterns of its lookarounds.
+  programs: Vec<Vec<Inst>>,
+  slot_count: usize,
+  pub flags: RegexFlags,
+
+  /// The name of each capture group (if it has one), starting with group 1.
+  pub group_names: Vec<Option<String>>,
+}
+
+/// The ranges of the match (index 0) and of each capture group, which are None for groups that
+/// didn't participate.
+#[derive(Clone, Debug)]
+pub struct RegexMatch {
+  pub captures: Vec<Option<(usize, usize)>>,
+}
+
+impl RegexMatch {
+  pub fn start(&self) -> usize {
+    self.captures[0].map_or(0, |(start, _)| start)
+  }
+
+  pub fn end(&self) -> usize {
+    self.captures[0].map_or(0, |(_, end)| end)
+  }
+}
+
+#[derive(Debug)]
+enum Node {
+  Empty,
+  Char(char),
+  Any,
+  Class(Class),
+  Start,
+  End,
+  WordBoundary(bool),
+  Group(Box<Node>, Option<usize>),
+  Backref(usize),
+  Look {
+    node: Box<Node>,
+    ahead: bool,
+    negate: bool,
+  },
+  Concat(Vec<Node>),
+  Alt(Vec<Node>),
+  Repeat {
+    node: Box<Node>,
+    min: usize,
+    max: Option<usize>,
+    greedy: bool,
+  },
+}
+
+#[derive(Debug)]
+struct Class {
+  negate: bool,
+  items: Vec<ClassItem>,
+}
+
+#[derive(Debug)]
+enum ClassItem {
+  Range(char, char),
+  Digit(bool),
+  Word(bool),
+  Space(bool),
+}
+
+impl Regex {
+  pub fn new(source: &str, flags: RegexFlags) -> Result<Regex, String> {
+    let mut parser = Parser {
+      chars: source.chars().collect(),
+      i: 0,
+      group_names: vec![],
+      named_backrefs: vec![],
+    };
+
+    let res = parser.parse_pattern();
+
+    match res {
+      Ok(mut node) => {
+        parser.resolve_named_backrefs(&mut node)?;
+
+        let mut compiler = Compiler {
+          programs: vec![],
+          slot_count: 0,
+        };
+
+        compiler.program(node);
+
+        Ok(Regex {
+          programs: compiler.programs,
+          slot_count: compiler.slot_count,
+          flags,
+          group_names: parser.group_names,
+        })
+      }
+      Err(message) => Err(format!(
+        "Invalid regular expression: /{}/: {}",
+        source, message
+      )),
+    }
+  }
+
+  pub fn group_count(&self) -> usize {
+    self.group_names.len()
+  }
+
+  /// Matches starting exactly at `start`. Errors when matching takes too many steps.
+  pub fn match_at(&self, input: &str, start: usize) -> Result<Option<RegexMatch>, String> {
+    Matcher::new(self, input).match_at(start)
+  }
+
+  /// Finds the first match at or after `start` (or only at `start` for sticky regexes).
+  pub fn find(&self, input: &str, start: usize) -> Result<Option<RegexMatch>, String> {
+    let mut matcher = Matcher::new(self, input);
+    let mut pos = start;
+
+    while pos <= input.len() && !input.is_char_boundary(pos) {
+      pos += 1;
+    }
+
+    if self.flags.sticky {
+      return match pos == start {
+        true => matcher.match_at(pos),
+        false => Ok(None),
+      };
+    }
+
+    while pos <= input.len() {
+      if let Some(m) = matcher.match_at(pos)? {
+        return Ok(Some(m));
+      }
+
+      pos = next_pos(input, pos);
+    }
+
+    Ok(None)
+  }
+
+  /// All the (non-overlapping) matches, like a global regex finds them.
+  pub fn find_all(&self, input: &str) -> Result<Vec<RegexMatch>, String> {
+    let mut matches = vec![];
+    let mut pos = 0;
+
+    while pos <= input.len() {
+      let m = match self.find(input, pos)? {
+        Some(m) => m,
+        None => break,
+      };
+
+      pos = match m.end() == m.start() {
+        true => next_pos(input, m.end()),
+        false => m.end(),
+      };
+
+      matches.push(m);
+    }
+
+    Ok(matches)
+  }
+}
+
+/// The position after the character at `pos` (or past the end of the string).
+pub fn next_pos(input: &str, pos: usize) -> usize {
+  match input[pos..].chars().next() {
+    Some(c) => pos + c.len_utf8(),
+    None => pos + 1,
+  }
+}
+
+struct Parser {
+  chars: Vec<char>,
+  i: usize,
+  group_names: Vec<Option<String>>,
+  named_backrefs: Vec<String>,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.i).copied()
+  }
+
+  fn eat(&mut self, c: char) -> bool {
+    if self.peek() == Some(c) {
+      self.i += 1;
+      return true;
+    }
+
+    false
+  }
+
+  fn eat_str(&mut self, s: &str) -> bool {
+    let len = s.chars().count();
+
+    if self.chars.len() >= self.i + len
+      && s
+        .chars()
+        .eq(self.chars[self.i..self.i + len].iter().copied())
+    {
+      self.i += len;
+      return true;
+    }
+
+    false
+  }
+
+  fn next(&mut self) -> Result<char, String> {
+    let c = self.peek().ok_or("\\ at end of pattern")?;
+    self.i += 1;
+    Ok(c)
+  }
+
+  fn parse_pattern(&mut self) -> Result<Node, String> {
+    let node = self.parse_alt()?;
+
+    if self.peek() == Some(')') {
+      return Err("Unmatched ')'".to_string());
+    }
+
+    Ok(node)
+  }
+
+  fn parse_alt(&mut self) -> Result<Node, String> {
+    let mut alts = vec![self.parse_concat()?];
+
+    while self.eat('|') {
+      alts.push(self.parse_concat()?);
+    }
+
+    Ok(match alts.len() {
+      1 => alts.pop().unwrap(),
+      _ => Node::Alt(alts),
+    })
+  }
+
+  fn parse_concat(&mut self) -> Result<Node, String> {
+    let mut nodes = vec![];
+
+    while let Some(c) = self.peek() {
+      if c == '|' || c == ')' {
+        break;
+      }
+
+      let atom = self.parse_atom()?;
+      nodes.push(self.parse_quantifier(atom)?);
+    }
+
+    Ok(match nodes.len() {
+      0 => Node::Empty,
+      1 => nodes.pop().unwrap(),
+      _ => Node::Concat(nodes),
+    })
+  }
+
+  fn parse_quantifier(&mut self, atom: Node) -> Result<Node, String> {
+    let start = self.i;
+
+    let (min, max) = match self.peek() {
+      Some('*') => (0, None),
+      Some('+') => (1, None),
+      Some('?') => (0, Some(1)),
+      Some('{') => match self.parse_braces() {
+        Some(min_max) => min_max,
+        None => {
+          // Not a quantifier, so the brace is matched literally
+          self.i = start;
+          return Ok(atom);
+        }
+      },
+      _ => return Ok(atom),
+    };
+
+    if self.i == start {
+      self.i += 1;
+    }
+
+    if let Some(max) = max {
+      if max < min {
+        return Err("numbers out of order in {} quantifier".to_string());
+      }
+    }
+
+    let greedy = !self.eat('?');
+
+    if matches!(self.peek(), Some('*' | '+' | '?')) {
+      return Err("Nothing to repeat".to_string());
+    }
+
+    Ok(Node::Repeat {
+      node: Box::new(atom),
+      min,
+      max,
+      greedy,
+    })
+  }
+
+  /// Parses `{n}`, `{n,}` or `{n,m}`.
+  fn parse_braces(&mut self) -> Option<(usize, Option<usize>)> {
+    self.i += 1;
+
+    let min = self.parse_number()?;
+
+    let max = match self.eat(',') {
+      true => match self.peek() {
+        Some('}') => None,
+        _ => Some(self.parse_number()?),
+      },
+      false => Some(min),
+    };
+
+    match self.eat('}') {
+      true => Some((min, max)),
+      false => None,
+    }
+  }
+
+  fn parse_number(&mut self) -> Option<usize> {
+    let start = self.i;
+
+    while matches!(self.peek(), Some('0'..='9')) {
+      self.i += 1;
+    }
+
+    self.chars[start..self.i]
+      .iter()
+      .collect::<String>()
+      .parse()
+      .ok()
+  }
+
+  fn parse_atom(&mut self) -> Result<Node, String> {
+    let c = self.next()?;
+
+    Ok(match c {
+      '^' => Node::Start,
+      '$' => Node::End,
+      '.' => Node::Any,
+      '(' => self.parse_group()?,
+      '[' => Node::Class(self.parse_class()?),
+      '\\' => self.parse_escape()?,
+      '*' | '+' | '?' => return Err("Nothing to repeat".to_string()),
+      '{' => {
+        let after_brace = self.i;
+        self.i -= 1;
+
+        if self.parse_braces().is_some() {
+          return Err("Nothing to repeat".to_string());
+        }
+
+        self.i = after_brace;
+        Node::Char('{')
+      }
+      _ => Node::Char(c),
+    })
+  }
+
+  fn parse_group(&mut self) -> Result<Node, String> {
+    let node = if self.eat_str("?:") {
+      Node::Group(Box::new(self.parse_alt()?), None)
+    } else if self.eat_str("?=") || self.eat_str("?!") {
+      let negate = self.chars[self.i - 1] == '!';
+
+      Node::Look {
+        node: Box::new(self.parse_alt()?),
+        ahead: true,
+        negate,
+      }
+    } else if self.eat_str("?<=") || self.eat_str("?<!") {
+      let negate = self.chars[self.i - 1] == '!';
+
+      Node::Look {
+        node: Box::new(self.parse_alt()?),
+        ahead: false,
+        negate,
+      }
+    } else if self.eat_str("?<") {
+      let name = self.parse_group_name()?;
+
+      if self.group_names.contains(&Some(name.clone())) {
+        return Err("Duplicate capture group name".to_string());
+      }
+
+      self.group_names.push(Some(name));
+      let group_index = self.group_names.len();
+
+      Node::Group(Box::new(self.parse_alt()?), Some(group_index))
+    } else if self.peek() == Some('?') {
+      return Err("Invalid group".to_string());
+    } else {
+      self.group_names.push(None);
+      let group_index = self.group_names.len();
+
+      Node::Group(Box::new(self.parse_alt()?), Some(group_index))
+    };
+
+    match self.eat(')') {
+      true => Ok(node),
+      false => Err("Unterminated group".to_string()),
+    }
+  }
+
+  /// Parses the `name>` of `(?<name>` or `\k<name>`.
+  fn parse_group_name(&mut self) -> Result<String, String> {
+    let mut name = String::new();
+
+    loop {
+      match self.peek() {
+        Some('>') => {
+          self.i += 1;
+          break;
+        }
+        Some(c) if c.is_alphanumeric() || c == '_' || c == '$' => {
+          self.i += 1;
+          name.push(c);
+        }
+        _ => return Err("Invalid capture group name".to_string()),
+      }
+    }
+
+    match name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+      true => Err("Invalid capture group name".to_string()),
+      false => Ok(name),
+    }
+  }
+
+  fn parse_escape(&mut self) -> Result<Node, String> {
+    let c = self.next()?;
+
+    Ok(match c {
+      'd' | 'D' | 'w' | 'W' | 's' | 'S' => Node::Class(Class {
+        negate: false,
+        items: vec![class_escape(c).unwrap()],
+      }),
+      'b' => Node::WordBoundary(false),
+      'B' => Node::WordBoundary(true),
+      '1'..='9' => {
+        self.i -= 1;
+        Node::Backref(self.parse_number().unwrap())
+      }
+      'k' if self.eat('<') => {
+        let name = self.parse_group_name()?;
+        self.named_backrefs.push(name);
+
+        // Resolved to the group index after parsing, since the group can come later
+        Node::Backref(usize::MAX - (self.named_backrefs.len() - 1))
+      }
+      _ => Node::Char(self.parse_char_escape(c)?),
+    })
+  }
+
+  /// The character for an escape like `\n` or `\x41` (after the backslash and `c`).
+  fn parse_char_escape(&mut self, c: char) -> Result<char, String> {
+    Ok(match c {
+      '0' => '\0',
+      'n' => '\n',
+      'r' => '\r',
+      't' => '\t',
+      'v' => '\u{b}',
+      'f' => '\u{c}',
+      'c' => match self.peek() {
+        Some(letter) if letter.is_ascii_alphabetic() => {
+          self.i += 1;
+          ((letter as u8) % 32) as char
+        }
+        _ => '\\',
+      },
+      'x' => self.parse_hex_escape(2).unwrap_or('x'),
+      'u' => match self.eat('{') {
+        true => {
+          let start = self.i;
+
+          while matches!(self.peek(), Some(c) if c.is_ascii_hexdigit()) {
+            self.i += 1;
+          }
+
+          let code = u32::from_str_radix(&self.chars[start..self.i].iter().collect::<String>(), 16)
+            .ok()
+            .and_then(char::from_u32);
+
+          match (code, self.eat('}')) {
+            (Some(c), true) => c,
+            _ => return Err("Invalid Unicode escape".to_string()),
+          }
+        }
+        false => self.parse_hex_escape(4).unwrap_or('u'),
+      },
+      _ => c,
+    })
+  }
+
+  fn parse_hex_escape(&mut self, len: usize) -> Option<char> {
+    let digits = self.chars.get(self.i..self.i + len)?;
+
+    if !digits.iter().all(|c| c.is_ascii_hexdigit()) {
+      return None;
+    }
+
+    let c = char::from_u32(u32::from_str_radix(&digits.iter().collect::<String>(), 16).ok()?)?;
+    self.i += len;
+
+    Some(c)
+  }
+
+  fn parse_class(&mut self) -> Result<Class, String> {
+    let negate = self.eat('^');
+    let mut items = vec![];
+
+    loop {
+      let start = match self.peek() {
+        None => return Err("Unterminated character class".to_string()),
+        Some(']') => {
+          self.i += 1;
+          break;
+        }
+        _ => self.parse_class_atom()?,
+      };
+
+      let is_range =
+        self.peek() == Some('-') && !matches!(self.chars.get(self.i + 1), None | Some(']'));
+
+      if !is_range {
+        items.push(start);
+        continue;
+      }
+
+      self.i += 1;
+      let end = self.parse_class_atom()?;
+
+      match (start, end) {
+        (ClassItem::Range(start, _), ClassItem::Range(end, _)) => {
+          if end < start {
+            return Err("Range out of order in character class".to_string());
+          }
+
+          items.push(ClassItem::Range(start, end));
+        }
+        (start, end) => {
+          // Ranges with escapes like \d just include the - as well
+          items.push(start);
+          items.push(ClassItem::Range('-', '-'));
+          items.push(end);
+        }
+      }
+    }
+
+    Ok(Class { negate, items })
+  }
+
+  fn parse_class_atom(&mut self) -> Result<ClassItem, String> {
+    let c = self.next()?;
+
+    if c != '\\' {
+      return Ok(ClassItem::Range(c, c));
+    }
+
+    let c = self.next()?;
+
+    if let Some(item) = class_escape(c) {
+      return Ok(item);
+    }
+
+    let c = match c {
+      'b' => '\u{8}',
+      '-' => '-',
+      _ => self.parse_char_escape(c)?,
+    };
+
+    Ok(ClassItem::Range(c, c))
+  }
+
+  fn resolve_named_backrefs(&self, node: &mut Node) -> Result<(), String> {
+    match node {
+      Node::Backref(i) if *i > usize::MAX - self.named_backrefs.len() => {
+        let name = &self.named_backrefs[usize::MAX - *i];
+
+        *i = match self
+          .group_names
+          .iter()
+          .position(|group_name| group_name.as_ref() == Some(name))
+        {
+          Some(pos) => pos + 1,
+          None => return Err(format!("Invalid named capture referenced '{}'", name)),
+        };
+      }
+      Node::Group(node, _) | Node::Look { node, .. } | Node::Repeat { node, .. } => {
+        self.resolve_named_backrefs(node)?;
+      }
+      Node::Concat(nodes) | Node::Alt(nodes) => {
+        for node in nodes {
+          self.resolve_named_backrefs(node)?;
+        }
+      }
+      _ => {}
+    }
+
+    Ok(())
+  }
+}
+
+fn class_escape(c: char) -> Option<ClassItem> {
+  Some(match c {
+    'd' => ClassItem::Digit(false),
+    'D' => ClassItem::Digit(true),
+    'w' => ClassItem::Word(false),
+    'W' => ClassItem::Word(true),
+    's' => ClassItem::Space(false),
+    'S' => ClassItem::Space(true),
+    _ => return None,
+  })
+}
+
+/// The most instructions a single search can run before giving up, so that patterns with
+/// catastrophic backtracking throw rather than hang.
+const MAX_STEPS: usize = 10_000_000;
+
+/// An instruction of a compiled regex. Each instruction continues with the next one unless it says
+/// otherwise, and instructions that fail to match backtrack to the most recent alternative.
+#[derive(Debug)]
+enum Inst {
+  Char(char),
+  Any,
+  Class(Class),
+  Start,
+  End,
+  WordBoundary(bool),
+  Backref(usize),
+
+  /// Records the start of a capture group in `slot`.
+  GroupStart(usize),
+
+  /// Captures `group` from the start recorded in `slot` to the current position.
+  GroupEnd {
+    group: usize,
+    slot: usize,
+  },
+
+  /// Continues with the next instruction, and with the instruction at the index when backtracking.
+  Split(usize),
+
+  Jump(usize),
+
+  /// Matches the program at the index, without consuming any input.
+  Look {
+    program: usize,
+    ahead: bool,
+    negate: bool,
+  },
+
+  /// Starts a repetition by resetting its count.
+  RepeatInit {
+    count_slot: usize,
+  },
+
+  /// Either starts another iteration (the next instruction) or finishes the repetition (at `exit`),
+  /// preferring another iteration when greedy.
+  RepeatLoop {
+    count_slot: usize,
+    min: usize,
+    max: Option<usize>,
+    greedy: bool,
+    exit: usize,
+  },
+
+  /// Records where an iteration starts.
+  RepeatBody {
+    start_slot: usize,
+  },
+
+  /// Finishes an iteration and goes back to the RepeatLoop at `loop_`.
+  RepeatEnd {
+    count_slot: usize,
+    start_slot: usize,
+    min: usize,
+    loop_: usize,
+  },
+
+  Match,
+}
+
+struct Compiler {
+  programs: Vec<Vec<Inst>>,
+  slot_count: usize,
+}
+
+impl Compiler {
+  /// Compiles `node` into a new program (ending in Match) and returns its index.
+  fn program(&mut self, node: Node) -> usize {
+    let index = self.programs.len();
+    self.programs.push(vec![]);
+
+    let mut insts = vec![];
+    self.compile(node, &mut insts);
+    insts.push(Inst::Match);
+
+    self.programs[index] = insts;
+
+    index
+  }
+
+  fn allocate_slot(&mut self) -> usize {
+    self.slot_count += 1;
+    self.slot_count - 1
+  }
+
+  fn compile(&mut self, node: Node, insts: &mut Vec<Inst>) {
+    match node {
+      Node::Empty => {}
+      Node::Char(c) => insts.push(Inst::Char(c)),
+      Node::Any => insts.push(Inst::Any),
+      Node::Class(class) => insts.push(Inst::Class(class)),
+      Node::Start => insts.push(Inst::Start),
+      Node::End => insts.push(Inst::End),
+      Node::WordBoundary(negate) => insts.push(Inst::WordBoundary(negate)),
+      Node::Backref(i) => insts.push(Inst::Backref(i)),
+      Node::Group(node, None) => self.compile(*node, insts),
+      Node::Group(node, Some(group)) => {
+        let slot = self.allocate_slot();
+
+        insts.push(Inst::GroupStart(slot));
+        self.compile(*node, insts);
+        insts.push(Inst::GroupEnd { group, slot });
+      }
+      Node::Look {
+        node,
+        ahead,
+        negate,
+      } => {
+        let program = self.program(*node);
+
+        insts.push(Inst::Look {
+          program,
+          ahead,
+          negate,
+        });
+      }
+      Node::Concat(nodes) => {
+        for node in nodes {
+          self.compile(node, insts);
+        }
+      }
+      Node::Alt(mut alts) => {
+        let last = alts.pop();
+        let mut jumps = vec![];
+
+        for alt in alts {
+          let split = insts.len();
+          insts.push(Inst::Split(0));
+
+          self.compile(alt, insts);
+
+          jumps.push(insts.len());
+          insts.push(Inst::Jump(0));
+
+          insts[split] = Inst::Split(insts.len());
+        }
+
+        if let Some(last) = last {
+          self.compile(last, insts);
+        }
+
+        for jump in jumps {
+          insts[jump] = Inst::Jump(insts.len());
+        }
+      }
+      Node::Repeat {
+        node,
+        min,
+        max,
+        greedy,
+      } => {
+        let count_slot = self.allocate_slot();
+        let start_slot = self.allocate_slot();
+
+        insts.push(Inst::RepeatInit { count_slot });
+
+        let loop_ = insts.len();
+        insts.push(Inst::Jump(0));
+
+        insts.push(Inst::RepeatBody { start_slot });
+        self.compile(*node, insts);
+
+        insts.push(Inst::RepeatEnd {
+          count_slot,
+          start_slot,
+          min,
+          loop_,
+        });
+
+        insts[loop_] = Inst::RepeatLoop {
+          count_slot,
+          min,
+          max,
+          greedy,
+          exit: insts.len(),
+        };
+      }
+    }
+  }
+}
+
+/// A change to the matcher's state, which is reverted when backtracking past it.
+enum Undo {
+  Slot(usize, usize),
+  Capture(usize, Option<(usize, usize)>),
+}
+
+struct Matcher<'a> {
+  regex: &'a Regex,
+  input: &'a str,
+  captures: Vec<Option<(usize, usize)>>,
+  slots: Vec<usize>,
+  undo: Vec<Undo>,
+  steps: usize,
+}
+
+impl<'a> Matcher<'a> {
+  fn new(regex: &'a Regex, input: &'a str) -> Matcher<'a> {
+    Matcher {
+      regex,
+      input,
+      captures: vec![],
+      slots: vec![],
+      undo: vec![],
+      steps: 0,
+    }
+  }
+
+  /// Matches starting exactly at `start`. The steps are counted across calls, so a search that
+  /// tries many starting positions shares the same limit.
+  fn match_at(&mut self, start: usize) -> Result<Option<RegexMatch>, String> {
+    self.captures = vec![None; self.regex.group_count() + 1];
+    self.slots = vec![0; self.regex.slot_count];
+    self.undo.clear();
+
+    Ok(self.run(0, start, None)?.map(|end| {
+      let mut captures = std::mem::take(&mut self.captures);
+      captures[0] = Some((start, end));
+
+      RegexMatch { captures }
+    }))
+  }
+
+  fn char_at(&self, pos: usize) -> Option<char> {
+    self.input.get(pos..)?.chars().next()
+  }
+
+  fn char_before(&self, pos: usize) -> Option<char> {
+    self.input.get(..pos)?.chars().next_back()
+  }
+
+  fn set_slot(&mut self, slot: usize, value: usize) {
+    self.undo.push(Undo::Slot(slot, self.slots[slot]));
+    self.slots[slot] = value;
+  }
+
+  fn set_capture(&mut self, group: usize, value: Option<(usize, usize)>) {
+    self.undo.push(Undo::Capture(group, self.captures[group]));
+    self.captures[group] = value;
+  }
+
+  fn undo_to(&mut self, len: usize) {
+    while self.undo.len() > len {
+      match self.undo.pop() {
+        Some(Undo::Slot(slot, value)) => self.slots[slot] = value,
+        Some(Undo::Capture(group, value)) => self.captures[group] = value,
+        None => {}
+      }
+    }
+  }
+
+  /// Runs `program` from `start`, backtracking with an explicit stack. Gives the end position of
+  /// the first way of matching (which must be `end` if provided), or None (having undone any
+  /// captures) when there isn't one.
+  fn run(
+    &mut self,
+    program: usize,
+    start: usize,
+    end: Option<usize>,
+  ) -> Result<Option<usize>, String> {
+    let regex = self.regex;
+    let insts = &regex.programs[program];
+
+    // The instruction, position and undo length to resume from when backtracking
+    let mut backtrack = Vec::<(usize, usize, usize)>::new();
+    let undo_start = self.undo.len();
+
+    let mut pc = 0;
+    let mut pos = start;
+
+    loop {
+      self.steps += 1;
+
+      if self.steps > MAX_STEPS {
+        return Err("Maximum regular expression steps exceeded".to_string());
+      }
+
+      let next = match &insts[pc] {
+        Inst::Char(expected) => match self.char_at(pos) {
+          Some(c) if chars_eq(c, *expected, regex.flags.ignore_case) => {
+            pos += c.len_utf8();
+            Some(pc + 1)
+          }
+          _ => None,
+        },
+        Inst::Any => match self.char_at(pos) {
+          Some(c) if regex.flags.dot_all || !is_line_terminator(c) => {
+            pos += c.len_utf8();
+            Some(pc + 1)
+          }
+          _ => None,
+        },
+        Inst::Class(class) => match self.char_at(pos) {
+          Some(c) if class_matches(class, c, regex.flags.ignore_case) => {
+            pos += c.len_utf8();
+            Some(pc + 1)
+          }
+          _ => None,
+        },
+        Inst::Start => {
+          let at_start = match self.char_before(pos) {
+            None => true,
+            Some(c) => regex.flags.multiline && is_line_terminator(c),
+          };
+
+          at_start.then_some(pc + 1)
+        }
+        Inst::End => {
+          let at_end = match self.char_at(pos) {
+            None => true,
+            Some(c) => regex.flags.multiline && is_line_terminator(c),
+          };
+
+          at_end.then_some(pc + 1)
+        }
+        Inst::WordBoundary(negate) => {
+          let before = self.char_before(pos).is_some_and(is_word_char);
+          let after = self.char_at(pos).is_some_and(is_word_char);
+
+          ((before != after) != *negate).then_some(pc + 1)
+        }
+        Inst::Backref(i) => match self.captures.get(*i).copied().flatten() {
+          None => Some(pc + 1),
+          Some((capture_start, capture_end)) => {
+            let mut end_pos = pos;
+
+            let matched = self.input[capture_start..capture_end]
+              .chars()
+              .all(|expected| match self.char_at(end_pos) {
+                Some(c) if chars_eq(c, expected, regex.flags.ignore_case) => {
+                  end_pos += c.len_utf8();
+                  true
+                }
+                _ => false,
+              });
+
+            match matched {
+              true => {
+                pos = end_pos;
+                Some(pc + 1)
+              }
+              false => None,
+            }
+          }
+        },
+        Inst::GroupStart(slot) => {
+          self.set_slot(*slot, pos);
+          Some(pc + 1)
+        }
+        Inst::GroupEnd { group, slot } => {
+          self.set_capture(*group, Some((self.slots[*slot], pos)));
+          Some(pc + 1)
+        }
+        Inst::Split(alt) => {
+          backtrack.push((*alt, pos, self.undo.len()));
+          Some(pc + 1)
+        }
+        Inst::Jump(target) => Some(*target),
+        Inst::Look {
+          program,
+          ahead,
+          negate,
+        } => {
+          let matched = match ahead {
+            true => self.run(*program, pos, None)?.is_some(),
+            false => {
+              let mut look_start = pos;
+
+              loop {
+                if self.run(*program, look_start, Some(pos))?.is_some() {
+                  break true;
+                }
+
+                match self.char_before(look_start) {
+                  Some(c) => look_start -= c.len_utf8(),
+                  None => break false,
+                }
+              }
+            }
+          };
+
+          (matched != *negate).then_some(pc + 1)
+        }
+        Inst::RepeatInit { count_slot } => {
+          self.set_slot(*count_slot, 0);
+          Some(pc + 1)
+        }
+        Inst::RepeatLoop {
+          count_slot,
+          min,
+          max,
+          greedy,
+          exit,
+        } => {
+          let count = self.slots[*count_slot];
+          let can_stop = count >= *min;
+          let can_continue = max.is_none_or(|max| count < max);
+
+          let (preferred, other) = match greedy {
+            true => (can_continue.then_some(pc + 1), can_stop.then_some(*exit)),
+            false => (can_stop.then_some(*exit), can_continue.then_some(pc + 1)),
+          };
+
+          match (preferred, other) {
+            (Some(preferred), Some(other)) => {
+              backtrack.push((other, pos, self.undo.len()));
+              Some(preferred)
+            }
+            (preferred, other) => preferred.or(other),
+          }
+        }
+        Inst::RepeatBody { start_slot } => {
+          self.set_slot(*start_slot, pos);
+          Some(pc + 1)
+        }
+        Inst::RepeatEnd {
+          count_slot,
+          start_slot,
+          min,
+          loop_,
+        } => {
+          let count = self.slots[*count_slot];
+
+          // Repeating an empty match would never end
+          match pos == self.slots[*start_slot] && count >= *min {
+            true => None,
+            false => {
+              self.set_slot(*count_slot, count + 1);
+              Some(*loop_)
+            }
+          }
+        }
+        Inst::Match => match end.is_none_or(|end| pos == end) {
+          true => return Ok(Some(pos)),
+          false => None,
+        },
+      };
+
+      match next {
+        Some(next) => pc = next,
+        None => match backtrack.pop() {
+          Some((next, saved_pos, undo_len)) => {
+            self.undo_to(undo_len);
+            pc = next;
+            pos = saved_pos;
+          }
+          None => {
+            self.undo_to(undo_start);
+            return Ok(None);
+          }
+        },
+      }
+    }
+  }
+}
+
+fn chars_eq(a: char, b: char, ignore_case: bool) -> bool {
+  a == b || (ignore_case && fold_case(a) == fold_case(b))
+}
+
+fn fold_case(c: char) -> char {
+  let mut lower = c.to_lowercase();
+
+  match (lower.next(), lower.next()) {
+    (Some(lower), None) => lower,
+    _ => c,
+  }
+}
+
+fn class_matches(class: &Class, c: char, ignore_case: bool) -> bool {
+  let item_matches = |c: char| {
+    class.items.iter().any(|item| match item {
+      ClassItem::Range(start, end) => *start <= c && c <= *end,
+      ClassItem::Digit(negate) => c.is_ascii_digit() != *negate,
+      ClassItem::Word(negate) => is_word_char(c) != *negate,
+      ClassItem::Space(negate) => (c.is_whitespace() || c == '\u{feff}') != *negate,
+    })
+  };
+
+  let matched = match ignore_case {
+    true => {
+      item_matches(c) || c.to_lowercase().any(item_matches) || c.to_uppercase().any(item_matches)
+    }
+    false => item_matches(c),
+  };
+
+  matched != class.negate
+}
+
+fn is_word_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_line_terminator(c: char) -> bool {
+  matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
@@ -0,0 +1,158 @@
+use std::cell::Cell;
+
+use crate::builtins::internal_error_builtin::ToInternalError;
+use crate::builtins::range_error_builtin::ToRangeError;
+use crate::counting_allocator::allocated_bytes;
+use crate::vs_value::Val;
+
+/// How much of the Rust stack the calls that run nested inside of a step (see
+/// `VirtualMachine::call_nested`) can use. Each of them uses a lot more of it in debug builds, so
+/// this is limited by size rather than by the number of calls. It keeps programs like
+/// `const o = { get a() { return this.a; } }; o.a` from overflowing the stack, and is well below
+/// the stack size of threads (2MiB by default) and wasm (1MiB).
+const MAX_NESTED_STACK_BYTES: usize = 512 * 1024;
+
+/// What's left of the limits of the run in progress. Calls that run nested inside of a step (like
+/// getters, `valueOf` and lazy values) use a separate stack, so they share the budget through here
+/// to make sure they can't be used to get around the limits.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RunBudget {
+  pub steps_left: Option<usize>,
+  pub memory_limit: Option<usize>,
+  pub memory_baseline: usize,
+  pub max_stack_depth: Option<usize>,
+
+  /// The number of frames on the stacks of the running VMs.
+  pub stack_depth: usize,
+
+  /// The position of the stack where the outermost nested call started, or 0 if there aren't any.
+  pub nested_stack_start: usize,
+}
+
+thread_local! {
+  static CURRENT_BUDGET: Cell<RunBudget> = Cell::new(RunBudget::default());
+}
+
+impl RunBudget {
+  pub fn new(
+    step_limit: Option<usize>,
+    memory_limit: Option<usize>,
+    max_stack_depth: Option<usize>,
+  ) -> Self {
+    RunBudget {
+      steps_left: step_limit,
+      memory_limit,
+      memory_baseline: match memory_limit {
+        Some(_) => allocated_bytes(),
+        None => 0,
+      },
+      max_stack_depth,
+      stack_depth: 0,
+      nested_stack_start: 0,
+    }
+  }
+
+  /// Whether steps need to be charged to the budget, see `charge_step`.
+  pub fn is_limited(&self) -> bool {
+    self.steps_left.is_some() || self.memory_limit.is_some()
+  }
+}
+
+/// Runs `f` with `budget` as the budget of the run in progress.
+pub(crate) fn with_run_budget<T>(budget: RunBudget, f: impl FnOnce() -> T) -> T {
+  let prev = CURRENT_BUDGET.with(|current| current.replace(budget));
+  let res = f();
+  CURRENT_BUDGET.with(|current| current.set(prev));
+
+  res
+}
+
+pub(crate) fn current_run_budget() -> RunBudget {
+  CURRENT_BUDGET.with(|current| current.get())
+}
+
+/// Runs a call nested inside of the current step, which continues to use the current budget.
+/// Throws a RangeError instead if the nested calls are using too much of the stack.
+pub(crate) fn with_nested_call<T>(f: impl FnOnce() -> Result<T, Val>) -> Result<T, Val> {
+  let prev = current_run_budget();
+
+  let stack_marker = 0u8;
+  let stack_pos = &stack_marker as *const u8 as usize;
+
+  let nested_stack_start = match prev.nested_stack_start {
+    0 => stack_pos,
+    start => start,
+  };
+
+  // The stack grows downwards
+  if nested_stack_start.saturating_sub(stack_pos) > MAX_NESTED_STACK_BYTES {
+    return Err("Maximum call stack size exceeded".to_range_error());
+  }
+
+  CURRENT_BUDGET.with(|current| {
+    current.set(RunBudget {
+      nested_stack_start,
+      ..prev
+    })
+  });
+
+  let res = f();
+
+  // The steps used by the nested call stay charged, but its frames are gone
+  CURRENT_BUDGET.with(|current| {
+    current.set(RunBudget {
+      stack_depth: prev.stack_depth,
+      nested_stack_start: prev.nested_stack_start,
+      ..current.get()
+    })
+  });
+
+  res
+}
+
+/// Charges a step to the current budget, or throws if the budget has run out.
+pub(crate) fn charge_step() -> Result<(), Val> {
+  let mut budget = current_run_budget();
+
+  if let Some(steps_left) = &mut budget.steps_left {
+    if *steps_left == 0 {
+      return Err("step limit reached".to_internal_error());
+    }
+
+    *steps_left -= 1;
+  }
+
+  if let Some(limit) = budget.memory_limit {
+    if allocated_bytes().saturating_sub(budget.memory_baseline) > limit {
+      return Err("memory limit reached".to_internal_error());
+    }
+  }
+
+  CURRENT_BUDGET.with(|current| current.set(budget));
+
+  Ok(())
+}
+
+/// Throws a RangeError if pushing another frame would exceed the maximum stack depth.
+pub(crate) fn check_stack_depth() -> Result<(), Val> {
+  let budget = current_run_budget();
+
+  match budget.max_stack_depth {
+    Some(max) if budget.stack_depth >= max => {
+      Err("Maximum call stack size exceeded".to_range_error())
+    }
+    _ => Ok(()),
+  }
+}
+
+/// Records a frame being pushed onto (or popped from) the stack of a running VM.
+pub(crate) fn add_stack_depth(delta: isize) {
+  CURRENT_BUDGET.with(|current| {
+    let budget = current.get();
+
+    current.set(RunBudget {
+      stack_depth: budget.stack_depth.saturating_add_signed(delta),
+      ..budget
+    })
+  });
+}
@@ -0,0 +1,48 @@
+use std::collections::VecDeque;
+
+use crate::vs_value::Val;
+
+/// A queued call of `fn_` with `params`.
+#[derive(Clone)]
+pub struct Task {
+  pub fn_: Val,
+  pub params: Vec<Val>,
+}
+
+/// Queues of work to run after the current call completes. Microtasks (such as promise reactions
+/// and `queueMicrotask` callbacks) always run before the next macrotask, and macrotasks are posted
+/// by the host (timers, I/O callbacks, etc).
+///
+/// Nothing here runs on its own. The host drives it with `VirtualMachine::run_until_idle`.
+#[derive(Default)]
+pub struct Scheduler {
+  microtasks: VecDeque<Task>,
+  macrotasks: VecDeque<Task>,
+}
+
+impl Scheduler {
+  pub fn queue_microtask(&mut self, fn_: Val, params: Vec<Val>) {
+    self.microtasks.push_back(Task { fn_, params });
+  }
+
+  pub fn post_macrotask(&mut self, fn_: Val, params: Vec<Val>) {
+    self.macrotasks.push_back(Task { fn_, params });
+  }
+
+  pub fn is_idle(&self) -> bool {
+    self.microtasks.is_empty() && self.macrotasks.is_empty()
+  }
+
+  pub fn next_microtask(&mut self) -> Option<Task> {
+    self.microtasks.pop_front()
+  }
+
+  /// The next task to run: the oldest microtask, or the oldest macrotask if there are no
+  /// microtasks.
+  pub fn next_task(&mut self) -> Option<Task> {
+    self
+      .microtasks
+      .pop_front()
+      .or_else(|| self.macrotasks.pop_front())
+  }
+}
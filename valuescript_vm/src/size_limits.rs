@@ -0,0 +1,47 @@
+use std::cell::Cell;
+
+use crate::builtins::range_error_builtin::ToRangeError;
+use crate::virtual_machine_builder::Limits;
+use crate::vs_value::Val;
+
+thread_local! {
+  static CURRENT_LIMITS: Cell<Limits> = Cell::new(Limits::default());
+}
+
+/// Applies the size limits in `limits` to the values created while `f` runs.
+pub(crate) fn with_size_limits<T>(limits: Limits, f: impl FnOnce() -> T) -> T {
+  let prev = CURRENT_LIMITS.with(|current| current.replace(limits));
+  let res = f();
+  CURRENT_LIMITS.with(|current| current.set(prev));
+
+  res
+}
+
+fn current_limits() -> Limits {
+  CURRENT_LIMITS.with(|current| current.get())
+}
+
+/// Checks a string length (in bytes) before creating the string.
+pub fn check_string_length(len: usize) -> Result<(), Val> {
+  match current_limits().max_string_length {
+    Some(max) if len > max => Err("Invalid string length".to_range_error()),
+    _ => Ok(()),
+  }
+}
+
+/// Checks an array length before creating or extending the array.
+pub fn check_array_length(len: usize) -> Result<(), Val> {
+  match current_limits().max_array_length {
+    Some(max) if len > max => Err("Invalid array length".to_range_error()),
+    _ => Ok(()),
+  }
+}
+
+/// Checks the number of bits in the magnitude of a bigint before creating it. Callers pass an
+/// upper bound when the exact size is only known after an expensive operation.
+pub fn check_bigint_bits(bits: u64) -> Result<(), Val> {
+  match current_limits().max_bigint_bits {
+    Some(max) if bits > max => Err("Maximum BigInt size exceeded".to_range_error()),
+    _ => Ok(()),
+  }
+}
@@ -1,3 +1,5 @@
+use crate::rc::MaybeSync;
+
 use super::vs_value::Val;
 
 pub type StackFrame = Box<dyn StackFrameTrait>;
@@ -14,11 +16,14 @@ pub enum FrameStepOk {
   Push(StackFrame),
   Yield(Val),
   YieldStar(Val),
+
+  /// Suspends the body of an async generator until the value has settled.
+  Await(Val),
 }
 
 pub type FrameStepResult = Result<FrameStepOk, Val>;
 
-pub trait StackFrameTrait {
+pub trait StackFrameTrait: MaybeSync {
   fn write_this(&mut self, const_: bool, this: Val) -> Result<(), Val>;
   fn write_param(&mut self, param: Val);
   fn step(&mut self) -> FrameStepResult;
@@ -26,6 +31,13 @@ pub trait StackFrameTrait {
   fn get_call_result(&mut self) -> CallResult;
   fn catch_exception(&mut self, exception: &mut Val);
   fn clone_to_stack_frame(&self) -> StackFrame;
+
+  /// Receives the value passed to `next(value)` when the generator running this frame resumes it
+  /// after a yield. Frames that don't yield can ignore it.
+  fn resume_with(&mut self, _value: Val) {}
+
+  /// Receives the class being constructed when this frame runs a constructor (for `new.target`).
+  fn write_new_target(&mut self, _new_target: Val) {}
 }
 
 impl Clone for StackFrame {
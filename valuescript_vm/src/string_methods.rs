@@ -1,16 +1,30 @@
-use std::{rc::Rc, str::Chars};
+use std::str::Chars;
 
 use crate::{
-  builtins::internal_error_builtin::ToInternalError,
+  builtins::{internal_error_builtin::ToInternalError, regexp_builtin::is_regexp},
   helpers::{to_wrapping_index, to_wrapping_index_clamped},
   iteration::string_iterator::StringIterator,
   native_function::{native_fn, NativeFunction},
+  operations::to_u32,
+  size_limits::check_string_length,
+  string_regex_methods::{split_regex, MATCH, MATCH_ALL, REPLACE, REPLACE_ALL, SEARCH},
+  utf16_string_methods::{get_utf16_string_method, is_utf16, op_sub_string_utf16},
+  vs_string::VsString,
   vs_symbol::VsSymbol,
   vs_value::{ToDynamicVal, ToVal, Val},
   ValTrait,
 };
 
-pub fn op_sub_string(string_data: &Rc<str>, subscript: &Val) -> Val {
+/// Reads `string_data[subscript]`. Indexes and `length` count UTF-8 code units (bytes) unless the VM
+/// uses StringEncoding::Utf16, and indexes that are out of range (or that aren't canonical array
+/// indexes) give undefined.
+pub fn op_sub_string(string_data: &VsString, subscript: &Val) -> Val {
+  if is_utf16() {
+    if let Some(res) = op_sub_string_utf16(string_data, subscript) {
+      return res;
+    }
+  }
+
   if let Some(subscript) = subscript.to_index() {
     let string_bytes = string_data.as_bytes();
 
@@ -27,6 +41,13 @@ pub fn op_sub_string(string_data: &Rc<str>, subscript: &Val) -> Val {
   if let Val::Symbol(subscript) = subscript {
     match subscript {
       VsSymbol::ITERATOR => return VALUES.to_val(),
+      VsSymbol::ASYNC_ITERATOR
+      | VsSymbol::TO_PRIMITIVE
+      | VsSymbol::HAS_INSTANCE
+      | VsSymbol::TO_STRING_TAG
+      | VsSymbol::GENERATOR_RETURN
+      | VsSymbol::CLASS_NAME
+      | VsSymbol::Registered(_) => return Val::Undefined,
     }
   }
 
@@ -35,12 +56,15 @@ pub fn op_sub_string(string_data: &Rc<str>, subscript: &Val) -> Val {
 
   return match method_str {
     "length" => Val::Number(string_data.as_bytes().len() as f64),
-    _ => get_string_method(method_str),
+    _ => match get_string_method(method_str) {
+      Some(method) => Val::Static(method),
+      None => Val::Undefined,
+    },
   };
 }
 
-pub fn get_string_method(method: &str) -> Val {
-  // Not supported: charAt, charCodeAt.
+pub fn get_string_method(method: &str) -> Option<&'static dyn ValTrait> {
+  // Not supported (unless the VM uses StringEncoding::Utf16): charAt, charCodeAt.
   //
   // These methods are inherently about utf16, which is not how strings work in ValueScript. They
   // also have some particularly strange behavior, like:
@@ -51,7 +75,13 @@ pub fn get_string_method(method: &str) -> Val {
   // utf8, there's more license to reinterpret strings and leave out things like this which aren't
   // desirable.
 
-  match method {
+  if is_utf16() {
+    if let Some(method) = get_utf16_string_method(method) {
+      return Some(method);
+    }
+  }
+
+  Some(match method {
     "at" => &AT,
     // "charAt" => &CHAR_AT,
     // "charCodeAt" => &CHAR_CODE_AT,
@@ -62,15 +92,15 @@ pub fn get_string_method(method: &str) -> Val {
     "indexOf" => &INDEX_OF,
     "lastIndexOf" => &LAST_INDEX_OF,
     "localeCompare" => &TODO_LOCALE, // (TODO)
-    "match" => &TODO_REGEXES,        // (TODO: regex)
-    "matchAll" => &TODO_REGEXES,     // (TODO: regex)
-    "normalize" => &NORMALIZE,       // (TODO)
+    "match" => &MATCH,
+    "matchAll" => &MATCH_ALL,
+    "normalize" => &NORMALIZE, // (TODO)
     "padEnd" => &PAD_END,
     "padStart" => &PAD_START,
     "repeat" => &REPEAT,
-    "replace" => &TODO_REGEXES,    // (TODO: regex)
-    "replaceAll" => &TODO_REGEXES, // (TODO: regex)
-    "search" => &TODO_REGEXES,     // (TODO: regex)
+    "replace" => &REPLACE,
+    "replaceAll" => &REPLACE_ALL,
+    "search" => &SEARCH,
     "slice" => &SLICE,
     "split" => &SPLIT,
     "startsWith" => &STARTS_WITH,
@@ -84,9 +114,8 @@ pub fn get_string_method(method: &str) -> Val {
     "trimEnd" => &TRIM_END,
     "trimStart" => &TRIM_START,
     "valueOf" => &VALUE_OF,
-    _ => return Val::Undefined,
-  }
-  .to_val()
+    _ => return None,
+  })
 }
 
 static AT: NativeFunction = native_fn(|this, params| {
@@ -114,11 +143,18 @@ static CODE_POINT_AT: NativeFunction = native_fn(|this, params| {
       let string_bytes = string_data.as_bytes();
 
       let index = match params.get(0) {
-        Some(i) => match i.to_index() {
-          None => return Ok(Val::Undefined),
-          Some(i) => i,
-        },
-        _ => return Ok(Val::Undefined),
+        Some(i) => {
+          let i = i.to_number().trunc();
+
+          if i.is_nan() {
+            0
+          } else if i < 0.0 {
+            return Ok(Val::Undefined);
+          } else {
+            i as usize
+          }
+        }
+        None => 0,
       };
 
       match code_point_at(string_bytes, string_bytes.len(), index) {
@@ -142,6 +178,8 @@ static CONCAT: NativeFunction = native_fn(|this, params| {
         };
       }
 
+      check_string_length(result.len())?;
+
       result.to_val()
     }
     _ => return Err("string indirection".to_internal_error()),
@@ -287,14 +325,6 @@ static TODO_LOCALE: NativeFunction = native_fn(|this, _params| {
   }
 });
 
-static TODO_REGEXES: NativeFunction = native_fn(|this, _params| {
-  // TODO: Ok(...)
-  match this.get() {
-    Val::String(_string_data) => Err("TODO: regexes".to_internal_error()),
-    _ => Err("string indirection".to_internal_error()),
-  }
-});
-
 static NORMALIZE: NativeFunction = native_fn(|this, _params| {
   // TODO: Ok(...)
   match this.get() {
@@ -322,6 +352,8 @@ static PAD_END: NativeFunction = native_fn(|this, params| {
         return Ok(Val::String(string_data.clone()));
       }
 
+      check_string_length(target_length)?;
+
       let mut string = string_data.to_string();
 
       let pad_string = match params.get(1) {
@@ -374,6 +406,8 @@ static PAD_START: NativeFunction = native_fn(|this, params| {
         return Ok(Val::String(string_data.clone()));
       }
 
+      check_string_length(target_length)?;
+
       let pad_string = match params.get(1) {
         Some(s) => s.to_string(),
         _ => " ".to_string(),
@@ -423,6 +457,8 @@ static REPEAT: NativeFunction = native_fn(|this, params| {
         _ => return Ok(Val::String(string_data.clone())),
       };
 
+      check_string_length(string_data.len().saturating_mul(count))?;
+
       let mut result = String::new();
 
       for _ in 0..count {
@@ -469,18 +505,23 @@ static SLICE: NativeFunction = native_fn(|this, params| {
 static SPLIT: NativeFunction = native_fn(|this, params| {
   Ok(match this.get() {
     Val::String(string_data) => {
-      let separator = match params.get(0) {
-        Some(s) => s.to_string(), // TODO: Regexes
-        None => return Ok(Val::String(string_data.clone())),
+      let limit = match params.get(1) {
+        None | Some(Val::Undefined) => u32::MAX as usize,
+        Some(l) => to_u32(l.to_number()) as usize,
       };
 
-      let limit = match params.get(1) {
-        // FIXME: to_index isn't quite right
-        Some(l) => match l.to_index() {
-          Some(i) => i,
-          None => string_data.as_bytes().len() + 1,
-        },
-        None => string_data.as_bytes().len() + 1,
+      let separator = match params.get(0) {
+        None | Some(Val::Undefined) => {
+          return Ok(
+            match limit {
+              0 => vec![],
+              _ => vec![Val::String(string_data.clone())],
+            }
+            .to_val(),
+          );
+        }
+        Some(s) if is_regexp(s) => return split_regex(string_data.as_str(), s, limit),
+        Some(s) => s.to_string(),
       };
 
       let mut result = Vec::<Val>::new();
@@ -0,0 +1,451 @@
+use crate::builtins::error_builtin::ToError;
+use crate::builtins::internal_error_builtin::ToInternalError;
+use crate::builtins::regexp_builtin::{is_regexp, match_array, to_regex, to_search_error};
+use crate::builtins::type_error_builtin::ToTypeError;
+use crate::native_frame_function::NativeFrameFunction;
+use crate::native_function::{native_fn, NativeFunction, ThisWrapper};
+use crate::property_map::PropertyMap;
+use crate::regex::{next_pos, Regex, RegexFlags, RegexMatch};
+use crate::stack_frame::{CallResult, FrameStepOk, FrameStepResult, StackFrame, StackFrameTrait};
+use crate::vs_object::VsObject;
+use crate::vs_value::{LoadFunctionResult, ToVal, Val, ValTrait};
+
+/// The regex for the argument of a string method like `match`, which is either a RegExp or a
+/// string that is used as a pattern (with `flags`).
+fn regex_param(param: Option<&Val>, flags: &str) -> Result<Regex, Val> {
+  let flags = RegexFlags::parse(flags).map_err(|message| message.to_error())?;
+
+  match param {
+    Some(regexp) if is_regexp(regexp) => Ok(to_regex(regexp)?.0),
+    None | Some(Val::Undefined) => Regex::new("(?:)", flags).map_err(|message| message.to_error()),
+    Some(pattern) => Regex::new(&pattern.to_string(), flags).map_err(|message| message.to_error()),
+  }
+}
+
+pub static MATCH: NativeFunction = native_fn(|this, params| {
+  let input = match this.get() {
+    Val::String(string_data) => string_data.as_str(),
+    _ => return Err("string indirection".to_internal_error()),
+  };
+
+  let regex = regex_param(params.first(), "")?;
+
+  if !regex.flags.global {
+    return Ok(match regex.find(input, 0).map_err(to_search_error)? {
+      Some(m) => match_array(input, &m),
+      None => Val::Null,
+    });
+  }
+
+  let matches = regex.find_all(input).map_err(to_search_error)?;
+
+  if matches.is_empty() {
+    return Ok(Val::Null);
+  }
+
+  Ok(
+    matches
+      .iter()
+      .map(|m| input[m.start()..m.end()].to_val())
+      .collect::<Vec<Val>>()
+      .to_val(),
+  )
+});
+
+/// Unlike JS, this gives an array of the matches rather than an iterator.
+pub static MATCH_ALL: NativeFunction = native_fn(|this, params| {
+  let input = match this.get() {
+    Val::String(string_data) => string_data.as_str(),
+    _ => return Err("string indirection".to_internal_error()),
+  };
+
+  let regex = regex_param(params.first(), "g")?;
+
+  if !regex.flags.global {
+    return Err("matchAll must be called with a global RegExp".to_type_error());
+  }
+
+  Ok(
+    regex
+      .find_all(input)
+      .map_err(to_search_error)?
+      .iter()
+      .map(|m| match_array(input, m))
+      .collect::<Vec<Val>>()
+      .to_val(),
+  )
+});
+
+pub static SEARCH: NativeFunction = native_fn(|this, params| {
+  let input = match this.get() {
+    Val::String(string_data) => string_data.as_str(),
+    _ => return Err("string indirection".to_internal_error()),
+  };
+
+  let regex = regex_param(params.first(), "")?;
+
+  Ok(Val::Number(
+    match regex.find(input, 0).map_err(to_search_error)? {
+      Some(m) => m.start() as f64,
+      None => -1.0,
+    },
+  ))
+});
+
+/// Implements `split` when the separator is a RegExp. Like JS, the capture groups of each separator
+/// are included in the result.
+pub fn split_regex(input: &str, separator: &Val, limit: usize) -> Result<Val, Val> {
+  let (mut regex, _) = to_regex(separator)?;
+  regex.flags.sticky = true;
+
+  let mut result = Vec::<Val>::new();
+
+  if limit == 0 {
+    return Ok(result.to_val());
+  }
+
+  if input.is_empty() {
+    if regex.match_at(input, 0).map_err(to_search_error)?.is_none() {
+      result.push(input.to_val());
+    }
+
+    return Ok(result.to_val());
+  }
+
+  let mut part_start = 0;
+  let mut pos = 0;
+
+  while pos < input.len() {
+    let m = match regex.match_at(input, pos).map_err(to_search_error)? {
+      Some(m) if m.end() != part_start && m.end() <= input.len() => m,
+      _ => {
+        pos = next_pos(input, pos);
+        continue;
+      }
+    };
+
+    result.push(input[part_start..pos].to_val());
+
+    if result.len() == limit {
+      return Ok(result.to_val());
+    }
+
+    for capture in &m.captures[1..] {
+      result.push(match capture {
+        Some((start, end)) => input[*start..*end].to_val(),
+        None => Val::Undefined,
+      });
+
+      if result.len() == limit {
+        return Ok(result.to_val());
+      }
+    }
+
+    part_start = m.end();
+    pos = part_start;
+  }
+
+  result.push(input[part_start..].to_val());
+
+  Ok(result.to_val())
+}
+
+pub static REPLACE: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(ReplaceFrame::new(false)),
+};
+
+pub static REPLACE_ALL: NativeFrameFunction = NativeFrameFunction {
+  make_frame: || Box::new(ReplaceFrame::new(true)),
+};
+
+/// Implements `replace` and `replaceAll`. This is a stack frame because the replacement can be a
+/// function, which is called for each match.
+#[derive(Clone)]
+struct ReplaceFrame {
+  all: bool,
+  this: Val,
+  params: Vec<Val>,
+
+  input: String,
+  matches: Option<Vec<RegexMatch>>,
+  group_names: Vec<Option<String>>,
+  replacements: Vec<String>,
+}
+
+impl ReplaceFrame {
+  fn new(all: bool) -> ReplaceFrame {
+    ReplaceFrame {
+      all,
+      this: Val::Undefined,
+      params: vec![],
+      input: String::new(),
+      matches: None,
+      group_names: vec![],
+      replacements: vec![],
+    }
+  }
+
+  fn find_matches(&mut self) -> Result<Vec<RegexMatch>, Val> {
+    self.input = match &self.this {
+      Val::String(string_data) => string_data.to_string(),
+      _ => return Err("string indirection".to_internal_error()),
+    };
+
+    let pattern = self.params.first().cloned().unwrap_or(Val::Undefined);
+
+    if is_regexp(&pattern) {
+      let (regex, _) = to_regex(&pattern)?;
+
+      if self.all && !regex.flags.global {
+        return Err("replaceAll must be called with a global RegExp".to_type_error());
+      }
+
+      self.group_names = regex.group_names.clone();
+
+      let matches = match regex.flags.global {
+        true => regex.find_all(&self.input),
+        false => regex.find(&self.input, 0).map(|m| m.into_iter().collect()),
+      };
+
+      return matches.map_err(to_search_error);
+    }
+
+    let pattern = pattern.to_string();
+
+    let starts = self
+      .input
+      .match_indices(pattern.as_str())
+      .map(|(start, _)| start)
+      .take(match self.all {
+        true => usize::MAX,
+        false => 1,
+      });
+
+    Ok(
+      starts
+        .map(|start| RegexMatch {
+          captures: vec![Some((start, start + pattern.len()))],
+        })
+        .collect(),
+    )
+  }
+
+  /// The arguments for a replacement function: the match, the capture groups, the index, the
+  /// input, and the named groups (when there are any).
+  fn replacer_args(&self, m: &RegexMatch) -> Vec<Val> {
+    let mut args = match_array(&self.input, m)
+      .as_array_data()
+      .map(|array_data| array_data.elements.clone())
+      .unwrap_or_default();
+
+    args.push(Val::Number(m.start() as f64));
+    args.push(self.input.clone().to_val());
+
+    if self.group_names.iter().any(|name| name.is_some()) {
+      args.push(self.named_groups(m));
+    }
+
+    args
+  }
+
+  fn named_groups(&self, m: &RegexMatch) -> Val {
+    let mut string_map = PropertyMap::default();
+
+    for (i, name) in self.group_names.iter().enumerate() {
+      if let Some(name) = name {
+        let value = match m.captures[i + 1] {
+          Some((start, end)) => self.input[start..end].to_val(),
+          None => Val::Undefined,
+        };
+
+        string_map.insert(name.clone(), value);
+      }
+    }
+
+    VsObject {
+      string_map,
+      symbol_map: Default::default(),
+      prototype: None,
+    }
+    .to_val()
+  }
+
+  /// Expands the patterns in a replacement string, like `$&` for the match and `$1` for the first
+  /// capture group.
+  fn expand_replacement(&self, template: &str, m: &RegexMatch) -> String {
+    let capture = |i: usize| match m.captures.get(i).copied().flatten() {
+      Some((start, end)) => &self.input[start..end],
+      None => "",
+    };
+
+    let group_count = m.captures.len() - 1;
+    let has_named_groups = self.group_names.iter().any(|name| name.is_some());
+
+    let mut res = String::new();
+    let mut rest = template;
+
+    while let Some(dollar_pos) = rest.find('$') {
+      res.push_str(&rest[..dollar_pos]);
+      rest = &rest[dollar_pos + 1..];
+
+      let digits = rest
+        .chars()
+        .take(2)
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+
+      if let Some(c) = rest.chars().next() {
+        match c {
+          '$' => {
+            res.push('$');
+            rest = &rest[1..];
+            continue;
+          }
+          '&' => {
+            res.push_str(capture(0));
+            rest = &rest[1..];
+            continue;
+          }
+          '`' => {
+            res.push_str(&self.input[..m.start()]);
+            rest = &rest[1..];
+            continue;
+          }
+          '\'' => {
+            res.push_str(&self.input[m.end()..]);
+            rest = &rest[1..];
+            continue;
+          }
+          '<' if has_named_groups => {
+            if let Some(close) = rest.find('>') {
+              let name = &rest[1..close];
+
+              if let Some(i) = self
+                .group_names
+                .iter()
+                .position(|group_name| group_name.as_deref() == Some(name))
+              {
+                res.push_str(capture(i + 1));
+              }
+
+              rest = &rest[close + 1..];
+              continue;
+            }
+          }
+          _ => {}
+        }
+      }
+
+      // Two digit group numbers are used when that group exists, like JS
+      let group = (1..=digits.len()).rev().find_map(|len| {
+        let i = digits[..len].parse::<usize>().ok()?;
+
+        match i >= 1 && i <= group_count {
+          true => Some((i, len)),
+          false => None,
+        }
+      });
+
+      match group {
+        Some((i, len)) => {
+          res.push_str(capture(i));
+          rest = &rest[len..];
+        }
+        None => res.push('$'),
+      }
+    }
+
+    res.push_str(rest);
+
+    res
+  }
+
+  fn result(&self, matches: &[RegexMatch]) -> String {
+    let mut res = String::new();
+    let mut pos = 0;
+
+    for (m, replacement) in matches.iter().zip(self.replacements.iter()) {
+      res.push_str(&self.input[pos..m.start()]);
+      res.push_str(replacement);
+      pos = m.end();
+    }
+
+    res.push_str(&self.input[pos..]);
+
+    res
+  }
+}
+
+impl StackFrameTrait for ReplaceFrame {
+  fn write_this(&mut self, _const: bool, this: Val) -> Result<(), Val> {
+    self.this = this;
+    Ok(())
+  }
+
+  fn write_param(&mut self, param: Val) {
+    self.params.push(param);
+  }
+
+  fn step(&mut self) -> FrameStepResult {
+    let matches = match self.matches.take() {
+      Some(matches) => matches,
+      None => self.find_matches()?,
+    };
+
+    let m = match matches.get(self.replacements.len()) {
+      Some(m) => m.clone(),
+      None => {
+        return Ok(FrameStepOk::Pop(CallResult {
+          return_: self.result(&matches).to_val(),
+          this: self.this.clone(),
+        }));
+      }
+    };
+
+    self.matches = Some(matches);
+
+    let replacer = self.params.get(1).cloned().unwrap_or(Val::Undefined);
+
+    match replacer.load_function() {
+      LoadFunctionResult::NotAFunction => {
+        let replacement = self.expand_replacement(&replacer.to_string(), &m);
+        self.replacements.push(replacement);
+
+        Ok(FrameStepOk::Continue)
+      }
+      LoadFunctionResult::NativeFunction(native_fn) => {
+        let replacement = native_fn(
+          ThisWrapper::new(true, &mut Val::Undefined),
+          self.replacer_args(&m),
+        )?;
+
+        self.replacements.push(replacement.to_string());
+
+        Ok(FrameStepOk::Continue)
+      }
+      LoadFunctionResult::StackFrame(mut new_frame) => {
+        new_frame.write_this(true, Val::Undefined)?;
+
+        for arg in self.replacer_args(&m) {
+          new_frame.write_param(arg);
+        }
+
+        Ok(FrameStepOk::Push(new_frame))
+      }
+    }
+  }
+
+  fn apply_call_result(&mut self, call_result: CallResult) {
+    self.replacements.push(call_result.return_.to_string());
+  }
+
+  fn get_call_result(&mut self) -> CallResult {
+    panic!("Not appropriate for ReplaceFrame")
+  }
+
+  fn catch_exception(&mut self, _exception: &mut Val) {}
+
+  fn clone_to_stack_frame(&self) -> StackFrame {
+    Box::new(self.clone())
+  }
+}
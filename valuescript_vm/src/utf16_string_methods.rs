@@ -0,0 +1,246 @@
+use crate::{
+  builtins::internal_error_builtin::ToInternalError,
+  helpers::{to_wrapping_index, to_wrapping_index_clamped},
+  host::{with_current_host, StringEncoding},
+  native_function::{native_fn, NativeFunction},
+  vs_string::VsString,
+  vs_value::{ToVal, Val},
+  ValTrait,
+};
+
+/// Whether the running VM measures strings in UTF-16 code units.
+pub fn is_utf16() -> bool {
+  with_current_host(|host| host.string_encoding == StringEncoding::Utf16)
+}
+
+/// Reads `string_data[subscript]` when the VM uses StringEncoding::Utf16. Returns None when the
+/// subscript isn't an index or `length`, so the caller can fall back to the usual lookup.
+pub fn op_sub_string_utf16(string_data: &VsString, subscript: &Val) -> Option<Val> {
+  if let Some(index) = subscript.to_index() {
+    return Some(match to_units(string_data).get(index) {
+      Some(unit) => from_units(&[*unit]),
+      None => Val::Undefined,
+    });
+  }
+
+  match subscript {
+    Val::String(key) if key.as_str() == "length" => {
+      Some(Val::Number(string_data.encode_utf16().count() as f64))
+    }
+    _ => None,
+  }
+}
+
+/// The methods that behave differently when strings are measured in UTF-16 code units.
+pub fn get_utf16_string_method(method: &str) -> Option<&'static dyn ValTrait> {
+  Some(match method {
+    "at" => &AT,
+    "charAt" => &CHAR_AT,
+    "charCodeAt" => &CHAR_CODE_AT,
+    "codePointAt" => &CODE_POINT_AT,
+    "indexOf" => &INDEX_OF,
+    "lastIndexOf" => &LAST_INDEX_OF,
+    "slice" => &SLICE,
+    "substring" => &SUBSTRING,
+    _ => return None,
+  })
+}
+
+static AT: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      match to_wrapping_index(params.first(), units.len()) {
+        Some(i) => from_units(&units[i..i + 1]),
+        None => Val::Undefined,
+      }
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static CHAR_AT: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      match unit_index(params.first(), units.len()) {
+        Some(i) => from_units(&units[i..i + 1]),
+        None => "".to_val(),
+      }
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static CHAR_CODE_AT: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      match unit_index(params.first(), units.len()) {
+        Some(i) => Val::Number(units[i] as f64),
+        None => Val::Number(f64::NAN),
+      }
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static CODE_POINT_AT: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      let i = match unit_index(params.first(), units.len()) {
+        Some(i) => i,
+        None => return Ok(Val::Undefined),
+      };
+
+      let code_point = match char::decode_utf16(units[i..].iter().copied()).next() {
+        Some(Ok(c)) => c as u32,
+        _ => units[i] as u32,
+      };
+
+      Val::Number(code_point as f64)
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static INDEX_OF: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      let search_units = match params.first() {
+        Some(s) => s.to_string().encode_utf16().collect::<Vec<u16>>(),
+        None => "undefined".encode_utf16().collect(),
+      };
+
+      let start = clamp_index(to_integer_or_infinity(params.get(1)), units.len());
+
+      if search_units.len() > units.len() {
+        return Ok(Val::Number(-1.0));
+      }
+
+      match (start..=units.len() - search_units.len())
+        .find(|i| units[*i..].starts_with(&search_units))
+      {
+        Some(i) => Val::Number(i as f64),
+        None => Val::Number(-1.0),
+      }
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static LAST_INDEX_OF: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      let search_units = match params.first() {
+        Some(s) => s.to_string().encode_utf16().collect::<Vec<u16>>(),
+        None => "undefined".encode_utf16().collect(),
+      };
+
+      if search_units.len() > units.len() {
+        return Ok(Val::Number(-1.0));
+      }
+
+      // Unlike the other positions, NaN means searching from the end
+      let pos = match params.get(1).map(|p| p.to_number()) {
+        Some(p) if !p.is_nan() => p.trunc(),
+        _ => f64::INFINITY,
+      };
+
+      let start = std::cmp::min(
+        clamp_index(pos, units.len()),
+        units.len() - search_units.len(),
+      );
+
+      match (0..=start)
+        .rev()
+        .find(|i| units[*i..].starts_with(&search_units))
+      {
+        Some(i) => Val::Number(i as f64),
+        None => Val::Number(-1.0),
+      }
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static SLICE: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      let start = match params.first() {
+        None => 0,
+        Some(v) => to_wrapping_index_clamped(v, units.len()) as usize,
+      };
+
+      let end = match params.get(1) {
+        None | Some(Val::Undefined) => units.len(),
+        Some(v) => to_wrapping_index_clamped(v, units.len()) as usize,
+      };
+
+      match start < end {
+        true => from_units(&units[start..end]),
+        false => "".to_val(),
+      }
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+static SUBSTRING: NativeFunction = native_fn(|this, params| {
+  Ok(match this.get() {
+    Val::String(string_data) => {
+      let units = to_units(string_data);
+
+      let start = clamp_index(to_integer_or_infinity(params.first()), units.len());
+
+      let end = match params.get(1) {
+        None | Some(Val::Undefined) => units.len(),
+        Some(v) => clamp_index(to_integer_or_infinity(Some(v)), units.len()),
+      };
+
+      from_units(&units[start.min(end)..start.max(end)])
+    }
+    _ => return Err("string indirection".to_internal_error()),
+  })
+});
+
+fn to_units(string_data: &VsString) -> Vec<u16> {
+  string_data.encode_utf16().collect()
+}
+
+fn from_units(units: &[u16]) -> Val {
+  String::from_utf16_lossy(units).to_val()
+}
+
+/// ToIntegerOrInfinity from the spec, where a missing argument (like NaN) is 0.
+fn to_integer_or_infinity(val: Option<&Val>) -> f64 {
+  match val.map(|v| v.to_number()) {
+    Some(x) if !x.is_nan() => x.trunc(),
+    _ => 0.0,
+  }
+}
+
+fn clamp_index(x: f64, len: usize) -> usize {
+  x.max(0.0).min(len as f64) as usize
+}
+
+/// The position used by charAt and friends, which don't wrap negative indexes.
+fn unit_index(val: Option<&Val>, len: usize) -> Option<usize> {
+  let i = to_integer_or_infinity(val);
+
+  match i >= 0.0 && i < len as f64 {
+    true => Some(i as usize),
+    false => None,
+  }
+}
@@ -0,0 +1,416 @@
+//! Structural diffs between Vals, for sending the changes to a value rather than the whole value
+//! (eg to clients that keep a copy of some state).
+//!
+//! Arrays and objects are compared property by property, and the changes are reported at the
+//! deepest path where they happen. Values are immutable, so unchanged parts of a value are usually
+//! shared with the value it was derived from, which lets them be skipped without comparing their
+//! contents.
+
+use crate::{
+  operations::op_triple_eq_impl,
+  rc::Rc,
+  vs_array::VsArray,
+  vs_object::VsObject,
+  vs_value::{ToVal, Val},
+};
+
+/// A step into a value: a property of an object or an element of an array.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+  Key(String),
+  Index(usize),
+}
+
+/// One of the changes that turn a value into another.
+#[derive(Clone, Debug)]
+pub enum Change {
+  /// The value at `path` was added or replaced by `value`.
+  Set { path: Vec<PathSegment>, value: Val },
+
+  /// The property at `path` was removed from its object.
+  Remove { path: Vec<PathSegment> },
+
+  /// The array at `path` was shortened to `len` elements.
+  Truncate { path: Vec<PathSegment>, len: usize },
+}
+
+/// The changes that turn `before` into `after`. Applying them in order to a copy of `before`
+/// produces `after`.
+pub fn diff(before: &Val, after: &Val) -> Vec<Change> {
+  let mut changes = Vec::<Change>::new();
+  diff_at(&mut vec![], before, after, &mut changes);
+
+  changes
+}
+
+/// The value at `path` within `val`, or undefined if there isn't one.
+pub fn sub_path(val: &Val, path: &[PathSegment]) -> Val {
+  let mut current = val.clone();
+
+  for segment in path {
+    current = match (&current, segment) {
+      (Val::Object(object), PathSegment::Key(key)) => {
+        object.string_map.get(key).cloned().unwrap_or_default()
+      }
+      (Val::Array(array), PathSegment::Index(i)) => {
+        array.elements.get(*i).cloned().unwrap_or_default()
+      }
+      _ => return Val::Undefined,
+    };
+  }
+
+  match current {
+    Val::Void => Val::Undefined,
+    current => current,
+  }
+}
+
+/// Applies `changes` (see `diff`) to `val`.
+pub fn apply(val: &Val, changes: &[Change]) -> Val {
+  changes
+    .iter()
+    .fold(val.clone(), |val, change| match change {
+      Change::Set { path, value } => update(&val, path, &mut |_| Some(value.clone())),
+      Change::Remove { path } => update(&val, path, &mut |_| None),
+      Change::Truncate { path, len } => update(&val, path, &mut |current| match current {
+        Val::Array(array) => Some(VsArray::from(array.elements[..*len].to_vec()).to_val()),
+        current => Some(current),
+      }),
+    })
+}
+
+fn diff_at(path: &mut Vec<PathSegment>, before: &Val, after: &Val, changes: &mut Vec<Change>) {
+  match (before, after) {
+    (Val::Array(before), Val::Array(after)) => {
+      if !Rc::ptr_eq(before, after) {
+        diff_arrays(path, before, after, changes);
+      }
+    }
+    (Val::Object(before), Val::Object(after)) if same_prototype(before, after) => {
+      if !Rc::ptr_eq(before, after) {
+        diff_objects(path, before, after, changes);
+      }
+    }
+    _ => {
+      if !same_value(before, after) {
+        changes.push(Change::Set {
+          path: path.clone(),
+          value: after.clone(),
+        });
+      }
+    }
+  }
+}
+
+fn diff_arrays(
+  path: &mut Vec<PathSegment>,
+  before: &VsArray,
+  after: &VsArray,
+  changes: &mut Vec<Change>,
+) {
+  for (i, (before, after)) in before.elements.iter().zip(&after.elements).enumerate() {
+    path.push(PathSegment::Index(i));
+    diff_at(path, before, after, changes);
+    path.pop();
+  }
+
+  if after.elements.len() < before.elements.len() {
+    changes.push(Change::Truncate {
+      path: path.clone(),
+      len: after.elements.len(),
+    });
+  }
+
+  for (i, element) in after
+    .elements
+    .iter()
+    .enumerate()
+    .skip(before.elements.len())
+  {
+    path.push(PathSegment::Index(i));
+
+    changes.push(Change::Set {
+      path: path.clone(),
+      value: element.clone(),
+    });
+
+    path.pop();
+  }
+}
+
+fn diff_objects(
+  path: &mut Vec<PathSegment>,
+  before: &VsObject,
+  after: &VsObject,
+  changes: &mut Vec<Change>,
+) {
+  // Symbol keys can't be described by a path, so objects with different ones are replaced
+  if !same_symbol_map(before, after) {
+    changes.push(Change::Set {
+      path: path.clone(),
+      value: Val::Object(Rc::new(after.clone())),
+    });
+
+    return;
+  }
+
+  for key in before.string_map.keys() {
+    if !after.string_map.contains_key(key) {
+      path.push(PathSegment::Key(key.clone()));
+      changes.push(Change::Remove { path: path.clone() });
+      path.pop();
+    }
+  }
+
+  for (key, after_value) in after.string_map.iter() {
+    path.push(PathSegment::Key(key.clone()));
+
+    match before.string_map.get(key) {
+      Some(before_value) => diff_at(path, before_value, after_value, changes),
+      None => changes.push(Change::Set {
+        path: path.clone(),
+        value: after_value.clone(),
+      }),
+    }
+
+    path.pop();
+  }
+}
+
+fn same_prototype(before: &VsObject, after: &VsObject) -> bool {
+  match (&before.prototype, &after.prototype) {
+    (None, None) => true,
+    (Some(before), Some(after)) => same_value(before, after),
+    _ => false,
+  }
+}
+
+fn same_symbol_map(before: &VsObject, after: &VsObject) -> bool {
+  before.symbol_map.len() == after.symbol_map.len()
+    && before.symbol_map.iter().zip(&after.symbol_map).all(
+      |((before_key, before), (after_key, after))| {
+        before_key == after_key && same_value(before, after)
+      },
+    )
+}
+
+fn same_value(before: &Val, after: &Val) -> bool {
+  match (before, after) {
+    (Val::Void | Val::Undefined, Val::Void | Val::Undefined) => true,
+
+    // Unlike ===, NaN is unchanged when it stays NaN
+    (Val::Number(before), Val::Number(after)) => {
+      before == after || (before.is_nan() && after.is_nan())
+    }
+
+    (before, after) => op_triple_eq_impl(before, after).unwrap_or(false),
+  }
+}
+
+/// Replaces the value at `path` within `val` with the result of `f`, or removes it if `f` returns
+/// None.
+fn update(val: &Val, path: &[PathSegment], f: &mut dyn FnMut(Val) -> Option<Val>) -> Val {
+  let (segment, rest) = match path.split_first() {
+    Some(split) => split,
+    None => return f(val.clone()).unwrap_or_default(),
+  };
+
+  match (val, segment) {
+    (Val::Object(object), PathSegment::Key(key)) => {
+      let mut object = (**object).clone();
+      let current = object.string_map.get(key).cloned().unwrap_or_default();
+
+      let updated = match rest.is_empty() {
+        true => f(current),
+        false => Some(update(&current, rest, f)),
+      };
+
+      match updated {
+        Some(updated) => {
+          object.string_map.insert(key.clone(), updated);
+        }
+        None => {
+          object.string_map.remove(key);
+        }
+      }
+
+      object.to_val()
+    }
+    (Val::Array(array), PathSegment::Index(i)) => {
+      let mut elements = array.elements.clone();
+
+      if elements.len() <= *i {
+        elements.resize(*i + 1, Val::Void);
+      }
+
+      elements[*i] = match rest.is_empty() {
+        true => f(elements[*i].clone()).unwrap_or_default(),
+        false => update(&elements[*i], rest, f),
+      };
+
+      VsArray::from(elements).to_val()
+    }
+    _ => val.clone(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::vs_value::ValTrait;
+
+  fn object(entries: Vec<(&str, Val)>) -> Val {
+    VsObject {
+      string_map: entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect(),
+      symbol_map: Default::default(),
+      prototype: None,
+    }
+    .to_val()
+  }
+
+  fn array(elements: Vec<Val>) -> Val {
+    VsArray::from(elements).to_val()
+  }
+
+  fn key(key: &str) -> PathSegment {
+    PathSegment::Key(key.to_string())
+  }
+
+  fn describe(changes: &[Change]) -> Vec<String> {
+    let path = |path: &Vec<PathSegment>| {
+      path
+        .iter()
+        .map(|segment| match segment {
+          PathSegment::Key(key) => key.clone(),
+          PathSegment::Index(i) => i.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+    };
+
+    changes
+      .iter()
+      .map(|change| match change {
+        Change::Set { path: p, value } => format!("set {} {}", path(p), value.codify()),
+        Change::Remove { path: p } => format!("remove {}", path(p)),
+        Change::Truncate { path: p, len } => format!("truncate {} {}", path(p), len),
+      })
+      .collect()
+  }
+
+  fn check_apply(before: &Val, after: &Val, changes: &[Change]) {
+    assert_eq!(apply(before, changes).codify(), after.codify());
+  }
+
+  #[test]
+  fn unchanged_values() {
+    let state = object(vec![
+      ("count", 1.0.to_val()),
+      ("items", array(vec!["a".to_val()])),
+      ("ratio", f64::NAN.to_val()),
+    ]);
+
+    // Equal but not shared
+    let copy = object(vec![
+      ("count", 1.0.to_val()),
+      ("items", array(vec!["a".to_val()])),
+      ("ratio", f64::NAN.to_val()),
+    ]);
+
+    assert!(diff(&state, &state).is_empty());
+    assert!(diff(&state, &copy).is_empty());
+    assert!(diff(&Val::Void, &Val::Undefined).is_empty());
+  }
+
+  #[test]
+  fn object_changes() {
+    let before = object(vec![
+      ("name", "todo".to_val()),
+      ("done", false.to_val()),
+      ("tags", object(vec![("urgent", true.to_val())])),
+    ]);
+
+    let after = object(vec![
+      ("name", "todo".to_val()),
+      ("tags", object(vec![("urgent", false.to_val())])),
+      ("due", 3.0.to_val()),
+    ]);
+
+    let changes = diff(&before, &after);
+
+    assert_eq!(
+      describe(&changes),
+      vec!["remove done", "set tags.urgent false", "set due 3"]
+    );
+
+    check_apply(&before, &after, &changes);
+  }
+
+  #[test]
+  fn array_changes() {
+    let before = array(vec![1.0.to_val(), 2.0.to_val(), 3.0.to_val()]);
+    let longer = array(vec![1.0.to_val(), 5.0.to_val(), 3.0.to_val(), 4.0.to_val()]);
+    let shorter = array(vec![0.0.to_val()]);
+
+    let changes = diff(&before, &longer);
+    assert_eq!(describe(&changes), vec!["set 1 5", "set 3 4"]);
+    check_apply(&before, &longer, &changes);
+
+    let changes = diff(&before, &shorter);
+    assert_eq!(describe(&changes), vec!["set 0 0", "truncate  1"]);
+    check_apply(&before, &shorter, &changes);
+  }
+
+  #[test]
+  fn replaced_values() {
+    let before = object(vec![("value", array(vec![1.0.to_val()]))]);
+    let after = object(vec![("value", object(vec![("0", 1.0.to_val())]))]);
+
+    let changes = diff(&before, &after);
+    assert_eq!(describe(&changes), vec![r#"set value {"0":1}"#]);
+    check_apply(&before, &after, &changes);
+
+    let changes = diff(&1.0.to_val(), &"1".to_val());
+    assert_eq!(describe(&changes), vec![r#"set  "1""#]);
+    check_apply(&1.0.to_val(), &"1".to_val(), &changes);
+  }
+
+  #[test]
+  fn nested_paths() {
+    let before = object(vec![(
+      "todos",
+      array(vec![object(vec![("title", "a".to_val())])]),
+    )]);
+
+    let after = object(vec![(
+      "todos",
+      array(vec![
+        object(vec![("title", "b".to_val())]),
+        object(vec![("title", "c".to_val())]),
+      ]),
+    )]);
+
+    let changes = diff(&before, &after);
+
+    assert_eq!(
+      describe(&changes),
+      vec![r#"set todos.0.title "b""#, r#"set todos.1 {"title":"c"}"#]
+    );
+
+    check_apply(&before, &after, &changes);
+
+    assert_eq!(
+      sub_path(&after, &[key("todos"), PathSegment::Index(1), key("title")]).codify(),
+      r#""c""#
+    );
+
+    assert_eq!(
+      sub_path(&after, &[key("missing"), key("title")]).codify(),
+      "undefined"
+    );
+  }
+}
@@ -1,16 +1,36 @@
-use std::rc::Rc;
-
 use crate::builtins::internal_error_builtin::ToInternalError;
+use crate::builtins::type_error_builtin::ToTypeError;
 use crate::bytecode::Bytecode;
 use crate::bytecode::DecoderMaker;
+use crate::embedding::{read_exports, FromVal, ToParams};
 use crate::first_stack_frame::FirstStackFrame;
+use crate::host::{
+  with_current_host, with_host, Clock, EntropySource, Host, Locale, RandomSource, SeededRandom,
+  StringEncoding,
+};
+use crate::native_function::ThisWrapper;
+use crate::promise::VsPromise;
+use crate::rc::Rc;
+use crate::run_budget::{
+  add_stack_depth, charge_step, check_stack_depth, current_run_budget, with_nested_call,
+  with_run_budget, RunBudget,
+};
+use crate::size_limits::with_size_limits;
 use crate::stack_frame::FrameStepOk;
-use crate::stack_frame::StackFrame;
+use crate::stack_frame::{CallResult, StackFrame};
+use crate::virtual_machine_builder::{Limits, VirtualMachineBuilder};
 use crate::vs_value::{LoadFunctionResult, Val, ValTrait};
 
 pub struct VirtualMachine {
   pub frame: StackFrame,
   pub stack: Vec<StackFrame>,
+  pub host: Host,
+  pub exports: Vec<(String, Val)>,
+  pub loaded: Option<Rc<Bytecode>>,
+  pub limits: Limits,
+
+  /// The number of steps (instructions) executed by this VM so far.
+  pub step_count: u64,
 }
 
 impl Default for VirtualMachine {
@@ -18,20 +38,75 @@ impl Default for VirtualMachine {
     VirtualMachine {
       frame: Box::new(FirstStackFrame::new()),
       stack: Default::default(),
+      host: Default::default(),
+      exports: Default::default(),
+      loaded: None,
+      limits: Default::default(),
+      step_count: 0,
     }
   }
 }
 
 impl VirtualMachine {
+  pub fn builder() -> VirtualMachineBuilder {
+    VirtualMachineBuilder::default()
+  }
+
+  /// Resets `Math.random` to a deterministic sequence determined by `seed`.
+  pub fn seed_random(&mut self, seed: u64) {
+    self.host.random = Box::new(SeededRandom::new(seed));
+  }
+
+  pub fn set_random_source(&mut self, random: Box<dyn RandomSource>) {
+    self.host.random = random;
+  }
+
+  pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+    self.host.clock = clock;
+  }
+
+  pub fn set_entropy_source(&mut self, entropy: Option<Box<dyn EntropySource>>) {
+    self.host.entropy = entropy;
+  }
+
+  pub fn set_locale(&mut self, locale: Box<dyn Locale>) {
+    self.host.locale = locale;
+  }
+
+  pub fn set_string_encoding(&mut self, string_encoding: StringEncoding) {
+    self.host.string_encoding = string_encoding;
+  }
+
   pub fn run(
     &mut self,
     bytecode: Rc<Bytecode>,
     step_limit: Option<usize>,
     params: &[Val],
   ) -> Result<Val, Val> {
-    let mut bd = bytecode.decoder(0);
+    let mut host = std::mem::take(&mut self.host);
+    let limits = self.limits;
+
+    let res = with_host(&mut host, || {
+      with_size_limits(limits, || self.run_impl(bytecode, step_limit, params))
+    });
+
+    self.host = host;
 
-    let main_fn = bd.decode_val(&mut Vec::new());
+    #[cfg(feature = "arena")]
+    crate::arena::release_arena();
+
+    res
+  }
+
+  fn run_impl(
+    &mut self,
+    bytecode: Rc<Bytecode>,
+    step_limit: Option<usize>,
+    params: &[Val],
+  ) -> Result<Val, Val> {
+    let mut bd = bytecode.decoder(bytecode.start);
+
+    let main_fn = bd.decode_val(&mut Vec::new())?;
 
     let mut frame = match main_fn.load_function() {
       LoadFunctionResult::StackFrame(f) => f,
@@ -42,34 +117,273 @@ impl VirtualMachine {
       frame.write_param(p.clone());
     }
 
-    self.push(frame);
+    let res = self.run_frame(frame, step_limit)?.return_;
 
-    match step_limit {
-      Some(step_limit) => {
-        let mut step_count = 0;
+    self.run_microtasks(step_limit)?;
 
-        while step_count < step_limit {
-          self.step()?;
-          step_count += 1;
+    // A program whose main function is async gives the result of its promise
+    match VsPromise::from_val(&res).and_then(|promise| promise.settled()) {
+      Some(settled) => settled,
+      None => Ok(res),
+    }
+  }
 
-          if self.stack.is_empty() {
-            return Ok(self.frame.get_call_result().return_);
+  /// Runs the queued microtasks (including the ones they queue), like JS does when a script
+  /// completes. Macrotasks are left for the host to run with `run_until_idle`.
+  fn run_microtasks(&mut self, step_limit: Option<usize>) -> Result<(), Val> {
+    while let Some(task) = with_current_host(|host| host.scheduler.next_microtask()) {
+      match task.fn_.load_function() {
+        LoadFunctionResult::StackFrame(mut frame) => {
+          for p in task.params {
+            frame.write_param(p);
           }
+
+          self.run_frame(frame, step_limit)?;
+        }
+        LoadFunctionResult::NativeFunction(native_fn) => {
+          native_fn(ThisWrapper::new(true, &mut Val::Undefined), task.params)?;
         }
+        LoadFunctionResult::NotAFunction => return Err("task is not a function".to_type_error()),
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Registers independently compiled (unlinked) bytecode as the module `id`, so that imports of
+  /// it can be resolved when they are used. Relative imports are resolved against the id of the
+  /// importing module, so ids are usually paths like `/src/lib.ts`.
+  ///
+  /// Imports are resolved once per bytecode instance, so bytecode from `Bytecode::shared` keeps the
+  /// modules it was first linked with.
+  pub fn register_module(&mut self, id: &str, bytecode: Rc<Bytecode>) {
+    self.host.modules.register(id, bytecode);
+  }
+
+  /// Loads `bytecode` so that its exports can be listed with `export_names` and called with
+  /// `call`. The bytecode can either be linked (like the output of `compile`), or import modules
+  /// that have been registered with `register_module`.
+  pub fn load(&mut self, bytecode: Rc<Bytecode>) -> Result<(), Val> {
+    let mut host = std::mem::take(&mut self.host);
+    let res = with_host(&mut host, || read_exports(&bytecode));
+    self.host = host;
+
+    self.exports = res?;
+    self.loaded = Some(bytecode);
+    Ok(())
+  }
+
+  /// Swaps the registered module `id` for updated bytecode (hot module reload) while the host
+  /// keeps its state.
+  ///
+  /// Values decoded from the registered modules and the loaded bytecode are discarded, so their
+  /// imports pick up the new version of the module, and the exports of the loaded bytecode are
+  /// read again. If the new version exports `migrate`, it is called with `state` so that it can
+  /// update the shape of the state, and its result is returned. Otherwise `state` is returned
+  /// unchanged.
+  pub fn reload_module(
+    &mut self,
+    id: &str,
+    bytecode: Rc<Bytecode>,
+    state: Val,
+  ) -> Result<Val, Val> {
+    let old_bytecode = self.host.modules.register(id, bytecode.clone());
+    self.host.modules.clear_caches();
+
+    if let Some(loaded) = &self.loaded {
+      loaded.cache.borrow_mut().clear();
+
+      let loaded = match &old_bytecode {
+        Some(old_bytecode) if Rc::ptr_eq(old_bytecode, loaded) => bytecode.clone(),
+        _ => loaded.clone(),
+      };
+
+      self.load(loaded)?;
+    }
+
+    let mut host = std::mem::take(&mut self.host);
+    let exports = with_host(&mut host, || read_exports(&bytecode));
+    self.host = host;
+
+    match exports?.into_iter().find(|(name, _)| name == "migrate") {
+      Some((_, migrate)) => self.call_val("migrate", migrate, vec![state]),
+      None => Ok(state),
+    }
+  }
+
+  /// The names of the exports of the loaded bytecode, with the default export as `default`.
+  pub fn export_names(&self) -> Vec<&str> {
+    self.exports.iter().map(|(name, _)| name.as_str()).collect()
+  }
+
+  /// Calls the export `name` of the loaded bytecode, converting the arguments from Rust values and
+  /// the result into the requested type. For example:
+  ///
+  /// ```ignore
+  /// let sum: f64 = vm.call("add", (1.0, 2.0))?;
+  /// ```
+  pub fn call<R: FromVal>(&mut self, name: &str, params: impl ToParams) -> Result<R, Val> {
+    let fn_ = match self
+      .exports
+      .iter()
+      .find(|(export_name, _)| export_name == name)
+    {
+      Some((_, fn_)) => fn_.clone(),
+      None => return Err(format!("{} is not exported", name).to_type_error()),
+    };
+
+    R::from_val(self.call_val(name, fn_, params.to_params())?)
+  }
+
+  /// Like `call`, but the export is called as a method of `this`, which is updated with the changes
+  /// the export makes to it. This allows state to be kept outside of the VM between calls.
+  pub fn call_mut<R: FromVal>(
+    &mut self,
+    name: &str,
+    this: &mut Val,
+    params: impl ToParams,
+  ) -> Result<R, Val> {
+    let fn_ = match self
+      .exports
+      .iter()
+      .find(|(export_name, _)| export_name == name)
+    {
+      Some((_, fn_)) => fn_.clone(),
+      None => return Err(format!("{} is not exported", name).to_type_error()),
+    };
+
+    R::from_val(self.call_val_impl(name, fn_, Some(this), params.to_params())?)
+  }
+
+  /// Queues a call of `fn_` to run before any macrotask, see `run_until_idle`.
+  pub fn queue_microtask(&mut self, fn_: Val, params: Vec<Val>) {
+    self.host.scheduler.queue_microtask(fn_, params);
+  }
+
+  /// Posts a call of `fn_` to run after the microtasks and previously posted macrotasks, see
+  /// `run_until_idle`.
+  pub fn post_task(&mut self, fn_: Val, params: Vec<Val>) {
+    self.host.scheduler.post_macrotask(fn_, params);
+  }
 
-        Err("step limit reached".to_internal_error())
+  /// Whether there are no queued microtasks or macrotasks.
+  pub fn is_idle(&self) -> bool {
+    self.host.scheduler.is_idle()
+  }
+
+  /// Runs queued tasks (including the ones they queue) until there are none left. Microtasks run
+  /// before each macrotask. If a task throws, the exception is returned and the remaining tasks
+  /// stay queued.
+  pub fn run_until_idle(&mut self) -> Result<(), Val> {
+    while let Some(task) = self.host.scheduler.next_task() {
+      self.call_val("task", task.fn_, task.params)?;
+    }
+
+    Ok(())
+  }
+
+  fn call_val(&mut self, name: &str, fn_: Val, params: Vec<Val>) -> Result<Val, Val> {
+    self.call_val_impl(name, fn_, None, params)
+  }
+
+  fn call_val_impl(
+    &mut self,
+    name: &str,
+    fn_: Val,
+    this: Option<&mut Val>,
+    params: Vec<Val>,
+  ) -> Result<Val, Val> {
+    let mut frame = match fn_.load_function() {
+      LoadFunctionResult::StackFrame(f) => f,
+      LoadFunctionResult::NativeFunction(native_fn) => {
+        // Native functions can use the host too (eg promise reactions)
+        let mut host = std::mem::take(&mut self.host);
+
+        let res = with_host(&mut host, || match this {
+          Some(this) => native_fn(ThisWrapper::new(false, this), params),
+          None => native_fn(ThisWrapper::new(true, &mut Val::Undefined), params),
+        });
+
+        self.host = host;
+
+        return res;
       }
-      None => {
-        while !self.stack.is_empty() {
-          self.step()?;
-        }
+      LoadFunctionResult::NotAFunction => {
+        return Err(format!("{} is not a function", name).to_type_error())
+      }
+    };
+
+    if let Some(this) = &this {
+      frame.write_this(false, (*this).clone())?;
+    }
+
+    for p in params {
+      frame.write_param(p);
+    }
+
+    let mut host = std::mem::take(&mut self.host);
+    let limits = self.limits;
+
+    let res = with_host(&mut host, || {
+      with_size_limits(limits, || self.run_frame(frame, None))
+    });
+
+    self.host = host;
+
+    #[cfg(feature = "arena")]
+    crate::arena::release_arena();
+
+    let call_result = res?;
+
+    if let Some(this) = this {
+      *this = call_result.this;
+    }
+
+    Ok(call_result.return_)
+  }
+
+  fn run_frame(&mut self, frame: StackFrame, step_limit: Option<usize>) -> Result<CallResult, Val> {
+    let step_limit = step_limit.or(self.limits.step_limit);
+    let memory_limit = self.limits.memory_limit;
+
+    // ArenaAllocator is used instead of CountingAllocator, so memory can't be measured
+    #[cfg(feature = "arena")]
+    if memory_limit.is_some() {
+      return Err("memory limit is not supported with the arena feature".to_internal_error());
+    }
+
+    let budget = RunBudget::new(step_limit, memory_limit, self.limits.max_stack_depth);
+
+    with_run_budget(budget, || {
+      self.push(frame);
+      self.run_stack(budget.is_limited())?;
+
+      Ok(self.frame.get_call_result())
+    })
+  }
 
-        Ok(self.frame.get_call_result().return_)
+  /// Steps until the stack is empty. When the run has a step or memory limit, each step is charged
+  /// to the budget of the run.
+  fn run_stack(&mut self, limited: bool) -> Result<(), Val> {
+    if !limited {
+      while !self.stack.is_empty() {
+        self.step()?;
       }
+
+      return Ok(());
+    }
+
+    while !self.stack.is_empty() {
+      charge_step()?;
+      self.step()?;
     }
+
+    Ok(())
   }
 
   pub fn step(&mut self) -> Result<(), Val> {
+    self.step_count += 1;
+
     let step_ok = match self.frame.step() {
       Ok(step_ok) => step_ok,
       Err(e) => return self.handle_exception(e),
@@ -82,6 +396,10 @@ impl VirtualMachine {
         self.frame.apply_call_result(call_result);
       }
       FrameStepOk::Push(new_frame) => {
+        if let Err(e) = check_stack_depth() {
+          return self.handle_exception(e);
+        }
+
         self.push(new_frame);
       }
       // TODO: Internal errors
@@ -91,6 +409,9 @@ impl VirtualMachine {
       FrameStepOk::YieldStar(_) => {
         return self.handle_exception("Unexpected yield*".to_internal_error())
       }
+      FrameStepOk::Await(_) => {
+        return self.handle_exception("Unexpected await".to_internal_error())
+      }
     }
 
     Ok(())
@@ -99,12 +420,14 @@ impl VirtualMachine {
   pub fn push(&mut self, mut frame: StackFrame) {
     std::mem::swap(&mut self.frame, &mut frame);
     self.stack.push(frame);
+    add_stack_depth(1);
   }
 
   pub fn pop(&mut self) {
     // This name is accurate after the swap
     let mut old_frame = self.stack.pop().unwrap();
     std::mem::swap(&mut self.frame, &mut old_frame);
+    add_stack_depth(-1);
   }
 
   pub fn handle_exception(&mut self, mut exception: Val) -> Result<(), Val> {
@@ -125,7 +448,60 @@ impl VirtualMachine {
     Err(exception)
   }
 
-  pub fn read_default_export(bytecode: Rc<Bytecode>) -> Val {
-    bytecode.decoder(0).decode_val(&mut Vec::new())
+  /// Calls `fn_` to completion on a separate stack and returns its result.
+  ///
+  /// This is for operations like ToPrimitive, which need to call user code from inside a single
+  /// step, so they can't push a frame. The host and the limits of the running VM still apply: the
+  /// nested call's steps and frames count towards them, and nested calls that use too much of the
+  /// Rust stack throw a RangeError.
+  pub fn call_nested(fn_: &Val, mut this: Val, params: Vec<Val>) -> Result<Val, Val> {
+    Self::call_nested_impl(fn_, true, &mut this, params)
+  }
+
+  /// Like call_nested, but `this` is mutable and is updated with the callee's changes to it.
+  pub fn call_nested_mut(fn_: &Val, this: &mut Val, params: Vec<Val>) -> Result<Val, Val> {
+    Self::call_nested_impl(fn_, false, this, params)
+  }
+
+  fn call_nested_impl(
+    fn_: &Val,
+    const_: bool,
+    this: &mut Val,
+    params: Vec<Val>,
+  ) -> Result<Val, Val> {
+    let mut frame = match fn_.load_function() {
+      LoadFunctionResult::NotAFunction => return Err("fn_ is not a function".to_type_error()),
+      LoadFunctionResult::NativeFunction(native_fn) => {
+        return native_fn(ThisWrapper::new(const_, this), params);
+      }
+      LoadFunctionResult::StackFrame(frame) => frame,
+    };
+
+    // `this` is only replaced once the call succeeds
+    frame.write_this(const_, this.clone())?;
+
+    for p in params {
+      frame.write_param(p);
+    }
+
+    let call_result = with_nested_call(|| {
+      check_stack_depth()?;
+
+      let mut vm = VirtualMachine::default();
+      vm.push(frame);
+      vm.run_stack(current_run_budget().is_limited())?;
+
+      Ok(vm.frame.get_call_result())
+    })?;
+
+    if !const_ {
+      *this = call_result.this;
+    }
+
+    Ok(call_result.return_)
+  }
+
+  pub fn read_default_export(bytecode: Rc<Bytecode>) -> Result<Val, Val> {
+    bytecode.decoder(bytecode.start).decode_val(&mut Vec::new())
   }
 }
@@ -0,0 +1,154 @@
+use crate::{
+  bytecode::Bytecode,
+  host::{Clock, Console, EntropySource, Host, Locale, RandomSource, SeededRandom, StringEncoding},
+  rc::Rc,
+  vs_value::Val,
+  VirtualMachine,
+};
+
+/// Limits on what a VirtualMachine can use while it runs. These apply to each `run` or `call`.
+///
+/// By default, the sizes of strings, arrays and bigints are limited like they are in V8, so that
+/// programs like `"x".repeat(1e12)` throw a RangeError instead of trying to allocate the result.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+  /// The maximum number of steps. A step limit passed to `run` takes precedence.
+  pub step_limit: Option<usize>,
+
+  /// The maximum depth of nested calls. Exceeding it throws a RangeError, which the program can
+  /// catch.
+  pub max_stack_depth: Option<usize>,
+
+  /// The maximum number of bytes allocated (and not yet freed) since the start of the run. This
+  /// requires CountingAllocator to be installed as the global allocator, so it isn't supported with
+  /// the arena feature (runs with a memory limit fail with an InternalError).
+  pub memory_limit: Option<usize>,
+
+  /// The maximum length of a string in bytes. Creating a longer string throws a RangeError.
+  pub max_string_length: Option<usize>,
+
+  /// The maximum length of an array. Creating or growing an array beyond it throws a RangeError.
+  pub max_array_length: Option<usize>,
+
+  /// The maximum number of bits in the magnitude of a bigint. Exceeding it throws a RangeError.
+  pub max_bigint_bits: Option<u64>,
+}
+
+impl Default for Limits {
+  fn default() -> Self {
+    Limits {
+      step_limit: None,
+      max_stack_depth: None,
+      memory_limit: None,
+      max_string_length: Some((1 << 29) - 24),
+      max_array_length: Some(1 << 27),
+      max_bigint_bits: Some(1 << 30),
+    }
+  }
+}
+
+/// Configures a VirtualMachine before it runs:
+///
+/// ```ignore
+/// let mut vm = VirtualMachine::builder()
+///   .step_limit(1_000_000)
+///   .seed(42)
+///   .builtin("answer", 42.0)
+///   .build();
+/// ```
+#[derive(Default)]
+pub struct VirtualMachineBuilder {
+  host: Host,
+  limits: Limits,
+}
+
+impl VirtualMachineBuilder {
+  pub fn step_limit(mut self, step_limit: usize) -> Self {
+    self.limits.step_limit = Some(step_limit);
+    self
+  }
+
+  pub fn max_stack_depth(mut self, max_stack_depth: usize) -> Self {
+    self.limits.max_stack_depth = Some(max_stack_depth);
+    self
+  }
+
+  pub fn memory_limit(mut self, memory_limit: usize) -> Self {
+    self.limits.memory_limit = Some(memory_limit);
+    self
+  }
+
+  pub fn max_string_length(mut self, max_string_length: usize) -> Self {
+    self.limits.max_string_length = Some(max_string_length);
+    self
+  }
+
+  pub fn max_array_length(mut self, max_array_length: usize) -> Self {
+    self.limits.max_array_length = Some(max_array_length);
+    self
+  }
+
+  pub fn max_bigint_bits(mut self, max_bigint_bits: u64) -> Self {
+    self.limits.max_bigint_bits = Some(max_bigint_bits);
+    self
+  }
+
+  /// Makes `Math.random` a deterministic sequence determined by `seed`.
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.host.random = Box::new(SeededRandom::new(seed));
+    self
+  }
+
+  pub fn random_source(mut self, random: Box<dyn RandomSource>) -> Self {
+    self.host.random = random;
+    self
+  }
+
+  pub fn clock(mut self, clock: Box<dyn Clock>) -> Self {
+    self.host.clock = clock;
+    self
+  }
+
+  pub fn entropy_source(mut self, entropy: Box<dyn EntropySource>) -> Self {
+    self.host.entropy = Some(entropy);
+    self
+  }
+
+  pub fn string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+    self.host.string_encoding = string_encoding;
+    self
+  }
+
+  pub fn console(mut self, console: Box<dyn Console>) -> Self {
+    self.host.console = console;
+    self
+  }
+
+  pub fn locale(mut self, locale: Box<dyn Locale>) -> Self {
+    self.host.locale = locale;
+    self
+  }
+
+  /// Adds a builtin, which scripts can access as `globalThis.<name>`.
+  pub fn builtin(mut self, name: &str, value: impl Into<Val>) -> Self {
+    self
+      .host
+      .extra_builtins
+      .insert(name.to_string(), value.into());
+    self
+  }
+
+  /// Registers unlinked bytecode as the module `id`, see `VirtualMachine::register_module`.
+  pub fn module(mut self, id: &str, bytecode: Rc<Bytecode>) -> Self {
+    self.host.modules.register(id, bytecode);
+    self
+  }
+
+  pub fn build(self) -> VirtualMachine {
+    VirtualMachine {
+      host: self.host,
+      limits: self.limits,
+      ..Default::default()
+    }
+  }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+
+use crate::{
+  builtins::type_error_builtin::ToTypeError,
+  rc::Rc,
+  vs_array::VsArray,
+  vs_class::VsClass,
+  vs_value::{ToDynamicVal, Val, VsType},
+  LoadFunctionResult, ValTrait, VirtualMachine,
+};
+
+/// A getter/setter pair. Accessors are stored in an object's property maps in place of a value
+/// (as Val::Dynamic), so they keep their position in the property order and can be inherited
+/// through prototypes like methods. Subscripting the object calls the getter, and assigning to
+/// the subscript calls the setter.
+#[derive(Clone, Default, Debug)]
+pub struct VsAccessor {
+  pub get: Option<Val>,
+  pub set: Option<Val>,
+}
+
+impl VsAccessor {
+  pub fn from_val(val: &Val) -> Option<&VsAccessor> {
+    match val {
+      Val::Dynamic(dynamic) => dynamic.as_any().downcast_ref::<VsAccessor>(),
+      _ => None,
+    }
+  }
+
+  /// Calls the getter with `this` as the receiver. Getters are called with a const `this`.
+  pub fn get_value(&self, this: Val) -> Result<Val, Val> {
+    match &self.get {
+      Some(get) => VirtualMachine::call_nested(get, this, vec![]),
+      None => Ok(Val::Undefined),
+    }
+  }
+
+  /// Calls the setter with `this` as the receiver, which is updated with any changes the setter
+  /// makes to it.
+  pub fn set_value(&self, this: &mut Val, value: Val) -> Result<(), Val> {
+    match &self.set {
+      Some(set) => VirtualMachine::call_nested_mut(set, this, vec![value]).map(|_| ()),
+      None => Err("Cannot set property which only has a getter".to_type_error()),
+    }
+  }
+
+  /// Adds the getter and/or setter to the accessor already at `existing` (if any), so that `get`
+  /// and `set` can be defined separately for the same key.
+  pub fn merged_with(self, existing: Option<&Val>) -> Val {
+    let mut accessor = existing
+      .and_then(VsAccessor::from_val)
+      .cloned()
+      .unwrap_or_default();
+
+    if self.get.is_some() {
+      accessor.get = self.get;
+    }
+
+    if self.set.is_some() {
+      accessor.set = self.set;
+    }
+
+    accessor.to_dynamic_val()
+  }
+}
+
+impl ValTrait for VsAccessor {
+  fn typeof_(&self) -> VsType {
+    VsType::Object
+  }
+
+  fn to_number(&self) -> f64 {
+    f64::NAN
+  }
+
+  fn to_index(&self) -> Option<usize> {
+    None
+  }
+
+  fn is_primitive(&self) -> bool {
+    false
+  }
+
+  fn is_truthy(&self) -> bool {
+    true
+  }
+
+  fn is_nullish(&self) -> bool {
+    false
+  }
+
+  fn bind(&self, _params: Vec<Val>) -> Option<Val> {
+    None
+  }
+
+  fn as_bigint_data(&self) -> Option<BigInt> {
+    None
+  }
+
+  fn as_array_data(&self) -> Option<Rc<VsArray>> {
+    None
+  }
+
+  fn as_class_data(&self) -> Option<Rc<VsClass>> {
+    None
+  }
+
+  fn load_function(&self) -> LoadFunctionResult {
+    LoadFunctionResult::NotAFunction
+  }
+
+  fn sub(&self, _key: &Val) -> Result<Val, Val> {
+    Ok(Val::Undefined)
+  }
+
+  fn has(&self, _key: &Val) -> Option<bool> {
+    Some(false)
+  }
+
+  fn submov(&mut self, _key: &Val, _value: Val) -> Result<(), Val> {
+    Err("Cannot assign to subscript of accessor".to_type_error())
+  }
+
+  fn pretty_fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\x1b[36m{}\x1b[39m", self.codify())
+  }
+
+  fn codify(&self) -> String {
+    match (&self.get, &self.set) {
+      (Some(_), Some(_)) => "[Getter/Setter]",
+      (Some(_), None) => "[Getter]",
+      (None, _) => "[Setter]",
+    }
+    .to_string()
+  }
+}
+
+impl fmt::Display for VsAccessor {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.codify())
+  }
+}
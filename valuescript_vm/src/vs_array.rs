@@ -1,7 +1,9 @@
-use std::rc::Rc;
-
+use crate::rc::Rc;
 use crate::vs_value::{ToVal, Val};
 
+/// Elided elements (holes, as in `[1, , 3]`) are stored as `Val::Void`. Reading a hole gives
+/// `undefined`, but unlike an element that is actually `undefined`, holes are skipped by `in`,
+/// `indexOf`, `flat` and most of the iteration methods.
 #[derive(Clone, Debug, Default)]
 pub struct VsArray {
   pub elements: Vec<Val>, // TODO: VsArray(Vec<Val>)?
@@ -13,6 +15,14 @@ impl VsArray {
   }
 }
 
+/// The value seen when reading an element, which is `undefined` for holes.
+pub fn read_element(element: &Val) -> &Val {
+  match element {
+    Val::Void => &Val::Undefined,
+    _ => element,
+  }
+}
+
 impl ToVal for VsArray {
   fn to_val(self) -> Val {
     Val::Array(Rc::new(self))
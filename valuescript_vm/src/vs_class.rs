@@ -1,5 +1,5 @@
-use std::rc::Rc;
-
+use crate::rc::Rc;
+use crate::vs_symbol::VsSymbol;
 use crate::vs_value::ToVal;
 
 use super::vs_value::Val;
@@ -15,6 +15,21 @@ pub struct VsClass {
 
 impl VsClass {}
 
+/// Records the name of a class on its prototype (see VsSymbol::CLASS_NAME), so that instances can
+/// be displayed with it.
+pub fn name_prototype(prototype: Val, name: &str) -> Val {
+  match prototype {
+    Val::Object(mut object) if !name.is_empty() => {
+      Rc::make_mut(&mut object)
+        .symbol_map
+        .insert(VsSymbol::CLASS_NAME, name.to_val());
+
+      Val::Object(object)
+    }
+    prototype => prototype,
+  }
+}
+
 impl ToVal for VsClass {
   fn to_val(self) -> Val {
     Val::Class(Rc::new(self))
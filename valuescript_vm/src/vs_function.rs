@@ -1,8 +1,9 @@
-use std::rc::Rc;
-
+use crate::async_frame::AsyncFrame;
 use crate::builtins::internal_error_builtin::ToInternalError;
 use crate::bytecode::{Bytecode, DecoderMaker};
 use crate::make_generator_frame::MakeGeneratorFrame;
+use crate::rc::Rc;
+use crate::stack_frame::StackFrameTrait;
 use crate::vs_value::ToVal;
 
 use super::bytecode_decoder::BytecodeDecoder;
@@ -15,8 +16,10 @@ pub struct VsFunction {
   pub bytecode: Rc<Bytecode>,
   pub meta_pos: Option<usize>,
   pub is_generator: bool,
+  pub is_async: bool,
   pub register_count: usize,
   pub parameter_count: usize,
+  pub has_rest_param: bool,
   pub start: usize,
   pub binds: Vec<Val>,
 }
@@ -33,8 +36,10 @@ impl VsFunction {
       bytecode: self.bytecode.clone(),
       meta_pos: self.meta_pos,
       is_generator: self.is_generator,
+      is_async: self.is_async,
       register_count: self.register_count,
       parameter_count: self.parameter_count,
+      has_rest_param: self.has_rest_param,
       start: self.start,
       binds: new_binds,
     }
@@ -56,35 +61,52 @@ impl VsFunction {
     registers.push(Val::Undefined);
     registers.push(Val::Undefined);
 
-    for bind_val in &self.binds {
-      registers.push(bind_val.clone());
-    }
-
     while registers.len() < registers.capacity() {
       registers.push(Val::Void);
     }
 
-    BytecodeStackFrame {
+    let (param_end, rest_param) = match self.has_rest_param {
+      false => (self.parameter_count + 2, None),
+      true => {
+        let rest_param = self.parameter_count + 1;
+        registers[rest_param] = Vec::<Val>::new().to_val();
+
+        (rest_param, Some(rest_param))
+      }
+    };
+
+    let mut frame = BytecodeStackFrame {
       decoder: BytecodeDecoder {
         bytecode: self.bytecode.clone(),
         pos: self.start,
       },
+      register_count: registers.len(),
       registers,
       const_this: true,
-      param_start: self.binds.len() + 2,
-      param_end: self.parameter_count + 2,
+      param_start: 2,
+      param_end,
+      rest_param,
       this_target: None,
       return_target: None,
       catch_setting: None,
+      new_target: Val::Undefined,
+    };
+
+    for bind_val in &self.binds {
+      frame.write_param(bind_val.clone());
     }
+
+    frame
   }
 
   pub fn make_frame(&self) -> StackFrame {
     let frame = self.make_bytecode_frame();
 
-    match self.is_generator {
-      false => Box::new(frame),
-      true => Box::new(MakeGeneratorFrame::new(frame)),
+    match (self.is_generator, self.is_async) {
+      (true, false) => Box::new(MakeGeneratorFrame::new(frame)),
+      (true, true) => Box::new(MakeGeneratorFrame::new_async(frame)),
+      (false, true) => Box::new(AsyncFrame::new(frame)),
+      (false, false) => Box::new(frame),
     }
   }
 }
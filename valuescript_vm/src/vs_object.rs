@@ -1,36 +1,73 @@
 use std::collections::BTreeMap;
-use std::rc::Rc;
 
+use crate::property_map::PropertyMap;
+use crate::rc::Rc;
+use crate::vs_accessor::VsAccessor;
 use crate::vs_symbol::VsSymbol;
 use crate::vs_value::ToVal;
 use crate::ValTrait;
 
 use super::vs_value::Val;
 
+/// Properties can hold accessors (see VsAccessor) instead of values. VsObject::sub returns them
+/// as-is, and op_sub/op_submov are responsible for calling them.
 #[derive(Clone, Default, Debug)]
 pub struct VsObject {
-  pub string_map: BTreeMap<String, Val>,
+  pub string_map: PropertyMap,
   pub symbol_map: BTreeMap<VsSymbol, Val>,
   pub prototype: Option<Val>,
 }
 
 impl VsObject {
+  /// Looks up `key` on this object and its prototypes, without calling getters.
   pub fn sub(&self, key: &Val) -> Val {
-    let val = match &key {
-      Val::String(string) => self.string_map.get(&**string),
-      Val::Symbol(symbol) => self.symbol_map.get(symbol),
-      _ => self.string_map.get(&key.to_string()),
-    };
-
-    if let Some(val) = val {
+    if let Some(val) = self.own_property(key) {
       return val.clone();
     }
 
     match &self.prototype {
+      Some(Val::Object(prototype)) => prototype.sub(key),
       Some(prototype) => prototype.sub(key).map_err(|e| e.to_string()).unwrap(), // TODO: Exception
       None => Val::Undefined,
     }
   }
+
+  /// The property stored on this object itself (not inherited) for `key`, which may be an accessor.
+  pub fn own_property(&self, key: &Val) -> Option<&Val> {
+    match key {
+      Val::String(string) => self.string_map.get(string),
+      Val::Symbol(symbol) => self.symbol_map.get(symbol),
+      _ => self.string_map.get(&key.to_string()),
+    }
+  }
+
+  /// Stores `value` for `key` directly, replacing any accessor rather than calling its setter.
+  pub fn define_value(&mut self, key: &Val, value: Val) {
+    match key {
+      Val::Symbol(symbol) => {
+        self.symbol_map.insert(symbol.clone(), value);
+      }
+      _ => {
+        self.string_map.insert(key.to_string(), value);
+      }
+    }
+  }
+
+  /// Defines a getter and/or setter for `key`, keeping the other half if the key already has an
+  /// accessor.
+  pub fn define_accessor(&mut self, key: &Val, accessor: VsAccessor) {
+    match key {
+      Val::Symbol(symbol) => {
+        let accessor = accessor.merged_with(self.symbol_map.get(symbol));
+        self.symbol_map.insert(symbol.clone(), accessor);
+      }
+      _ => {
+        let key = key.to_string();
+        let accessor = accessor.merged_with(self.string_map.get(&key));
+        self.string_map.insert(key, accessor);
+      }
+    }
+  }
 }
 
 impl ToVal for VsObject {
@@ -0,0 +1,747 @@
+//! Conversions between Vals and Rust types that implement serde's Serialize/Deserialize, so that
+//! structured data can be passed between the host and scripts without building Vals by hand.
+//!
+//! Structs and maps become objects, sequences and tuples become arrays, and `None` and `()`
+//! become `undefined`. Enums follow serde's default (externally tagged) representation: unit
+//! variants are strings, and other variants are objects with the variant name as the only key.
+//! All Rust numbers become JS numbers, so integers beyond 2^53 lose precision.
+
+use std::{collections::BTreeMap, fmt};
+
+use num_traits::ToPrimitive;
+use serde::{
+  de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+  },
+  forward_to_deserialize_any,
+  ser::{self, Serialize},
+};
+
+use crate::{
+  builtins::type_error_builtin::ToTypeError,
+  embedding::FromVal,
+  property_map::{self, PropertyMap},
+  vs_array::read_element,
+  vs_object::VsObject,
+  vs_value::{ToVal, Val, ValTrait},
+};
+
+/// Converts `value` to a Val.
+pub fn to_val<T: Serialize + ?Sized>(value: &T) -> Result<Val, Val> {
+  value.serialize(ValSerializer).map_err(Error::into_val)
+}
+
+/// Converts `val` to a Rust value, failing with a TypeError if it doesn't have the right shape.
+pub fn from_val<T: DeserializeOwned>(val: &Val) -> Result<T, Val> {
+  T::deserialize(ValDeserializer(val)).map_err(Error::into_val)
+}
+
+/// Wraps a serde type so it can be used where ToVal/FromVal are expected, eg:
+///
+/// ```ignore
+/// let Serde(point): Serde<Point> = vm.call("translate", (Serde(point), 1.0))?;
+/// ```
+pub struct Serde<T>(pub T);
+
+impl<T: Serialize> ToVal for Serde<T> {
+  /// Gives the TypeError instead if the conversion fails (eg a map with array keys), so that the
+  /// script receives it rather than the host panicking. Use `to_val` to handle the error instead.
+  fn to_val(self) -> Val {
+    match to_val(&self.0) {
+      Ok(val) => val,
+      Err(err) => err,
+    }
+  }
+}
+
+impl<T: DeserializeOwned> FromVal for Serde<T> {
+  fn from_val(val: Val) -> Result<Self, Val> {
+    from_val(&val).map(Serde)
+  }
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl Error {
+  fn into_val(self) -> Val {
+    self.0.to_type_error()
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+  fn custom<T: fmt::Display>(msg: T) -> Self {
+    Error(msg.to_string())
+  }
+}
+
+impl de::Error for Error {
+  fn custom<T: fmt::Display>(msg: T) -> Self {
+    Error(msg.to_string())
+  }
+}
+
+struct ValSerializer;
+
+impl ser::Serializer for ValSerializer {
+  type Ok = Val;
+  type Error = Error;
+
+  type SerializeSeq = SerializeArray;
+  type SerializeTuple = SerializeArray;
+  type SerializeTupleStruct = SerializeArray;
+  type SerializeTupleVariant = SerializeArray;
+  type SerializeMap = SerializeObject;
+  type SerializeStruct = SerializeObject;
+  type SerializeStructVariant = SerializeObject;
+
+  fn serialize_bool(self, v: bool) -> Result<Val, Error> {
+    Ok(v.to_val())
+  }
+
+  fn serialize_i8(self, v: i8) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_i16(self, v: i16) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_i32(self, v: i32) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_i64(self, v: i64) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_u8(self, v: u8) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_u16(self, v: u16) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_u32(self, v: u32) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_u64(self, v: u64) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_f32(self, v: f32) -> Result<Val, Error> {
+    Ok((v as f64).to_val())
+  }
+
+  fn serialize_f64(self, v: f64) -> Result<Val, Error> {
+    Ok(v.to_val())
+  }
+
+  fn serialize_char(self, v: char) -> Result<Val, Error> {
+    Ok(v.to_val())
+  }
+
+  fn serialize_str(self, v: &str) -> Result<Val, Error> {
+    Ok(v.to_val())
+  }
+
+  fn serialize_bytes(self, v: &[u8]) -> Result<Val, Error> {
+    Ok(
+      v.iter()
+        .map(|b| (*b as f64).to_val())
+        .collect::<Vec<Val>>()
+        .to_val(),
+    )
+  }
+
+  fn serialize_none(self) -> Result<Val, Error> {
+    Ok(Val::Undefined)
+  }
+
+  fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Val, Error> {
+    value.serialize(self)
+  }
+
+  fn serialize_unit(self) -> Result<Val, Error> {
+    Ok(Val::Undefined)
+  }
+
+  fn serialize_unit_struct(self, _name: &'static str) -> Result<Val, Error> {
+    Ok(Val::Undefined)
+  }
+
+  fn serialize_unit_variant(
+    self,
+    _name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+  ) -> Result<Val, Error> {
+    Ok(variant.to_val())
+  }
+
+  fn serialize_newtype_struct<T: Serialize + ?Sized>(
+    self,
+    _name: &'static str,
+    value: &T,
+  ) -> Result<Val, Error> {
+    value.serialize(self)
+  }
+
+  fn serialize_newtype_variant<T: Serialize + ?Sized>(
+    self,
+    _name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+    value: &T,
+  ) -> Result<Val, Error> {
+    Ok(tagged(variant, value.serialize(ValSerializer)?))
+  }
+
+  fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+    Ok(SerializeArray {
+      variant: None,
+      elements: Vec::with_capacity(len.unwrap_or(0)),
+    })
+  }
+
+  fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+    self.serialize_seq(Some(len))
+  }
+
+  fn serialize_tuple_struct(
+    self,
+    _name: &'static str,
+    len: usize,
+  ) -> Result<SerializeArray, Error> {
+    self.serialize_seq(Some(len))
+  }
+
+  fn serialize_tuple_variant(
+    self,
+    _name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+    len: usize,
+  ) -> Result<SerializeArray, Error> {
+    Ok(SerializeArray {
+      variant: Some(variant),
+      elements: Vec::with_capacity(len),
+    })
+  }
+
+  fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, Error> {
+    Ok(SerializeObject {
+      variant: None,
+      string_map: PropertyMap::new(),
+      key: None,
+    })
+  }
+
+  fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+    self.serialize_map(Some(len))
+  }
+
+  fn serialize_struct_variant(
+    self,
+    _name: &'static str,
+    _variant_index: u32,
+    variant: &'static str,
+    _len: usize,
+  ) -> Result<SerializeObject, Error> {
+    Ok(SerializeObject {
+      variant: Some(variant),
+      string_map: PropertyMap::new(),
+      key: None,
+    })
+  }
+}
+
+/// `{ [variant]: value }`, the representation of non-unit enum variants.
+fn tagged(variant: &str, value: Val) -> Val {
+  object(PropertyMap::from([(variant.to_string(), value)]))
+}
+
+fn object(string_map: PropertyMap) -> Val {
+  VsObject {
+    string_map,
+    symbol_map: BTreeMap::new(),
+    prototype: None,
+  }
+  .to_val()
+}
+
+struct SerializeArray {
+  variant: Option<&'static str>,
+  elements: Vec<Val>,
+}
+
+impl SerializeArray {
+  fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    self.elements.push(value.serialize(ValSerializer)?);
+    Ok(())
+  }
+
+  fn finish(self) -> Result<Val, Error> {
+    let array = self.elements.to_val();
+
+    Ok(match self.variant {
+      Some(variant) => tagged(variant, array),
+      None => array,
+    })
+  }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+  type Ok = Val;
+  type Error = Error;
+
+  fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+  type Ok = Val;
+  type Error = Error;
+
+  fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+  type Ok = Val;
+  type Error = Error;
+
+  fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+  type Ok = Val;
+  type Error = Error;
+
+  fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    self.push(value)
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+struct SerializeObject {
+  variant: Option<&'static str>,
+  string_map: PropertyMap,
+  key: Option<String>,
+}
+
+impl SerializeObject {
+  fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+    self.string_map.insert(key, value.serialize(ValSerializer)?);
+    Ok(())
+  }
+
+  fn finish(self) -> Result<Val, Error> {
+    let object = object(self.string_map);
+
+    Ok(match self.variant {
+      Some(variant) => tagged(variant, object),
+      None => object,
+    })
+  }
+}
+
+impl ser::SerializeMap for SerializeObject {
+  type Ok = Val;
+  type Error = Error;
+
+  /// Like JS property keys, map keys are converted to strings.
+  fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+    self.key = Some(match key.serialize(ValSerializer)? {
+      Val::Array(_) | Val::Object(_) => {
+        return Err(Error("Map keys must be strings or numbers".to_string()))
+      }
+      key => key.to_string(),
+    });
+
+    Ok(())
+  }
+
+  fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    match self.key.take() {
+      Some(key) => self.insert(key, value),
+      None => Err(Error(
+        "serialize_value called before serialize_key".to_string(),
+      )),
+    }
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+  type Ok = Val;
+  type Error = Error;
+
+  fn serialize_field<T: Serialize + ?Sized>(
+    &mut self,
+    key: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    self.insert(key.to_string(), value)
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+  type Ok = Val;
+  type Error = Error;
+
+  fn serialize_field<T: Serialize + ?Sized>(
+    &mut self,
+    key: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    self.insert(key.to_string(), value)
+  }
+
+  fn end(self) -> Result<Val, Error> {
+    self.finish()
+  }
+}
+
+struct ValDeserializer<'a>(&'a Val);
+
+impl<'de, 'a> de::Deserializer<'de> for ValDeserializer<'a> {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    match self.0 {
+      Val::Void | Val::Undefined | Val::Null => visitor.visit_unit(),
+      Val::Bool(bool) => visitor.visit_bool(*bool),
+      Val::Number(number) => {
+        // Integers are visited as integers so that they can be deserialized into integer types
+        if number.fract() == 0.0 && number.abs() < 2f64.powi(63) {
+          match *number < 0.0 {
+            true => visitor.visit_i64(*number as i64),
+            false => visitor.visit_u64(*number as u64),
+          }
+        } else {
+          visitor.visit_f64(*number)
+        }
+      }
+      Val::BigInt(bigint) => match (bigint.to_i64(), bigint.to_u64()) {
+        (Some(i), _) => visitor.visit_i64(i),
+        (None, Some(u)) => visitor.visit_u64(u),
+        (None, None) => Err(Error(format!("BigInt {} is out of range", bigint))),
+      },
+      Val::String(string) => visitor.visit_str(string),
+      Val::Array(array) => visitor.visit_seq(ArrayAccess(array.elements.iter())),
+      Val::Object(object) => visitor.visit_map(ObjectAccess {
+        iter: object.string_map.iter(),
+        value: None,
+      }),
+      val => Err(Error(format!("Cannot convert {} from Val", val.typeof_()))),
+    }
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    match self.0 {
+      Val::Void | Val::Undefined | Val::Null => visitor.visit_none(),
+      _ => visitor.visit_some(self),
+    }
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    match self.0 {
+      Val::String(variant) => visitor.visit_enum(Enum {
+        variant: variant.to_string(),
+        value: None,
+      }),
+      Val::Object(object) if object.string_map.len() == 1 => {
+        let (variant, value) = object.string_map.iter().next().expect("len is 1");
+
+        visitor.visit_enum(Enum {
+          variant: variant.clone(),
+          value: Some(value),
+        })
+      }
+      _ => Err(Error(
+        "Expected a string or an object with one key for an enum".to_string(),
+      )),
+    }
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+    unit_struct seq tuple tuple_struct map struct identifier ignored_any
+  }
+}
+
+struct ArrayAccess<'a>(std::slice::Iter<'a, Val>);
+
+impl<'de, 'a> SeqAccess<'de> for ArrayAccess<'a> {
+  type Error = Error;
+
+  fn next_element_seed<T: DeserializeSeed<'de>>(
+    &mut self,
+    seed: T,
+  ) -> Result<Option<T::Value>, Error> {
+    match self.0.next() {
+      Some(element) => seed
+        .deserialize(ValDeserializer(read_element(element)))
+        .map(Some),
+      None => Ok(None),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.0.len())
+  }
+}
+
+struct ObjectAccess<'a> {
+  iter: property_map::Iter<'a>,
+  value: Option<&'a Val>,
+}
+
+impl<'de, 'a> MapAccess<'de> for ObjectAccess<'a> {
+  type Error = Error;
+
+  fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+    match self.iter.next() {
+      Some((key, value)) => {
+        self.value = Some(value);
+        seed.deserialize(key.as_str().into_deserializer()).map(Some)
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+    match self.value.take() {
+      Some(value) => seed.deserialize(ValDeserializer(value)),
+      None => Err(Error(
+        "next_value_seed called before next_key_seed".to_string(),
+      )),
+    }
+  }
+}
+
+struct Enum<'a> {
+  variant: String,
+  value: Option<&'a Val>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for Enum<'a> {
+  type Error = Error;
+  type Variant = VariantValue<'a>;
+
+  fn variant_seed<V: DeserializeSeed<'de>>(
+    self,
+    seed: V,
+  ) -> Result<(V::Value, VariantValue<'a>), Error> {
+    let variant = seed.deserialize(self.variant.as_str().into_deserializer())?;
+    Ok((variant, VariantValue(self.value)))
+  }
+}
+
+struct VariantValue<'a>(Option<&'a Val>);
+
+impl<'a> VariantValue<'a> {
+  fn value(&self) -> Result<&'a Val, Error> {
+    self
+      .0
+      .ok_or_else(|| Error("Expected a value for the enum variant".to_string()))
+  }
+}
+
+impl<'de, 'a> VariantAccess<'de> for VariantValue<'a> {
+  type Error = Error;
+
+  fn unit_variant(self) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+    seed.deserialize(ValDeserializer(self.value()?))
+  }
+
+  fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+    de::Deserializer::deserialize_seq(ValDeserializer(self.value()?), visitor)
+  }
+
+  fn struct_variant<V: Visitor<'de>>(
+    self,
+    _fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    de::Deserializer::deserialize_map(ValDeserializer(self.value()?), visitor)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::{BTreeMap, HashMap};
+
+  use serde::{Deserialize, Serialize};
+
+  use super::*;
+
+  #[derive(Serialize, Deserialize, PartialEq, Debug)]
+  struct Point {
+    x: f64,
+    y: f64,
+  }
+
+  #[derive(Serialize, Deserialize, PartialEq, Debug)]
+  enum Shape {
+    Empty,
+    Circle(f64),
+    Line(Point, Point),
+    Rect { width: u32, height: u32 },
+  }
+
+  #[derive(Serialize, Deserialize, PartialEq, Debug)]
+  struct Scene {
+    name: String,
+    shapes: Vec<Shape>,
+    tags: Option<Vec<String>>,
+    grid: Vec<Vec<Vec<i32>>>,
+    layers: BTreeMap<String, Point>,
+  }
+
+  fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    from_val(&to_val(value).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn structs() {
+    let point = Point { x: 1.5, y: -2.0 };
+
+    assert_eq!(to_val(&point).unwrap().codify(), "{\"x\":1.5,\"y\":-2}");
+    assert_eq!(round_trip(&point), point);
+  }
+
+  #[test]
+  fn enums() {
+    let shapes = vec![
+      Shape::Empty,
+      Shape::Circle(3.0),
+      Shape::Line(Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 }),
+      Shape::Rect {
+        width: 4,
+        height: 5,
+      },
+    ];
+
+    assert_eq!(
+      to_val(&shapes).unwrap().codify(),
+      "[\"Empty\",{\"Circle\":3},{\"Line\":[{\"x\":0,\"y\":0},{\"x\":1,\"y\":1}]},{\"Rect\":{\"width\":4,\"height\":5}}]",
+    );
+
+    assert_eq!(round_trip(&shapes), shapes);
+  }
+
+  #[test]
+  fn maps() {
+    let mut counts = HashMap::<String, u64>::new();
+    counts.insert("a".to_string(), 1);
+    counts.insert("b".to_string(), 2);
+
+    assert_eq!(round_trip(&counts), counts);
+
+    // Like JS property keys, numeric keys become strings
+    let by_id = BTreeMap::from([(1, "one"), (2, "two")]);
+    assert_eq!(
+      to_val(&by_id).unwrap().codify(),
+      "{\"1\":\"one\",\"2\":\"two\"}"
+    );
+  }
+
+  #[test]
+  fn nested_arrays() {
+    let grid = vec![vec![vec![1, -2], vec![]], vec![vec![3]]];
+
+    assert_eq!(to_val(&grid).unwrap().codify(), "[[[1,-2],[]],[[3]]]");
+    assert_eq!(round_trip(&grid), grid);
+  }
+
+  #[test]
+  fn nested_structures() {
+    let scene = Scene {
+      name: "scene".to_string(),
+      shapes: vec![Shape::Circle(1.0), Shape::Empty],
+      tags: None,
+      grid: vec![vec![vec![1, 2], vec![3]]],
+      layers: BTreeMap::from([("origin".to_string(), Point { x: 0.0, y: 0.0 })]),
+    };
+
+    assert_eq!(round_trip(&scene), scene);
+
+    let tagged = Scene {
+      tags: Some(vec!["a".to_string()]),
+      ..scene
+    };
+
+    assert_eq!(round_trip(&tagged), tagged);
+  }
+
+  #[test]
+  fn failed_conversions() {
+    // Map keys can't be arrays
+    let by_pair = BTreeMap::from([((1, 2), "a")]);
+
+    assert!(to_val(&by_pair).is_err());
+    assert_eq!(
+      Serde(by_pair).to_val().codify(),
+      "TypeError{\"message\":\"Map keys must be strings or numbers\"}",
+    );
+
+    assert!(from_val::<Point>(&"not a point".to_val()).is_err());
+    assert!(from_val::<Shape>(&Val::Number(1.0)).is_err());
+  }
+}
@@ -0,0 +1,127 @@
+use std::{
+  borrow::Borrow,
+  cmp::Ordering,
+  fmt,
+  hash::{Hash, Hasher},
+  ops::Deref,
+};
+
+use crate::rc::Rc;
+
+const INLINE_CAPACITY: usize = 22;
+
+/// String data for Val::String. Short strings (most property keys and single characters) are
+/// stored inline, so they don't need a heap allocation. Longer strings are shared via Rc.
+#[derive(Clone)]
+pub struct VsString(Repr);
+
+#[derive(Clone)]
+enum Repr {
+  Inline {
+    len: u8,
+    bytes: [u8; INLINE_CAPACITY],
+  },
+  Heap(Rc<str>),
+}
+
+impl VsString {
+  pub fn as_str(&self) -> &str {
+    match &self.0 {
+      Repr::Inline { len, bytes } => {
+        // Safety: inline bytes are only ever copied from a str, in full
+        unsafe { std::str::from_utf8_unchecked(&bytes[..*len as usize]) }
+      }
+      Repr::Heap(rc) => rc,
+    }
+  }
+
+  fn inline(s: &str) -> Option<VsString> {
+    if s.len() > INLINE_CAPACITY {
+      return None;
+    }
+
+    let mut bytes = [0u8; INLINE_CAPACITY];
+    bytes[..s.len()].copy_from_slice(s.as_bytes());
+
+    Some(VsString(Repr::Inline {
+      len: s.len() as u8,
+      bytes,
+    }))
+  }
+}
+
+impl Deref for VsString {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl AsRef<str> for VsString {
+  fn as_ref(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl Borrow<str> for VsString {
+  fn borrow(&self) -> &str {
+    self.as_str()
+  }
+}
+
+impl From<&str> for VsString {
+  fn from(s: &str) -> Self {
+    VsString::inline(s).unwrap_or_else(|| VsString(Repr::Heap(Rc::from(s))))
+  }
+}
+
+impl From<String> for VsString {
+  fn from(s: String) -> Self {
+    VsString::inline(&s).unwrap_or_else(|| VsString(Repr::Heap(Rc::from(s))))
+  }
+}
+
+impl From<Rc<str>> for VsString {
+  fn from(s: Rc<str>) -> Self {
+    VsString::inline(&s).unwrap_or(VsString(Repr::Heap(s)))
+  }
+}
+
+impl PartialEq for VsString {
+  fn eq(&self, other: &Self) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+
+impl Eq for VsString {}
+
+impl PartialOrd for VsString {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for VsString {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.as_str().cmp(other.as_str())
+  }
+}
+
+impl Hash for VsString {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.as_str().hash(state)
+  }
+}
+
+impl fmt::Display for VsString {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(self.as_str(), f)
+  }
+}
+
+impl fmt::Debug for VsString {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Debug::fmt(self.as_str(), f)
+  }
+}
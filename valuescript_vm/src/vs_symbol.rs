@@ -1,13 +1,56 @@
+use crate::rc::Rc;
 use crate::vs_value::{ToVal, Val};
 
+#[allow(non_camel_case_types)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum VsSymbol {
   ITERATOR,
+  ASYNC_ITERATOR,
+  TO_PRIMITIVE,
+  HAS_INSTANCE,
+  TO_STRING_TAG,
+
+  /// Thrown through a generator by `generator.return()`, so that its finally blocks run on the way
+  /// out. This isn't accessible to programs, and catch clauses in generators rethrow it.
+  GENERATOR_RETURN,
+
+  /// Holds the name of a class on its prototype, so that instances can be displayed with it. This
+  /// isn't accessible to programs.
+  CLASS_NAME,
+
+  /// A symbol from the global registry (`Symbol.for(key)`). Registered symbols are identified by
+  /// their key alone, so the same key produces the same symbol in every VM instance, including
+  /// after serialization.
+  Registered(Rc<str>),
 }
 
-pub fn symbol_to_name(symbol: VsSymbol) -> &'static str {
-  match symbol {
-    VsSymbol::ITERATOR => "iterator",
+impl VsSymbol {
+  pub fn description(&self) -> String {
+    match self {
+      VsSymbol::ITERATOR => "Symbol.iterator".to_string(),
+      VsSymbol::ASYNC_ITERATOR => "Symbol.asyncIterator".to_string(),
+      VsSymbol::TO_PRIMITIVE => "Symbol.toPrimitive".to_string(),
+      VsSymbol::HAS_INSTANCE => "Symbol.hasInstance".to_string(),
+      VsSymbol::TO_STRING_TAG => "Symbol.toStringTag".to_string(),
+      VsSymbol::GENERATOR_RETURN => "GeneratorReturn".to_string(),
+      VsSymbol::CLASS_NAME => "ClassName".to_string(),
+      VsSymbol::Registered(key) => key.to_string(),
+    }
+  }
+
+  /// The key used to register this symbol, for `Symbol.keyFor`. Well-known symbols are not in the
+  /// registry.
+  pub fn registry_key(&self) -> Option<Rc<str>> {
+    match self {
+      VsSymbol::ITERATOR
+      | VsSymbol::ASYNC_ITERATOR
+      | VsSymbol::TO_PRIMITIVE
+      | VsSymbol::HAS_INSTANCE
+      | VsSymbol::TO_STRING_TAG
+      | VsSymbol::GENERATOR_RETURN
+      | VsSymbol::CLASS_NAME => None,
+      VsSymbol::Registered(key) => Some(key.clone()),
+    }
   }
 }
 
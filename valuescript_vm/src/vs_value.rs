@@ -1,22 +1,24 @@
 use core::fmt;
 use std::any::Any;
 use std::fmt::Display;
-use std::rc::Rc;
 use std::str::FromStr;
 
 use num_bigint::BigInt;
 use num_traits::cast::ToPrimitive;
 use num_traits::Zero;
+use valuescript_common::number_to_string;
 
 use crate::copy_counter::CopyCounter;
 use crate::native_function::ThisWrapper;
 use crate::operations::{op_sub, op_submov};
+use crate::rc::{MaybeSync, Rc};
 use crate::stack_frame::StackFrame;
 use crate::vs_array::VsArray;
 use crate::vs_class::VsClass;
 use crate::vs_function::VsFunction;
 use crate::vs_object::VsObject;
-use crate::vs_symbol::{symbol_to_name, VsSymbol};
+use crate::vs_string::VsString;
+use crate::vs_symbol::VsSymbol;
 
 #[derive(Clone, Debug, Default)]
 pub enum Val {
@@ -28,7 +30,7 @@ pub enum Val {
   Number(f64),
   BigInt(BigInt),
   Symbol(VsSymbol),
-  String(Rc<str>),
+  String(VsString),
   Array(Rc<VsArray>),
   Object(Rc<VsObject>),
   Function(Rc<VsFunction>),
@@ -77,7 +79,7 @@ pub enum LoadFunctionResult {
   NativeFunction(fn(this: ThisWrapper, params: Vec<Val>) -> Result<Val, Val>),
 }
 
-pub trait ValTrait: fmt::Display {
+pub trait ValTrait: fmt::Display + MaybeSync {
   fn typeof_(&self) -> VsType;
   fn to_number(&self) -> f64;
   fn to_index(&self) -> Option<usize>;
@@ -199,7 +201,7 @@ impl ValTrait for Val {
       Number(x) => *x,
       BigInt(x) => x.to_f64().unwrap_or(f64::NAN),
       Symbol(_) => f64::NAN, // TODO: Should be TypeError
-      String(s) => f64::from_str(s).unwrap_or(f64::NAN),
+      String(s) => string_to_number(s),
       Array(vals) => match vals.elements.len() {
         0 => 0_f64,
         1 => vals.elements[0].to_number(),
@@ -225,9 +227,10 @@ impl ValTrait for Val {
       Number(x) => number_to_index(*x),
       BigInt(b) => number_to_index(b.to_f64().unwrap_or(f64::NAN)),
       Symbol(_) => None,
+      // Only canonical numeric strings are indexes, so "01" and "1.0" are ordinary keys
       String(s) => match f64::from_str(s) {
-        Ok(x) => number_to_index(x),
-        Err(_) => None,
+        Ok(x) if number_to_string(x) == s.as_str() => number_to_index(x),
+        _ => None,
       },
       Array(_) => None,
       Object(_) => None,
@@ -423,7 +426,7 @@ impl ValTrait for Val {
           Some(i) => i,
         };
 
-        Some(index < array.elements.len())
+        Some(!matches!(array.elements.get(index), None | Some(Val::Void)))
       }
       Val::Object(object) => match key {
         Val::Symbol(symbol) => {
@@ -473,7 +476,10 @@ impl ValTrait for Val {
       Val::Bool(_) => self.to_string(),
       Val::Number(_) => self.to_string(),
       Val::BigInt(_) => self.to_string() + "n",
-      Val::Symbol(s) => format!("Symbol.{}", symbol_to_name(s.clone())),
+      Val::Symbol(s) => match s.registry_key() {
+        Some(key) => format!("Symbol.for({})", stringify_string(&key)),
+        None => s.description(),
+      },
       Val::String(str) => stringify_string(str),
       Val::Array(vals) => {
         if vals.elements.is_empty() {
@@ -498,12 +504,8 @@ impl ValTrait for Val {
       Val::Object(object) => {
         let mut res = String::new();
 
-        if let Some(proto) = &object.prototype {
-          if let Ok(name) = proto.sub(&"name".to_val()) {
-            if name.typeof_() == VsType::String {
-              res += &name.to_string();
-            }
-          }
+        if let Some(name) = prototype_name(object).or_else(|| to_string_tag(self)) {
+          res += &name;
         }
 
         if object.string_map.is_empty() {
@@ -553,19 +555,9 @@ impl fmt::Display for Val {
       Undefined => write!(f, "undefined"),
       Null => write!(f, "null"),
       Bool(b) => b.fmt(f),
-      Number(x) => {
-        if x.is_infinite() {
-          if x.is_sign_positive() {
-            write!(f, "Infinity")
-          } else {
-            write!(f, "-Infinity")
-          }
-        } else {
-          x.fmt(f)
-        }
-      } // TODO: Match js's number string format
+      Number(x) => f.write_str(&number_to_string(*x)),
       BigInt(x) => x.fmt(f),
-      Symbol(s) => write!(f, "Symbol(Symbol.{})", symbol_to_name(s.clone())),
+      Symbol(s) => write!(f, "Symbol({})", s.description()),
       String(s) => s.fmt(f),
       Array(vals) => {
         if vals.elements.is_empty() {
@@ -590,7 +582,10 @@ impl fmt::Display for Val {
           Ok(())
         }
       }
-      Object(_) => write!(f, "[object Object]"),
+      Object(_) => match to_string_tag(self) {
+        Some(tag) => write!(f, "[object {}]", tag),
+        None => write!(f, "[object Object]"),
+      },
       Function(_) => write!(f, "[function]"),
       Class(_) => write!(f, "[class]"),
       Static(val) => val.fmt(f),
@@ -626,13 +621,13 @@ impl ToVal for char {
 
 impl ToVal for &str {
   fn to_val(self) -> Val {
-    Val::String(Rc::from(self.to_owned()))
+    Val::String(VsString::from(self))
   }
 }
 
 impl ToVal for String {
   fn to_val(self) -> Val {
-    Val::String(Rc::from(self))
+    Val::String(VsString::from(self))
   }
 }
 
@@ -725,12 +720,16 @@ impl<'a> std::fmt::Display for PrettyVal<'a> {
         write!(f, " ]")
       }
       Val::Object(object) => {
-        if let Some(proto) = &object.prototype {
-          if let Ok(name) = proto.sub(&"name".to_val()) {
-            if name.typeof_() == VsType::String {
-              write!(f, "{} ", name)?;
-            }
-          }
+        let name = match object.sub(&VsSymbol::CLASS_NAME.to_val()) {
+          Val::String(class_name) => Some(class_name.to_string()),
+          _ => prototype_name(object),
+        };
+
+        match (name, to_string_tag(self.val)) {
+          (Some(name), Some(tag)) if name != tag => write!(f, "{} [{}] ", name, tag)?,
+          (Some(name), _) => write!(f, "{} ", name)?,
+          (None, Some(tag)) => write!(f, "Object [{}] ", tag)?,
+          (None, None) => {}
         }
 
         if object.string_map.is_empty() {
@@ -775,6 +774,69 @@ impl<'a> std::fmt::Display for PrettyVal<'a> {
   }
 }
 
+impl Val {
+  /// Like codify, but only the first `depth` levels of arrays and objects are shown. Deeper ones are
+  /// abbreviated as `[Array]` and `[Object]`. Used by `Debug.inspect`.
+  pub fn inspect(&self, depth: usize) -> String {
+    match self {
+      Val::Array(array) => {
+        if array.elements.is_empty() {
+          return "[]".to_string();
+        }
+
+        if depth == 0 {
+          return "[Array]".to_string();
+        }
+
+        let elements = array
+          .elements
+          .iter()
+          .map(|el| el.inspect(depth - 1))
+          .collect::<Vec<_>>();
+
+        format!("[{}]", elements.join(","))
+      }
+      Val::Object(object) => {
+        let name = prototype_name(object).or_else(|| to_string_tag(self));
+
+        if object.string_map.is_empty() {
+          return format!("{}{{}}", name.unwrap_or_default());
+        }
+
+        if depth == 0 {
+          return format!("[{}]", name.as_deref().unwrap_or("Object"));
+        }
+
+        let properties = object
+          .string_map
+          .iter()
+          .map(|(k, v)| format!("{}:{}", stringify_string(k), v.inspect(depth - 1)))
+          .collect::<Vec<_>>();
+
+        format!("{}{{{}}}", name.unwrap_or_default(), properties.join(","))
+      }
+      _ => self.codify(),
+    }
+  }
+}
+
+/// The `name` provided by the prototype of an object, which is how errors are displayed as eg
+/// `TypeError{"message":"..."}`.
+fn prototype_name(object: &VsObject) -> Option<String> {
+  match object.prototype.as_ref()?.sub(&"name".to_val()) {
+    Ok(name) if name.typeof_() == VsType::String => Some(name.to_string()),
+    _ => None,
+  }
+}
+
+/// The `Symbol.toStringTag` of a value (eg "Map" in "[object Map]"), if it's a string.
+fn to_string_tag(val: &Val) -> Option<String> {
+  match val.sub(&VsSymbol::TO_STRING_TAG.to_val()) {
+    Ok(Val::String(tag)) => Some(tag.to_string()),
+    _ => None,
+  }
+}
+
 pub fn number_to_index(x: f64) -> Option<usize> {
   if x < 0_f64 || x != x.floor() {
     return None;
@@ -783,6 +845,57 @@ pub fn number_to_index(x: f64) -> Option<usize> {
   Some(x as usize)
 }
 
+/// Converts a string to a number the way JavaScript does (StringToNumber in the spec), which is
+/// stricter than f64::from_str in some ways ("inf" is NaN) and looser in others (surrounding
+/// whitespace, "0x" prefixes, and "" being 0).
+pub fn string_to_number(s: &str) -> f64 {
+  let s = s.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}');
+
+  if s.is_empty() {
+    return 0_f64;
+  }
+
+  let radix_digits = match s.get(..2) {
+    Some("0x" | "0X") => Some((16, &s[2..])),
+    Some("0o" | "0O") => Some((8, &s[2..])),
+    Some("0b" | "0B") => Some((2, &s[2..])),
+    _ => None,
+  };
+
+  if let Some((radix, digits)) = radix_digits {
+    if digits.is_empty() {
+      return f64::NAN;
+    }
+
+    let mut res = 0_f64;
+
+    for c in digits.chars() {
+      match c.to_digit(radix) {
+        Some(digit) => res = res * radix as f64 + digit as f64,
+        None => return f64::NAN,
+      }
+    }
+
+    return res;
+  }
+
+  match s {
+    "Infinity" | "+Infinity" => return f64::INFINITY,
+    "-Infinity" => return f64::NEG_INFINITY,
+    _ => {}
+  }
+
+  // Excludes the other spellings of infinity and NaN that f64::from_str accepts
+  if !s
+    .chars()
+    .all(|c| matches!(c, '0'..='9' | '.' | 'e' | 'E' | '+' | '-'))
+  {
+    return f64::NAN;
+  }
+
+  f64::from_str(s).unwrap_or(f64::NAN)
+}
+
 fn stringify_string(str: &str) -> String {
   let mut res: String = "\"".into();
 
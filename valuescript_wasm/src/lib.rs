@@ -1,12 +1,13 @@
-use std::{collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use wasm_bindgen::prelude::*;
 
 use valuescript_compiler::{
   asm::Value, assemble, assembly_parser::AssemblyParser, compile as compile_internal,
-  CompileResult, Diagnostic, ResolvedPath, TryToVal,
+  disassemble as disassemble_internal, CompileResult, Diagnostic, ResolvedPath, TryToVal,
 };
 use valuescript_vm::{
+  rc::Rc,
   vs_value::{ToVal, Val},
   Bytecode, LoadFunctionResult, ValTrait, VirtualMachine,
 };
@@ -17,6 +18,8 @@ use valuescript_vm::{
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+mod test_session;
+
 #[wasm_bindgen]
 extern "C" {
   fn js_get_error_message(e: &JsValue) -> String;
@@ -51,9 +54,9 @@ impl CompilerOutputWasm {
   }
 }
 
-#[wasm_bindgen]
-pub fn compile(entry_point: &str, read_file: &js_sys::Function) -> String {
-  let compile_result = compile_internal(ResolvedPath::from(entry_point.to_string()), |path| {
+/// Adapts `read_file` from JS to the reader used by the compiler.
+fn js_read_file(read_file: &js_sys::Function) -> impl Fn(&str) -> Result<String, String> + '_ {
+  move |path| {
     let call_result = read_file.call1(&JsValue::UNDEFINED, &JsValue::from_str(path));
 
     match call_result {
@@ -62,23 +65,26 @@ pub fn compile(entry_point: &str, read_file: &js_sys::Function) -> String {
         .ok_or_else(|| "read_file from JS produced non-string".into()),
       Err(err) => Err(js_get_error_message(&err)),
     }
-  });
+  }
+}
+
+#[wasm_bindgen]
+pub fn compile(entry_point: &str, read_file: &js_sys::Function) -> String {
+  let compile_result = compile_internal(
+    ResolvedPath::from(entry_point.to_string()),
+    js_read_file(read_file),
+  );
 
   serde_json::to_string(&CompilerOutputWasm::from_compile_result(compile_result))
     .expect("Failed json serialization")
 }
 
-fn run_to_result(entry_point: &str, read_file: &js_sys::Function, args: &str) -> RunResult {
-  let compile_result = compile_internal(ResolvedPath::from(entry_point.to_string()), |path| {
-    let call_result = read_file.call1(&JsValue::UNDEFINED, &JsValue::from_str(path));
-
-    match call_result {
-      Ok(result) => result
-        .as_string()
-        .ok_or_else(|| "read_file from JS produced non-string".into()),
-      Err(err) => Err(js_get_error_message(&err)),
-    }
-  });
+/// Compiles and assembles `entry_point`, or produces the failed RunResult.
+fn compile_to_bytecode(
+  entry_point: &str,
+  read_file: impl Fn(&str) -> Result<String, String>,
+) -> Result<Rc<Bytecode>, RunResult> {
+  let compile_result = compile_internal(ResolvedPath::from(entry_point.to_string()), read_file);
 
   let diagnostic_len = compile_result
     .diagnostics
@@ -87,30 +93,38 @@ fn run_to_result(entry_point: &str, read_file: &js_sys::Function, args: &str) ->
     .sum::<usize>();
 
   if diagnostic_len > 0 {
-    return RunResult {
+    return Err(RunResult {
       diagnostics: compile_result
         .diagnostics // TODO: Avoid conversion
         .into_iter()
         .map(|(path, diagnostics)| (path.to_string(), diagnostics))
         .collect(),
       output: Err("Compile failed".into()),
-    };
+    });
   }
 
-  let module = match compile_result.module {
-    Some(module) => module,
-    None => {
-      return RunResult {
+  match compile_result.module {
+    Some(module) => assemble(&module)
+      .and_then(Bytecode::shared)
+      .map_err(|err| RunResult {
         diagnostics: HashMap::default(),
-        output: Err("Compilation did not emit module".into()),
-      }
-    }
-  };
+        output: Err(err),
+      }),
+    None => Err(RunResult {
+      diagnostics: HashMap::default(),
+      output: Err("Compilation did not emit module".into()),
+    }),
+  }
+}
 
-  let bytecode = Rc::new(Bytecode::new(assemble(&module)));
+fn run_to_result(entry_point: &str, read_file: &js_sys::Function, args: &str) -> RunResult {
+  let bytecode = match compile_to_bytecode(entry_point, js_read_file(read_file)) {
+    Ok(bytecode) => bytecode,
+    Err(run_result) => return run_result,
+  };
 
-  if let LoadFunctionResult::NotAFunction =
-    VirtualMachine::read_default_export(bytecode.clone()).load_function()
+  if let Ok(LoadFunctionResult::NotAFunction) =
+    VirtualMachine::read_default_export(bytecode.clone()).map(|default| default.load_function())
   {
     return RunResult {
       diagnostics: HashMap::default(),
@@ -147,6 +161,146 @@ pub fn run(entry_point: &str, read_file: &js_sys::Function, args: &str) -> Strin
   serde_json::to_string(&result).expect("Failed json serialization")
 }
 
+/// A VM that stays alive between calls from JS, for REPLs and other stateful uses.
+///
+/// Each call receives the session state as `this`, and the changes it makes to `this` are kept
+/// for the next call. Results are JSON like those of `run`.
+#[wasm_bindgen]
+pub struct Session {
+  vm: VirtualMachine,
+  state: Val,
+}
+
+impl Default for Session {
+  fn default() -> Self {
+    Session::new()
+  }
+}
+
+#[wasm_bindgen]
+impl Session {
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> Session {
+    Session {
+      vm: VirtualMachine::default(),
+      state: Val::Undefined,
+    }
+  }
+
+  /// Compiles and loads `entry_point` (replacing the previously loaded module), then calls its
+  /// default export with `args`. If the default export is not a function, it is the output.
+  pub fn eval(&mut self, entry_point: &str, read_file: &js_sys::Function, args: &str) -> String {
+    let result = self.eval_to_result(entry_point, js_read_file(read_file), args);
+    serde_json::to_string(&result).expect("Failed json serialization")
+  }
+
+  /// Calls the export `name` of the loaded module with `args`.
+  pub fn call(&mut self, name: &str, args: &str) -> String {
+    let output = match parse_args(args) {
+      Ok(args) => self.call_to_output(name, args),
+      Err(err) => Err(err.codify()),
+    };
+
+    let result = RunResult {
+      diagnostics: HashMap::default(),
+      output,
+    };
+
+    serde_json::to_string(&result).expect("Failed json serialization")
+  }
+
+  /// The names of the exports of the loaded module (as a JSON array), with the default export as
+  /// `default`.
+  pub fn export_names(&self) -> String {
+    serde_json::to_string(&self.vm.export_names()).expect("Failed json serialization")
+  }
+
+  pub fn state(&self) -> String {
+    self.state.codify()
+  }
+
+  /// Replaces the state with a value in assembly syntax (eg `{ "count": 0 }`). Returns an error
+  /// message if the value can't be parsed.
+  pub fn set_state(&mut self, state: &str) -> Option<String> {
+    match parse_val(state) {
+      Ok(state) => {
+        self.state = state;
+        None
+      }
+      Err(err) => Some(err.codify()),
+    }
+  }
+
+  /// Discards the state, the loaded module and the rest of the VM.
+  pub fn reset(&mut self) {
+    *self = Session::new();
+  }
+}
+
+impl Session {
+  fn eval_to_result(
+    &mut self,
+    entry_point: &str,
+    read_file: impl Fn(&str) -> Result<String, String>,
+    args: &str,
+  ) -> RunResult {
+    let bytecode = match compile_to_bytecode(entry_point, read_file) {
+      Ok(bytecode) => bytecode,
+      Err(run_result) => return run_result,
+    };
+
+    let val_args = match parse_args(args) {
+      Ok(args) => args,
+      Err(err) => {
+        return RunResult {
+          diagnostics: HashMap::default(),
+          output: Err(err.codify()),
+        }
+      }
+    };
+
+    if let Err(err) = self.vm.load(bytecode) {
+      return RunResult {
+        diagnostics: HashMap::default(),
+        output: Err(err.codify()),
+      };
+    }
+
+    let default_export = self
+      .vm
+      .exports
+      .iter()
+      .find(|(name, _)| name == "default")
+      .map(|(_, val)| val.clone())
+      .unwrap_or(Val::Undefined);
+
+    let output = match default_export.load_function() {
+      LoadFunctionResult::NotAFunction => Ok(default_export.codify()),
+      _ => self.call_to_output("default", val_args),
+    };
+
+    RunResult {
+      diagnostics: HashMap::default(),
+      output,
+    }
+  }
+
+  fn call_to_output(&mut self, name: &str, args: Vec<Val>) -> Result<String, String> {
+    match self.vm.call_mut::<Val>(name, &mut self.state, args) {
+      Ok(result) => Ok(result.codify()),
+      Err(err) => Err(err.codify()),
+    }
+  }
+}
+
+#[wasm_bindgen]
+pub fn disassemble(bytecode: &[u8]) -> String {
+  let assembly = Bytecode::decompress(bytecode.to_vec())
+    .and_then(|bytecode| disassemble_internal(&bytecode))
+    .map(|module| module.as_lines());
+  serde_json::to_string(&assembly).expect("Failed json serialization")
+}
+
 fn parse_args(args: &str) -> Result<Vec<Val>, Val> {
   let mut assembler = AssemblyParser {
     content: args,
@@ -156,7 +310,8 @@ fn parse_args(args: &str) -> Result<Vec<Val>, Val> {
   let value = assembler.assemble_value();
 
   let arr = match value {
-    Value::Array(arr) => arr,
+    Ok(Value::Array(arr)) => arr,
+    Err(diagnostic) => return Err(diagnostic.message.to_val()),
     _ => return Err("Expected array".to_val()),
   };
 
@@ -168,3 +323,15 @@ fn parse_args(args: &str) -> Result<Vec<Val>, Val> {
 
   Ok(result)
 }
+
+fn parse_val(content: &str) -> Result<Val, Val> {
+  let mut assembler = AssemblyParser {
+    content,
+    pos: content.chars().peekable(),
+  };
+
+  match assembler.assemble_value() {
+    Ok(value) => value.try_to_val(),
+    Err(diagnostic) => Err(diagnostic.message.to_val()),
+  }
+}
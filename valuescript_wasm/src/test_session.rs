@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+  use crate::Session;
+
+  const COUNTER: &str = "
+    export default function (this: { count: number }, n: number) {
+      this.count += n;
+      return this.count;
+    }
+
+    export function fail(this: { count: number }) {
+      this.count = 1000;
+      throw new Error('failed');
+    }
+
+    export const label = 'counter';
+  ";
+
+  fn read_file(path: &str) -> Result<String, String> {
+    match path {
+      "/counter.ts" => Ok(COUNTER.to_string()),
+      "/answer.ts" => Ok("export default 42;".to_string()),
+      "/broken.ts" => Ok("export default function ( {".to_string()),
+      _ => Err(format!("Not found: {}", path)),
+    }
+  }
+
+  fn eval(session: &mut Session, entry_point: &str, args: &str) -> Result<String, String> {
+    session.eval_to_result(entry_point, read_file, args).output
+  }
+
+  fn counter_session() -> Session {
+    let mut session = Session::new();
+    assert_eq!(session.set_state(r#"{ "count": 0 }"#), None);
+    assert_eq!(
+      eval(&mut session, "/counter.ts", "[1]"),
+      Ok("1".to_string())
+    );
+
+    session
+  }
+
+  #[test]
+  fn state_persists_between_calls() {
+    let mut session = counter_session();
+
+    assert_eq!(
+      session.call_to_output("default", vec![2.0.into()]),
+      Ok("3".to_string())
+    );
+    assert_eq!(session.state(), r#"{"count":3}"#);
+
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&session.call("default", "[4]")).unwrap(),
+      serde_json::json!({ "diagnostics": {}, "output": { "Ok": "7" } })
+    );
+
+    assert_eq!(session.export_names(), r#"["default","fail","label"]"#);
+  }
+
+  #[test]
+  fn evaluating_again_keeps_the_state() {
+    let mut session = counter_session();
+
+    assert_eq!(
+      eval(&mut session, "/counter.ts", "[10]"),
+      Ok("11".to_string())
+    );
+    assert_eq!(eval(&mut session, "/answer.ts", "[]"), Ok("42".to_string()));
+    assert_eq!(session.state(), r#"{"count":11}"#);
+    assert_eq!(session.export_names(), r#"["default"]"#);
+  }
+
+  #[test]
+  fn failed_calls_leave_the_state_unchanged() {
+    let mut session = counter_session();
+
+    assert_eq!(
+      session.call_to_output("fail", vec![]),
+      Err(r#"Error{"message":"failed"}"#.to_string())
+    );
+
+    assert_eq!(
+      session.call_to_output("missing", vec![]),
+      Err(r#"TypeError{"message":"missing is not exported"}"#.to_string())
+    );
+
+    assert_eq!(
+      session.call_to_output("label", vec![]),
+      Err(r#"TypeError{"message":"label is not a function"}"#.to_string())
+    );
+
+    assert_eq!(session.state(), r#"{"count":1}"#);
+  }
+
+  #[test]
+  fn invalid_input() {
+    let mut session = counter_session();
+
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&session.call("default", "2")).unwrap(),
+      serde_json::json!({ "diagnostics": {}, "output": { "Err": r#""Expected array""# } })
+    );
+
+    assert_eq!(
+      eval(&mut session, "/counter.ts", "{"),
+      Err(r#""Expected object content or end""#.to_string())
+    );
+
+    assert!(session.set_state("{ count").is_some());
+    assert_eq!(session.state(), r#"{"count":1}"#);
+  }
+
+  #[test]
+  fn compile_errors() {
+    let mut session = counter_session();
+
+    let result = session.eval_to_result("/broken.ts", read_file, "[]");
+    assert_eq!(result.output, Err("Compile failed".to_string()));
+    assert!(!result.diagnostics["/broken.ts"].is_empty());
+
+    let result = session.eval_to_result("/missing.ts", read_file, "[]");
+    assert_eq!(result.output, Err("Compile failed".to_string()));
+    assert_eq!(
+      result.diagnostics["/missing.ts"][0].message,
+      "File read failed: Not found: /missing.ts"
+    );
+
+    // The previously loaded module is still available
+    assert_eq!(
+      session.call_to_output("default", vec![1.0.into()]),
+      Ok("2".to_string())
+    );
+  }
+
+  #[test]
+  fn reset_discards_everything() {
+    let mut session = counter_session();
+    session.reset();
+
+    assert_eq!(session.state(), "undefined");
+    assert_eq!(session.export_names(), "[]");
+
+    assert_eq!(
+      session.call_to_output("default", vec![]),
+      Err(r#"TypeError{"message":"default is not exported"}"#.to_string())
+    );
+  }
+}
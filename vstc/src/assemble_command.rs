@@ -1,38 +1,133 @@
 use std::process::exit;
 
-use valuescript_compiler::{assemble, parse_module};
+use crate::handle_diagnostics_cli::handle_diagnostics_cli;
+use crate::resolve_entry_path::resolve_entry_path;
+
+use valuescript_compiler::{assemble_with_listing, link_assembly, parse_module, DiagnosticLevel};
 
 pub fn assemble_command(args: &Vec<String>) {
-  if args.len() != 3 {
-    println!("ERROR: Unrecognized command\n");
+  if args.len() == 3 && (args[2] == "-h" || args[2] == "--help") {
     show_help();
-    exit(1);
+    return;
   }
 
-  if args[2] == "-h" || args[2] == "--help" {
-    show_help();
-    return;
+  let Args {
+    input_paths,
+    output_path,
+    listing_path,
+  } = match parse_args(&args[2..]) {
+    Ok(parsed) => parsed,
+    Err(message) => {
+      println!("ERROR: {}\n", message);
+      show_help();
+      exit(1);
+    }
+  };
+
+  let mut modules = Vec::new();
+
+  for input_path in &input_paths {
+    let content = match std::fs::read_to_string(input_path) {
+      Ok(content) => content,
+      Err(_) => {
+        println!("Failed to read file {}", input_path);
+        exit(1);
+      }
+    };
+
+    match parse_module(&content) {
+      Ok(module) => modules.push((resolve_entry_path(input_path), module)),
+      Err(diagnostic) => {
+        handle_diagnostics_cli(input_path, &vec![diagnostic]);
+        exit(1);
+      }
+    }
   }
 
-  let read_result = std::fs::read_to_string(&args[2]);
+  let module = match modules.len() {
+    1 => modules.remove(0).1,
+    _ => {
+      let entry_path = modules[0].0.clone();
+      let link_result = link_assembly(modules);
 
-  if read_result.is_err() {
-    println!("Failed to read file {}", args[2]);
-    return;
+      handle_diagnostics_cli(&entry_path.path, &link_result.diagnostics);
+
+      let has_errors = link_result.diagnostics.iter().any(|diagnostic| {
+        matches!(
+          diagnostic.level,
+          DiagnosticLevel::Error | DiagnosticLevel::InternalError
+        )
+      });
+
+      match link_result.module {
+        Some(module) if !has_errors => module,
+        _ => exit(1),
+      }
+    }
+  };
+
+  let (bytecode, listing) = match assemble_with_listing(&module) {
+    Ok(result) => result,
+    Err(err) => {
+      println!("ERROR: {}", err);
+      exit(1);
+    }
+  };
+
+  if std::fs::write(&output_path, &*bytecode).is_err() {
+    println!("Failed to write file {}", output_path);
+    exit(1);
   }
 
-  let content = read_result.expect("");
-  let output_filename = "out.vsb";
+  if let Some(listing_path) = listing_path {
+    if std::fs::write(&listing_path, listing.to_string()).is_err() {
+      println!("Failed to write file {}", listing_path);
+      exit(1);
+    }
+  }
+}
+
+pub struct Args {
+  pub input_paths: Vec<String>,
+  pub output_path: String,
+  pub listing_path: Option<String>,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Args, String> {
+  let mut input_paths = Vec::<String>::new();
+  let mut output_path = "out.vsb".to_string();
+  let mut listing_path = None;
+
+  let mut i = 0;
+
+  while i < args.len() {
+    let arg = &args[i];
+    i += 1;
 
-  let module = parse_module(&content);
-  let bytecode = assemble(&module);
+    if !arg.starts_with('-') {
+      input_paths.push(arg.clone());
+      continue;
+    }
 
-  let write_result = std::fs::write(output_filename, &*bytecode);
+    match (arg.as_str(), args.get(i)) {
+      ("-o", Some(value)) => output_path = value.clone(),
+      ("--listing", Some(value)) => listing_path = Some(value.clone()),
+      ("-o" | "--listing", None) => return Err(format!("Missing value for {}", arg)),
+      _ => return Err(format!("Unrecognized option {}", arg)),
+    }
 
-  if write_result.is_err() {
-    println!("Failed to write file {}", output_filename);
-    std::process::exit(1);
+    i += 1;
   }
+
+  if input_paths.is_empty() {
+    return Err("Missing input file".to_string());
+  }
+
+  Ok(Args {
+    input_paths,
+    output_path,
+    listing_path,
+  })
 }
 
 fn show_help() {
@@ -41,5 +136,15 @@ fn show_help() {
   println!("Convert ValueScript assembly to bytecode");
   println!();
   println!("USAGE:");
-  println!("    vstc assemble <file>");
+  println!("    vstc assemble [OPTIONS] <file> [<file>...]");
+  println!();
+  println!("With several files, the first is the entry point and the files are linked together.");
+  println!("Each file can import the others by path (eg `import \"./lib.vsm\" %x`).");
+  println!();
+  println!("OPTIONS:");
+  println!("    -o <path>");
+  println!("            Output path (default: out.vsb)");
+  println!();
+  println!("    --listing <path>");
+  println!("            Also write a listing of the offset and bytes of each line of assembly");
 }
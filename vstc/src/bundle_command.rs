@@ -0,0 +1,169 @@
+use std::process::exit;
+
+use crate::handle_diagnostics_cli::handle_diagnostics_cli;
+use crate::read_source::read_source;
+use crate::resolve_entry_path::{parse_path_alias, resolve_entry_path};
+
+use valuescript_compiler::{assemble, bundle, CompileOptions, DiagnosticLevel, PathAlias};
+use valuescript_vm::Bytecode;
+
+pub struct BundleOptions {
+  pub entry_paths: Vec<String>,
+  pub output_path: String,
+  pub manifest_path: Option<String>,
+  pub compress: bool,
+  pub path_aliases: Vec<PathAlias>,
+}
+
+pub fn bundle_command(args: &Vec<String>) {
+  if args.len() == 3 && (args[2] == "-h" || args[2] == "--help") {
+    show_help();
+    return;
+  }
+
+  let options = match parse_options(&args[2..]) {
+    Ok(options) => options,
+    Err(message) => {
+      println!("ERROR: {}\n", message);
+      show_help();
+      exit(1);
+    }
+  };
+
+  let entry_points = options
+    .entry_paths
+    .iter()
+    .map(resolve_entry_path)
+    .collect::<Vec<_>>();
+
+  let bundle_result = bundle(
+    &entry_points,
+    read_source,
+    &CompileOptions {
+      path_aliases: options.path_aliases.clone(),
+      ..Default::default()
+    },
+  );
+
+  let mut has_errors = false;
+
+  for (path, diagnostics) in bundle_result.diagnostics.iter() {
+    handle_diagnostics_cli(&path.path, diagnostics);
+
+    has_errors |= diagnostics.iter().any(|diagnostic| {
+      matches!(
+        diagnostic.level,
+        DiagnosticLevel::Error | DiagnosticLevel::InternalError
+      )
+    });
+  }
+
+  let module = match bundle_result.module {
+    Some(module) if !has_errors => module,
+    _ => exit(1),
+  };
+
+  let bytecode = match assemble(&module) {
+    Ok(bytecode) => bytecode,
+    Err(err) => {
+      println!("ERROR: {}", err);
+      exit(1);
+    }
+  };
+
+  let bytecode = match options.compress {
+    true => Bytecode::compress(&bytecode),
+    false => bytecode,
+  };
+
+  write_output(&options.output_path, bytecode.as_slice());
+
+  if let Some(manifest_path) = &options.manifest_path {
+    write_output(manifest_path, bundle_result.manifest.to_json().as_bytes());
+  }
+}
+
+pub fn parse_options(args: &[String]) -> Result<BundleOptions, String> {
+  let mut entry_paths = Vec::<String>::new();
+  let mut output_path = "out.vsb".to_string();
+  let mut manifest_path = None;
+  let mut compress = false;
+  let mut path_aliases = Vec::<PathAlias>::new();
+
+  let mut i = 0;
+
+  while i < args.len() {
+    let arg = &args[i];
+    i += 1;
+
+    if !arg.starts_with('-') {
+      entry_paths.push(arg.clone());
+      continue;
+    }
+
+    if arg == "--compress" {
+      compress = true;
+      continue;
+    }
+
+    let value = match args.get(i) {
+      Some(value) => value.clone(),
+      None => return Err(format!("Missing value for {}", arg)),
+    };
+
+    i += 1;
+
+    match arg.as_str() {
+      "-o" => output_path = value,
+      "--manifest" => manifest_path = Some(value),
+      "--path-alias" => path_aliases.push(parse_path_alias(&value)?),
+      _ => return Err(format!("Unrecognized option {}", arg)),
+    }
+  }
+
+  if entry_paths.is_empty() {
+    return Err("Missing entry point".to_string());
+  }
+
+  Ok(BundleOptions {
+    entry_paths,
+    output_path,
+    manifest_path,
+    compress,
+    path_aliases,
+  })
+}
+
+fn write_output(path: &str, content: &[u8]) {
+  if std::fs::write(path, content).is_err() {
+    println!("Failed to write file {}", path);
+    exit(1);
+  }
+}
+
+fn show_help() {
+  println!("vstc bundle");
+  println!();
+  println!("Compile one or more entry points into a single bytecode file");
+  println!();
+  println!("USAGE:");
+  println!("    vstc bundle [OPTIONS] <entry point> [<entry point>...]");
+  println!();
+  println!("With several entry points, the default export of the bundle is an object with the");
+  println!("default export of each entry point (named after its file), and the named exports of");
+  println!("each entry point are grouped under its name.");
+  println!();
+  println!("OPTIONS:");
+  println!("    -o <path>");
+  println!("            Output path (default: out.vsb)");
+  println!();
+  println!("    --compress");
+  println!("            Write bytecode in a compressed container");
+  println!();
+  println!("    --manifest <path>");
+  println!("            Also write a JSON description of the exports of each entry point");
+  println!();
+  println!("    --path-alias <pattern>=<target>");
+  println!("            Resolve imports matching <pattern> to <target>, like tsconfig paths");
+  println!("            (eg @lib/*=./src/lib/*). Can be repeated.");
+}
@@ -1,34 +1,84 @@
-use std::fs::File;
-use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
-use crate::resolve_entry_path::resolve_entry_path;
+use crate::read_source::read_source;
+use crate::resolve_entry_path::{parse_path_alias, resolve_entry_path};
 
 use super::handle_diagnostics_cli::handle_diagnostics_cli;
 use valuescript_compiler::asm::Structured;
-use valuescript_compiler::compile;
+use valuescript_compiler::{assemble, compile_with_options, PathAlias};
+use valuescript_vm::Bytecode;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Emit {
+  Asm,
+  Bytecode,
+  Both,
+}
+
+struct CompileOptions {
+  emit: Emit,
+  compress: bool,
+  minify_names: bool,
+  path_aliases: Vec<PathAlias>,
+  output_path: Option<String>,
+  out_dir: Option<String>,
+  entry_path: String,
+}
 
 pub fn compile_command(args: &Vec<String>) {
-  if args.len() != 3 {
-    println!("ERROR: Unrecognized command\n");
+  if args.len() == 3 && (args[2] == "-h" || args[2] == "--help") {
     show_help();
-    exit(1);
+    return;
   }
 
-  let resolved_entry_path = resolve_entry_path(&args[2]);
+  let options = match parse_options(&args[2..]) {
+    Ok(options) => options,
+    Err(message) => {
+      println!("ERROR: {}\n", message);
+      show_help();
+      exit(1);
+    }
+  };
 
-  let compile_result = compile(resolved_entry_path, |path| {
-    std::fs::read_to_string(path).map_err(|err| err.to_string())
-  });
+  let resolved_entry_path = resolve_entry_path(&options.entry_path);
+
+  let compile_result = compile_with_options(
+    resolved_entry_path,
+    read_source,
+    &valuescript_compiler::CompileOptions {
+      minify_names: options.minify_names,
+      path_aliases: options.path_aliases.clone(),
+    },
+  );
 
   if let Some(module) = &compile_result.module {
-    let mut file = File::create("out.vsm").expect("Couldn't create out.vsm");
+    if options.emit != Emit::Bytecode {
+      let path = output_path(&options, "vsm");
+      let assembly = format!("{}\n", Structured(module));
+      write_output(&path, assembly.as_bytes());
+    }
+
+    if options.emit != Emit::Asm {
+      let path = output_path(&options, "vsb");
+      let bytecode = match assemble(module) {
+        Ok(bytecode) => bytecode,
+        Err(err) => {
+          println!("ERROR: {}", err);
+          exit(1);
+        }
+      };
 
-    file
-      .write_all(Structured(module).to_string().as_bytes())
-      .expect("Failed to write out.vsm");
+      match options.compress {
+        true => write_output(&path, &Bytecode::compress(&bytecode)),
+        false => write_output(&path, &bytecode),
+      }
+    }
+  }
 
-    file.write_all(b"\n").expect("Failed to write out.vsm");
+  if let Some(name_map) = &compile_result.name_map {
+    let path = output_path(&options, "vsm").with_extension("names");
+    write_output(&path, name_map.to_string().as_bytes());
   }
 
   for (path, diagnostics) in compile_result.diagnostics.iter() {
@@ -36,11 +86,137 @@ pub fn compile_command(args: &Vec<String>) {
   }
 }
 
+fn parse_options(args: &[String]) -> Result<CompileOptions, String> {
+  let mut emit = Emit::Asm;
+  let mut compress = false;
+  let mut minify_names = false;
+  let mut path_aliases = Vec::<PathAlias>::new();
+  let mut output_path = None;
+  let mut out_dir = None;
+  let mut entry_path = None;
+
+  let mut i = 0;
+
+  while i < args.len() {
+    let arg = &args[i];
+    i += 1;
+
+    if !arg.starts_with('-') {
+      if entry_path.is_some() {
+        return Err(format!("Unexpected argument {}", arg));
+      }
+
+      entry_path = Some(arg.clone());
+      continue;
+    }
+
+    if arg == "--compress" {
+      compress = true;
+      continue;
+    }
+
+    if arg == "--minify-names" {
+      minify_names = true;
+      continue;
+    }
+
+    let value = match args.get(i) {
+      Some(value) => value.clone(),
+      None => return Err(format!("Missing value for {}", arg)),
+    };
+
+    i += 1;
+
+    match arg.as_str() {
+      "--emit" => {
+        emit = match value.as_str() {
+          "asm" => Emit::Asm,
+          "bytecode" => Emit::Bytecode,
+          "both" => Emit::Both,
+          _ => return Err(format!("Unrecognized --emit value {}", value)),
+        }
+      }
+      "-o" => output_path = Some(value),
+      "--out-dir" => out_dir = Some(value),
+      "--path-alias" => path_aliases.push(parse_path_alias(&value)?),
+      _ => return Err(format!("Unrecognized option {}", arg)),
+    }
+  }
+
+  Ok(CompileOptions {
+    emit,
+    compress,
+    minify_names,
+    path_aliases,
+    output_path,
+    out_dir,
+    entry_path: entry_path.ok_or("Missing entry point")?,
+  })
+}
+
+/// Where to write the output with extension `ext`:
+/// - `-o` is used as is, except that its extension is replaced when emitting both outputs
+/// - With `--out-dir`, the output is named after the entry point
+/// - Otherwise, the output is `out.<ext>` in the current directory
+fn output_path(options: &CompileOptions, ext: &str) -> PathBuf {
+  let path = match (&options.output_path, &options.out_dir) {
+    (Some(output_path), _) => PathBuf::from(output_path),
+    (None, Some(out_dir)) => {
+      let stem = Path::new(&options.entry_path)
+        .file_stem()
+        .unwrap_or_else(|| "out".as_ref());
+
+      Path::new(out_dir).join(stem).with_extension(ext)
+    }
+    (None, None) => PathBuf::from("out").with_extension(ext),
+  };
+
+  match options.emit == Emit::Both && options.output_path.is_some() {
+    true => path.with_extension(ext),
+    false => path,
+  }
+}
+
+fn write_output(path: &Path, content: &[u8]) {
+  if let Some(parent) = path.parent() {
+    if !parent.as_os_str().is_empty() && std::fs::create_dir_all(parent).is_err() {
+      println!("Failed to create directory {}", parent.display());
+      exit(1);
+    }
+  }
+
+  if std::fs::write(path, content).is_err() {
+    println!("Failed to write file {}", path.display());
+    exit(1);
+  }
+}
+
 fn show_help() {
   println!("vstc compile");
   println!();
   println!("Compile ValueScript");
   println!();
   println!("USAGE:");
-  println!("    vstc compile <entry point>");
+  println!("    vstc compile [OPTIONS] <entry point>");
+  println!();
+  println!("OPTIONS:");
+  println!("    --emit <asm|bytecode|both>");
+  println!("            What to write (default: asm)");
+  println!();
+  println!("    --compress");
+  println!("            Write bytecode in a compressed container");
+  println!();
+  println!("    --minify-names");
+  println!("            Use short names for definitions and registers, and write the original");
+  println!("            names to a .names file next to the output");
+  println!();
+  println!("    -o <path>");
+  println!("            Output path (the extension is replaced when emitting both)");
+  println!();
+  println!("    --out-dir <dir>");
+  println!("            Write outputs into <dir>, named after the entry point");
+  println!();
+  println!("    --path-alias <pattern>=<target>");
+  println!("            Resolve imports matching <pattern> to <target>, like tsconfig paths");
+  println!("            (eg @lib/*=./src/lib/*). Can be repeated.");
 }
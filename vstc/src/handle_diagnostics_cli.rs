@@ -5,6 +5,8 @@ use url::Url;
 
 use valuescript_compiler::{Diagnostic, DiagnosticLevel};
 
+use crate::read_source::read_source;
+
 pub fn handle_diagnostics_cli(file_path: &String, diagnostics: &Vec<Diagnostic>) {
   let current_dir = std::env::current_dir().expect("Failed to get current directory");
   let abs_path = PathBuf::from(file_path);
@@ -12,7 +14,8 @@ pub fn handle_diagnostics_cli(file_path: &String, diagnostics: &Vec<Diagnostic>)
 
   let mut level_counts = HashMap::<DiagnosticLevel, usize>::new();
 
-  let text = std::fs::read_to_string(file_path).unwrap();
+  // Files that failed to read have a diagnostic about that, which doesn't need the text
+  let text = read_source(file_path).unwrap_or_default();
   let mut lines = Vec::<String>::new();
 
   for diagnostic in diagnostics {
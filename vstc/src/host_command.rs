@@ -0,0 +1,578 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::exit;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::json;
+use url::Url;
+
+use valuescript_vm::val_diff::{diff, sub_path, Change, PathSegment};
+use valuescript_vm::vs_serde::{from_val, to_val};
+use valuescript_vm::vs_value::Val;
+use valuescript_vm::{Bytecode, ValTrait, VirtualMachine};
+
+use crate::run_command::{format_from_path, to_bytecode};
+use crate::websocket::{
+  accept_key, read_frame, write_frame, OPCODE_CLOSE, OPCODE_PING, OPCODE_PONG, OPCODE_TEXT,
+};
+
+pub struct HostOptions {
+  pub file_path: String,
+  pub port: u16,
+}
+
+pub fn host_command(args: &[String]) {
+  if args.len() == 3 && (args[2] == "-h" || args[2] == "--help") {
+    show_help();
+    return;
+  }
+
+  let options = match parse_options(&args[2..]) {
+    Ok(options) => options,
+    Err(message) => {
+      println!("ERROR: {}\n", message);
+      show_help();
+      exit(1);
+    }
+  };
+
+  let bytecode = match to_bytecode(format_from_path(&options.file_path), &options.file_path) {
+    Ok(bytecode) => Rc::new(bytecode),
+    Err(err) => {
+      println!("ERROR: {}", err);
+      exit(1);
+    }
+  };
+
+  let listener = match TcpListener::bind(("127.0.0.1", options.port)) {
+    Ok(listener) => listener,
+    Err(err) => {
+      println!("ERROR: Failed to listen on port {}: {}", options.port, err);
+      exit(1);
+    }
+  };
+
+  println!(
+    "Hosting {} on http://127.0.0.1:{}",
+    options.file_path, options.port
+  );
+
+  if let Err(err) = host(listener, bytecode) {
+    println!("ERROR: {}", err);
+    exit(1);
+  }
+}
+
+pub fn parse_options(args: &[String]) -> Result<HostOptions, String> {
+  let mut file_path = None::<String>;
+  let mut port = 8080;
+
+  let mut i = 0;
+
+  while i < args.len() {
+    let arg = &args[i];
+    i += 1;
+
+    match arg.as_str() {
+      "--port" => {
+        let value = args
+          .get(i)
+          .ok_or_else(|| format!("Missing value for {}", arg))?;
+
+        i += 1;
+
+        port = value
+          .parse()
+          .map_err(|_| format!("Invalid port: {}", value))?;
+      }
+      option if option.starts_with('-') => return Err(format!("Unrecognized option {}", option)),
+      _ if file_path.is_none() => file_path = Some(arg.clone()),
+      _ => return Err(format!("Unexpected argument {}", arg)),
+    }
+  }
+
+  Ok(HostOptions {
+    file_path: file_path.ok_or("Missing file")?,
+    port,
+  })
+}
+
+/// Serves the state of `bytecode` on `listener`. The default export is the initial state, and the
+/// other exports are transactions, which are called with the state as `this`:
+///
+/// - `GET /state` responds with the state as JSON.
+/// - `POST /tx/<name>` runs a transaction with the JSON array in the body as its arguments (or
+///   none if the body is empty), and responds with its result. The changes the transaction makes
+///   to the state are only committed if it doesn't throw.
+/// - `GET /subscribe?path=<a/b/c>` upgrades to a WebSocket. It sends `{"value":...}` with the
+///   current value at the path, then `{"changes":[...]}` after each transaction that changes it.
+///
+/// The VM runs on the current thread, and each connection is handled on its own thread, so this
+/// doesn't return unless the VM fails to load.
+pub fn host(listener: TcpListener, bytecode: Rc<Bytecode>) -> Result<(), String> {
+  let mut vm = VirtualMachine::default();
+  vm.load(bytecode).map_err(|err| err.codify())?;
+
+  let state = vm
+    .exports
+    .iter()
+    .find(|(name, _)| name == "default")
+    .map(|(_, val)| val.clone())
+    .unwrap_or(Val::Undefined);
+
+  let (sender, receiver) = mpsc::channel::<Request>();
+
+  thread::spawn(move || {
+    for stream in listener.incoming().flatten() {
+      let sender = sender.clone();
+      thread::spawn(move || handle_connection(stream, sender));
+    }
+  });
+
+  run_vm(vm, state, receiver);
+
+  Ok(())
+}
+
+/// What the connection threads ask of the VM thread. Vals stay on the VM thread, so everything
+/// else is sent as JSON.
+enum Request {
+  State {
+    reply: Sender<Response>,
+  },
+  Transaction {
+    name: String,
+    args: serde_json::Value,
+    reply: Sender<Response>,
+  },
+  Subscribe {
+    path: Vec<String>,
+    messages: Sender<String>,
+  },
+}
+
+struct Response {
+  status: u16,
+  body: serde_json::Value,
+}
+
+impl Response {
+  fn ok(body: serde_json::Value) -> Response {
+    Response { status: 200, body }
+  }
+
+  fn error(status: u16, message: impl Into<String>) -> Response {
+    Response {
+      status,
+      body: json!({ "error": message.into() }),
+    }
+  }
+}
+
+struct Subscriber {
+  path: Vec<String>,
+  messages: Sender<String>,
+}
+
+fn run_vm(mut vm: VirtualMachine, mut state: Val, requests: Receiver<Request>) {
+  let mut subscribers = Vec::<Subscriber>::new();
+
+  for request in requests {
+    match request {
+      Request::State { reply } => {
+        let _ = reply.send(match to_json(&state) {
+          Ok(json) => Response::ok(json),
+          Err(err) => Response::error(500, err),
+        });
+      }
+      Request::Transaction { name, args, reply } => {
+        let before = state.clone();
+        let response = run_transaction(&mut vm, &mut state, &name, args);
+        let _ = reply.send(response);
+
+        subscribers.retain(|subscriber| {
+          let changes = diff(
+            &sub_path(&before, &path_segments(&before, &subscriber.path)),
+            &sub_path(&state, &path_segments(&state, &subscriber.path)),
+          );
+
+          changes.is_empty() || subscriber.messages.send(changes_message(&changes)).is_ok()
+        });
+      }
+      Request::Subscribe { path, messages } => {
+        let value = sub_path(&state, &path_segments(&state, &path));
+
+        let message = match to_json(&value) {
+          Ok(value) => json!({ "value": value }),
+          Err(err) => json!({ "error": err }),
+        };
+
+        if messages.send(message.to_string()).is_ok() {
+          subscribers.push(Subscriber { path, messages });
+        }
+      }
+    }
+  }
+}
+
+fn run_transaction(
+  vm: &mut VirtualMachine,
+  state: &mut Val,
+  name: &str,
+  args: serde_json::Value,
+) -> Response {
+  if name == "default"
+    || !vm
+      .exports
+      .iter()
+      .any(|(export_name, _)| export_name == name)
+  {
+    return Response::error(404, format!("No transaction named {}", name));
+  }
+
+  let args = match args {
+    serde_json::Value::Array(args) => args,
+    _ => return Response::error(400, "Expected an array of arguments"),
+  };
+
+  let args = match args.iter().map(to_val).collect::<Result<Vec<_>, _>>() {
+    Ok(args) => args,
+    Err(err) => return Response::error(400, err.codify()),
+  };
+
+  match vm.call_mut::<Val>(name, state, args) {
+    Ok(result) => match to_json(&result) {
+      Ok(result) => Response::ok(result),
+      Err(err) => Response::error(500, err),
+    },
+    Err(err) => Response::error(400, err.codify()),
+  }
+}
+
+/// The path of the subscription as segments of `state`. Segments are array indexes where the
+/// value they're applied to is an array, and keys otherwise.
+fn path_segments(state: &Val, path: &[String]) -> Vec<PathSegment> {
+  let mut segments = Vec::<PathSegment>::new();
+  let mut current = state.clone();
+
+  for part in path {
+    let segment = match (&current, part.parse::<usize>()) {
+      (Val::Array(_), Ok(i)) => PathSegment::Index(i),
+      _ => PathSegment::Key(part.clone()),
+    };
+
+    current = sub_path(&current, std::slice::from_ref(&segment));
+    segments.push(segment);
+  }
+
+  segments
+}
+
+fn changes_message(changes: &[Change]) -> String {
+  let path_json = |path: &Vec<PathSegment>| {
+    path
+      .iter()
+      .map(|segment| match segment {
+        PathSegment::Key(key) => json!(key),
+        PathSegment::Index(i) => json!(i),
+      })
+      .collect::<Vec<_>>()
+  };
+
+  let changes = changes
+    .iter()
+    .map(|change| match change {
+      Change::Set { path, value } => match to_json(value) {
+        Ok(value) => json!({ "op": "set", "path": path_json(path), "value": value }),
+        Err(err) => json!({ "op": "set", "path": path_json(path), "error": err }),
+      },
+      Change::Remove { path } => json!({ "op": "remove", "path": path_json(path) }),
+      Change::Truncate { path, len } => {
+        json!({ "op": "truncate", "path": path_json(path), "len": len })
+      }
+    })
+    .collect::<Vec<_>>();
+
+  json!({ "changes": changes }).to_string()
+}
+
+fn to_json(val: &Val) -> Result<serde_json::Value, String> {
+  from_val::<serde_json::Value>(val).map_err(|err| err.codify())
+}
+
+struct HttpRequest {
+  method: String,
+  url: Url,
+  headers: Vec<(String, String)>,
+  body: Vec<u8>,
+}
+
+impl HttpRequest {
+  fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(header, _)| header.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+}
+
+fn handle_connection(stream: TcpStream, requests: Sender<Request>) {
+  let mut reader = BufReader::new(match stream.try_clone() {
+    Ok(stream) => stream,
+    Err(_) => return,
+  });
+
+  let mut stream = stream;
+
+  let request = match read_request(&mut reader) {
+    Ok(request) => request,
+    Err(err) => {
+      let _ = write_response(&mut stream, &Response::error(400, err.to_string()));
+      return;
+    }
+  };
+
+  let path = request.url.path().to_string();
+
+  let response = match (request.method.as_str(), path.as_str()) {
+    ("GET", "/state") => ask_vm(&requests, |reply| Request::State { reply }),
+    ("POST", tx_path) if tx_path.starts_with("/tx/") => {
+      let args = match request.body.is_empty() {
+        true => Ok(json!([])),
+        false => serde_json::from_slice(&request.body),
+      };
+
+      match args {
+        Ok(args) => ask_vm(&requests, |reply| Request::Transaction {
+          name: tx_path["/tx/".len()..].to_string(),
+          args,
+          reply,
+        }),
+        Err(err) => Response::error(400, format!("Invalid JSON: {}", err)),
+      }
+    }
+    ("GET", "/subscribe") => {
+      subscribe(stream, reader, &request, &requests);
+      return;
+    }
+    _ => Response::error(404, format!("Not found: {} {}", request.method, path)),
+  };
+
+  let _ = write_response(&mut stream, &response);
+}
+
+fn ask_vm(
+  requests: &Sender<Request>,
+  request: impl FnOnce(Sender<Response>) -> Request,
+) -> Response {
+  let (reply, response) = mpsc::channel();
+
+  if requests.send(request(reply)).is_err() {
+    return Response::error(500, "The VM has stopped");
+  }
+
+  response
+    .recv()
+    .unwrap_or_else(|_| Response::error(500, "The VM has stopped"))
+}
+
+fn subscribe(
+  mut stream: TcpStream,
+  mut reader: BufReader<TcpStream>,
+  request: &HttpRequest,
+  requests: &Sender<Request>,
+) {
+  let key = match (
+    request.header("Upgrade"),
+    request.header("Sec-WebSocket-Key"),
+  ) {
+    (Some(upgrade), Some(key)) if upgrade.eq_ignore_ascii_case("websocket") => key,
+    _ => {
+      let response = Response::error(400, "Expected a WebSocket upgrade");
+      let _ = write_response(&mut stream, &response);
+      return;
+    }
+  };
+
+  let path = request
+    .url
+    .query_pairs()
+    .find(|(name, _)| name == "path")
+    .map(|(_, path)| {
+      path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+
+  let handshake = format!(
+    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+    accept_key(key)
+  );
+
+  if stream.write_all(handshake.as_bytes()).is_err() {
+    return;
+  }
+
+  let writer = Arc::new(Mutex::new(stream));
+  let (messages, message_receiver) = mpsc::channel::<String>();
+
+  if requests
+    .send(Request::Subscribe { path, messages })
+    .is_err()
+  {
+    return;
+  }
+
+  let message_writer = writer.clone();
+
+  thread::spawn(move || {
+    for message in message_receiver {
+      let mut stream = message_writer.lock().unwrap();
+
+      if write_frame(&mut *stream, OPCODE_TEXT, message.as_bytes()).is_err() {
+        break;
+      }
+    }
+  });
+
+  // Messages from the client are only read to respond to pings and closes. When the connection
+  // ends, the message thread stops at its next message, which unsubscribes it.
+  while let Ok(frame) = read_frame(&mut reader) {
+    let mut stream = writer.lock().unwrap();
+
+    let written = match frame.opcode {
+      OPCODE_PING => write_frame(&mut *stream, OPCODE_PONG, &frame.payload),
+      OPCODE_CLOSE => {
+        let _ = write_frame(&mut *stream, OPCODE_CLOSE, &frame.payload);
+        break;
+      }
+      _ => Ok(()),
+    };
+
+    if written.is_err() {
+      break;
+    }
+  }
+
+  let _ = writer.lock().unwrap().shutdown(std::net::Shutdown::Both);
+}
+
+/// The largest request body that's accepted.
+const MAX_BODY_LEN: usize = 1 << 24;
+
+fn read_request(reader: &mut impl BufRead) -> io::Result<HttpRequest> {
+  let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+  let mut request_line = String::new();
+  reader.read_line(&mut request_line)?;
+
+  let mut parts = request_line.split_whitespace();
+
+  let (method, target) = match (parts.next(), parts.next()) {
+    (Some(method), Some(target)) => (method.to_string(), target),
+    _ => return Err(invalid("Invalid request line")),
+  };
+
+  let url = Url::parse("http://localhost")
+    .and_then(|base| base.join(target))
+    .map_err(|_| invalid("Invalid request target"))?;
+
+  let mut headers = Vec::<(String, String)>::new();
+
+  loop {
+    let mut line = String::new();
+
+    if reader.read_line(&mut line)? == 0 {
+      return Err(invalid("Unexpected end of headers"));
+    }
+
+    let line = line.trim_end();
+
+    if line.is_empty() {
+      break;
+    }
+
+    match line.split_once(':') {
+      Some((name, value)) => headers.push((name.trim().to_string(), value.trim().to_string())),
+      None => return Err(invalid("Invalid header")),
+    }
+  }
+
+  let content_length = headers
+    .iter()
+    .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+    .map(|(_, value)| value.parse::<usize>())
+    .transpose()
+    .map_err(|_| invalid("Invalid Content-Length"))?
+    .unwrap_or(0);
+
+  if content_length > MAX_BODY_LEN {
+    return Err(invalid("Request body is too large"));
+  }
+
+  let mut body = vec![0u8; content_length];
+  reader.read_exact(&mut body)?;
+
+  Ok(HttpRequest {
+    method,
+    url,
+    headers,
+    body,
+  })
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+  let reason = match response.status {
+    200 => "OK",
+    400 => "Bad Request",
+    404 => "Not Found",
+    _ => "Internal Server Error",
+  };
+
+  let body = response.body.to_string();
+
+  write!(
+    stream,
+    "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+    response.status,
+    reason,
+    body.len(),
+    body
+  )?;
+
+  stream.flush()
+}
+
+fn show_help() {
+  println!("vstc host");
+  println!();
+  println!("Host the state of a ValueScript program over HTTP");
+  println!();
+  println!("USAGE:");
+  println!("    vstc host [OPTIONS] <file>");
+  println!();
+  println!("OPTIONS:");
+  println!("    --port <port>");
+  println!("            Listen on <port> (default: 8080)");
+  println!();
+  println!("ENDPOINTS:");
+  println!("    GET /state");
+  println!("            The state (the default export of <file>) as JSON");
+  println!();
+  println!("    POST /tx/<name>");
+  println!(
+    "            Run the export <name> on the state, with the JSON array in the body as its"
+  );
+  println!("            arguments");
+  println!();
+  println!("    GET /subscribe?path=<a/b/c>");
+  println!("            WebSocket that sends the changes to the state (or the value at the path)");
+  println!("            after each transaction");
+}
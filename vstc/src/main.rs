@@ -1,16 +1,36 @@
 mod assemble_command;
+mod bundle_command;
 mod compile_command;
 mod handle_diagnostics_cli;
+mod host_command;
+mod read_source;
 mod resolve_entry_path;
 mod run_command;
+mod test_assemble_command;
+mod test_bundle;
+mod test_embedding;
+mod test_host;
+mod test_host_command;
 mod test_inputs;
+mod test_limits;
+mod test_minify_names;
+mod test_resolve;
+mod test_run_command;
+mod websocket;
 
 use std::env;
 use std::process::exit;
 
 use assemble_command::assemble_command;
+use bundle_command::bundle_command;
 use compile_command::compile_command;
+use host_command::host_command;
 use run_command::run_command;
+use valuescript_vm::CountingAllocator;
+
+// Tracks memory for `vstc run --measure`
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator(std::alloc::System);
 
 fn main() {
   let args: Vec<String> = env::args().collect();
@@ -39,6 +59,16 @@ fn main() {
     return;
   }
 
+  if args.len() >= 2 && args[1] == "bundle" {
+    bundle_command(&args);
+    return;
+  }
+
+  if args.len() >= 2 && args[1] == "host" {
+    host_command(&args);
+    return;
+  }
+
   println!("ERROR: Unrecognized command\n");
   show_help();
   exit(1);
@@ -61,6 +91,9 @@ fn show_help() {
   println!("    compile");
   println!("            Compile an entry point");
   println!();
+  println!("    bundle");
+  println!("            Compile entry points into a single bytecode file");
+  println!();
   println!("    assemble");
   println!("            Convert assembly to bytecode");
   println!();
@@ -74,5 +107,5 @@ fn show_help() {
   println!("            Read Eval Print Loop");
   println!();
   println!("    host");
-  println!("            Start database server");
+  println!("            Host the state of a program over HTTP");
 }
@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tiny_keccak::{Hasher, Keccak};
+
+/// Reads a module for the compiler. Modules imported from https urls are downloaded into the cache
+/// directory the first time, and read from there afterwards. The cache directory is
+/// `$VSTC_CACHE_DIR`, or `~/.cache/vstc`.
+///
+/// Downloads use the `curl` command, so importing from urls requires curl to be installed.
+pub fn read_source(path: &str) -> Result<String, String> {
+  if !path.starts_with("https://") {
+    return fs::read_to_string(path).map_err(|err| err.to_string());
+  }
+
+  read_url(path, &cache_dir()?)
+}
+
+/// Reads an https url through the cache in `cache_dir`.
+///
+/// The hash of each download is recorded next to the cache (under `keccak256/`), and a cached file
+/// that doesn't match its hash is downloaded again. This only detects cached files that were
+/// damaged locally (eg truncated by a crash). It doesn't verify the downloads themselves, which
+/// are trusted as served.
+pub fn read_url(url: &str, cache_dir: &Path) -> Result<String, String> {
+  let content_path = cache_path(cache_dir, url, "https");
+  let hash_path = cache_path(cache_dir, url, "keccak256");
+
+  if let Some(content) = read_cached(&content_path, &hash_path) {
+    return Ok(content);
+  }
+
+  download(url, &content_path, &hash_path)
+}
+
+/// Reads a cached download, or None if it's missing or has been damaged since it was recorded.
+fn read_cached(cache_path: &Path, hash_path: &Path) -> Option<String> {
+  let content = fs::read_to_string(cache_path).ok()?;
+  let hash = fs::read_to_string(hash_path).ok()?;
+
+  match hash.trim() == content_hash(&content) {
+    true => Some(content),
+    false => None,
+  }
+}
+
+/// Downloads a url into the cache. Files are written to a temporary path and then renamed, so an
+/// interrupted (or concurrent) download can't leave a partial file in the cache. The hash is
+/// recorded before the download is moved into place.
+fn download(url: &str, cache_path: &Path, hash_path: &Path) -> Result<String, String> {
+  for dir in [cache_path.parent(), hash_path.parent()]
+    .into_iter()
+    .flatten()
+  {
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+  }
+
+  let tmp_path = tmp_path(cache_path);
+
+  let output = Command::new("curl")
+    .args([
+      "--silent",
+      "--show-error",
+      "--fail",
+      "--location",
+      "--output",
+    ])
+    .arg(&tmp_path)
+    .arg(url)
+    .output()
+    .map_err(|err| match err.kind() {
+      std::io::ErrorKind::NotFound => format!(
+        "Failed to download {}: importing from urls requires curl, which wasn't found",
+        url
+      ),
+      _ => format!("Failed to run curl: {}", err),
+    })?;
+
+  if !output.status.success() {
+    let _ = fs::remove_file(&tmp_path);
+
+    return Err(format!(
+      "Failed to download {}: {}",
+      url,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+
+  let result = fs::read_to_string(&tmp_path)
+    .and_then(|content| {
+      write_atomic(hash_path, &content_hash(&content))?;
+      fs::rename(&tmp_path, cache_path)?;
+
+      Ok(content)
+    })
+    .map_err(|err| format!("Failed to cache {}: {}", url, err));
+
+  if result.is_err() {
+    let _ = fs::remove_file(&tmp_path);
+  }
+
+  result
+}
+
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+  let tmp_path = tmp_path(path);
+
+  fs::write(&tmp_path, content)
+    .and_then(|()| fs::rename(&tmp_path, path))
+    .inspect_err(|_| {
+      let _ = fs::remove_file(&tmp_path);
+    })
+}
+
+/// A temporary path in the same directory (so that renaming it into place is atomic), which is
+/// unique to this process.
+fn tmp_path(path: &Path) -> PathBuf {
+  let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+  path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()))
+}
+
+pub fn content_hash(content: &str) -> String {
+  let mut k = Keccak::v256();
+  k.update(content.as_bytes());
+
+  let mut output = [0u8; 32];
+  k.finalize(&mut output);
+
+  output.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn cache_dir() -> Result<PathBuf, String> {
+  match (std::env::var_os("VSTC_CACHE_DIR"), std::env::var_os("HOME")) {
+    (Some(cache_dir), _) => Ok(PathBuf::from(cache_dir)),
+    (None, Some(home)) => Ok(PathBuf::from(home).join(".cache").join("vstc")),
+    (None, None) => Err("Set VSTC_CACHE_DIR to import from urls".to_string()),
+  }
+}
+
+/// The path for a url in a section of the cache directory.
+fn cache_path(cache_dir: &Path, url: &str, section: &str) -> PathBuf {
+  let mut path = cache_dir.join(section);
+
+  // Components are sanitized so that urls can't refer to files outside of the cache. This also
+  // removes leading dots, so urls can't refer to temporary files.
+  for component in url["https://".len()..].split('/') {
+    let component = component
+      .chars()
+      .map(
+        |c| match c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@') {
+          true => c,
+          false => '_',
+        },
+      )
+      .collect::<String>();
+
+    let component = component.trim_start_matches('.');
+
+    if !component.is_empty() {
+      path.push(component);
+    }
+  }
+
+  path
+}
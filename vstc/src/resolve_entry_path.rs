@@ -1,4 +1,4 @@
-use valuescript_compiler::{resolve_path, ResolvedPath};
+use valuescript_compiler::{resolve_path, PathAlias, ResolvedPath};
 
 pub fn resolve_entry_path(entry_path: &String) -> ResolvedPath {
   // Like cwd (current working dir), but it's cwd/file.
@@ -15,3 +15,17 @@ pub fn resolve_entry_path(entry_path: &String) -> ResolvedPath {
 
   resolve_path(&cwd_file, entry_path)
 }
+
+/// Parses `<pattern>=<target>` (eg `@lib/*=./src/lib/*`), resolving the target like an entry point.
+pub fn parse_path_alias(arg: &str) -> Result<PathAlias, String> {
+  match arg.split_once('=') {
+    Some((pattern, target)) if !pattern.is_empty() && !target.is_empty() => Ok(PathAlias {
+      pattern: pattern.to_string(),
+      target: resolve_entry_path(&target.to_string()).path,
+    }),
+    _ => Err(format!(
+      "Expected <pattern>=<target> for path alias: {}",
+      arg
+    )),
+  }
+}
@@ -1,11 +1,18 @@
 use std::fs;
 use std::rc::Rc;
+use std::time::Instant;
 use std::{ffi::OsStr, path::Path, process::exit};
 
-use valuescript_compiler::{assemble, compile, parse_module};
-use valuescript_vm::vs_value::Val;
-use valuescript_vm::{Bytecode, VirtualMachine};
+use valuescript_compiler::asm::Value;
+use valuescript_compiler::assembly_parser::AssemblyParser;
+use valuescript_compiler::{assemble, compile, parse_module, TryToVal};
+use valuescript_vm::vs_value::{ToVal, Val};
+use valuescript_vm::{
+  allocated_bytes, allocation_count, peak_allocated_bytes, reset_peak_allocated_bytes, Bytecode,
+  VirtualMachine,
+};
 
+use crate::read_source::read_source;
 use crate::resolve_entry_path::resolve_entry_path;
 
 use super::handle_diagnostics_cli::handle_diagnostics_cli;
@@ -17,36 +24,58 @@ pub fn run_command(args: &Vec<String>) {
     exit(1);
   }
 
-  let mut argpos = 2;
-
-  if args[argpos] == "-h" || args[argpos] == "--help" {
+  if args[2] == "-h" || args[2] == "--help" {
     show_help();
     return;
   }
 
-  let format = match args[argpos].chars().next() {
-    Some('-') => {
-      let res = format_from_option(&args[argpos]);
-      argpos += 1;
-      res
+  let run_args = match parse_run_args(&args[2..]) {
+    Ok(run_args) => run_args,
+    Err(err) => {
+      println!("ERROR: {}\n", err);
+      show_help();
+      exit(1);
     }
-    _ => format_from_path(&args[argpos]),
   };
 
-  let file_path = &args[argpos];
-  argpos += 1;
+  let format = run_args
+    .format
+    .unwrap_or_else(|| format_from_path(&run_args.file_path));
 
-  let bytecode = Rc::new(to_bytecode(format, file_path));
+  let bytecode = match to_bytecode(format, &run_args.file_path) {
+    Ok(bytecode) => Rc::new(bytecode),
+    Err(err) => {
+      println!("ERROR: {}", err);
+      exit(1);
+    }
+  };
 
   let mut vm = VirtualMachine::default();
 
-  let val_args: Vec<Val> = args[argpos..]
-    .iter()
-    .map(|a| Val::String(Rc::from(a.clone())))
-    .collect();
+  let memory_baseline = allocated_bytes();
+  let allocation_baseline = allocation_count();
+  reset_peak_allocated_bytes();
+  let start = Instant::now();
+
+  let result = run_entry(
+    &mut vm,
+    bytecode,
+    run_args.fn_name.as_deref(),
+    run_args.args,
+  );
+
+  if run_args.measure {
+    eprintln!("wall time: {:?}", start.elapsed());
+    eprintln!("instructions: {}", vm.step_count);
+    eprintln!(
+      "peak memory: {} bytes",
+      peak_allocated_bytes().saturating_sub(memory_baseline)
+    );
+    eprintln!("allocations: {}", allocation_count() - allocation_baseline);
+  }
 
-  match vm.run(bytecode, None, &val_args) {
-    Ok(Val::Undefined) => {},
+  match result {
+    Ok(Val::Undefined) => {}
     Ok(result) => {
       println!("{}", result.pretty());
     }
@@ -57,22 +86,102 @@ pub fn run_command(args: &Vec<String>) {
   }
 }
 
-enum RunFormat {
+/// The arguments of `vstc run` (after `run`).
+pub struct RunArgs {
+  pub format: Option<RunFormat>,
+  pub measure: bool,
+  pub fn_name: Option<String>,
+  pub file_path: String,
+  pub args: Vec<Val>,
+}
+
+pub fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+  let mut argpos = 0;
+  let mut format = None;
+  let mut measure = false;
+  let mut fn_name = None::<String>;
+  let mut json_args = None::<String>;
+  let mut file_path = None::<&String>;
+  let mut string_args = Vec::<Val>::new();
+
+  while argpos < args.len() {
+    let arg = &args[argpos];
+    argpos += 1;
+
+    match arg.as_str() {
+      "--fn" | "--args" => {
+        let value = match args.get(argpos) {
+          Some(value) => value.clone(),
+          None => return Err(format!("Missing value for {}", arg)),
+        };
+
+        argpos += 1;
+
+        match arg.as_str() {
+          "--fn" => fn_name = Some(value),
+          _ => json_args = Some(value),
+        }
+      }
+      "--measure" if file_path.is_none() => measure = true,
+      option if option.starts_with('-') && file_path.is_none() => {
+        format = Some(format_from_option(option)?)
+      }
+      _ if file_path.is_none() => file_path = Some(arg),
+      _ => string_args.push(Val::String(arg.as_str().into())),
+    }
+  }
+
+  let file_path = match file_path {
+    Some(file_path) => file_path.clone(),
+    None => return Err("Missing file".to_string()),
+  };
+
+  let args = match json_args {
+    Some(json_args) => parse_args(&json_args).map_err(|err| format!("Invalid --args: {}", err))?,
+    None => string_args,
+  };
+
+  Ok(RunArgs {
+    format,
+    measure,
+    fn_name,
+    file_path,
+    args,
+  })
+}
+
+/// Runs the export `fn_name` of `bytecode`, or the default export if it's None.
+pub fn run_entry(
+  vm: &mut VirtualMachine,
+  bytecode: Rc<Bytecode>,
+  fn_name: Option<&str>,
+  args: Vec<Val>,
+) -> Result<Val, Val> {
+  match fn_name {
+    Some(fn_name) => {
+      vm.load(bytecode)?;
+      vm.call::<Val>(fn_name, args)
+    }
+    None => vm.run(bytecode, None, &args),
+  }
+}
+
+pub enum RunFormat {
   TypeScript,
   Assembly,
   Bytecode,
 }
 
-fn format_from_option(option: &String) -> RunFormat {
-  return match option.as_str() {
-    "--typescript" => RunFormat::TypeScript,
-    "--assembly" => RunFormat::Assembly,
-    "--bytecode" => RunFormat::Bytecode,
-    _ => std::panic!("Unrecognized option {}", option),
-  };
+fn format_from_option(option: &str) -> Result<RunFormat, String> {
+  match option {
+    "--typescript" => Ok(RunFormat::TypeScript),
+    "--assembly" => Ok(RunFormat::Assembly),
+    "--bytecode" => Ok(RunFormat::Bytecode),
+    _ => Err(format!("Unrecognized option {}", option)),
+  }
 }
 
-fn format_from_path(file_path: &String) -> RunFormat {
+pub fn format_from_path(file_path: &String) -> RunFormat {
   let ext = Path::new(&file_path)
     .extension()
     .and_then(OsStr::to_str)
@@ -89,14 +198,12 @@ fn format_from_path(file_path: &String) -> RunFormat {
   }
 }
 
-fn to_bytecode(format: RunFormat, file_path: &String) -> Bytecode {
+pub fn to_bytecode(format: RunFormat, file_path: &String) -> Result<Bytecode, String> {
   Bytecode::new(match format {
     RunFormat::TypeScript => {
       let resolved_entry_path = resolve_entry_path(file_path);
 
-      let compile_result = compile(resolved_entry_path, |path| {
-        std::fs::read_to_string(path).map_err(|err| err.to_string())
-      });
+      let compile_result = compile(resolved_entry_path, read_source);
 
       for (path, diagnostics) in compile_result.diagnostics.iter() {
         handle_diagnostics_cli(&path.path, diagnostics);
@@ -106,15 +213,20 @@ fn to_bytecode(format: RunFormat, file_path: &String) -> Bytecode {
         &compile_result
           .module
           .expect("Should have exited if module is None"),
-      )
+      )?
     }
 
     RunFormat::Assembly => {
       let file_content = std::fs::read_to_string(file_path)
         .unwrap_or_else(|_| panic!("Failed to read file {}", file_path));
 
-      let module = parse_module(&file_content);
-      assemble(&module)
+      match parse_module(&file_content) {
+        Ok(module) => assemble(&module)?,
+        Err(diagnostic) => {
+          handle_diagnostics_cli(file_path, &vec![diagnostic]);
+          exit(1);
+        }
+      }
     }
 
     RunFormat::Bytecode => {
@@ -123,21 +235,53 @@ fn to_bytecode(format: RunFormat, file_path: &String) -> Bytecode {
   })
 }
 
+/// Parses `--args`, which is an array of JSON-like values (anything that's valid as a constant in
+/// assembly).
+fn parse_args(args: &str) -> Result<Vec<Val>, Val> {
+  let mut parser = AssemblyParser {
+    content: args,
+    pos: args.chars().peekable(),
+  };
+
+  let arr = match parser.assemble_value() {
+    Ok(Value::Array(arr)) => arr,
+    Err(diagnostic) => return Err(diagnostic.message.to_val()),
+    _ => return Err("Expected array".to_val()),
+  };
+
+  let mut result = Vec::<Val>::new();
+
+  for arg in arr.values {
+    result.push(arg.try_to_val()?);
+  }
+
+  Ok(result)
+}
+
 fn show_help() {
   println!("vstc run");
   println!();
   println!("Run a ValueScript program");
   println!();
   println!("USAGE:");
-  println!("    vstc run [OPTIONS] <file>");
+  println!("    vstc run [OPTIONS] <file> [args...]");
   println!();
   println!("OPTIONS:");
+  println!("    --args <array>");
+  println!("            Pass the elements of <array> (eg '[1,2]') instead of the string [args...]");
+  println!();
   println!("    --assembly");
   println!("            Interpret <file> as assembly");
   println!();
   println!("    --bytecode");
   println!("            Interpret <file> as bytecode");
   println!();
+  println!("    --fn <name>");
+  println!("            Run the export <name> instead of the default export");
+  println!();
+  println!("    --measure");
+  println!("            Report wall time, instructions, peak memory and allocations to stderr");
+  println!();
   println!("    --typescript");
   println!("            Interpret <file> as typescript");
   println!();
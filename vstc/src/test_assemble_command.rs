@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+
+  use valuescript_compiler::{assemble, link_assembly, parse_module, ResolvedPath};
+  use valuescript_vm::{Bytecode, ValTrait, VirtualMachine};
+
+  use crate::assemble_command::{parse_args, Args};
+
+  fn parse(args: &[&str]) -> Result<Args, String> {
+    parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+  }
+
+  #[test]
+  fn parses_inputs_and_options() {
+    let args = parse(&[
+      "main.vsm",
+      "-o",
+      "app.vsb",
+      "lib.vsm",
+      "--listing",
+      "app.txt",
+    ])
+    .unwrap();
+
+    assert_eq!(args.input_paths, vec!["main.vsm", "lib.vsm"]);
+    assert_eq!(args.output_path, "app.vsb");
+    assert_eq!(args.listing_path.as_deref(), Some("app.txt"));
+
+    let args = parse(&["main.vsm"]).unwrap();
+    assert_eq!(args.output_path, "out.vsb");
+    assert_eq!(args.listing_path, None);
+  }
+
+  #[test]
+  fn parse_errors() {
+    let err = |args: &[&str]| parse(args).err();
+
+    assert_eq!(err(&[]), Some("Missing input file".to_string()));
+    assert_eq!(
+      err(&["-o", "out.vsb"]),
+      Some("Missing input file".to_string())
+    );
+    assert_eq!(
+      err(&["main.vsm", "-o"]),
+      Some("Missing value for -o".to_string())
+    );
+    assert_eq!(
+      err(&["main.vsm", "--listing"]),
+      Some("Missing value for --listing".to_string())
+    );
+    assert_eq!(
+      err(&["main.vsm", "--compress"]),
+      Some("Unrecognized option --compress".to_string())
+    );
+  }
+
+  /// Links the assembly files (the first is the entry point) and runs the result, or gives the
+  /// messages of the diagnostics.
+  fn link_and_run(files: &[(&str, &str)]) -> Result<String, Vec<String>> {
+    let modules = files
+      .iter()
+      .map(|(path, content)| {
+        (
+          ResolvedPath::from(path.to_string()),
+          parse_module(content.trim()).expect("Failed to parse"),
+        )
+      })
+      .collect();
+
+    let link_result = link_assembly(modules);
+
+    if !link_result.diagnostics.is_empty() {
+      return Err(
+        link_result
+          .diagnostics
+          .iter()
+          .map(|diagnostic| diagnostic.message.clone())
+          .collect(),
+      );
+    }
+
+    let bytecode = assemble(&link_result.module.unwrap()).unwrap();
+    let bytecode = Rc::new(Bytecode::new(bytecode).unwrap());
+
+    VirtualMachine::default()
+      .run(bytecode, None, &[])
+      .map(|val| val.codify())
+      .map_err(|err| vec![err.codify()])
+  }
+
+  const MAIN: &str = r#"
+    export @main {}
+
+    @main = function() {
+      op+ @k @offset %return
+    }
+
+    @k = lazy {
+      import* "./lib/k.vsm" %return
+      sub %return "k" %return
+    }
+
+    @offset = 1
+  "#;
+
+  #[test]
+  fn links_imports_between_files() {
+    // Both files use @k, so one of them has to be renamed
+    let res = link_and_run(&[
+      ("/project/main.vsm", MAIN),
+      (
+        "/project/lib/k.vsm",
+        r#"
+          export @k { "k": @k }
+
+          @k = 41
+        "#,
+      ),
+    ]);
+
+    assert_eq!(res, Ok("42".to_string()));
+  }
+
+  #[test]
+  fn link_errors() {
+    assert_eq!(
+      link_and_run(&[("/project/main.vsm", MAIN)]),
+      Err(vec!["Module not found: /project/lib/k.vsm".to_string()])
+    );
+
+    assert_eq!(
+      link_and_run(&[]),
+      Err(vec!["No modules to link".to_string()])
+    );
+  }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use std::rc::Rc;
+
+  use valuescript_compiler::{
+    assemble, bundle, BundleResult, CompileOptions, PathAlias, ResolvedPath,
+  };
+  use valuescript_vm::{read_exports, Bytecode, ValTrait};
+
+  use crate::bundle_command::{parse_options, BundleOptions};
+
+  fn parse(args: &[&str]) -> Result<BundleOptions, String> {
+    parse_options(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+  }
+
+  #[test]
+  fn parses_options() {
+    let options = parse(&[
+      "a.ts",
+      "--compress",
+      "-o",
+      "app.vsb",
+      "b.ts",
+      "--manifest",
+      "app.json",
+      "--path-alias",
+      "@lib/*=/src/lib/*",
+    ])
+    .unwrap();
+
+    assert_eq!(options.entry_paths, vec!["a.ts", "b.ts"]);
+    assert_eq!(options.output_path, "app.vsb");
+    assert_eq!(options.manifest_path.as_deref(), Some("app.json"));
+    assert!(options.compress);
+    assert_eq!(options.path_aliases.len(), 1);
+    assert_eq!(options.path_aliases[0].pattern, "@lib/*");
+    assert_eq!(options.path_aliases[0].target, "/src/lib/*");
+  }
+
+  #[test]
+  fn parse_errors() {
+    let err = |args: &[&str]| parse(args).err();
+
+    assert_eq!(
+      err(&["--compress"]),
+      Some("Missing entry point".to_string())
+    );
+    assert_eq!(
+      err(&["a.ts", "-o"]),
+      Some("Missing value for -o".to_string())
+    );
+    assert_eq!(
+      err(&["a.ts", "--minify", "x"]),
+      Some("Unrecognized option --minify".to_string())
+    );
+    assert_eq!(
+      err(&["a.ts", "--path-alias", "@lib/*"]),
+      Some("Expected <pattern>=<target> for path alias: @lib/*".to_string())
+    );
+  }
+
+  const FILES: &[(&str, &str)] = &[
+    (
+      "/src/a.ts",
+      "
+        import { double } from '@lib/math.ts';
+        export default function () { return double(1); }
+        export const name = 'a';
+      ",
+    ),
+    (
+      "/src/b.ts",
+      "
+        import { double } from './lib/math.ts';
+        export default function () { return double(2); }
+        export function triple(x: number) { return 3 * x; }
+      ",
+    ),
+    (
+      "/src/lib/math.ts",
+      "
+        export function double(x: number) { return 2 * x; }
+        export function unused() { return 'this is not bundled'; }
+      ",
+    ),
+    ("/other/a.ts", "export default 'other';"),
+  ];
+
+  fn bundle_files(entry_points: &[&str]) -> BundleResult {
+    let files = FILES.iter().cloned().collect::<HashMap<_, _>>();
+
+    let entry_points = entry_points
+      .iter()
+      .map(|path| ResolvedPath::from(path.to_string()))
+      .collect::<Vec<_>>();
+
+    bundle(
+      &entry_points,
+      |path| {
+        files
+          .get(path)
+          .map(|content| content.to_string())
+          .ok_or_else(|| format!("Not found: {}", path))
+      },
+      &CompileOptions {
+        path_aliases: vec![PathAlias {
+          pattern: "@lib/*".to_string(),
+          target: "/src/lib/*".to_string(),
+        }],
+        ..Default::default()
+      },
+    )
+  }
+
+  fn errors(result: &BundleResult) -> Vec<String> {
+    result
+      .diagnostics
+      .values()
+      .flatten()
+      .map(|diagnostic| diagnostic.message.clone())
+      .collect()
+  }
+
+  /// The exports of the bundle, with functions called (without arguments) so that their results
+  /// can be compared.
+  fn exports(result: &BundleResult) -> Vec<(String, String)> {
+    let bytecode = assemble(result.module.as_ref().unwrap()).unwrap();
+    let bytecode = Rc::new(Bytecode::new(bytecode).unwrap());
+
+    read_exports(&bytecode)
+      .unwrap()
+      .into_iter()
+      .map(|(name, val)| (name, val.codify()))
+      .collect()
+  }
+
+  #[test]
+  fn bundles_single_entry_point() {
+    let result = bundle_files(&["/src/b.ts"]);
+    assert_eq!(errors(&result), Vec::<String>::new());
+
+    let exports = exports(&result);
+    let names = exports
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["default", "triple"]);
+
+    let listing = format!("{:?}", result.module.unwrap());
+    assert!(!listing.contains("this is not bundled"));
+
+    assert_eq!(
+      result.manifest.to_json(),
+      r#"{"entries":[{"name":"b","path":"/src/b.ts","default":true,"exports":["triple"]}]}"#
+    );
+  }
+
+  #[test]
+  fn bundles_several_entry_points() {
+    let result = bundle_files(&["/src/a.ts", "/src/b.ts"]);
+    assert_eq!(errors(&result), Vec::<String>::new());
+
+    let exports = exports(&result);
+    let names = exports
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["default", "a", "b"]);
+    assert_eq!(exports[1].1, r#"{"name":"a"}"#);
+
+    assert_eq!(
+      result.manifest.to_json(),
+      concat!(
+        r#"{"entries":["#,
+        r#"{"name":"a","path":"/src/a.ts","default":true,"exports":["name"]},"#,
+        r#"{"name":"b","path":"/src/b.ts","default":true,"exports":["triple"]}"#,
+        r#"]}"#
+      )
+    );
+  }
+
+  #[test]
+  fn bundle_errors() {
+    let result = bundle_files(&["/src/a.ts", "/other/a.ts"]);
+    assert!(errors(&result).contains(&"Another entry point is also named `a`".to_string()));
+
+    let result = bundle_files(&["/src/missing.ts"]);
+    assert!(errors(&result).contains(&"File read failed: Not found: /src/missing.ts".to_string()));
+  }
+}
@@ -0,0 +1,474 @@
+#[cfg(test)]
+mod tests {
+  use std::cell::RefCell;
+  use std::rc::Rc;
+
+  use valuescript_compiler::{assemble, compile_expr, compile_module, DiagnosticLevel};
+  use valuescript_vm::vs_value::{ToVal, Val};
+  use valuescript_vm::{Bytecode, Console, ValTrait, VirtualMachine};
+
+  fn bytecode(source: &str) -> Rc<Bytecode> {
+    let module = compile_module(source).module;
+
+    Rc::new(Bytecode::new(assemble(&module).expect("Failed to assemble")).unwrap())
+  }
+
+  fn load(source: &str) -> VirtualMachine {
+    let mut vm = VirtualMachine::default();
+    vm.load(bytecode(source)).expect("Failed to load");
+
+    vm
+  }
+
+  /// Codifies errors so that results can be compared.
+  fn codify_err<T>(res: Result<T, Val>) -> Result<T, String> {
+    res.map_err(|err| err.codify())
+  }
+
+  #[test]
+  fn call_converts_params_and_results() {
+    let mut vm = load(
+      "
+        export default function () { return 'default'; }
+        export function add(a: number, b: number) { return a + b; }
+        export function repeat(s: string, n: number) { return s.repeat(n); }
+        export function range(n: number) { return Array.from({ length: n }, (_, i) => i); }
+        export function maybe(x: boolean) { return x ? 1 : undefined; }
+      ",
+    );
+
+    assert_eq!(
+      vm.export_names(),
+      vec!["default", "add", "repeat", "range", "maybe"]
+    );
+
+    assert_eq!(
+      codify_err(vm.call::<String>("default", ())),
+      Ok("default".to_string())
+    );
+    assert_eq!(codify_err(vm.call::<f64>("add", (1.0, 2.0))), Ok(3.0));
+    assert_eq!(
+      codify_err(vm.call::<String>("repeat", ("ab", 2.0))),
+      Ok("abab".to_string())
+    );
+    assert_eq!(
+      codify_err(vm.call::<Vec<f64>>("range", (3.0,))),
+      Ok(vec![0.0, 1.0, 2.0])
+    );
+    assert_eq!(
+      codify_err(vm.call::<Option<f64>>("maybe", (true,))),
+      Ok(Some(1.0))
+    );
+    assert_eq!(
+      codify_err(vm.call::<Option<f64>>("maybe", (false,))),
+      Ok(None)
+    );
+  }
+
+  #[test]
+  fn call_errors() {
+    let mut vm = load(
+      "
+        export function add(a: number, b: number) { return a + b; }
+        export function fail() { throw new Error('failed'); }
+        export const notAFunction = 1;
+      ",
+    );
+
+    assert_eq!(
+      codify_err(vm.call::<f64>("sub", (1.0, 2.0))),
+      Err(r#"TypeError{"message":"sub is not exported"}"#.to_string())
+    );
+
+    assert_eq!(
+      codify_err(vm.call::<String>("add", (1.0, 2.0))),
+      Err(r#"TypeError{"message":"Expected string, got number"}"#.to_string())
+    );
+
+    assert_eq!(
+      codify_err(vm.call::<Val>("fail", ()).map(|val| val.codify())),
+      Err(r#"Error{"message":"failed"}"#.to_string())
+    );
+
+    assert_eq!(
+      codify_err(vm.call::<Val>("notAFunction", ()).map(|val| val.codify())),
+      Err(r#"TypeError{"message":"notAFunction is not a function"}"#.to_string())
+    );
+  }
+
+  #[test]
+  fn call_without_loading() {
+    let mut vm = VirtualMachine::default();
+
+    assert_eq!(
+      codify_err(vm.call::<Val>("default", ()).map(|val| val.codify())),
+      Err(r#"TypeError{"message":"default is not exported"}"#.to_string())
+    );
+  }
+
+  #[test]
+  fn call_mut_updates_this() {
+    let mut vm = load(
+      "
+        export function init() { return { count: 0 }; }
+
+        export function increment(this: { count: number }, by: number) {
+          this.count += by;
+          return this.count;
+        }
+      ",
+    );
+
+    let mut state = vm.call::<Val>("init", ()).unwrap();
+
+    assert_eq!(
+      codify_err(vm.call_mut::<f64>("increment", &mut state, (2.0,))),
+      Ok(2.0)
+    );
+    assert_eq!(
+      codify_err(vm.call_mut::<f64>("increment", &mut state, (3.0,))),
+      Ok(5.0)
+    );
+    assert_eq!(state.codify(), r#"{"count":5}"#);
+  }
+
+  #[test]
+  fn call_mut_leaves_this_unchanged_on_throw() {
+    let mut vm = load(
+      "
+        export function init() { return { count: 0 }; }
+
+        export function reset(this: { count: number }) {
+          this.count = 100;
+          throw new Error('reset failed');
+        }
+      ",
+    );
+
+    let mut state = vm.call::<Val>("init", ()).unwrap();
+
+    assert_eq!(
+      codify_err(
+        vm.call_mut::<Val>("reset", &mut state, ())
+          .map(|val| val.codify())
+      ),
+      Err(r#"Error{"message":"reset failed"}"#.to_string())
+    );
+
+    assert_eq!(state.codify(), r#"{"count":0}"#);
+  }
+
+  #[test]
+  fn links_registered_modules() {
+    let mut vm = VirtualMachine::builder()
+      .module(
+        "/src/math.ts",
+        bytecode(
+          "
+            import { scale } from '../config/scale.ts';
+            export default function double(x: number) { return x * 2 * scale; }
+          ",
+        ),
+      )
+      .build();
+
+    vm.register_module("/config/scale.ts", bytecode("export const scale = 10;"));
+
+    vm.load(bytecode(
+      "
+        import double from './src/math.ts';
+        import * as config from './config/scale.ts';
+        export function run(x: number) { return [double(x), config.scale]; }
+      ",
+    ))
+    .unwrap();
+
+    assert_eq!(
+      codify_err(vm.call::<Vec<f64>>("run", (2.0,))),
+      Ok(vec![40.0, 10.0])
+    );
+  }
+
+  #[test]
+  fn links_classes_from_registered_modules() {
+    let mut vm = VirtualMachine::builder()
+      .module(
+        "/point.ts",
+        bytecode(
+          "
+            export class Point {
+              constructor(public x: number, public y: number) {}
+              sum() { return this.x + this.y; }
+            }
+          ",
+        ),
+      )
+      .build();
+
+    vm.load(bytecode(
+      "
+        import { Point } from './point.ts';
+        export function run() { return new Point(1, 2).sum(); }
+      ",
+    ))
+    .unwrap();
+
+    assert_eq!(codify_err(vm.call::<f64>("run", ())), Ok(3.0));
+  }
+
+  #[test]
+  fn missing_module_throws_when_used() {
+    let mut vm = VirtualMachine::default();
+
+    vm.load(bytecode(
+      "
+        import missing from './missing.ts';
+        export function run() { return missing; }
+        export function ok() { return 'ok'; }
+      ",
+    ))
+    .unwrap();
+
+    assert_eq!(
+      codify_err(vm.call::<String>("ok", ())),
+      Ok("ok".to_string())
+    );
+
+    assert_eq!(
+      codify_err(vm.call::<Val>("run", ()).map(|val| val.codify())),
+      Err(r#"Error{"message":"Module not found: /missing.ts"}"#.to_string())
+    );
+  }
+
+  #[test]
+  fn reload_module_migrates_state() {
+    let mut vm = VirtualMachine::default();
+    vm.register_module("/counter.ts", bytecode("export const step = 1;"));
+
+    vm.load(bytecode(
+      "
+        import { step } from './counter.ts';
+        export function init() { return { count: 0 }; }
+        export function increment(this: { count: number }) { this.count += step; }
+      ",
+    ))
+    .unwrap();
+
+    let mut state = vm.call::<Val>("init", ()).unwrap();
+    vm.call_mut::<()>("increment", &mut state, ()).unwrap();
+
+    let mut state = vm
+      .reload_module(
+        "/counter.ts",
+        bytecode(
+          "
+            export const step = 10;
+            export function migrate(state: { count: number }) { return { count: state.count * 100 }; }
+          ",
+        ),
+        state,
+      )
+      .unwrap();
+
+    assert_eq!(state.codify(), r#"{"count":100}"#);
+
+    vm.call_mut::<()>("increment", &mut state, ()).unwrap();
+    assert_eq!(state.codify(), r#"{"count":110}"#);
+  }
+
+  #[test]
+  fn reload_module_without_migrate() {
+    let mut vm = VirtualMachine::default();
+    let main = bytecode("export function version() { return 1; }");
+    vm.register_module("/main.ts", main.clone());
+    vm.load(main).unwrap();
+
+    // Reloading the loaded module itself updates its exports
+    let state = vm
+      .reload_module(
+        "/main.ts",
+        bytecode("export function version() { return 2; }"),
+        Val::Number(5.0),
+      )
+      .unwrap();
+
+    assert_eq!(state.codify(), "5");
+    assert_eq!(codify_err(vm.call::<f64>("version", ())), Ok(2.0));
+  }
+
+  #[test]
+  fn reload_module_migrate_throws() {
+    let mut vm = VirtualMachine::default();
+    vm.register_module("/lib.ts", bytecode("export const x = 1;"));
+
+    let res = vm.reload_module(
+      "/lib.ts",
+      bytecode(
+        "
+          export const x = 2;
+          export function migrate() { throw new Error('incompatible state'); }
+        ",
+      ),
+      Val::Undefined,
+    );
+
+    assert_eq!(
+      codify_err(res.map(|val| val.codify())),
+      Err(r#"Error{"message":"incompatible state"}"#.to_string())
+    );
+  }
+
+  /// A console that keeps the logged lines so that tests can check them.
+  #[derive(Clone, Default)]
+  struct RecordingConsole {
+    lines: Rc<RefCell<Vec<String>>>,
+  }
+
+  impl Console for RecordingConsole {
+    fn log(&mut self, line: &str) {
+      self.lines.borrow_mut().push(line.to_string());
+    }
+  }
+
+  const TASKS_SOURCE: &str = "
+    export function logger() {
+      return (msg: string) => console.log(msg);
+    }
+
+    export function thrower() {
+      return () => { throw new Error('task failed'); };
+    }
+
+    export function queueNonFunction() {
+      queueMicrotask(1 as any);
+    }
+
+    export function start() {
+      console.log('start');
+      queueMicrotask(() => console.log('micro 1'));
+
+      queueMicrotask(() => {
+        console.log('micro 2');
+        queueMicrotask(() => console.log('micro 3'));
+      });
+    }
+  ";
+
+  #[test]
+  fn run_until_idle_runs_microtasks_first() {
+    let console = RecordingConsole::default();
+    let mut vm = VirtualMachine::builder()
+      .console(Box::new(console.clone()))
+      .build();
+    vm.load(bytecode(TASKS_SOURCE)).unwrap();
+
+    let log = vm.call::<Val>("logger", ()).unwrap();
+    vm.post_task(log.clone(), vec!["task 1".to_val()]);
+    vm.call::<()>("start", ()).unwrap();
+    vm.queue_microtask(log.clone(), vec!["micro 0".to_val()]);
+    vm.post_task(log, vec!["task 2".to_val()]);
+
+    assert!(!vm.is_idle());
+    assert_eq!(codify_err(vm.run_until_idle()), Ok(()));
+    assert!(vm.is_idle());
+
+    assert_eq!(
+      *console.lines.borrow(),
+      vec!["start", "micro 1", "micro 2", "micro 0", "micro 3", "task 1", "task 2"]
+    );
+  }
+
+  #[test]
+  fn run_until_idle_stops_at_exceptions() {
+    let console = RecordingConsole::default();
+    let mut vm = VirtualMachine::builder()
+      .console(Box::new(console.clone()))
+      .build();
+    vm.load(bytecode(TASKS_SOURCE)).unwrap();
+
+    let log = vm.call::<Val>("logger", ()).unwrap();
+    let throw = vm.call::<Val>("thrower", ()).unwrap();
+    vm.post_task(log.clone(), vec!["task 1".to_val()]);
+    vm.post_task(throw, vec![]);
+    vm.post_task(log, vec!["task 2".to_val()]);
+
+    assert_eq!(
+      codify_err(vm.run_until_idle()),
+      Err(r#"Error{"message":"task failed"}"#.to_string())
+    );
+
+    assert_eq!(*console.lines.borrow(), vec!["task 1"]);
+    assert!(!vm.is_idle());
+
+    assert_eq!(codify_err(vm.run_until_idle()), Ok(()));
+    assert_eq!(*console.lines.borrow(), vec!["task 1", "task 2"]);
+
+    assert_eq!(
+      codify_err(vm.call::<()>("queueNonFunction", ())),
+      Err(r#"TypeError{"message":"queueMicrotask: callback is not a function"}"#.to_string())
+    );
+    assert!(vm.is_idle());
+  }
+
+  /// Compiles `source` with `compile_expr` and runs it with the values of the bindings.
+  fn eval(source: &str, bindings: &[(&str, Val)]) -> Result<String, String> {
+    let names = bindings
+      .iter()
+      .map(|(name, _)| name.to_string())
+      .collect::<Vec<_>>();
+
+    let output = compile_expr(source, &names);
+
+    if let Some(diagnostic) = output
+      .diagnostics
+      .iter()
+      .find(|d| d.level == DiagnosticLevel::Error)
+    {
+      return Err(format!(
+        "{} at {}..{}",
+        diagnostic.message, diagnostic.span.lo.0, diagnostic.span.hi.0
+      ));
+    }
+
+    let bytecode = Rc::new(Bytecode::new(assemble(&output.module).unwrap()).unwrap());
+    let params = bindings
+      .iter()
+      .map(|(_, val)| val.clone())
+      .collect::<Vec<_>>();
+
+    codify_err(VirtualMachine::default().run(bytecode, None, &params)).map(|val| val.codify())
+  }
+
+  #[test]
+  fn compile_expr_uses_bindings() {
+    let bindings = [("x", Val::Number(3.0)), ("name", "abc".to_val())];
+
+    assert_eq!(eval("x * 2", &bindings), Ok("6".to_string()));
+    assert_eq!(eval("name + x", &bindings), Ok(r#""abc3""#.to_string()));
+    assert_eq!(
+      eval("const y = x + 1;\n[x, y]", &bindings),
+      Ok("[3,4]".to_string())
+    );
+    assert_eq!(
+      eval("const y = x + 1;", &bindings),
+      Ok("undefined".to_string())
+    );
+    assert_eq!(eval("", &[]), Ok("undefined".to_string()));
+  }
+
+  #[test]
+  fn compile_expr_errors() {
+    // Spans refer to the snippet rather than the wrapper around it
+    let res = eval("1 + ;", &[]);
+    assert!(
+      res.as_ref().is_err_and(|err| err.ends_with("at 4..5")),
+      "{:?}",
+      res
+    );
+
+    assert_eq!(
+      eval("throw new Error('boom')", &[]),
+      Err(r#"Error{"message":"boom"}"#.to_string())
+    );
+  }
+}
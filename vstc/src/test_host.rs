@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+
+  use valuescript_compiler::{assemble, compile_module};
+  use valuescript_vm::{Bytecode, Locale, NumberFormat, ValTrait, VirtualMachine};
+
+  fn bytecode(source: &str) -> Rc<Bytecode> {
+    let module = compile_module(source).module;
+
+    Rc::new(Bytecode::new(assemble(&module).expect("Failed to assemble")).unwrap())
+  }
+
+  fn run(vm: &mut VirtualMachine, source: &str) -> Result<String, String> {
+    vm.run(bytecode(source), None, &[])
+      .map(|val| val.codify())
+      .map_err(|err| err.codify())
+  }
+
+  /// Formats like de-DE, and includes the requested locales so the test can check them.
+  struct GermanLocale;
+
+  impl Locale for GermanLocale {
+    fn format_number(&mut self, locales: &[String], number: f64, format: &NumberFormat) -> String {
+      let formatted = format!("{:.*}", format.max_fraction_digits, number).replace('.', ",");
+
+      format!("{} ({})", formatted, locales.join("|"))
+    }
+  }
+
+  #[test]
+  fn to_locale_string_uses_default_locale() {
+    let mut vm = VirtualMachine::default();
+
+    let res = run(
+      &mut vm,
+      "
+        export default function () {
+          return [(1234.5).toLocaleString(), (1234.5).toLocaleString('de-DE')];
+        }
+      ",
+    );
+
+    assert_eq!(res, Ok(r#"["1,234.5","1,234.5"]"#.to_string()));
+  }
+
+  #[test]
+  fn to_locale_string_uses_host_locale() {
+    let mut vm = VirtualMachine::builder()
+      .locale(Box::new(GermanLocale))
+      .build();
+
+    let res = run(
+      &mut vm,
+      "
+        export default function () {
+          return [
+            (1.5).toLocaleString(),
+            (1.5).toLocaleString('de-DE', { maximumFractionDigits: 2 }),
+            (1.5).toLocaleString(['de-AT', 'de-DE'], { maximumFractionDigits: 1 }),
+          ];
+        }
+      ",
+    );
+
+    assert_eq!(
+      res,
+      Ok(r#"["1,500 ()","1,50 (de-DE)","1,5 (de-AT|de-DE)"]"#.to_string())
+    );
+  }
+}
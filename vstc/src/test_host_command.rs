@@ -0,0 +1,367 @@
+#[cfg(test)]
+mod tests {
+  use std::io::{BufRead, BufReader, Read, Write};
+  use std::net::{SocketAddr, TcpListener, TcpStream};
+  use std::rc::Rc;
+  use std::thread;
+
+  use valuescript_compiler::{assemble, compile_module};
+  use valuescript_vm::Bytecode;
+
+  use crate::host_command::{host, parse_options, HostOptions};
+  use crate::websocket::{accept_key, read_frame, write_frame, OPCODE_CLOSE, OPCODE_TEXT};
+
+  const SOURCE: &str = "
+    type State = { todos: { title: string; done: boolean }[]; count: number };
+
+    const state: State = { todos: [], count: 0 };
+    export default state;
+
+    export function add(this: State, title: string) {
+      this.todos.push({ title, done: false });
+      this.count++;
+
+      return this.count;
+    }
+
+    export function complete(this: State, i: number) {
+      this.todos[i].done = true;
+    }
+
+    export function fail(this: State) {
+      this.count = 1000;
+      throw new Error('failed');
+    }
+  ";
+
+  fn bytecode(source: &str) -> Rc<Bytecode> {
+    let module = compile_module(source).module;
+
+    Rc::new(Bytecode::new(assemble(&module).expect("Failed to assemble")).unwrap())
+  }
+
+  fn start_host() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || host(listener, bytecode(SOURCE)));
+
+    addr
+  }
+
+  fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+
+    write!(
+      stream,
+      "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+      method,
+      path,
+      body.len(),
+      body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+
+    (status, body.to_string())
+  }
+
+  fn parse(args: &[&str]) -> Result<HostOptions, String> {
+    parse_options(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+  }
+
+  #[test]
+  fn parses_options() {
+    let options = parse(&["app.ts", "--port", "3000"]).unwrap();
+    assert_eq!(options.file_path, "app.ts");
+    assert_eq!(options.port, 3000);
+
+    assert_eq!(parse(&["app.ts"]).unwrap().port, 8080);
+
+    let err = |args: &[&str]| parse(args).err();
+    assert_eq!(err(&[]), Some("Missing file".to_string()));
+    assert_eq!(
+      err(&["app.ts", "--port"]),
+      Some("Missing value for --port".to_string())
+    );
+    assert_eq!(
+      err(&["app.ts", "--port", "x"]),
+      Some("Invalid port: x".to_string())
+    );
+    assert_eq!(
+      err(&["--watch"]),
+      Some("Unrecognized option --watch".to_string())
+    );
+    assert_eq!(
+      err(&["a.ts", "b.ts"]),
+      Some("Unexpected argument b.ts".to_string())
+    );
+  }
+
+  #[test]
+  fn transactions() {
+    let addr = start_host();
+
+    assert_eq!(
+      request(addr, "GET", "/state", ""),
+      (200, r#"{"count":0,"todos":[]}"#.to_string())
+    );
+
+    assert_eq!(
+      request(addr, "POST", "/tx/add", r#"["write tests"]"#),
+      (200, "1".to_string())
+    );
+
+    assert_eq!(
+      request(addr, "POST", "/tx/complete", "[0]"),
+      (200, "null".to_string())
+    );
+
+    assert_eq!(
+      request(addr, "GET", "/state", ""),
+      (
+        200,
+        r#"{"count":1,"todos":[{"done":true,"title":"write tests"}]}"#.to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn transaction_errors() {
+    let addr = start_host();
+
+    assert_eq!(
+      request(addr, "POST", "/tx/fail", ""),
+      (
+        400,
+        r#"{"error":"Error{\"message\":\"failed\"}"}"#.to_string()
+      )
+    );
+
+    assert_eq!(
+      request(addr, "POST", "/tx/remove", ""),
+      (
+        404,
+        r#"{"error":"No transaction named remove"}"#.to_string()
+      )
+    );
+
+    assert_eq!(
+      request(addr, "POST", "/tx/default", ""),
+      (
+        404,
+        r#"{"error":"No transaction named default"}"#.to_string()
+      )
+    );
+
+    assert_eq!(request(addr, "POST", "/tx/add", "[").0, 400);
+
+    assert_eq!(
+      request(addr, "POST", "/tx/add", r#"{"title":"x"}"#),
+      (
+        400,
+        r#"{"error":"Expected an array of arguments"}"#.to_string()
+      )
+    );
+
+    assert_eq!(
+      request(addr, "DELETE", "/state", ""),
+      (404, r#"{"error":"Not found: DELETE /state"}"#.to_string())
+    );
+
+    // The failed transactions didn't change the state
+    assert_eq!(
+      request(addr, "GET", "/state", ""),
+      (200, r#"{"count":0,"todos":[]}"#.to_string())
+    );
+  }
+
+  /// A client of `/subscribe`.
+  struct Subscription {
+    reader: BufReader<TcpStream>,
+  }
+
+  impl Subscription {
+    fn connect(addr: SocketAddr, path: &str) -> Subscription {
+      let key = "dGhlIHNhbXBsZSBub25jZQ==";
+      let mut stream = TcpStream::connect(addr).unwrap();
+
+      write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        path, key
+      )
+      .unwrap();
+
+      let mut reader = BufReader::new(stream);
+      let mut headers = Vec::<String>::new();
+
+      loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+
+        match line.trim_end() {
+          "" => break,
+          line => headers.push(line.to_string()),
+        }
+      }
+
+      assert_eq!(headers[0], "HTTP/1.1 101 Switching Protocols");
+      assert!(headers.contains(&format!("Sec-WebSocket-Accept: {}", accept_key(key))));
+
+      Subscription { reader }
+    }
+
+    /// Reads a message from the server, whose frames aren't masked.
+    fn message(&mut self) -> String {
+      let mut header = [0u8; 2];
+      self.reader.read_exact(&mut header).unwrap();
+      assert_eq!(header[0], 0x80 | OPCODE_TEXT);
+
+      let len = match header[1] {
+        126 => {
+          let mut len = [0u8; 2];
+          self.reader.read_exact(&mut len).unwrap();
+          u16::from_be_bytes(len) as usize
+        }
+        len => len as usize,
+      };
+
+      let mut payload = vec![0u8; len];
+      self.reader.read_exact(&mut payload).unwrap();
+
+      String::from_utf8(payload).unwrap()
+    }
+  }
+
+  #[test]
+  fn subscriptions() {
+    let addr = start_host();
+
+    let mut all = Subscription::connect(addr, "/subscribe");
+    let mut first = Subscription::connect(addr, "/subscribe?path=todos/0");
+
+    assert_eq!(all.message(), r#"{"value":{"count":0,"todos":[]}}"#);
+    assert_eq!(first.message(), r#"{"value":null}"#);
+
+    request(addr, "POST", "/tx/add", r#"["a"]"#);
+
+    assert_eq!(
+      all.message(),
+      concat!(
+        r#"{"changes":["#,
+        r#"{"op":"set","path":["todos",0],"value":{"done":false,"title":"a"}},"#,
+        r#"{"op":"set","path":["count"],"value":1}"#,
+        r#"]}"#,
+      )
+    );
+
+    assert_eq!(
+      first.message(),
+      r#"{"changes":[{"op":"set","path":[],"value":{"done":false,"title":"a"}}]}"#
+    );
+
+    // Neither the failed transaction nor the second todo change the first one
+    request(addr, "POST", "/tx/fail", "");
+    request(addr, "POST", "/tx/add", r#"["b"]"#);
+    request(addr, "POST", "/tx/complete", "[0]");
+
+    assert_eq!(
+      first.message(),
+      r#"{"changes":[{"op":"set","path":["done"],"value":true}]}"#
+    );
+
+    assert_eq!(
+      all.message(),
+      concat!(
+        r#"{"changes":["#,
+        r#"{"op":"set","path":["todos",1],"value":{"done":false,"title":"b"}},"#,
+        r#"{"op":"set","path":["count"],"value":2}"#,
+        r#"]}"#,
+      )
+    );
+  }
+
+  #[test]
+  fn subscription_requires_upgrade() {
+    let addr = start_host();
+
+    assert_eq!(
+      request(addr, "GET", "/subscribe", ""),
+      (
+        400,
+        r#"{"error":"Expected a WebSocket upgrade"}"#.to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn closing_a_subscription() {
+    let addr = start_host();
+
+    let mut subscription = Subscription::connect(addr, "/subscribe?path=count");
+    assert_eq!(subscription.message(), r#"{"value":0}"#);
+
+    // A masked close frame, as sent by a client
+    let mask = [1u8, 2, 3, 4];
+    let payload = 1000u16.to_be_bytes();
+
+    let mut frame = vec![0x80 | OPCODE_CLOSE, 0x80 | payload.len() as u8];
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().zip(mask).map(|(byte, mask)| byte ^ mask));
+
+    subscription.reader.get_mut().write_all(&frame).unwrap();
+
+    let mut close = vec![];
+    subscription.reader.read_to_end(&mut close).unwrap();
+    assert_eq!(close, [0x80 | OPCODE_CLOSE, 2, 0x03, 0xe8]);
+
+    // The host carries on without the subscriber
+    assert_eq!(
+      request(addr, "POST", "/tx/add", r#"["a"]"#),
+      (200, "1".to_string())
+    );
+  }
+
+  #[test]
+  fn websocket_frames() {
+    // The example from RFC 6455
+    assert_eq!(
+      accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+      "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+
+    for len in [0, 125, 126, 65535, 65536] {
+      let payload = vec![b'x'; len];
+
+      let mut frame = vec![];
+      write_frame(&mut frame, OPCODE_TEXT, &payload).unwrap();
+
+      let header_len = match len {
+        0..=125 => 2,
+        126..=65535 => 4,
+        _ => 10,
+      };
+
+      assert_eq!(frame.len(), header_len + len);
+      assert_eq!(frame[0], 0x80 | OPCODE_TEXT);
+
+      // Unmasked frames are refused, because clients must mask them
+      assert!(read_frame(&mut &frame[..]).is_err());
+    }
+
+    let mask = [0x37, 0xfa, 0x21, 0x3d];
+    let mut frame = vec![0x80 | OPCODE_TEXT, 0x80 | 5];
+    frame.extend_from_slice(&mask);
+    frame.extend(b"Hello".iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+
+    let read = read_frame(&mut &frame[..]).unwrap();
+    assert_eq!(read.opcode, OPCODE_TEXT);
+    assert_eq!(read.payload, b"Hello");
+  }
+}
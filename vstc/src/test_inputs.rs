@@ -84,7 +84,10 @@ mod tests {
             .module
             .expect("Should have exited if module is None");
 
-          let bytecode = Rc::new(Bytecode::new(assemble(&module)));
+          let bytecode = Rc::new(
+            Bytecode::new(assemble(&module).expect("Failed to assemble"))
+              .expect("Failed to load bytecode"),
+          );
 
           let assembly = Structured(&module).to_string();
 
@@ -98,19 +101,26 @@ mod tests {
             fs::write(file, &assembly)?;
           }
 
-          let parsed_assembly = parse_module(&assembly);
-          let assembly_from_parse = Structured(&parsed_assembly).to_string();
+          match parse_module(&assembly) {
+            Ok(parsed_assembly) => {
+              let assembly_from_parse = Structured(&parsed_assembly).to_string();
 
-          if assembly_from_parse != assembly {
-            println!("  Parsed assembly doesn't match assembly");
-            failed_paths.insert(rel_file_path.clone());
-          }
+              if assembly_from_parse != assembly {
+                println!("  Parsed assembly doesn't match assembly");
+                failed_paths.insert(rel_file_path.clone());
+              }
 
-          let bytecode_via_assembly = assemble(&parsed_assembly);
+              let bytecode_via_assembly = assemble(&parsed_assembly).expect("Failed to assemble");
 
-          if bytecode.code != bytecode_via_assembly {
-            println!("  Bytecode mismatch between original and parsed assembly");
-            failed_paths.insert(rel_file_path.clone());
+              if bytecode.code != bytecode_via_assembly {
+                println!("  Bytecode mismatch between original and parsed assembly");
+                failed_paths.insert(rel_file_path.clone());
+              }
+            }
+            Err(diagnostic) => {
+              println!("  Failed to parse assembly: {}", diagnostic.message);
+              failed_paths.insert(rel_file_path.clone());
+            }
           }
 
           let mut vm = VirtualMachine::default();
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+
+  use valuescript_compiler::{assemble, compile_module};
+  use valuescript_vm::{Bytecode, ValTrait, VirtualMachine};
+
+  fn bytecode(source: &str) -> Rc<Bytecode> {
+    let module = compile_module(source).module;
+
+    Rc::new(Bytecode::new(assemble(&module).expect("Failed to assemble")).unwrap())
+  }
+
+  fn run(vm: &mut VirtualMachine, source: &str) -> Result<String, String> {
+    vm.run(bytecode(source), None, &[])
+      .map(|val| val.codify())
+      .map_err(|err| err.codify())
+  }
+
+  #[test]
+  fn step_limit_applies_to_getters() {
+    let mut vm = VirtualMachine::builder().step_limit(10_000).build();
+
+    let res = run(
+      &mut vm,
+      "
+        export default function () {
+          const o = {
+            get a() {
+              let i = 0;
+              while (true) { i++; }
+              return i;
+            },
+          };
+
+          return o.a;
+        }
+      ",
+    );
+
+    assert_eq!(
+      res,
+      Err(r#"InternalError{"message":"step limit reached"}"#.to_string())
+    );
+  }
+
+  #[test]
+  fn step_limit_applies_to_value_of() {
+    let mut vm = VirtualMachine::builder().step_limit(10_000).build();
+
+    let res = run(
+      &mut vm,
+      "
+        export default function () {
+          const x = {
+            valueOf() {
+              let i = 0;
+              while (true) { i++; }
+              return i;
+            },
+          };
+
+          return x + 1;
+        }
+      ",
+    );
+
+    assert_eq!(
+      res,
+      Err(r#"InternalError{"message":"step limit reached"}"#.to_string())
+    );
+  }
+
+  #[test]
+  fn max_stack_depth_applies_to_getters() {
+    let mut vm = VirtualMachine::builder().max_stack_depth(50).build();
+
+    let res = run(
+      &mut vm,
+      "
+        export default function () {
+          const o = {
+            get depth() {
+              return recurse(0);
+            },
+          };
+
+          try {
+            return o.depth;
+          } catch (e) {
+            return (e as Error).message;
+          }
+        }
+
+        function recurse(n: number): number {
+          return recurse(n + 1);
+        }
+      ",
+    );
+
+    assert_eq!(res, Ok(r#""Maximum call stack size exceeded""#.to_string()));
+  }
+}
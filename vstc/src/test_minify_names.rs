@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use std::rc::Rc;
+
+  use valuescript_compiler::asm::{Module, Value};
+  use valuescript_compiler::{
+    assemble, compile_module_with_options, compile_with_options, CompileOptions, ResolvedPath,
+  };
+  use valuescript_vm::{Bytecode, ValTrait, VirtualMachine};
+
+  const SOURCE: &str = "
+    export default function main() {
+      let counter = new Counter(10);
+      counter.increment(5);
+
+      let error = '';
+
+      try {
+        fail(counter.value);
+      } catch (e) {
+        error = (e as Error).message;
+      }
+
+      return [counter.value, fib(10), [1, 2, 3].map(x => x * scale), error];
+    }
+
+    const scale = 3;
+
+    class Counter {
+      value: number;
+
+      constructor(start: number) {
+        this.value = start;
+      }
+
+      increment(by: number) {
+        this.value += by;
+      }
+    }
+
+    function fib(n: number): number {
+      return n < 2 ? n : fib(n - 1) + fib(n - 2);
+    }
+
+    function fail(value: number) {
+      throw new Error(`failed with ${value}`);
+    }
+  ";
+
+  const EXPECTED: &str = r#"[15,55,[3,6,9],"failed with 15"]"#;
+
+  fn minify() -> CompileOptions {
+    CompileOptions {
+      minify_names: true,
+      ..CompileOptions::default()
+    }
+  }
+
+  fn run(module: &Module) -> Result<String, String> {
+    let bytecode = Rc::new(Bytecode::new(assemble(module).unwrap()).unwrap());
+
+    VirtualMachine::default()
+      .run(bytecode, None, &[])
+      .map(|val| val.codify())
+      .map_err(|err| err.codify())
+  }
+
+  #[test]
+  fn minified_module_behaves_the_same() {
+    let output = compile_module_with_options(SOURCE, &CompileOptions::default());
+    assert!(output.name_map.is_none());
+    assert_eq!(run(&output.module), Ok(EXPECTED.to_string()));
+
+    let output = compile_module_with_options(SOURCE, &minify());
+    assert_eq!(run(&output.module), Ok(EXPECTED.to_string()));
+
+    for definition in &output.module.definitions {
+      assert!(
+        definition.pointer.name.len() <= 2,
+        "{}",
+        definition.pointer.name
+      );
+    }
+  }
+
+  #[test]
+  fn name_map_has_original_names() {
+    let output = compile_module_with_options(SOURCE, &minify());
+    let name_map = output.name_map.unwrap();
+
+    let new_name = |original: &str| {
+      name_map
+        .pointers
+        .iter()
+        .find(|(_, name)| *name == original)
+        .map(|(new_name, _)| new_name.clone())
+        .unwrap_or_else(|| panic!("{} is missing from the name map", original))
+    };
+
+    match &output.module.export_default {
+      Value::Pointer(pointer) => assert_eq!(pointer.name, new_name("main")),
+      other => panic!("Unexpected default export {:?}", other),
+    }
+
+    let fail_registers = &name_map.registers[&new_name("fail")];
+    assert!(fail_registers.values().any(|name| name == "value"));
+
+    let listing = name_map.to_string();
+    assert!(listing.contains(&format!("@{} @fib\n", new_name("fib"))));
+    assert!(listing
+      .lines()
+      .any(|line| line.starts_with("  %") && line.ends_with(" %n")));
+  }
+
+  #[test]
+  fn many_definitions_get_longer_names() {
+    let source = (0..30)
+      .map(|i| format!("function f{}() {{ return {}; }}\n", i, i))
+      .chain(["export default function () { return f0() + f29(); }".to_string()])
+      .collect::<String>();
+
+    let output = compile_module_with_options(&source, &minify());
+    let name_map = output.name_map.unwrap();
+
+    assert!(name_map.pointers.contains_key("a"));
+    assert!(name_map.pointers.contains_key("aa"));
+    assert_eq!(name_map.pointers.len(), output.module.definitions.len());
+    assert_eq!(run(&output.module), Ok("29".to_string()));
+  }
+
+  #[test]
+  fn minifies_linked_programs() {
+    let files = HashMap::from([
+      (
+        "/main.ts",
+        "import { scale } from './lib.ts'; export default function () { return scale(2); }",
+      ),
+      (
+        "/lib.ts",
+        "const factor = 21; export function scale(x: number) { return x * factor; }",
+      ),
+    ]);
+
+    let read_file = |path: &str| {
+      files
+        .get(path)
+        .map(|content| content.to_string())
+        .ok_or_else(|| format!("Not found: {}", path))
+    };
+
+    let result = compile_with_options(
+      ResolvedPath::from("/main.ts".to_string()),
+      read_file,
+      &minify(),
+    );
+
+    let name_map = result.name_map.unwrap();
+    // Names are recorded after linking, which can add suffixes to avoid collisions
+    assert!(name_map
+      .pointers
+      .values()
+      .any(|name| name.starts_with("scale")));
+    assert_eq!(run(&result.module.unwrap()), Ok("42".to_string()));
+
+    // There's no module to minify when compilation fails
+    let result = compile_with_options(
+      ResolvedPath::from("/missing.ts".to_string()),
+      read_file,
+      &minify(),
+    );
+
+    assert!(result.module.is_none());
+    assert!(result.name_map.is_none());
+    assert!(!result.diagnostics.is_empty());
+  }
+}
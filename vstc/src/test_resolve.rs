@@ -0,0 +1,228 @@
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use std::fs;
+  use std::path::{Path, PathBuf};
+
+  use valuescript_compiler::{gather_modules, ResolvedPath};
+
+  use crate::read_source::{content_hash, read_url};
+
+  /// Gathers the modules starting from `entry_point`, reading files from `files`, and gives the
+  /// sorted paths of the modules that were found.
+  fn gather(entry_point: &str, files: &[(&str, &str)]) -> Vec<String> {
+    let files = files
+      .iter()
+      .map(|(path, content)| (path.to_string(), content.to_string()))
+      .collect::<HashMap<_, _>>();
+
+    let gm = gather_modules(ResolvedPath::from(entry_point.to_string()), |path| {
+      files
+        .get(path)
+        .cloned()
+        .ok_or_else(|| format!("Not found: {}", path))
+    });
+
+    let mut paths = gm
+      .modules
+      .keys()
+      .map(|path| path.path.clone())
+      .collect::<Vec<_>>();
+
+    paths.sort();
+    paths
+  }
+
+  #[test]
+  fn resolves_package_main() {
+    let paths = gather(
+      "/project/src/main.ts",
+      &[
+        (
+          "/project/src/main.ts",
+          "import lib from 'lib'; export default lib;",
+        ),
+        (
+          "/project/node_modules/lib/package.json",
+          r#"{ "main": "./dist/lib.ts" }"#,
+        ),
+        ("/project/node_modules/lib/dist/lib.ts", "export default 1;"),
+      ],
+    );
+
+    assert_eq!(
+      paths,
+      vec![
+        "/project/node_modules/lib/dist/lib.ts",
+        "/project/src/main.ts"
+      ]
+    );
+  }
+
+  #[test]
+  fn resolves_package_exports() {
+    let paths = gather(
+      "/project/main.ts",
+      &[
+        (
+          "/project/main.ts",
+          "import a from '@scope/pkg'; import b from '@scope/pkg/feature/b'; export default [a, b];",
+        ),
+        (
+          "/project/node_modules/@scope/pkg/package.json",
+          r#"{
+            "main": "./unused.ts",
+            "exports": {
+              ".": { "import": "./src/index.ts", "require": "./src/index.cjs" },
+              "./feature/*": "./src/features/*.ts"
+            }
+          }"#,
+        ),
+        ("/project/node_modules/@scope/pkg/src/index.ts", "export default 1;"),
+        (
+          "/project/node_modules/@scope/pkg/src/features/b.ts",
+          "export default 2;",
+        ),
+      ],
+    );
+
+    assert_eq!(
+      paths,
+      vec![
+        "/project/main.ts",
+        "/project/node_modules/@scope/pkg/src/features/b.ts",
+        "/project/node_modules/@scope/pkg/src/index.ts",
+      ]
+    );
+  }
+
+  #[test]
+  fn resolves_package_index_from_ancestor_node_modules() {
+    let paths = gather(
+      "/project/src/deep/main.ts",
+      &[
+        (
+          "/project/src/deep/main.ts",
+          "import lib from 'lib'; export default lib;",
+        ),
+        ("/project/node_modules/lib/package.json", "{}"),
+        ("/project/node_modules/lib/index.ts", "export default 1;"),
+      ],
+    );
+
+    assert_eq!(
+      paths,
+      vec![
+        "/project/node_modules/lib/index.ts",
+        "/project/src/deep/main.ts"
+      ]
+    );
+  }
+
+  #[test]
+  fn missing_package_is_left_unresolved() {
+    let paths = gather(
+      "/project/main.ts",
+      &[(
+        "/project/main.ts",
+        "import lib from 'lib'; export default lib;",
+      )],
+    );
+
+    assert_eq!(paths, vec!["/project/main.ts"]);
+  }
+
+  #[test]
+  fn resolves_imports_relative_to_urls() {
+    let paths = gather(
+      "https://example.com/app/main.ts",
+      &[
+        (
+          "https://example.com/app/main.ts",
+          "
+            import a from './a.ts';
+            import b from '../shared/b.ts';
+            import c from 'https://cdn.example.com/c.ts';
+            export default [a, b, c];
+          ",
+        ),
+        ("https://example.com/app/a.ts", "export default 1;"),
+        ("https://example.com/shared/b.ts", "export default 2;"),
+        (
+          "https://cdn.example.com/c.ts",
+          "import d from '/../../d.ts'; export default d;",
+        ),
+        ("https://cdn.example.com/d.ts", "export default 4;"),
+      ],
+    );
+
+    assert_eq!(
+      paths,
+      vec![
+        "https://cdn.example.com/c.ts",
+        "https://cdn.example.com/d.ts",
+        "https://example.com/app/a.ts",
+        "https://example.com/app/main.ts",
+        "https://example.com/shared/b.ts",
+      ]
+    );
+  }
+
+  /// An empty cache directory that is unique to the test.
+  fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vstc-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+
+    dir
+  }
+
+  /// Records `content` as the download of `url` in the cache.
+  fn write_cached(cache_dir: &Path, url: &str, content: &str) {
+    let rel_path = &url["https://".len()..];
+
+    for (section, content) in [
+      ("https", content.to_string()),
+      ("keccak256", content_hash(content)),
+    ] {
+      let path = cache_dir.join(section).join(rel_path);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(path, content).unwrap();
+    }
+  }
+
+  #[test]
+  fn reads_urls_from_cache() {
+    let dir = cache_dir("reads_urls_from_cache");
+    let url = "https://example.invalid/lib/a.ts";
+    write_cached(&dir, url, "export default 1;");
+
+    assert_eq!(read_url(url, &dir), Ok("export default 1;".to_string()));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn damaged_cache_is_downloaded_again() {
+    let dir = cache_dir("damaged_cache_is_downloaded_again");
+    let url = "https://example.invalid/lib/a.ts";
+    write_cached(&dir, url, "export default 1;");
+    fs::write(dir.join("https/example.invalid/lib/a.ts"), "export def").unwrap();
+
+    // The download fails because the domain doesn't exist, rather than using the damaged file
+    let res = read_url(url, &dir);
+    assert!(res.is_err(), "{:?}", res);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn cache_paths_stay_in_the_cache() {
+    let dir = cache_dir("cache_paths_stay_in_the_cache");
+    let url = "https://example.invalid/../../a.ts";
+    write_cached(&dir, "https://example.invalid/a.ts", "export default 1;");
+
+    assert_eq!(read_url(url, &dir), Ok("export default 1;".to_string()));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
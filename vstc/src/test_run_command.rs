@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+
+  use valuescript_compiler::{assemble, compile_module};
+  use valuescript_vm::vs_value::Val;
+  use valuescript_vm::{Bytecode, ValTrait, VirtualMachine};
+
+  use crate::run_command::{parse_run_args, run_entry, RunArgs};
+
+  fn parse(args: &[&str]) -> Result<RunArgs, String> {
+    parse_run_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+  }
+
+  fn codify_args(args: &[Val]) -> Vec<String> {
+    args.iter().map(|arg| arg.codify()).collect()
+  }
+
+  #[test]
+  fn parses_fn_and_args() {
+    let run_args = parse(&["--fn", "add", "--args", "[1, \"two\", [3]]", "main.ts"]).unwrap();
+
+    assert_eq!(run_args.fn_name.as_deref(), Some("add"));
+    assert_eq!(run_args.file_path, "main.ts");
+    assert_eq!(codify_args(&run_args.args), vec!["1", r#""two""#, "[3]"]);
+
+    // Options can also come after the file
+    let run_args = parse(&["main.ts", "--fn", "add"]).unwrap();
+    assert_eq!(run_args.fn_name.as_deref(), Some("add"));
+    assert!(run_args.args.is_empty());
+  }
+
+  #[test]
+  fn parses_string_args() {
+    let run_args = parse(&["--measure", "main.ts", "a", "--b"]).unwrap();
+
+    assert!(run_args.measure);
+    assert_eq!(run_args.fn_name, None);
+    assert_eq!(codify_args(&run_args.args), vec![r#""a""#, r#""--b""#]);
+  }
+
+  #[test]
+  fn parse_errors() {
+    let err = |args: &[&str]| parse(args).err();
+
+    assert_eq!(err(&[]), Some("Missing file".to_string()));
+    assert_eq!(
+      err(&["main.ts", "--fn"]),
+      Some("Missing value for --fn".to_string())
+    );
+    assert_eq!(
+      err(&["--args"]),
+      Some("Missing value for --args".to_string())
+    );
+    assert_eq!(
+      err(&["--bogus", "main.ts"]),
+      Some("Unrecognized option --bogus".to_string())
+    );
+    assert_eq!(
+      err(&["--args", "{}", "main.ts"]),
+      Some("Invalid --args: Expected array".to_string())
+    );
+    assert!(err(&["--args", "[1,", "main.ts"]).is_some_and(|err| err.starts_with("Invalid --args")));
+  }
+
+  fn bytecode(source: &str) -> Rc<Bytecode> {
+    let module = compile_module(source).module;
+
+    Rc::new(Bytecode::new(assemble(&module).expect("Failed to assemble")).unwrap())
+  }
+
+  fn run(fn_name: Option<&str>, args: Vec<Val>) -> Result<String, String> {
+    let bytecode = bytecode(
+      "
+        export default function (...args: unknown[]) { return ['default', ...args]; }
+        export function add(a: number, b: number) { return a + b; }
+      ",
+    );
+
+    run_entry(&mut VirtualMachine::default(), bytecode, fn_name, args)
+      .map(|val| val.codify())
+      .map_err(|err| err.codify())
+  }
+
+  #[test]
+  fn runs_selected_export() {
+    let args = parse(&["--args", "[1, 2]", "main.ts"]).unwrap().args;
+
+    assert_eq!(
+      run(None, args.clone()),
+      Ok(r#"["default",1,2]"#.to_string())
+    );
+    assert_eq!(run(Some("add"), args.clone()), Ok("3".to_string()));
+
+    assert_eq!(
+      run(Some("sub"), args),
+      Err(r#"TypeError{"message":"sub is not exported"}"#.to_string())
+    );
+  }
+}
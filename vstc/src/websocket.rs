@@ -0,0 +1,99 @@
+use std::io::{self, Read, Write};
+
+use sha1::{Digest, Sha1};
+
+/// Appended to the client's key to make the accept key of the handshake (RFC 6455 section 1.3).
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+pub const OPCODE_TEXT: u8 = 0x1;
+pub const OPCODE_CLOSE: u8 = 0x8;
+pub const OPCODE_PING: u8 = 0x9;
+pub const OPCODE_PONG: u8 = 0xA;
+
+/// Client frames larger than this are refused. Clients only send control frames and short
+/// messages, so this just stops them from making the server allocate a lot of memory.
+const MAX_PAYLOAD_LEN: u64 = 1 << 20;
+
+/// A frame from a client. Fragmented messages aren't reassembled, because the host only needs the
+/// control frames, which can't be fragmented.
+pub struct Frame {
+  pub opcode: u8,
+  pub payload: Vec<u8>,
+}
+
+/// The `Sec-WebSocket-Accept` header that accepts a handshake with `Sec-WebSocket-Key: key`.
+pub fn accept_key(key: &str) -> String {
+  let mut hasher = Sha1::new();
+  hasher.update(key.trim().as_bytes());
+  hasher.update(HANDSHAKE_GUID.as_bytes());
+
+  base64::encode(hasher.finalize())
+}
+
+/// Reads a frame sent by a client, unmasking its payload. Client frames must be masked.
+pub fn read_frame(reader: &mut impl Read) -> io::Result<Frame> {
+  let mut header = [0u8; 2];
+  reader.read_exact(&mut header)?;
+
+  let opcode = header[0] & 0x0f;
+  let masked = header[1] & 0x80 != 0;
+
+  let payload_len = match header[1] & 0x7f {
+    126 => {
+      let mut len = [0u8; 2];
+      reader.read_exact(&mut len)?;
+      u16::from_be_bytes(len) as u64
+    }
+    127 => {
+      let mut len = [0u8; 8];
+      reader.read_exact(&mut len)?;
+      u64::from_be_bytes(len)
+    }
+    len => len as u64,
+  };
+
+  if !masked {
+    return Err(invalid_data("Client frames must be masked"));
+  }
+
+  if payload_len > MAX_PAYLOAD_LEN {
+    return Err(invalid_data("Frame is too large"));
+  }
+
+  let mut mask = [0u8; 4];
+  reader.read_exact(&mut mask)?;
+
+  let mut payload = vec![0u8; payload_len as usize];
+  reader.read_exact(&mut payload)?;
+
+  for (i, byte) in payload.iter_mut().enumerate() {
+    *byte ^= mask[i % 4];
+  }
+
+  Ok(Frame { opcode, payload })
+}
+
+/// Writes an unfragmented frame from the server. Server frames are not masked.
+pub fn write_frame(writer: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+  let mut header = vec![0x80 | opcode];
+
+  match payload.len() {
+    len if len < 126 => header.push(len as u8),
+    len if len <= u16::MAX as usize => {
+      header.push(126);
+      header.extend_from_slice(&(len as u16).to_be_bytes());
+    }
+    len => {
+      header.push(127);
+      header.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+  }
+
+  writer.write_all(&header)?;
+  writer.write_all(payload)?;
+  writer.flush()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}